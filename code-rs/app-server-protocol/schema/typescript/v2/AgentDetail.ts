@@ -0,0 +1,18 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AgentDetail = { prompt: string, context: string | null, readOnly: boolean, result: string | null, error: string | null, progress: Array<string>, 
+/**
+ * Unix timestamps in seconds.
+ */
+createdAt: bigint, startedAt: bigint | null, completedAt: bigint | null, id: string, name: string | null, batchId: string | null, model: string | null, 
+/**
+ * One of `pending`, `running`, `completed`, `failed`, `cancelled`.
+ */
+status: string, lastProgress: string | null, 
+/**
+ * Worktree and branch created for write-enabled agents; `null` for
+ * read-only agents.
+ */
+worktreePath: string | null, branchName: string | null, };
@@ -0,0 +1,18 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AgentListParams = { 
+/**
+ * Only return agents launched in this batch.
+ */
+batchId?: string | null, 
+/**
+ * Only return agents in this status (`pending`, `running`, `completed`,
+ * `failed`, `cancelled`).
+ */
+status?: string | null, 
+/**
+ * Restrict the list to agents created in the last two hours.
+ */
+recentOnly: boolean, };
@@ -0,0 +1,12 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AutoDriveAgentRequest } from "./AutoDriveAgentRequest";
+import type { AutoDriveAgentsTiming } from "./AutoDriveAgentsTiming";
+import type { AutoDriveDecisionStatus } from "./AutoDriveDecisionStatus";
+
+export type AutoDriveDecisionNotification = { threadId: string, seq: bigint, status: AutoDriveDecisionStatus, statusTitle: string | null, statusSentToUser: string | null, goal: string | null, 
+/**
+ * Prompt submitted as the next turn; `null` when the coordinator is done.
+ */
+prompt: string | null, agentsTiming: AutoDriveAgentsTiming | null, agents: Array<AutoDriveAgentRequest>, };
@@ -0,0 +1,6 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AutoDriveState } from "./AutoDriveState";
+
+export type AutoDriveStateChangedNotification = { threadId: string, state: AutoDriveState, turnCount: number, message: string | null, };
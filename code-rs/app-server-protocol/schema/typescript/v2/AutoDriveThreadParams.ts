@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Params shared by `autoDrive/pause`, `autoDrive/resume` and `autoDrive/stop`.
+ */
+export type AutoDriveThreadParams = { threadId: string, };
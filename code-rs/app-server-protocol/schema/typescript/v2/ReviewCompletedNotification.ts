@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewOutput } from "./ReviewOutput";
+
+export type ReviewCompletedNotification = { threadId: string, turnId: string, 
+/**
+ * `null` when the review ended without a structured result.
+ */
+review: ReviewOutput | null, };
@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewFinding } from "./ReviewFinding";
+
+/**
+ * Structured outcome of a finished review.
+ */
+export type ReviewOutput = { findings: Array<ReviewFinding>, overallCorrectness: string, overallExplanation: string, overallConfidenceScore: number, };
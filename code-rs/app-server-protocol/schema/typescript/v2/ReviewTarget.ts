@@ -6,4 +6,20 @@ export type ReviewTarget = { "type": "uncommittedChanges" } | { "type": "baseBra
 /**
  * Optional human-readable label (e.g., commit subject) for UIs.
  */
-title: string | null, } | { "type": "custom", instructions: string, };
+title: string | null, } | { "type": "stagedChanges" } | { "type": "commitRange", 
+/**
+ * Exclusive start of the range (e.g. `main`, `HEAD~3`).
+ */
+from: string, 
+/**
+ * Inclusive end of the range (usually `HEAD`).
+ */
+to: string, } | { "type": "paths", 
+/**
+ * Git pathspecs relative to the repository root; globs are allowed.
+ */
+paths: Array<string>, 
+/**
+ * The diff being narrowed.
+ */
+scope: ReviewTarget, } | { "type": "custom", instructions: string, };
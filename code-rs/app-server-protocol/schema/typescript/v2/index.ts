@@ -4,7 +4,14 @@ export type { Account } from "./Account";
 export type { AccountLoginCompletedNotification } from "./AccountLoginCompletedNotification";
 export type { AccountRateLimitsUpdatedNotification } from "./AccountRateLimitsUpdatedNotification";
 export type { AccountUpdatedNotification } from "./AccountUpdatedNotification";
+export type { AgentDetail } from "./AgentDetail";
+export type { AgentListParams } from "./AgentListParams";
+export type { AgentListResponse } from "./AgentListResponse";
 export type { AgentMessageDeltaNotification } from "./AgentMessageDeltaNotification";
+export type { AgentReadParams } from "./AgentReadParams";
+export type { AgentReadResponse } from "./AgentReadResponse";
+export type { AgentStatusUpdatedNotification } from "./AgentStatusUpdatedNotification";
+export type { AgentSummary } from "./AgentSummary";
 export type { AnalyticsConfig } from "./AnalyticsConfig";
 export type { AppDisabledReason } from "./AppDisabledReason";
 export type { AppInfo } from "./AppInfo";
@@ -13,6 +20,16 @@ export type { AppsConfig } from "./AppsConfig";
 export type { AppsListParams } from "./AppsListParams";
 export type { AppsListResponse } from "./AppsListResponse";
 export type { AskForApproval } from "./AskForApproval";
+export type { AutoDriveActionNotification } from "./AutoDriveActionNotification";
+export type { AutoDriveAgentRequest } from "./AutoDriveAgentRequest";
+export type { AutoDriveAgentsTiming } from "./AutoDriveAgentsTiming";
+export type { AutoDriveDecisionNotification } from "./AutoDriveDecisionNotification";
+export type { AutoDriveDecisionStatus } from "./AutoDriveDecisionStatus";
+export type { AutoDriveStartParams } from "./AutoDriveStartParams";
+export type { AutoDriveState } from "./AutoDriveState";
+export type { AutoDriveStateChangedNotification } from "./AutoDriveStateChangedNotification";
+export type { AutoDriveStateResponse } from "./AutoDriveStateResponse";
+export type { AutoDriveThreadParams } from "./AutoDriveThreadParams";
 export type { ByteRange } from "./ByteRange";
 export type { CancelLoginAccountParams } from "./CancelLoginAccountParams";
 export type { CancelLoginAccountResponse } from "./CancelLoginAccountResponse";
@@ -107,7 +124,12 @@ export type { ReasoningSummaryTextDeltaNotification } from "./ReasoningSummaryTe
 export type { ReasoningTextDeltaNotification } from "./ReasoningTextDeltaNotification";
 export type { RemoteSkillSummary } from "./RemoteSkillSummary";
 export type { ResidencyRequirement } from "./ResidencyRequirement";
+export type { ReviewCodeLocation } from "./ReviewCodeLocation";
+export type { ReviewCompletedNotification } from "./ReviewCompletedNotification";
 export type { ReviewDelivery } from "./ReviewDelivery";
+export type { ReviewFinding } from "./ReviewFinding";
+export type { ReviewLineRange } from "./ReviewLineRange";
+export type { ReviewOutput } from "./ReviewOutput";
 export type { ReviewStartParams } from "./ReviewStartParams";
 export type { ReviewStartResponse } from "./ReviewStartResponse";
 export type { ReviewTarget } from "./ReviewTarget";
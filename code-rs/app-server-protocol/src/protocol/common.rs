@@ -281,6 +281,30 @@ client_request_definitions! {
         params: v2::ReviewStartParams,
         response: v2::ReviewStartResponse,
     },
+    AgentList => "agent/list" {
+        params: v2::AgentListParams,
+        response: v2::AgentListResponse,
+    },
+    AgentRead => "agent/read" {
+        params: v2::AgentReadParams,
+        response: v2::AgentReadResponse,
+    },
+    AutoDriveStart => "autoDrive/start" {
+        params: v2::AutoDriveStartParams,
+        response: v2::AutoDriveStateResponse,
+    },
+    AutoDrivePause => "autoDrive/pause" {
+        params: v2::AutoDriveThreadParams,
+        response: v2::AutoDriveStateResponse,
+    },
+    AutoDriveResume => "autoDrive/resume" {
+        params: v2::AutoDriveThreadParams,
+        response: v2::AutoDriveStateResponse,
+    },
+    AutoDriveStop => "autoDrive/stop" {
+        params: v2::AutoDriveThreadParams,
+        response: v2::AutoDriveStateResponse,
+    },
 
     ModelList => "model/list" {
         params: v2::ModelListParams,
@@ -743,6 +767,11 @@ server_notification_definitions! {
     ContextCompacted => "thread/compacted" (v2::ContextCompactedNotification),
     DeprecationNotice => "deprecationNotice" (v2::DeprecationNoticeNotification),
     ConfigWarning => "configWarning" (v2::ConfigWarningNotification),
+    AgentStatusUpdated => "agent/statusUpdated" (v2::AgentStatusUpdatedNotification),
+    ReviewCompleted => "review/completed" (v2::ReviewCompletedNotification),
+    AutoDriveStateChanged => "autoDrive/stateChanged" (v2::AutoDriveStateChangedNotification),
+    AutoDriveDecision => "autoDrive/decision" (v2::AutoDriveDecisionNotification),
+    AutoDriveAction => "autoDrive/action" (v2::AutoDriveActionNotification),
 
     /// Notifies the user of world-writable directories on Windows, which cannot be protected by the sandbox.
     WindowsWorldWritableWarning => "windows/worldWritableWarning" (v2::WindowsWorldWritableWarningNotification),
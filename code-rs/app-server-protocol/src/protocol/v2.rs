@@ -32,6 +32,9 @@ use code_protocol::protocol::CreditsSnapshot as CoreCreditsSnapshot;
 use code_protocol::protocol::NetworkAccess as CoreNetworkAccess;
 use code_protocol::protocol::RateLimitSnapshot as CoreRateLimitSnapshot;
 use code_protocol::protocol::RateLimitWindow as CoreRateLimitWindow;
+use code_protocol::protocol::ReviewCodeLocation as CoreReviewCodeLocation;
+use code_protocol::protocol::ReviewFinding as CoreReviewFinding;
+use code_protocol::protocol::ReviewOutputEvent as CoreReviewOutputEvent;
 use code_protocol::protocol::SessionSource as CoreSessionSource;
 use code_protocol::protocol::SkillDependencies as CoreSkillDependencies;
 use code_protocol::protocol::SkillErrorInfo as CoreSkillErrorInfo;
@@ -2194,6 +2197,80 @@ pub struct ReviewStartResponse {
     pub review_thread_id: String,
 }
 
+/// Structured outcome of a finished review.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ReviewOutput {
+    pub findings: Vec<ReviewFinding>,
+    pub overall_correctness: String,
+    pub overall_explanation: String,
+    pub overall_confidence_score: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ReviewFinding {
+    pub title: String,
+    pub body: String,
+    pub confidence_score: f32,
+    pub priority: i32,
+    pub code_location: ReviewCodeLocation,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ReviewCodeLocation {
+    pub absolute_file_path: PathBuf,
+    pub line_range: ReviewLineRange,
+}
+
+/// Inclusive line range in the reviewed file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ReviewLineRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl From<CoreReviewOutputEvent> for ReviewOutput {
+    fn from(value: CoreReviewOutputEvent) -> Self {
+        Self {
+            findings: value.findings.into_iter().map(Into::into).collect(),
+            overall_correctness: value.overall_correctness,
+            overall_explanation: value.overall_explanation,
+            overall_confidence_score: value.overall_confidence_score,
+        }
+    }
+}
+
+impl From<CoreReviewFinding> for ReviewFinding {
+    fn from(value: CoreReviewFinding) -> Self {
+        Self {
+            title: value.title,
+            body: value.body,
+            confidence_score: value.confidence_score,
+            priority: value.priority,
+            code_location: value.code_location.into(),
+        }
+    }
+}
+
+impl From<CoreReviewCodeLocation> for ReviewCodeLocation {
+    fn from(value: CoreReviewCodeLocation) -> Self {
+        Self {
+            absolute_file_path: value.absolute_file_path,
+            line_range: ReviewLineRange {
+                start: value.line_range.start,
+                end: value.line_range.end,
+            },
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "camelCase")]
 #[ts(tag = "type", export_to = "v2/")]
@@ -2215,6 +2292,29 @@ pub enum ReviewTarget {
         title: Option<String>,
     },
 
+    /// Review only the changes staged in the index.
+    StagedChanges,
+
+    /// Review the changes across a commit range (`from..to`).
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    CommitRange {
+        /// Exclusive start of the range (e.g. `main`, `HEAD~3`).
+        from: String,
+        /// Inclusive end of the range (usually `HEAD`).
+        to: String,
+    },
+
+    /// Narrow another target to a set of paths or globs.
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    Paths {
+        /// Git pathspecs relative to the repository root; globs are allowed.
+        paths: Vec<String>,
+        /// The diff being narrowed.
+        scope: Box<ReviewTarget>,
+    },
+
     /// Arbitrary instructions, equivalent to the old free-form prompt.
     #[serde(rename_all = "camelCase")]
     #[ts(rename_all = "camelCase")]
@@ -2259,6 +2359,142 @@ pub struct TurnInterruptParams {
 #[ts(export_to = "v2/")]
 pub struct TurnInterruptResponse {}
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentListParams {
+    /// Only return agents launched in this batch.
+    #[ts(optional = nullable)]
+    pub batch_id: Option<String>,
+    /// Only return agents in this status (`pending`, `running`, `completed`,
+    /// `failed`, `cancelled`).
+    #[ts(optional = nullable)]
+    pub status: Option<String>,
+    /// Restrict the list to agents created in the last two hours.
+    #[serde(default)]
+    pub recent_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentListResponse {
+    pub data: Vec<AgentSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentReadParams {
+    pub agent_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentReadResponse {
+    pub agent: AgentDetail,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentSummary {
+    pub id: String,
+    pub name: Option<String>,
+    pub batch_id: Option<String>,
+    pub model: Option<String>,
+    /// One of `pending`, `running`, `completed`, `failed`, `cancelled`.
+    pub status: String,
+    pub last_progress: Option<String>,
+    /// Worktree and branch created for write-enabled agents; `null` for
+    /// read-only agents.
+    pub worktree_path: Option<String>,
+    pub branch_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentDetail {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub summary: AgentSummary,
+    pub prompt: String,
+    pub context: Option<String>,
+    pub read_only: bool,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub progress: Vec<String>,
+    /// Unix timestamps in seconds.
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub completed_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveStartParams {
+    pub thread_id: String,
+    pub goal: String,
+}
+
+/// Params shared by `autoDrive/pause`, `autoDrive/resume` and `autoDrive/stop`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveThreadParams {
+    pub thread_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveStateResponse {
+    pub thread_id: String,
+    pub state: AutoDriveState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub enum AutoDriveState {
+    Running,
+    Paused,
+    Stopping,
+    Completed,
+    Failed,
+    Stopped,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub enum AutoDriveDecisionStatus {
+    Continue,
+    Success,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub enum AutoDriveAgentsTiming {
+    Parallel,
+    Blocking,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveAgentRequest {
+    pub prompt: String,
+    pub context: Option<String>,
+    pub write: bool,
+    pub models: Option<Vec<String>>,
+}
+
 // User input types
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
@@ -2971,6 +3207,60 @@ pub struct ContextCompactedNotification {
     pub turn_id: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AgentStatusUpdatedNotification {
+    pub thread_id: String,
+    pub agents: Vec<AgentSummary>,
+    pub context: Option<String>,
+    pub task: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ReviewCompletedNotification {
+    pub thread_id: String,
+    pub turn_id: String,
+    /// `null` when the review ended without a structured result.
+    pub review: Option<ReviewOutput>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveStateChangedNotification {
+    pub thread_id: String,
+    pub state: AutoDriveState,
+    pub turn_count: u32,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveDecisionNotification {
+    pub thread_id: String,
+    pub seq: u64,
+    pub status: AutoDriveDecisionStatus,
+    pub status_title: Option<String>,
+    pub status_sent_to_user: Option<String>,
+    pub goal: Option<String>,
+    /// Prompt submitted as the next turn; `null` when the coordinator is done.
+    pub prompt: Option<String>,
+    pub agents_timing: Option<AutoDriveAgentsTiming>,
+    pub agents: Vec<AutoDriveAgentRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct AutoDriveActionNotification {
+    pub thread_id: String,
+    pub message: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export_to = "v2/")]
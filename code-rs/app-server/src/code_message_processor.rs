@@ -120,6 +120,10 @@ use code_protocol::account::PlanType;
 use code_protocol::protocol::RateLimitSnapshot as CoreRateLimitSnapshot;
 use code_protocol::protocol::RateLimitWindow as CoreRateLimitWindow;
 
+mod agents_v2;
+mod auto_drive_v2;
+mod thread_v2;
+
 // Removed deprecated ChatGPT login support scaffolding
 
 const TOOL_REQUEST_USER_INPUT_METHOD: &str = "item/tool/requestUserInput";
@@ -147,6 +151,11 @@ pub struct CodexMessageProcessor {
     pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
     #[allow(dead_code)]
     pending_fuzzy_searches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    // v2 thread listeners, keyed into `conversation_listeners`; one per thread and connection.
+    thread_subscriptions: HashMap<(ConversationId, ConnectionId), Uuid>,
+    active_turns: thread_v2::ActiveTurns,
+    pending_rollbacks: thread_v2::PendingRollbacks,
+    auto_drive_runs: auto_drive_v2::AutoDriveRuns,
 }
 
 impl CodexMessageProcessor {
@@ -167,6 +176,10 @@ impl CodexMessageProcessor {
             active_login: Arc::new(Mutex::new(None)),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
             pending_fuzzy_searches: Arc::new(Mutex::new(HashMap::new())),
+            thread_subscriptions: HashMap::new(),
+            active_turns: Arc::new(Mutex::new(HashMap::new())),
+            pending_rollbacks: Arc::new(Mutex::new(HashMap::new())),
+            auto_drive_runs: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -278,6 +291,9 @@ impl CodexMessageProcessor {
                 let _ = registration.cancel_tx.send(());
             }
         }
+        self.thread_subscriptions
+            .retain(|(_, owner), _| *owner != connection_id);
+        self.stop_auto_drive_runs_for(connection_id).await;
     }
 
     pub(crate) async fn get_account_response_v2(
@@ -1869,7 +1885,13 @@ fn map_review_decision_from_wire(d: code_protocol::protocol::ReviewDecision) ->
             core_protocol::ReviewDecision::Approved
         }
         code_protocol::protocol::ReviewDecision::ApprovedForSession => core_protocol::ReviewDecision::ApprovedForSession,
+        code_protocol::protocol::ReviewDecision::ApprovedWithEdits { command } => {
+            core_protocol::ReviewDecision::ApprovedWithEdits { command }
+        }
         code_protocol::protocol::ReviewDecision::Denied => core_protocol::ReviewDecision::Denied,
+        code_protocol::protocol::ReviewDecision::DeniedWithFeedback { feedback } => {
+            core_protocol::ReviewDecision::DeniedWithFeedback { feedback }
+        }
         code_protocol::protocol::ReviewDecision::Abort => core_protocol::ReviewDecision::Abort,
     }
 }
@@ -0,0 +1,222 @@
+//! Sub-agent surface (`agent/list`, `agent/read`) plus the agent status and
+//! review outcome notifications forwarded from thread listeners.
+//!
+//! Agents live in the process-wide `AGENT_MANAGER`, so these methods see every
+//! batch launched by any thread served by this app server.
+
+use code_app_server_protocol::AgentDetail;
+use code_app_server_protocol::AgentListParams;
+use code_app_server_protocol::AgentListResponse;
+use code_app_server_protocol::AgentReadParams;
+use code_app_server_protocol::AgentReadResponse;
+use code_app_server_protocol::AgentStatusUpdatedNotification;
+use code_app_server_protocol::AgentSummary;
+use code_app_server_protocol::ReviewCompletedNotification;
+use code_app_server_protocol::ServerNotification;
+use code_core::AGENT_MANAGER;
+use code_core::protocol::AgentInfo;
+use code_core::protocol::Event;
+use code_core::protocol::EventMsg;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::RequestId;
+use serde::Serialize;
+
+use super::CodexMessageProcessor;
+use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+
+impl CodexMessageProcessor {
+    pub(crate) async fn agent_list_v2(&self, request_id: RequestId, params: AgentListParams) {
+        let AgentListParams {
+            batch_id,
+            status,
+            recent_only,
+        } = params;
+        let manager = AGENT_MANAGER.read().await;
+        let mut agents: Vec<_> = manager
+            .list_agents(None, batch_id, recent_only)
+            .into_iter()
+            .filter(|agent| {
+                status
+                    .as_deref()
+                    .is_none_or(|wanted| status_label(&agent.status) == wanted)
+            })
+            .collect();
+        drop(manager);
+        agents.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let data = agents
+            .iter()
+            .map(|agent| AgentSummary {
+                id: agent.id.clone(),
+                name: agent.name.clone(),
+                batch_id: agent.batch_id.clone(),
+                model: Some(agent.model.clone()),
+                status: status_label(&agent.status),
+                last_progress: agent.progress.last().cloned(),
+                worktree_path: agent.worktree_path.clone(),
+                branch_name: agent.branch_name.clone(),
+            })
+            .collect();
+        self.outgoing
+            .send_response(request_id, AgentListResponse { data })
+            .await;
+    }
+
+    pub(crate) async fn agent_read_v2(&self, request_id: RequestId, params: AgentReadParams) {
+        let Some(agent) = AGENT_MANAGER.read().await.get_agent(&params.agent_id) else {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!("agent not found: {}", params.agent_id),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        };
+
+        let detail = AgentDetail {
+            summary: AgentSummary {
+                id: agent.id.clone(),
+                name: agent.name.clone(),
+                batch_id: agent.batch_id.clone(),
+                model: Some(agent.model.clone()),
+                status: status_label(&agent.status),
+                last_progress: agent.progress.last().cloned(),
+                worktree_path: agent.worktree_path.clone(),
+                branch_name: agent.branch_name.clone(),
+            },
+            prompt: agent.prompt.clone(),
+            context: agent.context.clone(),
+            read_only: agent.read_only,
+            result: agent.result.clone(),
+            error: agent.error.clone(),
+            progress: agent.progress.clone(),
+            created_at: agent.created_at.timestamp(),
+            started_at: agent.started_at.map(|at| at.timestamp()),
+            completed_at: agent.completed_at.map(|at| at.timestamp()),
+        };
+        self.outgoing
+            .send_response(request_id, AgentReadResponse { agent: detail })
+            .await;
+    }
+}
+
+/// Wire label for an agent status; matches the lowercase serde form.
+fn status_label<T: Serialize>(status: &T) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn summary_from_info(info: &AgentInfo) -> AgentSummary {
+    AgentSummary {
+        id: info.id.clone(),
+        name: Some(info.name.clone()),
+        batch_id: info.batch_id.clone(),
+        model: info.model.clone(),
+        status: info.status.clone(),
+        last_progress: info.last_progress.clone(),
+        worktree_path: None,
+        branch_name: None,
+    }
+}
+
+/// Agent status or review outcome notification for one thread event, if
+/// the event carries either.
+pub(super) fn agent_notification(thread_id: &str, event: &Event) -> Option<ServerNotification> {
+    match &event.msg {
+        EventMsg::AgentStatusUpdate(update) => Some(ServerNotification::AgentStatusUpdated(
+            AgentStatusUpdatedNotification {
+                thread_id: thread_id.to_string(),
+                agents: update.agents.iter().map(summary_from_info).collect(),
+                context: update.context.clone(),
+                task: update.task.clone(),
+            },
+        )),
+        EventMsg::ExitedReviewMode(exited) => Some(ServerNotification::ReviewCompleted(
+            ReviewCompletedNotification {
+                thread_id: thread_id.to_string(),
+                turn_id: event.id.clone(),
+                review: exited.review_output.clone().map(Into::into),
+            },
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::protocol::AgentStatusUpdateEvent;
+    use code_core::protocol::ExitedReviewModeEvent;
+    use code_core::protocol::ReviewOutputEvent;
+    use serde_json::json;
+
+    fn event(msg: EventMsg) -> Event {
+        Event {
+            id: "3".to_string(),
+            event_seq: 0,
+            msg,
+            order: None,
+        }
+    }
+
+    #[test]
+    fn agent_status_update_becomes_notification() {
+        let info: AgentInfo = serde_json::from_value(json!({
+            "id": "a1",
+            "name": "claude",
+            "status": "running",
+            "batch_id": "b1",
+            "model": "claude-sonnet",
+            "last_progress": "reading files",
+        }))
+        .expect("agent info");
+        let notification = agent_notification(
+            "thread",
+            &event(EventMsg::AgentStatusUpdate(AgentStatusUpdateEvent {
+                agents: vec![info],
+                context: None,
+                task: Some("fix tests".to_string()),
+            })),
+        )
+        .expect("notification");
+
+        assert_eq!(notification.to_string(), "agent/statusUpdated");
+        let params = notification.to_params().expect("params");
+        assert_eq!(params["threadId"], "thread");
+        assert_eq!(params["task"], "fix tests");
+        assert_eq!(params["agents"][0]["batchId"], "b1");
+        assert_eq!(params["agents"][0]["lastProgress"], "reading files");
+    }
+
+    #[test]
+    fn exited_review_mode_reports_review_outcome() {
+        let notification = agent_notification(
+            "thread",
+            &event(EventMsg::ExitedReviewMode(ExitedReviewModeEvent {
+                review_output: Some(ReviewOutputEvent {
+                    overall_explanation: "looks good".to_string(),
+                    ..ReviewOutputEvent::default()
+                }),
+                snapshot: None,
+            })),
+        )
+        .expect("notification");
+
+        assert_eq!(notification.to_string(), "review/completed");
+        let params = notification.to_params().expect("params");
+        assert_eq!(params["turnId"], "3");
+        assert_eq!(params["review"]["overallExplanation"], "looks good");
+        assert!(params["review"].get("overall_explanation").is_none());
+    }
+
+    #[test]
+    fn unrelated_events_are_ignored() {
+        assert!(agent_notification("thread", &event(EventMsg::TaskStarted)).is_none());
+    }
+}
@@ -0,0 +1,581 @@
+//! Auto Drive over JSON-RPC (`autoDrive/start|pause|resume|stop`).
+//!
+//! A run pairs a loaded thread with an Auto Drive coordinator: each coordinator
+//! decision becomes a turn on the thread, and the finished turn is fed back to
+//! the coordinator. Turn completions reach the run through the thread listener,
+//! so a run only makes progress while its owning connection is subscribed.
+//! Pausing lets the current turn finish and holds the transcript update until
+//! the run is resumed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use code_app_server_protocol::AutoDriveActionNotification;
+use code_app_server_protocol::AutoDriveAgentRequest;
+use code_app_server_protocol::AutoDriveAgentsTiming;
+use code_app_server_protocol::AutoDriveDecisionNotification;
+use code_app_server_protocol::AutoDriveDecisionStatus;
+use code_app_server_protocol::AutoDriveStartParams;
+use code_app_server_protocol::AutoDriveState;
+use code_app_server_protocol::AutoDriveStateChangedNotification;
+use code_app_server_protocol::AutoDriveStateResponse;
+use code_app_server_protocol::AutoDriveThreadParams;
+use code_app_server_protocol::ServerNotification;
+use code_auto_drive_core::AutoCoordinatorCommand;
+use code_auto_drive_core::AutoCoordinatorEvent;
+use code_auto_drive_core::AutoCoordinatorEventSender;
+use code_auto_drive_core::AutoCoordinatorStatus;
+use code_auto_drive_core::AutoDriveHistory;
+use code_auto_drive_core::AutoTurnAgentsAction;
+use code_auto_drive_core::AutoTurnAgentsTiming;
+use code_auto_drive_core::MODEL_SLUG;
+use code_auto_drive_core::build_auto_prompt;
+use code_auto_drive_core::start_auto_coordinator;
+use code_core::CodexConversation;
+use code_core::config::Config;
+use code_core::protocol::Event;
+use code_core::protocol::EventMsg;
+use code_core::protocol::InputItem as CoreInputItem;
+use code_core::protocol::Op;
+use code_protocol::mcp_protocol::ConversationId;
+use code_protocol::models::ContentItem;
+use code_protocol::models::ResponseItem;
+use mcp_types::RequestId;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
+
+use super::CodexMessageProcessor;
+use super::thread_v2::invalid_request;
+use super::thread_v2::parse_thread_id;
+use super::thread_v2::send_server_notification;
+use crate::outgoing_message::ConnectionId;
+use crate::outgoing_message::OutgoingMessageSender;
+
+/// Auto Drive runs keyed by thread; shared with thread listeners so they can
+/// hand turn completions to the run driving that thread.
+pub(super) type AutoDriveRuns = Arc<Mutex<HashMap<ConversationId, AutoDriveRun>>>;
+
+pub(super) struct AutoDriveRun {
+    owner_connection_id: ConnectionId,
+    control_tx: mpsc::UnboundedSender<AutoDriveControl>,
+    turn_events_tx: mpsc::UnboundedSender<Event>,
+    state_rx: watch::Receiver<AutoDriveState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AutoDriveControl {
+    Pause,
+    Resume,
+    Stop,
+}
+
+impl CodexMessageProcessor {
+    pub(crate) async fn auto_drive_start_v2(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: RequestId,
+        params: AutoDriveStartParams,
+    ) {
+        let AutoDriveStartParams { thread_id, goal } = params;
+        let goal = goal.trim().to_string();
+        if goal.is_empty() {
+            let error = invalid_request("goal must not be empty".to_string());
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+        let (conversation_id, conversation) = match self.loaded_thread(&thread_id).await {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let mut runs = self.auto_drive_runs.lock().await;
+        if runs.contains_key(&conversation_id) {
+            drop(runs);
+            let error = invalid_request(format!("Auto Drive is already running on {thread_id}"));
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
+        let (turn_events_tx, turn_events_rx) = mpsc::unbounded_channel();
+        let (state_tx, state_rx) = watch::channel(AutoDriveState::Running);
+        runs.insert(
+            conversation_id,
+            AutoDriveRun {
+                owner_connection_id: connection_id,
+                control_tx,
+                turn_events_tx,
+                state_rx,
+            },
+        );
+        drop(runs);
+
+        self.ensure_thread_listener(connection_id, conversation_id, conversation.clone());
+        self.outgoing
+            .send_response(
+                request_id,
+                AutoDriveStateResponse {
+                    thread_id,
+                    state: AutoDriveState::Running,
+                },
+            )
+            .await;
+
+        let driver = AutoDriveDriver {
+            conversation_id,
+            conversation,
+            connection_id,
+            outgoing: self.outgoing.clone(),
+            runs: self.auto_drive_runs.clone(),
+            state_tx,
+            turn_count: 0,
+        };
+        let config = auto_drive_config(&self.config);
+        tokio::spawn(driver.run(goal, config, control_rx, turn_events_rx));
+    }
+
+    pub(crate) async fn auto_drive_control_v2(
+        &self,
+        request_id: RequestId,
+        method: &str,
+        params: AutoDriveThreadParams,
+    ) {
+        let (control, state) = match method {
+            "autoDrive/pause" => (AutoDriveControl::Pause, AutoDriveState::Paused),
+            "autoDrive/resume" => (AutoDriveControl::Resume, AutoDriveState::Running),
+            _ => (AutoDriveControl::Stop, AutoDriveState::Stopping),
+        };
+        let conversation_id = match parse_thread_id(&params.thread_id) {
+            Ok(id) => id,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let runs = self.auto_drive_runs.lock().await;
+        let Some(run) = runs.get(&conversation_id) else {
+            drop(runs);
+            let error =
+                invalid_request(format!("no Auto Drive run on thread {}", params.thread_id));
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        };
+        let current = *run.state_rx.borrow();
+        let state = match (control, current) {
+            // Stopping is final; pause/resume no longer apply.
+            (AutoDriveControl::Pause | AutoDriveControl::Resume, AutoDriveState::Stopping) => {
+                current
+            }
+            _ => {
+                let _ = run.control_tx.send(control);
+                state
+            }
+        };
+        drop(runs);
+
+        self.outgoing
+            .send_response(
+                request_id,
+                AutoDriveStateResponse {
+                    thread_id: params.thread_id,
+                    state,
+                },
+            )
+            .await;
+    }
+
+    /// Stop runs started by a connection that went away; nothing would observe
+    /// their turns any more.
+    pub(super) async fn stop_auto_drive_runs_for(&self, connection_id: ConnectionId) {
+        for run in self.auto_drive_runs.lock().await.values() {
+            if run.owner_connection_id == connection_id {
+                let _ = run.control_tx.send(AutoDriveControl::Stop);
+            }
+        }
+    }
+}
+
+/// Hand turn lifecycle events to the Auto Drive run on this thread, if any.
+pub(super) async fn forward_to_auto_drive(
+    runs: &AutoDriveRuns,
+    conversation_id: ConversationId,
+    event: &Event,
+) {
+    if !matches!(
+        event.msg,
+        EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_)
+    ) {
+        return;
+    }
+    if let Some(run) = runs.lock().await.get(&conversation_id) {
+        let _ = run.turn_events_tx.send(event.clone());
+    }
+}
+
+fn auto_drive_config(config: &Config) -> Config {
+    let mut auto_config = config.clone();
+    if !config.auto_drive_use_chat_model {
+        auto_config.model = config.auto_drive.model.trim().to_string();
+        if auto_config.model.is_empty() {
+            auto_config.model = MODEL_SLUG.to_string();
+        }
+        auto_config.model_reasoning_effort = config.auto_drive.model_reasoning_effort;
+    }
+    auto_config
+}
+
+struct AutoDriveDriver {
+    conversation_id: ConversationId,
+    conversation: Arc<CodexConversation>,
+    connection_id: ConnectionId,
+    outgoing: Arc<OutgoingMessageSender>,
+    runs: AutoDriveRuns,
+    state_tx: watch::Sender<AutoDriveState>,
+    turn_count: u32,
+}
+
+impl AutoDriveDriver {
+    async fn run(
+        mut self,
+        goal: String,
+        config: Config,
+        mut control_rx: mpsc::UnboundedReceiver<AutoDriveControl>,
+        mut turn_events_rx: mpsc::UnboundedReceiver<Event>,
+    ) {
+        let (auto_tx, mut auto_rx) = mpsc::unbounded_channel();
+        let sender = AutoCoordinatorEventSender::new(move |event| {
+            let _ = auto_tx.send(event);
+        });
+        let mut history = AutoDriveHistory::new();
+        let handle = match start_auto_coordinator(
+            sender,
+            goal,
+            history.raw_snapshot(),
+            config.clone(),
+            config.debug,
+            false,
+        ) {
+            Ok(handle) => handle,
+            Err(err) => {
+                let message = format!("failed to start Auto Drive: {err:#}");
+                self.finish(AutoDriveState::Failed, Some(message)).await;
+                return;
+            }
+        };
+
+        let mut paused = false;
+        let mut stop_requested = false;
+        let mut held_update: Option<Vec<ResponseItem>> = None;
+        let mut active_turn: Option<String> = None;
+        let mut outcome: Option<AutoCoordinatorStatus> = None;
+        let mut failure: Option<String> = None;
+
+        loop {
+            tokio::select! {
+                control = control_rx.recv() => match control {
+                    Some(AutoDriveControl::Pause) if !stop_requested => {
+                        paused = true;
+                        self.set_state(AutoDriveState::Paused, None).await;
+                    }
+                    Some(AutoDriveControl::Resume) if !stop_requested => {
+                        paused = false;
+                        self.set_state(AutoDriveState::Running, None).await;
+                        if let Some(items) = held_update.take() {
+                            let _ = handle.send(AutoCoordinatorCommand::UpdateConversation(items.into()));
+                        }
+                    }
+                    Some(AutoDriveControl::Pause | AutoDriveControl::Resume) => {}
+                    None => {
+                        let _ = handle.send(AutoCoordinatorCommand::Stop);
+                        break;
+                    }
+                    Some(AutoDriveControl::Stop) => {
+                        if !stop_requested {
+                            stop_requested = true;
+                            self.set_state(AutoDriveState::Stopping, None).await;
+                            if active_turn.is_some() {
+                                let _ = self.conversation.submit(Op::Interrupt).await;
+                            }
+                            if handle.send(AutoCoordinatorCommand::Stop).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                },
+                event = auto_rx.recv() => {
+                    let Some(event) = event else { break };
+                    match event {
+                        AutoCoordinatorEvent::Decision {
+                            seq,
+                            status,
+                            status_title,
+                            status_sent_to_user,
+                            goal,
+                            cli,
+                            agents_timing,
+                            agents,
+                            transcript,
+                        } => {
+                            history.append_raw(&transcript);
+                            let _ = handle.send(AutoCoordinatorCommand::AckDecision { seq });
+
+                            let prompt = cli
+                                .as_ref()
+                                .filter(|_| !stop_requested)
+                                .map(|cli| build_auto_prompt(cli, &agents, agents_timing));
+                            self.notify(ServerNotification::AutoDriveDecision(
+                                AutoDriveDecisionNotification {
+                                    thread_id: self.conversation_id.to_string(),
+                                    seq,
+                                    status: decision_status(status),
+                                    status_title,
+                                    status_sent_to_user,
+                                    goal,
+                                    prompt: prompt.clone(),
+                                    agents_timing: agents_timing.map(agents_timing_v2),
+                                    agents: agents.iter().map(agent_request).collect(),
+                                },
+                            ))
+                            .await;
+
+                            match prompt {
+                                Some(prompt) => {
+                                    match self.submit_turn(&mut history, prompt).await {
+                                        Ok(turn_id) => active_turn = Some(turn_id),
+                                        Err(message) => {
+                                            failure = Some(message);
+                                            stop_requested = true;
+                                            let _ = handle.send(AutoCoordinatorCommand::Stop);
+                                        }
+                                    }
+                                }
+                                None if status != AutoCoordinatorStatus::Continue => {
+                                    outcome = Some(status);
+                                    let _ = handle.send(AutoCoordinatorCommand::Stop);
+                                }
+                                None => {}
+                            }
+                        }
+                        AutoCoordinatorEvent::UserReply {
+                            user_response,
+                            cli_command,
+                        } => {
+                            if let Some(text) = user_response.filter(|text| !text.trim().is_empty()) {
+                                history.append_raw(&[assistant_message(text.clone())]);
+                                self.notify_action(text).await;
+                            }
+                            let command = cli_command
+                                .map(|command| command.trim().to_string())
+                                .filter(|command| !command.is_empty() && !stop_requested);
+                            if let Some(command) = command {
+                                match self.submit_turn(&mut history, command).await {
+                                    Ok(turn_id) => active_turn = Some(turn_id),
+                                    Err(message) => self.notify_action(message).await,
+                                }
+                            }
+                        }
+                        AutoCoordinatorEvent::Action { message } => {
+                            self.notify_action(message).await;
+                        }
+                        AutoCoordinatorEvent::CompactedHistory { conversation, .. } => {
+                            history.replace_all(conversation.to_vec());
+                        }
+                        AutoCoordinatorEvent::Thinking { .. }
+                        | AutoCoordinatorEvent::TokenMetrics { .. } => {}
+                        AutoCoordinatorEvent::StopAck => break,
+                    }
+                }
+                event = turn_events_rx.recv() => {
+                    let Some(event) = event else { break };
+                    if active_turn.as_deref() != Some(event.id.as_str()) {
+                        continue;
+                    }
+                    active_turn = None;
+                    self.turn_count += 1;
+                    if let EventMsg::TaskComplete(complete) = &event.msg
+                        && let Some(text) = complete.last_agent_message.clone()
+                    {
+                        history.append_raw(&[assistant_message(text)]);
+                    }
+                    if stop_requested {
+                        continue;
+                    }
+                    let update = history.raw_snapshot();
+                    if paused {
+                        held_update = Some(update);
+                    } else if handle
+                        .send(AutoCoordinatorCommand::UpdateConversation(update.into()))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+
+        handle.cancel();
+        let state = match (failure.is_some(), outcome) {
+            (true, _) | (false, Some(AutoCoordinatorStatus::Failed)) => AutoDriveState::Failed,
+            (false, Some(AutoCoordinatorStatus::Success)) => AutoDriveState::Completed,
+            _ => AutoDriveState::Stopped,
+        };
+        self.finish(state, failure).await;
+    }
+
+    async fn submit_turn(
+        &self,
+        history: &mut AutoDriveHistory,
+        prompt: String,
+    ) -> Result<String, String> {
+        history.append_raw(&[user_message(prompt.clone())]);
+        self.conversation
+            .submit(Op::UserInput {
+                items: vec![CoreInputItem::Text { text: prompt }],
+                final_output_json_schema: None,
+            })
+            .await
+            .map_err(|err| format!("failed to submit Auto Drive turn: {err}"))
+    }
+
+    async fn finish(&self, state: AutoDriveState, message: Option<String>) {
+        self.runs.lock().await.remove(&self.conversation_id);
+        self.set_state(state, message).await;
+    }
+
+    async fn set_state(&self, state: AutoDriveState, message: Option<String>) {
+        let _ = self.state_tx.send(state);
+        self.notify(ServerNotification::AutoDriveStateChanged(
+            AutoDriveStateChangedNotification {
+                thread_id: self.conversation_id.to_string(),
+                state,
+                turn_count: self.turn_count,
+                message,
+            },
+        ))
+        .await;
+    }
+
+    async fn notify_action(&self, message: String) {
+        self.notify(ServerNotification::AutoDriveAction(
+            AutoDriveActionNotification {
+                thread_id: self.conversation_id.to_string(),
+                message,
+            },
+        ))
+        .await;
+    }
+
+    async fn notify(&self, notification: ServerNotification) {
+        send_server_notification(&self.outgoing, self.connection_id, notification).await;
+    }
+}
+
+fn decision_status(status: AutoCoordinatorStatus) -> AutoDriveDecisionStatus {
+    match status {
+        AutoCoordinatorStatus::Continue => AutoDriveDecisionStatus::Continue,
+        AutoCoordinatorStatus::Success => AutoDriveDecisionStatus::Success,
+        AutoCoordinatorStatus::Failed => AutoDriveDecisionStatus::Failed,
+    }
+}
+
+fn agents_timing_v2(timing: AutoTurnAgentsTiming) -> AutoDriveAgentsTiming {
+    match timing {
+        AutoTurnAgentsTiming::Parallel => AutoDriveAgentsTiming::Parallel,
+        AutoTurnAgentsTiming::Blocking => AutoDriveAgentsTiming::Blocking,
+    }
+}
+
+fn agent_request(action: &AutoTurnAgentsAction) -> AutoDriveAgentRequest {
+    AutoDriveAgentRequest {
+        prompt: action.prompt.clone(),
+        context: action.context.clone(),
+        write: action.write,
+        models: action.models.clone(),
+    }
+}
+
+fn user_message(text: String) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText { text }],
+        end_turn: None,
+        phase: None,
+    }
+}
+
+fn assistant_message(text: String) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: vec![ContentItem::OutputText { text }],
+        end_turn: None,
+        phase: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::protocol::TaskCompleteEvent;
+
+    #[tokio::test]
+    async fn only_turn_lifecycle_events_reach_the_run() {
+        let runs: AutoDriveRuns = Arc::new(Mutex::new(HashMap::new()));
+        let conversation_id = ConversationId::new();
+        let (control_tx, _control_rx) = mpsc::unbounded_channel();
+        let (turn_events_tx, mut turn_events_rx) = mpsc::unbounded_channel();
+        let (_state_tx, state_rx) = watch::channel(AutoDriveState::Running);
+        runs.lock().await.insert(
+            conversation_id,
+            AutoDriveRun {
+                owner_connection_id: ConnectionId(1),
+                control_tx,
+                turn_events_tx,
+                state_rx,
+            },
+        );
+
+        let started = Event {
+            id: "1".to_string(),
+            event_seq: 0,
+            msg: EventMsg::TaskStarted,
+            order: None,
+        };
+        let completed = Event {
+            id: "1".to_string(),
+            event_seq: 1,
+            msg: EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: Some("done".to_string()),
+            }),
+            order: None,
+        };
+        forward_to_auto_drive(&runs, conversation_id, &started).await;
+        forward_to_auto_drive(&runs, conversation_id, &completed).await;
+
+        let forwarded = turn_events_rx.try_recv().expect("completion forwarded");
+        assert!(matches!(forwarded.msg, EventMsg::TaskComplete(_)));
+        assert!(turn_events_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn decision_labels_match_wire_names() {
+        assert_eq!(
+            serde_json::to_value(decision_status(AutoCoordinatorStatus::Success)).expect("status"),
+            "success"
+        );
+        assert_eq!(
+            serde_json::to_value(agents_timing_v2(AutoTurnAgentsTiming::Parallel)).expect("timing"),
+            "parallel"
+        );
+        assert_eq!(
+            serde_json::to_value(AutoDriveState::Stopping).expect("state"),
+            "stopping"
+        );
+    }
+}
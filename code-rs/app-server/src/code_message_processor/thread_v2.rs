@@ -0,0 +1,1836 @@
+//! v2 thread/turn surface (`thread/*`, `turn/*`, `review/start`,
+//! `model/list`) served on top of `ConversationManager` and the rollout
+//! catalog.
+//!
+//! Threads are conversations: the thread id is the conversation id, turn ids
+//! are the core submission ids, and persisted threads are read back from their
+//! rollout files.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use code_app_server_protocol::AgentMessageDeltaNotification;
+use code_app_server_protocol::AskForApproval as V2AskForApproval;
+use code_app_server_protocol::CommandAction;
+use code_app_server_protocol::CommandExecutionOutputDeltaNotification;
+use code_app_server_protocol::CommandExecutionStatus;
+use code_app_server_protocol::ErrorNotification;
+use code_app_server_protocol::FileUpdateChange;
+use code_app_server_protocol::GitInfo as V2GitInfo;
+use code_app_server_protocol::ItemCompletedNotification;
+use code_app_server_protocol::ItemStartedNotification;
+use code_app_server_protocol::McpToolCallError;
+use code_app_server_protocol::McpToolCallResult;
+use code_app_server_protocol::McpToolCallStatus;
+use code_app_server_protocol::Model;
+use code_app_server_protocol::ModelListParams;
+use code_app_server_protocol::ModelListResponse;
+use code_app_server_protocol::PatchApplyStatus;
+use code_app_server_protocol::PatchChangeKind;
+use code_app_server_protocol::ReasoningEffortOption;
+use code_app_server_protocol::ReasoningSummaryTextDeltaNotification;
+use code_app_server_protocol::ReviewDelivery;
+use code_app_server_protocol::ReviewStartParams;
+use code_app_server_protocol::ReviewStartResponse;
+use code_app_server_protocol::ReviewTarget;
+use code_app_server_protocol::SandboxMode as V2SandboxMode;
+use code_app_server_protocol::SandboxPolicy as V2SandboxPolicy;
+use code_app_server_protocol::ServerNotification;
+use code_app_server_protocol::SessionSource as V2SessionSource;
+use code_app_server_protocol::Thread;
+use code_app_server_protocol::ThreadForkParams;
+use code_app_server_protocol::ThreadForkResponse;
+use code_app_server_protocol::ThreadItem;
+use code_app_server_protocol::ThreadReadParams;
+use code_app_server_protocol::ThreadReadResponse;
+use code_app_server_protocol::ThreadRollbackParams;
+use code_app_server_protocol::ThreadRollbackResponse;
+use code_app_server_protocol::ThreadStartParams;
+use code_app_server_protocol::ThreadStartResponse;
+use code_app_server_protocol::ThreadStartedNotification;
+use code_app_server_protocol::ThreadTokenUsageUpdatedNotification;
+use code_app_server_protocol::Turn;
+use code_app_server_protocol::TurnCompletedNotification;
+use code_app_server_protocol::TurnDiffUpdatedNotification;
+use code_app_server_protocol::TurnError;
+use code_app_server_protocol::TurnInterruptParams;
+use code_app_server_protocol::TurnInterruptResponse;
+use code_app_server_protocol::TurnPlanUpdatedNotification;
+use code_app_server_protocol::TurnStartParams;
+use code_app_server_protocol::TurnStartResponse;
+use code_app_server_protocol::TurnStartedNotification;
+use code_app_server_protocol::TurnStatus;
+use code_app_server_protocol::TurnSteerParams;
+use code_app_server_protocol::TurnSteerResponse;
+use code_app_server_protocol::UserInput as V2UserInput;
+use code_app_server_protocol::build_turns_from_event_msgs;
+use code_common::model_presets::all_model_presets;
+use code_common::model_presets::model_preset_available_for_auth;
+use code_core::CodexConversation;
+use code_core::NewConversation;
+use code_core::config::Config;
+use code_core::config::ConfigOverrides;
+use code_core::protocol::Event;
+use code_core::protocol::EventMsg;
+use code_core::protocol::InputItem as CoreInputItem;
+use code_core::protocol::Op;
+use code_core::protocol::ReviewRequest;
+use code_core::protocol::SandboxPolicy;
+use code_core::review_prompts::review_request_for_target;
+use code_protocol::mcp_protocol::ConversationId;
+use code_protocol::openai_models::default_input_modalities;
+use code_protocol::protocol::EventMsg as ProtoEventMsg;
+use code_protocol::protocol::RolloutItem;
+use code_protocol::protocol::RolloutLine;
+use code_protocol::protocol::SessionMetaLine;
+use code_protocol::protocol::ThreadRolledBackEvent;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::RequestId;
+use tokio::sync::Mutex;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use super::CodexMessageProcessor;
+use super::ConversationListenerRegistration;
+use super::agents_v2::agent_notification;
+use super::apply_bespoke_event_handling;
+use super::auto_drive_v2::forward_to_auto_drive;
+use super::json_to_toml;
+use super::map_ask_for_approval_from_wire;
+use super::map_ask_for_approval_to_wire;
+use super::map_reasoning_effort_to_wire;
+use crate::error_code::INTERNAL_ERROR_CODE;
+use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+use crate::outgoing_message::ConnectionId;
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::outgoing_message::OutgoingNotification;
+
+/// Active turn id per loaded thread, used to validate `turn/steer` and
+/// `turn/interrupt` preconditions.
+pub(super) type ActiveTurns = Arc<Mutex<HashMap<ConversationId, String>>>;
+
+/// `thread/rollback` requests waiting for core, keyed by submission id.
+pub(super) type PendingRollbacks = Arc<Mutex<HashMap<String, oneshot::Sender<Result<(), String>>>>>;
+
+/// Config overrides shared by `thread/start` and `thread/fork`.
+#[derive(Default)]
+struct ThreadOverrides {
+    model: Option<String>,
+    model_provider: Option<String>,
+    cwd: Option<PathBuf>,
+    approval_policy: Option<V2AskForApproval>,
+    sandbox: Option<V2SandboxMode>,
+    config: Option<HashMap<String, serde_json::Value>>,
+    base_instructions: Option<String>,
+}
+
+impl CodexMessageProcessor {
+    pub(crate) async fn thread_start_v2(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: RequestId,
+        params: ThreadStartParams,
+    ) {
+        let overrides = ThreadOverrides {
+            model: params.model,
+            model_provider: params.model_provider,
+            cwd: params.cwd.map(PathBuf::from),
+            approval_policy: params.approval_policy,
+            sandbox: params.sandbox,
+            config: params.config,
+            base_instructions: params.base_instructions,
+        };
+        let config = match self.thread_config(overrides) {
+            Ok(config) => config,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let new_conversation = match self
+            .conversation_manager
+            .new_conversation(config.clone())
+            .await
+        {
+            Ok(new_conversation) => new_conversation,
+            Err(err) => {
+                let error = internal_error(format!("error creating thread: {err}"));
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        let NewConversation {
+            conversation_id,
+            conversation,
+            session_configured,
+        } = new_conversation;
+
+        let thread = fresh_thread(conversation_id, &config);
+        let response = ThreadStartResponse {
+            thread: thread.clone(),
+            model: session_configured.model,
+            model_provider: config.model_provider_id.clone(),
+            cwd: config.cwd.clone(),
+            approval_policy: map_ask_for_approval_to_wire(config.approval_policy).into(),
+            sandbox: sandbox_policy_to_v2(&config.sandbox_policy),
+            reasoning_effort: Some(map_reasoning_effort_to_wire(config.model_reasoning_effort)),
+        };
+        self.ensure_thread_listener(connection_id, conversation_id, conversation);
+        self.outgoing.send_response(request_id, response).await;
+        send_server_notification(
+            &self.outgoing,
+            connection_id,
+            ServerNotification::ThreadStarted(ThreadStartedNotification { thread }),
+        )
+        .await;
+    }
+
+    pub(crate) async fn thread_read_v2(&self, request_id: RequestId, params: ThreadReadParams) {
+        let result = async {
+            let path = self.rollout_path_for_thread(&params.thread_id).await?;
+            let snapshot = RolloutSnapshot::load(&path).await?;
+            Ok::<_, JSONRPCErrorError>(snapshot.thread(
+                &params.thread_id,
+                &path,
+                &self.config.model_provider_id,
+                params.include_turns,
+            ))
+        }
+        .await;
+
+        match result {
+            Ok(thread) => {
+                self.outgoing
+                    .send_response(request_id, ThreadReadResponse { thread })
+                    .await;
+            }
+            Err(error) => self.outgoing.send_error(request_id, error).await,
+        }
+    }
+
+    pub(crate) async fn thread_fork_v2(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: RequestId,
+        params: ThreadForkParams,
+    ) {
+        let path = match params.path {
+            Some(path) => path,
+            None => match self.rollout_path_for_thread(&params.thread_id).await {
+                Ok(path) => path,
+                Err(error) => {
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            },
+        };
+        let snapshot = match RolloutSnapshot::load(&path).await {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let overrides = ThreadOverrides {
+            model: params.model,
+            model_provider: params.model_provider,
+            // Forks keep working in the source thread's directory unless told otherwise.
+            cwd: params.cwd.map(PathBuf::from).or_else(|| snapshot.cwd()),
+            approval_policy: params.approval_policy,
+            sandbox: params.sandbox,
+            config: params.config,
+            base_instructions: params.base_instructions,
+        };
+        let config = match self.thread_config(overrides) {
+            Ok(config) => config,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let NewConversation {
+            conversation_id,
+            conversation,
+            session_configured,
+        } = match self
+            .conversation_manager
+            .fork_conversation(0, config.clone(), path.clone())
+            .await
+        {
+            Ok(new_conversation) => new_conversation,
+            Err(err) => {
+                let error = internal_error(format!("error forking thread: {err}"));
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let mut thread = fresh_thread(conversation_id, &config);
+        thread.preview = snapshot.preview();
+        thread.git_info = snapshot.git_info();
+        thread.turns = build_turns_from_event_msgs(&snapshot.events());
+
+        let response = ThreadForkResponse {
+            thread: thread.clone(),
+            model: session_configured.model,
+            model_provider: config.model_provider_id.clone(),
+            cwd: config.cwd.clone(),
+            approval_policy: map_ask_for_approval_to_wire(config.approval_policy).into(),
+            sandbox: sandbox_policy_to_v2(&config.sandbox_policy),
+            reasoning_effort: Some(map_reasoning_effort_to_wire(config.model_reasoning_effort)),
+        };
+        self.ensure_thread_listener(connection_id, conversation_id, conversation);
+        self.outgoing.send_response(request_id, response).await;
+        thread.turns.clear();
+        send_server_notification(
+            &self.outgoing,
+            connection_id,
+            ServerNotification::ThreadStarted(ThreadStartedNotification { thread }),
+        )
+        .await;
+    }
+
+    /// Drops the last `num_turns` turns from the loaded conversation in place.
+    /// Core rewrites its history and records the cut in the rollout, so the
+    /// thread keeps its id and later `thread/read` calls see the same turns.
+    /// The reply waits for core's verdict: core decides whether there are
+    /// enough turns, and its error is returned when it refuses.
+    pub(crate) async fn thread_rollback_v2(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: RequestId,
+        params: ThreadRollbackParams,
+    ) {
+        let ThreadRollbackParams {
+            thread_id,
+            num_turns,
+        } = params;
+        if num_turns == 0 {
+            let error = invalid_request("numTurns must be >= 1".to_string());
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+        let (conversation_id, conversation) = match self.loaded_thread(&thread_id).await {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        if let Some(active) = self.active_turns.lock().await.get(&conversation_id) {
+            let error = invalid_request(format!(
+                "thread {thread_id} has turn {active} in progress; interrupt it before rolling back"
+            ));
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+        let path = match self.rollout_path_for_thread(&thread_id).await {
+            Ok(path) => path,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        // No turn is running, so nothing but this rollback can append to the
+        // rollout between this snapshot and core's reply.
+        let mut snapshot = match RolloutSnapshot::load(&path).await {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        self.ensure_thread_listener(connection_id, conversation_id, conversation.clone());
+        let result_rx = {
+            // Hold the lock across submit so the listener cannot see core's
+            // reply before the submission is registered.
+            let mut pending = self.pending_rollbacks.lock().await;
+            let sub_id = match conversation.submit(Op::ThreadRollback { num_turns }).await {
+                Ok(sub_id) => sub_id,
+                Err(err) => {
+                    drop(pending);
+                    let error = internal_error(format!("error rolling back thread: {err}"));
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            };
+            let (result_tx, result_rx) = oneshot::channel();
+            pending.insert(sub_id, result_tx);
+            result_rx
+        };
+
+        let outgoing = self.outgoing.clone();
+        let default_provider = self.config.model_provider_id.clone();
+        tokio::spawn(async move {
+            match result_rx.await {
+                Ok(Ok(())) => {
+                    // Apply the same rollback event core just recorded, so the
+                    // reply matches what `thread/read` returns from the rollout.
+                    snapshot
+                        .items
+                        .push(RolloutItem::EventMsg(ProtoEventMsg::ThreadRolledBack(
+                            ThreadRolledBackEvent { num_turns },
+                        )));
+                    let thread = snapshot.thread(&thread_id, &path, &default_provider, true);
+                    outgoing
+                        .send_response(request_id, ThreadRollbackResponse { thread })
+                        .await;
+                }
+                Ok(Err(message)) => {
+                    let error = invalid_request(format!("error rolling back thread: {message}"));
+                    outgoing.send_error(request_id, error).await;
+                }
+                Err(_) => {
+                    let error = internal_error(format!(
+                        "thread {thread_id} stopped before the rollback finished"
+                    ));
+                    outgoing.send_error(request_id, error).await;
+                }
+            }
+        });
+    }
+
+    pub(crate) async fn turn_start_v2(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: RequestId,
+        params: TurnStartParams,
+    ) {
+        let (conversation_id, conversation) = match self.loaded_thread(&params.thread_id).await {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        if params.input.is_empty() {
+            let error = invalid_request("turn input must not be empty".to_string());
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+        let overrides = unsupported_turn_overrides(&params);
+        if !overrides.is_empty() {
+            let error = invalid_request(format!(
+                "turn/start does not support per-turn overrides ({}); start a new thread with the desired settings",
+                overrides.join(", ")
+            ));
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+        self.ensure_thread_listener(connection_id, conversation_id, conversation.clone());
+
+        let op = Op::UserInput {
+            items: input_items_from_v2(params.input),
+            final_output_json_schema: params.output_schema,
+        };
+        match conversation.submit(op).await {
+            Ok(turn_id) => {
+                self.active_turns
+                    .lock()
+                    .await
+                    .insert(conversation_id, turn_id.clone());
+                let response = TurnStartResponse {
+                    turn: in_progress_turn(turn_id),
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                let error = internal_error(format!("error starting turn: {err}"));
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    pub(crate) async fn turn_steer_v2(&self, request_id: RequestId, params: TurnSteerParams) {
+        let TurnSteerParams {
+            thread_id,
+            input,
+            expected_turn_id,
+        } = params;
+        let (conversation_id, conversation) = match self.loaded_thread(&thread_id).await {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        if let Err(error) = self
+            .check_active_turn(conversation_id, &expected_turn_id)
+            .await
+        {
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+
+        let op = Op::QueueUserInput {
+            items: input_items_from_v2(input),
+        };
+        match conversation.submit(op).await {
+            Ok(_) => {
+                let response = TurnSteerResponse {
+                    turn_id: expected_turn_id,
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                let error = internal_error(format!("error steering turn: {err}"));
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    pub(crate) async fn turn_interrupt_v2(
+        &self,
+        request_id: RequestId,
+        params: TurnInterruptParams,
+    ) {
+        let (conversation_id, conversation) = match self.loaded_thread(&params.thread_id).await {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        if let Err(error) = self
+            .check_active_turn(conversation_id, &params.turn_id)
+            .await
+        {
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+
+        // The turn itself reports `turn/completed` with an interrupted status.
+        let _ = conversation.submit(Op::Interrupt).await;
+        self.outgoing
+            .send_response(request_id, TurnInterruptResponse {})
+            .await;
+    }
+
+    pub(crate) async fn review_start_v2(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: RequestId,
+        params: ReviewStartParams,
+    ) {
+        let ReviewStartParams {
+            thread_id,
+            target,
+            delivery,
+        } = params;
+
+        let (conversation_id, conversation) = match delivery.unwrap_or(ReviewDelivery::Inline) {
+            ReviewDelivery::Inline => match self.loaded_thread(&thread_id).await {
+                Ok(loaded) => loaded,
+                Err(error) => {
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            },
+            ReviewDelivery::Detached => match self.fork_for_review(&thread_id).await {
+                Ok(forked) => forked,
+                Err(error) => {
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            },
+        };
+        self.ensure_thread_listener(connection_id, conversation_id, conversation.clone());
+
+        let op = Op::Review {
+            review_request: review_request_from_target(target),
+        };
+        match conversation.submit(op).await {
+            Ok(turn_id) => {
+                self.active_turns
+                    .lock()
+                    .await
+                    .insert(conversation_id, turn_id.clone());
+                let response = ReviewStartResponse {
+                    turn: in_progress_turn(turn_id),
+                    review_thread_id: conversation_id.to_string(),
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                let error = internal_error(format!("error starting review: {err}"));
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    pub(crate) async fn model_list_v2(&self, request_id: RequestId, params: ModelListParams) {
+        let auth_mode = self.auth_manager.auth().map(|auth| auth.mode);
+        let supports_pro_only = self.auth_manager.supports_pro_only_models();
+        let include_hidden = params.include_hidden.unwrap_or(false);
+
+        let models: Vec<Model> = all_model_presets()
+            .iter()
+            .filter(|preset| model_preset_available_for_auth(preset, auth_mode, supports_pro_only))
+            .filter(|preset| include_hidden || preset.show_in_picker)
+            .map(|preset| Model {
+                id: preset.id.clone(),
+                model: preset.model.clone(),
+                upgrade: preset.upgrade.as_ref().map(|upgrade| upgrade.id.clone()),
+                display_name: preset.display_name.clone(),
+                description: preset.description.clone(),
+                hidden: !preset.show_in_picker,
+                supported_reasoning_efforts: preset
+                    .supported_reasoning_efforts
+                    .iter()
+                    .map(|option| ReasoningEffortOption {
+                        reasoning_effort: option.effort,
+                        description: option.description.clone(),
+                    })
+                    .collect(),
+                default_reasoning_effort: preset.default_reasoning_effort,
+                input_modalities: default_input_modalities(),
+                supports_personality: false,
+                is_default: preset.is_default,
+            })
+            .collect();
+
+        let start = match params.cursor.as_deref() {
+            Some(cursor) => match cursor.parse::<usize>() {
+                Ok(start) if start <= models.len() => start,
+                _ => {
+                    let error = invalid_request("invalid cursor".to_string());
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            },
+            None => 0,
+        };
+        let limit = params
+            .limit
+            .and_then(|limit| usize::try_from(limit).ok())
+            .filter(|limit| *limit > 0)
+            .unwrap_or(models.len());
+        let end = start.saturating_add(limit).min(models.len());
+        let next_cursor = (end < models.len()).then(|| end.to_string());
+        let data = models[start..end].to_vec();
+
+        self.outgoing
+            .send_response(request_id, ModelListResponse { data, next_cursor })
+            .await;
+    }
+
+    fn thread_config(&self, overrides: ThreadOverrides) -> Result<Config, JSONRPCErrorError> {
+        derive_thread_config(overrides, self.code_linux_sandbox_exe.clone())
+            .map_err(|err| invalid_request(format!("error deriving config: {err}")))
+    }
+
+    pub(super) async fn loaded_thread(
+        &self,
+        thread_id: &str,
+    ) -> Result<(ConversationId, Arc<CodexConversation>), JSONRPCErrorError> {
+        let conversation_id = parse_thread_id(thread_id)?;
+        let conversation = self
+            .conversation_manager
+            .get_conversation(conversation_id)
+            .await
+            .map_err(|_| invalid_request(format!("thread not loaded: {thread_id}")))?;
+        Ok((conversation_id, conversation))
+    }
+
+    async fn rollout_path_for_thread(&self, thread_id: &str) -> Result<PathBuf, JSONRPCErrorError> {
+        let conversation_id = parse_thread_id(thread_id)?;
+        let uuid = Uuid::from(conversation_id);
+        let catalog = code_core::SessionCatalog::new(self.config.code_home.clone());
+        let entry = catalog
+            .find_by_id(&uuid.to_string())
+            .await
+            .map_err(|err| internal_error(format!("failed to read session catalog: {err}")))?
+            .filter(|entry| entry.session_id == uuid)
+            .ok_or_else(|| invalid_request(format!("thread not found: {thread_id}")))?;
+        Ok(catalog.entry_rollout_path(&entry))
+    }
+
+    async fn check_active_turn(
+        &self,
+        conversation_id: ConversationId,
+        expected_turn_id: &str,
+    ) -> Result<(), JSONRPCErrorError> {
+        match self.active_turns.lock().await.get(&conversation_id) {
+            Some(active) if active == expected_turn_id => Ok(()),
+            Some(active) => Err(invalid_request(format!(
+                "expected turn {expected_turn_id} but turn {active} is active"
+            ))),
+            None => Err(invalid_request("no active turn".to_string())),
+        }
+    }
+
+    async fn fork_for_review(
+        &self,
+        thread_id: &str,
+    ) -> Result<(ConversationId, Arc<CodexConversation>), JSONRPCErrorError> {
+        let path = self.rollout_path_for_thread(thread_id).await?;
+        let snapshot = RolloutSnapshot::load(&path).await?;
+        let config = self.thread_config(ThreadOverrides {
+            cwd: snapshot.cwd(),
+            ..ThreadOverrides::default()
+        })?;
+        let NewConversation {
+            conversation_id,
+            conversation,
+            ..
+        } = self
+            .conversation_manager
+            .fork_conversation(0, config, path)
+            .await
+            .map_err(|err| internal_error(format!("error creating review thread: {err}")))?;
+        Ok((conversation_id, conversation))
+    }
+
+    /// Subscribe `connection_id` to v2 notifications for a thread unless it
+    /// already is.
+    pub(super) fn ensure_thread_listener(
+        &mut self,
+        connection_id: ConnectionId,
+        conversation_id: ConversationId,
+        conversation: Arc<CodexConversation>,
+    ) {
+        let key = (conversation_id, connection_id);
+        if self
+            .thread_subscriptions
+            .get(&key)
+            .is_some_and(|subscription_id| {
+                self.conversation_listeners.contains_key(subscription_id)
+            })
+        {
+            return;
+        }
+
+        let subscription_id = Uuid::new_v4();
+        let (cancel_tx, mut cancel_rx) = oneshot::channel();
+        self.conversation_listeners.insert(
+            subscription_id,
+            ConversationListenerRegistration {
+                owner_connection_id: connection_id,
+                cancel_tx,
+            },
+        );
+        self.thread_subscriptions.insert(key, subscription_id);
+
+        let outgoing = self.outgoing.clone();
+        let pending_interrupts = self.pending_interrupts.clone();
+        let active_turns = self.active_turns.clone();
+        let auto_drive_runs = self.auto_drive_runs.clone();
+        let pending_rollbacks = self.pending_rollbacks.clone();
+        tokio::spawn(async move {
+            let thread_id = conversation_id.to_string();
+            let mut mapper = ThreadEventMapper::new(thread_id.clone());
+            loop {
+                tokio::select! {
+                    _ = &mut cancel_rx => break,
+                    event = conversation.next_event() => {
+                        let event = match event {
+                            Ok(event) => event,
+                            Err(err) => {
+                                tracing::warn!("conversation.next_event() failed with: {err}");
+                                break;
+                            }
+                        };
+
+                        track_active_turn(&active_turns, conversation_id, &event).await;
+                        resolve_pending_rollback(&pending_rollbacks, &event).await;
+                        for notification in mapper.map(&event) {
+                            send_server_notification(&outgoing, connection_id, notification).await;
+                        }
+                        if let Some(notification) = agent_notification(&thread_id, &event) {
+                            send_server_notification(&outgoing, connection_id, notification).await;
+                        }
+                        forward_to_auto_drive(&auto_drive_runs, conversation_id, &event).await;
+
+                        apply_bespoke_event_handling(
+                            event,
+                            conversation_id,
+                            connection_id,
+                            conversation.clone(),
+                            outgoing.clone(),
+                            pending_interrupts.clone(),
+                        )
+                        .await;
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn derive_thread_config(
+    overrides: ThreadOverrides,
+    code_linux_sandbox_exe: Option<PathBuf>,
+) -> std::io::Result<Config> {
+    let ThreadOverrides {
+        model,
+        model_provider,
+        cwd,
+        approval_policy,
+        sandbox,
+        config: cli_overrides,
+        base_instructions,
+    } = overrides;
+    let overrides = ConfigOverrides {
+        model,
+        review_model: None,
+        config_profile: None,
+        cwd,
+        approval_policy: approval_policy
+            .map(|policy| map_ask_for_approval_from_wire(policy.to_core())),
+        sandbox_mode: sandbox.map(V2SandboxMode::to_core),
+        model_provider,
+        code_linux_sandbox_exe,
+        base_instructions,
+        include_plan_tool: None,
+        include_apply_patch_tool: None,
+        include_view_image_tool: None,
+        disable_response_storage: None,
+        show_raw_agent_reasoning: None,
+        debug: None,
+        tools_web_search_request: None,
+        mcp_servers: None,
+        experimental_client_tools: None,
+        dynamic_tools: None,
+        compact_prompt_override: None,
+        compact_prompt_override_file: None,
+    };
+
+    let cli_overrides = cli_overrides
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, v)| (k, json_to_toml(v)))
+        .collect();
+
+    Config::load_with_cli_overrides(cli_overrides, overrides)
+}
+
+pub(super) fn parse_thread_id(thread_id: &str) -> Result<ConversationId, JSONRPCErrorError> {
+    ConversationId::from_string(thread_id)
+        .map_err(|_| invalid_request(format!("invalid thread id: {thread_id}")))
+}
+
+pub(super) fn invalid_request(message: String) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code: INVALID_REQUEST_ERROR_CODE,
+        message,
+        data: None,
+    }
+}
+
+fn internal_error(message: String) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code: INTERNAL_ERROR_CODE,
+        message,
+        data: None,
+    }
+}
+
+pub(super) async fn send_server_notification(
+    outgoing: &OutgoingMessageSender,
+    connection_id: ConnectionId,
+    notification: ServerNotification,
+) {
+    let method = notification.to_string();
+    let params = match notification.to_params() {
+        Ok(params) => params,
+        Err(err) => {
+            tracing::error!("failed to serialize {method} notification: {err}");
+            return;
+        }
+    };
+    outgoing
+        .send_notification_to_connection(
+            connection_id,
+            OutgoingNotification {
+                method,
+                params: Some(params),
+            },
+        )
+        .await;
+}
+
+async fn track_active_turn(
+    active_turns: &ActiveTurns,
+    conversation_id: ConversationId,
+    event: &Event,
+) {
+    match &event.msg {
+        EventMsg::TaskStarted => {
+            active_turns
+                .lock()
+                .await
+                .insert(conversation_id, event.id.clone());
+        }
+        EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) => {
+            let mut turns = active_turns.lock().await;
+            if turns.get(&conversation_id) == Some(&event.id) {
+                turns.remove(&conversation_id);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Hand core's answer to a `thread/rollback` submission to the request
+/// waiting on it.
+async fn resolve_pending_rollback(pending_rollbacks: &PendingRollbacks, event: &Event) {
+    let result = match &event.msg {
+        EventMsg::ThreadRolledBack(_) => Ok(()),
+        EventMsg::Error(error) => Err(error.message.clone()),
+        _ => return,
+    };
+    if let Some(result_tx) = pending_rollbacks.lock().await.remove(&event.id) {
+        let _ = result_tx.send(result);
+    }
+}
+
+fn fresh_thread(conversation_id: ConversationId, config: &Config) -> Thread {
+    let now = chrono::Utc::now().timestamp();
+    Thread {
+        id: conversation_id.to_string(),
+        preview: String::new(),
+        model_provider: config.model_provider_id.clone(),
+        created_at: now,
+        updated_at: now,
+        path: None,
+        cwd: config.cwd.clone(),
+        cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        source: V2SessionSource::AppServer,
+        git_info: None,
+        turns: Vec::new(),
+    }
+}
+
+fn in_progress_turn(turn_id: String) -> Turn {
+    Turn {
+        id: turn_id,
+        items: Vec::new(),
+        status: TurnStatus::InProgress,
+        error: None,
+    }
+}
+
+fn input_items_from_v2(input: Vec<V2UserInput>) -> Vec<CoreInputItem> {
+    input
+        .into_iter()
+        .map(|item| match item {
+            V2UserInput::Text { text, .. } => CoreInputItem::Text { text },
+            V2UserInput::Image { url } => CoreInputItem::Image { image_url: url },
+            V2UserInput::LocalImage { path } => CoreInputItem::LocalImage { path },
+            // Core has no structured skill/mention input; pass them as references.
+            V2UserInput::Skill { name, path } => CoreInputItem::Text {
+                text: format!("${name} ({})", path.display()),
+            },
+            V2UserInput::Mention { name, path } => CoreInputItem::Text {
+                text: format!("@{name} ({path})"),
+            },
+        })
+        .collect()
+}
+
+fn review_request_from_target(target: ReviewTarget) -> ReviewRequest {
+    review_request_for_target(review_target_to_core(target))
+}
+
+fn review_target_to_core(target: ReviewTarget) -> code_protocol::protocol::ReviewTarget {
+    match target {
+        ReviewTarget::UncommittedChanges => {
+            code_protocol::protocol::ReviewTarget::UncommittedChanges
+        }
+        ReviewTarget::BaseBranch { branch } => {
+            code_protocol::protocol::ReviewTarget::BaseBranch { branch }
+        }
+        ReviewTarget::Commit { sha, title } => {
+            code_protocol::protocol::ReviewTarget::Commit { sha, title }
+        }
+        ReviewTarget::StagedChanges => code_protocol::protocol::ReviewTarget::StagedChanges,
+        ReviewTarget::CommitRange { from, to } => {
+            code_protocol::protocol::ReviewTarget::CommitRange { from, to }
+        }
+        ReviewTarget::Paths { paths, scope } => code_protocol::protocol::ReviewTarget::Paths {
+            paths,
+            scope: Box::new(review_target_to_core(*scope)),
+        },
+        ReviewTarget::Custom { instructions } => {
+            code_protocol::protocol::ReviewTarget::Custom { instructions }
+        }
+    }
+}
+
+fn sandbox_policy_to_v2(policy: &SandboxPolicy) -> V2SandboxPolicy {
+    match policy {
+        SandboxPolicy::DangerFullAccess => V2SandboxPolicy::DangerFullAccess,
+        SandboxPolicy::ReadOnly => V2SandboxPolicy::ReadOnly,
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            network_access,
+            exclude_tmpdir_env_var,
+            exclude_slash_tmp,
+            ..
+        } => V2SandboxPolicy::WorkspaceWrite {
+            writable_roots: writable_roots
+                .iter()
+                .filter_map(|root| root.clone().try_into().ok())
+                .collect(),
+            network_access: *network_access,
+            exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
+            exclude_slash_tmp: *exclude_slash_tmp,
+        },
+    }
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|parsed| parsed.timestamp())
+}
+
+/// The parts of a rollout file needed to describe a persisted thread.
+struct RolloutSnapshot {
+    meta: Option<SessionMetaLine>,
+    items: Vec<RolloutItem>,
+    updated_at: Option<String>,
+}
+
+impl RolloutSnapshot {
+    async fn load(path: &Path) -> Result<Self, JSONRPCErrorError> {
+        let text = tokio::fs::read_to_string(path).await.map_err(|err| {
+            internal_error(format!("failed to read rollout {}: {err}", path.display()))
+        })?;
+        Ok(Self::parse(&text))
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut meta = None;
+        let mut items = Vec::new();
+        let mut updated_at = None;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let Ok(RolloutLine { timestamp, item }) = serde_json::from_str::<RolloutLine>(line)
+            else {
+                continue;
+            };
+            updated_at = Some(timestamp);
+            if let RolloutItem::SessionMeta(line) = &item
+                && meta.is_none()
+            {
+                meta = Some(line.clone());
+            }
+            items.push(item);
+        }
+        Self {
+            meta,
+            items,
+            updated_at,
+        }
+    }
+
+    fn events(&self) -> Vec<ProtoEventMsg> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                RolloutItem::Event(recorded) => Some(recorded.msg.clone()),
+                RolloutItem::EventMsg(msg) => Some(msg.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn cwd(&self) -> Option<PathBuf> {
+        self.meta
+            .as_ref()
+            .map(|line| line.meta.cwd.clone())
+            .filter(|cwd| !cwd.as_os_str().is_empty())
+    }
+
+    fn preview(&self) -> String {
+        self.events()
+            .into_iter()
+            .find_map(|msg| match msg {
+                ProtoEventMsg::UserMessage(user) => Some(user.message.trim().to_string()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn git_info(&self) -> Option<V2GitInfo> {
+        let git = self.meta.as_ref()?.git.as_ref()?;
+        Some(V2GitInfo {
+            sha: git.commit_hash.clone(),
+            branch: git.branch.clone(),
+            origin_url: git.repository_url.clone(),
+        })
+    }
+
+    fn thread(
+        &self,
+        thread_id: &str,
+        path: &Path,
+        default_provider: &str,
+        include_turns: bool,
+    ) -> Thread {
+        let meta = self.meta.as_ref().map(|line| &line.meta);
+        let created_at = meta
+            .and_then(|meta| parse_timestamp(&meta.timestamp))
+            .unwrap_or_default();
+        let updated_at = self
+            .updated_at
+            .as_deref()
+            .and_then(parse_timestamp)
+            .unwrap_or(created_at);
+        Thread {
+            id: thread_id.to_string(),
+            preview: self.preview(),
+            model_provider: meta
+                .and_then(|meta| meta.model_provider.clone())
+                .unwrap_or_else(|| default_provider.to_string()),
+            created_at,
+            updated_at,
+            path: Some(path.to_path_buf()),
+            cwd: self.cwd().unwrap_or_default(),
+            cli_version: meta
+                .map(|meta| meta.cli_version.clone())
+                .unwrap_or_default(),
+            source: meta
+                .map(|meta| meta.source.clone().into())
+                .unwrap_or(V2SessionSource::Unknown),
+            git_info: self.git_info(),
+            turns: if include_turns {
+                build_turns_from_event_msgs(&self.events())
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+/// Names of the `turn/start` overrides the request sets. The core session is
+/// configured once per thread, so these cannot be applied to a single turn.
+fn unsupported_turn_overrides(params: &TurnStartParams) -> Vec<&'static str> {
+    [
+        ("cwd", params.cwd.is_some()),
+        ("approvalPolicy", params.approval_policy.is_some()),
+        ("sandboxPolicy", params.sandbox_policy.is_some()),
+        ("model", params.model.is_some()),
+        ("effort", params.effort.is_some()),
+        ("summary", params.summary.is_some()),
+        ("personality", params.personality.is_some()),
+        ("collaborationMode", params.collaboration_mode.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(name, set)| set.then_some(name))
+    .collect()
+}
+
+/// Maps core events of one thread onto v2 notifications, keeping just enough
+/// state to give streamed items stable ids and to pair begin/end events.
+pub(super) struct ThreadEventMapper {
+    thread_id: String,
+    message_seq: u64,
+    open_message: Option<String>,
+    reasoning_seq: u64,
+    open_reasoning: Option<String>,
+    commands: HashMap<String, (String, PathBuf, Vec<CommandAction>)>,
+    patches: HashMap<String, Vec<FileUpdateChange>>,
+    turn_error: Option<TurnError>,
+}
+
+impl ThreadEventMapper {
+    pub(super) fn new(thread_id: String) -> Self {
+        Self {
+            thread_id,
+            message_seq: 0,
+            open_message: None,
+            reasoning_seq: 0,
+            open_reasoning: None,
+            commands: HashMap::new(),
+            patches: HashMap::new(),
+            turn_error: None,
+        }
+    }
+
+    pub(super) fn map(&mut self, event: &Event) -> Vec<ServerNotification> {
+        let thread_id = self.thread_id.clone();
+        let turn_id = event.id.clone();
+        match &event.msg {
+            EventMsg::TaskStarted => {
+                self.open_message = None;
+                self.open_reasoning = None;
+                self.turn_error = None;
+                vec![ServerNotification::TurnStarted(TurnStartedNotification {
+                    thread_id,
+                    turn: in_progress_turn(turn_id),
+                })]
+            }
+            EventMsg::TaskComplete(_) => {
+                let error = self.turn_error.take();
+                let status = if error.is_some() {
+                    TurnStatus::Failed
+                } else {
+                    TurnStatus::Completed
+                };
+                vec![ServerNotification::TurnCompleted(
+                    TurnCompletedNotification {
+                        thread_id,
+                        turn: Turn {
+                            id: turn_id,
+                            items: Vec::new(),
+                            status,
+                            error,
+                        },
+                    },
+                )]
+            }
+            EventMsg::TurnAborted(_) => {
+                self.turn_error = None;
+                vec![ServerNotification::TurnCompleted(
+                    TurnCompletedNotification {
+                        thread_id,
+                        turn: Turn {
+                            id: turn_id,
+                            items: Vec::new(),
+                            status: TurnStatus::Interrupted,
+                            error: None,
+                        },
+                    },
+                )]
+            }
+            EventMsg::Error(error) => {
+                let error = TurnError {
+                    message: error.message.clone(),
+                    codex_error_info: None,
+                    additional_details: None,
+                };
+                self.turn_error = Some(error.clone());
+                vec![ServerNotification::Error(ErrorNotification {
+                    error,
+                    will_retry: false,
+                    thread_id,
+                    turn_id,
+                })]
+            }
+            EventMsg::UserMessage(message) => {
+                let mut content = vec![V2UserInput::Text {
+                    text: message.message.clone(),
+                    text_elements: Vec::new(),
+                }];
+                content.extend(
+                    message
+                        .images
+                        .iter()
+                        .flatten()
+                        .map(|url| V2UserInput::Image { url: url.clone() }),
+                );
+                let item = ThreadItem::UserMessage {
+                    id: format!("{turn_id}-user"),
+                    content,
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::AgentMessageDelta(delta) => {
+                let mut out = Vec::new();
+                let item_id = match &self.open_message {
+                    Some(id) => id.clone(),
+                    None => {
+                        let id = format!("{turn_id}-msg-{}", self.message_seq);
+                        self.open_message = Some(id.clone());
+                        out.push(item_started(
+                            thread_id.clone(),
+                            turn_id.clone(),
+                            ThreadItem::AgentMessage {
+                                id: id.clone(),
+                                text: String::new(),
+                            },
+                        ));
+                        id
+                    }
+                };
+                out.push(ServerNotification::AgentMessageDelta(
+                    AgentMessageDeltaNotification {
+                        thread_id,
+                        turn_id,
+                        item_id,
+                        delta: delta.delta.clone(),
+                    },
+                ));
+                out
+            }
+            EventMsg::AgentMessage(message) => {
+                let id = self
+                    .open_message
+                    .take()
+                    .unwrap_or_else(|| format!("{turn_id}-msg-{}", self.message_seq));
+                self.message_seq += 1;
+                let item = ThreadItem::AgentMessage {
+                    id,
+                    text: message.message.clone(),
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::AgentReasoningDelta(delta) => {
+                let mut out = Vec::new();
+                let item_id = match &self.open_reasoning {
+                    Some(id) => id.clone(),
+                    None => {
+                        let id = format!("{turn_id}-reasoning-{}", self.reasoning_seq);
+                        self.open_reasoning = Some(id.clone());
+                        out.push(item_started(
+                            thread_id.clone(),
+                            turn_id.clone(),
+                            ThreadItem::Reasoning {
+                                id: id.clone(),
+                                summary: Vec::new(),
+                                content: Vec::new(),
+                            },
+                        ));
+                        id
+                    }
+                };
+                out.push(ServerNotification::ReasoningSummaryTextDelta(
+                    ReasoningSummaryTextDeltaNotification {
+                        thread_id,
+                        turn_id,
+                        item_id,
+                        delta: delta.delta.clone(),
+                        summary_index: 0,
+                    },
+                ));
+                out
+            }
+            EventMsg::AgentReasoning(reasoning) => {
+                let id = self
+                    .open_reasoning
+                    .take()
+                    .unwrap_or_else(|| format!("{turn_id}-reasoning-{}", self.reasoning_seq));
+                self.reasoning_seq += 1;
+                let item = ThreadItem::Reasoning {
+                    id,
+                    summary: vec![reasoning.text.clone()],
+                    content: Vec::new(),
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::ExecCommandBegin(begin) => {
+                let command = begin.command.join(" ");
+                let actions: Vec<CommandAction> = begin
+                    .parsed_cmd
+                    .iter()
+                    .cloned()
+                    .map(|parsed| {
+                        CommandAction::from(code_protocol::parse_command::ParsedCommand::from(
+                            parsed,
+                        ))
+                    })
+                    .collect();
+                self.commands.insert(
+                    begin.call_id.clone(),
+                    (command.clone(), begin.cwd.clone(), actions.clone()),
+                );
+                let item = ThreadItem::CommandExecution {
+                    id: begin.call_id.clone(),
+                    command,
+                    cwd: begin.cwd.clone(),
+                    process_id: None,
+                    status: CommandExecutionStatus::InProgress,
+                    command_actions: actions,
+                    aggregated_output: None,
+                    exit_code: None,
+                    duration_ms: None,
+                };
+                vec![item_started(thread_id, turn_id, item)]
+            }
+            EventMsg::ExecCommandOutputDelta(delta) => {
+                vec![ServerNotification::CommandExecutionOutputDelta(
+                    CommandExecutionOutputDeltaNotification {
+                        thread_id,
+                        turn_id,
+                        item_id: delta.call_id.clone(),
+                        delta: String::from_utf8_lossy(&delta.chunk).into_owned(),
+                    },
+                )]
+            }
+            EventMsg::ExecCommandEnd(end) => {
+                let (command, cwd, actions) =
+                    self.commands.remove(&end.call_id).unwrap_or_default();
+                let aggregated_output = match (end.stdout.is_empty(), end.stderr.is_empty()) {
+                    (_, true) => end.stdout.clone(),
+                    (true, false) => end.stderr.clone(),
+                    (false, false) => format!("{}\n{}", end.stdout, end.stderr),
+                };
+                let item = ThreadItem::CommandExecution {
+                    id: end.call_id.clone(),
+                    command,
+                    cwd,
+                    process_id: None,
+                    status: if end.exit_code == 0 {
+                        CommandExecutionStatus::Completed
+                    } else {
+                        CommandExecutionStatus::Failed
+                    },
+                    command_actions: actions,
+                    aggregated_output: Some(aggregated_output),
+                    exit_code: Some(end.exit_code),
+                    duration_ms: i64::try_from(end.duration.as_millis()).ok(),
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::PatchApplyBegin(begin) => {
+                let mut changes: Vec<FileUpdateChange> = begin
+                    .changes
+                    .iter()
+                    .map(|(path, change)| file_update_change(path, change))
+                    .collect();
+                changes.sort_by(|a, b| a.path.cmp(&b.path));
+                self.patches.insert(begin.call_id.clone(), changes.clone());
+                let item = ThreadItem::FileChange {
+                    id: begin.call_id.clone(),
+                    changes,
+                    status: PatchApplyStatus::InProgress,
+                };
+                vec![item_started(thread_id, turn_id, item)]
+            }
+            EventMsg::PatchApplyEnd(end) => {
+                let item = ThreadItem::FileChange {
+                    id: end.call_id.clone(),
+                    changes: self.patches.remove(&end.call_id).unwrap_or_default(),
+                    status: if end.success {
+                        PatchApplyStatus::Completed
+                    } else {
+                        PatchApplyStatus::Failed
+                    },
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::McpToolCallBegin(begin) => {
+                let item = ThreadItem::McpToolCall {
+                    id: begin.call_id.clone(),
+                    server: begin.invocation.server.clone(),
+                    tool: begin.invocation.tool.clone(),
+                    status: McpToolCallStatus::InProgress,
+                    arguments: begin.invocation.arguments.clone().unwrap_or_default(),
+                    result: None,
+                    error: None,
+                    duration_ms: None,
+                };
+                vec![item_started(thread_id, turn_id, item)]
+            }
+            EventMsg::McpToolCallEnd(end) => {
+                let (status, result, error) = match &end.result {
+                    Ok(result) => {
+                        let value = serde_json::to_value(result).unwrap_or_default();
+                        let failed = value
+                            .get("isError")
+                            .and_then(serde_json::Value::as_bool)
+                            .unwrap_or(false);
+                        let result = McpToolCallResult {
+                            content: value
+                                .get("content")
+                                .and_then(serde_json::Value::as_array)
+                                .cloned()
+                                .unwrap_or_default(),
+                            structured_content: value.get("structuredContent").cloned(),
+                        };
+                        let status = if failed {
+                            McpToolCallStatus::Failed
+                        } else {
+                            McpToolCallStatus::Completed
+                        };
+                        (status, Some(result), None)
+                    }
+                    Err(message) => (
+                        McpToolCallStatus::Failed,
+                        None,
+                        Some(McpToolCallError {
+                            message: message.clone(),
+                        }),
+                    ),
+                };
+                let item = ThreadItem::McpToolCall {
+                    id: end.call_id.clone(),
+                    server: end.invocation.server.clone(),
+                    tool: end.invocation.tool.clone(),
+                    status,
+                    arguments: end.invocation.arguments.clone().unwrap_or_default(),
+                    result,
+                    error,
+                    duration_ms: i64::try_from(end.duration.as_millis()).ok(),
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::WebSearchBegin(begin) => {
+                let item = ThreadItem::WebSearch {
+                    id: begin.call_id.clone(),
+                    query: begin.query.clone().unwrap_or_default(),
+                    action: None,
+                };
+                vec![item_started(thread_id, turn_id, item)]
+            }
+            EventMsg::WebSearchComplete(complete) => {
+                let item = ThreadItem::WebSearch {
+                    id: complete.call_id.clone(),
+                    query: complete.query.clone().unwrap_or_default(),
+                    action: None,
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::EnteredReviewMode(request) => {
+                let item = ThreadItem::EnteredReviewMode {
+                    id: format!("{turn_id}-review"),
+                    review: request
+                        .user_facing_hint
+                        .clone()
+                        .unwrap_or_else(|| request.prompt.clone()),
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::ExitedReviewMode(exited) => {
+                let item = ThreadItem::ExitedReviewMode {
+                    id: format!("{turn_id}-review"),
+                    review: exited
+                        .review_output
+                        .as_ref()
+                        .map(|output| output.overall_explanation.clone())
+                        .unwrap_or_default(),
+                };
+                vec![item_completed(thread_id, turn_id, item)]
+            }
+            EventMsg::TurnDiff(diff) => {
+                vec![ServerNotification::TurnDiffUpdated(
+                    TurnDiffUpdatedNotification {
+                        thread_id,
+                        turn_id,
+                        diff: diff.unified_diff.clone(),
+                    },
+                )]
+            }
+            EventMsg::PlanUpdate(update) => {
+                vec![ServerNotification::TurnPlanUpdated(
+                    TurnPlanUpdatedNotification {
+                        thread_id,
+                        turn_id,
+                        explanation: update.explanation.clone(),
+                        plan: update.plan.iter().cloned().map(Into::into).collect(),
+                    },
+                )]
+            }
+            EventMsg::TokenCount(_) => {
+                let Some(ProtoEventMsg::TokenCount(count)) =
+                    code_core::protocol::event_msg_to_protocol(&event.msg)
+                else {
+                    return Vec::new();
+                };
+                let Some(info) = count.info else {
+                    return Vec::new();
+                };
+                vec![ServerNotification::ThreadTokenUsageUpdated(
+                    ThreadTokenUsageUpdatedNotification {
+                        thread_id,
+                        turn_id,
+                        token_usage: info.into(),
+                    },
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn item_started(thread_id: String, turn_id: String, item: ThreadItem) -> ServerNotification {
+    ServerNotification::ItemStarted(ItemStartedNotification {
+        item,
+        thread_id,
+        turn_id,
+    })
+}
+
+fn item_completed(thread_id: String, turn_id: String, item: ThreadItem) -> ServerNotification {
+    ServerNotification::ItemCompleted(ItemCompletedNotification {
+        item,
+        thread_id,
+        turn_id,
+    })
+}
+
+fn file_update_change(path: &Path, change: &code_core::protocol::FileChange) -> FileUpdateChange {
+    let (kind, diff) = match change {
+        code_core::protocol::FileChange::Add { content } => (PatchChangeKind::Add, content.clone()),
+        code_core::protocol::FileChange::Delete => (PatchChangeKind::Delete, String::new()),
+        code_core::protocol::FileChange::Update {
+            unified_diff,
+            move_path,
+            ..
+        } => (
+            PatchChangeKind::Update {
+                move_path: move_path.clone(),
+            },
+            unified_diff.clone(),
+        ),
+    };
+    FileUpdateChange {
+        path: path.display().to_string(),
+        kind,
+        diff,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::protocol::AgentMessageDeltaEvent;
+    use code_core::protocol::AgentMessageEvent;
+    use code_core::protocol::ExecCommandBeginEvent;
+    use code_core::protocol::ExecCommandEndEvent;
+    use code_core::protocol::TaskCompleteEvent;
+    use std::time::Duration;
+
+    fn event(id: &str, msg: EventMsg) -> Event {
+        Event {
+            id: id.to_string(),
+            event_seq: 0,
+            msg,
+            order: None,
+        }
+    }
+
+    fn methods(notifications: &[ServerNotification]) -> Vec<String> {
+        notifications.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn mapper_streams_agent_message_under_one_item() {
+        let mut mapper = ThreadEventMapper::new("thread".to_string());
+
+        let started = mapper.map(&event("7", EventMsg::TaskStarted));
+        assert_eq!(methods(&started), vec!["turn/started"]);
+
+        let first = mapper.map(&event(
+            "7",
+            EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: "Hel".to_string(),
+            }),
+        ));
+        assert_eq!(
+            methods(&first),
+            vec!["item/started", "item/agentMessage/delta"]
+        );
+
+        let second = mapper.map(&event(
+            "7",
+            EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: "lo".to_string(),
+            }),
+        ));
+        assert_eq!(methods(&second), vec!["item/agentMessage/delta"]);
+
+        let done = mapper.map(&event(
+            "7",
+            EventMsg::AgentMessage(AgentMessageEvent {
+                message: "Hello".to_string(),
+            }),
+        ));
+        match done.as_slice() {
+            [ServerNotification::ItemCompleted(completed)] => {
+                assert_eq!(completed.turn_id, "7");
+                assert_eq!(
+                    completed.item,
+                    ThreadItem::AgentMessage {
+                        id: "7-msg-0".to_string(),
+                        text: "Hello".to_string(),
+                    }
+                );
+            }
+            other => panic!("unexpected notifications: {other:?}"),
+        }
+
+        let completed = mapper.map(&event(
+            "7",
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: None,
+            }),
+        ));
+        match completed.as_slice() {
+            [ServerNotification::TurnCompleted(done)] => {
+                assert_eq!(done.turn.status, TurnStatus::Completed);
+            }
+            other => panic!("unexpected notifications: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn mapper_pairs_command_begin_and_end() {
+        let mut mapper = ThreadEventMapper::new("thread".to_string());
+        mapper.map(&event(
+            "1",
+            EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+                call_id: "call".to_string(),
+                command: vec!["ls".to_string(), "-la".to_string()],
+                cwd: PathBuf::from("/repo"),
+                parsed_cmd: Vec::new(),
+            }),
+        ));
+        let end = mapper.map(&event(
+            "1",
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+                call_id: "call".to_string(),
+                stdout: "out".to_string(),
+                stderr: String::new(),
+                exit_code: 2,
+                duration: Duration::from_millis(15),
+            }),
+        ));
+        match end.as_slice() {
+            [ServerNotification::ItemCompleted(completed)] => match &completed.item {
+                ThreadItem::CommandExecution {
+                    command,
+                    cwd,
+                    status,
+                    aggregated_output,
+                    duration_ms,
+                    ..
+                } => {
+                    assert_eq!(command, "ls -la");
+                    assert_eq!(cwd, &PathBuf::from("/repo"));
+                    assert_eq!(status, &CommandExecutionStatus::Failed);
+                    assert_eq!(aggregated_output.as_deref(), Some("out"));
+                    assert_eq!(*duration_ms, Some(15));
+                }
+                other => panic!("unexpected item: {other:?}"),
+            },
+            other => panic!("unexpected notifications: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn snapshot_turns_follow_recorded_rollbacks() {
+        let rollout = [
+            r#"{"timestamp":"2025-01-01T00:00:00Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"<environment_context/>"}]}}"#,
+            r#"{"timestamp":"2025-01-01T00:00:01Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"first"}]}}"#,
+            r#"{"timestamp":"2025-01-01T00:00:01Z","type":"event_msg","payload":{"type":"user_message","message":"first"}}"#,
+            r#"{"timestamp":"2025-01-01T00:00:02Z","type":"event_msg","payload":{"type":"agent_message","message":"one"}}"#,
+            r#"{"timestamp":"2025-01-01T00:00:03Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"second"}]}}"#,
+            r#"{"timestamp":"2025-01-01T00:00:03Z","type":"event_msg","payload":{"type":"user_message","message":"second"}}"#,
+            r#"{"timestamp":"2025-01-01T00:00:04Z","type":"event_msg","payload":{"type":"agent_message","message":"two"}}"#,
+        ]
+        .join("\n");
+        let snapshot = RolloutSnapshot::parse(&rollout);
+        assert_eq!(snapshot.items.len(), 7);
+        assert_eq!(snapshot.preview(), "first");
+
+        assert_eq!(build_turns_from_event_msgs(&snapshot.events()).len(), 2);
+
+        let rolled_back = format!(
+            "{rollout}\n{}",
+            r#"{"timestamp":"2025-01-01T00:00:05Z","type":"event_msg","payload":{"type":"thread_rolled_back","num_turns":1}}"#
+        );
+        let snapshot = RolloutSnapshot::parse(&rolled_back);
+        assert_eq!(build_turns_from_event_msgs(&snapshot.events()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rollback_waits_for_core_result_of_its_submission() {
+        let pending: PendingRollbacks = Arc::new(Mutex::new(HashMap::new()));
+        let (ok_tx, ok_rx) = oneshot::channel();
+        let (err_tx, err_rx) = oneshot::channel();
+        pending.lock().await.insert("7".to_string(), ok_tx);
+        pending.lock().await.insert("8".to_string(), err_tx);
+
+        // Unrelated events and events for other submissions are ignored.
+        resolve_pending_rollback(&pending, &event("7", EventMsg::TaskStarted)).await;
+        resolve_pending_rollback(
+            &pending,
+            &event(
+                "9",
+                EventMsg::ThreadRolledBack(ThreadRolledBackEvent { num_turns: 1 }),
+            ),
+        )
+        .await;
+        assert_eq!(pending.lock().await.len(), 2);
+
+        resolve_pending_rollback(
+            &pending,
+            &event(
+                "7",
+                EventMsg::ThreadRolledBack(ThreadRolledBackEvent { num_turns: 1 }),
+            ),
+        )
+        .await;
+        resolve_pending_rollback(
+            &pending,
+            &event(
+                "8",
+                EventMsg::Error(code_core::protocol::ErrorEvent {
+                    message: "Thread has fewer than 3 turns to roll back".to_string(),
+                }),
+            ),
+        )
+        .await;
+        assert_eq!(ok_rx.await.expect("result"), Ok(()));
+        assert_eq!(
+            err_rx.await.expect("result"),
+            Err("Thread has fewer than 3 turns to roll back".to_string())
+        );
+        assert!(pending.lock().await.is_empty());
+    }
+
+    #[test]
+    fn turn_start_lists_unsupported_overrides() {
+        let mut params = TurnStartParams {
+            thread_id: "thread".to_string(),
+            input: Vec::new(),
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+            personality: None,
+            output_schema: Some(serde_json::json!({"type": "object"})),
+            collaboration_mode: None,
+        };
+        assert!(unsupported_turn_overrides(&params).is_empty());
+
+        params.cwd = Some(PathBuf::from("/tmp"));
+        params.model = Some("gpt-5".to_string());
+        assert_eq!(unsupported_turn_overrides(&params), vec!["cwd", "model"]);
+    }
+
+    #[test]
+    fn review_target_maps_to_core_request() {
+        let request = review_request_from_target(ReviewTarget::Commit {
+            sha: "0123456789abcdef".to_string(),
+            title: Some("Fix parser".to_string()),
+        });
+        assert_eq!(request.user_facing_hint.as_deref(), Some("commit 0123456"));
+        assert!(request.prompt.contains("0123456789abcdef (\"Fix parser\")"));
+        assert!(matches!(
+            request.target,
+            code_protocol::protocol::ReviewTarget::Commit { .. }
+        ));
+    }
+
+    #[test]
+    fn path_scoped_range_review_maps_to_core_target() {
+        let request = review_request_from_target(ReviewTarget::Paths {
+            paths: vec!["src/**".to_string()],
+            scope: Box::new(ReviewTarget::CommitRange {
+                from: "main".to_string(),
+                to: "HEAD".to_string(),
+            }),
+        });
+        assert_eq!(
+            request.target,
+            code_protocol::protocol::ReviewTarget::Paths {
+                paths: vec!["src/**".to_string()],
+                scope: Box::new(code_protocol::protocol::ReviewTarget::CommitRange {
+                    from: "main".to_string(),
+                    to: "HEAD".to_string(),
+                }),
+            }
+        );
+    }
+}
@@ -123,6 +123,18 @@ impl MessageProcessor {
             return;
         }
 
+        if self
+            .try_process_v2_thread_request(
+                connection_id,
+                request_id.clone(),
+                &request,
+                session.initialized,
+            )
+            .await
+        {
+            return;
+        }
+
         if let Ok(request_json) = serde_json::to_value(request)
             && let Ok(code_request) = serde_json::from_value::<ClientRequest>(request_json)
         {
@@ -481,6 +493,150 @@ impl MessageProcessor {
         }
     }
 
+    async fn try_process_v2_thread_request(
+        &mut self,
+        connection_id: ConnectionId,
+        request_id: mcp_types::RequestId,
+        request: &JSONRPCRequest,
+        session_initialized: bool,
+    ) -> bool {
+        let is_v2_request = matches!(
+            request.method.as_str(),
+            "thread/start"
+                | "thread/fork"
+                | "thread/rollback"
+                | "thread/read"
+                | "turn/start"
+                | "turn/steer"
+                | "turn/interrupt"
+                | "review/start"
+                | "model/list"
+                | "autoDrive/start"
+                | "autoDrive/pause"
+                | "autoDrive/resume"
+                | "autoDrive/stop"
+                | "agent/list"
+                | "agent/read"
+        );
+        if !is_v2_request {
+            return false;
+        }
+        if !session_initialized {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: "Not initialized".to_string(),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return true;
+        }
+
+        match request.method.as_str() {
+            "thread/start" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .thread_start_v2(connection_id, request_id, params)
+                        .await;
+                }
+            }
+            "thread/fork" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .thread_fork_v2(connection_id, request_id, params)
+                        .await;
+                }
+            }
+            "thread/rollback" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .thread_rollback_v2(connection_id, request_id, params)
+                        .await;
+                }
+            }
+            "thread/read" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor.thread_read_v2(request_id, params).await;
+                }
+            }
+            "turn/start" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .turn_start_v2(connection_id, request_id, params)
+                        .await;
+                }
+            }
+            "turn/steer" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor.turn_steer_v2(request_id, params).await;
+                }
+            }
+            "turn/interrupt" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor.turn_interrupt_v2(request_id, params).await;
+                }
+            }
+            "review/start" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .review_start_v2(connection_id, request_id, params)
+                        .await;
+                }
+            }
+            "model/list" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor.model_list_v2(request_id, params).await;
+                }
+            }
+            "autoDrive/start" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .auto_drive_start_v2(connection_id, request_id, params)
+                        .await;
+                }
+            }
+            "autoDrive/pause" | "autoDrive/resume" | "autoDrive/stop" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor
+                        .auto_drive_control_v2(request_id, &request.method, params)
+                        .await;
+                }
+            }
+            "agent/list" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor.agent_list_v2(request_id, params).await;
+                }
+            }
+            "agent/read" => {
+                if let Some(params) = self.parse_v2_params(&request_id, request).await {
+                    self.code_message_processor.agent_read_v2(request_id, params).await;
+                }
+            }
+            _ => unreachable!("checked by is_v2_request"),
+        }
+        true
+    }
+
+    /// Parse v2 request params, replying with an invalid-request error on failure.
+    async fn parse_v2_params<T: serde::de::DeserializeOwned>(
+        &self,
+        request_id: &mcp_types::RequestId,
+        request: &JSONRPCRequest,
+    ) -> Option<T> {
+        let params_value = request.params.clone().unwrap_or_else(|| json!({}));
+        match serde_json::from_value(params_value) {
+            Ok(params) => Some(params),
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INVALID_REQUEST_ERROR_CODE,
+                    message: format!("Invalid {} params: {err}", request.method),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id.clone(), error).await;
+                None
+            }
+        }
+    }
+
     fn load_effective_config(&self, cwd: Option<&str>) -> Result<Config, JSONRPCErrorError> {
         let mut overrides = code_core::config::ConfigOverrides::default();
         overrides.code_linux_sandbox_exe = self.base_config.code_linux_sandbox_exe.clone();
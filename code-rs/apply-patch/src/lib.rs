@@ -205,7 +205,7 @@ pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ApplyPatchFileChange {
     Add {
         content: String,
@@ -261,6 +261,26 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// Returns a copy of this action with its changes replaced, e.g. after the
+    /// user approved only a subset of the hunks.
+    pub fn with_changes(&self, changes: HashMap<PathBuf, ApplyPatchFileChange>) -> Self {
+        Self {
+            changes,
+            patch: self.patch.clone(),
+            cwd: self.cwd.clone(),
+        }
+    }
+
+    /// Builds an action from changes that did not come from a parsed patch,
+    /// e.g. the files an agent modified in its worktree.
+    pub fn from_changes(changes: HashMap<PathBuf, ApplyPatchFileChange>, cwd: PathBuf) -> Self {
+        Self {
+            changes,
+            patch: String::new(),
+            cwd,
+        }
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::bail;
+use clap::Parser;
+use code_core::agent_board::{self, BoardEntry, BoardEntryKind};
+
+#[derive(Debug, Parser)]
+pub struct AgentBoardCli {
+    #[command(subcommand)]
+    pub cmd: AgentBoardSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum AgentBoardSubcommand {
+    /// Show notes from other agents and guidance for this agent posted since
+    /// the last read.
+    Read {
+        /// Show the whole board instead of only new entries.
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Share a finding with the other agents.
+    Post {
+        #[arg(value_name = "TEXT", required = true, num_args = 1..)]
+        text: Vec<String>,
+    },
+
+    /// Claim files before editing them. Fails if another agent holds one.
+    Claim {
+        #[arg(value_name = "FILE", required = true, num_args = 1..)]
+        files: Vec<String>,
+    },
+
+    /// Release claimed files, or every claim this agent holds.
+    Release {
+        #[arg(value_name = "FILE")]
+        files: Vec<String>,
+    },
+
+    /// List files currently claimed and who holds them.
+    Claims,
+}
+
+pub fn run_agent_board(cli: AgentBoardCli) -> anyhow::Result<()> {
+    let board = std::env::var_os(agent_board::BOARD_ENV_VAR)
+        .map(PathBuf::from)
+        .with_context(|| {
+            format!(
+                "{} is not set; agent-board only works inside an agent launched by the agent tool",
+                agent_board::BOARD_ENV_VAR
+            )
+        })?;
+    let agent_id = std::env::var(agent_board::AGENT_ID_ENV_VAR).ok();
+    let require_agent = || -> anyhow::Result<String> {
+        match agent_id.clone() {
+            Some(id) if !id.is_empty() => Ok(id),
+            _ => bail!("{} is not set", agent_board::AGENT_ID_ENV_VAR),
+        }
+    };
+
+    match cli.cmd {
+        AgentBoardSubcommand::Read { all } => {
+            let entries = if all {
+                agent_board::read_entries(&board)
+            } else {
+                agent_board::read_unseen(&board, &require_agent()?)
+            }
+            .map_err(anyhow::Error::msg)?;
+            if entries.is_empty() {
+                println!("No new entries.");
+            }
+            for entry in entries {
+                println!("{}", entry.display_line());
+            }
+        }
+        AgentBoardSubcommand::Post { text } => {
+            let entry = BoardEntry::new(require_agent()?, BoardEntryKind::Note, text.join(" "));
+            agent_board::post(&board, &entry).map_err(anyhow::Error::msg)?;
+            println!("Posted.");
+        }
+        AgentBoardSubcommand::Claim { files } => {
+            let files = normalize(&files);
+            agent_board::claim(&board, &require_agent()?, &files).map_err(anyhow::Error::msg)?;
+            println!("Claimed {}.", files.join(", "));
+        }
+        AgentBoardSubcommand::Release { files } => {
+            let files = normalize(&files);
+            let released = agent_board::release(&board, &require_agent()?, &files)
+                .map_err(anyhow::Error::msg)?;
+            if released.is_empty() {
+                println!("Nothing to release.");
+            } else {
+                println!("Released {}.", released.join(", "));
+            }
+        }
+        AgentBoardSubcommand::Claims => {
+            let entries = agent_board::read_entries(&board).map_err(anyhow::Error::msg)?;
+            let claims = agent_board::active_claims(&entries);
+            if claims.is_empty() {
+                println!("No files are claimed.");
+            }
+            for (file, holder) in claims {
+                println!("{file}\t{holder}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Claims are compared across worktrees, so make paths relative to the
+/// checkout root.
+fn normalize(files: &[String]) -> Vec<String> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let root = std::process::Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+        .unwrap_or_else(|| cwd.clone());
+    files
+        .iter()
+        .map(|file| {
+            let path = PathBuf::from(file);
+            let absolute = if path.is_absolute() {
+                path
+            } else {
+                cwd.join(path)
+            };
+            agent_board::normalize_claim_path(&absolute.to_string_lossy(), &root)
+        })
+        .collect()
+}
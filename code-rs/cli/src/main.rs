@@ -18,6 +18,12 @@ use code_cli::login::run_logout;
 mod bridge;
 mod llm;
 use llm::{LlmCli, run_llm};
+mod usage_cmd;
+use usage_cmd::{UsageCli, run_usage};
+mod worktrees_cmd;
+use worktrees_cmd::{WorktreesCli, run_worktrees};
+mod agent_board_cmd;
+use agent_board_cmd::{AgentBoardCli, run_agent_board};
 use code_common::CliConfigOverrides;
 use code_core::{entry_to_rollout_path, SessionCatalog, SessionQuery};
 use code_core::spawn::spawn_std_command_with_retry;
@@ -159,6 +165,16 @@ enum Subcommand {
     /// Side-channel LLM utilities (no TUI events).
     Llm(LlmCli),
 
+    /// Token usage history reports.
+    Usage(UsageCli),
+
+    /// List, open, diff and prune worktrees created by /branch and agents.
+    Worktrees(WorktreesCli),
+
+    /// Internal: shared board used by concurrent agents to post findings and claim files.
+    #[clap(hide = true)]
+    AgentBoard(AgentBoardCli),
+
     /// Manage Code Bridge subscription for this workspace.
     Bridge(BridgeCommand),
 }
@@ -441,23 +457,7 @@ async fn cli_main(code_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()>
                 &mut interactive.config_overrides,
                 root_config_overrides.clone(),
             );
-            let ExitSummary {
-                token_usage,
-                session_id,
-            } = code_tui::run_main(interactive, code_linux_sandbox_exe).await?;
-            if !token_usage.is_zero() {
-                println!(
-                    "{}",
-                    code_core::protocol::FinalOutput::from(token_usage.clone())
-                );
-            }
-            if let Some(session_id) = session_id {
-                println!(
-                    "To continue this session, run {} resume {}",
-                    resume_command_name(),
-                    session_id
-                );
-            }
+            run_interactive(interactive, code_linux_sandbox_exe).await?;
         }
         Some(Subcommand::Exec(mut exec_cli)) => {
             if auto_drive {
@@ -506,23 +506,7 @@ async fn cli_main(code_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()>
                 last,
                 config_overrides,
             );
-            let ExitSummary {
-                token_usage,
-                session_id,
-            } = code_tui::run_main(interactive, code_linux_sandbox_exe).await?;
-            if !token_usage.is_zero() {
-                println!(
-                    "{}",
-                    code_core::protocol::FinalOutput::from(token_usage.clone())
-                );
-            }
-            if let Some(session_id) = session_id {
-                println!(
-                    "To continue this session, run {} resume {}",
-                    resume_command_name(),
-                    session_id
-                );
-            }
+            run_interactive(interactive, code_linux_sandbox_exe).await?;
         }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(
@@ -622,6 +606,19 @@ async fn cli_main(code_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()>
         Some(Subcommand::Bridge(bridge_cli)) => {
             run_bridge_command(bridge_cli).await?;
         }
+        Some(Subcommand::Usage(usage_cli)) => {
+            run_usage(usage_cli)?;
+        }
+        Some(Subcommand::Worktrees(worktrees_cli)) => {
+            if let Some(worktree) = run_worktrees(worktrees_cli).await? {
+                interactive.cwd = Some(worktree);
+                prepend_config_flags(&mut interactive.config_overrides, root_config_overrides);
+                run_interactive(interactive, code_linux_sandbox_exe).await?;
+            }
+        }
+        Some(Subcommand::AgentBoard(board_cli)) => {
+            run_agent_board(board_cli)?;
+        }
         Some(Subcommand::Llm(mut llm_cli)) => {
             prepend_config_flags(
                 &mut llm_cli.config_overrides,
@@ -973,6 +970,31 @@ fn find_code_dir(start: &Path) -> Option<PathBuf> {
     None
 }
 
+/// Run the interactive TUI and print the exit summary.
+async fn run_interactive(
+    interactive: TuiCli,
+    code_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let ExitSummary {
+        token_usage,
+        session_id,
+    } = code_tui::run_main(interactive, code_linux_sandbox_exe).await?;
+    if !token_usage.is_zero() {
+        println!(
+            "{}",
+            code_core::protocol::FinalOutput::from(token_usage.clone())
+        );
+    }
+    if let Some(session_id) = session_id {
+        println!(
+            "To continue this session, run {} resume {}",
+            resume_command_name(),
+            session_id
+        );
+    }
+    Ok(())
+}
+
 /// Build the final `TuiCli` for a `codex resume` invocation.
 fn finalize_resume_interactive(
     mut interactive: TuiCli,
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use clap::{Parser, ValueEnum};
+use code_core::auth_accounts;
+use code_core::usage_report::{UsageReport, UsageReportDimension, UsageReportPeriod};
+
+#[derive(Debug, Parser)]
+pub struct UsageCli {
+    #[command(subcommand)]
+    pub cmd: UsageSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum UsageSubcommand {
+    /// Summarize recorded token usage by account, model, project or source.
+    Report(ReportArgs),
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PeriodArg {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GroupByArg {
+    Account,
+    Model,
+    Project,
+    Source,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FormatArg {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Parser)]
+pub struct ReportArgs {
+    /// Bucket size for the breakdown.
+    #[arg(long, value_enum, default_value_t = PeriodArg::Daily)]
+    pub period: PeriodArg,
+
+    /// Dimension to group usage by.
+    #[arg(long = "by", value_enum, default_value_t = GroupByArg::Account)]
+    pub group_by: GroupByArg,
+
+    /// First day to include (YYYY-MM-DD). Defaults to 14 days, 8 weeks or
+    /// 6 months back depending on --period.
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+
+    /// Last day to include (YYYY-MM-DD). Defaults to today.
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = FormatArg::Table)]
+    pub format: FormatArg,
+}
+
+pub fn run_usage(cli: UsageCli) -> anyhow::Result<()> {
+    match cli.cmd {
+        UsageSubcommand::Report(args) => run_usage_report(args),
+    }
+}
+
+fn run_usage_report(args: ReportArgs) -> anyhow::Result<()> {
+    let code_home = code_core::config::find_code_home().context("failed to resolve code home")?;
+    let period = match args.period {
+        PeriodArg::Daily => UsageReportPeriod::Daily,
+        PeriodArg::Weekly => UsageReportPeriod::Weekly,
+        PeriodArg::Monthly => UsageReportPeriod::Monthly,
+    };
+    let group_by = match args.group_by {
+        GroupByArg::Account => UsageReportDimension::Account,
+        GroupByArg::Model => UsageReportDimension::Model,
+        GroupByArg::Project => UsageReportDimension::Project,
+        GroupByArg::Source => UsageReportDimension::Source,
+    };
+
+    let until = match args.until {
+        Some(day) => start_of_day(day.succ_opt().unwrap_or(day)),
+        None => Utc::now(),
+    };
+    let since = match args.since {
+        Some(day) => start_of_day(day),
+        None => until - period.default_lookback(),
+    };
+    if since >= until {
+        anyhow::bail!("--since must be before --until");
+    }
+
+    let mut report = UsageReport::load(&code_home, period, group_by, since, until)
+        .context("failed to read usage ledger")?;
+    if matches!(group_by, UsageReportDimension::Account) {
+        let labels: HashMap<String, String> = auth_accounts::list_accounts(&code_home)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|account| {
+                let label = account.label.clone().or_else(|| {
+                    account
+                        .tokens
+                        .as_ref()
+                        .and_then(|tokens| tokens.id_token.email.clone())
+                })?;
+                Some((account.id, label))
+            })
+            .collect();
+        report.relabel_keys(|id| labels.get(id).cloned());
+    }
+
+    match args.format {
+        FormatArg::Csv => print!("{}", report.to_csv()),
+        FormatArg::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        FormatArg::Table => print_table(&report),
+    }
+    Ok(())
+}
+
+fn start_of_day(day: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+fn print_table(report: &UsageReport) {
+    if report.rows.is_empty() {
+        println!(
+            "No usage recorded between {} and {}.",
+            report.since.with_timezone(&Local).format("%Y-%m-%d"),
+            report.until.with_timezone(&Local).format("%Y-%m-%d")
+        );
+        return;
+    }
+
+    let key_width = report
+        .rows
+        .iter()
+        .map(|row| row.key.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp(5, 48);
+    println!(
+        "{:<10}  {:<key_width$}  {:>8}  {:>14}  {:>14}  {:>14}",
+        "period", "key", "requests", "input", "cached", "output"
+    );
+    for row in &report.rows {
+        println!(
+            "{:<10}  {:<key_width$}  {:>8}  {:>14}  {:>14}  {:>14}",
+            row.period_start.to_string(),
+            truncate(&row.key, key_width),
+            row.requests,
+            row.tokens.input_tokens,
+            row.tokens.cached_input_tokens,
+            row.tokens.output_tokens,
+        );
+    }
+
+    println!();
+    println!("Totals");
+    for (key, requests, tokens) in report.totals_by_key() {
+        println!(
+            "  {:<key_width$}  {:>8} requests  {:>14} tokens",
+            truncate(&key, key_width),
+            requests,
+            tokens.total_tokens,
+        );
+    }
+}
+
+fn truncate(value: &str, width: usize) -> String {
+    if value.chars().count() <= width {
+        return value.to_string();
+    }
+    let tail: String = value
+        .chars()
+        .rev()
+        .take(width.saturating_sub(1))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("…{tail}")
+}
@@ -0,0 +1,228 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use chrono::{DateTime, Local};
+use clap::Parser;
+use code_core::worktree_manager::{self, ManagedWorktree, PruneOutcome};
+
+#[derive(Debug, Parser)]
+pub struct WorktreesCli {
+    #[command(subcommand)]
+    pub cmd: WorktreesSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum WorktreesSubcommand {
+    /// List worktrees created by /branch, agents and reviews.
+    List,
+
+    /// Remove worktrees. Skips worktrees in use, with uncommitted changes, or
+    /// with commits not merged into their base unless --force is given.
+    Prune(PruneArgs),
+
+    /// Start an interactive session inside a worktree.
+    Open(WorktreeArg),
+
+    /// Show a worktree's changes relative to where it branched off its base.
+    Diff(DiffArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct PruneArgs {
+    /// Worktree names, branch names or paths to prune.
+    #[arg(value_name = "WORKTREE", required_unless_present = "all")]
+    pub worktrees: Vec<String>,
+
+    /// Consider every worktree.
+    #[arg(long, conflicts_with = "worktrees")]
+    pub all: bool,
+
+    /// Also remove worktrees with uncommitted changes or unmerged commits.
+    /// Worktrees used by a running session are never removed.
+    #[arg(long)]
+    pub force: bool,
+
+    /// Report what would be removed without touching anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct WorktreeArg {
+    /// Worktree name, branch name or path.
+    pub worktree: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct DiffArgs {
+    /// Worktree name, branch name or path.
+    pub worktree: String,
+
+    /// Print a diffstat instead of the full patch.
+    #[arg(long)]
+    pub stat: bool,
+}
+
+/// Run a `worktrees` subcommand. Returns the directory to start an
+/// interactive session in for `open`.
+pub async fn run_worktrees(cli: WorktreesCli) -> anyhow::Result<Option<PathBuf>> {
+    match cli.cmd {
+        WorktreesSubcommand::List => {
+            print_worktrees(&worktree_manager::list_worktrees().await);
+            Ok(None)
+        }
+        WorktreesSubcommand::Prune(args) => {
+            run_prune(args).await?;
+            Ok(None)
+        }
+        WorktreesSubcommand::Open(arg) => {
+            let worktree = find(&arg.worktree).await?;
+            Ok(Some(worktree.path))
+        }
+        WorktreesSubcommand::Diff(args) => {
+            let worktree = find(&args.worktree).await?;
+            let diff = worktree_manager::worktree_diff(&worktree, args.stat)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            if diff.is_empty() {
+                println!("No changes in {}.", worktree.name);
+            } else {
+                print!("{diff}");
+            }
+            Ok(None)
+        }
+    }
+}
+
+async fn find(query: &str) -> anyhow::Result<ManagedWorktree> {
+    worktree_manager::find_worktree(query)
+        .await
+        .map_err(anyhow::Error::msg)
+}
+
+fn print_worktrees(worktrees: &[ManagedWorktree]) {
+    if worktrees.is_empty() {
+        println!("No worktrees found.");
+        return;
+    }
+    let rows: Vec<[String; 7]> = worktrees.iter().map(row).collect();
+    let headers = [
+        "REPO",
+        "WORKTREE",
+        "OWNER",
+        "BASE",
+        "AHEAD/BEHIND",
+        "SIZE",
+        "MODIFIED",
+    ];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let print_row = |cells: &[String]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(&headers.map(str::to_string));
+    for row in &rows {
+        print_row(row);
+    }
+}
+
+fn row(worktree: &ManagedWorktree) -> [String; 7] {
+    let mut name = worktree.name.clone();
+    if worktree.dirty {
+        name.push_str(" *");
+    }
+    let counts = match (worktree.ahead, worktree.behind) {
+        (Some(ahead), Some(behind)) => format!("+{ahead} -{behind}"),
+        _ => "-".to_string(),
+    };
+    let modified = worktree
+        .modified
+        .map(|time| {
+            DateTime::<Local>::from(time)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "-".to_string());
+    [
+        worktree.repo_name.clone(),
+        name,
+        worktree.owner_label(),
+        worktree.base.clone().unwrap_or_else(|| "-".to_string()),
+        counts,
+        worktree_manager::format_disk_usage(worktree.disk_bytes),
+        modified,
+    ]
+}
+
+async fn run_prune(args: PruneArgs) -> anyhow::Result<()> {
+    let targets = if args.all {
+        worktree_manager::list_worktrees().await
+    } else {
+        let mut targets = Vec::new();
+        for query in &args.worktrees {
+            targets.push(find(query).await?);
+        }
+        targets
+    };
+
+    let mut removed = 0usize;
+    let mut freed = 0u64;
+    let mut skipped = 0usize;
+    let mut failed = 0usize;
+    for worktree in &targets {
+        let label = format!("{}/{}", worktree.repo_name, worktree.name);
+        if args.dry_run {
+            let blockers = worktree.prune_blockers(args.force);
+            if blockers.is_empty() {
+                println!("would remove {label}");
+                removed += 1;
+                freed += worktree.disk_bytes;
+            } else {
+                println!("would skip {label}: {}", blockers.join(", "));
+                skipped += 1;
+            }
+            continue;
+        }
+        match worktree_manager::prune_worktree(worktree, args.force).await {
+            PruneOutcome::Removed { bytes } => {
+                println!("removed {label}");
+                removed += 1;
+                freed += bytes;
+            }
+            PruneOutcome::Skipped { reasons } => {
+                println!("skipped {label}: {}", reasons.join(", "));
+                skipped += 1;
+            }
+            PruneOutcome::Failed(err) => {
+                eprintln!("failed to remove {label}: {err}");
+                failed += 1;
+            }
+        }
+    }
+
+    let verb = if args.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    println!(
+        "{verb} {removed} worktree(s), freeing {}; skipped {skipped}.",
+        worktree_manager::format_disk_usage(freed)
+    );
+    if skipped > 0 && !args.force {
+        println!("Pass --force to also remove worktrees with unmerged work.");
+    }
+    if failed > 0 {
+        bail!("{failed} worktree(s) could not be removed");
+    }
+    Ok(())
+}
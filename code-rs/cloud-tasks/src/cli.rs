@@ -5,6 +5,11 @@ use code_common::CliConfigOverrides;
 pub enum Command {
     /// Submit a new task non-interactively and print the created id
     Submit(SubmitArgs),
+
+    /// Drain the local task queue of the current repository (started
+    /// detached by the local backend).
+    #[command(name = "local-runner", hide = true)]
+    LocalRunner,
 }
 
 #[derive(Parser, Debug, Default, Clone)]
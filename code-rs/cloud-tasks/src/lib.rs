@@ -156,8 +156,16 @@ fn spawn_apply(
 pub async fn run_main(cli: Cli, _code_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     // Non-interactive submit mode: used by the core agent runner to create a
     // cloud task and return its id on stdout.
-    if let Some(crate::cli::Command::Submit(args)) = cli.cmd {
-        return run_submit(args).await;
+    match cli.cmd {
+        Some(crate::cli::Command::Submit(args)) => return run_submit(args).await,
+        Some(crate::cli::Command::LocalRunner) => {
+            return local_backend()
+                .await?
+                .run_queue()
+                .await
+                .map_err(|e| anyhow::anyhow!("local cloud runner failed: {e}"));
+        }
+        None => {}
     }
     // Very minimal logging setup; mirrors other crates' pattern.
     let default_level = "error";
@@ -174,14 +182,18 @@ pub async fn run_main(cli: Cli, _code_linux_sandbox_exe: Option<PathBuf>) -> any
     info!("Launching Cloud Tasks list UI");
     set_user_agent_suffix("code_cloud_tasks_tui");
 
-    // Default to online unless explicitly configured to use mock.
+    // Default to online unless explicitly configured to use mock or local.
     let use_mock = matches!(
         std::env::var("CODEX_CLOUD_TASKS_MODE").ok().as_deref(),
         Some("mock") | Some("MOCK")
     );
+    let use_local = local_mode();
 
     let backend: Arc<dyn code_cloud_tasks_client::CloudBackend> = if use_mock {
         Arc::new(code_cloud_tasks_client::MockClient)
+    } else if use_local {
+        append_info_log("startup: local worktree backend");
+        Arc::new(local_backend().await?)
     } else {
         // Build an HTTP client against the configured (or default) base URL.
         let base_url = std::env::var("CODEX_CLOUD_TASKS_BASE_URL")
@@ -323,7 +335,20 @@ pub async fn run_main(cli: Cli, _code_linux_sandbox_exe: Option<PathBuf>) -> any
         });
     }
     // Fetch environment list in parallel so the header can show friendly names quickly.
-    {
+    if use_local {
+        let _ = tx.send(app::AppEvent::EnvironmentsLoaded(Ok(vec![app::EnvironmentRow {
+            id: code_cloud_tasks_client::LOCAL_ENVIRONMENT_ID.to_string(),
+            label: Some(LOCAL_ENVIRONMENT_LABEL.to_string()),
+            is_pinned: true,
+            repo_hints: None,
+        }])));
+        let _ = tx.send(app::AppEvent::EnvironmentAutodetected(Ok(
+            crate::env_detect::AutodetectSelection {
+                id: code_cloud_tasks_client::LOCAL_ENVIRONMENT_ID.to_string(),
+                label: Some(LOCAL_ENVIRONMENT_LABEL.to_string()),
+            },
+        )));
+    } else {
         let tx = tx.clone();
         tokio::spawn(async move {
             let base_url = util::normalize_base_url(
@@ -338,7 +363,7 @@ pub async fn run_main(cli: Cli, _code_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // Try to auto-detect a likely environment id on startup and refresh if found.
     // Do this concurrently so the initial list shows quickly; on success we refetch with filter.
-    {
+    if !use_local {
         let tx = tx.clone();
         tokio::spawn(async move {
             let base_url = util::normalize_base_url(
@@ -1515,6 +1540,23 @@ pub async fn run_main(cli: Cli, _code_linux_sandbox_exe: Option<PathBuf>) -> any
     Ok(())
 }
 
+const LOCAL_ENVIRONMENT_LABEL: &str = "Local worktrees";
+
+/// `CODEX_CLOUD_TASKS_MODE=local` runs tasks in git worktrees on this machine.
+fn local_mode() -> bool {
+    std::env::var("CODEX_CLOUD_TASKS_MODE")
+        .map(|mode| mode.eq_ignore_ascii_case("local"))
+        .unwrap_or(false)
+}
+
+async fn local_backend() -> anyhow::Result<code_cloud_tasks_client::LocalBackend> {
+    let code_home = code_core::config::find_code_home()?;
+    let cwd = std::env::current_dir()?;
+    code_cloud_tasks_client::LocalBackend::discover(&code_home, &cwd)
+        .await
+        .map_err(|e| anyhow::anyhow!("local cloud tasks need a git repository: {e}"))
+}
+
 // Lightweight non-interactive submit implementation. Accepts a prompt and
 // optional env/best-of/qa/git-ref and prints only the created task id.
 async fn run_submit(args: crate::cli::SubmitArgs) -> anyhow::Result<()> {
@@ -1524,9 +1566,12 @@ async fn run_submit(args: crate::cli::SubmitArgs) -> anyhow::Result<()> {
         std::env::var("CODEX_CLOUD_TASKS_MODE").ok().as_deref(),
         Some("mock") | Some("MOCK")
     );
+    let use_local = local_mode();
 
     let backend: Arc<dyn code_cloud_tasks_client::CloudBackend> = if use_mock {
         Arc::new(code_cloud_tasks_client::MockClient)
+    } else if use_local {
+        Arc::new(local_backend().await?)
     } else {
         let base_url = std::env::var("CODEX_CLOUD_TASKS_BASE_URL")
             .unwrap_or_else(|_| "https://chatgpt.com/backend-api".to_string());
@@ -1571,7 +1616,11 @@ async fn run_submit(args: crate::cli::SubmitArgs) -> anyhow::Result<()> {
     };
 
     // Resolve target environment id
-    let env_id = if let Some(e) = args.env.clone() { e } else {
+    let env_id = if let Some(e) = args.env.clone() {
+        e
+    } else if use_local {
+        code_cloud_tasks_client::LOCAL_ENVIRONMENT_ID.to_string()
+    } else {
         let base_url = util::normalize_base_url(
             &std::env::var("CODEX_CLOUD_TASKS_BASE_URL")
                 .unwrap_or_else(|_| "https://chatgpt.com/backend-api".to_string()),
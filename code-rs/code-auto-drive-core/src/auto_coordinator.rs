@@ -6,6 +6,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{anyhow, Context, Result};
+use code_core::account_usage::{UsageSource, record_usage_ledger_entry};
 use code_core::config::Config;
 use code_core::agent_defaults::{
     build_model_guide_description,
@@ -3378,6 +3379,20 @@ fn request_decision_with_model(
                             _ => {}
                         }
                     }
+                    if let Some(entry) = token_usage.as_ref().and_then(|usage| {
+                        client.usage_ledger_entry(
+                            usage,
+                            &model_slug,
+                            Some(UsageSource::AutoDrive),
+                        )
+                    }) {
+                        let code_home = client.code_home().to_path_buf();
+                        tokio::task::spawn_blocking(move || {
+                            if let Err(err) = record_usage_ledger_entry(&code_home, &entry) {
+                                warn!("failed to record Auto Drive usage: {err}");
+                            }
+                        });
+                    }
                     if !reasoning_delta_accumulator.trim().is_empty()
                         && !response_items
                             .iter()
@@ -3698,6 +3713,7 @@ fn classify_model_error_with_auto_switch(
                                 current_account_id.as_str(),
                                 current_auth_mode,
                                 blocked_until,
+                                &client.account_switch_policy(),
                             ) {
                                 Ok(Some(next_account_id)) => {
                                     let next_label = auth_accounts::find_account(
@@ -6,6 +6,7 @@ mod coordinator_router;
 mod coordinator_user_schema;
 mod controller;
 mod retry;
+mod turn_prompt;
 
 #[cfg(feature = "dev-faults")]
 mod faults;
@@ -52,6 +53,7 @@ pub use coordinator_router::{
     CoordinatorContext,
     CoordinatorRouterResponse,
 };
+pub use turn_prompt::build_auto_prompt;
 pub use coordinator_user_schema::{
     parse_user_turn_reply,
     user_turn_schema,
@@ -0,0 +1,76 @@
+//! Prompt text for the CLI turn that carries out an Auto Drive decision.
+
+use crate::AutoTurnAgentsAction;
+use crate::AutoTurnAgentsTiming;
+use crate::AutoTurnCliAction;
+
+/// Combine the coordinator's CLI prompt, its context, and any requested agent
+/// batch into the user message submitted for the next turn.
+pub fn build_auto_prompt(
+    cli_action: &AutoTurnCliAction,
+    agents: &[AutoTurnAgentsAction],
+    agents_timing: Option<AutoTurnAgentsTiming>,
+) -> String {
+    let mut sections: Vec<String> = Vec::new();
+
+    if let Some(ctx) = cli_action
+        .context
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        sections.push(ctx.to_string());
+    }
+
+    let cli_prompt = cli_action.prompt.trim();
+    if !cli_prompt.is_empty() {
+        sections.push(cli_prompt.to_string());
+    }
+
+    if !agents.is_empty() {
+        let mut lines: Vec<String> = Vec::new();
+        lines.push("<agents>".to_string());
+        lines.push("Please use agents to help you complete this task.".to_string());
+
+        for action in agents {
+            let prompt = action.prompt.trim().replace('\n', " ").replace('"', "\\\"");
+            let write_text = if action.write {
+                "write: true"
+            } else {
+                "write: false"
+            };
+
+            lines.push(String::new());
+            lines.push(format!("prompt: \"{prompt}\" ({write_text})"));
+
+            if let Some(ctx) = action
+                .context
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+            {
+                lines.push(format!("context: {}", ctx.replace('\n', " ")));
+            }
+
+            if let Some(models) = action.models.as_ref().filter(|list| !list.is_empty()) {
+                lines.push(format!("models: {}", models.join(", ")));
+            }
+        }
+
+        let timing_line = match agents_timing {
+            Some(AutoTurnAgentsTiming::Parallel) =>
+                "Timing: parallel — continue the CLI prompt while agents run; call agent.wait when ready to merge results.".to_string(),
+            Some(AutoTurnAgentsTiming::Blocking) =>
+                "Timing: blocking — launch agents first, wait with agent.wait, then continue the CLI prompt.".to_string(),
+            None =>
+                "Timing: blocking — wait for agent.wait before continuing the CLI prompt.".to_string(),
+        };
+        lines.push(String::new());
+        lines.push(timing_line);
+        lines.push("</agents>".to_string());
+
+        sections.push(lines.join("\n"));
+    }
+
+    sections.join("\n\n")
+}
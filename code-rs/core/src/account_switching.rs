@@ -1,14 +1,17 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use code_app_server_protocol::AuthMode;
+use wildmatch::WildMatch;
 
 use crate::auth;
 use crate::account_usage;
+use crate::account_usage::{AccountSwitchReason, AccountSwitchRecord};
 use crate::auth_accounts;
-use crate::config_types::AccountSwitchingMode;
+use crate::config::Config;
+use crate::config_types::{AccountBudget, AccountPinRule, AccountSwitchingMode};
 
 #[derive(Debug, Default)]
 pub struct RateLimitSwitchState {
@@ -52,6 +55,225 @@ impl RateLimitSwitchState {
     }
 }
 
+/// The repository a session runs in, as seen by account pin rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectIdentity {
+    pub root: PathBuf,
+    pub remotes: Vec<String>,
+}
+
+impl ProjectIdentity {
+    /// Find the enclosing git checkout of `cwd` and read its remote URLs from
+    /// the git config on disk (no `git` subprocess).
+    pub fn discover(cwd: &Path) -> Option<Self> {
+        let root = cwd.ancestors().find(|dir| dir.join(".git").exists())?;
+        let git_dir = resolve_git_dir(&root.join(".git"))?;
+        let config = std::fs::read_to_string(git_dir.join("config")).unwrap_or_default();
+        Some(Self {
+            root: root.to_path_buf(),
+            remotes: parse_remote_urls(&config),
+        })
+    }
+}
+
+/// `.git` is a directory for normal checkouts and a `gitdir:` file for
+/// worktrees; worktrees share the main repository's config via `commondir`.
+fn resolve_git_dir(dot_git: &Path) -> Option<PathBuf> {
+    if dot_git.is_dir() {
+        return Some(dot_git.to_path_buf());
+    }
+    let contents = std::fs::read_to_string(dot_git).ok()?;
+    let gitdir = contents.trim().strip_prefix("gitdir:")?.trim();
+    let gitdir = dot_git.parent()?.join(gitdir);
+    match std::fs::read_to_string(gitdir.join("commondir")) {
+        Ok(common) => Some(gitdir.join(common.trim())),
+        Err(_) => Some(gitdir),
+    }
+}
+
+fn parse_remote_urls(config: &str) -> Vec<String> {
+    let mut in_remote = false;
+    let mut urls = Vec::new();
+    for line in config.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_remote = line.starts_with("[remote ");
+            continue;
+        }
+        if !in_remote {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=')
+            && key.trim() == "url"
+        {
+            urls.push(value.trim().to_string());
+        }
+    }
+    urls
+}
+
+fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ => pattern.to_string(),
+    }
+}
+
+fn path_matches(pattern: &str, root: &Path) -> bool {
+    let pattern = expand_home(pattern);
+    if pattern.contains(['*', '?']) {
+        WildMatch::new(&pattern).matches(&root.to_string_lossy())
+    } else {
+        root.starts_with(Path::new(&pattern))
+    }
+}
+
+impl AccountPinRule {
+    pub fn matches(&self, project: &ProjectIdentity) -> bool {
+        if self.path.is_none() && self.remote.is_none() {
+            return false;
+        }
+        let path_ok = self
+            .path
+            .as_deref()
+            .is_none_or(|pattern| path_matches(pattern, &project.root));
+        let remote_ok = self.remote.as_deref().is_none_or(|pattern| {
+            let matcher = WildMatch::new(pattern);
+            project.remotes.iter().any(|url| matcher.matches(url))
+        });
+        path_ok && remote_ok
+    }
+
+    /// Short human description, e.g. `remote *github.com:acme/*`.
+    pub fn describe(&self) -> String {
+        match (self.path.as_deref(), self.remote.as_deref()) {
+            (Some(path), Some(remote)) => format!("path {path}, remote {remote}"),
+            (Some(path), None) => format!("path {path}"),
+            (None, Some(remote)) => format!("remote {remote}"),
+            (None, None) => "empty rule".to_string(),
+        }
+    }
+}
+
+/// The first pin rule that matches `project`.
+pub fn matching_pin<'a>(
+    pins: &'a [AccountPinRule],
+    project: &ProjectIdentity,
+) -> Option<&'a AccountPinRule> {
+    pins.iter().find(|rule| rule.matches(project))
+}
+
+/// Whether a pin rule's `account` reference names `account` (by id, label,
+/// or ChatGPT email).
+pub fn pin_refers_to(reference: &str, account: &auth_accounts::StoredAccount) -> bool {
+    let reference = reference.trim();
+    account.id == reference
+        || account
+            .label
+            .as_deref()
+            .is_some_and(|label| label.eq_ignore_ascii_case(reference))
+        || account
+            .tokens
+            .as_ref()
+            .and_then(|tokens| tokens.id_token.email.as_deref())
+            .is_some_and(|email| email.eq_ignore_ascii_case(reference))
+}
+
+/// Project pins and account budgets layered on top of the switching mode.
+#[derive(Debug, Clone, Default)]
+pub struct AccountSwitchPolicy {
+    pin: Option<AccountPinRule>,
+    budgets: BTreeMap<String, AccountBudget>,
+}
+
+impl AccountSwitchPolicy {
+    pub fn new(
+        pins: &[AccountPinRule],
+        budgets: BTreeMap<String, AccountBudget>,
+        project: Option<&ProjectIdentity>,
+    ) -> Self {
+        Self {
+            pin: project.and_then(|project| matching_pin(pins, project).cloned()),
+            budgets,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        let project = ProjectIdentity::discover(&config.cwd);
+        Self::new(
+            &config.account_pins,
+            config.account_budgets.clone(),
+            project.as_ref(),
+        )
+    }
+
+    /// True when pins or budgets can change the selection, which makes the
+    /// pre-request check worthwhile even in on-limit mode.
+    pub fn is_active(&self) -> bool {
+        self.pin.is_some() || !self.budgets.is_empty()
+    }
+
+    pub fn pin(&self) -> Option<&AccountPinRule> {
+        self.pin.as_ref()
+    }
+
+    fn pinned_account<'a>(
+        &self,
+        accounts: &'a [auth_accounts::StoredAccount],
+    ) -> Option<&'a auth_accounts::StoredAccount> {
+        let pin = self.pin.as_ref()?;
+        accounts
+            .iter()
+            .filter(|acc| account_has_credentials(acc))
+            .find(|acc| pin_refers_to(&pin.account, acc))
+    }
+
+    fn is_strict(&self) -> bool {
+        self.pin.as_ref().is_some_and(|pin| pin.strict)
+    }
+
+    fn over_budget(
+        &self,
+        account_id: &str,
+        snapshot: Option<&account_usage::StoredRateLimitSnapshot>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let (Some(budget), Some(snapshot)) = (self.budgets.get(account_id), snapshot) else {
+            return false;
+        };
+        usage_over_budget(budget, snapshot, now)
+    }
+}
+
+/// A pick made by [`select_preferred_account_id`] and why it was made.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AccountSelection {
+    pub account_id: String,
+    pub reason: AccountSwitchReason,
+    pub detail: Option<String>,
+}
+
+/// Append an automatic switch to the usage switch log; failures are only logged.
+pub(crate) fn record_switch(
+    code_home: &Path,
+    from_account_id: Option<&str>,
+    to_account_id: &str,
+    reason: AccountSwitchReason,
+    detail: Option<String>,
+    now: DateTime<Utc>,
+) {
+    let record = AccountSwitchRecord {
+        switched_at: now,
+        from_account_id: from_account_id.map(str::to_string),
+        to_account_id: to_account_id.to_string(),
+        reason,
+        detail,
+    };
+    if let Err(err) = account_usage::record_account_switch(code_home, &record) {
+        tracing::warn!("failed to record account switch: {err}");
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct CandidateScore {
     used_percent: f64,
@@ -96,6 +318,30 @@ fn usage_is_exhausted(snapshot: &account_usage::StoredRateLimitSnapshot) -> bool
     snapshot.primary_used_percent >= 100.0 || snapshot.secondary_used_percent >= 100.0
 }
 
+/// A window's usage only counts against its budget until that window resets.
+fn usage_over_budget(
+    budget: &AccountBudget,
+    snapshot: &account_usage::StoredRateLimitSnapshot,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some(usage) = snapshot.snapshot.as_ref() else {
+        return false;
+    };
+    let window_over = |cap: Option<f64>, used: f64, reset_at: Option<DateTime<Utc>>| {
+        cap.is_some_and(|cap| used.is_finite() && used >= cap)
+            && !reset_at.is_some_and(|reset_at| reset_at <= now)
+    };
+    window_over(
+        budget.primary_percent,
+        usage.primary_used_percent,
+        snapshot.primary_next_reset_at,
+    ) || window_over(
+        budget.secondary_percent,
+        usage.secondary_used_percent,
+        snapshot.secondary_next_reset_at,
+    )
+}
+
 fn usage_is_auth_invalid(snapshot: &account_usage::StoredRateLimitSnapshot) -> bool {
     snapshot.auth_invalid_at.is_some()
 }
@@ -104,6 +350,30 @@ fn is_blocked(now: DateTime<Utc>, blocked_until: Option<DateTime<Utc>>) -> bool
     blocked_until.is_some_and(|until| until > now)
 }
 
+/// Whether a ChatGPT account can take over right now: not already tried in
+/// this request, not blocked until a reset, not exhausted, not rejected, and
+/// not over its budget.
+fn chatgpt_account_available(
+    account: &auth_accounts::StoredAccount,
+    state: &RateLimitSwitchState,
+    snapshot: Option<&account_usage::StoredRateLimitSnapshot>,
+    policy: &AccountSwitchPolicy,
+    now: DateTime<Utc>,
+) -> bool {
+    if state.has_tried(&account.id) {
+        return false;
+    }
+    let blocked_until = state
+        .blocked_until(&account.id)
+        .into_iter()
+        .chain(snapshot.and_then(usage_reset_blocked_until))
+        .max();
+    !is_blocked(now, blocked_until)
+        && !snapshot.is_some_and(usage_is_exhausted)
+        && !snapshot.is_some_and(usage_is_auth_invalid)
+        && !policy.over_budget(&account.id, snapshot, now)
+}
+
 pub(crate) fn select_next_account_id(
     code_home: &Path,
     state: &RateLimitSwitchState,
@@ -111,6 +381,7 @@ pub(crate) fn select_next_account_id(
     mode: AccountSwitchingMode,
     now: DateTime<Utc>,
     current_account_id: Option<&str>,
+    policy: &AccountSwitchPolicy,
 ) -> io::Result<Option<String>> {
     if mode == AccountSwitchingMode::Manual {
         return Ok(None);
@@ -128,6 +399,25 @@ pub(crate) fn select_next_account_id(
         .map(|snap| (snap.account_id.clone(), snap))
         .collect();
 
+    let current = current.as_deref();
+
+    // A matching project pin gets the first chance to take over; a strict pin
+    // never hands the project to any other account.
+    if let Some(pinned) = policy.pinned_account(&accounts) {
+        let is_current = current.is_some_and(|id| id == pinned.id);
+        let available = if pinned.mode.is_chatgpt() {
+            chatgpt_account_available(pinned, state, snapshot_map.get(&pinned.id), policy, now)
+        } else {
+            !state.has_tried(&pinned.id)
+        };
+        if !is_current && available {
+            return Ok(Some(pinned.id.clone()));
+        }
+        if policy.is_strict() {
+            return Ok(None);
+        }
+    }
+
     let mut chatgpt_accounts: Vec<&auth_accounts::StoredAccount> = accounts
         .iter()
         .filter(|acc| acc.mode.is_chatgpt())
@@ -143,38 +433,13 @@ pub(crate) fn select_next_account_id(
     chatgpt_accounts.sort_by(|a, b| a.id.cmp(&b.id));
     api_key_accounts.sort_by(|a, b| a.id.cmp(&b.id));
 
-    let current = current.as_deref();
-
     let mut best_chatgpt: Option<(&auth_accounts::StoredAccount, CandidateScore)> = None;
     let mut best_reset_secs: Option<i64> = None;
     for account in &chatgpt_accounts {
         if current.is_some_and(|id| id == account.id) {
             continue;
         }
-        if state.has_tried(&account.id) {
-            continue;
-        }
-
-        let blocked_until = state
-            .blocked_until(&account.id)
-            .into_iter()
-            .chain(snapshot_map.get(&account.id).and_then(usage_reset_blocked_until))
-            .max();
-        if is_blocked(now, blocked_until) {
-            continue;
-        }
-
-        if snapshot_map
-            .get(&account.id)
-            .is_some_and(usage_is_exhausted)
-        {
-            continue;
-        }
-
-        if snapshot_map
-            .get(&account.id)
-            .is_some_and(usage_is_auth_invalid)
-        {
+        if !chatgpt_account_available(account, state, snapshot_map.get(&account.id), policy, now) {
             continue;
         }
 
@@ -230,21 +495,21 @@ pub(crate) fn select_next_account_id(
         return Ok(None);
     }
 
-    // Only allow API key fallback when every ChatGPT account is either blocked
-    // or has already been tried and still rate/usage limited.
+    // Only allow API key fallback when every ChatGPT account is either blocked,
+    // over budget, or has already been tried and still rate/usage limited.
     let all_chatgpt_unavailable = chatgpt_accounts.iter().all(|account| {
+        let snapshot = snapshot_map.get(&account.id);
         let blocked_until = state
             .blocked_until(&account.id)
             .into_iter()
-            .chain(snapshot_map.get(&account.id).and_then(usage_reset_blocked_until))
+            .chain(snapshot.and_then(usage_reset_blocked_until))
             .max();
         let blocked = is_blocked(now, blocked_until);
-        let usage_exhausted = snapshot_map
-            .get(&account.id)
-            .is_some_and(usage_is_exhausted);
+        let usage_exhausted = snapshot.is_some_and(usage_is_exhausted);
+        let over_budget = policy.over_budget(&account.id, snapshot, now);
         let exhausted = state.limited_chatgpt_accounts.contains(&account.id);
         let tried = state.has_tried(&account.id);
-        blocked || usage_exhausted || (tried && exhausted)
+        blocked || usage_exhausted || over_budget || (tried && exhausted)
     });
 
     if !chatgpt_accounts.is_empty() && !all_chatgpt_unavailable {
@@ -361,13 +626,32 @@ fn preferred_reset_based_account_id(
         .map(|(id, _, _)| id.clone())
 }
 
+fn switching_mode_name(mode: AccountSwitchingMode) -> &'static str {
+    match mode {
+        AccountSwitchingMode::Manual => "manual",
+        AccountSwitchingMode::OnLimit => "on-limit",
+        AccountSwitchingMode::EvenUsage => "even-usage",
+        AccountSwitchingMode::Step45 => "step-45",
+        AccountSwitchingMode::ResetBased => "reset-based",
+    }
+}
+
+/// Pick the account a request should start on, before any limit is hit.
+///
+/// Precedence: a matching project pin, then moving off an account that is
+/// over its budget, then the switching mode's own preference.
 pub(crate) fn select_preferred_account_id(
     code_home: &Path,
     mode: AccountSwitchingMode,
     allow_api_key_fallback: bool,
     now: DateTime<Utc>,
     current_account_id: Option<&str>,
-) -> io::Result<Option<String>> {
+    policy: &AccountSwitchPolicy,
+) -> io::Result<Option<AccountSelection>> {
+    if mode == AccountSwitchingMode::Manual {
+        return Ok(None);
+    }
+
     let accounts = auth_accounts::list_accounts(code_home)?;
 
     let snapshots = account_usage::list_rate_limit_snapshots(code_home).unwrap_or_default();
@@ -384,18 +668,18 @@ pub(crate) fn select_preferred_account_id(
         }
         match acc.mode {
             AuthMode::ChatGPT | AuthMode::ChatgptAuthTokens => {
-                if snapshot_map.get(&acc.id).is_some_and(usage_is_exhausted) {
+                let snapshot = snapshot_map.get(&acc.id);
+                if snapshot.is_some_and(usage_is_exhausted) {
                     continue;
                 }
-                if snapshot_map.get(&acc.id).is_some_and(usage_is_auth_invalid) {
+                if snapshot.is_some_and(usage_is_auth_invalid) {
                     continue;
                 }
-                let used = snapshot_map
-                    .get(&acc.id)
-                    .and_then(usage_used_percent)
-                    .unwrap_or(0.0);
-                let reset_secs = snapshot_map
-                    .get(&acc.id)
+                if policy.over_budget(&acc.id, snapshot, now) {
+                    continue;
+                }
+                let used = snapshot.and_then(usage_used_percent).unwrap_or(0.0);
+                let reset_secs = snapshot
                     .and_then(|snap| snap.primary_next_reset_at)
                     .map(|reset_at| reset_at.signed_duration_since(now).num_seconds().max(0));
                 chatgpt_candidates.push((acc.id.clone(), used, reset_secs));
@@ -410,6 +694,56 @@ pub(crate) fn select_preferred_account_id(
     api_key_candidates.sort();
 
     let current = current_account_id;
+    let switch_to = |account_id: &str, reason: AccountSwitchReason, detail: Option<String>| {
+        if current.is_some_and(|id| id == account_id) {
+            None
+        } else {
+            Some(AccountSelection {
+                account_id: account_id.to_string(),
+                reason,
+                detail,
+            })
+        }
+    };
+
+    if let Some(pinned) = policy.pinned_account(&accounts) {
+        let available = if pinned.mode.is_chatgpt() {
+            chatgpt_candidates.iter().any(|(id, _, _)| *id == pinned.id)
+        } else {
+            true
+        };
+        if available {
+            let detail = policy.pin().map(AccountPinRule::describe);
+            return Ok(switch_to(&pinned.id, AccountSwitchReason::ProjectPin, detail));
+        }
+        if policy.is_strict() {
+            return Ok(None);
+        }
+    }
+
+    let current_over_budget = current.is_some_and(|id| {
+        accounts
+            .iter()
+            .any(|acc| acc.id == id && acc.mode.is_chatgpt())
+            && policy.over_budget(id, snapshot_map.get(id), now)
+    });
+    if current_over_budget {
+        let pick = chatgpt_candidates
+            .iter()
+            .min_by(|(a_id, a_used, _), (b_id, b_used, _)| {
+                a_used
+                    .partial_cmp(b_used)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a_id.cmp(b_id))
+            })
+            .map(|(id, _, _)| id.clone())
+            .or_else(|| {
+                allow_api_key_fallback
+                    .then(|| api_key_candidates.first().cloned())
+                    .flatten()
+            });
+        return Ok(pick.and_then(|id| switch_to(&id, AccountSwitchReason::BudgetExceeded, None)));
+    }
 
     let pick = match mode {
         AccountSwitchingMode::Manual => None,
@@ -432,23 +766,25 @@ pub(crate) fn select_preferred_account_id(
     };
 
     if let Some(pick) = pick {
-        if current.is_some_and(|id| id == pick) {
-            return Ok(None);
-        }
-        return Ok(Some(pick));
+        let detail = Some(switching_mode_name(mode).to_string());
+        return Ok(switch_to(&pick, AccountSwitchReason::Strategy, detail));
     }
 
-    if !allow_api_key_fallback {
+    if !allow_api_key_fallback || mode == AccountSwitchingMode::OnLimit {
         return Ok(None);
     }
 
     if chatgpt_candidates.is_empty() {
-        return Ok(api_key_candidates.into_iter().next());
+        return Ok(api_key_candidates
+            .first()
+            .and_then(|id| switch_to(id, AccountSwitchReason::Strategy, None)));
     }
 
     Ok(None)
 }
 
+/// Mark the current account limited after a usage-limit response, activate
+/// the next eligible account, and record the switch.
 pub fn switch_active_account_on_rate_limit(
     code_home: &Path,
     state: &mut RateLimitSwitchState,
@@ -458,6 +794,7 @@ pub fn switch_active_account_on_rate_limit(
     current_account_id: &str,
     current_mode: AuthMode,
     blocked_until: Option<DateTime<Utc>>,
+    policy: &AccountSwitchPolicy,
 ) -> io::Result<Option<String>> {
     state.mark_limited(current_account_id, current_mode, blocked_until);
 
@@ -468,10 +805,19 @@ pub fn switch_active_account_on_rate_limit(
         mode,
         now,
         Some(current_account_id),
+        policy,
     )?;
 
     if let Some(next_account_id) = next_account_id.as_deref() {
         auth::activate_account(code_home, next_account_id)?;
+        record_switch(
+            code_home,
+            Some(current_account_id),
+            next_account_id,
+            AccountSwitchReason::UsageLimitReached,
+            None,
+            now,
+        );
     }
 
     Ok(next_account_id)
@@ -572,6 +918,7 @@ mod tests {
             AccountSwitchingMode::OnLimit,
             fixed_now(),
             Some(a.id.as_str()),
+            &AccountSwitchPolicy::default(),
         )
         .expect("select");
         assert_eq!(next.as_deref(), Some(b.id.as_str()));
@@ -612,6 +959,7 @@ mod tests {
                 AccountSwitchingMode::OnLimit,
                 now,
                 Some(a.id.as_str()),
+                &AccountSwitchPolicy::default(),
             )
                 .expect("select");
         assert!(next.is_none());
@@ -655,6 +1003,7 @@ mod tests {
             AccountSwitchingMode::OnLimit,
             now,
             Some(a.id.as_str()),
+            &AccountSwitchPolicy::default(),
         )
             .expect("select");
         assert_eq!(next.as_deref(), Some(b.id.as_str()));
@@ -668,6 +1017,7 @@ mod tests {
             AccountSwitchingMode::OnLimit,
             now,
             Some(b.id.as_str()),
+            &AccountSwitchPolicy::default(),
         )
             .expect("select");
         assert_eq!(next.as_deref(), Some(api.id.as_str()));
@@ -703,6 +1053,7 @@ mod tests {
             AccountSwitchingMode::OnLimit,
             fixed_now(),
             Some(b.id.as_str()),
+            &AccountSwitchPolicy::default(),
         )
         .expect("select");
 
@@ -740,6 +1091,7 @@ mod tests {
             a.id.as_str(),
             AuthMode::ChatGPT,
             None,
+            &AccountSwitchPolicy::default(),
         )
         .expect("switch");
 
@@ -806,6 +1158,7 @@ mod tests {
             AccountSwitchingMode::OnLimit,
             now,
             Some(a.id.as_str()),
+            &AccountSwitchPolicy::default(),
         )
         .expect("select");
 
@@ -858,6 +1211,7 @@ mod tests {
             AccountSwitchingMode::OnLimit,
             now,
             Some(a.id.as_str()),
+            &AccountSwitchPolicy::default(),
         )
         .expect("select");
 
@@ -908,9 +1262,179 @@ mod tests {
             false,
             now,
             None,
+            &AccountSwitchPolicy::default(),
         )
         .expect("preferred selection");
 
-        assert_eq!(pick.as_deref(), Some(b.id.as_str()));
+        assert_eq!(
+            pick.map(|pick| pick.account_id).as_deref(),
+            Some(b.id.as_str())
+        );
+    }
+
+    fn pin(account: &str, path: Option<&str>, remote: Option<&str>, strict: bool) -> AccountPinRule {
+        AccountPinRule {
+            account: account.to_string(),
+            path: path.map(str::to_string),
+            remote: remote.map(str::to_string),
+            strict,
+        }
+    }
+
+    #[test]
+    fn pin_rules_match_paths_and_remotes() {
+        let config = "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = git@github.com:acme/api.git\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n[branch \"main\"]\n\tremote = origin\n";
+        let project = ProjectIdentity {
+            root: PathBuf::from("/work/api"),
+            remotes: parse_remote_urls(config),
+        };
+        assert_eq!(project.remotes, vec!["git@github.com:acme/api.git".to_string()]);
+
+        assert!(pin("a", None, Some("*github.com:acme/*"), false).matches(&project));
+        assert!(pin("a", Some("/work"), None, false).matches(&project));
+        assert!(pin("a", Some("/work/*"), Some("*acme*"), false).matches(&project));
+        assert!(!pin("a", Some("/work/*"), Some("*github.com:oss/*"), false).matches(&project));
+        assert!(!pin("a", Some("/wor"), None, false).matches(&project));
+        assert!(!pin("a", None, None, false).matches(&project));
+    }
+
+    #[test]
+    fn project_pin_selects_pinned_account_and_strict_pin_holds() {
+        let home = tempdir().expect("tmp");
+        let a = auth_accounts::upsert_chatgpt_account(
+            home.path(),
+            chatgpt_tokens("acct-a", "a@example.com"),
+            Utc::now(),
+            None,
+            true,
+        )
+        .expect("insert a");
+        let b = auth_accounts::upsert_chatgpt_account(
+            home.path(),
+            chatgpt_tokens("acct-b", "b@example.com"),
+            Utc::now(),
+            None,
+            false,
+        )
+        .expect("insert b");
+
+        let project = ProjectIdentity {
+            root: PathBuf::from("/work/api"),
+            remotes: Vec::new(),
+        };
+        let policy = AccountSwitchPolicy::new(
+            &[pin("B@example.com", Some("/work"), None, true)],
+            BTreeMap::new(),
+            Some(&project),
+        );
+
+        let pick = select_preferred_account_id(
+            home.path(),
+            AccountSwitchingMode::OnLimit,
+            false,
+            fixed_now(),
+            Some(a.id.as_str()),
+            &policy,
+        )
+        .expect("preferred selection")
+        .expect("pinned account selected");
+        assert_eq!(pick.account_id, b.id);
+        assert_eq!(pick.reason, AccountSwitchReason::ProjectPin);
+        assert_eq!(pick.detail.as_deref(), Some("path /work"));
+
+        // A strict pin never hands the project to another account on limits.
+        let mut state = RateLimitSwitchState::default();
+        state.mark_limited(&b.id, AuthMode::ChatGPT, None);
+        let next = select_next_account_id(
+            home.path(),
+            &state,
+            false,
+            AccountSwitchingMode::OnLimit,
+            fixed_now(),
+            Some(b.id.as_str()),
+            &policy,
+        )
+        .expect("select");
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn budgets_skip_candidates_and_move_off_current_account() {
+        let home = tempdir().expect("tmp");
+        let a = auth_accounts::upsert_chatgpt_account(
+            home.path(),
+            chatgpt_tokens("acct-a", "a@example.com"),
+            Utc::now(),
+            None,
+            true,
+        )
+        .expect("insert a");
+        let b = auth_accounts::upsert_chatgpt_account(
+            home.path(),
+            chatgpt_tokens("acct-b", "b@example.com"),
+            Utc::now(),
+            None,
+            false,
+        )
+        .expect("insert b");
+        let c = auth_accounts::upsert_chatgpt_account(
+            home.path(),
+            chatgpt_tokens("acct-c", "c@example.com"),
+            Utc::now(),
+            None,
+            false,
+        )
+        .expect("insert c");
+
+        let now = fixed_now();
+        for (id, used) in [(&a.id, 85.0), (&b.id, 40.0), (&c.id, 60.0)] {
+            account_usage::record_rate_limit_snapshot(
+                home.path(),
+                id,
+                Some("Pro"),
+                &snapshot(used, 10.0, Some(3_600)),
+                now,
+            )
+            .expect("snapshot");
+        }
+        let capped = AccountBudget {
+            primary_percent: Some(80.0),
+            secondary_percent: None,
+        };
+        let budgets = BTreeMap::from([
+            (a.id.clone(), capped),
+            (b.id.clone(), AccountBudget {
+                primary_percent: Some(30.0),
+                secondary_percent: None,
+            }),
+        ]);
+        let policy = AccountSwitchPolicy::new(&[], budgets, None);
+
+        let pick = select_preferred_account_id(
+            home.path(),
+            AccountSwitchingMode::OnLimit,
+            false,
+            now,
+            Some(a.id.as_str()),
+            &policy,
+        )
+        .expect("preferred selection")
+        .expect("moves off the over-budget account");
+        assert_eq!(pick.account_id, c.id);
+        assert_eq!(pick.reason, AccountSwitchReason::BudgetExceeded);
+
+        let mut state = RateLimitSwitchState::default();
+        state.mark_limited(&c.id, AuthMode::ChatGPT, None);
+        let next = select_next_account_id(
+            home.path(),
+            &state,
+            false,
+            AccountSwitchingMode::OnLimit,
+            now,
+            Some(c.id.as_str()),
+            &policy,
+        )
+        .expect("select");
+        assert!(next.is_none(), "every other account is over its budget");
     }
 }
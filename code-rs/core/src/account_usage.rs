@@ -33,7 +33,7 @@ struct RateLimitWarningRecord {
     logged_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct TokenTotals {
     #[serde(default)]
     pub input_tokens: u64,
@@ -60,7 +60,7 @@ impl TokenTotals {
         self.total_tokens = self.total_tokens.saturating_add(usage.total_tokens);
     }
 
-    fn add_totals(&mut self, other: &TokenTotals) {
+    pub fn add_totals(&mut self, other: &TokenTotals) {
         self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
         self.cached_input_tokens = self
             .cached_input_tokens
@@ -346,6 +346,10 @@ fn warning_log_path(code_home: &Path) -> PathBuf {
     usage_dir(code_home).join("rate_limit_warnings.log")
 }
 
+fn switch_log_path(code_home: &Path) -> PathBuf {
+    usage_dir(code_home).join("account_switches.jsonl")
+}
+
 fn usage_file_path(code_home: &Path, account_id: &str) -> PathBuf {
     usage_dir(code_home).join(format!("{account_id}.json"))
 }
@@ -838,6 +842,241 @@ pub fn load_account_usage(
     }))
 }
 
+/// Why the active account was changed automatically.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountSwitchReason {
+    /// The account returned 429 or another rate-limit response.
+    RateLimited,
+    /// The account reported `usage_limit_reached`.
+    UsageLimitReached,
+    /// The account's credentials were rejected (401).
+    AuthInvalid,
+    /// A project pin rule selected a different account for this repository.
+    ProjectPin,
+    /// The account went over its configured usage budget.
+    BudgetExceeded,
+    /// The switching strategy (even usage, step, reset-based) preferred another account.
+    Strategy,
+}
+
+impl AccountSwitchReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::RateLimited => "rate limited",
+            Self::UsageLimitReached => "usage limit reached",
+            Self::AuthInvalid => "credentials rejected",
+            Self::ProjectPin => "project pin",
+            Self::BudgetExceeded => "over budget",
+            Self::Strategy => "switching strategy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccountSwitchRecord {
+    pub switched_at: DateTime<Utc>,
+    #[serde(default)]
+    pub from_account_id: Option<String>,
+    pub to_account_id: String,
+    pub reason: AccountSwitchReason,
+    /// Extra context such as the matching pin rule or the strategy name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Append an automatic account switch to `usage/account_switches.jsonl`.
+pub fn record_account_switch(
+    code_home: &Path,
+    record: &AccountSwitchRecord,
+) -> std::io::Result<()> {
+    fs::create_dir_all(usage_dir(code_home))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(switch_log_path(code_home))?;
+    file.lock_exclusive()?;
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    let write_res = file.write_all(line.as_bytes());
+    let unlock_res = file.unlock();
+    write_res?;
+    unlock_res?;
+    Ok(())
+}
+
+/// The most recent `limit` recorded switches, oldest first. Unparseable
+/// lines are skipped.
+pub fn list_account_switches(
+    code_home: &Path,
+    limit: usize,
+) -> std::io::Result<Vec<AccountSwitchRecord>> {
+    let contents = match fs::read_to_string(switch_log_path(code_home)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let records: Vec<AccountSwitchRecord> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = records.len().saturating_sub(limit);
+    Ok(records.into_iter().skip(skip).collect())
+}
+
+/// Where a model request originated, for usage reporting.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSource {
+    Tui,
+    Exec,
+    AutoDrive,
+    Agent,
+}
+
+impl UsageSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tui => "TUI",
+            Self::Exec => "exec",
+            Self::AutoDrive => "Auto Drive",
+            Self::Agent => "agents",
+        }
+    }
+
+    /// Classify the current process: agent children carry
+    /// `CODE_OPENAI_SUBAGENT`, and `exec` installs its own originator.
+    pub fn for_current_process(originator: &str) -> Self {
+        let is_agent = std::env::var("CODE_OPENAI_SUBAGENT")
+            .map(|value| !value.trim().is_empty())
+            .unwrap_or(false);
+        if is_agent {
+            Self::Agent
+        } else if originator == "code_exec" {
+            Self::Exec
+        } else {
+            Self::Tui
+        }
+    }
+}
+
+/// One completed model request, as stored in the usage ledger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsageLedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub account_id: String,
+    pub model: String,
+    /// Git root of the session's working directory, when inside a repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub source: UsageSource,
+    pub tokens: TokenTotals,
+}
+
+impl UsageLedgerEntry {
+    pub fn new(
+        account_id: String,
+        model: String,
+        project: Option<String>,
+        source: UsageSource,
+        usage: &TokenUsage,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            timestamp,
+            account_id,
+            model,
+            project,
+            source,
+            tokens: TokenTotals::from_usage(usage),
+        }
+    }
+}
+
+fn ledger_dir(code_home: &Path) -> PathBuf {
+    usage_dir(code_home).join("ledger")
+}
+
+fn ledger_file_path(code_home: &Path, month: NaiveDate) -> PathBuf {
+    ledger_dir(code_home).join(format!("{}.jsonl", month.format("%Y-%m")))
+}
+
+/// Append a request to the monthly ledger file `usage/ledger/YYYY-MM.jsonl`.
+/// Ledger files older than the hourly history window are removed when a
+/// new month starts.
+pub fn record_usage_ledger_entry(
+    code_home: &Path,
+    entry: &UsageLedgerEntry,
+) -> std::io::Result<()> {
+    let dir = ledger_dir(code_home);
+    fs::create_dir_all(&dir)?;
+    let month = truncate_to_month(entry.timestamp).date_naive();
+    let path = ledger_file_path(code_home, month);
+    let is_new_month = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.lock_exclusive()?;
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let write_res = file.write_all(line.as_bytes());
+    let unlock_res = file.unlock();
+    write_res?;
+    unlock_res?;
+
+    if is_new_month {
+        prune_usage_ledger(code_home, entry.timestamp);
+    }
+    Ok(())
+}
+
+fn prune_usage_ledger(code_home: &Path, now: DateTime<Utc>) {
+    let cutoff = truncate_to_month(now - Duration::days(HOURLY_HISTORY_DAYS)).date_naive();
+    let Ok(entries) = fs::read_dir(ledger_dir(code_home)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(stem) = name.to_str().and_then(|name| name.strip_suffix(".jsonl")) else {
+            continue;
+        };
+        let Ok(month) = NaiveDate::parse_from_str(&format!("{stem}-01"), "%Y-%m-%d") else {
+            continue;
+        };
+        if month < cutoff {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Ledger entries with `since <= timestamp < until`, oldest first.
+/// Unparseable lines are skipped.
+pub fn load_usage_ledger(
+    code_home: &Path,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> std::io::Result<Vec<UsageLedgerEntry>> {
+    let mut entries = Vec::new();
+    let mut month = truncate_to_month(since).date_naive();
+    let last_month = truncate_to_month(until).date_naive();
+    while month <= last_month {
+        match fs::read_to_string(ledger_file_path(code_home, month)) {
+            Ok(contents) => entries.extend(
+                contents
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<UsageLedgerEntry>(line).ok())
+                    .filter(|entry| entry.timestamp >= since && entry.timestamp < until),
+            ),
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        month = match month.checked_add_months(chrono::Months::new(1)) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    entries.sort_by_key(|entry| entry.timestamp);
+    Ok(entries)
+}
+
 #[cfg(test)]
 mod tests {
     //! Regression coverage for rate-limit warning relogging.
@@ -1188,4 +1427,66 @@ mod tests {
         assert_eq!(parsed.tokens_last_hour.total_tokens, 210);
         assert_eq!(parsed.hourly_entries.len(), 1);
     }
+
+    #[test]
+    fn account_switches_round_trip_and_keep_latest() {
+        let home = TempDir::new().expect("tempdir");
+        let now = Utc::now();
+        for (idx, reason) in [
+            AccountSwitchReason::RateLimited,
+            AccountSwitchReason::ProjectPin,
+            AccountSwitchReason::BudgetExceeded,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            record_account_switch(
+                home.path(),
+                &AccountSwitchRecord {
+                    switched_at: now + Duration::seconds(idx as i64),
+                    from_account_id: Some(format!("acct-{idx}")),
+                    to_account_id: format!("acct-{}", idx + 1),
+                    reason,
+                    detail: None,
+                },
+            )
+            .expect("record switch");
+        }
+
+        let latest = list_account_switches(home.path(), 2).expect("list switches");
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].reason, AccountSwitchReason::ProjectPin);
+        assert_eq!(latest[1].to_account_id, "acct-3");
+    }
+
+    #[test]
+    fn usage_ledger_spans_months_and_filters_window() {
+        let home = TempDir::new().expect("tempdir");
+        let usage = sample_usage();
+        let timestamps = [
+            Utc.with_ymd_and_hms(2026, 1, 31, 23, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 2, 1, 1, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 2, 20, 1, 0, 0).unwrap(),
+        ];
+        for ts in timestamps {
+            let entry = UsageLedgerEntry::new(
+                "acct".to_string(),
+                "gpt-5".to_string(),
+                Some("/repo".to_string()),
+                UsageSource::Exec,
+                &usage,
+                ts,
+            );
+            record_usage_ledger_entry(home.path(), &entry).expect("record ledger entry");
+        }
+
+        assert!(home.path().join("usage/ledger/2026-01.jsonl").exists());
+        assert!(home.path().join("usage/ledger/2026-02.jsonl").exists());
+
+        let loaded = load_usage_ledger(home.path(), timestamps[0], timestamps[2])
+            .expect("load ledger");
+        let loaded_at: Vec<DateTime<Utc>> = loaded.iter().map(|entry| entry.timestamp).collect();
+        assert_eq!(loaded_at, vec![timestamps[0], timestamps[1]]);
+        assert_eq!(loaded[0].tokens.total_tokens, usage.total_tokens);
+    }
 }
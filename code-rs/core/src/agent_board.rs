@@ -0,0 +1,426 @@
+//! Session-scoped scratchpad shared by concurrent agents.
+//!
+//! Agents run as separate processes, so the board is an append-only JSONL
+//! file under `~/.magik/working/_agents/`, one per orchestrating process.
+//! Children find it through [`BOARD_ENV_VAR`] and talk to it with
+//! `magik agent-board`: they post findings, claim files before editing them,
+//! and read notes from the other agents and guidance from the orchestrator.
+//! Claims are advisory; they only prevent collisions between agents that
+//! check them. Writers serialize on a sidecar `.lock` file so a claim's
+//! check and append are atomic across processes. Housekeeping removes boards
+//! whose process has exited.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Utc;
+use fs2::FileExt;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Path of the board file, set in every agent's environment.
+pub const BOARD_ENV_VAR: &str = "CODE_AGENT_BOARD";
+/// Id of the agent the process runs as, set in every agent's environment.
+pub const AGENT_ID_ENV_VAR: &str = "CODE_AGENT_ID";
+/// Author used for entries posted by the orchestrating session.
+pub const ORCHESTRATOR: &str = "orchestrator";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardEntryKind {
+    /// A finding or status update for everyone.
+    Note,
+    /// The author is about to edit `files`.
+    Claim,
+    /// The author is done with `files`.
+    Release,
+    /// Mid-run instructions from the orchestrator.
+    Guidance,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardEntry {
+    /// 1-based position in the board; assigned when read.
+    #[serde(skip)]
+    pub seq: usize,
+    pub at: DateTime<Utc>,
+    pub from: String,
+    /// Recipient agent id; `None` addresses every agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub kind: BoardEntryKind,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+impl BoardEntry {
+    pub fn new(from: impl Into<String>, kind: BoardEntryKind, text: impl Into<String>) -> Self {
+        Self {
+            seq: 0,
+            at: Utc::now(),
+            from: from.into(),
+            to: None,
+            kind,
+            text: text.into(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Whether `agent_id` should see this entry when reading the board.
+    pub fn is_visible_to(&self, agent_id: &str) -> bool {
+        self.from != agent_id && self.to.as_deref().is_none_or(|to| to == agent_id)
+    }
+
+    /// One-line rendering without the author, e.g. `claimed src/lib.rs`.
+    pub fn summary(&self) -> String {
+        match self.kind {
+            BoardEntryKind::Note => format!("note: {}", self.text),
+            BoardEntryKind::Guidance => format!("guidance: {}", self.text),
+            BoardEntryKind::Claim => format!("claimed {}", self.files.join(", ")),
+            BoardEntryKind::Release => format!("released {}", self.files.join(", ")),
+        }
+    }
+
+    pub fn display_line(&self) -> String {
+        let to = self
+            .to
+            .as_deref()
+            .map(|to| format!(" → {to}"))
+            .unwrap_or_default();
+        format!(
+            "#{} {} {}{to}: {}",
+            self.seq,
+            self.at.format("%H:%M:%S"),
+            self.from,
+            self.summary()
+        )
+    }
+}
+
+/// Board file for agents launched by this process.
+pub fn session_board_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".magik")
+        .join("working")
+        .join("_agents")
+        .join(format!("pid-{}.board.jsonl", std::process::id()))
+}
+
+/// Append one entry.
+pub fn post(path: &Path, entry: &BoardEntry) -> Result<(), String> {
+    let _lock = lock_board(path)?;
+    append(path, entry)
+}
+
+/// Hold the board's writer lock until the returned file is dropped.
+fn lock_board(path: &Path) -> Result<File, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    let lock_path = path.with_file_name(name);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("failed to open {}: {e}", lock_path.display()))?;
+    file.lock_exclusive()
+        .map_err(|e| format!("failed to lock {}: {e}", lock_path.display()))?;
+    Ok(file)
+}
+
+/// Each entry is a single `write` on an append-mode file, so readers never
+/// see two entries interleaved.
+fn append(path: &Path, entry: &BoardEntry) -> Result<(), String> {
+    let mut line =
+        serde_json::to_string(entry).map_err(|e| format!("failed to encode entry: {e}"))?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("failed to open {}: {e}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// Every entry on the board, oldest first. A missing board is empty.
+pub fn read_entries(path: &Path) -> Result<Vec<BoardEntry>, String> {
+    let (mut entries, _) = read_entries_from(path, 0)?;
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        entry.seq = idx + 1;
+    }
+    Ok(entries)
+}
+
+/// Complete entries written after byte `offset`, and the offset to resume
+/// from. Used to follow the board without re-reading it. `seq` is not set.
+pub fn read_entries_from(path: &Path, offset: u64) -> Result<(Vec<BoardEntry>, u64), String> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), offset)),
+        Err(e) => return Err(format!("failed to open {}: {e}", path.display())),
+    };
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    // Leave a partially written last line for the next read.
+    let complete = text.rfind('\n').map_or(0, |idx| idx + 1);
+    let entries = text[..complete]
+        .lines()
+        .filter_map(|line| serde_json::from_str::<BoardEntry>(line).ok())
+        .collect();
+    Ok((entries, offset + complete as u64))
+}
+
+/// Files currently claimed, mapped to the agent holding each. A file keeps
+/// its first claimant until that agent releases it.
+pub fn active_claims(entries: &[BoardEntry]) -> BTreeMap<String, String> {
+    let mut claims = BTreeMap::new();
+    for entry in entries {
+        match entry.kind {
+            BoardEntryKind::Claim => {
+                for file in &entry.files {
+                    claims
+                        .entry(file.clone())
+                        .or_insert_with(|| entry.from.clone());
+                }
+            }
+            BoardEntryKind::Release => {
+                for file in &entry.files {
+                    if claims.get(file) == Some(&entry.from) {
+                        claims.remove(file);
+                    }
+                }
+            }
+            BoardEntryKind::Note | BoardEntryKind::Guidance => {}
+        }
+    }
+    claims
+}
+
+/// Claim `files` for `agent_id`. Fails without claiming anything when another
+/// agent holds one of them.
+pub fn claim(path: &Path, agent_id: &str, files: &[String]) -> Result<(), String> {
+    let _lock = lock_board(path)?;
+    let claims = active_claims(&read_entries(path)?);
+    let held: Vec<String> = files
+        .iter()
+        .filter_map(|file| match claims.get(file) {
+            Some(holder) if holder != agent_id => Some(format!("{file} (held by {holder})")),
+            _ => None,
+        })
+        .collect();
+    if !held.is_empty() {
+        return Err(format!("already claimed: {}", held.join(", ")));
+    }
+    let mut entry = BoardEntry::new(agent_id, BoardEntryKind::Claim, "");
+    entry.files = files.to_vec();
+    append(path, &entry)
+}
+
+/// Release `files`, or every claim `agent_id` holds when `files` is empty.
+/// Returns the files released.
+pub fn release(path: &Path, agent_id: &str, files: &[String]) -> Result<Vec<String>, String> {
+    let _lock = lock_board(path)?;
+    let claims = active_claims(&read_entries(path)?);
+    let released: Vec<String> = claims
+        .into_iter()
+        .filter(|(file, holder)| holder == agent_id && (files.is_empty() || files.contains(file)))
+        .map(|(file, _)| file)
+        .collect();
+    if !released.is_empty() {
+        let mut entry = BoardEntry::new(agent_id, BoardEntryKind::Release, "");
+        entry.files = released.clone();
+        append(path, &entry)?;
+    }
+    Ok(released)
+}
+
+/// Entries for `agent_id` that it has not read yet. The read position is
+/// kept next to the board so each call only returns what is new.
+pub fn read_unseen(path: &Path, agent_id: &str) -> Result<Vec<BoardEntry>, String> {
+    let cursor_path = cursor_path(path, agent_id);
+    let last_seen = std::fs::read_to_string(&cursor_path)
+        .ok()
+        .and_then(|text| text.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    let entries = read_entries(path)?;
+    if let Some(last) = entries.last() {
+        let _ = std::fs::write(&cursor_path, last.seq.to_string());
+    }
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.seq > last_seen && entry.is_visible_to(agent_id))
+        .collect())
+}
+
+fn cursor_path(path: &Path, agent_id: &str) -> PathBuf {
+    let safe: String = agent_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{safe}.cursor"));
+    path.with_file_name(name)
+}
+
+/// Normalize a claimed path so agents in different worktrees agree on it:
+/// relative to the checkout root, forward slashes, no leading `./`.
+pub fn normalize_claim_path(raw: &str, checkout_root: &Path) -> String {
+    let path = Path::new(raw.trim());
+    let relative = path.strip_prefix(checkout_root).unwrap_or(path);
+    let text = relative.to_string_lossy().replace('\\', "/");
+    text.trim_start_matches("./").to_string()
+}
+
+/// Instructions appended to an agent's prompt. `exe` is the binary that
+/// provides `agent-board`.
+pub fn agent_instructions(exe: &Path, read_only: bool) -> String {
+    let cmd = format!("\"{}\" agent-board", exe.display());
+    let mut text = format!(
+        "Shared agent board: other agents are working on related tasks at the same time. Coordinate through the board with these shell commands:\n- `{cmd} read` shows new notes from other agents and guidance from the orchestrator. Run it before you start, before each major step, and before you finish. Follow any guidance addressed to you.\n- `{cmd} post \"<text>\"` shares a finding others should know (a root cause, an API decision, a dead end). Keep posts short and useful.\n"
+    );
+    if !read_only {
+        text.push_str(&format!(
+            "- `{cmd} claim <file>...` before editing files. If it reports a file is held by another agent, work on something else or coordinate with a post instead of editing it.\n- `{cmd} release <file>...` when you are done with a file. Claims are released automatically when you exit.\n"
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn claims_block_other_agents_until_released() {
+        let dir = TempDir::new().unwrap();
+        let board = dir.path().join("board.jsonl");
+        let files = vec!["src/lib.rs".to_string(), "README.md".to_string()];
+
+        claim(&board, "a1", &files).unwrap();
+        let err = claim(&board, "a2", &["src/lib.rs".to_string()]).unwrap_err();
+        assert_eq!(err, "already claimed: src/lib.rs (held by a1)");
+
+        let released = release(&board, "a1", &[]).unwrap();
+        assert_eq!(
+            released,
+            vec!["README.md".to_string(), "src/lib.rs".to_string()]
+        );
+        claim(&board, "a2", &["src/lib.rs".to_string()]).unwrap();
+        let claims = active_claims(&read_entries(&board).unwrap());
+        assert_eq!(claims.get("src/lib.rs").map(String::as_str), Some("a2"));
+    }
+
+    #[test]
+    fn first_claim_wins_and_concurrent_claims_do_not_both_succeed() {
+        let dir = TempDir::new().unwrap();
+        let board = dir.path().join("board.jsonl");
+        for agent in ["a1", "a2"] {
+            let mut entry = BoardEntry::new(agent, BoardEntryKind::Claim, "");
+            entry.files = vec!["src/lib.rs".to_string()];
+            post(&board, &entry).unwrap();
+        }
+        let claims = active_claims(&read_entries(&board).unwrap());
+        assert_eq!(claims.get("src/lib.rs").map(String::as_str), Some("a1"));
+        // A release by the later claimant does not free the file.
+        release(&board, "a2", &[]).unwrap();
+        let claims = active_claims(&read_entries(&board).unwrap());
+        assert_eq!(claims.get("src/lib.rs").map(String::as_str), Some("a1"));
+
+        let contested = dir.path().join("contested.jsonl");
+        let handles: Vec<_> = (0..8)
+            .map(|idx| {
+                let contested = contested.clone();
+                std::thread::spawn(move || {
+                    claim(
+                        &contested,
+                        &format!("agent-{idx}"),
+                        &["src/main.rs".to_string()],
+                    )
+                    .is_ok()
+                })
+            })
+            .collect();
+        let winners = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|won| *won)
+            .count();
+        assert_eq!(winners, 1);
+    }
+
+    #[test]
+    fn read_unseen_skips_own_and_foreign_guidance() {
+        let dir = TempDir::new().unwrap();
+        let board = dir.path().join("board.jsonl");
+        let post_ok = |entry: BoardEntry| {
+            post(&board, &entry).unwrap();
+        };
+        post_ok(BoardEntry::new("a1", BoardEntryKind::Note, "mine"));
+        post_ok(BoardEntry::new("a2", BoardEntryKind::Note, "theirs"));
+        let mut guidance = BoardEntry::new(ORCHESTRATOR, BoardEntryKind::Guidance, "for a2");
+        guidance.to = Some("a2".to_string());
+        post_ok(guidance);
+
+        let texts = |entries: Vec<BoardEntry>| -> Vec<String> {
+            entries.into_iter().map(|entry| entry.text).collect()
+        };
+        let unseen = read_unseen(&board, "a1").unwrap();
+        assert_eq!(texts(unseen), vec!["theirs".to_string()]);
+        assert!(read_unseen(&board, "a1").unwrap().is_empty());
+
+        post_ok(BoardEntry::new(
+            ORCHESTRATOR,
+            BoardEntryKind::Guidance,
+            "everyone",
+        ));
+        let unseen = read_unseen(&board, "a1").unwrap();
+        assert_eq!(texts(unseen), vec!["everyone".to_string()]);
+    }
+
+    #[test]
+    fn follows_board_by_offset_and_normalizes_paths() {
+        let dir = TempDir::new().unwrap();
+        let board = dir.path().join("board.jsonl");
+        post(&board, &BoardEntry::new("a1", BoardEntryKind::Note, "one")).unwrap();
+        let (first, offset) = read_entries_from(&board, 0).unwrap();
+        assert_eq!(first.len(), 1);
+        post(&board, &BoardEntry::new("a1", BoardEntryKind::Note, "two")).unwrap();
+        let (next, _) = read_entries_from(&board, offset).unwrap();
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].text, "two");
+
+        let root = Path::new("/wt/agent-1");
+        assert_eq!(
+            normalize_claim_path("/wt/agent-1/src/a.rs", root),
+            "src/a.rs"
+        );
+        assert_eq!(normalize_claim_path("./src/a.rs", root), "src/a.rs");
+    }
+}
@@ -0,0 +1,621 @@
+//! Best-of-N scoring for agent worktrees.
+//!
+//! Every candidate worktree from a `/code` batch is scored the same way: its
+//! diff against the commit it branched from, the project's configured
+//! commands run inside the worktree, and the findings the patch harness
+//! reports for the files it touched. [`rank_candidates`] orders the results
+//! so the winner's patch can be applied to the session checkout with
+//! [`apply_candidate`].
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use code_apply_patch::ApplyPatchAction;
+use code_apply_patch::ApplyPatchFileChange;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config_types::GithubConfig;
+use crate::config_types::ValidationConfig;
+use crate::patch_harness::run_patch_harness;
+use crate::project_features::ProjectCommand;
+
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(600);
+const OUTPUT_TAIL_LINES: usize = 20;
+
+/// One agent worktree to score.
+#[derive(Debug, Clone)]
+pub struct CandidateInput {
+    pub agent_id: String,
+    /// Display label, usually the agent name or model.
+    pub label: String,
+    pub worktree: PathBuf,
+    pub branch: Option<String>,
+    /// Branch the worktree was created from; the session's HEAD when unset.
+    pub base: Option<String>,
+}
+
+/// How candidates are scored. Built from the session config.
+#[derive(Debug, Clone)]
+pub struct EvaluationSettings {
+    /// Root of the session checkout; project command paths are rebased from
+    /// here onto each worktree.
+    pub project_root: PathBuf,
+    pub commands: Vec<ProjectCommand>,
+    pub validation: ValidationConfig,
+    pub github: GithubConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResult {
+    pub name: String,
+    pub passed: bool,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// Last lines of combined stdout/stderr.
+    pub output_tail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CandidateScore {
+    pub agent_id: String,
+    pub label: String,
+    pub worktree: PathBuf,
+    pub branch: Option<String>,
+    /// Commit the diff is taken against.
+    pub base_commit: Option<String>,
+    /// Binary-safe patch of the candidate's changes, suitable for `git apply`.
+    pub patch: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub commands: Vec<CommandResult>,
+    pub findings: Vec<String>,
+    /// Harness checks that actually ran.
+    pub checks_ran: Vec<String>,
+    /// Set when the candidate could not be scored at all.
+    pub error: Option<String>,
+    /// 1-based position after [`rank_candidates`].
+    pub rank: usize,
+}
+
+impl CandidateScore {
+    fn new(input: &CandidateInput) -> Self {
+        Self {
+            agent_id: input.agent_id.clone(),
+            label: input.label.clone(),
+            worktree: input.worktree.clone(),
+            branch: input.branch.clone(),
+            base_commit: None,
+            patch: String::new(),
+            files_changed: 0,
+            insertions: 0,
+            deletions: 0,
+            commands: Vec::new(),
+            findings: Vec::new(),
+            checks_ran: Vec::new(),
+            error: None,
+            rank: 0,
+        }
+    }
+
+    pub fn commands_passed(&self) -> usize {
+        self.commands.iter().filter(|result| result.passed).count()
+    }
+
+    pub fn lines_changed(&self) -> usize {
+        self.insertions + self.deletions
+    }
+
+    /// A candidate is only worth applying if it changed something and could
+    /// be scored.
+    pub fn is_viable(&self) -> bool {
+        self.error.is_none() && !self.patch.is_empty()
+    }
+}
+
+/// Score every candidate, one at a time so project commands never compete
+/// for build directories or ports, and return them ranked.
+pub async fn evaluate_candidates<F>(
+    candidates: Vec<CandidateInput>,
+    settings: &EvaluationSettings,
+    on_progress: F,
+) -> Vec<CandidateScore>
+where
+    F: Fn(String),
+{
+    let fallback_base = git(&settings.project_root, &["rev-parse", "HEAD"])
+        .await
+        .ok()
+        .map(|sha| sha.trim().to_string());
+    let total = candidates.len();
+    let mut scores = Vec::with_capacity(total);
+    for (idx, candidate) in candidates.iter().enumerate() {
+        on_progress(format!(
+            "Evaluating {} ({}/{total})…",
+            candidate.label,
+            idx + 1
+        ));
+        let score = evaluate_candidate(candidate, settings, fallback_base.as_deref()).await;
+        scores.push(score);
+    }
+    rank_candidates(&mut scores);
+    scores
+}
+
+async fn evaluate_candidate(
+    input: &CandidateInput,
+    settings: &EvaluationSettings,
+    fallback_base: Option<&str>,
+) -> CandidateScore {
+    let mut score = CandidateScore::new(input);
+    if !input.worktree.is_dir() {
+        score.error = Some("worktree no longer exists".to_string());
+        return score;
+    }
+
+    let base = match resolve_base(&input.worktree, input.base.as_deref(), fallback_base).await {
+        Ok(base) => base,
+        Err(err) => {
+            score.error = Some(err);
+            return score;
+        }
+    };
+    let index = match ScratchIndex::new(&input.worktree).await {
+        Ok(index) => index,
+        Err(err) => {
+            score.error = Some(err);
+            return score;
+        }
+    };
+    let index = Some(index.path.as_path());
+    match git_with_index(&input.worktree, index, &["diff", "--binary", &base]).await {
+        Ok(patch) => score.patch = patch,
+        Err(err) => {
+            score.error = Some(err);
+            return score;
+        }
+    }
+    if let Ok(numstat) = git_with_index(&input.worktree, index, &["diff", "--numstat", &base]).await
+    {
+        let (files, insertions, deletions) = parse_numstat(&numstat);
+        score.files_changed = files;
+        score.insertions = insertions;
+        score.deletions = deletions;
+    }
+    score.base_commit = Some(base.clone());
+    if score.patch.is_empty() {
+        return score;
+    }
+
+    match changed_files(&input.worktree, index, &base).await {
+        Ok(changes) if !changes.is_empty() => {
+            let action = ApplyPatchAction::from_changes(changes, input.worktree.clone());
+            let worktree = input.worktree.clone();
+            let validation = settings.validation.clone();
+            let github = settings.github.clone();
+            let harness = tokio::task::spawn_blocking(move || {
+                run_patch_harness(&action, &worktree, &validation, &github)
+            })
+            .await
+            .ok()
+            .flatten();
+            if let Some((findings, ran)) = harness {
+                score.findings = findings
+                    .into_iter()
+                    .map(|finding| match finding.file {
+                        Some(file) => format!(
+                            "{}: {}: {}",
+                            finding.tool,
+                            file.strip_prefix(&input.worktree)
+                                .unwrap_or(&file)
+                                .display(),
+                            finding.message
+                        ),
+                        None => format!("{}: {}", finding.tool, finding.message),
+                    })
+                    .collect();
+                score.checks_ran = ran;
+            }
+        }
+        Ok(_) => {}
+        Err(err) => score.findings.push(format!("harness: {err}")),
+    }
+
+    for command in &settings.commands {
+        let cwd = rebase_cwd(
+            &command.resolved_cwd(&settings.project_root),
+            &settings.project_root,
+            &input.worktree,
+        );
+        score.commands.push(run_command(command, &cwd).await);
+    }
+    score
+}
+
+/// Merge base between the worktree's HEAD and the branch it was created from.
+async fn resolve_base(
+    worktree: &Path,
+    base: Option<&str>,
+    fallback: Option<&str>,
+) -> Result<String, String> {
+    let base_ref = match base {
+        Some(branch) => format!("refs/heads/{branch}"),
+        None => fallback
+            .map(str::to_string)
+            .ok_or_else(|| "could not determine the base commit".to_string())?,
+    };
+    git(worktree, &["merge-base", "HEAD", &base_ref])
+        .await
+        .map(|sha| sha.trim().to_string())
+}
+
+/// Files touched since `base`, in the shape the patch harness expects.
+async fn changed_files(
+    worktree: &Path,
+    index: Option<&Path>,
+    base: &str,
+) -> Result<HashMap<PathBuf, ApplyPatchFileChange>, String> {
+    let status = git_with_index(
+        worktree,
+        index,
+        &["diff", "--name-status", "--no-renames", "-z", base],
+    )
+    .await?;
+    let mut changes = HashMap::new();
+    let mut fields = status.split('\0').filter(|field| !field.is_empty());
+    while let (Some(code), Some(path)) = (fields.next(), fields.next()) {
+        let full = worktree.join(path);
+        let change = if code.starts_with('D') {
+            let content = git(worktree, &["show", &format!("{base}:{path}")])
+                .await
+                .unwrap_or_default();
+            ApplyPatchFileChange::Delete { content }
+        } else {
+            // Binary files have no text for the harness to check.
+            let Ok(content) = tokio::fs::read_to_string(&full).await else {
+                continue;
+            };
+            if code.starts_with('A') {
+                ApplyPatchFileChange::Add { content }
+            } else {
+                ApplyPatchFileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: None,
+                    new_content: content,
+                }
+            }
+        };
+        changes.insert(full, change);
+    }
+    Ok(changes)
+}
+
+/// Map a command directory inside the session checkout to the same place in
+/// the candidate worktree. Directories outside the checkout are kept.
+fn rebase_cwd(cwd: &Path, project_root: &Path, worktree: &Path) -> PathBuf {
+    match cwd.strip_prefix(project_root) {
+        Ok(rel) => worktree.join(rel),
+        Err(_) => cwd.to_path_buf(),
+    }
+}
+
+async fn run_command(command: &ProjectCommand, cwd: &Path) -> CommandResult {
+    let mut result = CommandResult {
+        name: command.name.clone(),
+        passed: false,
+        timed_out: false,
+        duration_ms: 0,
+        output_tail: String::new(),
+    };
+    let Some((program, args)) = command.command.split_first() else {
+        result.output_tail = "empty command".to_string();
+        return result;
+    };
+    let timeout = command
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_COMMAND_TIMEOUT);
+    let started = Instant::now();
+    let child = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .envs(&command.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(timeout, child).await {
+        Ok(Ok(output)) => {
+            result.passed = output.status.success();
+            let mut combined = String::from_utf8_lossy(&output.stdout).into_owned();
+            combined.push_str(&String::from_utf8_lossy(&output.stderr));
+            result.output_tail = tail_lines(&combined, OUTPUT_TAIL_LINES);
+        }
+        Ok(Err(err)) => result.output_tail = format!("failed to start: {err}"),
+        Err(_) => {
+            result.timed_out = true;
+            result.output_tail = format!("timed out after {}s", timeout.as_secs());
+        }
+    }
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+fn tail_lines(text: &str, max: usize) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(max);
+    lines[start..].join("\n")
+}
+
+/// `(files, insertions, deletions)` from `git diff --numstat`. Binary files
+/// count as changed files with no line counts.
+fn parse_numstat(numstat: &str) -> (usize, usize, usize) {
+    let mut files = 0;
+    let mut insertions = 0;
+    let mut deletions = 0;
+    for line in numstat.lines() {
+        let mut parts = line.split('\t');
+        let (Some(added), Some(removed)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        files += 1;
+        insertions += added.parse::<usize>().unwrap_or(0);
+        deletions += removed.parse::<usize>().unwrap_or(0);
+    }
+    (files, insertions, deletions)
+}
+
+/// Order candidates best first: viable before unusable, then most project
+/// commands passed, fewest harness findings, and the smallest diff.
+pub fn rank_candidates(scores: &mut [CandidateScore]) {
+    scores.sort_by(|a, b| {
+        b.is_viable()
+            .cmp(&a.is_viable())
+            .then_with(|| b.commands_passed().cmp(&a.commands_passed()))
+            .then_with(|| a.findings.len().cmp(&b.findings.len()))
+            .then_with(|| a.lines_changed().cmp(&b.lines_changed()))
+    });
+    for (idx, score) in scores.iter_mut().enumerate() {
+        score.rank = idx + 1;
+    }
+}
+
+/// Apply a candidate's patch to `cwd` with a three-way merge so changes that
+/// overlap local edits become conflicts instead of failures.
+pub async fn apply_candidate(cwd: &Path, score: &CandidateScore) -> Result<(), String> {
+    if score.patch.is_empty() {
+        return Err(format!("{} has no changes to apply", score.label));
+    }
+    let mut child = Command::new("git")
+        .current_dir(cwd)
+        .args(["apply", "--3way", "--whitespace=nowarn", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run git apply: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(score.patch.as_bytes())
+            .await
+            .map_err(|e| format!("failed to write patch: {e}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("failed to run git apply: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git apply failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Hidden instructions for a judge turn comparing the ranked candidates.
+pub fn judge_preface(scores: &[CandidateScore]) -> String {
+    let mut preface = String::from(
+        "[developer] `/evaluate` scored the worktrees from the last agent batch. Act as a judge and pick the best candidate.\n\nAutomatic ranking (commands passed, then harness findings, then diff size):\n",
+    );
+    for score in scores {
+        preface.push_str(&format!(
+            "{}. {} — worktree {}; {} file(s), +{} -{}; commands {}/{} passed; {} finding(s)",
+            score.rank,
+            score.label,
+            score.worktree.display(),
+            score.files_changed,
+            score.insertions,
+            score.deletions,
+            score.commands_passed(),
+            score.commands.len(),
+            score.findings.len(),
+        ));
+        if let Some(err) = &score.error {
+            preface.push_str(&format!("; not scored: {err}"));
+        }
+        preface.push('\n');
+        for command in score.commands.iter().filter(|command| !command.passed) {
+            preface.push_str(&format!("   - `{}` failed\n", command.name));
+        }
+        for finding in score.findings.iter().take(5) {
+            preface.push_str(&format!("   - {finding}\n"));
+        }
+    }
+    preface.push_str(
+        "\nRead each candidate's diff (`git -C <worktree> diff <base>`, with the base from `git -C <worktree> merge-base HEAD <base branch>`) and judge correctness, completeness against the original task, and code quality. The automatic ranking only measures what the commands cover; overrule it when a lower-ranked diff is clearly more correct. Do not modify any worktree or the current checkout.\n\nReply with a short comparison table, then name the winner and why in two or three sentences. The user applies the winner from the /evaluate panel.\n",
+    );
+    preface
+}
+
+/// Private copy of a worktree's index with its untracked files recorded as
+/// intent-to-add, so diffs against it include new files without touching the
+/// index the agent left behind. Deleted on drop.
+struct ScratchIndex {
+    _dir: tempfile::TempDir,
+    path: PathBuf,
+}
+
+impl ScratchIndex {
+    async fn new(worktree: &Path) -> Result<Self, String> {
+        let dir = tempfile::tempdir()
+            .map_err(|e| format!("failed to create a scratch git index: {e}"))?;
+        let path = dir.path().join("index");
+        let source = git(worktree, &["rev-parse", "--git-path", "index"]).await?;
+        let source = worktree.join(source.trim());
+        if source.exists() {
+            tokio::fs::copy(&source, &path)
+                .await
+                .map_err(|e| format!("failed to copy {}: {e}", source.display()))?;
+        }
+        let _ = git_with_index(worktree, Some(&path), &["add", "--intent-to-add", "--all"]).await;
+        Ok(Self { _dir: dir, path })
+    }
+}
+
+async fn git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    git_with_index(cwd, None, args).await
+}
+
+/// Runs git with `GIT_INDEX_FILE` pointed at `index` when one is given.
+async fn git_with_index(cwd: &Path, index: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command.current_dir(cwd).args(args);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("failed to run git {}: {e}", args.first().unwrap_or(&"")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn score(label: &str, passed: usize, findings: usize, lines: usize) -> CandidateScore {
+        let mut score = CandidateScore::new(&CandidateInput {
+            agent_id: label.to_string(),
+            label: label.to_string(),
+            worktree: PathBuf::from(format!("/tmp/{label}")),
+            branch: None,
+            base: None,
+        });
+        score.patch = "diff --git a/x b/x\n".to_string();
+        score.insertions = lines;
+        score.commands = (0..passed)
+            .map(|idx| CommandResult {
+                name: format!("cmd{idx}"),
+                passed: true,
+                timed_out: false,
+                duration_ms: 0,
+                output_tail: String::new(),
+            })
+            .collect();
+        score.findings = vec!["finding".to_string(); findings];
+        score
+    }
+
+    #[test]
+    fn ranks_by_commands_then_findings_then_size() {
+        let mut empty = score("empty", 2, 0, 0);
+        empty.patch.clear();
+        let mut scores = vec![
+            score("big", 2, 0, 500),
+            score("failing", 1, 0, 1),
+            empty,
+            score("noisy", 2, 3, 1),
+            score("small", 2, 0, 10),
+        ];
+        rank_candidates(&mut scores);
+        let order: Vec<&str> = scores.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(order, vec!["small", "big", "noisy", "failing", "empty"]);
+        assert_eq!(scores[0].rank, 1);
+        assert_eq!(scores[4].rank, 5);
+    }
+
+    #[test]
+    fn parses_numstat_and_rebases_cwd() {
+        let numstat = "3\t1\tsrc/lib.rs\n-\t-\tassets/logo.png\n10\t0\tREADME.md\n";
+        assert_eq!(parse_numstat(numstat), (3, 13, 1));
+
+        let root = Path::new("/repo");
+        let worktree = Path::new("/wt/agent-1");
+        assert_eq!(
+            rebase_cwd(Path::new("/repo/web"), root, worktree),
+            PathBuf::from("/wt/agent-1/web")
+        );
+        assert_eq!(
+            rebase_cwd(Path::new("/opt/tools"), root, worktree),
+            PathBuf::from("/opt/tools")
+        );
+    }
+
+    fn run_git(cwd: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .current_dir(cwd)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .expect("run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    #[tokio::test]
+    async fn evaluation_diffs_untracked_files_without_staging_them() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let root = repo.path();
+        run_git(root, &["init", "-q"]);
+        std::fs::write(root.join("tracked.txt"), "one\n").expect("write tracked");
+        run_git(root, &["add", "tracked.txt"]);
+        run_git(root, &["commit", "-q", "-m", "init"]);
+        std::fs::write(root.join("tracked.txt"), "two\n").expect("edit tracked");
+        std::fs::write(root.join("new.txt"), "fresh\n").expect("write untracked");
+
+        let input = CandidateInput {
+            agent_id: "agent".to_string(),
+            label: "agent".to_string(),
+            worktree: root.to_path_buf(),
+            branch: None,
+            base: None,
+        };
+        let mut validation = ValidationConfig::default();
+        validation.groups.functional = false;
+        validation.groups.stylistic = false;
+        let settings = EvaluationSettings {
+            project_root: root.to_path_buf(),
+            commands: Vec::new(),
+            validation,
+            github: GithubConfig::default(),
+        };
+        let score = evaluate_candidate(&input, &settings, Some("HEAD")).await;
+
+        assert_eq!(score.error, None);
+        assert!(score.patch.contains("b/new.txt"), "{}", score.patch);
+        assert!(score.patch.contains("b/tracked.txt"), "{}", score.patch);
+        assert_eq!(score.files_changed, 2);
+        assert_eq!(
+            run_git(root, &["status", "--porcelain"]),
+            " M tracked.txt\n?? new.txt\n"
+        );
+        assert_eq!(run_git(root, &["diff", "--cached", "--name-only"]), "");
+    }
+
+    #[test]
+    fn tail_keeps_last_lines() {
+        assert_eq!(tail_lines("a\nb\nc\n", 2), "b\nc");
+        assert_eq!(tail_lines("", 2), "");
+    }
+}
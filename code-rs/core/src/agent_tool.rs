@@ -23,7 +23,11 @@ use std::sync::Arc;
 use std::thread;
 use std::time::{Duration as StdDuration, Instant};
 use crate::spawn::spawn_tokio_command_with_retry;
+use crate::agent_board::{self, BoardEntry, BoardEntryKind};
 use crate::protocol::AgentSourceKind;
+use code_otel::spans::OtelSpan;
+use code_otel::spans::TRACEPARENT_ENV_VAR;
+use code_otel::spans::attr;
 use tracing::{debug, info, warn};
 
 #[cfg(target_os = "windows")]
@@ -412,6 +416,7 @@ pub struct AgentManager {
     event_sender: Option<mpsc::UnboundedSender<AgentStatusUpdatePayload>>,
     debug_log_root: Option<PathBuf>,
     watchdog_handle: Option<JoinHandle<()>>,
+    board_watcher_handle: Option<JoinHandle<()>>,
     inactivity_timeout: Duration,
     diagnostics: AgentManagerDiagnostics,
 }
@@ -447,6 +452,7 @@ const MAX_AGENT_PROGRESS_LINE_BYTES: usize = 2048;
 const MAX_AGENT_RESULT_BYTES: usize = 64 * 1024;
 const MAX_TRACKED_TERMINAL_AGENTS: usize = 512;
 const MAX_STATUS_TERMINAL_AGENTS: usize = 128;
+const BOARD_POLL_INTERVAL: TokioDuration = TokioDuration::from_secs(2);
 
 #[derive(Debug, Clone)]
 pub struct AgentStatusUpdatePayload {
@@ -464,6 +470,7 @@ impl AgentManager {
             event_sender: None,
             debug_log_root: None,
             watchdog_handle: None,
+            board_watcher_handle: None,
             inactivity_timeout: Duration::minutes(30),
             diagnostics: AgentManagerDiagnostics::default(),
         }
@@ -528,6 +535,66 @@ impl AgentManager {
         }));
     }
 
+    /// Follow the shared agent board and mirror new entries into agent
+    /// progress so they show up in the agents overview.
+    fn start_board_watcher(&mut self) {
+        if self.board_watcher_handle.is_some() {
+            return;
+        }
+
+        let manager = Arc::downgrade(&AGENT_MANAGER);
+        self.board_watcher_handle = Some(tokio::spawn(async move {
+            let path = agent_board::session_board_path();
+            let mut offset = 0u64;
+            let mut ticker = tokio::time::interval(BOARD_POLL_INTERVAL);
+            loop {
+                ticker.tick().await;
+
+                let Some(manager_arc) = manager.upgrade() else { break; };
+                let entries = match agent_board::read_entries_from(&path, offset) {
+                    Ok((entries, next)) => {
+                        offset = next;
+                        entries
+                    }
+                    Err(err) => {
+                        debug!("failed to read agent board: {err}");
+                        continue;
+                    }
+                };
+                if entries.is_empty() {
+                    continue;
+                }
+
+                let mut mgr = manager_arc.write().await;
+                for entry in entries {
+                    mgr.record_board_entry(&entry).await;
+                }
+            }
+        }));
+    }
+
+    async fn record_board_entry(&mut self, entry: &BoardEntry) {
+        if entry.kind == BoardEntryKind::Guidance {
+            // Guidance shows on the agents it is addressed to.
+            let targets: Vec<String> = match entry.to.as_ref() {
+                Some(agent_id) => vec![agent_id.clone()],
+                None => self
+                    .agents
+                    .values()
+                    .filter(|agent| matches!(agent.status, AgentStatus::Pending | AgentStatus::Running))
+                    .map(|agent| agent.id.clone())
+                    .collect(),
+            };
+            for agent_id in targets {
+                self.add_progress(&agent_id, format!("[board] {}", entry.summary()))
+                    .await;
+            }
+        } else {
+            self.add_progress(&entry.from, format!("[board] {}", entry.summary()))
+                .await;
+        }
+    }
+
     pub fn set_debug_log_root(&mut self, root: Option<PathBuf>) {
         self.debug_log_root = root;
     }
@@ -1032,11 +1099,15 @@ impl AgentManager {
         // Send initial status update
         self.send_agent_status_update().await;
 
-        // Spawn async agent
+        // Spawn async agent. The agent outlives the tool call that created
+        // it, so it gets its own trace linked back to the spawning span.
+        let span = OtelSpan::root_linked(
+            "codex.subagent",
+            vec![attr("agent.id", agent_id.as_str()), attr("model", agent.model.as_str())],
+            &OtelSpan::current(),
+        );
         let agent_id_clone = agent_id.clone();
-        let handle = tokio::spawn(async move {
-            execute_agent(agent_id_clone, config).await;
-        });
+        let handle = tokio::spawn(span.in_span(execute_agent(agent_id_clone, config)));
 
         self.handles.insert(agent_id.clone(), handle);
 
@@ -1265,6 +1336,74 @@ impl AgentManager {
         }
     }
 
+    /// Post orchestrator guidance to one agent, or to every running agent in
+    /// the batch. Returns the agents addressed.
+    pub fn post_guidance(
+        &self,
+        batch_id: &str,
+        agent_id: Option<&str>,
+        text: &str,
+    ) -> Result<Vec<String>, String> {
+        let recipients: Vec<String> = match agent_id {
+            Some(agent_id) => {
+                let agent = self
+                    .get_agent(agent_id)
+                    .ok_or_else(|| format!("Agent {agent_id} not found"))?;
+                if agent.batch_id.as_deref() != Some(batch_id) {
+                    return Err(format!("Agent {agent_id} does not belong to batch {batch_id}"));
+                }
+                if !matches!(agent.status, AgentStatus::Pending | AgentStatus::Running) {
+                    return Err(format!("Agent {agent_id} is no longer running"));
+                }
+                vec![agent.id]
+            }
+            None => self
+                .list_agents(None, Some(batch_id.to_string()), false)
+                .into_iter()
+                .filter(|agent| matches!(agent.status, AgentStatus::Pending | AgentStatus::Running))
+                .map(|agent| agent.id)
+                .collect(),
+        };
+        if recipients.is_empty() {
+            return Err(format!("No running agents in batch {batch_id}"));
+        }
+        let path = agent_board::session_board_path();
+        for recipient in &recipients {
+            let mut entry = BoardEntry::new(agent_board::ORCHESTRATOR, BoardEntryKind::Guidance, text);
+            entry.to = Some(recipient.clone());
+            agent_board::post(&path, &entry)?;
+        }
+        Ok(recipients)
+    }
+
+    /// Recent board entries and active claims, optionally limited to the
+    /// agents of one batch.
+    pub fn board_snapshot(
+        &self,
+        batch_id: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<BoardEntry>, BTreeMap<String, String>), String> {
+        let entries = agent_board::read_entries(&agent_board::session_board_path())?;
+        let mut claims = agent_board::active_claims(&entries);
+        let in_scope = |id: &str| match batch_id {
+            Some(batch) => self
+                .get_agent(id)
+                .is_some_and(|agent| agent.batch_id.as_deref() == Some(batch)),
+            None => true,
+        };
+        claims.retain(|_, holder| in_scope(holder));
+        let mut entries: Vec<BoardEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                let author_in_scope = entry.from == agent_board::ORCHESTRATOR || in_scope(&entry.from);
+                author_in_scope && entry.to.as_deref().is_none_or(&in_scope)
+            })
+            .collect();
+        let skip = entries.len().saturating_sub(limit);
+        entries.drain(..skip);
+        Ok((entries, claims))
+    }
+
     pub async fn update_worktree_info(
         &mut self,
         agent_id: &str,
@@ -1331,7 +1470,10 @@ fn generate_branch_id(model: &str, agent: &str) -> String {
     format!("code-{}-{}", model_s, suffix_s)
 }
 
+use crate::git_worktree::WorktreeOwner;
+use crate::git_worktree::load_branch_metadata;
 use crate::git_worktree::setup_worktree;
+use crate::git_worktree::write_branch_metadata;
 
 async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
     let mut manager = AGENT_MANAGER.write().await;
@@ -1346,6 +1488,7 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
     manager
         .update_agent_status(&agent_id, AgentStatus::Running)
         .await;
+    manager.start_board_watcher();
     manager
         .add_progress(
             &agent_id,
@@ -1391,6 +1534,16 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
     if !files.is_empty() {
         full_prompt = format!("{}\n\nFiles to consider: {}", full_prompt, files.join(", "));
     }
+    // Review agents work alone; everyone else coordinates through the board.
+    if !matches!(source_kind, Some(AgentSourceKind::AutoReview)) {
+        if let Ok(exe) = current_code_binary_path() {
+            full_prompt = format!(
+                "{}\n\n{}",
+                full_prompt,
+                agent_board::agent_instructions(&exe, read_only)
+            );
+        }
+    }
 
     // Setup working directory and execute
     let gating_error_message = |spec: &crate::agent_defaults::AgentModelSpec| {
@@ -1440,6 +1593,20 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
                             .await;
                         drop(manager);
 
+                        // Record the owner so `magik worktrees` can trace the worktree back.
+                        let mut metadata =
+                            load_branch_metadata(&worktree_path).unwrap_or_default();
+                        if metadata.base_branch.is_none() {
+                            metadata.base_branch = agent.worktree_base.clone();
+                        }
+                        metadata.owner = Some(WorktreeOwner::Agent {
+                            agent_id: agent_id.clone(),
+                            model: model.clone(),
+                        });
+                        if let Err(err) = write_branch_metadata(&worktree_path, &metadata).await {
+                            warn!("failed to record agent worktree owner: {err}");
+                        }
+
                         // Prepare optional review-output JSON path for /review agents
                         let review_output_json_path: Option<PathBuf> = agent
                             .source_kind
@@ -1541,6 +1708,10 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
 
     // Update result; if a review-output JSON was produced, prefer its contents.
     let final_result = prefer_json_result(review_output_json_path_capture.as_ref(), result);
+    // Free any files the agent left claimed so others can pick them up.
+    if let Err(err) = agent_board::release(&agent_board::session_board_path(), &agent_id, &[]) {
+        debug!("failed to release board claims for {agent_id}: {err}");
+    }
     let mut manager = AGENT_MANAGER.write().await;
     manager.update_agent_result(&agent_id, final_result).await;
 }
@@ -1787,6 +1958,17 @@ async fn execute_model_with_permissions(
         if let Some(ref e) = cfg.env { for (k, v) in e { env.insert(k.clone(), v.clone()); } }
     }
 
+    if let Some(traceparent) = OtelSpan::current().traceparent() {
+        env.insert(TRACEPARENT_ENV_VAR.to_string(), traceparent);
+    }
+
+    // Point `agent-board` at this session's board.
+    env.insert(
+        agent_board::BOARD_ENV_VAR.to_string(),
+        agent_board::session_board_path().display().to_string(),
+    );
+    env.insert(agent_board::AGENT_ID_ENV_VAR.to_string(), agent_id.to_string());
+
     if debug_subagent {
         env.entry("CODE_SUBAGENT_DEBUG".to_string())
             .or_insert_with(|| "1".to_string());
@@ -2364,10 +2546,10 @@ pub fn create_agent_tool(allowed_models: &[String]) -> OpenAiTool {
         "action".to_string(),
         JsonSchema::String {
             description: Some(
-                "Required: choose one of ['create','status','wait','result','cancel','list']".to_string(),
+                "Required: choose one of ['create','status','wait','result','cancel','list','message','board']".to_string(),
             ),
             allowed_values: Some(
-                ["create", "status", "wait", "result", "cancel", "list"]
+                ["create", "status", "wait", "result", "cancel", "list", "message", "board"]
                     .into_iter()
                     .map(|value| value.to_string())
                     .collect(),
@@ -2589,6 +2771,62 @@ pub fn create_agent_tool(allowed_models: &[String]) -> OpenAiTool {
         },
     );
 
+    let mut message_properties = BTreeMap::new();
+    message_properties.insert(
+        "batch_id".to_string(),
+        JsonSchema::String {
+            description: Some("Batch whose running agents receive the message".to_string()),
+            allowed_values: None,
+        },
+    );
+    message_properties.insert(
+        "agent_id".to_string(),
+        JsonSchema::String {
+            description: Some("Only message this agent instead of the whole batch".to_string()),
+            allowed_values: None,
+        },
+    );
+    message_properties.insert(
+        "text".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Guidance for running agents; they see it the next time they read the shared board".to_string(),
+            ),
+            allowed_values: None,
+        },
+    );
+    properties.insert(
+        "message".to_string(),
+        JsonSchema::Object {
+            properties: message_properties,
+            required: Some(vec!["batch_id".to_string(), "text".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    );
+
+    let mut board_properties = BTreeMap::new();
+    board_properties.insert(
+        "batch_id".to_string(),
+        JsonSchema::String {
+            description: Some("Only show entries from and to agents in this batch".to_string()),
+            allowed_values: None,
+        },
+    );
+    board_properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some("Most recent entries to return (default 50)".to_string()),
+        },
+    );
+    properties.insert(
+        "board".to_string(),
+        JsonSchema::Object {
+            properties: board_properties,
+            required: Some(Vec::new()),
+            additional_properties: Some(false.into()),
+        },
+    );
+
     let required = Some(vec!["action".to_string()]);
 
     OpenAiTool::Function(ResponsesApiTool {
@@ -2661,6 +2899,19 @@ pub struct AgentListOptions {
     pub recent_only: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentMessageOptions {
+    pub batch_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentBoardOptions {
+    pub batch_id: Option<String>,
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentToolRequest {
     pub action: String,
@@ -2670,6 +2921,8 @@ pub struct AgentToolRequest {
     pub cancel: Option<AgentCancelOptions>,
     pub wait: Option<AgentWaitOptions>,
     pub list: Option<AgentListOptions>,
+    pub message: Option<AgentMessageOptions>,
+    pub board: Option<AgentBoardOptions>,
 }
 
 pub(crate) fn normalize_agent_name(name: Option<String>) -> Option<String> {
@@ -0,0 +1,869 @@
+//! Native implementation of the Anthropic Messages API (`wire_api = "anthropic"`).
+//!
+//! The request is built from the same [`Prompt`] the Responses and Chat
+//! implementations use, and the streamed reply is mapped back onto
+//! [`ResponseEvent`]s. Thinking blocks round-trip through
+//! `ResponseItem::Reasoning`: the block signature is kept in
+//! `encrypted_content` so tool-use turns can be continued with thinking on.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use code_otel::otel_event_manager::OtelEventManager;
+use code_protocol::models::ContentItem;
+use code_protocol::models::FunctionCallOutputContentItem;
+use code_protocol::models::FunctionCallOutputPayload;
+use code_protocol::models::ReasoningItemContent;
+use code_protocol::models::ReasoningItemReasoningSummary;
+use code_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::client_common::replace_image_payloads_for_model;
+use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
+use crate::debug_logger::DebugLogger;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::model_traffic::TrafficWireApi;
+use crate::native_wire::ByteStream;
+use crate::native_wire::WireModelOptions;
+use crate::native_wire::WireRequestContext;
+use crate::native_wire::arguments_to_object;
+use crate::native_wire::function_tool_specs;
+use crate::native_wire::next_sse_event;
+use crate::native_wire::open_event_stream;
+use crate::native_wire::parse_data_url;
+use crate::native_wire::parse_sse_data;
+use crate::protocol::TokenUsage;
+
+/// Used when neither the config nor the model family sets an output limit.
+const DEFAULT_MAX_TOKENS: u64 = 16_384;
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u64 = 1_024;
+/// Marks `encrypted_content` that holds a `redacted_thinking` block.
+const REDACTED_THINKING_PREFIX: &str = "anthropic-redacted:";
+
+pub(crate) async fn stream_anthropic_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    options: WireModelOptions,
+    ctx: WireRequestContext<'_>,
+) -> Result<ResponseStream> {
+    if prompt.output_schema.is_some() {
+        return Err(CodexErr::UnsupportedOperation(
+            "output_schema is not supported for the Anthropic Messages API".to_string(),
+        ));
+    }
+
+    let payload = build_messages_payload(prompt, model_family, model_slug, options)?;
+    let url = ctx.provider.get_full_url(&None);
+    let (stream, request_id) =
+        open_event_stream(&ctx, TrafficWireApi::Anthropic, &url, &payload).await?;
+
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+    tokio::spawn(process_anthropic_sse(
+        stream,
+        tx_event,
+        ctx.provider.stream_idle_timeout(),
+        Arc::clone(ctx.debug_logger),
+        request_id,
+        ctx.otel_event_manager,
+    ));
+    Ok(ResponseStream { rx_event })
+}
+
+/// Build the `/v1/messages` request body.
+pub(crate) fn build_messages_payload(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    options: WireModelOptions,
+) -> Result<Value> {
+    let mut input = prompt.get_formatted_input();
+    replace_image_payloads_for_model(&mut input, model_slug);
+
+    // Developer messages ahead of the conversation extend the system prompt;
+    // later ones are sent as user text since the API has no developer role.
+    let mut system = vec![prompt.get_full_instructions(model_family).into_owned()];
+    let mut messages: Vec<Value> = Vec::new();
+    let mut in_preamble = true;
+    for item in &input {
+        if let ResponseItem::Message { role, content, .. } = item
+            && (role == "developer" || role == "system")
+        {
+            let text = message_text(content);
+            if text.trim().is_empty() {
+                continue;
+            }
+            if in_preamble {
+                system.push(text);
+            } else {
+                push_block(&mut messages, "user", json!({ "type": "text", "text": text }));
+            }
+            continue;
+        }
+        in_preamble = false;
+
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" { "assistant" } else { "user" };
+                for block in content.iter().filter_map(content_block) {
+                    push_block(&mut messages, role, block);
+                }
+            }
+            ResponseItem::Reasoning {
+                summary,
+                content,
+                encrypted_content: Some(signature),
+                ..
+            } => {
+                let block = match signature.strip_prefix(REDACTED_THINKING_PREFIX) {
+                    Some(data) => json!({ "type": "redacted_thinking", "data": data }),
+                    None => json!({
+                        "type": "thinking",
+                        "thinking": reasoning_text(summary, content.as_deref()),
+                        "signature": signature,
+                    }),
+                };
+                push_block(&mut messages, "assistant", block);
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                push_block(
+                    &mut messages,
+                    "assistant",
+                    json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": arguments_to_object(arguments),
+                    }),
+                );
+            }
+            ResponseItem::LocalShellCall {
+                id,
+                call_id,
+                action,
+                ..
+            } => {
+                let Some(call_id) = call_id.clone().or_else(|| id.clone()) else {
+                    continue;
+                };
+                push_block(
+                    &mut messages,
+                    "assistant",
+                    json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": "local_shell",
+                        "input": serde_json::to_value(action)?,
+                    }),
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                push_block(
+                    &mut messages,
+                    "assistant",
+                    json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": { "input": input },
+                    }),
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                push_block(&mut messages, "user", tool_result_block(call_id, output));
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                push_block(
+                    &mut messages,
+                    "user",
+                    json!({ "type": "tool_result", "tool_use_id": call_id, "content": output }),
+                );
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::GhostSnapshot { .. }
+            | ResponseItem::CompactionSummary { .. }
+            | ResponseItem::Other => {}
+        }
+    }
+
+    let max_tokens = options.max_output_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let mut payload = json!({
+        "model": model_slug,
+        "max_tokens": max_tokens,
+        "system": [{
+            "type": "text",
+            "text": system.join("\n\n"),
+            "cache_control": { "type": "ephemeral" },
+        }],
+        "messages": messages,
+        "stream": true,
+    });
+
+    let tools: Vec<Value> = function_tool_specs(&prompt.tools)?
+        .into_iter()
+        .map(|tool| {
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "input_schema": tool.parameters,
+            })
+        })
+        .collect();
+    if let Some(obj) = payload.as_object_mut() {
+        if !tools.is_empty() {
+            obj.insert("tools".to_string(), Value::Array(tools));
+        }
+        if let Some(budget) = thinking_budget(options.effort, max_tokens) {
+            obj.insert(
+                "thinking".to_string(),
+                json!({ "type": "enabled", "budget_tokens": budget }),
+            );
+        }
+    }
+    Ok(payload)
+}
+
+/// Thinking budget for the configured reasoning effort, kept below
+/// `max_tokens` as the API requires. `None` disables extended thinking.
+fn thinking_budget(effort: ReasoningEffortConfig, max_tokens: u64) -> Option<u64> {
+    let budget = match effort {
+        ReasoningEffortConfig::Minimal | ReasoningEffortConfig::None => return None,
+        ReasoningEffortConfig::Low => 2_048,
+        ReasoningEffortConfig::Medium => 8_192,
+        ReasoningEffortConfig::High => 16_384,
+        ReasoningEffortConfig::XHigh => 32_768,
+    };
+    let budget = budget.min(max_tokens / 2);
+    (budget >= MIN_THINKING_BUDGET).then_some(budget)
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .filter_map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                Some(text.as_str())
+            }
+            ContentItem::InputImage { .. } => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn content_block(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+            (!text.is_empty()).then(|| json!({ "type": "text", "text": text }))
+        }
+        ContentItem::InputImage { image_url } => Some(image_block(image_url)),
+    }
+}
+
+fn image_block(image_url: &str) -> Value {
+    match parse_data_url(image_url) {
+        Some((media_type, data)) => json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": media_type, "data": data },
+        }),
+        None => json!({
+            "type": "image",
+            "source": { "type": "url", "url": image_url },
+        }),
+    }
+}
+
+fn tool_result_block(call_id: &str, output: &FunctionCallOutputPayload) -> Value {
+    let content = match output.content_items() {
+        Some(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| match item {
+                    FunctionCallOutputContentItem::InputText { text } => {
+                        json!({ "type": "text", "text": text })
+                    }
+                    FunctionCallOutputContentItem::InputImage { image_url } => {
+                        image_block(image_url)
+                    }
+                })
+                .collect(),
+        ),
+        None => Value::String(output.to_string()),
+    };
+    let mut block = json!({ "type": "tool_result", "tool_use_id": call_id, "content": content });
+    if output.success == Some(false)
+        && let Some(obj) = block.as_object_mut()
+    {
+        obj.insert("is_error".to_string(), Value::Bool(true));
+    }
+    block
+}
+
+fn reasoning_text(
+    summary: &[ReasoningItemReasoningSummary],
+    content: Option<&[ReasoningItemContent]>,
+) -> String {
+    if let Some(content) = content.filter(|content| !content.is_empty()) {
+        return content
+            .iter()
+            .map(|item| match item {
+                ReasoningItemContent::ReasoningText { text } | ReasoningItemContent::Text { text } => {
+                    text.as_str()
+                }
+            })
+            .collect();
+    }
+    summary
+        .iter()
+        .map(|ReasoningItemReasoningSummary::SummaryText { text }| text.as_str())
+        .collect()
+}
+
+/// Append a content block, merging into the previous message when the role
+/// repeats so user/assistant turns alternate as the API expects.
+fn push_block(messages: &mut Vec<Value>, role: &str, block: Value) {
+    if let Some(last) = messages.last_mut()
+        && last.get("role").and_then(Value::as_str) == Some(role)
+        && let Some(content) = last.get_mut("content").and_then(Value::as_array_mut)
+    {
+        content.push(block);
+        return;
+    }
+    messages.push(json!({ "role": role, "content": [block] }));
+}
+
+enum ContentBlock {
+    Text(String),
+    Thinking { text: String, signature: String },
+    RedactedThinking(String),
+    ToolUse { id: String, name: String, json: String },
+}
+
+#[derive(Default)]
+struct UsageTotals {
+    input: i64,
+    cache_creation: i64,
+    cache_read: i64,
+    output: i64,
+}
+
+impl UsageTotals {
+    fn update(&mut self, usage: &Value) {
+        let field = |name: &str| usage.get(name).and_then(Value::as_i64);
+        if let Some(value) = field("input_tokens") {
+            self.input = value;
+        }
+        if let Some(value) = field("cache_creation_input_tokens") {
+            self.cache_creation = value;
+        }
+        if let Some(value) = field("cache_read_input_tokens") {
+            self.cache_read = value;
+        }
+        if let Some(value) = field("output_tokens") {
+            self.output = value;
+        }
+    }
+
+    /// Anthropic reports cache reads and writes separately from
+    /// `input_tokens`; `TokenUsage` counts cached tokens as part of the input.
+    fn to_token_usage(&self) -> TokenUsage {
+        let input_tokens = self.input + self.cache_creation + self.cache_read;
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens: self.cache_read,
+            output_tokens: self.output,
+            reasoning_output_tokens: 0,
+            total_tokens: input_tokens + self.output,
+        }
+    }
+}
+
+/// Map the Messages streaming events onto [`ResponseEvent`]s.
+async fn process_anthropic_sse(
+    stream: ByteStream,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+    debug_logger: Arc<Mutex<DebugLogger>>,
+    request_id: String,
+    otel_event_manager: Option<OtelEventManager>,
+) {
+    let mut stream = stream.eventsource();
+    let mut blocks: BTreeMap<u64, ContentBlock> = BTreeMap::new();
+    let mut message_id = String::new();
+    let mut usage = UsageTotals::default();
+
+    loop {
+        let event = match next_sse_event(
+            &mut stream,
+            idle_timeout,
+            otel_event_manager.as_ref(),
+            &request_id,
+        )
+        .await
+        {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Err(err) => {
+                let _ = tx_event.send(Err(err)).await;
+                return;
+            }
+        };
+        let Some(data) = parse_sse_data(&event, &debug_logger, &request_id) else {
+            continue;
+        };
+
+        let index = data.get("index").and_then(Value::as_u64).unwrap_or(0);
+        let item_id = (!message_id.is_empty()).then(|| format!("{message_id}_{index}"));
+        match data.get("type").and_then(Value::as_str).unwrap_or_default() {
+            "message_start" => {
+                let message = data.get("message").cloned().unwrap_or_default();
+                message_id = message
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                if let Some(message_usage) = message.get("usage") {
+                    usage.update(message_usage);
+                }
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Created {
+                        response_id: (!message_id.is_empty()).then(|| message_id.clone()),
+                        response_model: message
+                            .get("model")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    }))
+                    .await;
+            }
+            "content_block_start" => {
+                let block = data.get("content_block").cloned().unwrap_or_default();
+                let text_field = |name: &str| {
+                    block
+                        .get(name)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let state = match block.get("type").and_then(Value::as_str) {
+                    Some("text") => ContentBlock::Text(text_field("text")),
+                    Some("thinking") => ContentBlock::Thinking {
+                        text: text_field("thinking"),
+                        signature: text_field("signature"),
+                    },
+                    Some("redacted_thinking") => ContentBlock::RedactedThinking(text_field("data")),
+                    Some("tool_use") => ContentBlock::ToolUse {
+                        id: text_field("id"),
+                        name: text_field("name"),
+                        json: String::new(),
+                    },
+                    _ => continue,
+                };
+                blocks.insert(index, state);
+            }
+            "content_block_delta" => {
+                let delta = data.get("delta").cloned().unwrap_or_default();
+                let delta_text = |name: &str| {
+                    delta
+                        .get(name)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                match (blocks.get_mut(&index), delta.get("type").and_then(Value::as_str)) {
+                    (Some(ContentBlock::Text(text)), Some("text_delta")) => {
+                        let chunk = delta_text("text");
+                        text.push_str(&chunk);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputTextDelta {
+                                delta: chunk,
+                                item_id,
+                                sequence_number: None,
+                                output_index: None,
+                            }))
+                            .await;
+                    }
+                    (Some(ContentBlock::Thinking { text, .. }), Some("thinking_delta")) => {
+                        let chunk = delta_text("thinking");
+                        text.push_str(&chunk);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::ReasoningSummaryDelta {
+                                delta: chunk,
+                                item_id,
+                                sequence_number: None,
+                                output_index: None,
+                                summary_index: Some(0),
+                            }))
+                            .await;
+                    }
+                    (Some(ContentBlock::Thinking { signature, .. }), Some("signature_delta")) => {
+                        signature.push_str(&delta_text("signature"));
+                    }
+                    (Some(ContentBlock::ToolUse { json, .. }), Some("input_json_delta")) => {
+                        json.push_str(&delta_text("partial_json"));
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                let Some(block) = blocks.remove(&index) else {
+                    continue;
+                };
+                let item = match block {
+                    ContentBlock::Text(text) => ResponseItem::Message {
+                        id: item_id,
+                        role: "assistant".to_string(),
+                        content: vec![ContentItem::OutputText { text }],
+                        end_turn: None,
+                        phase: None,
+                    },
+                    ContentBlock::Thinking { text, signature } => ResponseItem::Reasoning {
+                        id: item_id.unwrap_or_default(),
+                        summary: vec![ReasoningItemReasoningSummary::SummaryText { text }],
+                        content: None,
+                        encrypted_content: (!signature.is_empty()).then_some(signature),
+                    },
+                    ContentBlock::RedactedThinking(data) => ResponseItem::Reasoning {
+                        id: item_id.unwrap_or_default(),
+                        summary: Vec::new(),
+                        content: None,
+                        encrypted_content: Some(format!("{REDACTED_THINKING_PREFIX}{data}")),
+                    },
+                    ContentBlock::ToolUse { id, name, json } => ResponseItem::FunctionCall {
+                        id: item_id,
+                        name,
+                        arguments: if json.trim().is_empty() {
+                            "{}".to_string()
+                        } else {
+                            json
+                        },
+                        call_id: id,
+                    },
+                };
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::OutputItemDone {
+                        item,
+                        sequence_number: None,
+                        output_index: None,
+                    }))
+                    .await;
+            }
+            "message_delta" => {
+                if let Some(delta_usage) = data.get("usage") {
+                    usage.update(delta_usage);
+                }
+            }
+            "message_stop" => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: message_id.clone(),
+                        token_usage: Some(usage.to_token_usage()),
+                    }))
+                    .await;
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.end_request_log(&request_id);
+                }
+                return;
+            }
+            "error" => {
+                let error = data.get("error").cloned().unwrap_or_default();
+                let kind = error
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("error");
+                let message = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error");
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        format!("{kind}: {message}"),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelProviderInfo;
+    use crate::WireApi;
+    use crate::model_family::find_family_for_model;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn options() -> WireModelOptions {
+        WireModelOptions {
+            effort: ReasoningEffortConfig::Low,
+            max_output_tokens: Some(8_000),
+        }
+    }
+
+    fn user_message(content: Vec<ContentItem>) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content,
+            end_turn: None,
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn payload_maps_tool_turns_images_and_thinking() {
+        let prompt = Prompt {
+            input: vec![
+                user_message(vec![
+                    ContentItem::InputText {
+                        text: "what is in this image?".to_string(),
+                    },
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAAA".to_string(),
+                    },
+                ]),
+                ResponseItem::Reasoning {
+                    id: "r1".to_string(),
+                    summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                        text: "look closer".to_string(),
+                    }],
+                    content: None,
+                    encrypted_content: Some("sig-1".to_string()),
+                },
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: "shell".to_string(),
+                    arguments: r#"{"command":["ls"]}"#.to_string(),
+                    call_id: "toolu_1".to_string(),
+                },
+                ResponseItem::FunctionCallOutput {
+                    call_id: "toolu_1".to_string(),
+                    output: FunctionCallOutputPayload::from_text("cat.png".to_string()),
+                },
+            ],
+            include_additional_instructions: false,
+            base_instructions_override: Some("be brief".to_string()),
+            ..Prompt::default()
+        };
+        let family = find_family_for_model("gpt-4.1").expect("family");
+
+        let payload =
+            build_messages_payload(&prompt, &family, "claude-sonnet-4-5", options()).unwrap();
+
+        assert_eq!(payload["system"][0]["text"], json!("be brief"));
+        assert_eq!(payload["max_tokens"], json!(8_000));
+        assert_eq!(
+            payload["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 2_048 })
+        );
+        assert_eq!(
+            payload["messages"],
+            json!([
+                {
+                    "role": "user",
+                    "content": [
+                        { "type": "text", "text": "what is in this image?" },
+                        {
+                            "type": "image",
+                            "source": { "type": "base64", "media_type": "image/png", "data": "AAAA" }
+                        }
+                    ]
+                },
+                {
+                    "role": "assistant",
+                    "content": [
+                        { "type": "thinking", "thinking": "look closer", "signature": "sig-1" },
+                        {
+                            "type": "tool_use",
+                            "id": "toolu_1",
+                            "name": "shell",
+                            "input": { "command": ["ls"] }
+                        }
+                    ]
+                },
+                {
+                    "role": "user",
+                    "content": [
+                        { "type": "tool_result", "tool_use_id": "toolu_1", "content": "cat.png" }
+                    ]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn thinking_budget_stays_below_max_tokens() {
+        assert_eq!(thinking_budget(ReasoningEffortConfig::High, 64_000), Some(16_384));
+        assert_eq!(thinking_budget(ReasoningEffortConfig::High, 10_000), Some(5_000));
+        assert_eq!(thinking_budget(ReasoningEffortConfig::Low, 1_500), None);
+        assert_eq!(thinking_budget(ReasoningEffortConfig::Minimal, 64_000), None);
+    }
+
+    #[tokio::test]
+    async fn streams_text_thinking_tool_use_and_usage_from_mock_server() {
+        let server = MockServer::start().await;
+        let events = [
+            json!({"type": "message_start", "message": {
+                "id": "msg_1", "model": "claude-sonnet-4-5",
+                "usage": {"input_tokens": 20, "cache_read_input_tokens": 100, "output_tokens": 1}
+            }}),
+            json!({"type": "content_block_start", "index": 0,
+                "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "thinking_delta", "thinking": "Need to list."}}),
+            json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "signature_delta", "signature": "sig-abc"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1,
+                "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1,
+                "delta": {"type": "text_delta", "text": "Listing files."}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2,
+                "content_block": {"type": "tool_use", "id": "toolu_9", "name": "shell", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2,
+                "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+            json!({"type": "content_block_delta", "index": 2,
+                "delta": {"type": "input_json_delta", "partial_json": "[\"ls\"]}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"},
+                "usage": {"output_tokens": 42}}),
+            json!({"type": "message_stop"}),
+        ];
+        let body: String = events
+            .iter()
+            .map(|event| format!("event: {}\ndata: {event}\n\n", event["type"].as_str().unwrap()))
+            .collect();
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("x-api-key", "test-key"))
+            .and(header("anthropic-version", "2023-06-01"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = ModelProviderInfo {
+            name: "Anthropic".to_string(),
+            base_url: Some(format!("{}/v1", server.uri())),
+            env_key: None,
+            env_key_instructions: None,
+            experimental_bearer_token: Some("test-key".to_string()),
+            wire_api: WireApi::Anthropic,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: Some(0),
+            stream_max_retries: Some(0),
+            stream_idle_timeout_ms: Some(5_000),
+            requires_openai_auth: false,
+            openrouter: None,
+        };
+        let client = reqwest::Client::new();
+        let debug_logger = Arc::new(Mutex::new(DebugLogger::new(false).unwrap()));
+        let prompt = Prompt {
+            input: vec![user_message(vec![ContentItem::InputText {
+                text: "list files".to_string(),
+            }])],
+            ..Prompt::default()
+        };
+        let family = find_family_for_model("gpt-4.1").expect("family");
+        let ctx = WireRequestContext {
+            client: &client,
+            provider: &provider,
+            debug_logger: &debug_logger,
+            otel_event_manager: None,
+            log_tag: None,
+            traffic: None,
+        };
+
+        let mut stream =
+            stream_anthropic_messages(&prompt, &family, "claude-sonnet-4-5", options(), ctx)
+                .await
+                .unwrap();
+        let mut items = Vec::new();
+        let mut deltas = String::new();
+        let usage = loop {
+            match stream.rx_event.recv().await.expect("stream ended").unwrap() {
+                ResponseEvent::OutputTextDelta { delta, .. } => deltas.push_str(&delta),
+                ResponseEvent::OutputItemDone { item, .. } => items.push(item),
+                ResponseEvent::Completed { token_usage, .. } => break token_usage,
+                _ => {}
+            }
+        };
+
+        assert_eq!(deltas, "Listing files.");
+        assert_eq!(items.len(), 3);
+        assert!(matches!(
+            &items[0],
+            ResponseItem::Reasoning { encrypted_content: Some(sig), .. } if sig == "sig-abc"
+        ));
+        assert!(matches!(&items[1], ResponseItem::Message { role, .. } if role == "assistant"));
+        match &items[2] {
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                assert_eq!(name, "shell");
+                assert_eq!(arguments, r#"{"command":["ls"]}"#);
+                assert_eq!(call_id, "toolu_9");
+            }
+            other => panic!("unexpected item: {other:?}"),
+        }
+        assert_eq!(
+            usage,
+            Some(TokenUsage {
+                input_tokens: 120,
+                cached_input_tokens: 100,
+                output_tokens: 42,
+                reasoning_output_tokens: 0,
+                total_tokens: 162,
+            })
+        );
+    }
+}
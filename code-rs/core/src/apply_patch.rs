@@ -3,6 +3,7 @@ use anyhow::Result;
 use crate::acp::AcpFileSystem;
 use crate::codex::Session;
 use crate::patch_harness::run_patch_harness;
+use crate::patch_review::apply_hunk_review;
 use crate::protocol::FileChange;
 use crate::protocol::ReviewDecision;
 use crate::safety::assess_patch_safety;
@@ -28,6 +29,10 @@ pub(crate) struct ApplyPatchRun {
     pub stderr: String,
     pub success: bool,
     pub harness_summary_json: Option<String>,
+    /// Summary of hunks the user rejected or edited during a hunk-level review.
+    pub review_note: Option<String>,
+    /// Changes actually applied when they differ from the proposed patch.
+    pub applied_changes: Option<HashMap<PathBuf, FileChange>>,
 }
 
 pub(crate) enum ApplyPatchResult {
@@ -138,6 +143,10 @@ pub(crate) async fn apply_patch(
             .await;
     }
 
+    let mut action = action;
+    let mut review_note: Option<String> = None;
+    let mut applied_changes: Option<HashMap<PathBuf, FileChange>> = None;
+
     let auto_approved = match assess_patch_safety(
         &action,
         sess.get_approval_policy(),
@@ -146,12 +155,66 @@ pub(crate) async fn apply_patch(
     ) {
         SafetyCheck::AutoApprove { .. } => true,
         SafetyCheck::AskUser => {
+            let shown_changes = convert_apply_patch_to_protocol(&action);
             let rx = sess
-                .request_patch_approval(sub_id.to_owned(), call_id.to_owned(), &action, None, None)
+                .request_patch_approval(
+                    sub_id.to_owned(),
+                    call_id.to_owned(),
+                    shown_changes.clone(),
+                    None,
+                    None,
+                )
                 .await;
             match rx.await.unwrap_or_default() {
                 ReviewDecision::Approved | ReviewDecision::ApprovedForSession => false,
-                ReviewDecision::Denied | ReviewDecision::Abort => {
+                ReviewDecision::ApprovedHunks { hunks } => {
+                    let reviewed = match apply_hunk_review(action.changes(), &shown_changes, &hunks) {
+                        Ok(reviewed) => reviewed,
+                        Err(err) => {
+                            return ApplyPatchResult::Reply(ResponseInputItem::FunctionCallOutput {
+                                call_id: call_id.to_owned(),
+                                output: FunctionCallOutputPayload {
+                                    body: code_protocol::models::FunctionCallOutputBody::Text(format!(
+                                        "patch not applied: {err}. Re-read the file and propose the patch again."
+                                    )),
+                                    success: Some(false)},
+                            });
+                        }
+                    };
+                    if reviewed.changes.is_empty() {
+                        let mut message = "patch rejected by user".to_string();
+                        if let Some(note) = reviewed.note {
+                            message.push('\n');
+                            message.push_str(&note);
+                        }
+                        return ApplyPatchResult::Reply(ResponseInputItem::FunctionCallOutput {
+                            call_id: call_id.to_owned(),
+                            output: FunctionCallOutputPayload {
+                                body: code_protocol::models::FunctionCallOutputBody::Text(message),
+                                success: Some(false)},
+                        });
+                    }
+                    action = action.with_changes(reviewed.changes);
+                    review_note = reviewed.note;
+                    applied_changes = Some(convert_apply_patch_to_protocol(&action));
+                    false
+                }
+                ReviewDecision::DeniedWithFeedback { feedback } => {
+                    let message = format!(
+                        "patch rejected by user. The user said:\n{}",
+                        feedback.trim()
+                    );
+                    return ApplyPatchResult::Reply(ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id.to_owned(),
+                        output: FunctionCallOutputPayload {
+                            body: code_protocol::models::FunctionCallOutputBody::Text(message),
+                            success: Some(false)},
+                    });
+                }
+                // Command edits only apply to exec approvals.
+                ReviewDecision::Denied
+                | ReviewDecision::Abort
+                | ReviewDecision::ApprovedWithEdits { .. } => {
                     return ApplyPatchResult::Reply(ResponseInputItem::FunctionCallOutput {
                         call_id: call_id.to_owned(),
                         output: FunctionCallOutputPayload {
@@ -189,6 +252,8 @@ pub(crate) async fn apply_patch(
         stderr,
         success,
         harness_summary_json,
+        review_note,
+        applied_changes,
     })
 }
 
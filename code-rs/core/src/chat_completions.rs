@@ -31,6 +31,7 @@ use crate::error::Result;
 use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
 use crate::model_family::ModelFamily;
+use crate::model_traffic::{ModelTraffic, TrafficWireApi};
 use crate::openai_tools::create_tools_json_for_chat_completions_api;
 use crate::util::backoff;
 use std::sync::{Arc, Mutex};
@@ -49,6 +50,7 @@ pub(crate) async fn stream_chat_completions(
     auth_manager: Option<Arc<AuthManager>>,
     otel_event_manager: Option<OtelEventManager>,
     log_tag: Option<&str>,
+    traffic: Option<&ModelTraffic>,
 ) -> Result<ResponseStream> {
     if prompt.output_schema.is_some() {
         return Err(CodexErr::UnsupportedOperation(
@@ -338,6 +340,20 @@ pub(crate) async fn stream_chat_completions(
         serde_json::to_string_pretty(&payload).unwrap_or_default()
     );
 
+    if let Some(traffic) = traffic.filter(|traffic| traffic.is_replay()) {
+        let stream = traffic.replay(TrafficWireApi::Chat, &payload)?;
+        let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+        tokio::spawn(process_chat_sse(
+            stream,
+            tx_event,
+            provider.stream_idle_timeout(),
+            Arc::clone(debug_logger),
+            String::new(),
+            otel_event_manager,
+        ));
+        return Ok(ResponseStream { rx_event });
+    }
+
     let mut attempt = 0;
     let max_retries = provider.request_max_retries();
     let mut request_id = String::new();
@@ -395,6 +411,15 @@ pub(crate) async fn stream_chat_completions(
                 }
                 let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
                 let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                let stream = match traffic {
+                    Some(traffic) => traffic.record(
+                        TrafficWireApi::Chat,
+                        &provider.get_full_url(&auth),
+                        &payload,
+                        stream,
+                    ),
+                    None => stream.boxed(),
+                };
                 let debug_logger_clone = Arc::clone(&debug_logger);
                 let request_id_clone = request_id.clone();
                 tokio::spawn(process_chat_sse(
@@ -1227,7 +1252,7 @@ impl<S> AggregatedChatStream<S> {
     }
 }
 
-fn header_map_to_json(headers: &HeaderMap) -> serde_json::Value {
+pub(crate) fn header_map_to_json(headers: &HeaderMap) -> serde_json::Value {
     let mut ordered: BTreeMap<String, Vec<String>> = BTreeMap::new();
     for (name, value) in headers.iter() {
         let entry = ordered.entry(name.as_str().to_string()).or_default();
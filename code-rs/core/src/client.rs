@@ -8,6 +8,7 @@ use std::time::Duration;
 use crate::AuthManager;
 use crate::RefreshTokenError;
 use crate::account_usage;
+use crate::account_usage::AccountSwitchReason;
 use crate::auth;
 use crate::auth_accounts;
 use bytes::Bytes;
@@ -43,6 +44,7 @@ use crate::agent_defaults::{
     enabled_agent_model_specs_for_auth,
     filter_agent_model_names_for_auth,
 };
+use crate::anthropic_messages::stream_anthropic_messages;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
 use crate::client_common::Prompt;
@@ -64,9 +66,13 @@ use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
 use crate::error::UsageLimitReachedError;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
+use crate::model_traffic::{ModelTraffic, TrafficWireApi};
 use crate::model_family::{find_family_for_model, ModelFamily};
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
+use crate::gemini::stream_gemini;
+use crate::native_wire::WireModelOptions;
+use crate::native_wire::WireRequestContext;
 use crate::openai_tools::create_tools_json_for_responses_api;
 use crate::openai_tools::ConfigShellToolType;
 use crate::openai_tools::ToolsConfig;
@@ -248,6 +254,7 @@ pub struct ModelClient {
     reasoning_summary_disabled: AtomicBool,
     verbosity: TextVerbosityConfig,
     debug_logger: Arc<Mutex<DebugLogger>>,
+    traffic: Option<Arc<ModelTraffic>>,
 }
 
 impl Clone for ModelClient {
@@ -266,6 +273,7 @@ impl Clone for ModelClient {
             ),
             verbosity: self.verbosity,
             debug_logger: Arc::clone(&self.debug_logger),
+            traffic: self.traffic.clone(),
         }
     }
 }
@@ -285,6 +293,8 @@ impl ModelClient {
         let effective_verbosity = clamp_text_verbosity_for_model(config.model.as_str(), verbosity);
         let clamped_effort = clamp_reasoning_effort_for_model(config.model.as_str(), effort);
         let client = create_client(&config.responses_originator_header);
+        let traffic = ModelTraffic::from_config(&config.model_traffic, &config.code_home, session_id)
+            .map(Arc::new);
 
         Self {
             config,
@@ -298,6 +308,7 @@ impl ModelClient {
             reasoning_summary_disabled: AtomicBool::new(false),
             verbosity: effective_verbosity,
             debug_logger,
+            traffic,
         }
     }
 
@@ -373,6 +384,39 @@ impl ModelClient {
         self.config.account_switching_mode
     }
 
+    /// Project pins and budgets that apply to this session's working directory.
+    pub fn account_switch_policy(&self) -> crate::account_switching::AccountSwitchPolicy {
+        crate::account_switching::AccountSwitchPolicy::from_config(&self.config)
+    }
+
+    /// Build a usage ledger entry for a completed request on the active
+    /// account. `source` defaults to the current process (TUI, exec or agent).
+    pub fn usage_ledger_entry(
+        &self,
+        usage: &crate::protocol::TokenUsage,
+        model: &str,
+        source: Option<account_usage::UsageSource>,
+    ) -> Option<account_usage::UsageLedgerEntry> {
+        let account_id = auth_accounts::get_active_account_id(&self.config.code_home)
+            .ok()
+            .flatten()?;
+        let source = source.unwrap_or_else(|| {
+            account_usage::UsageSource::for_current_process(
+                &self.config.responses_originator_header,
+            )
+        });
+        let project = crate::git_info::get_git_repo_root(&self.config.cwd)
+            .map(|root| root.to_string_lossy().into_owned());
+        Some(account_usage::UsageLedgerEntry::new(
+            account_id,
+            model.to_string(),
+            project,
+            source,
+            usage,
+            Utc::now(),
+        ))
+    }
+
     pub fn build_tools_config_with_sandbox(
         &self,
         sandbox_policy: SandboxPolicy,
@@ -507,7 +551,8 @@ impl ModelClient {
                     })
                     .unwrap_or(self.config.model_family.prefer_websockets);
 
-                if prefer_websockets {
+                // Recording and replay operate on the HTTP SSE stream.
+                if prefer_websockets && self.traffic.is_none() {
                     match self.stream_responses_websocket(prompt, log_tag).await {
                         Ok(stream) => Ok(stream),
                         Err(err) => {
@@ -521,6 +566,9 @@ impl ModelClient {
                     self.stream_responses(prompt, log_tag).await
                 }
             }
+            WireApi::ResponsesWebsocket if self.traffic.is_some() => {
+                self.stream_responses(prompt, log_tag).await
+            }
             WireApi::ResponsesWebsocket => self.stream_responses_websocket(prompt, log_tag).await,
             WireApi::Chat => {
                 let effective_family = prompt
@@ -542,6 +590,7 @@ impl ModelClient {
                     self.auth_manager.clone(),
                     self.otel_event_manager.clone(),
                     log_tag,
+                    self.traffic.as_deref(),
                 )
                 .await?;
 
@@ -570,6 +619,34 @@ impl ModelClient {
 
                 Ok(ResponseStream { rx_event: rx })
             }
+            WireApi::Anthropic | WireApi::Gemini => {
+                let effective_family = prompt
+                    .model_family_override
+                    .as_ref()
+                    .unwrap_or(&self.config.model_family);
+                let model_slug = prompt
+                    .model_override
+                    .as_deref()
+                    .unwrap_or(self.config.model.as_str());
+                let options = WireModelOptions {
+                    effort: self.effort,
+                    max_output_tokens: self.config.model_max_output_tokens,
+                };
+                let ctx = WireRequestContext {
+                    client: &self.client,
+                    provider: &self.provider,
+                    debug_logger: &self.debug_logger,
+                    otel_event_manager: self.otel_event_manager.clone(),
+                    log_tag,
+                    traffic: self.traffic.as_deref(),
+                };
+                if self.provider.wire_api == WireApi::Anthropic {
+                    stream_anthropic_messages(prompt, effective_family, model_slug, options, ctx)
+                        .await
+                } else {
+                    stream_gemini(prompt, effective_family, model_slug, options, ctx).await
+                }
+            }
         }
     }
 
@@ -1067,18 +1144,22 @@ impl ModelClient {
         let max_retries = self.provider.request_max_retries();
         let mut request_id = String::new();
         let mut rate_limit_switch_state = crate::account_switching::RateLimitSwitchState::default();
+        let switch_policy = self.account_switch_policy();
         let mut unauthorized_refresh_attempts: u8 = 0;
         const MAX_UNAUTHORIZED_REFRESH_RETRIES: u8 = 1;
 
+        // Project pins and budgets apply before the first request even in
+        // on-limit mode; the other strategies always get a say.
         if self.config.auto_switch_accounts_on_rate_limit
-            && !matches!(
-                self.config.account_switching_mode,
-                crate::config_types::AccountSwitchingMode::Manual
-                    | crate::config_types::AccountSwitchingMode::OnLimit
-            )
+            && self.config.account_switching_mode
+                != crate::config_types::AccountSwitchingMode::Manual
+            && (self.config.account_switching_mode
+                != crate::config_types::AccountSwitchingMode::OnLimit
+                || switch_policy.is_active())
             && auth_accounts::session_account_override().is_none()
             && auth_manager.is_some()
             && auth::read_code_api_key_from_env().is_none()
+            && !self.traffic.as_ref().is_some_and(|traffic| traffic.is_replay())
         {
             let now = Utc::now();
             let auth = auth_manager.as_ref().and_then(|m| m.auth());
@@ -1092,21 +1173,32 @@ impl ModelClient {
             .ok()
             .flatten();
 
-            if let Ok(Some(next_account_id)) = crate::account_switching::select_preferred_account_id(
+            if let Ok(Some(selection)) = crate::account_switching::select_preferred_account_id(
                 self.code_home(),
                 self.config.account_switching_mode,
                 self.config.api_key_fallback_on_all_accounts_limited,
                 now,
                 current_account_id.as_deref(),
+                &switch_policy,
             ) {
+                let next_account_id = selection.account_id;
                 tracing::info!(
                     from_account_id = %current_account_id.as_deref().unwrap_or(""),
                     to_account_id = %next_account_id,
                     mode = ?self.config.account_switching_mode,
+                    reason = selection.reason.label(),
                     "preselecting preferred account"
                 );
                 match auth::activate_account(self.code_home(), &next_account_id) {
                     Ok(()) => {
+                        crate::account_switching::record_switch(
+                            self.code_home(),
+                            current_account_id.as_deref(),
+                            &next_account_id,
+                            selection.reason,
+                            selection.detail,
+                            now,
+                        );
                         if let Some(manager) = auth_manager.as_ref() {
                             manager.reload();
                         }
@@ -1182,6 +1274,21 @@ impl ModelClient {
             }
             let payload_body = serde_json::to_string(&payload_json)?;
 
+            if let Some(traffic) = self.traffic.as_ref().filter(|traffic| traffic.is_replay()) {
+                let stream = traffic.replay(TrafficWireApi::Responses, &payload_json)?;
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                tokio::spawn(process_sse(
+                    stream,
+                    tx_event,
+                    self.provider.stream_idle_timeout(),
+                    Arc::clone(&self.debug_logger),
+                    request_id.clone(),
+                    self.otel_event_manager.clone(),
+                    Arc::new(RwLock::new(StreamCheckpoint::default())),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+
             let mut auth_refresh_error: Option<RefreshTokenError> = None;
 
             // Always fetch the latest auth in case a prior attempt refreshed the token.
@@ -1336,6 +1443,15 @@ impl ModelClient {
 
                     // spawn task to process SSE
                     let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                    let stream = match self.traffic.as_ref() {
+                        Some(traffic) => traffic.record(
+                            TrafficWireApi::Responses,
+                            &self.provider.get_full_url(&auth),
+                            &payload_json,
+                            stream,
+                        ),
+                        None => stream.boxed(),
+                    };
                     let debug_logger = Arc::clone(&self.debug_logger);
                     let request_id_clone = request_id.clone();
                     let otel_event_manager = self.otel_event_manager.clone();
@@ -1503,6 +1619,7 @@ impl ModelClient {
                                     self.config.account_switching_mode,
                                     now,
                                     Some(current_account_id.as_str()),
+                                    &switch_policy,
                                 )
                             {
                                 if should_record_usage_limit {
@@ -1558,6 +1675,19 @@ impl ModelClient {
                                         "failed to activate account after rate limit"
                                     );
                                 } else {
+                                    let reason = if switch_reason == "http_429" {
+                                        AccountSwitchReason::RateLimited
+                                    } else {
+                                        AccountSwitchReason::UsageLimitReached
+                                    };
+                                    crate::account_switching::record_switch(
+                                        self.code_home(),
+                                        Some(current_account_id.as_str()),
+                                        &next_account_id,
+                                        reason,
+                                        Some(switch_reason.to_string()),
+                                        now,
+                                    );
                                     if let Some(manager) = auth_manager.as_ref() {
                                         manager.reload();
                                     }
@@ -1626,6 +1756,7 @@ impl ModelClient {
                                     self.config.account_switching_mode,
                                     now,
                                     Some(current_account_id.as_str()),
+                                    &switch_policy,
                                 )
                             {
                                 tracing::info!(
@@ -1657,6 +1788,14 @@ impl ModelClient {
                                         "failed to activate account after unauthorized"
                                     );
                                 } else {
+                                    crate::account_switching::record_switch(
+                                        self.code_home(),
+                                        Some(current_account_id.as_str()),
+                                        &next_account_id,
+                                        AccountSwitchReason::AuthInvalid,
+                                        None,
+                                        now,
+                                    );
                                     if let Some(manager) = auth_manager.as_ref() {
                                         manager.reload();
                                     }
@@ -1934,6 +2073,7 @@ impl ModelClient {
 
         let auth_manager = self.auth_manager.clone();
         let mut rate_limit_switch_state = crate::account_switching::RateLimitSwitchState::default();
+        let switch_policy = self.account_switch_policy();
         let mut unauthorized_refresh_attempts: u8 = 0;
         const MAX_UNAUTHORIZED_REFRESH_RETRIES: u8 = 1;
 
@@ -2107,6 +2247,7 @@ impl ModelClient {
                             self.config.account_switching_mode,
                             now,
                             Some(current_account_id.as_str()),
+                            &switch_policy,
                         )
                     {
                         tracing::info!(
@@ -2122,6 +2263,14 @@ impl ModelClient {
                                 "failed to activate account after rate limit during compact"
                             );
                         } else {
+                            crate::account_switching::record_switch(
+                                self.code_home(),
+                                Some(current_account_id.as_str()),
+                                &next_account_id,
+                                AccountSwitchReason::RateLimited,
+                                Some("compact".to_string()),
+                                now,
+                            );
                             if let Some(manager) = auth_manager.as_ref() {
                                 manager.reload();
                             }
@@ -2168,6 +2317,7 @@ impl ModelClient {
                             self.config.account_switching_mode,
                             now,
                             Some(current_account_id.as_str()),
+                            &switch_policy,
                         )
                     {
                         tracing::info!(
@@ -2183,6 +2333,14 @@ impl ModelClient {
                                 "failed to activate account after unauthorized during compact"
                             );
                         } else {
+                            crate::account_switching::record_switch(
+                                self.code_home(),
+                                Some(current_account_id.as_str()),
+                                &next_account_id,
+                                AccountSwitchReason::AuthInvalid,
+                                Some("compact".to_string()),
+                                now,
+                            );
                             if let Some(manager) = auth_manager.as_ref() {
                                 manager.reload();
                             }
@@ -161,52 +161,63 @@ impl Prompt {
         })
     }
 
-    pub(crate) fn get_formatted_input(&self) -> Vec<ResponseItem> {
-        let mut input_with_instructions =
-            Vec::with_capacity(self.input.len() + self.status_items.len() + 3);
-        if self.include_additional_instructions {
-            let developer_text = self.additional_instructions().into_owned();
-            input_with_instructions.push(ResponseItem::Message {
+    /// Developer, environment and AGENTS.md messages sent ahead of the
+    /// conversation input. Environment context and user instructions are
+    /// skipped when the input already carries them.
+    pub(crate) fn formatted_prefix(&self) -> Vec<ResponseItem> {
+        let mut prefix = Vec::new();
+        if !self.include_additional_instructions {
+            return prefix;
+        }
+
+        let developer_text = self.additional_instructions().into_owned();
+        prefix.push(ResponseItem::Message {
+            id: None,
+            role: "developer".to_string(),
+            content: vec![ContentItem::InputText { text: developer_text }], end_turn: None, phase: None});
+        for message in &self.prepend_developer_messages {
+            let trimmed = message.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            prefix.push(ResponseItem::Message {
                 id: None,
                 role: "developer".to_string(),
-                content: vec![ContentItem::InputText { text: developer_text }], end_turn: None, phase: None});
-            for message in &self.prepend_developer_messages {
-                let trimmed = message.trim();
-                if trimmed.is_empty() {
-                    continue;
-                }
-                input_with_instructions.push(ResponseItem::Message {
+                content: vec![ContentItem::InputText {
+                    text: trimmed.to_string(),
+                }], end_turn: None, phase: None});
+        }
+        if let Some(ec) = self.get_formatted_environment_context() {
+            let has_environment_context = self.input.iter().any(|item| {
+                matches!(item, ResponseItem::Message { role, content, .. }
+                    if role == "user"
+                        && content.iter().any(|c| matches!(c,
+                            ContentItem::InputText { text } if text.contains(ENVIRONMENT_CONTEXT_START.trim())
+                        )))
+            });
+            if !has_environment_context {
+                prefix.push(ResponseItem::Message {
                     id: None,
-                    role: "developer".to_string(),
-                    content: vec![ContentItem::InputText {
-                        text: trimmed.to_string(),
-                    }], end_turn: None, phase: None});
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText { text: ec }], end_turn: None, phase: None});
             }
-            if let Some(ec) = self.get_formatted_environment_context() {
-                let has_environment_context = self.input.iter().any(|item| {
-                    matches!(item, ResponseItem::Message { role, content, .. }
-                        if role == "user"
-                            && content.iter().any(|c| matches!(c,
-                                ContentItem::InputText { text } if text.contains(ENVIRONMENT_CONTEXT_START.trim())
-                            )))
-                });
-                if !has_environment_context {
-                    input_with_instructions.push(ResponseItem::Message {
-                        id: None,
-                        role: "user".to_string(),
-                        content: vec![ContentItem::InputText { text: ec }], end_turn: None, phase: None});
-                }
-            }
-            if let Some(ui) = self.get_formatted_user_instructions() {
-                let has_user_instructions = self.input.iter().any(|item| {
-                    matches!(item, ResponseItem::Message { role, content, .. }
-                        if role == "user" && UserInstructions::is_user_instructions(content))
-                });
-                if !has_user_instructions {
-                    input_with_instructions.push(ui);
-                }
+        }
+        if let Some(ui) = self.get_formatted_user_instructions() {
+            let has_user_instructions = self.input.iter().any(|item| {
+                matches!(item, ResponseItem::Message { role, content, .. }
+                    if role == "user" && UserInstructions::is_user_instructions(content))
+            });
+            if !has_user_instructions {
+                prefix.push(ui);
             }
         }
+        prefix
+    }
+
+    pub(crate) fn get_formatted_input(&self) -> Vec<ResponseItem> {
+        let mut input_with_instructions =
+            Vec::with_capacity(self.input.len() + self.status_items.len() + 3);
+        input_with_instructions.extend(self.formatted_prefix());
         // Deduplicate function call outputs before adding to input
         let mut seen_call_ids = std::collections::HashSet::new();
         for item in &self.input {
@@ -223,11 +223,19 @@ fn to_proto_sandbox_policy(policy: SandboxPolicy) -> ProtoSandboxPolicy {
     }
 }
 
-fn to_proto_review_decision(decision: ReviewDecision) -> ProtoReviewDecision {
+fn to_proto_review_decision(decision: &ReviewDecision) -> ProtoReviewDecision {
     match decision {
-        ReviewDecision::Approved => ProtoReviewDecision::Approved,
+        ReviewDecision::Approved | ReviewDecision::ApprovedHunks { .. } => {
+            ProtoReviewDecision::Approved
+        }
         ReviewDecision::ApprovedForSession => ProtoReviewDecision::ApprovedForSession,
+        ReviewDecision::ApprovedWithEdits { command } => ProtoReviewDecision::ApprovedWithEdits {
+            command: command.clone(),
+        },
         ReviewDecision::Denied => ProtoReviewDecision::Denied,
+        ReviewDecision::DeniedWithFeedback { feedback } => ProtoReviewDecision::DeniedWithFeedback {
+            feedback: feedback.clone(),
+        },
         ReviewDecision::Abort => ProtoReviewDecision::Abort,
     }
 }
@@ -968,6 +976,7 @@ use crate::environment_context::{
     EnvironmentContextTracker,
     ViewportDimensions,
 };
+use crate::user_instructions::ScopedInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::config::{persist_model_selection, Config};
 use crate::timeboxed_exec_guidance::{
@@ -1008,7 +1017,9 @@ use crate::slash_commands::get_enabled_agents;
 use crate::dry_run_guard::{analyze_command, DryRunAnalysis, DryRunDisposition, DryRunGuardState};
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
+use crate::project_doc::ScopedDocTracker;
 use crate::project_doc::get_user_instructions;
+use crate::project_doc::paths_touched_by;
 use crate::skills::loader::load_skills;
 use crate::project_features::{ProjectCommand, ProjectHook, ProjectHooks};
 use crate::protocol::AgentMessageDeltaEvent;
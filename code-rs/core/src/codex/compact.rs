@@ -19,6 +19,7 @@ use code_protocol::protocol::CompactionCheckpointWarningEvent;
 use crate::protocol::InputItem;
 use crate::protocol::TaskCompleteEvent;
 use crate::truncate::truncate_middle;
+use crate::user_instructions::ScopedInstructions;
 use crate::util::backoff;
 use askama::Template;
 use code_protocol::models::ContentItem;
@@ -91,7 +92,11 @@ pub fn collect_compaction_snippets(items: &[ResponseItem]) -> Vec<CompactionSnip
             let Some(text) = content_items_to_text(content) else {
                 continue;
             };
-            if role == "user" && is_session_prefix_message(&text) {
+            if role == "user"
+                && (is_session_prefix_message(&text)
+                    || ScopedInstructions::is_scoped_instructions(content))
+            {
+                // Re-added from the session when the compacted history is built.
                 continue;
             }
             let truncated = truncate_for_compact(text, COMPACT_TEXT_CONTENT_MAX_BYTES);
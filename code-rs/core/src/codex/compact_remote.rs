@@ -161,6 +161,14 @@ async fn run_remote_compact_task_inner(
         }
     };
 
+    // The server's summary may drop nested AGENTS.md messages; put back any
+    // that are missing so the rules keep applying.
+    let mut new_history = new_history;
+    for message in sess.scoped_doc_messages() {
+        if !new_history.contains(&message) {
+            new_history.push(message);
+        }
+    }
     sess.replace_history(new_history.clone());
     {
         let mut state = sess.state.lock().unwrap();
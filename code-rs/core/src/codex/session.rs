@@ -372,6 +372,8 @@ pub(crate) struct Session {
     /// Pending browser screenshots to include in the next model request
     #[allow(dead_code)]
     pub(super) pending_browser_screenshots: Mutex<Vec<PathBuf>>,
+    /// Nested AGENTS.md files loaded as turns reach their directories.
+    pub(super) scoped_docs: Mutex<ScopedDocTracker>,
     /// Track the last system status to detect changes
     pub(super) last_system_status: Mutex<Option<String>>,
     /// Track the last screenshot path and hash to detect changes
@@ -888,6 +890,38 @@ fn trim_sub_id_sequence_map(
         "trimmed long-lived sequence map to cap memory growth"
     );
 }
+/// Index of the user message that starts the `num_turns`-th most recent turn,
+/// or `None` when the history holds fewer turns than that.
+pub(super) fn rollback_cut_index(history: &[ResponseItem], num_turns: usize) -> Option<usize> {
+    if num_turns == 0 {
+        return None;
+    }
+    let turn_starts: Vec<usize> = history
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| match item {
+            ResponseItem::Message { role, content, .. } if role == "user" => {
+                let starts_turn = content.iter().any(|c| match c {
+                    ContentItem::InputText { text } => {
+                        !text.trim().is_empty()
+                            && matches!(
+                                code_protocol::protocol::InputMessageKind::from(("user", text.as_str())),
+                                code_protocol::protocol::InputMessageKind::User
+                            )
+                    }
+                    _ => false,
+                });
+                starts_turn.then_some(idx)
+            }
+            _ => None,
+        })
+        .collect();
+    turn_starts
+        .len()
+        .checked_sub(num_turns)
+        .map(|first_dropped| turn_starts[first_dropped])
+}
+
 impl Session {
     pub(super) fn set_task(&self, agent: AgentTask) {
         let mut state = self.state.lock().unwrap();
@@ -923,6 +957,96 @@ impl Session {
         state.history.replace(items);
     }
 
+    /// Token breakdown of what the next turn would send, for `/context`.
+    pub(super) fn inspect_context(
+        &self,
+        turn_context: &TurnContext,
+    ) -> crate::protocol::ContextInspectionEvent {
+        let prompt = Prompt {
+            input: self.state.lock().unwrap().history.contents(),
+            user_instructions: turn_context.user_instructions.clone(),
+            environment_context: Some(EnvironmentContext::new(
+                Some(turn_context.cwd.clone()),
+                Some(turn_context.approval_policy),
+                Some(turn_context.sandbox_policy.clone()),
+                Some(self.user_shell.clone()),
+            )),
+            base_instructions_override: turn_context.base_instructions.clone(),
+            prepend_developer_messages: turn_context.demo_developer_message.clone().into_iter().collect(),
+            model_descriptions: self.model_descriptions.clone(),
+            ..Default::default()
+        };
+        let instructions = prompt.get_full_instructions(turn_context.client.default_model_family());
+        let prefix = prompt.formatted_prefix();
+        crate::context_inspector::inspect(&crate::context_inspector::ContextSources {
+            instructions: &instructions,
+            prefix: &prefix,
+            history: &prompt.input,
+            context_window: self.client.get_model_context_window(),
+        })
+    }
+
+    /// Apply `/context` edits to the history and return a notice describing
+    /// the outcome. The edited history is persisted so a resumed session
+    /// picks it up.
+    pub(super) async fn edit_context(&self, edits: &[crate::protocol::ContextEdit]) -> String {
+        let outcome = {
+            let mut state = self.state.lock().unwrap();
+            if state.current_task.is_some() {
+                return "Context can't be edited while a turn is running; try again when it finishes."
+                    .to_string();
+            }
+            let outcome = crate::context_inspector::apply_edits(state.history.take_contents(), edits);
+            state.history.replace(outcome.history.clone());
+            outcome
+        };
+        let notice = outcome.notice();
+        if outcome.changed() {
+            let rollout_item = RolloutItem::Compacted(code_protocol::protocol::CompactedItem {
+                message: notice.clone(),
+                replacement_history: Some(outcome.history),
+            });
+            self.persist_rollout_items(&[rollout_item]).await;
+        }
+        notice
+    }
+
+    /// Drop the last `num_turns` user turns from the live history and record
+    /// the cut in the rollout so resumed sessions see the same history.
+    pub(super) async fn rollback_turns(&self, num_turns: u32) -> Result<(), String> {
+        if num_turns == 0 {
+            return Err("numTurns must be at least 1".to_string());
+        }
+        let kept = {
+            let mut state = self.state.lock().unwrap();
+            if state.current_task.is_some() {
+                return Err(
+                    "Thread can't be rolled back while a turn is running; try again when it finishes."
+                        .to_string(),
+                );
+            }
+            let history = state.history.contents();
+            let Some(cut) = rollback_cut_index(&history, num_turns as usize) else {
+                return Err(format!("Thread has fewer than {num_turns} turns to roll back"));
+            };
+            let kept: Vec<ResponseItem> = history[..cut].to_vec();
+            state.history.replace(kept.clone());
+            kept
+        };
+        let noun = if num_turns == 1 { "turn" } else { "turns" };
+        self.persist_rollout_items(&[
+            RolloutItem::Compacted(code_protocol::protocol::CompactedItem {
+                message: format!("Rolled back {num_turns} {noun}"),
+                replacement_history: Some(kept),
+            }),
+            RolloutItem::EventMsg(code_protocol::protocol::EventMsg::ThreadRolledBack(
+                code_protocol::protocol::ThreadRolledBackEvent { num_turns },
+            )),
+        ])
+        .await;
+        Ok(())
+    }
+
     pub fn remove_task(&self, sub_id: &str) {
         let mut state = self.state.lock().unwrap();
         if let Some(agent) = &state.current_task {
@@ -1187,7 +1311,7 @@ impl Session {
         &self,
         sub_id: String,
         call_id: String,
-        action: &ApplyPatchAction,
+        changes: HashMap<PathBuf, FileChange>,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
@@ -1196,7 +1320,7 @@ impl Session {
             &sub_id,
             EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
                 call_id: call_id.clone(),
-                changes: convert_apply_patch_to_protocol(action),
+                changes,
                 reason,
                 grant_root,
             }),
@@ -1290,6 +1414,43 @@ impl Session {
 
     }
 
+    /// Load nested AGENTS.md files for directories the given tool calls
+    /// touched and record them ahead of the next request. Each directory is
+    /// only considered once per session.
+    pub(super) async fn record_scoped_docs(&self, cwd: &Path, items: &[ResponseItem]) {
+        let paths: Vec<PathBuf> = items
+            .iter()
+            .flat_map(|item| paths_touched_by(item, cwd))
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+        let docs = self.scoped_docs.lock().unwrap().docs_for_paths(&paths);
+        if docs.is_empty() {
+            return;
+        }
+        for doc in &docs {
+            info!("loaded nested AGENTS.md from {}", doc.directory.display());
+        }
+        let messages: Vec<ResponseItem> = docs
+            .into_iter()
+            .map(|doc| ScopedInstructions::from(doc).into())
+            .collect();
+        self.record_conversation_items(&messages).await;
+    }
+
+    /// Messages for every nested AGENTS.md loaded so far.
+    pub(super) fn scoped_doc_messages(&self) -> Vec<ResponseItem> {
+        self.scoped_docs
+            .lock()
+            .unwrap()
+            .loaded()
+            .iter()
+            .cloned()
+            .map(|doc| ScopedInstructions::from(doc).into())
+            .collect()
+    }
+
     /// Clean up old screenshots and system status messages from conversation history
     /// This is called when a new user message arrives to keep history manageable
     pub(super) async fn cleanup_old_status_items(&self) {
@@ -1591,6 +1752,8 @@ impl Session {
                 .into(),
             );
         }
+        // Nested docs loaded earlier in the session survive compaction.
+        items.extend(self.scoped_doc_messages());
 
         let env_context = EnvironmentContext::new(
             Some(turn_context.cwd.clone()),
@@ -1843,17 +2006,25 @@ impl Session {
 
         for item in rollout_items {
             match item {
+                // Ghost snapshots are replayed to the UI separately and never
+                // reach the model.
+                RolloutItem::ResponseItem(ResponseItem::GhostSnapshot { .. }) => {}
                 RolloutItem::ResponseItem(response_item) => {
                     history.push(response_item.clone());
                     process_rollout_env_item(&mut replay_ctx, response_item);
                 }
                 RolloutItem::Compacted(compacted) => {
-                    let snippets = collect_compaction_snippets(&history);
-                    history = build_compacted_history(
-                        self.build_initial_context(turn_context),
-                        &snippets,
-                        &compacted.message,
-                    );
+                    // Edits from `/context` carry the exact history to restore.
+                    if let Some(replacement) = &compacted.replacement_history {
+                        history = replacement.clone();
+                    } else {
+                        let snippets = collect_compaction_snippets(&history);
+                        history = build_compacted_history(
+                            self.build_initial_context(turn_context),
+                            &snippets,
+                            &compacted.message,
+                        );
+                    }
                 }
                 RolloutItem::Event(recorded_event) => {
                     if let code_protocol::protocol::EventMsg::UserMessage(user_msg_event) = &recorded_event.msg {
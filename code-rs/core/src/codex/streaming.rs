@@ -26,6 +26,8 @@ use crate::account_switching::RateLimitSwitchState;
 use crate::agent_tool::external_agent_command_exists;
 use crate::protocol::McpListToolsResponseEvent;
 use code_app_server_protocol::AuthMode as AppAuthMode;
+use code_otel::spans::OtelSpan;
+use code_otel::spans::attr;
 use code_protocol::models::FunctionCallOutputContentItem;
 use std::collections::HashMap;
 
@@ -60,9 +62,13 @@ impl AgentTask {
             let sess_clone = Arc::clone(&sess);
             let tc_clone = Arc::clone(&turn_context);
             let sub_clone = sub_id.clone();
-            tokio::spawn(async move {
-                run_agent(sess_clone, tc_clone, sub_clone, input).await;
-            })
+            let otel = sess.client.get_otel_event_manager();
+            tokio::spawn(traced_turn(
+                otel,
+                sub_id.clone(),
+                "regular",
+                run_agent(sess_clone, tc_clone, sub_clone, input),
+            ))
             .abort_handle()
         };
         Self {
@@ -83,15 +89,13 @@ impl AgentTask {
             let sess_clone = Arc::clone(&sess);
             let tc_clone = Arc::clone(&turn_context);
             let sub_clone = sub_id.clone();
-            tokio::spawn(async move {
-                compact::run_compact_task(
-                    sess_clone,
-                    tc_clone,
-                    sub_clone,
-                    input,
-                )
-                .await;
-            })
+            let otel = sess.client.get_otel_event_manager();
+            tokio::spawn(traced_turn(
+                otel,
+                sub_id.clone(),
+                "compact",
+                compact::run_compact_task(sess_clone, tc_clone, sub_clone, input),
+            ))
             .abort_handle()
         };
         Self {
@@ -112,9 +116,13 @@ impl AgentTask {
             let sess_clone = Arc::clone(&sess);
             let tc_clone = Arc::clone(&turn_context);
             let sub_clone = sub_id.clone();
-            tokio::spawn(async move {
-                run_agent(sess_clone, tc_clone, sub_clone, input).await;
-            })
+            let otel = sess.client.get_otel_event_manager();
+            tokio::spawn(traced_turn(
+                otel,
+                sub_id.clone(),
+                "review",
+                run_agent(sess_clone, tc_clone, sub_clone, input),
+            ))
             .abort_handle()
         };
         Self {
@@ -144,6 +152,23 @@ impl AgentTask {
     }
 }
 
+/// Run a task inside a `codex.turn` span and record its wall time. Aborting
+/// the task drops the span, which ends it.
+async fn traced_turn(
+    otel: Option<OtelEventManager>,
+    sub_id: String,
+    kind: &'static str,
+    task: impl Future<Output = ()>,
+) {
+    let Some(otel) = otel else {
+        task.await;
+        return;
+    };
+    let started = Instant::now();
+    otel.turn_span(&sub_id, kind).in_span(task).await;
+    otel.turn_completed(kind, started.elapsed());
+}
+
 pub(super) async fn submission_loop(
     mut session_id: Uuid,
     config: Arc<Config>,
@@ -663,6 +688,7 @@ pub(super) async fn submission_loop(
                         remote.refresh_remote_models().await;
                     });
                 }
+                let scoped_docs = ScopedDocTracker::new(&cwd, config.project_doc_max_bytes);
                 let mut new_session = Arc::new(Session {
                     id: session_id,
                     client,
@@ -692,6 +718,7 @@ pub(super) async fn submission_loop(
                     user_shell: default_shell,
                     show_raw_agent_reasoning: config.show_raw_agent_reasoning,
                     pending_browser_screenshots: Mutex::new(Vec::new()),
+                    scoped_docs: Mutex::new(scoped_docs),
                     last_system_status: Mutex::new(None),
                     last_screenshot_info: Mutex::new(None),
                     time_budget: Mutex::new(config.max_run_seconds.map(|secs| {
@@ -741,6 +768,11 @@ pub(super) async fn submission_loop(
                             st.history = ConversationHistory::new();
                             st.history.record_items(reconstructed.iter());
                         }
+                        sess_arc
+                            .scoped_docs
+                            .lock()
+                            .unwrap()
+                            .restore_from_history(&reconstructed);
                         if let Some(selected_tools) =
                             extract_mcp_tool_selection_from_history(&reconstructed)
                         {
@@ -797,11 +829,22 @@ pub(super) async fn submission_loop(
                     let history_snapshot_value = restored_history_snapshot
                         .as_ref()
                         .and_then(|snapshot| serde_json::to_value(snapshot).ok());
+                    let ghost_snapshots = restored_items
+                        .iter()
+                        .flatten()
+                        .filter_map(|item| match item {
+                            RolloutItem::ResponseItem(ResponseItem::GhostSnapshot {
+                                ghost_commit,
+                            }) => Some(ghost_commit.clone()),
+                            _ => None,
+                        })
+                        .collect();
                     let event = sess_arc.make_event(
                         &sub.id,
                         EventMsg::ReplayHistory(crate::protocol::ReplayHistoryEvent {
                             items,
                             history_snapshot: history_snapshot_value,
+                            ghost_snapshots,
                         }),
                     );
                     if let Err(e) = tx_event.send(event).await {
@@ -1027,6 +1070,39 @@ pub(super) async fn submission_loop(
                 }
             }
 
+            Op::RecordGhostSnapshot { ghost_commit } => {
+                let Some(sess) = sess.as_ref() else {
+                    send_no_session_event(sub.id).await;
+                    continue;
+                };
+                let cwd = sess.get_cwd().to_path_buf();
+                let code_home = sess.client.code_home().to_path_buf();
+                let session = sess.session_uuid().to_string();
+                let recorder = sess.clone_rollout_recorder();
+                tokio::spawn(async move {
+                    let commit = ghost_commit.clone();
+                    let saved = tokio::task::spawn_blocking(move || {
+                        code_git_tooling::save_snapshot_ref(&cwd, &session, &commit)?;
+                        crate::housekeeping::register_snapshot_repo(&code_home, &cwd);
+                        Ok::<_, code_git_tooling::GitToolingError>(())
+                    })
+                    .await;
+                    match saved {
+                        Ok(Ok(())) => {}
+                        Ok(Err(err)) => warn!("failed to save snapshot ref: {err}"),
+                        Err(err) => warn!("snapshot ref task failed: {err}"),
+                    }
+                    if let Some(recorder) = recorder {
+                        let item = RolloutItem::ResponseItem(ResponseItem::GhostSnapshot {
+                            ghost_commit,
+                        });
+                        if let Err(e) = recorder.record_items(&[item]).await {
+                            warn!("failed to record ghost snapshot: {e}");
+                        }
+                    }
+                });
+            }
+
             Op::RunProjectCommand { name } => {
                 let sess = match sess.as_ref() {
                     Some(sess) => sess,
@@ -1139,6 +1215,50 @@ pub(super) async fn submission_loop(
 
                 sess.send_event(event).await;
             }
+            Op::InspectContext => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let turn_context = sess.make_turn_context();
+                let inspection = sess.inspect_context(&turn_context);
+                sess.send_event(sess.make_event(&sub.id, EventMsg::ContextInspection(inspection)))
+                    .await;
+            }
+            Op::EditContext { edits } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let notice = sess.edit_context(&edits).await;
+                let turn_context = sess.make_turn_context();
+                let mut inspection = sess.inspect_context(&turn_context);
+                inspection.notice = Some(notice);
+                sess.send_event(sess.make_event(&sub.id, EventMsg::ContextInspection(inspection)))
+                    .await;
+            }
+            Op::ThreadRollback { num_turns } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let msg = match sess.rollback_turns(num_turns).await {
+                    Ok(()) => EventMsg::ThreadRolledBack(crate::protocol::ThreadRolledBackEvent {
+                        num_turns,
+                    }),
+                    Err(message) => EventMsg::Error(ErrorEvent { message }),
+                };
+                sess.send_event(sess.make_event(&sub.id, msg)).await;
+            }
             Op::ListSkills => {
                 let sess = match sess.as_ref() {
                     Some(sess) => Arc::clone(sess),
@@ -1420,10 +1540,17 @@ async fn spawn_review_thread(
         final_output_json_schema: None,
     });
 
+    // Structured targets may arrive without the legacy prompt text; derive it
+    // from the target so every caller gets the same instructions.
+    let task_prompt = if review_request.prompt.trim().is_empty() {
+        crate::review_prompts::review_prompt(&review_request.target)
+    } else {
+        review_request.prompt.clone()
+    };
     let review_prompt_text = format!(
         "{}\n\n---\n\nNow, here's your task: {}",
         REVIEW_PROMPT.trim(),
-        review_request.prompt.trim()
+        task_prompt.trim()
     );
     let review_input = vec![InputItem::Text {
         text: review_prompt_text,
@@ -1950,6 +2077,11 @@ async fn run_agent(sess: Arc<Session>, turn_context: Arc<TurnContext>, sub_id: S
                         // outputs appear in the correct order in conversation history.
                         sess.record_conversation_items(&items_to_record_in_conversation_history)
                             .await;
+                        sess.record_scoped_docs(
+                            &turn_context.cwd,
+                            &items_to_record_in_conversation_history,
+                        )
+                        .await;
                     }
                 }
 
@@ -2330,6 +2462,7 @@ async fn run_turn(
                                 current_account_id.as_str(),
                                 current_auth_mode,
                                 blocked_until,
+                                &sess.client.account_switch_policy(),
                             ) {
                                 Ok(Some(next_account_id)) => {
                                     let next_label = auth_accounts::find_account(
@@ -2654,6 +2787,9 @@ async fn run_turn(
 
                 if retries < max_retries {
                     retries += 1;
+                    if let Some(otel) = sess.client.get_otel_event_manager() {
+                        otel.stream_retry("stream");
+                    }
                     let (delay, retry_eta) = match e {
                         CodexErr::Stream(_, Some(ref retry_after), _) => {
                             let eta = format_retry_eta(&retry_after);
@@ -3059,15 +3195,24 @@ struct TurnLatencyGuard<'a> {
     sess: &'a Session,
     attempt_req: u64,
     active: bool,
+    span: OtelSpan,
 }
 
 impl<'a> TurnLatencyGuard<'a> {
     fn new(sess: &'a Session, attempt_req: u64, prompt: &Prompt) -> Self {
         sess.turn_latency_request_scheduled(attempt_req, prompt);
+        let span = OtelSpan::start(
+            "codex.model_request",
+            vec![
+                attr("attempt", attempt_req),
+                attr("model", sess.client.get_model()),
+            ],
+        );
         Self {
             sess,
             attempt_req,
             active: true,
+            span,
         }
     }
 
@@ -3078,6 +3223,11 @@ impl<'a> TurnLatencyGuard<'a> {
         self
             .sess
             .turn_latency_request_completed(self.attempt_req, output_item_count, token_usage);
+        if let Some(usage) = token_usage {
+            self.span.set_attribute(attr("tokens.input", usage.input_tokens));
+            self.span.set_attribute(attr("tokens.output", usage.output_tokens));
+        }
+        self.span.end();
         self.active = false;
     }
 
@@ -3085,6 +3235,8 @@ impl<'a> TurnLatencyGuard<'a> {
         if !self.active {
             return;
         }
+        self.span.record_error(note.as_deref().unwrap_or("request failed"));
+        self.span.end();
         self.sess.turn_latency_request_failed(self.attempt_req, note);
         self.active = false;
     }
@@ -3093,6 +3245,8 @@ impl<'a> TurnLatencyGuard<'a> {
 impl Drop for TurnLatencyGuard<'_> {
     fn drop(&mut self) {
         if self.active {
+            self.span.record_error("dropped_without_outcome");
+            self.span.end();
             self
                 .sess
                 .turn_latency_request_failed(self.attempt_req, Some("dropped_without_outcome".to_string()));
@@ -3347,6 +3501,19 @@ async fn try_run_turn(
                 }
 
                 if let Some(usage) = token_usage.as_ref() {
+                    let ledger_model = latest_response_model
+                        .clone()
+                        .unwrap_or_else(|| requested_model.clone());
+                    if let Some(entry) = sess.client.usage_ledger_entry(usage, &ledger_model, None) {
+                        let usage_home = sess.client.code_home().to_path_buf();
+                        spawn_usage_task(move || {
+                            if let Err(err) =
+                                account_usage::record_usage_ledger_entry(&usage_home, &entry)
+                            {
+                                warn!("Failed to record usage ledger entry: {err}");
+                            }
+                        });
+                    }
                     if let Some(ctx) = account_usage_context(sess) {
                         let usage_home = ctx.code_home.clone();
                         let usage_account = ctx.account_id.clone();
@@ -3566,8 +3733,9 @@ async fn handle_response_item(
             };
 
             let exec_params = to_exec_params(params, sess);
+            let span = exec_span(&exec_params.command);
             Some(
-            handle_container_exec_with_params(
+            span.in_span(handle_container_exec_with_params(
                 exec_params,
                 sess,
                 turn_diff_tracker,
@@ -3576,7 +3744,7 @@ async fn handle_response_item(
                 seq_hint,
                 output_index,
                 attempt_req,
-            )
+            ))
             .await,
             )
         }
@@ -3740,6 +3908,72 @@ async fn handle_function_call(
     seq_hint: Option<u64>,
     output_index: Option<u32>,
     attempt_req: u64,
+) -> ResponseInputItem {
+    let span = OtelSpan::start(
+        "codex.tool_call",
+        vec![attr("tool.name", name.as_str()), attr("tool.call_id", call_id.as_str())],
+    );
+    let started = Instant::now();
+    let tool_name = name.clone();
+    let output = span
+        .instrument(dispatch_function_call(
+            sess,
+            turn_diff_tracker,
+            sub_id,
+            name,
+            arguments,
+            call_id,
+            seq_hint,
+            output_index,
+            attempt_req,
+        ))
+        .await;
+    let success = response_input_succeeded(&output);
+    if !success {
+        span.record_error("tool call failed");
+    }
+    span.end();
+    if let Some(otel) = sess.client.get_otel_event_manager() {
+        otel.tool_call_completed(&tool_name, success, started.elapsed());
+    }
+    output
+}
+
+/// Span for a shell command. Only the program name is recorded; arguments may
+/// carry secrets.
+fn exec_span(command: &[String]) -> OtelSpan {
+    let program = command
+        .first()
+        .map(|program| {
+            Path::new(program)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| program.clone())
+        })
+        .unwrap_or_default();
+    OtelSpan::start("codex.exec", vec![attr("process.executable.name", program)])
+}
+
+fn response_input_succeeded(item: &ResponseInputItem) -> bool {
+    match item {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.success.unwrap_or(true),
+        ResponseInputItem::McpToolCallOutput { result, .. } => result
+            .as_ref()
+            .is_ok_and(|result| !result.is_error.unwrap_or(false)),
+        ResponseInputItem::Message { .. } | ResponseInputItem::CustomToolCallOutput { .. } => true,
+    }
+}
+
+async fn dispatch_function_call(
+    sess: &Session,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    name: String,
+    arguments: String,
+    call_id: String,
+    seq_hint: Option<u64>,
+    output_index: Option<u32>,
+    attempt_req: u64,
 ) -> ResponseInputItem {
     let ctx = ToolCallCtx::new(sub_id.clone(), call_id.clone(), seq_hint, output_index);
     match name.as_str() {
@@ -3750,15 +3984,29 @@ async fn handle_function_call(
                     return *output;
                 }
             };
-            handle_container_exec_with_params(params, sess, turn_diff_tracker, sub_id, call_id, seq_hint, output_index, attempt_req)
-                .await
+            let span = exec_span(&params.command);
+            span.in_span(handle_container_exec_with_params(
+                params,
+                sess,
+                turn_diff_tracker,
+                sub_id,
+                call_id,
+                seq_hint,
+                output_index,
+                attempt_req,
+            ))
+            .await
         }
         "update_plan" => handle_update_plan(sess, &ctx, arguments).await,
         "request_user_input" => handle_request_user_input(sess, &ctx, arguments).await,
         // agent tool
         "agent" => handle_agent_tool(sess, &ctx, arguments).await,
         // unified browser tool
-        "browser" => handle_browser_tool(sess, &ctx, arguments).await,
+        "browser" => {
+            OtelSpan::start("codex.browser_action", Vec::new())
+                .in_span(handle_browser_tool(sess, &ctx, arguments))
+                .await
+        }
         "web_fetch" => handle_web_fetch(sess, &ctx, arguments).await,
         "image_view" => handle_image_view(sess, &ctx, arguments).await,
         "wait" => handle_wait(sess, &ctx, arguments).await,
@@ -3773,7 +4021,15 @@ async fn handle_function_call(
             match sess.mcp_connection_manager.parse_tool_name(&name) {
                 Some((server, tool_name)) => {
                     // Tool timeouts are derived from per-server config; no per-call override here.
-                    handle_mcp_tool_call(sess, &ctx, server, tool_name, arguments).await
+                    let span = OtelSpan::start(
+                        "codex.mcp_tool_call",
+                        vec![
+                            attr("mcp.server", server.as_str()),
+                            attr("mcp.tool", tool_name.as_str()),
+                        ],
+                    );
+                    span.in_span(handle_mcp_tool_call(sess, &ctx, server, tool_name, arguments))
+                        .await
                 }
                 None => {
                     // Unknown function: reply with structured failure so the model can adapt.
@@ -7195,10 +7451,120 @@ pub(crate) async fn handle_agent_tool(
                 Err(e) => agent_tool_failure(ctx, format!("Failed to encode list arguments: {}", e)),
             }
         }
+        "message" => {
+            let Some(message_opts) = req.message.take() else {
+                return agent_tool_failure(ctx, "action=message requires a 'message' object");
+            };
+            let batch_id = match message_opts.batch_id {
+                Some(batch) if !batch.trim().is_empty() => batch,
+                _ => return agent_tool_failure(ctx, "action=message requires 'message.batch_id'"),
+            };
+            let text = match message_opts.text {
+                Some(text) if !text.trim().is_empty() => text,
+                _ => return agent_tool_failure(ctx, "action=message requires 'message.text'"),
+            };
+            let agent_id = message_opts.agent_id.filter(|id| !id.trim().is_empty());
+            let mut message_event = serde_json::Map::new();
+            message_event.insert("batch_id".to_string(), serde_json::Value::String(batch_id.clone()));
+            if let Some(ref id) = agent_id {
+                message_event.insert("agent_id".to_string(), serde_json::Value::String(id.clone()));
+            }
+            message_event.insert("text".to_string(), serde_json::Value::String(text.clone()));
+            let event_payload = serde_json::json!({
+                "action": "message",
+                "message": serde_json::Value::Object(message_event),
+            });
+            handle_agent_message(sess, ctx, batch_id, agent_id, text, event_payload).await
+        }
+        "board" => {
+            let board_opts = req.board.take();
+            let batch_id = board_opts
+                .as_ref()
+                .and_then(|opts| opts.batch_id.clone())
+                .filter(|batch| !batch.trim().is_empty());
+            let limit = board_opts.and_then(|opts| opts.limit).unwrap_or(50).max(1);
+            let event_payload = serde_json::json!({
+                "action": "board",
+                "board": { "batch_id": batch_id, "limit": limit },
+            });
+            handle_agent_board(sess, ctx, batch_id, limit, event_payload).await
+        }
         other => agent_tool_failure(ctx, format!("Unsupported agent action: {}", other)),
     }
 }
 
+async fn handle_agent_message(
+    sess: &Session,
+    ctx: &ToolCallCtx,
+    batch_id: String,
+    agent_id: Option<String>,
+    text: String,
+    event_payload: serde_json::Value,
+) -> ResponseInputItem {
+    let call_id_clone = ctx.call_id.clone();
+    execute_custom_tool(
+        sess,
+        ctx,
+        "agent".to_string(),
+        Some(event_payload),
+        || async move {
+            let manager = AGENT_MANAGER.read().await;
+            let (body, success) =
+                match manager.post_guidance(&batch_id, agent_id.as_deref(), &text) {
+                    Ok(recipients) => (
+                        format!(
+                            "Posted guidance to {} on the shared board. Agents see it the next time they read the board.",
+                            recipients.join(", ")
+                        ),
+                        true,
+                    ),
+                    Err(err) => (err, false),
+                };
+            ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone,
+                output: FunctionCallOutputPayload {
+                    body: code_protocol::models::FunctionCallOutputBody::Text(body),
+                    success: Some(success)},
+            }
+        },
+    ).await
+}
+
+async fn handle_agent_board(
+    sess: &Session,
+    ctx: &ToolCallCtx,
+    batch_id: Option<String>,
+    limit: usize,
+    event_payload: serde_json::Value,
+) -> ResponseInputItem {
+    let call_id_clone = ctx.call_id.clone();
+    execute_custom_tool(
+        sess,
+        ctx,
+        "agent".to_string(),
+        Some(event_payload),
+        || async move {
+            let manager = AGENT_MANAGER.read().await;
+            let (body, success) = match manager.board_snapshot(batch_id.as_deref(), limit) {
+                Ok((entries, claims)) => {
+                    let summary = serde_json::json!({
+                        "entries": entries.iter().map(|entry| entry.display_line()).collect::<Vec<_>>(),
+                        "claims": claims,
+                    });
+                    (summary.to_string(), true)
+                }
+                Err(err) => (format!("Failed to read the agent board: {err}"), false),
+            };
+            ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone,
+                output: FunctionCallOutputPayload {
+                    body: code_protocol::models::FunctionCallOutputBody::Text(body),
+                    success: Some(success)},
+            }
+        },
+    ).await
+}
+
 fn resolve_agent_command_for_check(
     model: &str,
     cfg: Option<&crate::config_types::AgentConfig>,
@@ -9170,6 +9536,15 @@ async fn handle_container_exec_with_params(
                         output_index,
                         sequence_number: seq_hint,
                     };
+                    let changes = match run.applied_changes.clone() {
+                        Some(applied) => {
+                            if let Some(ctx) = hook_ctx.apply_patch.as_mut() {
+                                ctx.changes = applied.clone();
+                            }
+                            applied
+                        }
+                        None => changes,
+                    };
                     let begin_event = EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                         call_id: call_id.clone(),
                         auto_approved: run.auto_approved,
@@ -9240,6 +9615,12 @@ async fn handle_container_exec_with_params(
                         }
                         content.push_str(&format!("stderr: {}", run.stderr));
                     }
+                    if let Some(note) = run.review_note {
+                        if !content.is_empty() {
+                            content.push('\n');
+                        }
+                        content.push_str(&note);
+                    }
                     if let Some(summary) = run.harness_summary_json {
                         if !summary.is_empty() {
                             if !content.is_empty() {
@@ -9285,6 +9666,8 @@ async fn handle_container_exec_with_params(
     };
     let command_for_display = params.command.clone();
     let harness_summary_json: Option<String> = None;
+    let mut approval_note: Option<String> = None;
+    let mut edited_command: Option<Vec<String>> = None;
 
     let sandbox_type = match safety {
         SafetyCheck::AutoApprove {
@@ -9303,7 +9686,7 @@ async fn handle_container_exec_with_params(
                 manager.tool_decision(
                     tool_name,
                     call_id.as_str(),
-                    to_proto_review_decision(decision_for_log),
+                    to_proto_review_decision(&decision_for_log),
                     source,
                 );
             }
@@ -9325,7 +9708,7 @@ async fn handle_container_exec_with_params(
                 manager.tool_decision(
                     tool_name,
                     call_id.as_str(),
-                    to_proto_review_decision(decision),
+                    to_proto_review_decision(&decision),
                     ToolDecisionSource::User,
                 );
             }
@@ -9339,13 +9722,19 @@ async fn handle_container_exec_with_params(
                         None,
                     ));
                 }
-                ReviewDecision::Denied | ReviewDecision::Abort => {
-                    return ResponseInputItem::FunctionCallOutput {
-                        call_id,
-                        output: FunctionCallOutputPayload {
-                            body: code_protocol::models::FunctionCallOutputBody::Text("exec command rejected by user".to_string()),
-                            success: None},
-                    };
+                ReviewDecision::ApprovedWithEdits { command } if !command.is_empty() => {
+                    approval_note = Some(edited_command_note(&command));
+                    edited_command = Some(command);
+                }
+                ReviewDecision::DeniedWithFeedback { feedback } => {
+                    return exec_rejected_by_user(call_id, Some(&feedback));
+                }
+                // Hunk-level decisions only apply to patches; never run a command on one.
+                ReviewDecision::Denied
+                | ReviewDecision::Abort
+                | ReviewDecision::ApprovedWithEdits { .. }
+                | ReviewDecision::ApprovedHunks { .. } => {
+                    return exec_rejected_by_user(call_id, None);
                 }
             }
             // No sandboxing is applied because the user has given
@@ -9364,6 +9753,19 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    // The user may have rewritten the command in the approval modal; run and
+    // display exactly what they approved.
+    let (params, command_for_display) = match edited_command {
+        Some(command) => (
+            ExecParams {
+                command: command.clone(),
+                ..params
+            },
+            command,
+        ),
+        None => (params, command_for_display),
+    };
+
     let exec_command_context = ExecCommandContext {
         sub_id: sub_id.clone(),
         call_id: call_id.clone(),
@@ -9626,6 +10028,9 @@ async fn handle_container_exec_with_params(
                     content.push_str(harness);
                 }
             }
+            if let Some(note) = approval_note.as_ref() {
+                content = format!("{note}\n{content}");
+            }
 
             sess
                 .run_hooks_for_exec_event(
@@ -9660,6 +10065,10 @@ async fn handle_container_exec_with_params(
         call_id,
         call_id
     );
+    let header = match approval_note.as_ref() {
+        Some(note) => format!("{note}\n{header}"),
+        None => header,
+    };
     let msg = if tail.is_empty() {
         header
     } else {
@@ -9668,6 +10077,32 @@ async fn handle_container_exec_with_params(
     ResponseInputItem::FunctionCallOutput { call_id: call_id.clone(), output: FunctionCallOutputPayload {body: code_protocol::models::FunctionCallOutputBody::Text(msg), success: Some(true)} }
 }
 
+/// Tool output for a command the user declined. Any feedback they typed is
+/// passed through so the model can change course instead of retrying a
+/// near-identical command.
+fn exec_rejected_by_user(call_id: String, feedback: Option<&str>) -> ResponseInputItem {
+    let text = match feedback.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(feedback) => format!(
+            "exec command rejected by user. The user said:\n{feedback}\nAdjust your approach based on this feedback instead of retrying the same command."
+        ),
+        None => "exec command rejected by user".to_string(),
+    };
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            body: code_protocol::models::FunctionCallOutputBody::Text(text),
+            success: None,
+        },
+    }
+}
+
+fn edited_command_note(command: &[String]) -> String {
+    format!(
+        "Note: the user edited this command before approving it. It ran as: {}",
+        crate::util::strip_bash_lc_and_escape(command)
+    )
+}
+
 #[allow(dead_code)]
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
@@ -9776,13 +10211,13 @@ async fn handle_sandbox_error(
         manager.tool_decision(
             tool_name,
             call_id.as_str(),
-            to_proto_review_decision(decision),
+            to_proto_review_decision(&decision),
             ToolDecisionSource::User,
         );
     }
 
-    match decision {
-        ReviewDecision::Approved => {}
+    let params = match decision {
+        ReviewDecision::Approved => params,
         ReviewDecision::ApprovedForSession => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future executions skip the sandbox directly.
@@ -9791,15 +10226,22 @@ async fn handle_sandbox_error(
                 ApprovedCommandMatchKind::Exact,
                 None,
             ));
+            params
         }
-        ReviewDecision::Denied | ReviewDecision::Abort => {
+        ReviewDecision::ApprovedWithEdits { command } if !command.is_empty() => ExecParams {
+            command,
+            ..params
+        },
+        ReviewDecision::DeniedWithFeedback { feedback } => {
+            return exec_rejected_by_user(call_id, Some(&feedback));
+        }
+        // Hunk-level decisions only apply to patches; never run a command on one.
+        ReviewDecision::Denied
+        | ReviewDecision::Abort
+        | ReviewDecision::ApprovedWithEdits { .. }
+        | ReviewDecision::ApprovedHunks { .. } => {
             // Fall through to original failure handling.
-            return ResponseInputItem::FunctionCallOutput {
-                call_id,
-                output: FunctionCallOutputPayload {
-                    body: code_protocol::models::FunctionCallOutputBody::Text("exec command rejected by user".to_string()),
-                    success: None},
-            };
+            return exec_rejected_by_user(call_id, None);
         }
     };
 
@@ -12428,6 +12870,7 @@ mod command_guard_detection_tests {
 mod cleanup_tests {
     use super::*;
     use super::super::session::prune_history_items;
+    use super::super::session::rollback_cut_index;
     use code_protocol::protocol::{
         BROWSER_SNAPSHOT_CLOSE_TAG,
         BROWSER_SNAPSHOT_OPEN_TAG,
@@ -12455,6 +12898,29 @@ mod cleanup_tests {
             }], end_turn: None, phase: None}
     }
 
+    #[test]
+    fn rollback_cut_index_skips_context_messages() {
+        let baseline = make_text_message(&format!(
+            "{}\n{{}}\n{}",
+            ENVIRONMENT_CONTEXT_OPEN_TAG, ENVIRONMENT_CONTEXT_CLOSE_TAG
+        ));
+        let delta = make_text_message(&format!(
+            "{}\n{{\"cwd\":\"/repo\"}}\n{}",
+            ENVIRONMENT_CONTEXT_DELTA_OPEN_TAG, ENVIRONMENT_CONTEXT_DELTA_CLOSE_TAG
+        ));
+        let history = vec![
+            baseline,
+            make_text_message("first"),
+            delta,
+            make_text_message("second"),
+        ];
+
+        assert_eq!(rollback_cut_index(&history, 1), Some(3));
+        assert_eq!(rollback_cut_index(&history, 2), Some(1));
+        assert_eq!(rollback_cut_index(&history, 3), None);
+        assert_eq!(rollback_cut_index(&history, 0), None);
+    }
+
     #[test]
     fn prune_history_retains_recent_env_items() {
         let baseline1 = make_text_message(&format!(
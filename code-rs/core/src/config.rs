@@ -2,6 +2,7 @@ use crate::codex::ApprovedCommandPattern;
 use crate::protocol::ApprovedCommandMatchKind;
 use crate::config_profile::ConfigProfile;
 use crate::config_types::AgentConfig;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use crate::config_types::AutoDriveSettings;
 use crate::config_types::AutoDriveModelRoutingEntry;
@@ -26,6 +27,7 @@ use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::config_types::TextVerbosity;
 use crate::config_types::Tui;
+use crate::keybindings::KeyBindings;
 use crate::config_types::UriBasedFileOpener;
 use crate::config_types::ConfirmGuardConfig;
 use crate::config_types::Personality;
@@ -340,6 +342,9 @@ pub struct Config {
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
 
+    /// Effective TUI key chords: `[tui.keybindings]` merged over the defaults.
+    pub tui_keybindings: KeyBindings,
+
     /// Cadence (in requests) for running the Auto Drive observer thread.
     pub auto_drive_observer_cadence: u32,
 
@@ -464,6 +469,15 @@ pub struct Config {
     /// Strategy for picking which account to use when auto-switching is enabled.
     pub account_switching_mode: crate::config_types::AccountSwitchingMode,
 
+    /// Rules pinning repositories or git remotes to specific accounts.
+    pub account_pins: Vec<crate::config_types::AccountPinRule>,
+
+    /// Per-account usage budgets keyed by account id.
+    pub account_budgets: BTreeMap<String, crate::config_types::AccountBudget>,
+
+    /// Record/replay of raw model traffic.
+    pub model_traffic: crate::config_types::ModelTrafficConfig,
+
     /// GitHub integration configuration.
     pub github: GithubConfig,
 
@@ -780,6 +794,18 @@ pub struct ConfigToml {
     #[serde(default)]
     pub account_switching_mode: Option<crate::config_types::AccountSwitchingMode>,
 
+    /// Rules pinning repositories or git remotes to specific accounts.
+    #[serde(default)]
+    pub account_pins: Vec<crate::config_types::AccountPinRule>,
+
+    /// Per-account usage budgets keyed by account id.
+    #[serde(default)]
+    pub account_budgets: BTreeMap<String, crate::config_types::AccountBudget>,
+
+    /// Record/replay of raw model traffic.
+    #[serde(default)]
+    pub model_traffic: crate::config_types::ModelTrafficConfig,
+
     /// Nested tools section for feature toggles
     pub tools: Option<ToolsToml>,
 
@@ -1324,6 +1350,16 @@ impl Config {
             }
         }
 
+        // Keybindings only matter to the TUI, so a bad entry must not stop
+        // `exec`, `app-server` or `mcp-server` from loading the config.
+        let tui_keybindings = match cfg.tui.as_ref() {
+            Some(tui) => KeyBindings::resolve(&tui.keybindings).unwrap_or_else(|err| {
+                tracing::warn!("ignoring [tui.keybindings], using the defaults: {err}");
+                KeyBindings::default()
+            }),
+            None => KeyBindings::default(),
+        };
+
         // Default review model when not set in config; allow CLI override to take precedence.
         let review_model = override_review_model
             .or(config_profile.review_model.clone())
@@ -1619,6 +1655,9 @@ impl Config {
             auto_switch_accounts_on_rate_limit,
             api_key_fallback_on_all_accounts_limited,
             account_switching_mode,
+            account_pins: cfg.account_pins.clone(),
+            account_budgets: cfg.account_budgets.clone(),
+            model_traffic: cfg.model_traffic.clone(),
             github: cfg.github.unwrap_or_default(),
             validation: cfg.validation.unwrap_or_default(),
             subagent_commands: cfg
@@ -1635,6 +1674,7 @@ impl Config {
                 .as_ref()
                 .map(|t| t.notifications.clone())
                 .unwrap_or_default(),
+            tui_keybindings,
             auto_drive_observer_cadence: cfg.auto_drive_observer_cadence.unwrap_or(5),
             otel: {
                 let t: OtelConfigToml = cfg.otel.unwrap_or_default();
@@ -1840,6 +1880,78 @@ persistence = "none"
         assert_eq!(parsed_bool.auto_upgrade_enabled, Some(true));
     }
 
+    #[test]
+    fn custom_validation_tools_deserialize() {
+        use crate::config_types::CustomValidationTool;
+        use crate::config_types::ValidationCategory;
+        use crate::config_types::ValidationOutputFormat;
+
+        let cfg = r#"
+[validation]
+patch_harness = true
+
+[[validation.custom]]
+name = "clippy"
+command = ["cargo", "clippy", "--message-format=short"]
+globs = ["*.rs"]
+workspace = true
+timeout_seconds = 120
+format = "regex"
+pattern = '^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*)$'
+
+[[validation.custom]]
+name = "semgrep"
+command = ["semgrep", "--sarif", "--output", "{report}", "{files}"]
+category = "stylistic"
+format = "sarif"
+"#;
+        let parsed =
+            toml::from_str::<ConfigToml>(cfg).expect("custom validators should deserialize");
+        let custom = parsed.validation.expect("validation section").custom;
+        assert_eq!(
+            custom,
+            vec![
+                CustomValidationTool {
+                    name: "clippy".to_string(),
+                    command: vec![
+                        "cargo".to_string(),
+                        "clippy".to_string(),
+                        "--message-format=short".to_string(),
+                    ],
+                    globs: vec!["*.rs".to_string()],
+                    category: ValidationCategory::Functional,
+                    timeout_seconds: Some(120),
+                    workspace: true,
+                    format: ValidationOutputFormat::Regex,
+                    pattern: Some(
+                        r"^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*)$"
+                            .to_string(),
+                    ),
+                },
+                CustomValidationTool {
+                    name: "semgrep".to_string(),
+                    command: vec![
+                        "semgrep".to_string(),
+                        "--sarif".to_string(),
+                        "--output".to_string(),
+                        "{report}".to_string(),
+                        "{files}".to_string(),
+                    ],
+                    globs: Vec::new(),
+                    category: ValidationCategory::Stylistic,
+                    timeout_seconds: None,
+                    workspace: false,
+                    format: ValidationOutputFormat::Sarif,
+                    pattern: None,
+                },
+            ]
+        );
+
+        let unknown_format =
+            "[[validation.custom]]\nname = \"x\"\ncommand = [\"x\"]\nformat = \"junit\"\n";
+        assert!(toml::from_str::<ConfigToml>(unknown_format).is_err());
+    }
+
     #[test]
     fn tool_output_max_bytes_defaults_and_overrides() -> std::io::Result<()> {
         let code_home = TempDir::new()?;
@@ -1865,6 +1977,25 @@ persistence = "none"
         Ok(())
     }
 
+    #[test]
+    fn invalid_keybindings_fall_back_to_defaults() -> std::io::Result<()> {
+        let code_home = TempDir::new()?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tui.keybindings]
+toggle_reasoning = "j"
+"#,
+        )
+        .expect("TOML should deserialize");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            code_home.path().to_path_buf(),
+        )?;
+        assert_eq!(config.tui_keybindings, KeyBindings::default());
+        Ok(())
+    }
+
     #[test]
     fn load_default_with_cli_overrides_applies_cli_model_override() -> std::io::Result<()> {
         let _code_home_guard = EnvVarGuard::new("CODE_HOME");
@@ -1,4 +1,6 @@
 use crate::config::resolve_code_path_for_read;
+use crate::config_types::AccountBudget;
+use crate::config_types::AccountPinRule;
 use crate::config_types::SubagentCommandConfig;
 use anyhow::Result;
 use std::path::Path;
@@ -338,6 +340,97 @@ async fn write_new_or_append(
 
 
 // Internal helper to support persist_* variants above.
+/// Replace the top-level `[[account_pins]]` entries with `pins`, preserving
+/// the rest of the file. An empty slice removes the array.
+pub async fn persist_account_pins(code_home: &Path, pins: &[AccountPinRule]) -> Result<()> {
+    let config_path = code_home.join(CONFIG_TOML_FILE);
+    let read_path = resolve_code_path_for_read(code_home, Path::new(CONFIG_TOML_FILE));
+    let mut doc = match tokio::fs::read_to_string(&read_path).await {
+        Ok(s) => s.parse::<DocumentMut>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if pins.is_empty() {
+                return Ok(());
+            }
+            tokio::fs::create_dir_all(code_home).await?;
+            DocumentMut::new()
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if pins.is_empty() {
+        doc.as_table_mut().remove("account_pins");
+    } else {
+        let mut entries = toml_edit::ArrayOfTables::new();
+        for pin in pins {
+            let mut t = toml_edit::Table::new();
+            t["account"] = toml_edit::value(pin.account.clone());
+            if let Some(path) = &pin.path {
+                t["path"] = toml_edit::value(path.clone());
+            }
+            if let Some(remote) = &pin.remote {
+                t["remote"] = toml_edit::value(remote.clone());
+            }
+            if pin.strict {
+                t["strict"] = toml_edit::value(true);
+            }
+            entries.push(t);
+        }
+        doc["account_pins"] = toml_edit::Item::ArrayOfTables(entries);
+    }
+
+    let tmp_file = NamedTempFile::new_in(code_home)?;
+    tokio::fs::write(tmp_file.path(), doc.to_string()).await?;
+    tmp_file.persist(config_path)?;
+    Ok(())
+}
+
+/// Write `[account_budgets."<account_id>"]`; a budget with no caps removes
+/// the entry.
+pub async fn persist_account_budget(
+    code_home: &Path,
+    account_id: &str,
+    budget: &AccountBudget,
+) -> Result<()> {
+    let config_path = code_home.join(CONFIG_TOML_FILE);
+    let read_path = resolve_code_path_for_read(code_home, Path::new(CONFIG_TOML_FILE));
+    let is_empty = budget.primary_percent.is_none() && budget.secondary_percent.is_none();
+    let mut doc = match tokio::fs::read_to_string(&read_path).await {
+        Ok(s) => s.parse::<DocumentMut>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if is_empty {
+                return Ok(());
+            }
+            tokio::fs::create_dir_all(code_home).await?;
+            DocumentMut::new()
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if is_empty {
+        if !remove_toml_edit_segments(&mut doc, &["account_budgets", account_id]) {
+            return Ok(());
+        }
+    } else {
+        let mut t = toml_edit::Table::new();
+        if let Some(percent) = budget.primary_percent {
+            t["primary_percent"] = toml_edit::value(percent);
+        }
+        if let Some(percent) = budget.secondary_percent {
+            t["secondary_percent"] = toml_edit::value(percent);
+        }
+        apply_toml_edit_override_segments(
+            &mut doc,
+            &["account_budgets", account_id],
+            toml_edit::Item::Table(t),
+        );
+    }
+
+    let tmp_file = NamedTempFile::new_in(code_home)?;
+    tokio::fs::write(tmp_file.path(), doc.to_string()).await?;
+    tmp_file.persist(config_path)?;
+    Ok(())
+}
+
 async fn persist_overrides_with_behavior(
     code_home: &Path,
     profile: Option<&str>,
@@ -1023,6 +1116,47 @@ model_reasoning_effort = "high"
     }
 
     // Test helper moved to bottom per review guidance.
+    /// Verifies account pins and budgets round-trip through `ConfigToml`.
+    #[tokio::test]
+    async fn account_pins_and_budgets_round_trip() {
+        let tmpdir = tempdir().expect("tmp");
+        let code_home = tmpdir.path();
+        tokio::fs::write(code_home.join(CONFIG_TOML_FILE), "model = \"o3\"\n")
+            .await
+            .expect("seed write");
+
+        let pins = vec![AccountPinRule {
+            account: "work@example.com".to_string(),
+            path: None,
+            remote: Some("*github.com:acme/*".to_string()),
+            strict: true,
+        }];
+        persist_account_pins(code_home, &pins)
+            .await
+            .expect("persist pins");
+        let budget = AccountBudget {
+            primary_percent: Some(80.0),
+            secondary_percent: None,
+        };
+        persist_account_budget(code_home, "acct-1", &budget)
+            .await
+            .expect("persist budget");
+
+        let parsed: crate::config::ConfigToml =
+            toml::from_str(&read_config(code_home).await).expect("valid config");
+        assert_eq!(parsed.account_pins, pins);
+        assert_eq!(parsed.account_budgets.get("acct-1"), Some(&budget));
+
+        persist_account_budget(code_home, "acct-1", &AccountBudget::default())
+            .await
+            .expect("clear budget");
+        persist_account_pins(code_home, &[]).await.expect("clear pins");
+        let contents = read_config(code_home).await;
+        assert!(!contents.contains("acct-1"), "{contents}");
+        assert!(!contents.contains("account_pins"), "{contents}");
+        assert!(contents.contains("model = \"o3\""));
+    }
+
     async fn read_config(code_home: &Path) -> String {
         let p = code_home.join(CONFIG_TOML_FILE);
         tokio::fs::read_to_string(p).await.unwrap_or_default()
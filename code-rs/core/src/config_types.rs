@@ -3,6 +3,7 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -45,6 +46,65 @@ impl Default for AccountSwitchingMode {
     }
 }
 
+/// Pins a repository to a specific account. A rule matches when every
+/// criterion it sets matches the current project; a rule without criteria
+/// never matches.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct AccountPinRule {
+    /// Account id, label, or ChatGPT email of the account to use.
+    pub account: String,
+    /// Glob (or plain prefix) matched against the project's git root,
+    /// e.g. `~/work/**`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Glob matched against any of the project's git remote URLs,
+    /// e.g. `*github.com:acme/*`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// When true, never switch away from the pinned account, even on limits.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Hard usage caps for one account, as a percentage of each rate-limit
+/// window. An account at or over a cap is treated as limited by the
+/// switching engine.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, JsonSchema)]
+pub struct AccountBudget {
+    /// Cap for the short (5 hour) window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_percent: Option<f64>,
+    /// Cap for the long (weekly) window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_percent: Option<f64>,
+}
+
+/// Record or replay raw model traffic (see `model_traffic.rs`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModelTrafficMode {
+    #[default]
+    Off,
+    /// Save every request body and raw SSE response.
+    Record,
+    /// Serve responses from a recording instead of the network.
+    Replay,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+pub struct ModelTrafficConfig {
+    #[serde(default)]
+    pub mode: ModelTrafficMode,
+    /// Recording directory. Defaults to `$CODE_HOME/sessions/traffic/<session id>`
+    /// when recording; required when replaying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// Fail a replayed request whose hash matches no recorded exchange
+    /// instead of serving the next recording in order.
+    #[serde(default)]
+    pub strict: bool,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum OtelHttpProtocol {
@@ -515,6 +575,10 @@ pub struct ValidationConfig {
     /// Per-tool enable flags (unset implies enabled).
     #[serde(default)]
     pub tools: ValidationTools,
+
+    /// Project-specific validators declared with `[[validation.custom]]`.
+    #[serde(default)]
+    pub custom: Vec<CustomValidationTool>,
 }
 
 impl Default for ValidationConfig {
@@ -525,6 +589,7 @@ impl Default for ValidationConfig {
             timeout_seconds: None,
             groups: ValidationGroups::default(),
             tools: ValidationTools::default(),
+            custom: Vec::new(),
         }
     }
 }
@@ -567,8 +632,66 @@ pub struct ValidationTools {
     pub golangci_lint: Option<bool>,
 }
 
+/// A validator declared under `[[validation.custom]]`. It runs on touched
+/// files matching `globs`, and its output is turned into harness findings by
+/// `format`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomValidationTool {
+    /// Name used in findings and matched against `tools_allowlist`.
+    pub name: String,
+
+    /// Command template. `{files}` expands to one argument per matching file,
+    /// `{file}` runs the command once per file, `{root}` is the directory the
+    /// command runs in, and `{report}` is a temporary file that is parsed
+    /// instead of stdout.
+    pub command: Vec<String>,
+
+    /// Globs (relative to the workspace root) selecting the touched files
+    /// that trigger this tool. Empty matches every touched file.
+    #[serde(default)]
+    pub globs: Vec<String>,
+
+    #[serde(default = "default_custom_validation_category")]
+    pub category: ValidationCategory,
+
+    /// Overrides `validation.timeout_seconds` for this tool.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+
+    /// Run in the real workspace with the patch overlaid instead of a scratch
+    /// copy of the touched files. Needed by tools that read project
+    /// configuration or resolve imports, such as clippy.
+    #[serde(default)]
+    pub workspace: bool,
+
+    #[serde(default)]
+    pub format: ValidationOutputFormat,
+
+    /// Problem matcher for `format = "regex"`. Named groups `file`, `line`,
+    /// `column`, `severity`, `code` and `message` are picked up.
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+fn default_custom_validation_category() -> ValidationCategory {
+    ValidationCategory::Functional
+}
+
+/// How the output of a custom validation tool is parsed.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationOutputFormat {
+    /// Every non-empty output line is a finding when the tool exits non-zero.
+    #[default]
+    Lines,
+    Regex,
+    Sarif,
+    Checkstyle,
+}
+
 /// Category groupings for validation checks.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 pub enum ValidationCategory {
     Functional,
     Stylistic,
@@ -757,6 +880,49 @@ pub struct Tui {
     /// Capture Git ghost snapshots for `/undo` and snapshot-scoped review flows.
     #[serde(default = "default_true")]
     pub git_snapshots_enabled: bool,
+
+    /// Enable vim-style modal editing (normal/insert/visual) in the composer.
+    #[serde(default)]
+    pub vim_mode: bool,
+
+    /// Key chords for named TUI actions (`[tui.keybindings]`). Actions not
+    /// listed keep their default chords.
+    #[serde(default)]
+    pub keybindings: BTreeMap<KeyAction, KeyBindingSpec>,
+}
+
+/// TUI actions that can be rebound under `[tui.keybindings]`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    Submit,
+    Newline,
+    ToggleReasoning,
+    SwitchModel,
+    HistoryPrev,
+    HistoryNext,
+    ScrollPageUp,
+    ScrollPageDown,
+    Interrupt,
+    OpenEditor,
+    HistorySearch,
+}
+
+/// One chord (`"ctrl+g"`) or several (`["ctrl+j", "shift+enter"]`).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeyBindingSpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBindingSpec {
+    pub fn chords(&self) -> Vec<&str> {
+        match self {
+            KeyBindingSpec::One(chord) => vec![chord.as_str()],
+            KeyBindingSpec::Many(chords) => chords.iter().map(String::as_str).collect(),
+        }
+    }
 }
 
 // Important: Provide a manual Default so that when no config file exists and we
@@ -783,6 +949,8 @@ impl Default for Tui {
             auto_review_enabled: true,
             auto_review_symbols_only: false,
             git_snapshots_enabled: true,
+            vim_mode: false,
+            keybindings: BTreeMap::new(),
         }
     }
 }
@@ -0,0 +1,757 @@
+//! Token breakdown behind `/context`: what the next request would carry,
+//! grouped by category, plus the drop/collapse edits a user can apply to the
+//! session history before the next turn.
+//!
+//! Counts are estimates (about four bytes per token and a flat cost per
+//! image). They are good for comparing items and deciding what to compact,
+//! not for billing.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use code_protocol::models::ContentItem;
+use code_protocol::models::FunctionCallOutputBody;
+use code_protocol::models::FunctionCallOutputContentItem;
+use code_protocol::models::FunctionCallOutputPayload;
+use code_protocol::models::LocalShellAction;
+use code_protocol::models::ReasoningItemContent;
+use code_protocol::models::ReasoningItemReasoningSummary;
+use code_protocol::models::ResponseItem;
+use code_protocol::protocol::BROWSER_SNAPSHOT_OPEN_TAG;
+use code_protocol::protocol::ENVIRONMENT_CONTEXT_DELTA_OPEN_TAG;
+use code_protocol::protocol::ENVIRONMENT_CONTEXT_OPEN_TAG;
+
+use crate::protocol::ContextCategory;
+use crate::protocol::ContextCategoryUsage;
+use crate::protocol::ContextEdit;
+use crate::protocol::ContextEditAction;
+use crate::protocol::ContextInspectionEvent;
+use crate::protocol::ContextItemSummary;
+use crate::user_instructions::ScopedInstructions;
+use crate::user_instructions::UserInstructions;
+
+const BYTES_PER_TOKEN: usize = 4;
+/// Rough cost of one high-detail image tile set.
+const IMAGE_TOKEN_ESTIMATE: u64 = 765;
+const MAX_LISTED_ITEMS: usize = 40;
+const LABEL_PREVIEW_CHARS: usize = 60;
+const COLLAPSED_PREVIEW_CHARS: usize = 160;
+/// First line of the summary message built from
+/// `templates/compact/history_bridge.md`.
+const COMPACTION_SUMMARY_HEADER: &str = "[Compaction Summary]";
+const SKILL_OPEN_TAG: &str = "<skill>";
+const SYSTEM_STATUS_MARKER: &str = "== System Status ==";
+const COLLAPSED_MARKER: &str = "collapsed from /context";
+
+const CATEGORY_ORDER: [ContextCategory; 8] = [
+    ContextCategory::Instructions,
+    ContextCategory::AgentsMd,
+    ContextCategory::Skills,
+    ContextCategory::Messages,
+    ContextCategory::ToolOutputs,
+    ContextCategory::Images,
+    ContextCategory::EnvironmentContext,
+    ContextCategory::CompactionSummary,
+];
+
+/// Everything the next request would send ahead of the tool schemas.
+pub(crate) struct ContextSources<'a> {
+    /// Base instructions sent in the `instructions` field.
+    pub instructions: &'a str,
+    /// Messages the prompt prepends to the history (developer prompt,
+    /// environment context and AGENTS.md when the history lacks them).
+    pub prefix: &'a [ResponseItem],
+    pub history: &'a [ResponseItem],
+    pub context_window: Option<u64>,
+}
+
+/// A history entry as shown in the inspector. Tool calls are grouped with
+/// their outputs so dropping one never orphans the other.
+#[derive(Debug)]
+struct Entry {
+    indices: Vec<usize>,
+    category: ContextCategory,
+    label: String,
+    tokens: u64,
+    can_drop: bool,
+    can_collapse: bool,
+}
+
+impl Entry {
+    fn id(&self, history: &[ResponseItem]) -> String {
+        let mut hasher = DefaultHasher::new();
+        for index in &self.indices {
+            serde_json::to_string(&history[*index])
+                .unwrap_or_default()
+                .hash(&mut hasher);
+        }
+        format!("h{}-{:016x}", self.indices[0], hasher.finish())
+    }
+}
+
+pub(crate) fn inspect(sources: &ContextSources<'_>) -> ContextInspectionEvent {
+    let mut items: Vec<ContextItemSummary> = Vec::new();
+    if !sources.instructions.is_empty() {
+        items.push(fixed_item(
+            "instructions",
+            ContextCategory::Instructions,
+            "Base instructions".to_string(),
+            text_tokens(sources.instructions.len()),
+        ));
+    }
+    for (index, item) in sources.prefix.iter().enumerate() {
+        let (category, label) = match item {
+            ResponseItem::Message { role, content, .. } if role == "developer" => {
+                let label = if index == 0 {
+                    "Developer prompt".to_string()
+                } else {
+                    format!("Developer message: {}", preview(&message_text(content)))
+                };
+                (ContextCategory::Instructions, label)
+            }
+            ResponseItem::Message { content, .. } => classify_message("user", content),
+            _ => (ContextCategory::Instructions, "Prompt item".to_string()),
+        };
+        items.push(fixed_item(
+            &format!("p{index}"),
+            category,
+            label,
+            item_tokens(item),
+        ));
+    }
+    for entry in history_entries(sources.history) {
+        items.push(ContextItemSummary {
+            id: entry.id(sources.history),
+            category: entry.category,
+            label: entry.label,
+            tokens: entry.tokens,
+            can_drop: entry.can_drop,
+            can_collapse: entry.can_collapse,
+        });
+    }
+
+    let mut usage: HashMap<ContextCategory, (u64, usize)> = HashMap::new();
+    for item in &items {
+        let slot = usage.entry(item.category).or_default();
+        slot.0 += item.tokens;
+        slot.1 += 1;
+    }
+    let categories = CATEGORY_ORDER
+        .iter()
+        .map(|category| {
+            let (tokens, count) = usage.get(category).copied().unwrap_or_default();
+            ContextCategoryUsage {
+                category: *category,
+                tokens,
+                items: count,
+            }
+        })
+        .collect();
+    let total_tokens = items.iter().map(|item| item.tokens).sum();
+
+    items.sort_by(|a, b| b.tokens.cmp(&a.tokens));
+    items.truncate(MAX_LISTED_ITEMS);
+
+    ContextInspectionEvent {
+        total_tokens,
+        context_window: sources.context_window,
+        categories,
+        largest_items: items,
+        notice: None,
+    }
+}
+
+fn fixed_item(
+    id: &str,
+    category: ContextCategory,
+    label: String,
+    tokens: u64,
+) -> ContextItemSummary {
+    ContextItemSummary {
+        id: id.to_string(),
+        category,
+        label,
+        tokens,
+        can_drop: false,
+        can_collapse: false,
+    }
+}
+
+/// Result of [`apply_edits`].
+#[derive(Debug)]
+pub(crate) struct EditOutcome {
+    pub history: Vec<ResponseItem>,
+    pub dropped: usize,
+    pub collapsed: usize,
+    /// Edits whose id no longer matches the history, or whose action the
+    /// entry does not allow.
+    pub skipped: usize,
+}
+
+impl EditOutcome {
+    pub(crate) fn changed(&self) -> bool {
+        self.dropped > 0 || self.collapsed > 0
+    }
+
+    pub(crate) fn notice(&self) -> String {
+        let mut parts = Vec::new();
+        if self.dropped > 0 {
+            parts.push(format!("dropped {}", plural(self.dropped, "item")));
+        }
+        if self.collapsed > 0 {
+            parts.push(format!("collapsed {}", plural(self.collapsed, "item")));
+        }
+        if self.skipped > 0 {
+            parts.push(format!(
+                "skipped {} that changed since the view was opened",
+                plural(self.skipped, "edit")
+            ));
+        }
+        if parts.is_empty() {
+            return "No context changes applied.".to_string();
+        }
+        let mut notice = parts.join(", ");
+        if let Some(first) = notice.get(..1) {
+            notice = format!("{}{}", first.to_uppercase(), &notice[1..]);
+        }
+        format!("{notice}.")
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Apply drop/collapse edits to `history`. Ids are matched against the
+/// current history, so edits made against an outdated view are skipped.
+pub(crate) fn apply_edits(history: Vec<ResponseItem>, edits: &[ContextEdit]) -> EditOutcome {
+    let entries: HashMap<String, Entry> = history_entries(&history)
+        .into_iter()
+        .map(|entry| (entry.id(&history), entry))
+        .collect();
+
+    let mut dropped_indices: HashSet<usize> = HashSet::new();
+    let mut collapse: Vec<(Vec<usize>, u64)> = Vec::new();
+    let mut dropped = 0;
+    let mut collapsed = 0;
+    let mut skipped = 0;
+    let mut seen: HashSet<&str> = HashSet::new();
+    for edit in edits {
+        if !seen.insert(edit.item_id.as_str()) {
+            continue;
+        }
+        let Some(entry) = entries.get(&edit.item_id) else {
+            skipped += 1;
+            continue;
+        };
+        match edit.action {
+            ContextEditAction::Drop if entry.can_drop => {
+                dropped_indices.extend(entry.indices.iter().copied());
+                dropped += 1;
+            }
+            ContextEditAction::Collapse if entry.can_collapse => {
+                collapse.push((entry.indices.clone(), entry.tokens));
+                collapsed += 1;
+            }
+            _ => skipped += 1,
+        }
+    }
+
+    let mut history = history;
+    for (indices, tokens) in collapse {
+        if indices.iter().any(|index| dropped_indices.contains(index)) {
+            collapsed -= 1;
+            continue;
+        }
+        // For a tool call the output is what gets collapsed; the call stays
+        // so the model still sees what it asked for.
+        let Some(target) = indices.last().copied() else {
+            continue;
+        };
+        collapse_item(&mut history[target], tokens);
+    }
+    let history = history
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !dropped_indices.contains(index))
+        .map(|(_, item)| item)
+        .collect();
+
+    EditOutcome {
+        history,
+        dropped,
+        collapsed,
+        skipped,
+    }
+}
+
+fn collapse_item(item: &mut ResponseItem, tokens: u64) {
+    let placeholder = format!("[{COLLAPSED_MARKER}: ~{tokens} tokens omitted]");
+    match item {
+        ResponseItem::FunctionCallOutput { output, .. } => {
+            *output = FunctionCallOutputPayload {
+                body: FunctionCallOutputBody::Text(placeholder),
+                success: output.success,
+            };
+        }
+        ResponseItem::CustomToolCallOutput { output, .. } => {
+            *output = placeholder;
+        }
+        ResponseItem::Message { role, content, .. } => {
+            let text = message_text(content);
+            let kept: String = text.chars().take(COLLAPSED_PREVIEW_CHARS).collect();
+            let text = if kept.trim().is_empty() {
+                placeholder
+            } else {
+                format!("{} … {placeholder}", kept.trim_end())
+            };
+            let collapsed = if role == "assistant" {
+                ContentItem::OutputText { text }
+            } else {
+                ContentItem::InputText { text }
+            };
+            *content = vec![collapsed];
+        }
+        _ => {}
+    }
+}
+
+fn history_entries(history: &[ResponseItem]) -> Vec<Entry> {
+    let mut output_index: HashMap<&str, usize> = HashMap::new();
+    for (index, item) in history.iter().enumerate() {
+        if let ResponseItem::FunctionCallOutput { call_id, .. }
+        | ResponseItem::CustomToolCallOutput { call_id, .. } = item
+        {
+            output_index.entry(call_id.as_str()).or_insert(index);
+        }
+    }
+
+    let mut consumed: HashSet<usize> = HashSet::new();
+    let mut entries = Vec::new();
+    for (index, item) in history.iter().enumerate() {
+        if consumed.contains(&index) {
+            continue;
+        }
+        let tool_call = match item {
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => Some((call_id.as_str(), format!("{name} {}", preview(arguments)))),
+            ResponseItem::LocalShellCall {
+                call_id: Some(call_id),
+                action: LocalShellAction::Exec(action),
+                ..
+            } => Some((call_id.as_str(), format!("shell {}", preview(&action.command.join(" "))))),
+            ResponseItem::CustomToolCall {
+                name,
+                input,
+                call_id,
+                ..
+            } => Some((call_id.as_str(), format!("{name} {}", preview(input)))),
+            _ => None,
+        };
+        if let Some((call_id, label)) = tool_call {
+            let mut indices = vec![index];
+            let output = output_index.get(call_id).copied().filter(|out| *out > index);
+            if let Some(output) = output {
+                consumed.insert(output);
+                indices.push(output);
+            }
+            let tokens = indices.iter().map(|i| item_tokens(&history[*i])).sum();
+            let collapsed = output.is_some_and(|out| is_collapsed(&history[out]));
+            entries.push(Entry {
+                indices,
+                category: ContextCategory::ToolOutputs,
+                label: label.trim_end().to_string(),
+                tokens,
+                can_drop: true,
+                can_collapse: output.is_some() && !collapsed,
+            });
+            continue;
+        }
+
+        let (category, label, can_drop, can_collapse) = match item {
+            ResponseItem::Message { role, content, .. } => {
+                let (category, label) = classify_message(role, content);
+                let (can_drop, can_collapse) = match category {
+                    // The session-level doc is re-injected by the prompt when
+                    // missing, so only nested docs can be dropped.
+                    ContextCategory::AgentsMd => {
+                        (ScopedInstructions::is_scoped_instructions(content), false)
+                    }
+                    ContextCategory::EnvironmentContext => {
+                        (!is_environment_baseline(content), false)
+                    }
+                    ContextCategory::Messages | ContextCategory::Images => {
+                        (true, !is_collapsed(item))
+                    }
+                    _ => (true, false),
+                };
+                (category, label, can_drop, can_collapse)
+            }
+            ResponseItem::FunctionCallOutput { call_id, .. }
+            | ResponseItem::CustomToolCallOutput { call_id, .. } => (
+                ContextCategory::ToolOutputs,
+                format!("Tool output ({call_id})"),
+                true,
+                !is_collapsed(item),
+            ),
+            ResponseItem::Reasoning { .. } => {
+                (ContextCategory::Messages, "Reasoning".to_string(), true, false)
+            }
+            ResponseItem::WebSearchCall { .. } => {
+                (ContextCategory::ToolOutputs, "Web search".to_string(), true, false)
+            }
+            ResponseItem::CompactionSummary { .. } => (
+                ContextCategory::CompactionSummary,
+                "Compaction summary (remote)".to_string(),
+                false,
+                false,
+            ),
+            _ => continue,
+        };
+        entries.push(Entry {
+            indices: vec![index],
+            category,
+            label,
+            tokens: item_tokens(item),
+            can_drop,
+            can_collapse,
+        });
+    }
+    entries
+}
+
+fn classify_message(role: &str, content: &[ContentItem]) -> (ContextCategory, String) {
+    let text = message_text(content);
+    if role == "developer" || role == "system" {
+        return (
+            ContextCategory::Instructions,
+            format!("Developer message: {}", preview(&text)),
+        );
+    }
+    if role == "user" {
+        if UserInstructions::is_user_instructions(content) {
+            return (ContextCategory::AgentsMd, "AGENTS.md instructions".to_string());
+        }
+        if let Some(scoped) = ScopedInstructions::parse(content) {
+            return (
+                ContextCategory::AgentsMd,
+                format!("AGENTS.md for {}", scoped.directory),
+            );
+        }
+        if text.trim_start().starts_with(SKILL_OPEN_TAG) {
+            let name = between(&text, "<name>", "</name>").unwrap_or("skill");
+            return (ContextCategory::Skills, format!("Skill: {name}"));
+        }
+        if text.contains(ENVIRONMENT_CONTEXT_DELTA_OPEN_TAG) {
+            return (
+                ContextCategory::EnvironmentContext,
+                "Environment context delta".to_string(),
+            );
+        }
+        if text.contains(ENVIRONMENT_CONTEXT_OPEN_TAG) {
+            return (ContextCategory::EnvironmentContext, "Environment context".to_string());
+        }
+        if text.contains(BROWSER_SNAPSHOT_OPEN_TAG) {
+            return (ContextCategory::EnvironmentContext, "Browser snapshot".to_string());
+        }
+        if text.contains(SYSTEM_STATUS_MARKER) {
+            return (ContextCategory::EnvironmentContext, "System status".to_string());
+        }
+        if text.trim_start().starts_with(COMPACTION_SUMMARY_HEADER) {
+            return (ContextCategory::CompactionSummary, "Compaction summary".to_string());
+        }
+    }
+    let speaker = if role == "assistant" { "Assistant" } else { "User" };
+    let images = content
+        .iter()
+        .filter(|item| matches!(item, ContentItem::InputImage { .. }))
+        .count();
+    if images > 0 {
+        let noun = if images == 1 { "image" } else { "images" };
+        let label = format!("{speaker}: {images} {noun} {}", preview(&text));
+        return (ContextCategory::Images, label.trim_end().to_string());
+    }
+    (ContextCategory::Messages, format!("{speaker}: {}", preview(&text)))
+}
+
+fn is_environment_baseline(content: &[ContentItem]) -> bool {
+    let text = message_text(content);
+    text.contains(ENVIRONMENT_CONTEXT_OPEN_TAG) && !text.contains(ENVIRONMENT_CONTEXT_DELTA_OPEN_TAG)
+}
+
+fn is_collapsed(item: &ResponseItem) -> bool {
+    match item {
+        ResponseItem::Message { content, .. } => message_text(content).contains(COLLAPSED_MARKER),
+        ResponseItem::FunctionCallOutput { output, .. } => matches!(
+            &output.body,
+            FunctionCallOutputBody::Text(text) if text.contains(COLLAPSED_MARKER)
+        ),
+        ResponseItem::CustomToolCallOutput { output, .. } => output.contains(COLLAPSED_MARKER),
+        _ => false,
+    }
+}
+
+fn between<'a>(text: &'a str, open: &str, close: &str) -> Option<&'a str> {
+    let start = text.find(open)? + open.len();
+    let end = text[start..].find(close)? + start;
+    Some(text[start..end].trim())
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .filter_map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                Some(text.as_str())
+            }
+            ContentItem::InputImage { .. } => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// First line of `text`, shortened for a one-line label.
+fn preview(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let mut shortened: String = line.chars().take(LABEL_PREVIEW_CHARS).collect();
+    if line.chars().count() > LABEL_PREVIEW_CHARS {
+        shortened.push('…');
+    }
+    shortened
+}
+
+fn text_tokens(bytes: usize) -> u64 {
+    bytes.div_ceil(BYTES_PER_TOKEN) as u64
+}
+
+fn content_tokens(content: &[ContentItem]) -> u64 {
+    content
+        .iter()
+        .map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                text_tokens(text.len())
+            }
+            ContentItem::InputImage { .. } => IMAGE_TOKEN_ESTIMATE,
+        })
+        .sum()
+}
+
+fn item_tokens(item: &ResponseItem) -> u64 {
+    match item {
+        ResponseItem::Message { content, .. } => content_tokens(content),
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => text_tokens(name.len() + arguments.len()),
+        ResponseItem::FunctionCallOutput { output, .. } => match &output.body {
+            FunctionCallOutputBody::Text(text) => text_tokens(text.len()),
+            FunctionCallOutputBody::ContentItems(items) => items
+                .iter()
+                .map(|item| match item {
+                    FunctionCallOutputContentItem::InputText { text } => text_tokens(text.len()),
+                    FunctionCallOutputContentItem::InputImage { .. } => IMAGE_TOKEN_ESTIMATE,
+                })
+                .sum(),
+        },
+        ResponseItem::CustomToolCall { name, input, .. } => text_tokens(name.len() + input.len()),
+        ResponseItem::CustomToolCallOutput { output, .. } => text_tokens(output.len()),
+        ResponseItem::Reasoning {
+            summary,
+            content,
+            encrypted_content,
+            ..
+        } => {
+            let summary_bytes: usize = summary
+                .iter()
+                .map(|ReasoningItemReasoningSummary::SummaryText { text }| text.len())
+                .sum();
+            let content_bytes: usize = content
+                .iter()
+                .flatten()
+                .map(|item| match item {
+                    ReasoningItemContent::ReasoningText { text }
+                    | ReasoningItemContent::Text { text } => text.len(),
+                })
+                .sum();
+            let encrypted_bytes = encrypted_content.as_ref().map_or(0, String::len);
+            text_tokens(summary_bytes + content_bytes + encrypted_bytes)
+        }
+        ResponseItem::CompactionSummary { encrypted_content } => {
+            text_tokens(encrypted_content.len())
+        }
+        ResponseItem::LocalShellCall { .. } | ResponseItem::WebSearchCall { .. } => {
+            text_tokens(serde_json::to_string(item).map_or(0, |json| json.len()))
+        }
+        ResponseItem::GhostSnapshot { .. } | ResponseItem::Other => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn user(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+            end_turn: None,
+            phase: None,
+        }
+    }
+
+    fn call(call_id: &str) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: r#"{"command":["cargo","test"]}"#.to_string(),
+            call_id: call_id.to_string(),
+        }
+    }
+
+    fn output(call_id: &str, text: &str) -> ResponseItem {
+        ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload::from_text(text.to_string()),
+        }
+    }
+
+    fn history() -> Vec<ResponseItem> {
+        vec![
+            UserInstructions {
+                directory: "/repo".to_string(),
+                text: "Be terse.".to_string(),
+            }
+            .into(),
+            user("<environment_context>\n<cwd>/repo</cwd>\n</environment_context>"),
+            user("Run the tests"),
+            call("c1"),
+            output("c1", &"test output line\n".repeat(200)),
+            user("[Compaction Summary]\n\nKey takeaways:\nnone"),
+        ]
+    }
+
+    fn id_for(event: &ContextInspectionEvent, category: ContextCategory) -> String {
+        event
+            .largest_items
+            .iter()
+            .find(|item| item.category == category)
+            .map(|item| item.id.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn groups_history_by_category() {
+        let history = history();
+        let event = inspect(&ContextSources {
+            instructions: "You are a coding agent.",
+            prefix: &[],
+            history: &history,
+            context_window: Some(272_000),
+        });
+
+        let tools = event
+            .categories
+            .iter()
+            .find(|usage| usage.category == ContextCategory::ToolOutputs)
+            .unwrap();
+        assert_eq!(tools.items, 1);
+        assert_eq!(event.largest_items[0].category, ContextCategory::ToolOutputs);
+        assert_eq!(event.largest_items[0].label, r#"shell {"command":["cargo","test"]}"#);
+
+        let by_category = |category| {
+            event
+                .largest_items
+                .iter()
+                .find(|item| item.category == category)
+                .unwrap()
+        };
+        assert!(!by_category(ContextCategory::AgentsMd).can_drop);
+        assert!(!by_category(ContextCategory::EnvironmentContext).can_drop);
+        assert!(by_category(ContextCategory::Messages).can_collapse);
+        assert_eq!(
+            by_category(ContextCategory::CompactionSummary).label,
+            "Compaction summary"
+        );
+        let listed: u64 = event.categories.iter().map(|usage| usage.tokens).sum();
+        assert_eq!(listed, event.total_tokens);
+    }
+
+    #[test]
+    fn dropping_a_tool_call_removes_its_output() {
+        let history = history();
+        let event = inspect(&ContextSources {
+            instructions: "",
+            prefix: &[],
+            history: &history,
+            context_window: None,
+        });
+        let outcome = apply_edits(
+            history,
+            &[ContextEdit {
+                item_id: id_for(&event, ContextCategory::ToolOutputs),
+                action: ContextEditAction::Drop,
+            }],
+        );
+        assert_eq!(outcome.dropped, 1);
+        assert_eq!(outcome.history.len(), 4);
+        assert!(!outcome.history.iter().any(|item| matches!(
+            item,
+            ResponseItem::FunctionCall { .. } | ResponseItem::FunctionCallOutput { .. }
+        )));
+        assert_eq!(outcome.notice(), "Dropped 1 item.");
+    }
+
+    #[test]
+    fn collapsing_keeps_the_call_and_shrinks_the_output() {
+        let history = history();
+        let event = inspect(&ContextSources {
+            instructions: "",
+            prefix: &[],
+            history: &history,
+            context_window: None,
+        });
+        let id = id_for(&event, ContextCategory::ToolOutputs);
+        let outcome = apply_edits(
+            history,
+            &[ContextEdit {
+                item_id: id.clone(),
+                action: ContextEditAction::Collapse,
+            }],
+        );
+        assert_eq!(outcome.collapsed, 1);
+        let ResponseItem::FunctionCallOutput { output, .. } = &outcome.history[4] else {
+            panic!("expected the output to stay in place");
+        };
+        assert!(is_collapsed(&outcome.history[4]));
+        assert!(matches!(&output.body, FunctionCallOutputBody::Text(text) if text.len() < 80));
+
+        // The old id no longer matches once the output changed.
+        let again = apply_edits(
+            outcome.history,
+            &[ContextEdit {
+                item_id: id,
+                action: ContextEditAction::Drop,
+            }],
+        );
+        assert_eq!(again.skipped, 1);
+        assert!(!again.changed());
+    }
+}
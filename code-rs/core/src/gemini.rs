@@ -0,0 +1,882 @@
+//! Native implementation of the Gemini `streamGenerateContent` API
+//! (`wire_api = "gemini"`).
+//!
+//! Gemini tool calls carry no ids of their own on older models, so call ids
+//! are derived from the response id and each function response names the
+//! call it answers. Thought signatures are stored on the
+//! `ResponseItem::Reasoning` that precedes the part they belong to and are
+//! re-attached to that part on the next request.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use code_otel::otel_event_manager::OtelEventManager;
+use code_protocol::models::ContentItem;
+use code_protocol::models::FunctionCallOutputContentItem;
+use code_protocol::models::ReasoningItemReasoningSummary;
+use code_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::client_common::replace_image_payloads_for_model;
+use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
+use crate::debug_logger::DebugLogger;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::model_traffic::TrafficWireApi;
+use crate::native_wire::ByteStream;
+use crate::native_wire::WireModelOptions;
+use crate::native_wire::WireRequestContext;
+use crate::native_wire::arguments_to_object;
+use crate::native_wire::function_tool_specs;
+use crate::native_wire::next_sse_event;
+use crate::native_wire::open_event_stream;
+use crate::native_wire::parse_data_url;
+use crate::native_wire::parse_sse_data;
+use crate::protocol::TokenUsage;
+
+/// JSON Schema keywords Gemini's OpenAPI-style schema rejects.
+const UNSUPPORTED_SCHEMA_KEYS: &[&str] = &["additionalProperties", "$schema"];
+
+pub(crate) async fn stream_gemini(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    options: WireModelOptions,
+    ctx: WireRequestContext<'_>,
+) -> Result<ResponseStream> {
+    let payload = build_generate_content_payload(prompt, model_family, model_slug, options)?;
+    let url = ctx.provider.get_model_stream_url(&None, model_slug);
+    let (stream, request_id) =
+        open_event_stream(&ctx, TrafficWireApi::Gemini, &url, &payload).await?;
+
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+    tokio::spawn(process_gemini_sse(
+        stream,
+        tx_event,
+        ctx.provider.stream_idle_timeout(),
+        Arc::clone(ctx.debug_logger),
+        request_id,
+        ctx.otel_event_manager,
+    ));
+    Ok(ResponseStream { rx_event })
+}
+
+/// Build the `streamGenerateContent` request body.
+pub(crate) fn build_generate_content_payload(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    options: WireModelOptions,
+) -> Result<Value> {
+    let mut input = prompt.get_formatted_input();
+    replace_image_payloads_for_model(&mut input, model_slug);
+
+    let call_names: HashMap<&str, &str> = input
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCall { name, call_id, .. }
+            | ResponseItem::CustomToolCall { name, call_id, .. } => {
+                Some((call_id.as_str(), name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    // Developer messages ahead of the conversation extend the system
+    // instruction; later ones are sent as user text.
+    let mut system = vec![prompt.get_full_instructions(model_family).into_owned()];
+    let mut contents: Vec<Value> = Vec::new();
+    let mut in_preamble = true;
+    let mut pending_signature: Option<&str> = None;
+    for item in &input {
+        if let ResponseItem::Message { role, content, .. } = item
+            && (role == "developer" || role == "system")
+        {
+            let text = content
+                .iter()
+                .filter_map(|item| match item {
+                    ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                        Some(text.as_str())
+                    }
+                    ContentItem::InputImage { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if text.trim().is_empty() {
+                continue;
+            }
+            if in_preamble {
+                system.push(text);
+            } else {
+                push_part(&mut contents, "user", json!({ "text": text }));
+            }
+            continue;
+        }
+        in_preamble = false;
+
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" { "model" } else { "user" };
+                for item in content {
+                    let mut part = match item {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            if text.is_empty() {
+                                continue;
+                            }
+                            json!({ "text": text })
+                        }
+                        ContentItem::InputImage { image_url } => image_part(image_url),
+                    };
+                    if role == "model" {
+                        attach_signature(&mut part, pending_signature.take());
+                    }
+                    push_part(&mut contents, role, part);
+                }
+            }
+            ResponseItem::Reasoning {
+                encrypted_content: Some(signature),
+                ..
+            } => {
+                pending_signature = Some(signature.as_str());
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let mut part = json!({
+                    "functionCall": {
+                        "id": call_id,
+                        "name": name,
+                        "args": arguments_to_object(arguments),
+                    }
+                });
+                attach_signature(&mut part, pending_signature.take());
+                push_part(&mut contents, "model", part);
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                let mut part = json!({
+                    "functionCall": { "id": call_id, "name": name, "args": { "input": input } }
+                });
+                attach_signature(&mut part, pending_signature.take());
+                push_part(&mut contents, "model", part);
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let Some(name) = call_names.get(call_id.as_str()) else {
+                    continue;
+                };
+                push_part(
+                    &mut contents,
+                    "user",
+                    json!({
+                        "functionResponse": {
+                            "id": call_id,
+                            "name": name,
+                            "response": { "output": output.to_string() },
+                        }
+                    }),
+                );
+                // Images returned by tools travel as inline parts after the
+                // response they belong to.
+                for item in output.content_items().unwrap_or_default() {
+                    if let FunctionCallOutputContentItem::InputImage { image_url } = item {
+                        push_part(&mut contents, "user", image_part(image_url));
+                    }
+                }
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let Some(name) = call_names.get(call_id.as_str()) else {
+                    continue;
+                };
+                push_part(
+                    &mut contents,
+                    "user",
+                    json!({
+                        "functionResponse": {
+                            "id": call_id,
+                            "name": name,
+                            "response": { "output": output },
+                        }
+                    }),
+                );
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::GhostSnapshot { .. }
+            | ResponseItem::CompactionSummary { .. }
+            | ResponseItem::Other => {}
+        }
+    }
+
+    let mut generation_config = serde_json::Map::new();
+    // Minimal effort omits the thinking config so models without thinking
+    // support still accept the request.
+    if !matches!(
+        options.effort,
+        ReasoningEffortConfig::Minimal | ReasoningEffortConfig::None
+    ) {
+        let mut thinking_config = json!({ "includeThoughts": true });
+        if let Some(budget) = thinking_budget(options.effort) {
+            thinking_config["thinkingBudget"] = json!(budget);
+        }
+        generation_config.insert("thinkingConfig".to_string(), thinking_config);
+    }
+    if let Some(max_output_tokens) = options.max_output_tokens {
+        generation_config.insert("maxOutputTokens".to_string(), json!(max_output_tokens));
+    }
+    if let Some(schema) = &prompt.output_schema {
+        generation_config.insert("responseMimeType".to_string(), json!("application/json"));
+        generation_config.insert("responseJsonSchema".to_string(), schema.clone());
+    }
+
+    let mut payload = json!({
+        "systemInstruction": { "parts": [{ "text": system.join("\n\n") }] },
+        "contents": contents,
+        "generationConfig": generation_config,
+    });
+
+    let declarations: Vec<Value> = function_tool_specs(&prompt.tools)?
+        .into_iter()
+        .map(|tool| {
+            let mut parameters = tool.parameters;
+            strip_unsupported_schema_keys(&mut parameters);
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "parameters": parameters,
+            })
+        })
+        .collect();
+    if !declarations.is_empty()
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert(
+            "tools".to_string(),
+            json!([{ "functionDeclarations": declarations }]),
+        );
+    }
+    Ok(payload)
+}
+
+/// Thinking budget for the configured reasoning effort. `None` leaves the
+/// budget to the model (dynamic thinking).
+fn thinking_budget(effort: ReasoningEffortConfig) -> Option<u64> {
+    match effort {
+        ReasoningEffortConfig::Minimal
+        | ReasoningEffortConfig::None
+        | ReasoningEffortConfig::Medium => None,
+        ReasoningEffortConfig::Low => Some(2_048),
+        ReasoningEffortConfig::High => Some(16_384),
+        ReasoningEffortConfig::XHigh => Some(32_768),
+    }
+}
+
+fn image_part(image_url: &str) -> Value {
+    match parse_data_url(image_url) {
+        Some((mime_type, data)) => json!({ "inlineData": { "mimeType": mime_type, "data": data } }),
+        None => json!({ "fileData": { "fileUri": image_url } }),
+    }
+}
+
+fn attach_signature(part: &mut Value, signature: Option<&str>) {
+    if let (Some(signature), Some(obj)) = (signature, part.as_object_mut()) {
+        obj.insert("thoughtSignature".to_string(), json!(signature));
+    }
+}
+
+/// Append a part, merging into the previous turn when the role repeats.
+fn push_part(contents: &mut Vec<Value>, role: &str, part: Value) {
+    if let Some(last) = contents.last_mut()
+        && last.get("role").and_then(Value::as_str) == Some(role)
+        && let Some(parts) = last.get_mut("parts").and_then(Value::as_array_mut)
+    {
+        parts.push(part);
+        return;
+    }
+    contents.push(json!({ "role": role, "parts": [part] }));
+}
+
+fn strip_unsupported_schema_keys(schema: &mut Value) {
+    match schema {
+        Value::Object(map) => {
+            for key in UNSUPPORTED_SCHEMA_KEYS {
+                map.remove(*key);
+            }
+            for value in map.values_mut() {
+                strip_unsupported_schema_keys(value);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_unsupported_schema_keys),
+        _ => {}
+    }
+}
+
+/// `usageMetadata` counts thoughts separately from candidates; `TokenUsage`
+/// treats reasoning as part of the output.
+fn token_usage_from_metadata(metadata: &Value) -> TokenUsage {
+    let field = |name: &str| metadata.get(name).and_then(Value::as_i64).unwrap_or(0);
+    let input_tokens = field("promptTokenCount");
+    let reasoning_output_tokens = field("thoughtsTokenCount");
+    let output_tokens = field("candidatesTokenCount") + reasoning_output_tokens;
+    let total_tokens = metadata
+        .get("totalTokenCount")
+        .and_then(Value::as_i64)
+        .unwrap_or(input_tokens + output_tokens);
+    TokenUsage {
+        input_tokens,
+        cached_input_tokens: field("cachedContentTokenCount"),
+        output_tokens,
+        reasoning_output_tokens,
+        total_tokens,
+    }
+}
+
+#[derive(Default)]
+struct GeminiStreamState {
+    response_id: String,
+    text: String,
+    thoughts: String,
+    signature: Option<String>,
+    calls: usize,
+    reasoning_items: usize,
+    messages: usize,
+    usage: Option<TokenUsage>,
+}
+
+impl GeminiStreamState {
+    fn item_id(&self, suffix: &str) -> Option<String> {
+        (!self.response_id.is_empty()).then(|| format!("{}_{suffix}", self.response_id))
+    }
+
+    fn reasoning_id(&self) -> Option<String> {
+        self.item_id(&format!("reasoning_{}", self.reasoning_items))
+    }
+
+    fn message_id(&self) -> Option<String> {
+        self.item_id(&format!("text_{}", self.messages))
+    }
+
+    /// Reasoning goes out before the part it precedes so signatures stay in
+    /// order when the history is replayed.
+    fn take_reasoning(&mut self) -> Option<ResponseItem> {
+        if self.thoughts.is_empty() && self.signature.is_none() {
+            return None;
+        }
+        let summary = if self.thoughts.is_empty() {
+            Vec::new()
+        } else {
+            vec![ReasoningItemReasoningSummary::SummaryText {
+                text: std::mem::take(&mut self.thoughts),
+            }]
+        };
+        let item = ResponseItem::Reasoning {
+            id: self.reasoning_id().unwrap_or_default(),
+            summary,
+            content: None,
+            encrypted_content: self.signature.take(),
+        };
+        self.reasoning_items += 1;
+        Some(item)
+    }
+
+    fn take_message(&mut self) -> Option<ResponseItem> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let item = ResponseItem::Message {
+            id: self.message_id(),
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: std::mem::take(&mut self.text),
+            }],
+            end_turn: None,
+            phase: None,
+        };
+        self.messages += 1;
+        Some(item)
+    }
+
+    fn next_call_id(&mut self) -> String {
+        self.calls += 1;
+        if self.response_id.is_empty() {
+            format!("gemini_call_{}", self.calls)
+        } else {
+            format!("{}_call_{}", self.response_id, self.calls)
+        }
+    }
+}
+
+async fn send_item(tx_event: &mpsc::Sender<Result<ResponseEvent>>, item: Option<ResponseItem>) {
+    if let Some(item) = item {
+        let _ = tx_event
+            .send(Ok(ResponseEvent::OutputItemDone {
+                item,
+                sequence_number: None,
+                output_index: None,
+            }))
+            .await;
+    }
+}
+
+/// Map `streamGenerateContent` chunks onto [`ResponseEvent`]s. Gemini ends
+/// the stream by closing the connection rather than with a terminal event.
+async fn process_gemini_sse(
+    stream: ByteStream,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+    debug_logger: Arc<Mutex<DebugLogger>>,
+    request_id: String,
+    otel_event_manager: Option<OtelEventManager>,
+) {
+    let mut stream = stream.eventsource();
+    let mut state = GeminiStreamState::default();
+    let mut created_emitted = false;
+
+    loop {
+        let event = match next_sse_event(
+            &mut stream,
+            idle_timeout,
+            otel_event_manager.as_ref(),
+            &request_id,
+        )
+        .await
+        {
+            Ok(Some(event)) => event,
+            Ok(None) => break,
+            Err(err) => {
+                let _ = tx_event.send(Err(err)).await;
+                return;
+            }
+        };
+        let Some(chunk) = parse_sse_data(&event, &debug_logger, &request_id) else {
+            continue;
+        };
+
+        if let Some(error) = chunk.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            let status = error
+                .get("status")
+                .and_then(Value::as_str)
+                .unwrap_or("error");
+            let _ = tx_event
+                .send(Err(CodexErr::Stream(
+                    format!("{status}: {message}"),
+                    None,
+                    Some(request_id.clone()),
+                )))
+                .await;
+            return;
+        }
+
+        if !created_emitted {
+            state.response_id = chunk
+                .get("responseId")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let _ = tx_event
+                .send(Ok(ResponseEvent::Created {
+                    response_id: (!state.response_id.is_empty())
+                        .then(|| state.response_id.clone()),
+                    response_model: chunk
+                        .get("modelVersion")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                }))
+                .await;
+            created_emitted = true;
+        }
+
+        if let Some(metadata) = chunk.get("usageMetadata") {
+            state.usage = Some(token_usage_from_metadata(metadata));
+        }
+
+        let parts = chunk
+            .pointer("/candidates/0/content/parts")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for part in parts {
+            let signature = part
+                .get("thoughtSignature")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let text = part.get("text").and_then(Value::as_str).unwrap_or_default();
+
+            if part.get("thought").and_then(Value::as_bool) == Some(true) {
+                if signature.is_some() {
+                    state.signature = signature;
+                }
+                if !text.is_empty() {
+                    state.thoughts.push_str(text);
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::ReasoningSummaryDelta {
+                            delta: text.to_string(),
+                            item_id: state.reasoning_id(),
+                            sequence_number: None,
+                            output_index: None,
+                            summary_index: Some(0),
+                        }))
+                        .await;
+                }
+                continue;
+            }
+
+            if let Some(call) = part.get("functionCall") {
+                let message = state.take_message();
+                send_item(&tx_event, message).await;
+                if signature.is_some() {
+                    state.signature = signature;
+                }
+                let reasoning = state.take_reasoning();
+                send_item(&tx_event, reasoning).await;
+                let call_id = match call.get("id").and_then(Value::as_str) {
+                    Some(id) if !id.is_empty() => id.to_string(),
+                    _ => state.next_call_id(),
+                };
+                let item = ResponseItem::FunctionCall {
+                    id: state.item_id(&call_id),
+                    name: call
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    arguments: call
+                        .get("args")
+                        .cloned()
+                        .unwrap_or_else(|| json!({}))
+                        .to_string(),
+                    call_id,
+                };
+                send_item(&tx_event, Some(item)).await;
+                continue;
+            }
+
+            if text.is_empty() {
+                continue;
+            }
+            if state.text.is_empty() {
+                if signature.is_some() {
+                    state.signature = signature;
+                }
+                let reasoning = state.take_reasoning();
+                send_item(&tx_event, reasoning).await;
+            }
+            state.text.push_str(text);
+            let _ = tx_event
+                .send(Ok(ResponseEvent::OutputTextDelta {
+                    delta: text.to_string(),
+                    item_id: state.message_id(),
+                    sequence_number: None,
+                    output_index: None,
+                }))
+                .await;
+        }
+    }
+
+    let reasoning = state.take_reasoning();
+    send_item(&tx_event, reasoning).await;
+    let message = state.take_message();
+    send_item(&tx_event, message).await;
+    let _ = tx_event
+        .send(Ok(ResponseEvent::Completed {
+            response_id: state.response_id.clone(),
+            token_usage: state.usage.take(),
+        }))
+        .await;
+    if let Ok(logger) = debug_logger.lock() {
+        let _ = logger.end_request_log(&request_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelProviderInfo;
+    use crate::WireApi;
+    use crate::model_family::find_family_for_model;
+    use code_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+
+    fn options() -> WireModelOptions {
+        WireModelOptions {
+            effort: ReasoningEffortConfig::Medium,
+            max_output_tokens: None,
+        }
+    }
+
+    #[test]
+    fn payload_maps_calls_responses_images_and_signatures() {
+        let prompt = Prompt {
+            input: vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![
+                        ContentItem::InputText {
+                            text: "describe".to_string(),
+                        },
+                        ContentItem::InputImage {
+                            image_url: "data:image/jpeg;base64,/9j/".to_string(),
+                        },
+                    ],
+                    end_turn: None,
+                    phase: None,
+                },
+                ResponseItem::Reasoning {
+                    id: String::new(),
+                    summary: Vec::new(),
+                    content: None,
+                    encrypted_content: Some("sig-g".to_string()),
+                },
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: "shell".to_string(),
+                    arguments: r#"{"command":["pwd"]}"#.to_string(),
+                    call_id: "resp_call_1".to_string(),
+                },
+                ResponseItem::FunctionCallOutput {
+                    call_id: "resp_call_1".to_string(),
+                    output: FunctionCallOutputPayload::from_text("/tmp".to_string()),
+                },
+            ],
+            include_additional_instructions: false,
+            base_instructions_override: Some("be brief".to_string()),
+            ..Prompt::default()
+        };
+        let family = find_family_for_model("gpt-4.1").expect("family");
+
+        let payload =
+            build_generate_content_payload(&prompt, &family, "gemini-2.5-pro", options())
+                .unwrap();
+
+        assert_eq!(
+            payload["systemInstruction"],
+            json!({ "parts": [{ "text": "be brief" }] })
+        );
+        assert_eq!(
+            payload["generationConfig"],
+            json!({ "thinkingConfig": { "includeThoughts": true } })
+        );
+        assert_eq!(
+            payload["contents"],
+            json!([
+                {
+                    "role": "user",
+                    "parts": [
+                        { "text": "describe" },
+                        { "inlineData": { "mimeType": "image/jpeg", "data": "/9j/" } }
+                    ]
+                },
+                {
+                    "role": "model",
+                    "parts": [{
+                        "functionCall": {
+                            "id": "resp_call_1",
+                            "name": "shell",
+                            "args": { "command": ["pwd"] }
+                        },
+                        "thoughtSignature": "sig-g"
+                    }]
+                },
+                {
+                    "role": "user",
+                    "parts": [{
+                        "functionResponse": {
+                            "id": "resp_call_1",
+                            "name": "shell",
+                            "response": { "output": "/tmp" }
+                        }
+                    }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn schema_keys_gemini_rejects_are_stripped() {
+        let mut schema = json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "items": { "type": "array", "items": { "type": "object", "additionalProperties": false } }
+            }
+        });
+        strip_unsupported_schema_keys(&mut schema);
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": { "items": { "type": "array", "items": { "type": "object" } } }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_thoughts_text_function_calls_and_usage_from_mock_server() {
+        let server = MockServer::start().await;
+        let chunks = [
+            json!({
+                "responseId": "resp-7",
+                "modelVersion": "gemini-2.5-pro",
+                "candidates": [{ "content": { "role": "model", "parts": [
+                    { "text": "Checking the directory.", "thought": true }
+                ]}}]
+            }),
+            json!({
+                "responseId": "resp-7",
+                "candidates": [{ "content": { "role": "model", "parts": [
+                    { "text": "Let me look." }
+                ]}}]
+            }),
+            json!({
+                "responseId": "resp-7",
+                "candidates": [{ "content": { "role": "model", "parts": [
+                    { "functionCall": { "name": "shell", "args": { "command": ["ls"] } },
+                      "thoughtSignature": "sig-7" }
+                ]}, "finishReason": "STOP" }],
+                "usageMetadata": {
+                    "promptTokenCount": 50,
+                    "cachedContentTokenCount": 10,
+                    "candidatesTokenCount": 12,
+                    "thoughtsTokenCount": 30,
+                    "totalTokenCount": 92
+                }
+            }),
+        ];
+        let body: String = chunks.iter().map(|chunk| format!("data: {chunk}\n\n")).collect();
+        Mock::given(method("POST"))
+            .and(path("/v1beta/models/gemini-2.5-pro:streamGenerateContent"))
+            .and(query_param("alt", "sse"))
+            .and(header("x-goog-api-key", "test-key"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = ModelProviderInfo {
+            name: "Gemini".to_string(),
+            base_url: Some(format!("{}/v1beta", server.uri())),
+            env_key: None,
+            env_key_instructions: None,
+            experimental_bearer_token: Some("test-key".to_string()),
+            wire_api: WireApi::Gemini,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: Some(0),
+            stream_max_retries: Some(0),
+            stream_idle_timeout_ms: Some(5_000),
+            requires_openai_auth: false,
+            openrouter: None,
+        };
+        let client = reqwest::Client::new();
+        let debug_logger = Arc::new(Mutex::new(DebugLogger::new(false).unwrap()));
+        let prompt = Prompt {
+            input: vec![ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+                end_turn: None,
+                phase: None,
+            }],
+            ..Prompt::default()
+        };
+        let family = find_family_for_model("gpt-4.1").expect("family");
+        let ctx = WireRequestContext {
+            client: &client,
+            provider: &provider,
+            debug_logger: &debug_logger,
+            otel_event_manager: None,
+            log_tag: None,
+            traffic: None,
+        };
+
+        let mut stream = stream_gemini(&prompt, &family, "gemini-2.5-pro", options(), ctx)
+            .await
+            .unwrap();
+        let mut items = Vec::new();
+        let mut reasoning_deltas = String::new();
+        let (response_id, usage) = loop {
+            match stream.rx_event.recv().await.expect("stream ended").unwrap() {
+                ResponseEvent::ReasoningSummaryDelta { delta, .. } => {
+                    reasoning_deltas.push_str(&delta)
+                }
+                ResponseEvent::OutputItemDone { item, .. } => items.push(item),
+                ResponseEvent::Completed {
+                    response_id,
+                    token_usage,
+                } => break (response_id, token_usage),
+                _ => {}
+            }
+        };
+
+        assert_eq!(response_id, "resp-7");
+        assert_eq!(reasoning_deltas, "Checking the directory.");
+        assert_eq!(items.len(), 4);
+        assert!(matches!(
+            &items[0],
+            ResponseItem::Reasoning { summary, encrypted_content: None, .. } if summary.len() == 1
+        ));
+        assert!(matches!(&items[1], ResponseItem::Message { role, .. } if role == "assistant"));
+        assert!(matches!(
+            &items[2],
+            ResponseItem::Reasoning { encrypted_content: Some(sig), .. } if sig == "sig-7"
+        ));
+        match &items[3] {
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                assert_eq!(name, "shell");
+                assert_eq!(arguments, r#"{"command":["ls"]}"#);
+                assert_eq!(call_id, "resp-7_call_1");
+            }
+            other => panic!("unexpected item: {other:?}"),
+        }
+        assert_eq!(
+            usage,
+            Some(TokenUsage {
+                input_tokens: 50,
+                cached_input_tokens: 10,
+                output_tokens: 42,
+                reasoning_output_tokens: 30,
+                total_tokens: 92,
+            })
+        );
+    }
+}
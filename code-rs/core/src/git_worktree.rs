@@ -106,6 +106,35 @@ pub struct BranchMetadata {
     pub remote_ref: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remote_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<WorktreeOwner>,
+}
+
+/// What created a worktree, recorded so stale ones can be traced back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WorktreeOwner {
+    /// A `/branch` worktree opened from an interactive session.
+    Session {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        pid: u32,
+    },
+    /// A worktree an agent ran in.
+    Agent { agent_id: String, model: String },
+}
+
+impl std::fmt::Display for WorktreeOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorktreeOwner::Session {
+                session_id: Some(id),
+                ..
+            } => write!(f, "session {id}"),
+            WorktreeOwner::Session { pid, .. } => write!(f, "session (pid {pid})"),
+            WorktreeOwner::Agent { agent_id, model } => write!(f, "agent {model} ({agent_id})"),
+        }
+    }
 }
 
 /// Resolve the git repository root (top-level) for the given cwd.
@@ -526,6 +555,7 @@ pub async fn ensure_local_default_remote(
         remote_name: Some(remote_name.to_string()),
         remote_ref: None,
         remote_url: Some(remote_url),
+        owner: None,
     };
 
     if let Some(base) = base_branch_clean {
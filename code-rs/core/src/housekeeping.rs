@@ -1,3 +1,4 @@
+use crate::git_info::resolve_root_git_project_for_trust;
 use crate::git_worktree;
 use crate::rollout::SESSIONS_SUBDIR;
 use fs2::FileExt;
@@ -14,6 +15,13 @@ use tracing::{debug, info, warn};
 const DEFAULT_SESSION_RETENTION_DAYS: i64 = 7;
 const DEFAULT_WORKTREE_RETENTION_DAYS: i64 = 3;
 const DEFAULT_MIN_INTERVAL_HOURS: i64 = 6;
+const DEFAULT_SNAPSHOT_RETENTION_DAYS: i64 = 14;
+const DEFAULT_SNAPSHOT_MAX_PER_REPO: i64 = 200;
+/// Repositories holding `/undo` snapshot refs, one path per line, relative to
+/// the code home.
+const SNAPSHOT_REPOS_FILE: &str = "working/_snapshots/repos.txt";
+/// Per-process agent boards (see `agent_board::session_board_path`).
+const AGENT_BOARDS_DIR: &str = "working/_agents";
 const LOCK_FILE_NAME: &str = "cleanup.lock";
 const STATE_FILE_NAME: &str = "cleanup-state.json";
 
@@ -26,6 +34,8 @@ pub struct CleanupOutcome {
     pub worktree_files_removed: usize,
     pub worktree_bytes_reclaimed: u64,
     pub worktrees_skipped_active: usize,
+    pub snapshot_refs_removed: usize,
+    pub agent_boards_removed: usize,
     pub errors: usize,
 }
 
@@ -33,6 +43,8 @@ pub struct CleanupOutcome {
 struct HousekeepingConfig {
     session_retention_days: Option<i64>,
     worktree_retention_days: Option<i64>,
+    snapshot_retention_days: Option<i64>,
+    snapshot_max_per_repo: usize,
     min_interval_hours: i64,
     disabled: bool,
 }
@@ -51,6 +63,14 @@ impl HousekeepingConfig {
             "CODE_CLEANUP_WORKTREE_RETENTION_DAYS",
             DEFAULT_WORKTREE_RETENTION_DAYS,
         );
+        let snapshot_retention_days = parse_days_env(
+            "CODE_CLEANUP_SNAPSHOT_RETENTION_DAYS",
+            DEFAULT_SNAPSHOT_RETENTION_DAYS,
+        );
+        let snapshot_max_per_repo = parse_positive_i64_env(
+            "CODE_CLEANUP_SNAPSHOT_MAX_PER_REPO",
+            DEFAULT_SNAPSHOT_MAX_PER_REPO,
+        ) as usize;
         let min_interval_hours = parse_positive_i64_env(
             "CODE_CLEANUP_MIN_INTERVAL_HOURS",
             DEFAULT_MIN_INTERVAL_HOURS,
@@ -59,6 +79,8 @@ impl HousekeepingConfig {
         Self {
             session_retention_days,
             worktree_retention_days,
+            snapshot_retention_days,
+            snapshot_max_per_repo,
             min_interval_hours,
             disabled,
         }
@@ -129,13 +151,19 @@ pub fn run_housekeeping_if_due(code_home: &Path) -> io::Result<Option<CleanupOut
         );
     }
 
-    if outcome.session_days_removed > 0 || outcome.worktrees_removed > 0 {
+    if outcome.session_days_removed > 0
+        || outcome.worktrees_removed > 0
+        || outcome.snapshot_refs_removed > 0
+        || outcome.agent_boards_removed > 0
+    {
         info!(
             sessions_pruned = outcome.session_days_removed,
             session_bytes_reclaimed = outcome.session_bytes_reclaimed,
             worktrees_pruned = outcome.worktrees_removed,
             worktree_bytes_reclaimed = outcome.worktree_bytes_reclaimed,
             skipped_active_worktrees = outcome.worktrees_skipped_active,
+            snapshot_refs_pruned = outcome.snapshot_refs_removed,
+            agent_boards_pruned = outcome.agent_boards_removed,
             "code home housekeeping pruned stale artifacts"
         );
     } else {
@@ -171,6 +199,15 @@ fn perform_housekeeping(
         }
     }
 
+    if let Some(stats) = cleanup_snapshot_refs(code_home, now, config)? {
+        outcome.snapshot_refs_removed = stats.removed_refs;
+        outcome.errors += stats.errors;
+    }
+
+    let (boards_removed, board_errors) = cleanup_agent_boards(&code_home.join(AGENT_BOARDS_DIR));
+    outcome.agent_boards_removed = boards_removed;
+    outcome.errors += board_errors;
+
     Ok(outcome)
 }
 
@@ -384,7 +421,144 @@ fn cleanup_worktrees(
     Ok(Some(stats))
 }
 
-fn should_prune_worktree_branch(branch_name: &str) -> bool {
+/// Remember that the repository containing `cwd` holds snapshot refs so
+/// housekeeping can prune them later.
+pub(crate) fn register_snapshot_repo(code_home: &Path, cwd: &Path) {
+    let Some(repo_root) = resolve_root_git_project_for_trust(cwd) else {
+        return;
+    };
+    let registry = code_home.join(SNAPSHOT_REPOS_FILE);
+    let repo_str = repo_root.to_string_lossy().to_string();
+    let existing = fs::read_to_string(&registry).unwrap_or_default();
+    if existing.lines().any(|line| line == repo_str) {
+        return;
+    }
+    if let Some(parent) = registry.parent()
+        && let Err(err) = fs::create_dir_all(parent)
+    {
+        warn!("failed to create snapshot registry dir {:?}: {err}", parent);
+        return;
+    }
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&registry)
+        .and_then(|mut file| writeln!(file, "{repo_str}"));
+    if let Err(err) = result {
+        warn!("failed to register snapshot repo {:?}: {err}", repo_root);
+    }
+}
+
+/// Remove the boards of orchestrating processes that have exited, with their
+/// lock and read-cursor files. Returns `(boards removed, errors)`.
+fn cleanup_agent_boards(boards_dir: &Path) -> (usize, usize) {
+    let mut removed = 0;
+    let mut errors = 0;
+    for entry in list_dir_sorted(boards_dir) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Some(pid) = agent_board_pid(&name) else {
+            continue;
+        };
+        if check_pid_alive(pid) != Some(false) {
+            continue;
+        }
+        match fs::remove_file(entry.path()) {
+            Ok(()) if name.ends_with(".board.jsonl") => removed += 1,
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => {
+                warn!(
+                    "failed to remove agent board file {:?}: {err}",
+                    entry.path()
+                );
+                errors += 1;
+            }
+        }
+    }
+    (removed, errors)
+}
+
+/// Pid a board file belongs to, from names like `pid-42.board.jsonl` and its
+/// `pid-42.board.jsonl.<agent>.cursor` / `.lock` companions.
+fn agent_board_pid(file_name: &str) -> Option<i32> {
+    let (prefix, _) = file_name.split_once(".board.jsonl")?;
+    prefix.strip_prefix("pid-")?.parse().ok()
+}
+
+/// Delete `/undo` snapshot refs older than the retention window, then the
+/// oldest ones beyond the per-repository limit.
+fn cleanup_snapshot_refs(
+    code_home: &Path,
+    now: OffsetDateTime,
+    config: &HousekeepingConfig,
+) -> io::Result<Option<SnapshotCleanupStats>> {
+    let registry = code_home.join(SNAPSHOT_REPOS_FILE);
+    let data = match fs::read_to_string(&registry) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let mut stats = SnapshotCleanupStats::default();
+    let cutoff = config
+        .snapshot_retention_days
+        .map(|days| now.unix_timestamp() - days.max(0) * 86_400);
+    let mut kept_repos: Vec<&str> = Vec::new();
+
+    for repo in data.lines().map(str::trim) {
+        if repo.is_empty() || kept_repos.contains(&repo) {
+            continue;
+        }
+        let repo_path = Path::new(repo);
+        if !repo_path.is_dir() {
+            continue;
+        }
+
+        let refs = match code_git_tooling::list_snapshot_refs(repo_path) {
+            Ok(refs) => refs,
+            Err(code_git_tooling::GitToolingError::NotAGitRepository { .. }) => continue,
+            Err(err) => {
+                stats.errors += 1;
+                warn!("failed to list snapshot refs in {:?}: {err}", repo_path);
+                kept_repos.push(repo);
+                continue;
+            }
+        };
+
+        // Refs are sorted oldest first.
+        let excess = refs.len().saturating_sub(config.snapshot_max_per_repo);
+        let (stale, fresh): (Vec<_>, Vec<_>) =
+            refs.into_iter().enumerate().partition(|(index, snapshot)| {
+                *index < excess || cutoff.is_some_and(|cutoff| snapshot.created_at < cutoff)
+            });
+        let stale: Vec<_> = stale.into_iter().map(|(_, snapshot)| snapshot).collect();
+
+        match code_git_tooling::delete_snapshot_refs(repo_path, &stale) {
+            Ok(()) => {
+                stats.removed_refs += stale.len();
+                if !fresh.is_empty() {
+                    kept_repos.push(repo);
+                }
+            }
+            Err(err) => {
+                stats.errors += 1;
+                warn!("failed to delete snapshot refs in {:?}: {err}", repo_path);
+                kept_repos.push(repo);
+            }
+        }
+    }
+
+    let mut content = kept_repos.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    fs::write(&registry, content)?;
+
+    Ok(Some(stats))
+}
+
+pub(crate) fn should_prune_worktree_branch(branch_name: &str) -> bool {
     if branch_name == "main" || branch_name == "master" {
         return false;
     }
@@ -392,7 +566,7 @@ fn should_prune_worktree_branch(branch_name: &str) -> bool {
     branch_name.starts_with("code-") || branch_name == "auto-review"
 }
 
-fn run_git_branch_delete_if_merged(repo_root: &Path, branch_name: &str) {
+pub(crate) fn run_git_branch_delete_if_merged(repo_root: &Path, branch_name: &str) {
     if !repo_root.exists() {
         return;
     }
@@ -420,7 +594,7 @@ fn run_git_branch_delete_if_merged(repo_root: &Path, branch_name: &str) {
     }
 }
 
-fn run_git_worktree_remove(worktree_path: &Path) {
+pub(crate) fn run_git_worktree_remove(worktree_path: &Path) {
     let Some(repo_root) = detect_repo_root(worktree_path) else {
         return;
     };
@@ -456,7 +630,7 @@ fn run_git_worktree_remove(worktree_path: &Path) {
     }
 }
 
-fn detect_repo_root(worktree_path: &Path) -> Option<PathBuf> {
+pub(crate) fn detect_repo_root(worktree_path: &Path) -> Option<PathBuf> {
     let git_file = worktree_path.join(".git");
     let data = fs::read_to_string(git_file).ok()?;
     let gitdir_line = data
@@ -530,7 +704,7 @@ fn collect_active_worktrees(session_dir: &Path) -> HashSet<PathBuf> {
     set
 }
 
-fn purge_session_registry(session_dir: &Path, worktree_path: &Path) {
+pub(crate) fn purge_session_registry(session_dir: &Path, worktree_path: &Path) {
     let entries = match fs::read_dir(session_dir) {
         Ok(entries) => entries,
         Err(_) => return,
@@ -571,7 +745,7 @@ fn purge_session_registry(session_dir: &Path, worktree_path: &Path) {
     }
 }
 
-fn pid_file_is_active(file_name: &OsStr) -> Option<bool> {
+pub(crate) fn pid_file_is_active(file_name: &OsStr) -> Option<bool> {
     let name = file_name.to_string_lossy();
     let rest = name.strip_prefix("pid-")?;
     let pid_str = rest.strip_suffix(".txt").unwrap_or(rest);
@@ -632,6 +806,12 @@ struct SessionCleanupStats {
     errors: usize,
 }
 
+#[derive(Default)]
+struct SnapshotCleanupStats {
+    removed_refs: usize,
+    errors: usize,
+}
+
 #[derive(Default)]
 struct WorktreeCleanupStats {
     removed_worktrees: usize,
@@ -641,7 +821,7 @@ struct WorktreeCleanupStats {
     errors: usize,
 }
 
-fn directory_stats(path: &Path) -> DirStats {
+pub(crate) fn directory_stats(path: &Path) -> DirStats {
     let mut stats = DirStats::default();
     let mut stack = vec![path.to_path_buf()];
 
@@ -671,9 +851,9 @@ fn directory_stats(path: &Path) -> DirStats {
 }
 
 #[derive(Default)]
-struct DirStats {
-    bytes: u64,
-    files: usize,
+pub(crate) struct DirStats {
+    pub(crate) bytes: u64,
+    pub(crate) files: usize,
 }
 
 fn list_dir_sorted(path: &Path) -> Vec<fs::DirEntry> {
@@ -819,6 +999,8 @@ mod tests {
         let config = HousekeepingConfig {
             session_retention_days: Some(7),
             worktree_retention_days: None,
+            snapshot_retention_days: None,
+            snapshot_max_per_repo: 200,
             min_interval_hours: 1,
             disabled: false,
         };
@@ -844,6 +1026,8 @@ mod tests {
         let config = HousekeepingConfig {
             session_retention_days: None,
             worktree_retention_days: Some(0),
+            snapshot_retention_days: None,
+            snapshot_max_per_repo: 200,
             min_interval_hours: 1,
             disabled: false,
         };
@@ -872,6 +1056,8 @@ mod tests {
         let config = HousekeepingConfig {
             session_retention_days: None,
             worktree_retention_days: Some(0),
+            snapshot_retention_days: None,
+            snapshot_max_per_repo: 200,
             min_interval_hours: 1,
             disabled: false,
         };
@@ -919,6 +1105,8 @@ mod tests {
         let config = HousekeepingConfig {
             session_retention_days: None,
             worktree_retention_days: Some(0),
+            snapshot_retention_days: None,
+            snapshot_max_per_repo: 200,
             min_interval_hours: 1,
             disabled: false,
         };
@@ -931,6 +1119,46 @@ mod tests {
         assert!(!branch_exists(&repo_dir, "code-branch-test"));
     }
 
+    #[test]
+    fn prunes_snapshot_refs_by_age_and_count() {
+        let temp = TempDir::new().unwrap();
+        let code_home = temp.path().join("home");
+        let repo_dir = temp.path().join("repo");
+        fs::create_dir_all(&repo_dir).unwrap();
+        run_git(&repo_dir, ["init"]).unwrap();
+
+        for contents in ["one", "two", "three"] {
+            fs::write(repo_dir.join("notes.txt"), contents).unwrap();
+            let options = code_git_tooling::CreateGhostCommitOptions::new(&repo_dir);
+            let commit = code_git_tooling::create_ghost_commit(&options).unwrap();
+            code_git_tooling::save_snapshot_ref(&repo_dir, "session", &commit).unwrap();
+        }
+        register_snapshot_repo(&code_home, &repo_dir);
+        register_snapshot_repo(&code_home, &repo_dir);
+
+        let mut config = HousekeepingConfig {
+            session_retention_days: None,
+            worktree_retention_days: None,
+            snapshot_retention_days: Some(14),
+            snapshot_max_per_repo: 2,
+            min_interval_hours: 1,
+            disabled: false,
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let outcome = perform_housekeeping(&code_home, now, &config).unwrap();
+        assert_eq!(outcome.snapshot_refs_removed, 1);
+        assert_eq!(code_git_tooling::list_snapshot_refs(&repo_dir).unwrap().len(), 2);
+
+        config.snapshot_max_per_repo = 200;
+        let later = now + time::Duration::days(15);
+        let outcome = perform_housekeeping(&code_home, later, &config).unwrap();
+        assert_eq!(outcome.snapshot_refs_removed, 2);
+        assert!(code_git_tooling::list_snapshot_refs(&repo_dir).unwrap().is_empty());
+        let registry = fs::read_to_string(code_home.join(SNAPSHOT_REPOS_FILE)).unwrap();
+        assert!(registry.is_empty());
+    }
+
     fn run_git(repo_root: &Path, args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>) -> io::Result<()> {
         let output = std::process::Command::new("git")
             .current_dir(repo_root)
@@ -966,6 +1194,32 @@ mod tests {
         assert!(!registry_path.exists());
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn removes_agent_boards_of_exited_processes() {
+        let temp = TempDir::new().unwrap();
+        let boards_dir = temp.path().join(AGENT_BOARDS_DIR);
+        fs::create_dir_all(&boards_dir).unwrap();
+        let dead = [
+            "pid-999999.board.jsonl",
+            "pid-999999.board.jsonl.a1.cursor",
+            "pid-999999.board.jsonl.lock",
+        ];
+        for name in dead {
+            fs::write(boards_dir.join(name), "").unwrap();
+        }
+        let live = format!("pid-{}.board.jsonl", std::process::id());
+        fs::write(boards_dir.join(&live), "").unwrap();
+        fs::write(boards_dir.join("notes.txt"), "").unwrap();
+
+        assert_eq!(super::cleanup_agent_boards(&boards_dir), (1, 0));
+        for name in dead {
+            assert!(!boards_dir.join(name).exists(), "{name} should be removed");
+        }
+        assert!(boards_dir.join(&live).exists());
+        assert!(boards_dir.join("notes.txt").exists());
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn process_still_running_reports_status() {
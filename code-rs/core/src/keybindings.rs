@@ -0,0 +1,472 @@
+//! Resolution of `[tui.keybindings]` into concrete key chords.
+//!
+//! The TUI owns the actual key dispatch; this module only parses chords,
+//! fills in defaults for actions the user did not rebind and rejects configs
+//! that bind one chord to two actions, to a chord the TUI handles itself, or
+//! to a bare printable key the composer needs for typing.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config_types::KeyAction;
+use crate::config_types::KeyBindingSpec;
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 11] = [
+        KeyAction::Submit,
+        KeyAction::Newline,
+        KeyAction::ToggleReasoning,
+        KeyAction::SwitchModel,
+        KeyAction::HistoryPrev,
+        KeyAction::HistoryNext,
+        KeyAction::ScrollPageUp,
+        KeyAction::ScrollPageDown,
+        KeyAction::Interrupt,
+        KeyAction::OpenEditor,
+        KeyAction::HistorySearch,
+    ];
+
+    /// Chords the TUI handles for this action when it is not rebound. The
+    /// first one is the canonical chord rebound keys are translated to.
+    pub fn default_chords(self) -> &'static [&'static str] {
+        match self {
+            KeyAction::Submit => &["enter"],
+            KeyAction::Newline => &["ctrl+j", "shift+enter"],
+            KeyAction::ToggleReasoning => &["ctrl+r"],
+            KeyAction::SwitchModel => &["ctrl+y"],
+            KeyAction::HistoryPrev => &["shift+up"],
+            KeyAction::HistoryNext => &["shift+down"],
+            KeyAction::ScrollPageUp => &["pageup"],
+            KeyAction::ScrollPageDown => &["pagedown"],
+            KeyAction::Interrupt => &["ctrl+c"],
+            KeyAction::OpenEditor => &["ctrl+g"],
+            KeyAction::HistorySearch => &["ctrl+p"],
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::Submit => "Send message",
+            KeyAction::Newline => "Insert newline",
+            KeyAction::ToggleReasoning => "Toggle reasoning",
+            KeyAction::SwitchModel => "Switch model",
+            KeyAction::HistoryPrev => "Previous input from history",
+            KeyAction::HistoryNext => "Next input from history",
+            KeyAction::ScrollPageUp => "Scroll history up one page",
+            KeyAction::ScrollPageDown => "Scroll history down one page",
+            KeyAction::Interrupt => "End current task",
+            KeyAction::OpenEditor => "Open external editor",
+            KeyAction::HistorySearch => "Search transcript",
+        }
+    }
+
+    /// Name used as the key in `[tui.keybindings]`.
+    pub fn config_name(self) -> &'static str {
+        match self {
+            KeyAction::Submit => "submit",
+            KeyAction::Newline => "newline",
+            KeyAction::ToggleReasoning => "toggle_reasoning",
+            KeyAction::SwitchModel => "switch_model",
+            KeyAction::HistoryPrev => "history_prev",
+            KeyAction::HistoryNext => "history_next",
+            KeyAction::ScrollPageUp => "scroll_page_up",
+            KeyAction::ScrollPageDown => "scroll_page_down",
+            KeyAction::Interrupt => "interrupt",
+            KeyAction::OpenEditor => "open_editor",
+            KeyAction::HistorySearch => "history_search",
+        }
+    }
+}
+
+/// Chords the TUI handles directly and that cannot be rebound, with what
+/// they do. Covers the app-level shortcuts and the composer's emacs-style
+/// editing keys.
+pub const RESERVED_CHORDS: &[(&str, &str)] = &[
+    ("f1", "Help overlay"),
+    ("esc", "End current task / close popups"),
+    ("ctrl+n", "Cycle reasoning level"),
+    ("ctrl+e", "Toggle explore details"),
+    ("ctrl+t", "Toggle screen"),
+    ("ctrl+d", "Diff viewer"),
+    ("ctrl+z", "Undo"),
+    ("ctrl+s", "Auto Drive settings"),
+    ("ctrl+shift+c", "Toggle context expansion"),
+    ("ctrl+shift+y", "Toggle mouse capture"),
+    ("ctrl+alt+v", "Paste image"),
+    ("ctrl+o", "Snatch prompt"),
+    ("ctrl+l", "Clear input"),
+    ("ctrl+a", "Move to line start"),
+    ("ctrl+b", "Move left"),
+    ("ctrl+f", "Move right"),
+    ("alt+b", "Move back one word"),
+    ("alt+f", "Move forward one word"),
+    ("ctrl+w", "Delete previous word"),
+    ("ctrl+h", "Delete previous char"),
+    ("ctrl+u", "Delete to line start"),
+    ("ctrl+k", "Delete to line end"),
+];
+
+/// Non-modifier part of a chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyName {
+    Char(char),
+    Enter,
+    Tab,
+    Esc,
+    Backspace,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+/// A key plus modifiers, e.g. `ctrl+shift+enter`. Letters are stored
+/// lowercase; use the `shift` modifier for uppercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: KeyName,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return Err("empty key chord".to_string());
+        }
+        let mut ctrl = false;
+        let mut alt = false;
+        let mut shift = false;
+        let mut parts: Vec<&str> = trimmed.split('+').collect();
+        // `ctrl++` binds the plus key itself.
+        if trimmed.ends_with("++") {
+            parts.truncate(parts.len().saturating_sub(2));
+            parts.push("+");
+        }
+        let Some((key_part, modifiers)) = parts.split_last() else {
+            return Err(format!("invalid key chord `{raw}`"));
+        };
+        for modifier in modifiers {
+            match modifier.trim().to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "alt" | "meta" | "option" => alt = true,
+                "shift" => shift = true,
+                other => return Err(format!("unknown modifier `{other}` in `{raw}`")),
+            }
+        }
+        let key_lower = key_part.trim().to_ascii_lowercase();
+        let key = match key_lower.as_str() {
+            "enter" | "return" => KeyName::Enter,
+            "tab" => KeyName::Tab,
+            "esc" | "escape" => KeyName::Esc,
+            "backspace" => KeyName::Backspace,
+            "delete" | "del" => KeyName::Delete,
+            "insert" | "ins" => KeyName::Insert,
+            "up" => KeyName::Up,
+            "down" => KeyName::Down,
+            "left" => KeyName::Left,
+            "right" => KeyName::Right,
+            "home" => KeyName::Home,
+            "end" => KeyName::End,
+            "pageup" | "pgup" => KeyName::PageUp,
+            "pagedown" | "pgdn" => KeyName::PageDown,
+            "space" => KeyName::Char(' '),
+            other => {
+                let mut chars = other.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyName::Char(c),
+                    (Some('f'), Some(_)) => match other[1..].parse::<u8>() {
+                        Ok(n) if (1..=24).contains(&n) => KeyName::F(n),
+                        _ => return Err(format!("unknown key `{}` in `{raw}`", key_part.trim())),
+                    },
+                    _ => return Err(format!("unknown key `{}` in `{raw}`", key_part.trim())),
+                }
+            }
+        };
+        Ok(KeyChord {
+            key,
+            ctrl,
+            alt,
+            shift,
+        })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        match self.key {
+            KeyName::Char(' ') => f.write_str("Space"),
+            KeyName::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyName::Enter => f.write_str("Enter"),
+            KeyName::Tab => f.write_str("Tab"),
+            KeyName::Esc => f.write_str("Esc"),
+            KeyName::Backspace => f.write_str("Backspace"),
+            KeyName::Delete => f.write_str("Delete"),
+            KeyName::Insert => f.write_str("Insert"),
+            KeyName::Up => f.write_str("Up"),
+            KeyName::Down => f.write_str("Down"),
+            KeyName::Left => f.write_str("Left"),
+            KeyName::Right => f.write_str("Right"),
+            KeyName::Home => f.write_str("Home"),
+            KeyName::End => f.write_str("End"),
+            KeyName::PageUp => f.write_str("PageUp"),
+            KeyName::PageDown => f.write_str("PageDown"),
+            KeyName::F(n) => write!(f, "F{n}"),
+        }
+    }
+}
+
+impl KeyChord {
+    /// Whether the chord types a character in the composer: a character key
+    /// with no modifier other than shift.
+    pub fn is_printable(&self) -> bool {
+        matches!(self.key, KeyName::Char(_)) && !self.ctrl && !self.alt
+    }
+}
+
+/// Effective chords for every [`KeyAction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: BTreeMap<KeyAction, Vec<KeyChord>>,
+    /// Actions whose configured chords differ from their defaults.
+    rebound: BTreeSet<KeyAction>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::resolve(&BTreeMap::new()).unwrap_or_else(|_| Self {
+            bindings: BTreeMap::new(),
+            rebound: BTreeSet::new(),
+        })
+    }
+}
+
+impl KeyBindings {
+    /// Merge user bindings over the defaults. Fails on unparsable chords,
+    /// empty bindings, bare printable keys, or a chord bound to more than one
+    /// action.
+    pub fn resolve(config: &BTreeMap<KeyAction, KeyBindingSpec>) -> Result<Self, String> {
+        let mut bindings = BTreeMap::new();
+        let mut rebound = BTreeSet::new();
+        for action in KeyAction::ALL {
+            let defaults = action
+                .default_chords()
+                .iter()
+                .map(|chord| chord.parse::<KeyChord>())
+                .collect::<Result<Vec<_>, _>>()?;
+            let chords = match config.get(&action) {
+                Some(spec) => {
+                    let raw = spec.chords();
+                    if raw.is_empty() {
+                        return Err(format!(
+                            "tui.keybindings.{} must list at least one key chord",
+                            action.config_name()
+                        ));
+                    }
+                    let chords = raw
+                        .into_iter()
+                        .map(|chord| {
+                            chord.parse::<KeyChord>().map_err(|err| {
+                                format!("tui.keybindings.{}: {err}", action.config_name())
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(chord) = chords.iter().find(|chord| chord.is_printable()) {
+                        return Err(format!(
+                            "tui.keybindings.{}: {chord} needs ctrl or alt; a bare key would stop the composer from typing it",
+                            action.config_name()
+                        ));
+                    }
+                    let configured: BTreeSet<String> =
+                        chords.iter().map(ToString::to_string).collect();
+                    let default_set: BTreeSet<String> =
+                        defaults.iter().map(ToString::to_string).collect();
+                    if configured != default_set {
+                        rebound.insert(action);
+                    }
+                    chords
+                }
+                None => defaults,
+            };
+            bindings.insert(action, chords);
+        }
+
+        let reserved = RESERVED_CHORDS
+            .iter()
+            .map(|(chord, description)| Ok((chord.parse::<KeyChord>()?, *description)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut owners: BTreeMap<String, KeyAction> = BTreeMap::new();
+        for (action, chords) in &bindings {
+            for chord in chords {
+                if let Some((_, description)) = reserved.iter().find(|(r, _)| r == chord) {
+                    return Err(format!(
+                        "tui.keybindings.{}: {chord} is reserved for \"{description}\"",
+                        action.config_name()
+                    ));
+                }
+                if let Some(previous) = owners.insert(chord.to_string(), *action)
+                    && previous != *action
+                {
+                    return Err(format!(
+                        "tui.keybindings: {chord} is bound to both `{}` and `{}`",
+                        previous.config_name(),
+                        action.config_name()
+                    ));
+                }
+            }
+        }
+
+        Ok(Self { bindings, rebound })
+    }
+
+    pub fn chords(&self, action: KeyAction) -> &[KeyChord] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The action bound to `chord`, if any.
+    pub fn action_for(&self, chord: &KeyChord) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, chords)| chords.contains(chord))
+            .map(|(action, _)| *action)
+    }
+
+    /// Whether the user bound `action` to chords other than its defaults.
+    /// Listing the defaults, in any order, does not count as a rebind.
+    pub fn is_rebound(&self, action: KeyAction) -> bool {
+        self.rebound.contains(&action)
+    }
+
+    /// Chords joined for display, e.g. `Ctrl+J / Shift+Enter`.
+    pub fn display(&self, action: KeyAction) -> String {
+        self.chords(action)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_chords_case_insensitively() {
+        let chord: KeyChord = "Ctrl+Shift+Enter".parse().expect("chord");
+        assert_eq!(
+            chord,
+            KeyChord {
+                key: KeyName::Enter,
+                ctrl: true,
+                alt: false,
+                shift: true,
+            }
+        );
+        assert_eq!("alt+x".parse::<KeyChord>().expect("chord").to_string(), "Alt+X");
+        assert_eq!("f5".parse::<KeyChord>().expect("chord").key, KeyName::F(5));
+        assert!("hyper+x".parse::<KeyChord>().is_err());
+        assert!("ctrl+bogus".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn user_bindings_replace_defaults() {
+        let config = BTreeMap::from([(
+            KeyAction::OpenEditor,
+            KeyBindingSpec::Many(vec!["ctrl+x".to_string(), "f2".to_string()]),
+        )]);
+        let bindings = KeyBindings::resolve(&config).expect("valid");
+        assert_eq!(bindings.display(KeyAction::OpenEditor), "Ctrl+X / F2");
+        assert!(bindings.is_rebound(KeyAction::OpenEditor));
+        assert!(!bindings.is_rebound(KeyAction::Submit));
+        let chord: KeyChord = "f2".parse().expect("chord");
+        assert_eq!(bindings.action_for(&chord), Some(KeyAction::OpenEditor));
+    }
+
+    #[test]
+    fn rejects_conflicting_bindings() {
+        let config = BTreeMap::from([(
+            KeyAction::OpenEditor,
+            KeyBindingSpec::One("ctrl+r".to_string()),
+        )]);
+        let err = KeyBindings::resolve(&config).expect_err("conflict");
+        assert_eq!(
+            err,
+            "tui.keybindings: Ctrl+R is bound to both `toggle_reasoning` and `open_editor`"
+        );
+    }
+
+    #[test]
+    fn rejects_reserved_chords() {
+        let config = BTreeMap::from([(
+            KeyAction::OpenEditor,
+            KeyBindingSpec::One("ctrl+t".to_string()),
+        )]);
+        let err = KeyBindings::resolve(&config).expect_err("reserved");
+        assert_eq!(
+            err,
+            "tui.keybindings.open_editor: Ctrl+T is reserved for \"Toggle screen\""
+        );
+        assert!(KeyBindings::resolve(&BTreeMap::new()).is_ok());
+    }
+
+    #[test]
+    fn rejects_bare_printable_keys() {
+        for chord in ["j", "/", "shift+j", "space"] {
+            let config = BTreeMap::from([(
+                KeyAction::ToggleReasoning,
+                KeyBindingSpec::One(chord.to_string()),
+            )]);
+            let err = KeyBindings::resolve(&config).expect_err(chord);
+            assert!(err.contains("needs ctrl or alt"), "{chord}: {err}");
+        }
+        let config = BTreeMap::from([(
+            KeyAction::ToggleReasoning,
+            KeyBindingSpec::Many(vec!["alt+j".to_string(), "f3".to_string()]),
+        )]);
+        assert!(KeyBindings::resolve(&config).is_ok());
+    }
+
+    #[test]
+    fn listing_the_defaults_is_not_a_rebind() {
+        let config = BTreeMap::from([(
+            KeyAction::Newline,
+            KeyBindingSpec::Many(vec!["shift+enter".to_string(), "ctrl+j".to_string()]),
+        )]);
+        let bindings = KeyBindings::resolve(&config).expect("valid");
+        assert!(!bindings.is_rebound(KeyAction::Newline));
+
+        let config = BTreeMap::from([(
+            KeyAction::Newline,
+            KeyBindingSpec::One("shift+enter".to_string()),
+        )]);
+        let bindings = KeyBindings::resolve(&config).expect("valid");
+        assert!(bindings.is_rebound(KeyAction::Newline));
+    }
+}
@@ -11,10 +11,16 @@ mod fs_sanitize;
 pub mod auth;
 pub mod auth_accounts;
 pub mod account_usage;
+pub mod usage_report;
+pub mod model_traffic;
 mod account_switching;
-pub use account_switching::{RateLimitSwitchState, switch_active_account_on_rate_limit};
+pub use account_switching::{
+    AccountSwitchPolicy, ProjectIdentity, RateLimitSwitchState, matching_pin, pin_refers_to,
+    switch_active_account_on_rate_limit,
+};
 pub mod bash;
 mod auto_drive_pid;
+mod anthropic_messages;
 mod chat_completions;
 mod client;
 mod client_common;
@@ -31,6 +37,7 @@ pub mod config_profile;
 pub mod config_types;
 mod config_loader;
 mod conversation_history;
+mod context_inspector;
 pub mod context_timeline;
 pub mod acp;
 pub mod custom_prompts;
@@ -54,23 +61,31 @@ pub mod exec;
 mod exec_command;
 pub mod exec_env;
 mod flags;
+mod gemini;
 pub mod git_info;
 pub mod landlock;
 pub mod http_client;
+pub mod keybindings;
 pub mod housekeeping;
 pub mod mcp_connection_manager;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
+mod native_wire;
 pub mod remote_models;
 // Remote model discovery caches its own on-disk state within the module.
 mod cgroup;
 pub mod agent_defaults;
 mod agent_tool;
 pub use agent_tool::AGENT_MANAGER;
+pub use agent_tool::AgentStatus;
 mod dry_run_guard;
 mod image_comparison;
 pub mod git_worktree;
+pub mod agent_board;
+pub mod agent_evaluation;
+pub mod merge_conflicts;
+pub mod worktree_manager;
 pub mod slash_commands;
 pub mod parse_command;
 pub mod history;
@@ -88,7 +103,9 @@ pub use model_provider_info::create_oss_provider_with_base_url;
 mod conversation_manager;
 pub mod protocol;
 mod event_mapping;
+pub mod review_export;
 pub mod review_format;
+pub mod review_prompts;
 #[cfg(test)]
 mod prompt_assembly_tests;
 pub use code_protocol::protocol::InitialHistory;
@@ -104,6 +121,7 @@ pub mod default_client;
 pub mod model_family;
 mod openai_tools;
 mod patch_harness;
+pub mod patch_review;
 pub mod plan_tool;
 pub mod project_doc;
 pub mod project_features;
@@ -118,6 +136,7 @@ pub mod otel_init;
 mod text_encoding;
 pub mod theme_files;
 mod tool_apply_patch;
+mod validation_output;
 mod workflow_validation;
 pub mod turn_diff_tracker;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
@@ -0,0 +1,476 @@
+//! Conflict detection and resolution helpers for `/merge`.
+//!
+//! [`trial_merge`] merges a branch into its target inside a throwaway
+//! detached worktree, so conflicts are known before either checkout is
+//! touched. When there are conflicts, [`start_resolution`] merges the target
+//! branch into the `/branch` worktree instead, leaving conflict markers for an
+//! agent to resolve there. Each resolved file is staged only once the user
+//! approves it ([`approve_file`]), and a rejected file gets its markers back
+//! ([`reject_file`]). The default branch is only updated by the regular
+//! fast-forward path once the merge commit has been built and validated.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::process::Command;
+
+const CONFLICT_START_MARKER: &str = "<<<<<<< ";
+
+/// How each side changed a conflicted path, from `git status --porcelain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both sides modified the file.
+    Content,
+    /// Both sides added the file with different contents.
+    BothAdded,
+    /// The branch being merged in deleted the file; the other side changed it.
+    DeletedByThem,
+    /// The checkout being merged into deleted the file; the other side changed it.
+    DeletedByUs,
+    /// Any other unmerged state (renames, both deleted).
+    Other,
+}
+
+impl ConflictKind {
+    fn from_porcelain(code: &str) -> Self {
+        match code {
+            "UU" => ConflictKind::Content,
+            "AA" => ConflictKind::BothAdded,
+            "UD" => ConflictKind::DeletedByThem,
+            "DU" => ConflictKind::DeletedByUs,
+            _ => ConflictKind::Other,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictKind::Content => "both modified",
+            ConflictKind::BothAdded => "both added",
+            ConflictKind::DeletedByThem => "deleted by them",
+            ConflictKind::DeletedByUs => "deleted by us",
+            ConflictKind::Other => "unmerged",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictedFile {
+    /// Path relative to the checkout root.
+    pub path: String,
+    pub kind: ConflictKind,
+    /// Conflict regions left in the working file; zero once resolved.
+    pub hunks: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrialMergeOutcome {
+    Clean,
+    Conflicts(Vec<ConflictedFile>),
+}
+
+/// Merge `branch` into `target` in a scratch worktree and report conflicts.
+/// The scratch worktree is always removed afterwards.
+pub async fn trial_merge(
+    git_root: &Path,
+    target: &str,
+    branch: &str,
+) -> Result<TrialMergeOutcome, String> {
+    let scratch = scratch_worktree_path();
+    let scratch_str = scratch
+        .to_str()
+        .ok_or_else(|| "Invalid scratch worktree path".to_string())?;
+    let target_ref = format!("refs/heads/{target}");
+    git(
+        git_root,
+        &["worktree", "add", "--detach", scratch_str, &target_ref],
+    )
+    .await
+    .map_err(|e| format!("Failed to create scratch worktree: {e}"))?;
+
+    let outcome = run_trial(&scratch, branch).await;
+
+    let _ = git(git_root, &["worktree", "remove", "--force", scratch_str]).await;
+    if scratch.exists() {
+        let _ = tokio::fs::remove_dir_all(&scratch).await;
+    }
+    let _ = git(git_root, &["worktree", "prune"]).await;
+    outcome
+}
+
+async fn run_trial(scratch: &Path, branch: &str) -> Result<TrialMergeOutcome, String> {
+    let branch_ref = format!("refs/heads/{branch}");
+    let merge = Command::new("git")
+        .current_dir(scratch)
+        .args(["merge", "--no-ff", "--no-commit", &branch_ref])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run trial merge: {e}"))?;
+    if merge.status.success() {
+        return Ok(TrialMergeOutcome::Clean);
+    }
+    let files = conflicted_files(scratch).await?;
+    if files.is_empty() {
+        let stderr = String::from_utf8_lossy(&merge.stderr);
+        return Err(format!("Trial merge failed: {}", stderr.trim()));
+    }
+    Ok(TrialMergeOutcome::Conflicts(files))
+}
+
+fn scratch_worktree_path() -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!("magik-merge-trial-{}-{nanos}", std::process::id()))
+}
+
+/// Merge `target` into the worktree without committing, leaving conflict
+/// markers in place. Returns the conflicted files.
+pub async fn start_resolution(
+    worktree: &Path,
+    target: &str,
+) -> Result<Vec<ConflictedFile>, String> {
+    let target_ref = format!("refs/heads/{target}");
+    let merge = Command::new("git")
+        .current_dir(worktree)
+        .args(["merge", "--no-ff", "--no-commit", &target_ref])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to start merge: {e}"))?;
+    let files = conflicted_files(worktree).await?;
+    if !merge.status.success() && files.is_empty() {
+        let stderr = String::from_utf8_lossy(&merge.stderr);
+        return Err(format!("Failed to start merge: {}", stderr.trim()));
+    }
+    Ok(files)
+}
+
+/// Unmerged paths in `cwd` with the number of conflict regions left in each.
+pub async fn conflicted_files(cwd: &Path) -> Result<Vec<ConflictedFile>, String> {
+    let status = git(
+        cwd,
+        &["status", "--porcelain=v1", "-z", "--untracked-files=no"],
+    )
+    .await
+    .map_err(|e| format!("Failed to read merge status: {e}"))?;
+    let mut files = Vec::new();
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        // Entries are `XY <path>`; renames and copies add the source path as
+        // a separate entry.
+        let Some((code, path)) = entry.split_at_checked(2) else {
+            continue;
+        };
+        if code.contains(['R', 'C']) {
+            entries.next();
+        }
+        if !is_unmerged(code) {
+            continue;
+        }
+        let path = path.strip_prefix(' ').unwrap_or(path).to_string();
+        let hunks = match tokio::fs::read_to_string(cwd.join(&path)).await {
+            Ok(text) => count_conflict_hunks(&text),
+            Err(_) => 0,
+        };
+        files.push(ConflictedFile {
+            path,
+            kind: ConflictKind::from_porcelain(code),
+            hunks,
+        });
+    }
+    Ok(files)
+}
+
+fn is_unmerged(code: &str) -> bool {
+    matches!(code, "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU")
+}
+
+/// Number of `<<<<<<<` regions in a file.
+pub fn count_conflict_hunks(text: &str) -> usize {
+    text.lines()
+        .filter(|line| line.starts_with(CONFLICT_START_MARKER) || *line == "<<<<<<<")
+        .count()
+}
+
+/// Stage a resolved file. Refuses while conflict markers remain.
+pub async fn approve_file(cwd: &Path, path: &str) -> Result<(), String> {
+    let full = cwd.join(path);
+    if full.exists() {
+        let text = tokio::fs::read_to_string(&full).await.unwrap_or_default();
+        let hunks = count_conflict_hunks(&text);
+        if hunks > 0 {
+            return Err(format!("{path} still has {hunks} conflict marker(s)"));
+        }
+        git(cwd, &["add", "--", path]).await?;
+    } else {
+        git(cwd, &["rm", "--quiet", "--", path]).await?;
+    }
+    Ok(())
+}
+
+/// Throw away a proposed resolution and restore the conflict markers.
+pub async fn reject_file(cwd: &Path, path: &str) -> Result<(), String> {
+    git(cwd, &["checkout", "--merge", "--", path])
+        .await
+        .map(|_| ())
+}
+
+/// Abandon an in-progress resolution, restoring the worktree to its branch.
+pub async fn abort_resolution(cwd: &Path) -> Result<(), String> {
+    git(cwd, &["merge", "--abort"]).await.map(|_| ())
+}
+
+/// Commit the merge once every file is staged.
+pub async fn commit_resolution(cwd: &Path) -> Result<(), String> {
+    let remaining = conflicted_files(cwd).await?;
+    if !remaining.is_empty() {
+        return Err(format!("{} file(s) still unmerged", remaining.len()));
+    }
+    git(cwd, &["commit", "--no-edit"]).await.map(|_| ())
+}
+
+async fn git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .current_dir(cwd)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("failed to run git {}: {e}", args.first().unwrap_or(&"")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Hidden instructions for the agent turn that resolves `files` in `worktree`.
+pub fn resolution_preface(
+    worktree: &Path,
+    target: &str,
+    branch: &str,
+    files: &[ConflictedFile],
+) -> String {
+    let mut preface = format!(
+        "[developer] `/merge` merged '{target}' into branch '{branch}' in the worktree {worktree} so the conflicts can be resolved there before '{branch}' is fast-forwarded into '{target}'. The merge is in progress and not committed.\n\nResolve these files one at a time, in order:\n",
+        worktree = worktree.display(),
+    );
+    for file in files {
+        preface.push_str(&format!(
+            "- {} ({}, {} conflict region(s))\n",
+            file.path,
+            file.kind.label(),
+            file.hunks
+        ));
+    }
+    preface.push_str(
+        "\nFor each file:\n- Read the whole file and the history of both sides (`git log --oneline -3 MERGE_HEAD -- <file>` and `git log --oneline -3 HEAD -- <file>`) to understand what each change intended.\n- Edit the conflict regions with apply_patch so both sides' intent survives. Remove every `<<<<<<<`, `=======` and `>>>>>>>` marker.\n- For delete/modify conflicts, keep the file only if the other side's changes are still needed, and say which you chose.\n- Do NOT run `git add`, `git commit`, `git merge --abort`, `git checkout`, `git reset`, or use `-X ours/theirs`. The user approves each file in the merge panel, which stages it.\n\nWhen every file is edited, reply with one line per file summarizing how it was resolved, then stop.\n",
+    );
+    preface
+}
+
+/// Hidden instructions for the turn that commits and validates the merge.
+pub fn validation_preface(worktree: &Path, target: &str, branch: &str) -> String {
+    format!(
+        "[developer] Every conflicted file in {worktree} has been resolved and approved, and the merge of '{target}' into '{branch}' is already committed there. Validate it:\n1. Build the project and run its tests and linters the way this repository does (check AGENTS.md, README, CI config). Fix any breakage caused by the merge with apply_patch and commit the fixes on top of the merge commit.\n2. Do not touch the '{target}' checkout and do not remove the worktree.\n3. Report the commands you ran and their results. If everything passes, tell the user to run /merge again to fast-forward '{target}' and clean up the worktree.\n",
+        worktree = worktree.display(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn counts_conflict_regions() {
+        let text = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> main\nb\n<<<<<<< HEAD\nx\n=======\n>>>>>>> main\n";
+        assert_eq!(count_conflict_hunks(text), 2);
+        assert_eq!(count_conflict_hunks("no markers <<<<<<< inline\n"), 0);
+    }
+
+    #[test]
+    fn classifies_porcelain_codes() {
+        assert!(is_unmerged("UU"));
+        assert!(!is_unmerged("M "));
+        assert_eq!(ConflictKind::from_porcelain("UU"), ConflictKind::Content);
+        assert_eq!(
+            ConflictKind::from_porcelain("DU"),
+            ConflictKind::DeletedByUs
+        );
+        assert_eq!(ConflictKind::from_porcelain("DD"), ConflictKind::Other);
+    }
+
+    #[test]
+    fn validation_preface_starts_from_the_merge_commit() {
+        let preface = validation_preface(Path::new("/tmp/wt"), "main", "code-branch-fix");
+        assert!(preface.contains("is already committed"));
+        assert!(!preface.contains("git commit --no-edit"));
+    }
+
+    /// Runs a git command in the test repository and asserts success.
+    fn run_git_in(repo: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(repo)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    /// Runs a git command and returns its trimmed stdout output.
+    fn run_git_stdout(repo: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .current_dir(repo)
+            .args(args)
+            .output()
+            .expect("git command");
+        assert!(output.status.success(), "git command failed: {args:?}");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_all(repo: &Path, message: &str) {
+        run_git_in(repo, &["add", "-A"]);
+        run_git_in(repo, &["commit", "--quiet", "-m", message]);
+    }
+
+    /// Repository on `main` with a `feature` branch that conflicts with it:
+    /// both sides rewrite `shared.txt`, `feature` deletes `notes.txt` while
+    /// `main` edits it, and `docs` only adds a file.
+    fn conflicting_repo(repo: &Path) {
+        run_git_in(repo, &["init", "--quiet"]);
+        run_git_in(repo, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        run_git_in(repo, &["config", "user.name", "Merge Tests"]);
+        run_git_in(repo, &["config", "user.email", "merge@example.com"]);
+        run_git_in(repo, &["config", "core.autocrlf", "false"]);
+        std::fs::write(repo.join("shared.txt"), "base\n").unwrap();
+        std::fs::write(repo.join("notes.txt"), "notes\n").unwrap();
+        commit_all(repo, "base");
+
+        run_git_in(repo, &["checkout", "--quiet", "-b", "feature"]);
+        std::fs::write(repo.join("shared.txt"), "feature\n").unwrap();
+        std::fs::remove_file(repo.join("notes.txt")).unwrap();
+        commit_all(repo, "feature");
+
+        run_git_in(repo, &["checkout", "--quiet", "-b", "docs", "main"]);
+        std::fs::write(repo.join("README.md"), "docs\n").unwrap();
+        commit_all(repo, "docs");
+
+        run_git_in(repo, &["checkout", "--quiet", "main"]);
+        std::fs::write(repo.join("shared.txt"), "main\n").unwrap();
+        std::fs::write(repo.join("notes.txt"), "notes from main\n").unwrap();
+        commit_all(repo, "main");
+    }
+
+    #[tokio::test]
+    async fn trial_merge_reports_conflicts_without_touching_the_checkout() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path();
+        conflicting_repo(repo);
+        let head = run_git_stdout(repo, &["rev-parse", "HEAD"]);
+
+        let outcome = trial_merge(repo, "main", "feature").await.unwrap();
+        assert_eq!(
+            outcome,
+            TrialMergeOutcome::Conflicts(vec![
+                ConflictedFile {
+                    path: "notes.txt".to_string(),
+                    kind: ConflictKind::DeletedByThem,
+                    hunks: 0,
+                },
+                ConflictedFile {
+                    path: "shared.txt".to_string(),
+                    kind: ConflictKind::Content,
+                    hunks: 1,
+                },
+            ])
+        );
+        assert_eq!(
+            trial_merge(repo, "main", "docs").await.unwrap(),
+            TrialMergeOutcome::Clean
+        );
+
+        assert_eq!(run_git_stdout(repo, &["rev-parse", "HEAD"]), head);
+        assert_eq!(run_git_stdout(repo, &["status", "--porcelain"]), "");
+        let worktrees = run_git_stdout(repo, &["worktree", "list", "--porcelain"]);
+        assert_eq!(worktrees.matches("worktree ").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn resolution_stages_only_approved_files_and_commits_the_merge() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        conflicting_repo(&repo);
+        let worktree = temp.path().join("feature-wt");
+        run_git_in(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "--quiet",
+                worktree.to_str().unwrap(),
+                "feature",
+            ],
+        );
+
+        let files = start_resolution(&worktree, "main").await.unwrap();
+        assert_eq!(
+            files,
+            vec![
+                ConflictedFile {
+                    path: "notes.txt".to_string(),
+                    kind: ConflictKind::DeletedByUs,
+                    hunks: 0,
+                },
+                ConflictedFile {
+                    path: "shared.txt".to_string(),
+                    kind: ConflictKind::Content,
+                    hunks: 1,
+                },
+            ]
+        );
+
+        let err = approve_file(&worktree, "shared.txt").await.unwrap_err();
+        assert_eq!(err, "shared.txt still has 1 conflict marker(s)");
+
+        // A rejected resolution gets its conflict markers back.
+        std::fs::write(worktree.join("shared.txt"), "proposed\n").unwrap();
+        reject_file(&worktree, "shared.txt").await.unwrap();
+        let restored = std::fs::read_to_string(worktree.join("shared.txt")).unwrap();
+        assert_eq!(count_conflict_hunks(&restored), 1);
+        assert_eq!(conflicted_files(&worktree).await.unwrap().len(), 2);
+
+        std::fs::write(worktree.join("shared.txt"), "feature and main\n").unwrap();
+        approve_file(&worktree, "shared.txt").await.unwrap();
+        let remaining = conflicted_files(&worktree).await.unwrap();
+        assert_eq!(
+            remaining
+                .iter()
+                .map(|f| f.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["notes.txt"]
+        );
+        assert!(commit_resolution(&worktree).await.is_err());
+
+        // Keeping the deletion stages the removal.
+        std::fs::remove_file(worktree.join("notes.txt")).unwrap();
+        approve_file(&worktree, "notes.txt").await.unwrap();
+        commit_resolution(&worktree).await.unwrap();
+
+        let parents = run_git_stdout(&worktree, &["rev-list", "--parents", "-n", "1", "HEAD"]);
+        assert_eq!(parents.split_whitespace().count(), 3);
+        assert_eq!(
+            run_git_stdout(&worktree, &["show", "HEAD:shared.txt"]),
+            "feature and main"
+        );
+        assert_eq!(
+            run_git_stdout(&worktree, &["ls-tree", "--name-only", "HEAD"]),
+            "shared.txt"
+        );
+    }
+}
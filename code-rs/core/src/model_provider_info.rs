@@ -22,13 +22,17 @@ const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
 const MAX_REQUEST_MAX_RETRIES: u64 = 100;
+const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
 
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
-/// *Responses* API. The two protocols use different request/response shapes
-/// and *cannot* be auto-detected at runtime, therefore each provider entry
-/// must declare which one it expects.
+/// *Responses* API. Anthropic and Gemini are spoken natively as well. The
+/// protocols use different request/response shapes and *cannot* be
+/// auto-detected at runtime, therefore each provider entry must declare which
+/// one it expects.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WireApi {
@@ -42,6 +46,12 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// Anthropic Messages API at `/v1/messages`.
+    Anthropic,
+
+    /// Gemini `streamGenerateContent` API at `/v1beta/models/{model}`.
+    Gemini,
 }
 
 /// Serializable representation of a provider definition.
@@ -206,11 +216,8 @@ impl ModelProviderInfo {
 
         let url = self.get_full_url(&effective_auth);
 
-        let mut builder = client.post(&url);
-
-        if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
-        }
+        let builder = client.post(&url);
+        let builder = self.apply_auth(builder, effective_auth.as_ref()).await?;
 
         Ok(self.apply_http_headers(builder))
     }
@@ -226,11 +233,8 @@ impl ModelProviderInfo {
     ) -> crate::error::Result<reqwest::RequestBuilder> {
         let effective_auth = self.effective_auth(auth)?;
 
-        let mut builder = client.request(method, url);
-
-        if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
-        }
+        let builder = client.request(method, url);
+        let builder = self.apply_auth(builder, effective_auth.as_ref()).await?;
 
         Ok(self.apply_http_headers(builder))
     }
@@ -260,6 +264,42 @@ impl ModelProviderInfo {
         Ok(self.apply_http_headers(builder))
     }
 
+    /// Attach credentials the way the provider's wire API expects them:
+    /// `x-api-key` for Anthropic, `x-goog-api-key` for Gemini and a bearer
+    /// token for everything else.
+    async fn apply_auth(
+        &self,
+        builder: reqwest::RequestBuilder,
+        auth: Option<&CodexAuth>,
+    ) -> crate::error::Result<reqwest::RequestBuilder> {
+        let Some(auth) = auth else {
+            return Ok(builder);
+        };
+        let token = auth.get_token().await?;
+        Ok(match self.wire_api {
+            WireApi::Anthropic => {
+                let builder = builder.header("x-api-key", token);
+                if self.has_http_header("anthropic-version") {
+                    builder
+                } else {
+                    builder.header("anthropic-version", DEFAULT_ANTHROPIC_VERSION)
+                }
+            }
+            WireApi::Gemini => builder.header("x-goog-api-key", token),
+            WireApi::Responses | WireApi::ResponsesWebsocket | WireApi::Chat => {
+                builder.bearer_auth(token)
+            }
+        })
+    }
+
+    fn has_http_header(&self, name: &str) -> bool {
+        self.http_headers
+            .iter()
+            .flat_map(|headers| headers.keys())
+            .chain(self.env_http_headers.iter().flat_map(|headers| headers.keys()))
+            .any(|key| key.eq_ignore_ascii_case(name))
+    }
+
     fn effective_auth(
         &self,
         auth: &Option<CodexAuth>,
@@ -305,13 +345,13 @@ impl ModelProviderInfo {
     }
 
     pub(crate) fn get_full_url(&self, auth: &Option<CodexAuth>) -> String {
-        let default_base_url = if auth
-            .as_ref()
-            .is_some_and(|auth| auth.mode.is_chatgpt())
-        {
-            "https://chatgpt.com/backend-api/codex"
-        } else {
-            "https://api.openai.com/v1"
+        let default_base_url = match self.wire_api {
+            WireApi::Anthropic => DEFAULT_ANTHROPIC_BASE_URL,
+            WireApi::Gemini => DEFAULT_GEMINI_BASE_URL,
+            _ if auth.as_ref().is_some_and(|auth| auth.mode.is_chatgpt()) => {
+                "https://chatgpt.com/backend-api/codex"
+            }
+            _ => "https://api.openai.com/v1",
         };
         let query_string = self.get_query_string();
         let base_url = self
@@ -324,7 +364,29 @@ impl ModelProviderInfo {
                 format!("{base_url}/responses{query_string}")
             }
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::Anthropic => format!("{base_url}/messages{query_string}"),
+            WireApi::Gemini => format!("{base_url}/models{query_string}"),
+        }
+    }
+
+    /// Streaming endpoint for a specific model. Only Gemini encodes the model
+    /// in the path; every other wire API posts to [`Self::get_full_url`].
+    pub(crate) fn get_model_stream_url(&self, auth: &Option<CodexAuth>, model: &str) -> String {
+        if self.wire_api != WireApi::Gemini {
+            return self.get_full_url(auth);
         }
+        let base_url = self
+            .base_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_GEMINI_BASE_URL.to_string());
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        let mut url = format!("{base_url}/models/{model}:streamGenerateContent?alt=sse");
+        if let Some(params) = &self.query_params {
+            for (key, value) in params {
+                url.push_str(&format!("&{key}={value}"));
+            }
+        }
+        url
     }
 
     pub(crate) fn get_compact_url(&self, auth: &Option<CodexAuth>) -> Option<String> {
@@ -773,4 +835,34 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
 
         assert_eq!(version, code_version::wire_compatible_version());
     }
+
+    #[test]
+    fn native_wire_apis_use_their_own_endpoints() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Gemini"
+wire_api = "gemini"
+query_params = { key = "abc" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(provider.wire_api, WireApi::Gemini);
+        assert_eq!(
+            provider.get_model_stream_url(&None, "models/gemini-2.5-pro"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse&key=abc"
+        );
+
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Anthropic"
+wire_api = "anthropic"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(provider.get_full_url(&None), "https://api.anthropic.com/v1/messages");
+        assert_eq!(
+            provider.get_model_stream_url(&None, "claude-sonnet-4-5"),
+            provider.get_full_url(&None)
+        );
+    }
 }
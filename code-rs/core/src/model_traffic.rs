@@ -0,0 +1,525 @@
+//! Record/replay of raw model traffic.
+//!
+//! In record mode every streamed model request is saved as a pair of files in
+//! the recording directory: `NNNN.request.json` (wire API, endpoint, request
+//! hash and body) and `NNNN.sse` (the raw response bytes). Replay mode loads a
+//! recording and feeds those bytes back through the same SSE parsers, so a
+//! session can be re-run deterministically without network access.
+//!
+//! Replayed requests are matched by a hash of the request body with volatile
+//! fields removed. When no unused exchange has the same hash, replay serves
+//! the next unused exchange in recording order (or fails in strict mode) and
+//! appends a divergence record to `divergences.jsonl`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+use tokio_util::io::ReaderStream;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config_types::{ModelTrafficConfig, ModelTrafficMode};
+use crate::error::{CodexErr, Result};
+
+/// Request fields that change between otherwise identical runs.
+const VOLATILE_REQUEST_FIELDS: &[&str] = &["prompt_cache_key"];
+const DIVERGENCE_LOG: &str = "divergences.jsonl";
+const REQUEST_SUFFIX: &str = ".request.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficWireApi {
+    Responses,
+    Chat,
+    Anthropic,
+    Gemini,
+}
+
+/// Metadata and body of one recorded request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub index: usize,
+    pub wire_api: TrafficWireApi,
+    pub endpoint: String,
+    pub request_hash: String,
+    pub recorded_at: DateTime<Utc>,
+    pub request: Value,
+}
+
+/// A replayed request that did not match its recording exactly.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplayDivergence {
+    /// 1-based position of the request within this replay.
+    pub request_number: usize,
+    pub request_hash: String,
+    /// Recorded exchange served instead, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_hash: Option<String>,
+    /// JSON pointer to the first field that differs from the served recording.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_difference: Option<String>,
+}
+
+#[derive(Debug)]
+enum TrafficState {
+    Record {
+        next_index: usize,
+    },
+    Replay {
+        exchanges: Vec<RecordedRequest>,
+        used: Vec<bool>,
+        requests_seen: usize,
+        load_error: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct ModelTraffic {
+    dir: PathBuf,
+    replay: bool,
+    strict: bool,
+    state: Mutex<TrafficState>,
+}
+
+impl ModelTraffic {
+    /// Build the recorder/replayer for a session, or `None` when traffic
+    /// capture is off. Replay problems (missing directory, unreadable files)
+    /// surface as errors on the first request rather than silently falling
+    /// back to the network.
+    pub(crate) fn from_config(
+        config: &ModelTrafficConfig,
+        code_home: &Path,
+        session_id: Uuid,
+    ) -> Option<Self> {
+        let dir = config.dir.clone().unwrap_or_else(|| {
+            code_home
+                .join("sessions")
+                .join("traffic")
+                .join(session_id.to_string())
+        });
+
+        let state = match config.mode {
+            ModelTrafficMode::Off => return None,
+            ModelTrafficMode::Record => TrafficState::Record {
+                next_index: load_exchanges(&dir)
+                    .ok()
+                    .and_then(|exchanges| exchanges.last().map(|last| last.index + 1))
+                    .unwrap_or(1),
+            },
+            ModelTrafficMode::Replay => {
+                let (exchanges, load_error) = if config.dir.is_none() {
+                    (
+                        Vec::new(),
+                        Some("model_traffic.dir must point at a recording to replay".to_string()),
+                    )
+                } else {
+                    match load_exchanges(&dir) {
+                        Ok(exchanges) if exchanges.is_empty() => (
+                            Vec::new(),
+                            Some(format!("no recorded exchanges in {}", dir.display())),
+                        ),
+                        Ok(exchanges) => (exchanges, None),
+                        Err(err) => (
+                            Vec::new(),
+                            Some(format!("failed to read {}: {err}", dir.display())),
+                        ),
+                    }
+                };
+                TrafficState::Replay {
+                    used: vec![false; exchanges.len()],
+                    exchanges,
+                    requests_seen: 0,
+                    load_error,
+                }
+            }
+        };
+
+        Some(Self {
+            dir,
+            replay: config.mode == ModelTrafficMode::Replay,
+            strict: config.strict,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub(crate) fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    /// Wrap a live response byte stream so its chunks are also written to the
+    /// recording. Recording failures are logged and never interrupt the stream.
+    pub(crate) fn record<S>(
+        &self,
+        wire_api: TrafficWireApi,
+        endpoint: &str,
+        request: &Value,
+        stream: S,
+    ) -> BoxStream<'static, Result<Bytes>>
+    where
+        S: Stream<Item = Result<Bytes>> + Send + 'static,
+    {
+        let mut file = match self.start_exchange(wire_api, endpoint, request) {
+            Ok(file) => file,
+            Err(err) => {
+                warn!("failed to record model traffic in {}: {err}", self.dir.display());
+                None
+            }
+        };
+        stream
+            .map(move |chunk| {
+                let failed = match (&chunk, file.as_mut()) {
+                    (Ok(bytes), Some(out)) => out.write_all(bytes).err(),
+                    _ => None,
+                };
+                if let Some(err) = failed {
+                    warn!("failed to record model traffic: {err}");
+                    file = None;
+                }
+                chunk
+            })
+            .boxed()
+    }
+
+    fn start_exchange(
+        &self,
+        wire_api: TrafficWireApi,
+        endpoint: &str,
+        request: &Value,
+    ) -> io::Result<Option<File>> {
+        let index = {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| io::Error::other("model traffic state poisoned"))?;
+            let TrafficState::Record { next_index } = &mut *state else {
+                return Ok(None);
+            };
+            let index = *next_index;
+            *next_index += 1;
+            index
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let recorded = RecordedRequest {
+            index,
+            wire_api,
+            endpoint: endpoint.to_string(),
+            request_hash: request_hash(request),
+            recorded_at: Utc::now(),
+            request: request.clone(),
+        };
+        fs::write(
+            self.dir.join(format!("{index:04}{REQUEST_SUFFIX}")),
+            serde_json::to_vec_pretty(&recorded)?,
+        )?;
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(self.dir.join(format!("{index:04}.sse")))?;
+        Ok(Some(file))
+    }
+
+    /// Serve the recorded response for `request`.
+    pub(crate) fn replay(
+        &self,
+        wire_api: TrafficWireApi,
+        request: &Value,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let hash = request_hash(request);
+        let (index, divergence) = {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| replay_error("model traffic state poisoned".to_string()))?;
+            let TrafficState::Replay {
+                exchanges,
+                used,
+                requests_seen,
+                load_error,
+            } = &mut *state
+            else {
+                return Err(replay_error("model traffic is not in replay mode".to_string()));
+            };
+            if let Some(err) = load_error {
+                return Err(replay_error(err.clone()));
+            }
+            *requests_seen += 1;
+
+            let unused = |idx: &usize| !used[*idx] && exchanges[*idx].wire_api == wire_api;
+            let exact = (0..exchanges.len())
+                .filter(unused)
+                .find(|idx| exchanges[*idx].request_hash == hash);
+            let chosen = exact.or_else(|| {
+                if self.strict {
+                    None
+                } else {
+                    (0..exchanges.len()).find(unused)
+                }
+            });
+            let divergence = exact.is_none().then(|| ReplayDivergence {
+                request_number: *requests_seen,
+                request_hash: hash.clone(),
+                served_index: chosen.map(|idx| exchanges[idx].index),
+                expected_hash: chosen.map(|idx| exchanges[idx].request_hash.clone()),
+                first_difference: chosen
+                    .and_then(|idx| first_difference(&exchanges[idx].request, request)),
+            });
+            if let Some(idx) = chosen {
+                used[idx] = true;
+            }
+            (chosen.map(|idx| exchanges[idx].index), divergence)
+        };
+
+        if let Some(divergence) = divergence.as_ref() {
+            warn!(
+                request = divergence.request_number,
+                served = ?divergence.served_index,
+                difference = divergence.first_difference.as_deref().unwrap_or(""),
+                "replayed model request diverged from the recording"
+            );
+            if let Err(err) = self.append_divergence(divergence) {
+                warn!("failed to write replay divergence: {err}");
+            }
+        }
+
+        let Some(index) = index else {
+            return Err(replay_error(format!(
+                "no recorded exchange left for request {hash} in {}",
+                self.dir.display()
+            )));
+        };
+        let bytes = fs::read(self.dir.join(format!("{index:04}.sse")))?;
+        Ok(ReaderStream::new(io::Cursor::new(bytes))
+            .map_err(CodexErr::Io)
+            .boxed())
+    }
+
+    fn append_divergence(&self, divergence: &ReplayDivergence) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(DIVERGENCE_LOG))?;
+        let mut line = serde_json::to_string(divergence)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+    }
+}
+
+fn replay_error(message: String) -> CodexErr {
+    CodexErr::Io(io::Error::other(format!("model traffic replay: {message}")))
+}
+
+/// Recorded exchanges in `dir`, ordered by index.
+pub fn load_exchanges(dir: &Path) -> io::Result<Vec<RecordedRequest>> {
+    let mut exchanges = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_request = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(REQUEST_SUFFIX));
+        if !is_request {
+            continue;
+        }
+        let contents = fs::read(&path)?;
+        match serde_json::from_slice::<RecordedRequest>(&contents) {
+            Ok(recorded) => exchanges.push(recorded),
+            Err(err) => warn!("skipping unreadable recording {}: {err}", path.display()),
+        }
+    }
+    exchanges.sort_by_key(|recorded| recorded.index);
+    Ok(exchanges)
+}
+
+/// Stable hash of a request body, ignoring [`VOLATILE_REQUEST_FIELDS`].
+pub fn request_hash(request: &Value) -> String {
+    let mut normalized = request.clone();
+    if let Some(obj) = normalized.as_object_mut() {
+        for field in VOLATILE_REQUEST_FIELDS {
+            obj.remove(*field);
+        }
+    }
+    let encoded = serde_json::to_vec(&normalized).unwrap_or_default();
+    let mut sha = Sha1::new();
+    sha.update(encoded);
+    format!("{:x}", sha.finalize())
+}
+
+/// JSON pointer of the first difference between two requests, skipping
+/// volatile top-level fields.
+fn first_difference(expected: &Value, actual: &Value) -> Option<String> {
+    fn walk(expected: &Value, actual: &Value, pointer: &mut String, top: bool) -> bool {
+        match (expected, actual) {
+            (Value::Object(a), Value::Object(b)) => {
+                let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    if top && VOLATILE_REQUEST_FIELDS.contains(&key.as_str()) {
+                        continue;
+                    }
+                    let len = pointer.len();
+                    pointer.push('/');
+                    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                    let differs = match (a.get(key), b.get(key)) {
+                        (Some(a), Some(b)) => walk(a, b, pointer, false),
+                        _ => true,
+                    };
+                    if differs {
+                        return true;
+                    }
+                    pointer.truncate(len);
+                }
+                false
+            }
+            (Value::Array(a), Value::Array(b)) => {
+                for idx in 0..a.len().max(b.len()) {
+                    let len = pointer.len();
+                    pointer.push_str(&format!("/{idx}"));
+                    let differs = match (a.get(idx), b.get(idx)) {
+                        (Some(a), Some(b)) => walk(a, b, pointer, false),
+                        _ => true,
+                    };
+                    if differs {
+                        return true;
+                    }
+                    pointer.truncate(len);
+                }
+                false
+            }
+            (a, b) => a != b,
+        }
+    }
+
+    let mut pointer = String::new();
+    walk(expected, actual, &mut pointer, true).then_some(pointer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn config(mode: ModelTrafficMode, dir: &Path, strict: bool) -> ModelTrafficConfig {
+        ModelTrafficConfig {
+            mode,
+            dir: Some(dir.to_path_buf()),
+            strict,
+        }
+    }
+
+    async fn collect(stream: BoxStream<'static, Result<Bytes>>) -> Vec<u8> {
+        stream
+            .try_fold(Vec::new(), |mut acc, chunk| async move {
+                acc.extend_from_slice(&chunk);
+                Ok(acc)
+            })
+            .await
+            .expect("stream bytes")
+    }
+
+    fn request(text: &str, cache_key: &str) -> Value {
+        json!({
+            "model": "gpt-5",
+            "input": [{"role": "user", "content": text}],
+            "prompt_cache_key": cache_key,
+        })
+    }
+
+    async fn record_two(dir: &Path) {
+        let recorder = ModelTraffic::from_config(
+            &config(ModelTrafficMode::Record, dir, false),
+            dir,
+            Uuid::nil(),
+        )
+        .expect("recorder");
+        for (text, body) in [("first", "data: one\n\n"), ("second", "data: two\n\n")] {
+            let live = futures::stream::iter(vec![Ok(Bytes::from(body))]);
+            let recorded = recorder.record(
+                TrafficWireApi::Responses,
+                "https://example.test/v1/responses",
+                &request(text, "session-a"),
+                live,
+            );
+            assert_eq!(collect(recorded).await, body.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_matches_by_hash_ignoring_cache_key() {
+        let tmp = TempDir::new().expect("tempdir");
+        record_two(tmp.path()).await;
+
+        let replayer = ModelTraffic::from_config(
+            &config(ModelTrafficMode::Replay, tmp.path(), true),
+            tmp.path(),
+            Uuid::nil(),
+        )
+        .expect("replayer");
+        assert!(replayer.is_replay());
+
+        // Out of order and with a different session cache key.
+        let second = replayer
+            .replay(TrafficWireApi::Responses, &request("second", "session-b"))
+            .expect("second");
+        assert_eq!(collect(second).await, b"data: two\n\n");
+        let first = replayer
+            .replay(TrafficWireApi::Responses, &request("first", "session-b"))
+            .expect("first");
+        assert_eq!(collect(first).await, b"data: one\n\n");
+        assert!(!tmp.path().join(DIVERGENCE_LOG).exists());
+    }
+
+    #[tokio::test]
+    async fn divergent_request_falls_back_in_order_and_is_reported() {
+        let tmp = TempDir::new().expect("tempdir");
+        record_two(tmp.path()).await;
+
+        let replayer = ModelTraffic::from_config(
+            &config(ModelTrafficMode::Replay, tmp.path(), false),
+            tmp.path(),
+            Uuid::nil(),
+        )
+        .expect("replayer");
+        let served = replayer
+            .replay(TrafficWireApi::Responses, &request("changed", "session-b"))
+            .expect("fallback");
+        assert_eq!(collect(served).await, b"data: one\n\n");
+
+        let log = fs::read_to_string(tmp.path().join(DIVERGENCE_LOG)).expect("divergence log");
+        let divergence: ReplayDivergence =
+            serde_json::from_str(log.lines().next().expect("one line")).expect("parse");
+        assert_eq!(divergence.request_number, 1);
+        assert_eq!(divergence.served_index, Some(1));
+        assert_eq!(
+            divergence.first_difference.as_deref(),
+            Some("/input/0/content")
+        );
+
+        let strict = ModelTraffic::from_config(
+            &config(ModelTrafficMode::Replay, tmp.path(), true),
+            tmp.path(),
+            Uuid::nil(),
+        )
+        .expect("strict replayer");
+        assert!(
+            strict
+                .replay(TrafficWireApi::Responses, &request("changed", "x"))
+                .is_err()
+        );
+    }
+}
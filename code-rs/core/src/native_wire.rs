@@ -0,0 +1,331 @@
+//! Request plumbing shared by the native (non-OpenAI) wire implementations in
+//! [`crate::anthropic_messages`] and [`crate::gemini`].
+//!
+//! Both APIs authenticate with provider API keys only, never with the
+//! OpenAI/ChatGPT login, so credentials are resolved from the provider entry
+//! (`env_key` or `experimental_bearer_token`) alone.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bytes::Bytes;
+use code_otel::otel_event_manager::OtelEventManager;
+use eventsource_stream::Event;
+use eventsource_stream::EventStream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use futures::stream::BoxStream;
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio::time::timeout;
+use tracing::debug;
+
+use crate::ModelProviderInfo;
+use crate::chat_completions::header_map_to_json;
+use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
+use crate::debug_logger::DebugLogger;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::error::RetryLimitReachedError;
+use crate::error::UnexpectedResponseError;
+use crate::model_traffic::ModelTraffic;
+use crate::model_traffic::TrafficWireApi;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::create_tools_json_for_responses_api;
+use crate::util::backoff;
+
+pub(crate) type ByteStream = BoxStream<'static, Result<Bytes>>;
+
+/// Transport state borrowed from the `ModelClient` for a single request.
+pub(crate) struct WireRequestContext<'a> {
+    pub(crate) client: &'a reqwest::Client,
+    pub(crate) provider: &'a ModelProviderInfo,
+    pub(crate) debug_logger: &'a Arc<Mutex<DebugLogger>>,
+    pub(crate) otel_event_manager: Option<OtelEventManager>,
+    pub(crate) log_tag: Option<&'a str>,
+    pub(crate) traffic: Option<&'a ModelTraffic>,
+}
+
+/// Model settings that the native APIs express in the request body.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WireModelOptions {
+    pub(crate) effort: ReasoningEffortConfig,
+    pub(crate) max_output_tokens: Option<u64>,
+}
+
+/// A function tool in provider-neutral form.
+pub(crate) struct FunctionToolSpec {
+    pub(crate) name: String,
+    pub(crate) description: String,
+    pub(crate) parameters: Value,
+}
+
+/// Function tools from the prompt. Responses-only tool kinds (local shell,
+/// freeform, web search) have no native equivalent and are skipped, matching
+/// the Chat Completions conversion.
+pub(crate) fn function_tool_specs(tools: &[OpenAiTool]) -> Result<Vec<FunctionToolSpec>> {
+    let specs = create_tools_json_for_responses_api(tools)?
+        .into_iter()
+        .filter(|tool| tool.get("type").and_then(Value::as_str) == Some("function"))
+        .filter_map(|mut tool| {
+            let name = tool.get("name")?.as_str()?.to_string();
+            let description = tool
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let parameters = tool
+                .get_mut("parameters")
+                .map(Value::take)
+                .unwrap_or_else(|| serde_json::json!({ "type": "object", "properties": {} }));
+            Some(FunctionToolSpec {
+                name,
+                description,
+                parameters,
+            })
+        })
+        .collect();
+    Ok(specs)
+}
+
+/// Split a `data:<mime>;base64,<payload>` URL into its media type and payload.
+pub(crate) fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix("data:")?;
+    let (meta, data) = rest.split_once(',')?;
+    let mime = meta.strip_suffix(";base64")?;
+    Some((mime, data))
+}
+
+/// Parse tool-call arguments recorded as a JSON string, falling back to an
+/// empty object so a malformed call still round-trips.
+pub(crate) fn arguments_to_object(arguments: &str) -> Value {
+    match serde_json::from_str::<Value>(arguments) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => Value::Object(serde_json::Map::new()),
+    }
+}
+
+/// POST `payload` to `url` and return the response body as a byte stream,
+/// retrying rate limits and server errors like the Chat implementation does.
+/// When model traffic is being replayed the recording is served instead.
+pub(crate) async fn open_event_stream(
+    ctx: &WireRequestContext<'_>,
+    wire_api: TrafficWireApi,
+    url: &str,
+    payload: &Value,
+) -> Result<(ByteStream, String)> {
+    debug!(
+        "POST to {}: {}",
+        url,
+        serde_json::to_string_pretty(payload).unwrap_or_default()
+    );
+
+    if let Some(traffic) = ctx.traffic.filter(|traffic| traffic.is_replay()) {
+        return Ok((traffic.replay(wire_api, payload)?, String::new()));
+    }
+
+    let parsed_url = reqwest::Url::parse(url).map_err(|err| {
+        CodexErr::UnsupportedOperation(format!("invalid provider URL {url}: {err}"))
+    })?;
+    let provider = ctx.provider;
+    let max_retries = provider.request_max_retries();
+    let mut attempt = 0;
+    let mut request_id = String::new();
+    loop {
+        attempt += 1;
+
+        let req_builder = provider
+            .create_request_builder_for_url(
+                ctx.client,
+                &None,
+                reqwest::Method::POST,
+                parsed_url.clone(),
+            )
+            .await?
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(payload);
+
+        if request_id.is_empty() {
+            let header_snapshot = req_builder
+                .try_clone()
+                .and_then(|builder| builder.build().ok())
+                .map(|req| header_map_to_json(req.headers()));
+            if let Ok(logger) = ctx.debug_logger.lock() {
+                request_id = logger
+                    .start_request_log(url, payload, header_snapshot.as_ref(), ctx.log_tag)
+                    .unwrap_or_default();
+            }
+        }
+
+        match req_builder.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                if let Ok(logger) = ctx.debug_logger.lock() {
+                    let _ = logger.append_response_event(
+                        &request_id,
+                        "stream_initiated",
+                        &serde_json::json!({
+                            "status": "success",
+                            "status_code": resp.status().as_u16()
+                        }),
+                    );
+                }
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                let stream = match ctx.traffic {
+                    Some(traffic) => traffic.record(wire_api, url, payload, stream),
+                    None => stream.boxed(),
+                };
+                return Ok((stream, request_id));
+            }
+            Ok(res) => {
+                let status = res.status();
+                let retryable =
+                    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if !retryable {
+                    let body = res.text().await.unwrap_or_default();
+                    if let Ok(logger) = ctx.debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "error",
+                            &serde_json::json!({ "status": status.as_u16(), "body": body }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    return Err(CodexErr::UnexpectedStatus(UnexpectedResponseError {
+                        status,
+                        body,
+                        request_id: None,
+                    }));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(RetryLimitReachedError {
+                        status,
+                        request_id: None,
+                        retryable,
+                    }));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+                let delay = retry_after_secs
+                    .map(|s| Duration::from_millis(s * 1_000))
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    if let Ok(logger) = ctx.debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "network_error",
+                            &serde_json::json!({ "error": e.to_string() }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    if e.is_connect() || e.is_timeout() || e.is_request() {
+                        let req_id = (!request_id.is_empty()).then(|| request_id.clone());
+                        return Err(CodexErr::Stream(
+                            format!("[transport] network unavailable: {e}"),
+                            None,
+                            req_id,
+                        ));
+                    }
+                    return Err(e.into());
+                }
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Next SSE event, mapping transport failures and idle timeouts onto
+/// retryable stream errors. `Ok(None)` means the server closed the stream.
+pub(crate) async fn next_sse_event(
+    stream: &mut EventStream<ByteStream>,
+    idle_timeout: Duration,
+    otel_event_manager: Option<&OtelEventManager>,
+    request_id: &str,
+) -> Result<Option<Event>> {
+    let next = match otel_event_manager {
+        Some(manager) => {
+            manager
+                .log_sse_event(|| timeout(idle_timeout, stream.next()))
+                .await
+        }
+        None => timeout(idle_timeout, stream.next()).await,
+    };
+    match next {
+        Ok(Some(Ok(event))) => Ok(Some(event)),
+        Ok(Some(Err(e))) => Err(CodexErr::Stream(
+            format!("[transport] {e}"),
+            None,
+            Some(request_id.to_string()),
+        )),
+        Ok(None) => Ok(None),
+        Err(_) => Err(CodexErr::Stream(
+            "[idle] timeout waiting for SSE".into(),
+            None,
+            Some(request_id.to_string()),
+        )),
+    }
+}
+
+/// Parse one SSE payload, logging it to the debug log. Unparseable payloads
+/// are logged and skipped.
+pub(crate) fn parse_sse_data(
+    event: &Event,
+    debug_logger: &Arc<Mutex<DebugLogger>>,
+    request_id: &str,
+) -> Option<Value> {
+    let data = event.data.trim();
+    if data.is_empty() {
+        return None;
+    }
+    match serde_json::from_str::<Value>(data) {
+        Ok(value) => {
+            if let Ok(logger) = debug_logger.lock() {
+                let _ = logger.append_response_event(request_id, "sse_event", &value);
+            }
+            Some(value)
+        }
+        Err(err) => {
+            let excerpt: String = data.chars().take(600).collect();
+            debug!("SSE parse error: {err} | data: {excerpt}");
+            if let Ok(logger) = debug_logger.lock() {
+                let _ = logger.append_response_event(
+                    request_id,
+                    "sse_parse_error",
+                    &serde_json::json!({ "error": err.to_string(), "data_excerpt": excerpt }),
+                );
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn data_urls_split_into_media_type_and_payload() {
+        assert_eq!(
+            parse_data_url("data:image/png;base64,iVBORw0KGgo="),
+            Some(("image/png", "iVBORw0KGgo="))
+        );
+        assert_eq!(parse_data_url("https://example.com/cat.png"), None);
+        assert_eq!(parse_data_url("data:text/plain,hello"), None);
+    }
+
+    #[test]
+    fn malformed_arguments_become_empty_object() {
+        assert_eq!(arguments_to_object(r#"{"path":"a"}"#), serde_json::json!({"path": "a"}));
+        assert_eq!(arguments_to_object("not json"), serde_json::json!({}));
+        assert_eq!(arguments_to_object("[1]"), serde_json::json!({}));
+    }
+}
@@ -1,8 +1,10 @@
-use crate::config_types::{validation_tool_category, GithubConfig, ValidationCategory, ValidationConfig};
+use crate::config_types::{validation_tool_category, CustomValidationTool, GithubConfig, ValidationCategory, ValidationConfig, ValidationOutputFormat};
+use crate::validation_output::{parse_output, ParsedProblem};
 use crate::workflow_validation::maybe_run_actionlint;
 use code_apply_patch::{ApplyPatchAction, ApplyPatchFileChange};
 use serde_json as json;
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -671,6 +673,32 @@ pub fn run_patch_harness(
         }
     }
 
+    // 4) Project-specific tools from `[[validation.custom]]`.
+    for tool in &cfg.custom {
+        if !category_enabled(tool.category) || !is_allowed(&tool.name) {
+            continue;
+        }
+        let files = custom_tool_files(tool, &changed_paths);
+        if files.is_empty() {
+            continue;
+        }
+        let Some(exe) = tool.command.first().and_then(|program| resolve_program(cwd, program)) else { continue };
+        record_ran(&tool.name);
+        let tool_timeout = tool.timeout_seconds.unwrap_or(timeout);
+        if tool.workspace {
+            match WorkspaceOverlay::apply(action) {
+                Ok(_overlay) => findings.extend(run_custom_tool(tool, &exe, cwd, cwd, &files, tool_timeout)),
+                Err(err) => findings.push(HarnessFinding {
+                    tool: tool.name.clone(),
+                    file: None,
+                    message: format!("failed to stage workspace for {}: {err}", tool.name),
+                }),
+            }
+        } else {
+            findings.extend(run_custom_tool(tool, &exe, staged_root, cwd, &files, tool_timeout));
+        }
+    }
+
     if findings.is_empty() && ran.is_empty() {
         None
     } else {
@@ -678,6 +706,169 @@ pub fn run_patch_harness(
     }
 }
 
+fn custom_tool_files(tool: &CustomValidationTool, changed_paths: &[PathBuf]) -> Vec<PathBuf> {
+    let patterns: Vec<wildmatch::WildMatch> = tool.globs.iter().map(|glob| wildmatch::WildMatch::new(glob)).collect();
+    changed_paths
+        .iter()
+        .filter(|path| {
+            let normalized = path.to_string_lossy().replace('\\', "/");
+            patterns.is_empty() || patterns.iter().any(|pattern| pattern.matches(&normalized))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Commands such as `scripts/lint.sh` resolve against the workspace; bare
+/// names are looked up on `PATH`.
+fn resolve_program(cwd: &Path, program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.is_relative() && path.components().count() > 1 {
+        let candidate = cwd.join(path);
+        return candidate.is_file().then_some(candidate);
+    }
+    which(path)
+}
+
+/// Run a `[[validation.custom]]` tool in `root` and turn its output into
+/// findings. Paths reported by the tool are mapped back onto `cwd`.
+fn run_custom_tool(
+    tool: &CustomValidationTool,
+    exe: &Path,
+    root: &Path,
+    cwd: &Path,
+    files: &[PathBuf],
+    timeout: u64,
+) -> Vec<HarnessFinding> {
+    let per_file = tool.command.iter().any(|arg| arg.contains("{file}"));
+    let batches: Vec<&[PathBuf]> = if per_file { files.chunks(1).collect() } else { vec![files] };
+    let uses_report = tool.command.iter().any(|arg| arg.contains("{report}"));
+    let failure = |message: String| HarnessFinding { tool: tool.name.clone(), file: None, message };
+
+    let mut findings: Vec<HarnessFinding> = Vec::new();
+    for batch in batches {
+        let report = if uses_report {
+            match tempfile::NamedTempFile::new() {
+                Ok(report) => Some(report),
+                Err(err) => {
+                    findings.push(failure(format!("failed to create report file: {err}")));
+                    break;
+                }
+            }
+        } else {
+            None
+        };
+        let report_path = report.as_ref().map(|report| report.path());
+
+        let mut cmd = std::process::Command::new(exe);
+        cmd.current_dir(root);
+        cmd.args(expand_custom_args(&tool.command[1..], batch, root, report_path));
+        let Some(output) = run_with_timeout(cmd, timeout) else {
+            findings.push(failure(format!("{} timed out after {timeout} second(s)", tool.name)));
+            continue;
+        };
+        let failed = output.status.map_or(true, |status| !status.success());
+
+        let report_text = report_path
+            .and_then(|path| fs::read_to_string(path).ok())
+            .filter(|text| !text.trim().is_empty());
+        let text = match (report_text, tool.format) {
+            (Some(text), _) => text,
+            // Structured formats are written to stdout; stderr only carries noise.
+            (None, ValidationOutputFormat::Sarif | ValidationOutputFormat::Checkstyle) => {
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+            (None, _) => collect_output_lines(&output.stdout, &output.stderr).join("\n"),
+        };
+
+        let parsed = parse_output(tool.format, tool.pattern.as_deref(), &text);
+        let lines = collect_output_lines(&output.stdout, &output.stderr);
+        findings.extend(custom_tool_findings(tool, parsed, failed, lines, root, cwd, batch));
+        if findings.len() >= 24 {
+            break;
+        }
+    }
+    findings.truncate(24);
+    findings
+}
+
+/// Findings for one run of a custom tool. Parsed problems win over the exit
+/// status, and a parse error only counts when the tool reported success.
+/// SARIF and checkstyle reports often cover the whole project, so their
+/// problems are limited to `files`.
+fn custom_tool_findings(
+    tool: &CustomValidationTool,
+    parsed: Result<Option<Vec<ParsedProblem>>, String>,
+    failed: bool,
+    output_lines: Vec<String>,
+    root: &Path,
+    cwd: &Path,
+    files: &[PathBuf],
+) -> Vec<HarnessFinding> {
+    let failure = |message: String| HarnessFinding { tool: tool.name.clone(), file: None, message };
+    match parsed {
+        Ok(Some(problems)) if !problems.is_empty() => {
+            let structured = matches!(tool.format, ValidationOutputFormat::Sarif | ValidationOutputFormat::Checkstyle);
+            let selected: HashSet<PathBuf> = files.iter().map(|file| cwd.join(file)).collect();
+            problems
+                .into_iter()
+                .filter_map(|problem| {
+                    let file = problem.file.as_deref().map(|file| map_reported_path(file, root, cwd));
+                    if structured && file.as_ref().is_some_and(|file| !selected.contains(file)) {
+                        return None;
+                    }
+                    Some(HarnessFinding { tool: tool.name.clone(), file, message: problem.summary() })
+                })
+                .collect()
+        }
+        Err(err) if !failed => vec![failure(format!("could not parse {} output: {err}", tool.name))],
+        _ if failed => {
+            let mut lines = output_lines;
+            if lines.is_empty() {
+                lines.push(format!("{} failed (no output)", tool.name));
+            }
+            lines.into_iter().map(failure).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn expand_custom_args(args: &[String], files: &[PathBuf], root: &Path, report: Option<&Path>) -> Vec<OsString> {
+    let joined_files = files.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(" ");
+    let first_file = files.first().map(|path| path.display().to_string()).unwrap_or_default();
+    let root_str = root.display().to_string();
+    let report_str = report.map(|path| path.display().to_string()).unwrap_or_default();
+
+    let mut expanded: Vec<OsString> = Vec::new();
+    for arg in args {
+        if arg == "{files}" {
+            expanded.extend(files.iter().map(|path| path.as_os_str().to_os_string()));
+            continue;
+        }
+        let value = arg
+            .replace("{files}", &joined_files)
+            .replace("{file}", &first_file)
+            .replace("{root}", &root_str)
+            .replace("{report}", &report_str);
+        expanded.push(OsString::from(value));
+    }
+    expanded
+}
+
+/// Tools report paths relative to where they ran, or absolute inside the
+/// scratch copy; either way the finding should point into the workspace.
+fn map_reported_path(file: &str, root: &Path, cwd: &Path) -> PathBuf {
+    let path = Path::new(file);
+    let relative = if path.is_absolute() {
+        match path.strip_prefix(root) {
+            Ok(relative) => relative,
+            Err(_) => return path.to_path_buf(),
+        }
+    } else {
+        path
+    };
+    cwd.join(relative.strip_prefix(".").unwrap_or(relative))
+}
+
 fn is_shell_script(staged_root: &Path, relative: &Path) -> bool {
     match relative.extension().and_then(|ext| ext.to_str()) {
         Some("sh") => true,
@@ -1099,3 +1290,232 @@ struct CommandCapture {
     stdout: Vec<u8>,
     stderr: Vec<u8>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn custom_tool(format: ValidationOutputFormat, globs: &[&str]) -> CustomValidationTool {
+        CustomValidationTool {
+            name: "lint".to_string(),
+            command: vec!["lint".to_string()],
+            globs: globs.iter().map(|glob| glob.to_string()).collect(),
+            category: ValidationCategory::Functional,
+            timeout_seconds: None,
+            workspace: false,
+            format,
+            pattern: None,
+        }
+    }
+
+    fn problem(file: Option<&str>, message: &str) -> ParsedProblem {
+        ParsedProblem {
+            file: file.map(str::to_string),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn messages(findings: &[HarnessFinding]) -> Vec<&str> {
+        findings
+            .iter()
+            .map(|finding| finding.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn expands_file_report_and_root_placeholders() {
+        let args: Vec<String> = [
+            "--check",
+            "{files}",
+            "--first={file}",
+            "--all={files}",
+            "--out={report}",
+            "{root}/lint.toml",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let files = vec![PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")];
+        let expanded = expand_custom_args(
+            &args,
+            &files,
+            Path::new("/scratch"),
+            Some(Path::new("/tmp/report.sarif")),
+        );
+        assert_eq!(
+            expanded,
+            vec![
+                OsString::from("--check"),
+                OsString::from("src/a.rs"),
+                OsString::from("src/b.rs"),
+                OsString::from("--first=src/a.rs"),
+                OsString::from("--all=src/a.rs src/b.rs"),
+                OsString::from("--out=/tmp/report.sarif"),
+                OsString::from("/scratch/lint.toml"),
+            ]
+        );
+
+        let without_report = expand_custom_args(
+            &["--out={report}".to_string()],
+            &files,
+            Path::new("/scratch"),
+            None,
+        );
+        assert_eq!(without_report, vec![OsString::from("--out=")]);
+    }
+
+    #[test]
+    fn selects_changed_files_by_glob() {
+        let changed = vec![
+            PathBuf::from("README.md"),
+            PathBuf::from("build.rs"),
+            PathBuf::from("src/net/mod.rs"),
+        ];
+        let rust = custom_tool(ValidationOutputFormat::Lines, &["*.rs"]);
+        assert_eq!(
+            custom_tool_files(&rust, &changed),
+            vec![PathBuf::from("build.rs"), PathBuf::from("src/net/mod.rs")]
+        );
+
+        let nested = custom_tool(ValidationOutputFormat::Lines, &["src/*", "docs/*.md"]);
+        assert_eq!(
+            custom_tool_files(&nested, &changed),
+            vec![PathBuf::from("src/net/mod.rs")]
+        );
+
+        let everything = custom_tool(ValidationOutputFormat::Lines, &[]);
+        assert_eq!(custom_tool_files(&everything, &changed), changed);
+    }
+
+    #[test]
+    fn maps_reported_paths_onto_the_workspace() {
+        let root = Path::new("/tmp/scratch");
+        let cwd = Path::new("/work/repo");
+        assert_eq!(
+            map_reported_path("/tmp/scratch/src/a.rs", root, cwd),
+            PathBuf::from("/work/repo/src/a.rs")
+        );
+        assert_eq!(
+            map_reported_path("./src/a.rs", root, cwd),
+            PathBuf::from("/work/repo/src/a.rs")
+        );
+        assert_eq!(
+            map_reported_path("src/a.rs", root, cwd),
+            PathBuf::from("/work/repo/src/a.rs")
+        );
+        assert_eq!(
+            map_reported_path("/usr/include/x.h", root, cwd),
+            PathBuf::from("/usr/include/x.h")
+        );
+    }
+
+    #[test]
+    fn parsed_problems_take_precedence_over_exit_status() {
+        let tool = custom_tool(ValidationOutputFormat::Regex, &[]);
+        let root = Path::new("/tmp/scratch");
+        let cwd = Path::new("/work/repo");
+        let files = [PathBuf::from("src/a.rs")];
+        let lines = || vec!["raw output".to_string()];
+
+        let parsed = Ok(Some(vec![problem(Some("src/a.rs"), "bad")]));
+        let findings = custom_tool_findings(&tool, parsed, true, lines(), root, cwd, &files);
+        assert_eq!(messages(&findings), vec!["bad"]);
+        assert_eq!(findings[0].file, Some(PathBuf::from("/work/repo/src/a.rs")));
+
+        // A parse error is only reported when the exit status says success.
+        let findings = custom_tool_findings(
+            &tool,
+            Err("no match".to_string()),
+            false,
+            lines(),
+            root,
+            cwd,
+            &files,
+        );
+        assert_eq!(
+            messages(&findings),
+            vec!["could not parse lint output: no match"]
+        );
+        let findings = custom_tool_findings(
+            &tool,
+            Err("no match".to_string()),
+            true,
+            lines(),
+            root,
+            cwd,
+            &files,
+        );
+        assert_eq!(messages(&findings), vec!["raw output"]);
+
+        // Nothing parsed: the exit status decides.
+        let findings = custom_tool_findings(
+            &tool,
+            Ok(Some(Vec::new())),
+            true,
+            Vec::new(),
+            root,
+            cwd,
+            &files,
+        );
+        assert_eq!(messages(&findings), vec!["lint failed (no output)"]);
+        assert!(
+            custom_tool_findings(
+                &tool,
+                Ok(Some(Vec::new())),
+                false,
+                lines(),
+                root,
+                cwd,
+                &files
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn structured_reports_are_limited_to_selected_files() {
+        let root = Path::new("/tmp/scratch");
+        let cwd = Path::new("/work/repo");
+        let files = [PathBuf::from("src/a.rs")];
+        let problems = || {
+            Ok(Some(vec![
+                problem(Some("/tmp/scratch/src/a.rs"), "in a"),
+                problem(Some("src/other.rs"), "in other"),
+                problem(None, "tool note"),
+            ]))
+        };
+
+        for format in [
+            ValidationOutputFormat::Sarif,
+            ValidationOutputFormat::Checkstyle,
+        ] {
+            let tool = custom_tool(format, &[]);
+            let findings =
+                custom_tool_findings(&tool, problems(), true, Vec::new(), root, cwd, &files);
+            assert_eq!(messages(&findings), vec!["in a", "tool note"]);
+        }
+
+        // Problems only in files outside the selection are not a failure.
+        let tool = custom_tool(ValidationOutputFormat::Sarif, &[]);
+        let elsewhere = Ok(Some(vec![problem(Some("src/other.rs"), "in other")]));
+        assert!(
+            custom_tool_findings(
+                &tool,
+                elsewhere,
+                true,
+                vec!["raw".to_string()],
+                root,
+                cwd,
+                &files
+            )
+            .is_empty()
+        );
+
+        let regex = custom_tool(ValidationOutputFormat::Regex, &[]);
+        let findings =
+            custom_tool_findings(&regex, problems(), true, Vec::new(), root, cwd, &files);
+        assert_eq!(findings.len(), 3);
+    }
+}
@@ -0,0 +1,464 @@
+//! Hunk-level review of `apply_patch` changes.
+//!
+//! The TUI splits each proposed file change into hunks so the user can accept,
+//! reject or edit them individually. Core recomputes the same hunks from the
+//! same inputs when the decision comes back, so both sides agree on hunk
+//! indices without shipping the hunk bodies over the wire.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use code_apply_patch::ApplyPatchFileChange;
+use similar::ChangeTag;
+use similar::DiffTag;
+use similar::TextDiff;
+
+use crate::protocol::FileChange;
+use crate::protocol::HunkDecision;
+use crate::protocol::PatchHunkReview;
+
+/// Number of unchanged lines kept around each change when grouping hunks.
+/// Matches the context used by unified diffs elsewhere in the UI.
+const HUNK_CONTEXT_LINES: usize = 3;
+
+/// One reviewable hunk of a file change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// 0-based line index in the original file where the hunk starts.
+    pub old_start: usize,
+    /// Original lines covered by the hunk (line terminators preserved).
+    pub old_lines: Vec<String>,
+    /// 0-based line index in the proposed file where the hunk starts.
+    pub new_start: usize,
+    /// Proposed lines covered by the hunk (line terminators preserved).
+    pub new_lines: Vec<String>,
+}
+
+impl PatchHunk {
+    /// Unified-diff style header, e.g. `@@ -10,3 +10,4 @@`.
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start + 1,
+            self.old_lines.len(),
+            self.new_start + 1,
+            self.new_lines.len()
+        )
+    }
+
+    /// Proposed text of the hunk; used as the starting point for edits.
+    pub fn new_text(&self) -> String {
+        self.new_lines.concat()
+    }
+
+    /// Lines of the hunk in diff order, without line terminators.
+    pub fn lines(&self) -> Vec<(HunkLineKind, String)> {
+        let old = self.old_lines.concat();
+        let new = self.new_lines.concat();
+        let diff = TextDiff::from_lines(old.as_str(), new.as_str());
+        diff.iter_all_changes()
+            .map(|change| {
+                let kind = match change.tag() {
+                    ChangeTag::Delete => HunkLineKind::Delete,
+                    ChangeTag::Insert => HunkLineKind::Insert,
+                    ChangeTag::Equal => HunkLineKind::Context,
+                };
+                let text = change.value().trim_end_matches(['\n', '\r']).to_string();
+                (kind, text)
+            })
+            .collect()
+    }
+}
+
+/// Kind of a line inside a [`PatchHunk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkLineKind {
+    Context,
+    Delete,
+    Insert,
+}
+
+/// Split the transition `original -> proposed` into reviewable hunks.
+pub fn compute_hunks(original: &str, proposed: &str) -> Vec<PatchHunk> {
+    let diff = TextDiff::from_lines(original, proposed);
+    let old_lines: Vec<&str> = diff.old_slices().to_vec();
+    let new_lines: Vec<&str> = diff.new_slices().to_vec();
+
+    diff.grouped_ops(HUNK_CONTEXT_LINES)
+        .into_iter()
+        .filter(|group| group.iter().any(|op| op.tag() != DiffTag::Equal))
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            Some(PatchHunk {
+                old_start: old_range.start,
+                old_lines: old_lines[old_range].iter().map(|s| (*s).to_string()).collect(),
+                new_start: new_range.start,
+                new_lines: new_lines[new_range].iter().map(|s| (*s).to_string()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Hunks for a protocol-level `FileChange` as shown in the approval modal.
+///
+/// Deletions have no hunks; reviewers accept or reject them as a whole by
+/// reporting a decision for hunk index 0.
+pub fn hunks_for_file_change(change: &FileChange) -> Vec<PatchHunk> {
+    match change {
+        FileChange::Add { content } => compute_hunks("", content),
+        FileChange::Delete => Vec::new(),
+        FileChange::Update {
+            original_content,
+            new_content,
+            ..
+        } => compute_hunks(original_content, new_content),
+    }
+}
+
+/// Rebuild file content from `original` keeping only the hunks the user
+/// accepted (or edited). Rejected hunks keep the original lines.
+pub fn compose_reviewed_content(
+    original: &str,
+    hunks: &[PatchHunk],
+    decisions: &[HunkDecision],
+) -> String {
+    let original_lines: Vec<&str> = original.split_inclusive('\n').collect();
+
+    let mut out = String::with_capacity(original.len());
+    let mut cursor = 0usize;
+    for (idx, hunk) in hunks.iter().enumerate() {
+        let start = hunk.old_start.min(original_lines.len());
+        for line in &original_lines[cursor.min(start)..start] {
+            out.push_str(line);
+        }
+        match decisions.get(idx).unwrap_or(&HunkDecision::Reject) {
+            HunkDecision::Accept => {
+                for line in &hunk.new_lines {
+                    out.push_str(line);
+                }
+            }
+            HunkDecision::Reject => {
+                for line in &hunk.old_lines {
+                    out.push_str(line);
+                }
+            }
+            HunkDecision::Edit { replacement } => {
+                out.push_str(replacement);
+                let proposed_had_newline = hunk.new_lines.last().is_some_and(|l| l.ends_with('\n'));
+                if proposed_had_newline && !replacement.is_empty() && !replacement.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+        }
+        cursor = start + hunk.old_lines.len();
+    }
+    for line in original_lines.iter().skip(cursor) {
+        out.push_str(line);
+    }
+    out
+}
+
+/// Outcome of applying a hunk-level review to an `apply_patch` action.
+pub(crate) struct ReviewedPatch {
+    /// Changes to actually apply (files with every hunk rejected are dropped).
+    pub changes: HashMap<PathBuf, ApplyPatchFileChange>,
+    /// Human/model readable summary of what was rejected or edited.
+    pub note: Option<String>,
+}
+
+/// Apply per-hunk decisions to the changes of an `apply_patch` action.
+///
+/// `reviewed` holds the changes exactly as they were shown to the user; hunks
+/// are rebuilt from the original content recorded there. Fails when a file
+/// no longer matches that original or cannot be read, so accepted hunks are
+/// never applied to text the user did not review.
+///
+/// Hunks without an explicit decision are treated as rejected so a client
+/// that only reports the hunks it looked at can never apply more than the
+/// user saw.
+pub(crate) fn apply_hunk_review(
+    changes: &HashMap<PathBuf, ApplyPatchFileChange>,
+    reviewed: &HashMap<PathBuf, FileChange>,
+    reviews: &[PatchHunkReview],
+) -> Result<ReviewedPatch, String> {
+    let mut by_path: HashMap<&Path, Vec<&PatchHunkReview>> = HashMap::new();
+    for review in reviews {
+        by_path.entry(review.path.as_path()).or_default().push(review);
+    }
+
+    let mut paths: Vec<&PathBuf> = changes.keys().collect();
+    paths.sort();
+
+    let mut kept: HashMap<PathBuf, ApplyPatchFileChange> = HashMap::new();
+    let mut notes: Vec<String> = Vec::new();
+
+    for path in paths {
+        let change = &changes[path];
+        let file_reviews = by_path.get(path.as_path()).cloned().unwrap_or_default();
+        let display = path.display();
+
+        if let ApplyPatchFileChange::Delete { .. } = change {
+            let accepted = file_reviews
+                .iter()
+                .any(|r| matches!(r.decision, HunkDecision::Accept));
+            if accepted {
+                kept.insert(path.clone(), change.clone());
+            } else {
+                notes.push(format!("- {display}: deletion rejected"));
+            }
+            continue;
+        }
+
+        let (original, proposed) = match change {
+            ApplyPatchFileChange::Add { content } => (String::new(), content.clone()),
+            ApplyPatchFileChange::Update { new_content, .. } => {
+                let Some(FileChange::Update {
+                    original_content, ..
+                }) = reviewed.get(path)
+                else {
+                    return Err(format!("{display} was not part of the reviewed patch"));
+                };
+                let current = std::fs::read_to_string(path)
+                    .map_err(|e| format!("failed to re-read {display}: {e}"))?;
+                if current != *original_content {
+                    return Err(format!("{display} changed on disk after it was reviewed"));
+                }
+                (current, new_content.clone())
+            }
+            ApplyPatchFileChange::Delete { .. } => unreachable!("deletes handled above"),
+        };
+        let hunks = compute_hunks(&original, &proposed);
+        let mut decisions = vec![HunkDecision::Reject; hunks.len()];
+        for review in &file_reviews {
+            if let Some(slot) = decisions.get_mut(review.hunk_index) {
+                *slot = review.decision.clone();
+            }
+        }
+
+        let mut rejected: Vec<String> = Vec::new();
+        let mut edited: Vec<String> = Vec::new();
+        for (idx, (hunk, decision)) in hunks.iter().zip(decisions.iter()).enumerate() {
+            match decision {
+                HunkDecision::Accept => {}
+                HunkDecision::Reject => rejected.push(format!("#{} {}", idx + 1, hunk.header())),
+                HunkDecision::Edit { .. } => edited.push(format!("#{} {}", idx + 1, hunk.header())),
+            }
+        }
+
+        let all_rejected = !hunks.is_empty() && rejected.len() == hunks.len();
+        if all_rejected {
+            // The rename is not a hunk, so rejecting every hunk keeps it.
+            if let ApplyPatchFileChange::Update {
+                move_path: Some(dest),
+                ..
+            } = change
+            {
+                notes.push(format!(
+                    "- {display}: all hunks rejected; moved to {} without content changes",
+                    dest.display()
+                ));
+                kept.insert(
+                    path.clone(),
+                    ApplyPatchFileChange::Update {
+                        unified_diff: String::new(),
+                        move_path: Some(dest.clone()),
+                        new_content: original,
+                    },
+                );
+            } else {
+                notes.push(format!(
+                    "- {display}: all hunks rejected; file left unchanged"
+                ));
+            }
+            continue;
+        }
+        if !rejected.is_empty() {
+            notes.push(format!("- {display}: rejected hunks {}", rejected.join(", ")));
+        }
+        if !edited.is_empty() {
+            notes.push(format!("- {display}: user edited hunks {}", edited.join(", ")));
+        }
+
+        if rejected.is_empty() && edited.is_empty() {
+            kept.insert(path.clone(), change.clone());
+            continue;
+        }
+
+        let content = compose_reviewed_content(&original, &hunks, &decisions);
+        let reviewed = match change {
+            ApplyPatchFileChange::Add { .. } => ApplyPatchFileChange::Add { content },
+            ApplyPatchFileChange::Update { move_path, .. } => ApplyPatchFileChange::Update {
+                unified_diff: similar::TextDiff::from_lines(original.as_str(), content.as_str())
+                    .unified_diff()
+                    .context_radius(HUNK_CONTEXT_LINES)
+                    .to_string(),
+                move_path: move_path.clone(),
+                new_content: content,
+            },
+            ApplyPatchFileChange::Delete { .. } => unreachable!("deletes handled above"),
+        };
+        kept.insert(path.clone(), reviewed);
+    }
+
+    let note = if notes.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "The user reviewed this patch hunk by hunk; only the approved subset was applied.\n{}",
+            notes.join("\n")
+        ))
+    };
+
+    Ok(ReviewedPatch {
+        changes: kept,
+        note,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const ORIGINAL: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+    const PROPOSED: &str = "A\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nM\n";
+
+    #[test]
+    fn splits_distant_changes_into_separate_hunks() {
+        let hunks = compute_hunks(ORIGINAL, PROPOSED);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header(), "@@ -1,4 +1,4 @@");
+        assert_eq!(hunks[1].header(), "@@ -10,4 +10,4 @@");
+    }
+
+    #[test]
+    fn compose_keeps_only_accepted_hunks() {
+        let hunks = compute_hunks(ORIGINAL, PROPOSED);
+        let content = compose_reviewed_content(
+            ORIGINAL,
+            &hunks,
+            &[HunkDecision::Reject, HunkDecision::Accept],
+        );
+        assert_eq!(content, "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nM\n");
+    }
+
+    #[test]
+    fn compose_uses_edited_replacement() {
+        let hunks = compute_hunks(ORIGINAL, PROPOSED);
+        let content = compose_reviewed_content(
+            ORIGINAL,
+            &hunks,
+            &[
+                HunkDecision::Edit {
+                    replacement: "Z\nb\nc\nd".to_string(),
+                },
+                HunkDecision::Reject,
+            ],
+        );
+        assert_eq!(content, "Z\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n");
+    }
+
+    #[test]
+    fn review_of_added_file_reports_rejections() {
+        let path = PathBuf::from("/tmp/code-patch-review-new.txt");
+        let mut changes = HashMap::new();
+        changes.insert(
+            path.clone(),
+            ApplyPatchFileChange::Add {
+                content: "hello\n".to_string(),
+            },
+        );
+        let reviewed = apply_hunk_review(&changes, &HashMap::new(), &[]).unwrap();
+        assert!(reviewed.changes.is_empty());
+        let note = reviewed.note.expect("note");
+        assert!(note.contains("all hunks rejected"), "{note}");
+    }
+
+    fn update_review(
+        path: &Path,
+        original: &str,
+        move_path: Option<PathBuf>,
+    ) -> (
+        HashMap<PathBuf, ApplyPatchFileChange>,
+        HashMap<PathBuf, FileChange>,
+    ) {
+        let mut changes = HashMap::new();
+        changes.insert(
+            path.to_path_buf(),
+            ApplyPatchFileChange::Update {
+                unified_diff: String::new(),
+                move_path: move_path.clone(),
+                new_content: PROPOSED.to_string(),
+            },
+        );
+        let mut reviewed = HashMap::new();
+        reviewed.insert(
+            path.to_path_buf(),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path,
+                original_content: original.to_string(),
+                new_content: PROPOSED.to_string(),
+            },
+        );
+        (changes, reviewed)
+    }
+
+    fn accept(path: &Path, hunk_index: usize) -> PatchHunkReview {
+        PatchHunkReview {
+            path: path.to_path_buf(),
+            hunk_index,
+            decision: HunkDecision::Accept,
+        }
+    }
+
+    #[test]
+    fn review_fails_closed_when_the_file_changed_or_cannot_be_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, ORIGINAL).unwrap();
+        let (changes, reviewed) = update_review(&path, ORIGINAL, None);
+        let ok = apply_hunk_review(&changes, &reviewed, &[accept(&path, 1)]).unwrap();
+        let Some(ApplyPatchFileChange::Update { new_content, .. }) = ok.changes.get(&path) else {
+            panic!("expected update");
+        };
+        assert_eq!(new_content, "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nM\n");
+
+        std::fs::write(&path, "rewritten elsewhere\n").unwrap();
+        let err = apply_hunk_review(&changes, &reviewed, &[accept(&path, 1)])
+            .err()
+            .expect("changed file must be rejected");
+        assert!(err.contains("changed on disk"), "{err}");
+
+        std::fs::remove_file(&path).unwrap();
+        let err = apply_hunk_review(&changes, &reviewed, &[accept(&path, 1)])
+            .err()
+            .expect("unreadable file must be rejected");
+        assert!(err.contains("failed to re-read"), "{err}");
+    }
+
+    #[test]
+    fn rejecting_every_hunk_keeps_the_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.txt");
+        let dest = dir.path().join("new.txt");
+        std::fs::write(&path, ORIGINAL).unwrap();
+        let (changes, reviewed) = update_review(&path, ORIGINAL, Some(dest.clone()));
+
+        let result = apply_hunk_review(&changes, &reviewed, &[]).unwrap();
+        assert_eq!(
+            result.changes.get(&path),
+            Some(&ApplyPatchFileChange::Update {
+                unified_diff: String::new(),
+                move_path: Some(dest),
+                new_content: ORIGINAL.to_string(),
+            })
+        );
+        let note = result.note.expect("note");
+        assert!(note.contains("without content changes"), "{note}");
+    }
+}
@@ -12,14 +12,24 @@
 //!     current working directory (inclusive) and concatenate their contents in
 //!     that order.
 //! 3.  We do **not** walk past the Git root.
+//!
+//! `AGENTS.md` files in other directories of the repository are picked up
+//! later by [`ScopedDocTracker`], the first time a turn reads, edits or runs
+//! commands under them. Any doc may pull in another file with a line of the
+//! form `@path/to/file.md`, resolved relative to the doc that contains it.
 
 use crate::config::Config;
 use crate::skills::SkillMetadata;
 use crate::skills::render_skills_section;
+use crate::user_instructions::ScopedInstructions;
+use code_protocol::models::LocalShellAction;
+use code_protocol::models::ResponseItem;
+use code_protocol::models::ShellToolCallParams;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
-use tokio::io::AsyncReadExt;
 use tracing::error;
+use tracing::warn;
 
 /// Filenames recognised for standard agent instructions.
 const AGENT_FILENAMES: &[&str] = &["AGENTS.md"];
@@ -27,6 +37,12 @@ const AGENT_FILENAMES: &[&str] = &["AGENTS.md"];
 /// Filenames recognised for Auto Drive instructions.
 const AUTO_AGENT_FILENAMES: &[&str] = &["AUTO_AGENTS.md"];
 
+/// How deeply `@file` includes may nest.
+const MAX_INCLUDE_DEPTH: usize = 4;
+
+/// Upper bound on shell words inspected when looking for touched paths.
+const MAX_COMMAND_WORDS: usize = 64;
+
 /// When both `Config::instructions` and the project doc are present, they will
 /// be concatenated with the following separator.
 const PROJECT_DOC_SEPARATOR: &str = "\n\n--- project-doc ---\n\n";
@@ -114,29 +130,17 @@ async fn read_project_doc_parts_with_candidates(
             break;
         }
 
-        let file = match tokio::fs::File::open(&p).await {
-            Ok(f) => f,
+        let data = match tokio::fs::read(&p).await {
+            Ok(data) => data,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
             Err(e) => return Err(e),
         };
 
-        let size = file.metadata().await?.len();
-        let mut reader = tokio::io::BufReader::new(file).take(remaining);
-        let mut data: Vec<u8> = Vec::new();
-        reader.read_to_end(&mut data).await?;
-
-        if size > remaining {
-            tracing::warn!(
-                "Project doc `{}` exceeds remaining budget ({} bytes) - truncating.",
-                p.display(),
-                remaining,
-            );
-        }
-
-        let text = String::from_utf8_lossy(&data).to_string();
+        let text = expand_includes(&p, &String::from_utf8_lossy(&data));
+        let text = truncate_to_budget(&p, text, remaining as usize);
         if !text.trim().is_empty() {
+            remaining = remaining.saturating_sub(text.len() as u64);
             parts.push(text);
-            remaining = remaining.saturating_sub(data.len() as u64);
         }
     }
 
@@ -181,16 +185,10 @@ pub async fn read_project_doc_parts(config: &Config) -> std::io::Result<Option<V
     }
 }
 
-/// Discover the list of AGENTS.md files using the same search rules as
-/// `read_project_docs`, but return the file paths instead of concatenated
-/// contents. The list is ordered from repository root to the current working
-/// directory (inclusive). Symlinks are allowed. When `project_doc_max_bytes`
-/// is zero, returns an empty list.
-fn discover_project_doc_paths_with_candidates(
-    config: &Config,
-    candidate_filenames: &[&str],
-) -> std::io::Result<Vec<PathBuf>> {
-    let mut dir = config.cwd.clone();
+/// Directories searched for project docs at session start, ordered from the
+/// Git root (or `cwd` when there is no repository) down to `cwd`.
+fn project_doc_search_dirs(cwd: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dir = cwd.to_path_buf();
     if let Ok(canon) = dir.canonicalize() {
         dir = canon;
     }
@@ -231,9 +229,23 @@ fn discover_project_doc_paths_with_candidates(
         }
         dirs
     } else {
-        vec![config.cwd.clone()]
+        vec![cwd.to_path_buf()]
     };
 
+    Ok(search_dirs)
+}
+
+/// Discover the list of AGENTS.md files using the same search rules as
+/// `read_project_docs`, but return the file paths instead of concatenated
+/// contents. The list is ordered from repository root to the current working
+/// directory (inclusive). Symlinks are allowed. When `project_doc_max_bytes`
+/// is zero, returns an empty list.
+fn discover_project_doc_paths_with_candidates(
+    config: &Config,
+    candidate_filenames: &[&str],
+) -> std::io::Result<Vec<PathBuf>> {
+    let search_dirs = project_doc_search_dirs(&config.cwd)?;
+
     let mut found: Vec<PathBuf> = Vec::new();
     for d in search_dirs {
         for name in candidate_filenames {
@@ -256,6 +268,345 @@ fn discover_project_doc_paths_with_candidates(
     Ok(found)
 }
 
+/// Replace lines of the form `@path/to/file.md` with the contents of that
+/// file, resolved relative to `doc_path`. Lines inside fenced code blocks are
+/// left alone, as are includes that are missing, cyclic or nested deeper than
+/// [`MAX_INCLUDE_DEPTH`].
+fn expand_includes(doc_path: &Path, text: &str) -> String {
+    let mut stack: Vec<PathBuf> = doc_path.canonicalize().into_iter().collect();
+    expand_includes_inner(doc_path, text, &mut stack)
+}
+
+fn expand_includes_inner(doc_path: &Path, text: &str, stack: &mut Vec<PathBuf>) -> String {
+    let base = doc_path.parent().unwrap_or_else(|| Path::new(""));
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let included = if in_fence {
+            None
+        } else {
+            include_target(line).and_then(|target| load_include(&base.join(target), stack))
+        };
+        match included {
+            Some(contents) => {
+                out.push_str(&contents);
+                if line.ends_with('\n') && !contents.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+fn include_target(line: &str) -> Option<&str> {
+    let target = line.trim().strip_prefix('@')?;
+    (!target.is_empty() && !target.contains(char::is_whitespace)).then_some(target)
+}
+
+fn load_include(path: &Path, stack: &mut Vec<PathBuf>) -> Option<String> {
+    let canon = path.canonicalize().ok()?;
+    if !canon.is_file() {
+        return None;
+    }
+    if stack.contains(&canon) {
+        warn!("Skipping recursive include of `{}`.", canon.display());
+        return None;
+    }
+    if stack.len() > MAX_INCLUDE_DEPTH {
+        warn!("Skipping include of `{}`: nested too deeply.", canon.display());
+        return None;
+    }
+    let data = match std::fs::read(&canon) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to read include `{}`: {e}", canon.display());
+            return None;
+        }
+    };
+    stack.push(canon);
+    let expanded = expand_includes_inner(path, &String::from_utf8_lossy(&data), stack);
+    stack.pop();
+    Some(expanded)
+}
+
+fn truncate_to_budget(path: &Path, mut text: String, budget: usize) -> String {
+    if text.len() <= budget {
+        return text;
+    }
+    warn!(
+        "Project doc `{}` exceeds remaining budget ({} bytes) - truncating.",
+        path.display(),
+        budget,
+    );
+    let mut end = budget;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text
+}
+
+/// An `AGENTS.md` loaded after session start for a directory a turn touched.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScopedDoc {
+    pub directory: PathBuf,
+    pub text: String,
+}
+
+impl From<ScopedDoc> for ScopedInstructions {
+    fn from(doc: ScopedDoc) -> Self {
+        ScopedInstructions {
+            directory: doc.directory.to_string_lossy().into_owned(),
+            text: doc.text,
+        }
+    }
+}
+
+/// Loads `AGENTS.md` files that were not part of the startup search path the
+/// first time a turn touches a path beneath them. Each directory is checked
+/// once per session; loaded docs are kept so they can be re-sent after the
+/// history is compacted. Startup and scoped docs share one
+/// `project_doc_max_bytes` budget for the whole session.
+#[derive(Debug, Default)]
+pub(crate) struct ScopedDocTracker {
+    /// Only directories under this root can contribute docs.
+    root: Option<PathBuf>,
+    /// Directories already covered by the session's initial instructions.
+    startup_dirs: HashSet<PathBuf>,
+    checked_dirs: HashSet<PathBuf>,
+    loaded: Vec<ScopedDoc>,
+    /// Bytes of `project_doc_max_bytes` not yet spent on startup or scoped docs.
+    remaining_bytes: usize,
+}
+
+impl ScopedDocTracker {
+    /// A `max_bytes` of zero disables scoped docs, matching
+    /// `project_doc_max_bytes`.
+    pub(crate) fn new(cwd: &Path, max_bytes: usize) -> Self {
+        if max_bytes == 0 {
+            return Self::default();
+        }
+        let dirs = match project_doc_search_dirs(cwd) {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                warn!("scoped AGENTS.md discovery disabled: {e:#}");
+                return Self::default();
+            }
+        };
+        let startup_dirs: HashSet<PathBuf> = dirs
+            .iter()
+            .map(|dir| dir.canonicalize().unwrap_or_else(|_| dir.clone()))
+            .collect();
+        let root = dirs
+            .first()
+            .map(|dir| dir.canonicalize().unwrap_or_else(|_| dir.clone()));
+        let mut remaining_bytes = max_bytes;
+        for dir in &dirs {
+            if let Some(text) = read_scoped_doc(dir, remaining_bytes) {
+                remaining_bytes -= text.len();
+            }
+        }
+        Self {
+            root,
+            startup_dirs,
+            remaining_bytes,
+            ..Default::default()
+        }
+    }
+
+    /// Docs loaded so far, in the order they were first injected.
+    pub(crate) fn loaded(&self) -> &[ScopedDoc] {
+        &self.loaded
+    }
+
+    /// Check every directory between the root and each of `paths`, returning
+    /// docs that have not been loaded before, outermost first.
+    pub(crate) fn docs_for_paths(&mut self, paths: &[PathBuf]) -> Vec<ScopedDoc> {
+        let Some(root) = self.root.clone() else {
+            return Vec::new();
+        };
+        let mut new_docs = Vec::new();
+        for path in paths {
+            let Some(dir) = nearest_existing_dir(path) else {
+                continue;
+            };
+            if !dir.starts_with(&root) {
+                continue;
+            }
+            let mut chain: Vec<&Path> = dir
+                .ancestors()
+                .take_while(|ancestor| ancestor.starts_with(&root))
+                .collect();
+            chain.reverse();
+            for dir in chain {
+                if self.remaining_bytes == 0 {
+                    return new_docs;
+                }
+                if self.startup_dirs.contains(dir) || !self.checked_dirs.insert(dir.to_path_buf())
+                {
+                    continue;
+                }
+                if let Some(text) = read_scoped_doc(dir, self.remaining_bytes) {
+                    self.remaining_bytes -= text.len();
+                    let doc = ScopedDoc {
+                        directory: dir.to_path_buf(),
+                        text,
+                    };
+                    self.loaded.push(doc.clone());
+                    new_docs.push(doc);
+                }
+            }
+        }
+        new_docs
+    }
+
+    /// Mark docs that already appear in a restored history as loaded so a
+    /// resumed session does not inject them twice.
+    pub(crate) fn restore_from_history(&mut self, history: &[ResponseItem]) {
+        for item in history {
+            let ResponseItem::Message { role, content, .. } = item else {
+                continue;
+            };
+            if role != "user" {
+                continue;
+            }
+            let Some(scoped) = ScopedInstructions::parse(content) else {
+                continue;
+            };
+            let directory = PathBuf::from(scoped.directory);
+            if self.checked_dirs.insert(directory.clone()) {
+                self.remaining_bytes = self.remaining_bytes.saturating_sub(scoped.text.len());
+                self.loaded.push(ScopedDoc {
+                    directory,
+                    text: scoped.text,
+                });
+            }
+        }
+    }
+}
+
+fn nearest_existing_dir(path: &Path) -> Option<PathBuf> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    let canon = existing.canonicalize().ok()?;
+    if canon.is_dir() {
+        Some(canon)
+    } else {
+        canon.parent().map(Path::to_path_buf)
+    }
+}
+
+fn read_scoped_doc(dir: &Path, max_bytes: usize) -> Option<String> {
+    for name in AGENT_FILENAMES {
+        let path = dir.join(name);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                warn!("Failed to read `{}`: {e}", path.display());
+                continue;
+            }
+        };
+        let text = expand_includes(&path, &String::from_utf8_lossy(&data));
+        let text = truncate_to_budget(&path, text, max_bytes);
+        if !text.trim().is_empty() {
+            return Some(text);
+        }
+    }
+    None
+}
+
+/// Paths a tool call reads, edits or runs in, resolved against `cwd`. Shell
+/// commands contribute their working directory plus any argument that names
+/// an existing file or directory; `apply_patch` bodies, whether run through
+/// the shell or called as a tool, contribute the files they touch.
+pub(crate) fn paths_touched_by(item: &ResponseItem, cwd: &Path) -> Vec<PathBuf> {
+    match item {
+        ResponseItem::CustomToolCall { name, input, .. } if name == "apply_patch" => {
+            patch_paths(input)
+                .into_iter()
+                .map(|path| cwd.join(path))
+                .collect()
+        }
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => match name.as_str() {
+            "apply_patch" => serde_json::from_str::<serde_json::Value>(arguments)
+                .ok()
+                .and_then(|value| value.get("input")?.as_str().map(patch_paths))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| cwd.join(path))
+                .collect(),
+            "shell" | "container.exec" => {
+                match serde_json::from_str::<ShellToolCallParams>(arguments) {
+                    Ok(params) => command_paths(&params.command, params.workdir.as_deref(), cwd),
+                    Err(_) => Vec::new(),
+                }
+            }
+            "image_view" => serde_json::from_str::<serde_json::Value>(arguments)
+                .ok()
+                .and_then(|value| value.get("path")?.as_str().map(|path| cwd.join(path)))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        },
+        ResponseItem::LocalShellCall {
+            action: LocalShellAction::Exec(exec),
+            ..
+        } => command_paths(&exec.command, exec.working_directory.as_deref(), cwd),
+        _ => Vec::new(),
+    }
+}
+
+fn command_paths(command: &[String], workdir: Option<&str>, cwd: &Path) -> Vec<PathBuf> {
+    let workdir = workdir.map_or_else(|| cwd.to_path_buf(), |dir| cwd.join(dir));
+    let mut paths = vec![workdir.clone()];
+    let mut words: Vec<String> = Vec::new();
+    for arg in command.iter().skip(1) {
+        if arg.contains('\n') || arg.contains(' ') {
+            // Scripts passed via `bash -lc`, and patch bodies whose added
+            // files do not exist yet.
+            paths.extend(patch_paths(arg).into_iter().map(|path| workdir.join(path)));
+            words.extend(shlex::split(arg).unwrap_or_default().into_iter().skip(1));
+        } else {
+            words.push(arg.clone());
+        }
+    }
+
+    for word in words.into_iter().take(MAX_COMMAND_WORDS) {
+        if word.starts_with('-') || word.contains('\n') {
+            continue;
+        }
+        let path = workdir.join(&word);
+        if path.exists() && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+fn patch_paths(text: &str) -> Vec<String> {
+    const HEADERS: &[&str] = &[
+        "*** Add File: ",
+        "*** Update File: ",
+        "*** Delete File: ",
+        "*** Move to: ",
+    ];
+    text.lines()
+        .filter_map(|line| {
+            HEADERS
+                .iter()
+                .find_map(|header| line.trim().strip_prefix(header))
+                .map(|path| path.trim().to_string())
+        })
+        .collect()
+}
+
 pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBuf>> {
     discover_project_doc_paths_with_candidates(config, AGENT_FILENAMES)
 }
@@ -531,4 +882,132 @@ mod tests {
 
         assert_eq!(res, "root auto\n\nnested auto");
     }
+
+    #[tokio::test]
+    async fn expands_file_includes_relative_to_doc() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        fs::write(
+            tmp.path().join("AGENTS.md"),
+            "intro\n@docs/style.md\n```\n@docs/style.md\n```\n@missing.md\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("docs/style.md"), "use tabs\n@../AGENTS.md").unwrap();
+
+        let res = get_user_instructions(&make_config(&tmp, 4096, None), None)
+            .await
+            .expect("doc expected");
+
+        // The recursive include back to AGENTS.md is left as written.
+        assert_eq!(
+            res,
+            "intro\nuse tabs\n@../AGENTS.md\n```\n@docs/style.md\n```\n@missing.md\n"
+        );
+    }
+
+    #[test]
+    fn scoped_docs_load_once_for_touched_directories() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::write(repo.path().join(".git"), "gitdir: /dev/null\n").unwrap();
+        fs::write(repo.path().join("AGENTS.md"), "root doc").unwrap();
+        let web = repo.path().join("packages/web");
+        fs::create_dir_all(web.join("src")).unwrap();
+        fs::write(web.join("AGENTS.md"), "web doc").unwrap();
+        fs::write(web.join("src/app.ts"), "").unwrap();
+
+        let mut tracker = ScopedDocTracker::new(repo.path(), 4096);
+        let docs = tracker.docs_for_paths(&[web.join("src/app.ts")]);
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].text, "web doc");
+        assert!(tracker.docs_for_paths(&[web.join("src/new.ts")]).is_empty());
+        assert_eq!(tracker.loaded().len(), 1);
+
+        let mut resumed = ScopedDocTracker::new(repo.path(), 4096);
+        let message: ResponseItem = ScopedInstructions::from(docs[0].clone()).into();
+        resumed.restore_from_history(&[message]);
+        assert!(resumed.docs_for_paths(&[web.clone()]).is_empty());
+        assert_eq!(resumed.loaded(), tracker.loaded());
+    }
+
+    #[test]
+    fn shell_calls_report_workdir_existing_args_and_patch_targets() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(repo.path().join("pkg/a")).unwrap();
+        fs::write(repo.path().join("pkg/a/lib.rs"), "").unwrap();
+
+        let call = |command: Vec<&str>| ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: serde_json::json!({ "command": command, "workdir": "pkg" }).to_string(),
+            call_id: "call-1".to_string(),
+        };
+
+        let script = call(vec!["bash", "-lc", "cat a/lib.rs missing.rs"]);
+        let paths = paths_touched_by(&script, repo.path());
+        assert_eq!(
+            paths,
+            vec![repo.path().join("pkg"), repo.path().join("pkg/a/lib.rs")]
+        );
+
+        let patch = "*** Begin Patch\n*** Add File: b/new.rs\n+fn main() {}\n*** End Patch";
+        let paths = paths_touched_by(&call(vec!["apply_patch", patch]), repo.path());
+        assert!(paths.contains(&repo.path().join("pkg/b/new.rs")));
+    }
+
+    const TOOL_PATCH: &str = "*** Begin Patch\n*** Update File: pkg/a/lib.rs\n@@\n-old\n+new\n*** Add File: web/new.ts\n+x\n*** End Patch";
+
+    #[test]
+    fn freeform_apply_patch_calls_report_patch_targets() {
+        let cwd = Path::new("/repo");
+        let item = ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id: "call-1".to_string(),
+            name: "apply_patch".to_string(),
+            input: TOOL_PATCH.to_string(),
+        };
+        assert_eq!(
+            paths_touched_by(&item, cwd),
+            vec![cwd.join("pkg/a/lib.rs"), cwd.join("web/new.ts")]
+        );
+    }
+
+    #[test]
+    fn json_apply_patch_calls_report_patch_targets() {
+        let cwd = Path::new("/repo");
+        let item = ResponseItem::FunctionCall {
+            id: None,
+            name: "apply_patch".to_string(),
+            arguments: serde_json::json!({ "input": TOOL_PATCH }).to_string(),
+            call_id: "call-1".to_string(),
+        };
+        assert_eq!(
+            paths_touched_by(&item, cwd),
+            vec![cwd.join("pkg/a/lib.rs"), cwd.join("web/new.ts")]
+        );
+    }
+
+    #[test]
+    fn scoped_docs_share_the_session_budget() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::write(repo.path().join(".git"), "gitdir: /dev/null\n").unwrap();
+        fs::write(repo.path().join("AGENTS.md"), "root").unwrap();
+        for (dir, text) in [("a", "aaaaaa"), ("b", "bbbbbb"), ("c", "cccccc")] {
+            fs::create_dir_all(repo.path().join(dir)).unwrap();
+            fs::write(repo.path().join(dir).join("AGENTS.md"), text).unwrap();
+        }
+
+        // 16 bytes: 4 for the startup doc, 6 for `a`, the last 6 for `b`.
+        let mut tracker = ScopedDocTracker::new(repo.path(), 16);
+        let docs = tracker.docs_for_paths(&[repo.path().join("a"), repo.path().join("b")]);
+        let texts: Vec<&str> = docs.iter().map(|doc| doc.text.as_str()).collect();
+        assert_eq!(texts, vec!["aaaaaa", "bbbbbb"]);
+        assert!(tracker.docs_for_paths(&[repo.path().join("c")]).is_empty());
+
+        // A doc that does not fit is truncated to what is left.
+        let mut tracker = ScopedDocTracker::new(repo.path(), 12);
+        let docs = tracker.docs_for_paths(&[repo.path().join("a"), repo.path().join("b")]);
+        let texts: Vec<&str> = docs.iter().map(|doc| doc.text.as_str()).collect();
+        assert_eq!(texts, vec!["aaaaaa", "bb"]);
+    }
 }
@@ -42,6 +42,7 @@ pub use code_protocol::protocol::GitInfo;
 pub use code_protocol::protocol::RolloutItem;
 pub use code_protocol::protocol::RolloutLine;
 pub use code_protocol::protocol::ConversationPathResponseEvent;
+pub use code_protocol::protocol::ThreadRolledBackEvent;
 pub use code_protocol::protocol::McpListToolsResponseEvent;
 pub use code_protocol::protocol::McpServerFailure;
 pub use code_protocol::protocol::McpServerFailurePhase;
@@ -253,6 +254,12 @@ pub enum Op {
         snapshot: serde_json::Value,
     },
 
+    /// Keep a ghost snapshot reachable under `refs/magik/snapshots/<session>`
+    /// and record it in the rollout so `/undo` can offer it after a resume.
+    RecordGhostSnapshot {
+        ghost_commit: code_git_tooling::GhostCommit,
+    },
+
     /// Execute a project-scoped custom command defined in configuration.
     RunProjectCommand {
         name: String,
@@ -279,6 +286,21 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::ListSkillsResponse`.
     ListSkills,
 
+    /// Request a token breakdown of what the next turn would send.
+    /// Reply is delivered via `EventMsg::ContextInspection`.
+    InspectContext,
+
+    /// Drop or collapse history items listed by `InspectContext` before the
+    /// next turn. Rejected while a turn is running. Reply is a refreshed
+    /// `EventMsg::ContextInspection`.
+    EditContext { edits: Vec<ContextEdit> },
+
+    /// Drop the last `num_turns` user turns from the conversation history in
+    /// place. Rejected while a turn is running. Reply is
+    /// `EventMsg::ThreadRolledBack`, or `EventMsg::Error` when the rollback
+    /// cannot be applied.
+    ThreadRollback { num_turns: u32 },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -864,6 +886,9 @@ pub enum EventMsg {
     /// List of skills available to the agent.
     ListSkillsResponse(ListSkillsResponseEvent),
 
+    /// Token breakdown of the context the next turn would send.
+    ContextInspection(ContextInspectionEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     /// Browser screenshot has been captured and is ready for display
@@ -887,6 +912,9 @@ pub enum EventMsg {
     /// Response to a conversation path request.
     ConversationPath(code_protocol::protocol::ConversationPathResponseEvent),
 
+    /// The last `num_turns` user turns were removed from the history.
+    ThreadRolledBack(ThreadRolledBackEvent),
+
     /// Entered review mode with the provided request.
     EnteredReviewMode(code_protocol::protocol::ReviewRequest),
 
@@ -1067,6 +1095,10 @@ pub struct ReplayHistoryEvent {
     pub items: Vec<code_protocol::models::ResponseItem>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub history_snapshot: Option<serde_json::Value>,
+    /// Ghost snapshots recorded in the rollout, oldest first. They are not
+    /// part of `items` because they never reach the model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ghost_snapshots: Vec<code_git_tooling::GhostCommit>,
 }
 
 impl From<TokenUsage> for FinalOutput {
@@ -1339,6 +1371,90 @@ pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
+/// What a piece of the model context is made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextCategory {
+    /// Base instructions and developer messages.
+    Instructions,
+    AgentsMd,
+    Skills,
+    /// User and assistant messages, plus reasoning.
+    Messages,
+    /// Tool calls together with their outputs.
+    ToolOutputs,
+    Images,
+    /// Environment context, deltas, browser snapshots and status messages.
+    EnvironmentContext,
+    CompactionSummary,
+}
+
+impl ContextCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            ContextCategory::Instructions => "System & instructions",
+            ContextCategory::AgentsMd => "AGENTS.md",
+            ContextCategory::Skills => "Skills",
+            ContextCategory::Messages => "History messages",
+            ContextCategory::ToolOutputs => "Tool calls & outputs",
+            ContextCategory::Images => "Images",
+            ContextCategory::EnvironmentContext => "Environment context",
+            ContextCategory::CompactionSummary => "Compaction summaries",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContextCategoryUsage {
+    pub category: ContextCategory,
+    pub tokens: u64,
+    pub items: usize,
+}
+
+/// One entry in the context, as listed by `/context`. A tool call and its
+/// output form a single entry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContextItemSummary {
+    /// Stable only until the history changes; edits with a stale id are
+    /// skipped.
+    pub id: String,
+    pub category: ContextCategory,
+    pub label: String,
+    pub tokens: u64,
+    pub can_drop: bool,
+    pub can_collapse: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContextInspectionEvent {
+    /// Estimated tokens for instructions plus history; tool schemas and
+    /// per-request status items are not included.
+    pub total_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    pub categories: Vec<ContextCategoryUsage>,
+    /// Largest entries first.
+    pub largest_items: Vec<ContextItemSummary>,
+    /// Outcome of the edits that produced this inspection, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notice: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextEditAction {
+    /// Remove the entry from history.
+    Drop,
+    /// Keep the entry but replace its body with a short placeholder.
+    Collapse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContextEdit {
+    pub item_id: String,
+    pub action: ContextEditAction,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
@@ -1440,7 +1556,7 @@ pub struct AgentInfo {
 }
 
 /// User's decision in response to an ExecApprovalRequest.
-#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ReviewDecision {
     /// User has approved this command and the agent should execute it.
@@ -1451,16 +1567,48 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// Exec approvals only: the user rewrote the proposed command before
+    /// approving it. The agent runs `command` instead of the original argv.
+    ApprovedWithEdits { command: Vec<String> },
+
+    /// Patch approvals only: the user reviewed the patch hunk by hunk and
+    /// only the accepted (or edited) hunks should be applied. Hunks without
+    /// an entry are treated as rejected.
+    ApprovedHunks { hunks: Vec<PatchHunkReview> },
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
     Denied,
 
+    /// Like `Denied`, but the user explained why. The feedback is returned to
+    /// the model with the rejection so it can change course.
+    DeniedWithFeedback { feedback: String },
+
     /// User has denied this command and the agent should not do anything until
     /// the user's next command.
     Abort,
 }
 
+/// Decision for a single hunk of an `apply_patch` change.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HunkDecision {
+    Accept,
+    Reject,
+    /// Replace the hunk's proposed lines with `replacement`.
+    Edit { replacement: String },
+}
+
+/// Review of one hunk, addressed by file path and 0-based hunk index as
+/// produced by `code_core::patch_review::compute_hunks`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PatchHunkReview {
+    pub path: PathBuf,
+    pub hunk_index: usize,
+    pub decision: HunkDecision,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FileChange {
@@ -0,0 +1,761 @@
+//! Machine-readable exports of review findings.
+//!
+//! - [`review_to_sarif`] renders a SARIF 2.1.0 log for code-scanning
+//!   dashboards; [`review_failure_to_sarif`] renders a review that produced
+//!   no output as a failed invocation rather than a clean run.
+//! - [`review_to_comments`] renders a pull-request review payload (summary
+//!   body plus one comment per finding, keyed by file and line) in the shape
+//!   accepted by the GitHub "create a review" endpoint.
+//!
+//! Both take the repository root so file locations can be reported relative to
+//! it; findings outside the root keep an absolute `file://` URI.
+
+use std::path::Path;
+
+use serde::Serialize;
+use url::Url;
+
+use crate::protocol::ReviewFinding;
+use crate::protocol::ReviewOutputEvent;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const SRCROOT: &str = "%SRCROOT%";
+const TOOL_NAME: &str = "code-review";
+const TOOL_URI: &str = "https://github.com/just-every/code";
+
+/// Findings below this confidence are reported one SARIF level lower.
+const LOW_CONFIDENCE: f32 = 0.5;
+
+/// Priority bucket derived from the finding's numeric priority, falling back
+/// to a `[P0]`..`[P3]` tag at the start of the title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Priority {
+    P0,
+    P1,
+    P2,
+    P3,
+    Unknown,
+}
+
+impl Priority {
+    fn of(finding: &ReviewFinding) -> Self {
+        match finding.priority {
+            0 => Self::P0,
+            1 => Self::P1,
+            2 => Self::P2,
+            3 => Self::P3,
+            _ => Self::from_title(&finding.title),
+        }
+    }
+
+    fn from_title(title: &str) -> Self {
+        let tag = title.trim_start();
+        let tag = tag
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .map(|(inner, _)| inner.trim().to_ascii_uppercase());
+        match tag.as_deref() {
+            Some("P0") => Self::P0,
+            Some("P1") => Self::P1,
+            Some("P2") => Self::P2,
+            Some("P3") => Self::P3,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn rule_id(self) -> &'static str {
+        match self {
+            Self::P0 => "review/p0",
+            Self::P1 => "review/p1",
+            Self::P2 => "review/p2",
+            Self::P3 => "review/p3",
+            Self::Unknown => "review/unprioritized",
+        }
+    }
+
+    fn rule_description(self) -> &'static str {
+        match self {
+            Self::P0 => "Blocking issue; drop everything to fix.",
+            Self::P1 => "Urgent issue; address in the next cycle.",
+            Self::P2 => "Normal issue; fix eventually.",
+            Self::P3 => "Low priority; nice to have.",
+            Self::Unknown => "Review finding without a priority.",
+        }
+    }
+
+    fn base_level(self) -> SarifLevel {
+        match self {
+            Self::P0 | Self::P1 => SarifLevel::Error,
+            Self::P2 | Self::Unknown => SarifLevel::Warning,
+            Self::P3 => SarifLevel::Note,
+        }
+    }
+
+    const ALL: [Priority; 5] = [Self::P0, Self::P1, Self::P2, Self::P3, Self::Unknown];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum SarifLevel {
+    Error,
+    Warning,
+    Note,
+}
+
+impl SarifLevel {
+    fn downgraded(self) -> Self {
+        match self {
+            Self::Error => Self::Warning,
+            Self::Warning | Self::Note => Self::Note,
+        }
+    }
+}
+
+/// SARIF level for a finding: priority sets the base level and low-confidence
+/// findings drop one level.
+fn sarif_level(finding: &ReviewFinding) -> SarifLevel {
+    let level = Priority::of(finding).base_level();
+    if finding.confidence_score < LOW_CONFIDENCE {
+        level.downgraded()
+    } else {
+        level
+    }
+}
+
+/// Where a finding points, relative to the repository root when possible.
+enum FileRef {
+    /// `path` is `/`-separated and relative to the root; `uri` is its
+    /// percent-encoded form.
+    Relative {
+        path: String,
+        uri: String,
+    },
+    Absolute {
+        path: String,
+        uri: String,
+    },
+}
+
+impl FileRef {
+    fn resolve(file: &Path, repo_root: Option<&Path>) -> Self {
+        let relative = match repo_root {
+            Some(root) if file.is_absolute() => file.strip_prefix(root).ok(),
+            Some(_) | None => file.is_relative().then_some(file),
+        };
+        if let Some(relative) = relative {
+            let path = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            let uri = percent_encode_path(&path);
+            return Self::Relative { path, uri };
+        }
+        let path = file.display().to_string();
+        let uri = Url::from_file_path(file)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|()| percent_encode_path(&path));
+        Self::Absolute { path, uri }
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            Self::Relative { path, .. } | Self::Absolute { path, .. } => path,
+        }
+    }
+}
+
+/// Percent-encode everything outside RFC 3986 unreserved characters and `/`.
+fn percent_encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun {
+    tool: SarifTool,
+    invocations: Vec<SarifInvocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_uri_base_ids: Option<serde_json::Value>,
+    results: Vec<SarifResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<SarifRunProperties>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifInvocation {
+    execution_successful: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_execution_notifications: Vec<SarifNotification>,
+}
+
+#[derive(Serialize)]
+struct SarifNotification {
+    level: SarifLevel,
+    message: SarifMessage,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRunProperties {
+    overall_correctness: String,
+    overall_explanation: String,
+    overall_confidence_score: f32,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    information_uri: &'static str,
+    version: String,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: &'static str,
+    short_description: SarifMessage,
+    default_configuration: SarifRuleConfiguration,
+}
+
+#[derive(Serialize)]
+struct SarifRuleConfiguration {
+    level: SarifLevel,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: SarifLevel,
+    /// 0-100 relevance, taken from the finding's confidence.
+    rank: f64,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+    properties: SarifResultProperties,
+}
+
+#[derive(Serialize)]
+struct SarifResultProperties {
+    title: String,
+    priority: i32,
+    confidence: f32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: u32,
+    end_line: u32,
+}
+
+/// Normalized 1-based line range; `None` when the model reported no line.
+fn line_span(finding: &ReviewFinding) -> Option<(u32, u32)> {
+    let range = &finding.code_location.line_range;
+    let start = range.start.min(range.end);
+    let end = range.start.max(range.end);
+    if end == 0 {
+        return None;
+    }
+    Some((start.max(1), end))
+}
+
+fn sarif_tool(tool_version: &str) -> SarifTool {
+    let rules = Priority::ALL
+        .iter()
+        .map(|priority| SarifRule {
+            id: priority.rule_id(),
+            short_description: SarifMessage {
+                text: priority.rule_description().to_string(),
+            },
+            default_configuration: SarifRuleConfiguration {
+                level: priority.base_level(),
+            },
+        })
+        .collect();
+    SarifTool {
+        driver: SarifDriver {
+            name: TOOL_NAME,
+            information_uri: TOOL_URI,
+            version: tool_version.to_string(),
+            rules,
+        },
+    }
+}
+
+fn sarif_log(run: SarifRun) -> serde_json::Value {
+    let log = SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![run],
+    };
+    serde_json::to_value(log).unwrap_or(serde_json::Value::Null)
+}
+
+/// Render `output` as a SARIF 2.1.0 log with one run.
+pub fn review_to_sarif(
+    output: &ReviewOutputEvent,
+    repo_root: Option<&Path>,
+    tool_version: &str,
+) -> serde_json::Value {
+    let mut uses_srcroot = false;
+    let results = output
+        .findings
+        .iter()
+        .map(|finding| {
+            let priority = Priority::of(finding);
+            let (uri, uri_base_id) =
+                match FileRef::resolve(&finding.code_location.absolute_file_path, repo_root) {
+                    FileRef::Relative { uri, .. } => {
+                        uses_srcroot = true;
+                        (uri, Some(SRCROOT))
+                    }
+                    FileRef::Absolute { uri, .. } => (uri, None),
+                };
+            let text = if finding.body.trim().is_empty() {
+                finding.title.trim().to_string()
+            } else {
+                format!("{}\n\n{}", finding.title.trim(), finding.body.trim())
+            };
+            SarifResult {
+                rule_id: priority.rule_id(),
+                rule_index: Priority::ALL
+                    .iter()
+                    .position(|p| *p == priority)
+                    .unwrap_or_default(),
+                level: sarif_level(finding),
+                rank: (f64::from(finding.confidence_score.clamp(0.0, 1.0)) * 100.0).round(),
+                message: SarifMessage { text },
+                locations: vec![SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation { uri, uri_base_id },
+                        region: line_span(finding).map(|(start_line, end_line)| SarifRegion {
+                            start_line,
+                            end_line,
+                        }),
+                    },
+                }],
+                properties: SarifResultProperties {
+                    title: finding.title.clone(),
+                    priority: finding.priority,
+                    confidence: finding.confidence_score,
+                },
+            }
+        })
+        .collect();
+
+    let original_uri_base_ids = match repo_root {
+        Some(root) if uses_srcroot => Url::from_directory_path(root).ok().map(|url| {
+            let mut base_ids = serde_json::Map::new();
+            base_ids.insert(
+                SRCROOT.to_string(),
+                serde_json::json!({ "uri": url.to_string() }),
+            );
+            serde_json::Value::Object(base_ids)
+        }),
+        _ => None,
+    };
+
+    sarif_log(SarifRun {
+        tool: sarif_tool(tool_version),
+        invocations: vec![SarifInvocation {
+            execution_successful: true,
+            tool_execution_notifications: Vec::new(),
+        }],
+        original_uri_base_ids,
+        results,
+        properties: Some(SarifRunProperties {
+            overall_correctness: output.overall_correctness.clone(),
+            overall_explanation: output.overall_explanation.clone(),
+            overall_confidence_score: output.overall_confidence_score,
+        }),
+    })
+}
+
+/// Render a review that did not finish as a SARIF 2.1.0 log whose only
+/// invocation failed with `error`. Dashboards then show a failed scan
+/// instead of a clean one with no findings.
+pub fn review_failure_to_sarif(error: &str, tool_version: &str) -> serde_json::Value {
+    sarif_log(SarifRun {
+        tool: sarif_tool(tool_version),
+        invocations: vec![SarifInvocation {
+            execution_successful: false,
+            tool_execution_notifications: vec![SarifNotification {
+                level: SarifLevel::Error,
+                message: SarifMessage {
+                    text: error.to_string(),
+                },
+            }],
+        }],
+        original_uri_base_ids: None,
+        results: Vec::new(),
+        properties: None,
+    })
+}
+
+/// Pull-request review payload: a summary body plus inline comments.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReviewCommentsExport {
+    /// Always `COMMENT`; the export never approves or blocks a PR on its own.
+    pub event: &'static str,
+    pub body: String,
+    /// Sorted by path, then line.
+    pub comments: Vec<ReviewComment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReviewComment {
+    pub path: String,
+    /// Last line of the range the comment is attached to.
+    pub line: u32,
+    /// First line for multi-line comments; omitted for single lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    pub side: &'static str,
+    pub body: String,
+}
+
+/// Render `output` as PR review comments keyed by file and line. Findings
+/// without a usable line are folded into the summary body instead.
+pub fn review_to_comments(
+    output: &ReviewOutputEvent,
+    repo_root: Option<&Path>,
+) -> ReviewCommentsExport {
+    let mut body = String::new();
+    let explanation = output.overall_explanation.trim();
+    if !explanation.is_empty() {
+        body.push_str(explanation);
+    }
+    let correctness = output.overall_correctness.trim();
+    if !correctness.is_empty() {
+        if !body.is_empty() {
+            body.push_str("\n\n");
+        }
+        body.push_str(&format!("**Overall correctness:** {correctness}"));
+    }
+
+    let mut comments = Vec::new();
+    let mut unanchored = Vec::new();
+    for finding in &output.findings {
+        let path = FileRef::resolve(&finding.code_location.absolute_file_path, repo_root);
+        let text = comment_body(finding);
+        match (path, line_span(finding)) {
+            (FileRef::Relative { path, .. }, Some((start, end))) => {
+                comments.push(ReviewComment {
+                    path,
+                    line: end,
+                    start_line: (start < end).then_some(start),
+                    side: "RIGHT",
+                    body: text,
+                });
+            }
+            (path, _) => {
+                unanchored.push(format!("- `{}`: {}", path.path(), text.replace('\n', " ")));
+            }
+        }
+    }
+    comments.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+
+    if !unanchored.is_empty() {
+        if !body.is_empty() {
+            body.push_str("\n\n");
+        }
+        body.push_str("Findings outside the diff:\n");
+        body.push_str(&unanchored.join("\n"));
+    }
+
+    ReviewCommentsExport {
+        event: "COMMENT",
+        body,
+        comments,
+    }
+}
+
+fn comment_body(finding: &ReviewFinding) -> String {
+    let level = match sarif_level(finding) {
+        SarifLevel::Error => "error",
+        SarifLevel::Warning => "warning",
+        SarifLevel::Note => "note",
+    };
+    let confidence = (finding.confidence_score.clamp(0.0, 1.0) * 100.0).round();
+    let mut text = format!("**{}**", finding.title.trim());
+    let details = finding.body.trim();
+    if !details.is_empty() {
+        text.push_str("\n\n");
+        text.push_str(details);
+    }
+    text.push_str(&format!("\n\n_{level} · confidence {confidence}%_"));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ReviewCodeLocation;
+    use crate::protocol::ReviewLineRange;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn finding(path: &str, priority: i32, confidence: f32, start: u32, end: u32) -> ReviewFinding {
+        ReviewFinding {
+            title: "[P1] Off-by-one in loop".to_string(),
+            body: "The loop skips the last element.".to_string(),
+            confidence_score: confidence,
+            priority,
+            code_location: ReviewCodeLocation {
+                absolute_file_path: PathBuf::from(path),
+                line_range: ReviewLineRange { start, end },
+            },
+        }
+    }
+
+    fn output(findings: Vec<ReviewFinding>) -> ReviewOutputEvent {
+        ReviewOutputEvent {
+            findings,
+            overall_correctness: "patch is incorrect".to_string(),
+            overall_explanation: "One bug.".to_string(),
+            overall_confidence_score: 0.8,
+        }
+    }
+
+    #[test]
+    fn priority_and_confidence_map_to_levels() {
+        assert_eq!(
+            sarif_level(&finding("/r/a.rs", 0, 0.9, 1, 1)),
+            SarifLevel::Error
+        );
+        assert_eq!(
+            sarif_level(&finding("/r/a.rs", 1, 0.2, 1, 1)),
+            SarifLevel::Warning
+        );
+        assert_eq!(
+            sarif_level(&finding("/r/a.rs", 2, 0.9, 1, 1)),
+            SarifLevel::Warning
+        );
+        assert_eq!(
+            sarif_level(&finding("/r/a.rs", 3, 0.1, 1, 1)),
+            SarifLevel::Note
+        );
+        // Out-of-range priority falls back to the title tag.
+        assert_eq!(
+            Priority::of(&finding("/r/a.rs", -1, 0.9, 1, 1)),
+            Priority::P1
+        );
+    }
+
+    #[test]
+    fn sarif_uses_repo_relative_uris_and_regions() {
+        let sarif = review_to_sarif(
+            &output(vec![
+                finding("/repo/src/my file.rs", 1, 0.75, 10, 12),
+                finding("/elsewhere/x.rs", 2, 0.9, 0, 0),
+            ]),
+            Some(Path::new("/repo")),
+            "1.2.3",
+        );
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["version"], "1.2.3");
+        assert_eq!(
+            sarif["runs"][0]["originalUriBaseIds"]["%SRCROOT%"]["uri"],
+            "file:///repo/"
+        );
+        let results = sarif["runs"][0]["results"].as_array().expect("results");
+        let first = &results[0];
+        assert_eq!(first["ruleId"], "review/p1");
+        assert_eq!(first["level"], "error");
+        assert_eq!(first["rank"], 75.0);
+        let location = &first["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/my%20file.rs");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(location["region"]["startLine"], 10);
+        assert_eq!(location["region"]["endLine"], 12);
+
+        let second = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(second["artifactLocation"]["uri"], "file:///elsewhere/x.rs");
+        assert!(second.get("region").is_none());
+        let rules = sarif["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .expect("rules");
+        assert_eq!(
+            rules[results[1]["ruleIndex"].as_u64().unwrap() as usize]["id"],
+            "review/p2"
+        );
+    }
+
+    /// Checks the properties SARIF 2.1.0 marks as required (plus the value
+    /// constraints on the optional ones we emit).
+    fn assert_sarif_required_fields(sarif: &serde_json::Value) {
+        assert_eq!(sarif["version"], "2.1.0");
+        let runs = sarif["runs"].as_array().expect("runs is an array");
+        assert!(!runs.is_empty());
+        for run in runs {
+            for invocation in run["invocations"].as_array().expect("invocations") {
+                assert!(invocation["executionSuccessful"].is_boolean());
+                for notification in invocation["toolExecutionNotifications"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                {
+                    assert!(notification["message"]["text"].as_str().is_some());
+                }
+            }
+            let driver = &run["tool"]["driver"];
+            assert!(driver["name"].as_str().is_some_and(|name| !name.is_empty()));
+            let rules = driver["rules"].as_array().expect("rules");
+            for rule in rules {
+                assert!(rule["id"].as_str().is_some());
+            }
+            let results = run["results"].as_array().expect("results is an array");
+            for result in results {
+                assert!(result["message"]["text"].as_str().is_some());
+                assert!(
+                    ["none", "note", "warning", "error"]
+                        .contains(&result["level"].as_str().expect("level"))
+                );
+                let rank = result["rank"].as_f64().expect("rank");
+                assert!((-1.0..=100.0).contains(&rank));
+                let rule_index = result["ruleIndex"].as_u64().expect("ruleIndex") as usize;
+                assert_eq!(rules[rule_index]["id"], result["ruleId"]);
+                for location in result["locations"].as_array().expect("locations") {
+                    let physical = &location["physicalLocation"];
+                    assert!(physical["artifactLocation"]["uri"].as_str().is_some());
+                    if let Some(region) = physical.get("region") {
+                        let start = region["startLine"].as_u64().expect("startLine");
+                        let end = region["endLine"].as_u64().expect("endLine");
+                        assert!(start >= 1 && end >= start);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sarif_output_has_required_fields() {
+        let populated = review_to_sarif(
+            &output(vec![
+                finding("/repo/src/a.rs", 0, 0.9, 12, 4),
+                finding("/elsewhere/x.rs", 3, 0.1, 0, 0),
+            ]),
+            Some(Path::new("/repo")),
+            "1.2.3",
+        );
+        assert_sarif_required_fields(&populated);
+
+        assert_eq!(
+            populated["runs"][0]["invocations"],
+            serde_json::json!([{ "executionSuccessful": true }])
+        );
+
+        let empty = review_to_sarif(&ReviewOutputEvent::default(), None, "1.2.3");
+        assert_sarif_required_fields(&empty);
+        assert_eq!(empty["runs"][0]["results"], serde_json::json!([]));
+        assert!(empty["runs"][0].get("originalUriBaseIds").is_none());
+
+        let failed = review_failure_to_sarif("review produced no output", "1.2.3");
+        assert_sarif_required_fields(&failed);
+        let invocation = &failed["runs"][0]["invocations"][0];
+        assert_eq!(invocation["executionSuccessful"], false);
+        assert_eq!(
+            invocation["toolExecutionNotifications"][0]["message"]["text"],
+            "review produced no output"
+        );
+        assert_eq!(failed["runs"][0]["results"], serde_json::json!([]));
+        assert!(failed["runs"][0].get("properties").is_none());
+
+        let comments = review_to_comments(&ReviewOutputEvent::default(), None);
+        assert!(comments.comments.is_empty());
+        assert_eq!(
+            serde_json::to_value(&comments).expect("comments")["comments"],
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn comments_are_keyed_by_file_and_line() {
+        let export = review_to_comments(
+            &output(vec![
+                finding("/repo/src/b.rs", 2, 0.9, 7, 7),
+                finding("/repo/src/a.rs", 1, 0.9, 3, 5),
+                finding("/repo/src/c.rs", 1, 0.9, 0, 0),
+            ]),
+            Some(Path::new("/repo")),
+        );
+
+        let anchors: Vec<_> = export
+            .comments
+            .iter()
+            .map(|c| (c.path.as_str(), c.start_line, c.line))
+            .collect();
+        assert_eq!(
+            anchors,
+            vec![("src/a.rs", Some(3), 5), ("src/b.rs", None, 7)]
+        );
+        assert!(
+            export.comments[0]
+                .body
+                .starts_with("**[P1] Off-by-one in loop**")
+        );
+        assert!(
+            export
+                .body
+                .contains("Findings outside the diff:\n- `src/c.rs`")
+        );
+        assert_eq!(export.event, "COMMENT");
+    }
+}
@@ -0,0 +1,312 @@
+//! Prompt and hint text for structured review targets.
+//!
+//! Every frontend (TUI picker, `code exec`, app server) turns a
+//! [`ReviewTarget`] into the same instructions here so the review model sees
+//! identical wording regardless of where the review was started.
+
+use code_protocol::protocol::ReviewRequest;
+use code_protocol::protocol::ReviewTarget;
+
+const WORKSPACE_PROMPT: &str = "Review the current workspace changes (staged, unstaged, and untracked files) and highlight bugs, regressions, risky patterns, and missing tests before merge.";
+const STAGED_PROMPT: &str = "Review only the staged changes (`git diff --cached`); ignore unstaged and untracked files. Highlight bugs, regressions, risky patterns, and missing tests before merge.";
+
+/// Build a review request whose prompt and hint are derived from `target`.
+pub fn review_request_for_target(target: ReviewTarget) -> ReviewRequest {
+    let prompt = review_prompt(&target);
+    let hint = review_hint(&target);
+    ReviewRequest {
+        target,
+        user_facing_hint: Some(hint),
+        prompt,
+    }
+}
+
+/// Instructions handed to the review model for `target`.
+pub fn review_prompt(target: &ReviewTarget) -> String {
+    match target {
+        ReviewTarget::UncommittedChanges => WORKSPACE_PROMPT.to_string(),
+        ReviewTarget::StagedChanges => STAGED_PROMPT.to_string(),
+        ReviewTarget::BaseBranch { branch } => format!(
+            "Review the code changes that would merge into '{branch}'. Identify bugs, regressions, risky patterns, and missing tests before merge."
+        ),
+        ReviewTarget::Commit { sha, title } => match title {
+            Some(title) => format!(
+                "Review the code changes introduced by commit {sha} (\"{title}\"). Provide prioritized, actionable findings."
+            ),
+            None => format!(
+                "Review the code changes introduced by commit {sha}. Provide prioritized, actionable findings."
+            ),
+        },
+        ReviewTarget::CommitRange { from, to } => format!(
+            "Review the code changes introduced by the commits in {from}..{to} (see `git log {from}..{to}` and `git diff {from}..{to}`). Treat them as one change set and provide prioritized, actionable findings."
+        ),
+        ReviewTarget::Paths { paths, scope } => {
+            let mut prompt = review_prompt(scope);
+            prompt.push_str(
+                "\n\nOnly review changes to the following paths (git pathspecs; globs allowed) and ignore everything else in the diff:",
+            );
+            for path in paths {
+                prompt.push_str("\n- ");
+                prompt.push_str(path);
+            }
+            let commands = review_git_commands(target);
+            if !commands.is_empty() {
+                prompt.push_str(
+                    "\n\nInspect the changes with these commands, which are already limited to those paths, instead of the unfiltered ones above:",
+                );
+                for command in commands {
+                    let joined = shlex::try_join(command.iter().map(String::as_str))
+                        .unwrap_or_else(|_| command.join(" "));
+                    prompt.push_str("\n- `git ");
+                    prompt.push_str(&joined);
+                    prompt.push('`');
+                }
+            }
+            prompt
+        }
+        ReviewTarget::Custom { instructions } => instructions.clone(),
+    }
+}
+
+/// Git arguments (without the leading `git`) that show the changes a review
+/// of `target` covers. A path filter is passed to each command as pathspecs.
+/// Custom reviews have no fixed change set and return nothing.
+pub fn review_git_commands(target: &ReviewTarget) -> Vec<Vec<String>> {
+    let args = |parts: &[&str]| parts.iter().map(|part| (*part).to_string()).collect();
+    match target {
+        ReviewTarget::UncommittedChanges => {
+            vec![args(&["status", "--short"]), args(&["diff", "HEAD"])]
+        }
+        ReviewTarget::StagedChanges => vec![args(&["diff", "--cached"])],
+        ReviewTarget::BaseBranch { branch } => vec![args(&["diff", "--merge-base", branch])],
+        ReviewTarget::Commit { sha, .. } => vec![args(&["show", sha])],
+        ReviewTarget::CommitRange { from, to } => {
+            let range = format!("{from}..{to}");
+            vec![args(&["log", &range]), args(&["diff", &range])]
+        }
+        ReviewTarget::Paths { paths, scope } => review_git_commands(scope)
+            .into_iter()
+            .map(|mut command| {
+                if !command.iter().any(|arg| arg == "--") {
+                    command.push("--".to_string());
+                }
+                command.extend(paths.iter().cloned());
+                command
+            })
+            .collect(),
+        ReviewTarget::Custom { .. } => Vec::new(),
+    }
+}
+
+/// Short label for `target` shown in history cells and status lines.
+pub fn review_hint(target: &ReviewTarget) -> String {
+    match target {
+        ReviewTarget::UncommittedChanges => "current workspace changes".to_string(),
+        ReviewTarget::StagedChanges => "staged changes".to_string(),
+        ReviewTarget::BaseBranch { branch } => format!("against {branch}"),
+        ReviewTarget::Commit { sha, .. } => {
+            let short: String = sha.chars().take(7).collect();
+            format!("commit {short}")
+        }
+        ReviewTarget::CommitRange { from, to } => format!("commits {from}..{to}"),
+        ReviewTarget::Paths { paths, scope } => {
+            format!("{} in {}", review_hint(scope), paths.join(", "))
+        }
+        ReviewTarget::Custom { instructions } => instructions.clone(),
+    }
+}
+
+/// Parse a commit range spec: `A..B`, `A..` (through `HEAD`), or a bare count
+/// `N` meaning the last N commits.
+pub fn parse_commit_range(spec: &str) -> Result<ReviewTarget, String> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Err("commit range is empty".to_string());
+    }
+    if let Ok(count) = spec.parse::<u32>() {
+        if count == 0 {
+            return Err("commit count must be at least 1".to_string());
+        }
+        return Ok(ReviewTarget::CommitRange {
+            from: format!("HEAD~{count}"),
+            to: "HEAD".to_string(),
+        });
+    }
+    if spec.contains("...") {
+        return Err(format!(
+            "symmetric ranges are not supported: {spec} (use A..B)"
+        ));
+    }
+    let Some((from, to)) = spec.split_once("..") else {
+        return Err(format!(
+            "invalid commit range: {spec} (expected A..B or a commit count)"
+        ));
+    };
+    let from = from.trim();
+    let to = to.trim();
+    if from.is_empty() {
+        return Err(format!("commit range is missing a start: {spec}"));
+    }
+    Ok(ReviewTarget::CommitRange {
+        from: from.to_string(),
+        to: if to.is_empty() { "HEAD" } else { to }.to_string(),
+    })
+}
+
+/// Split a comma- or whitespace-separated list of paths/globs.
+pub fn parse_review_paths(spec: &str) -> Vec<String> {
+    spec.split(|c: char| c == ',' || c.is_whitespace())
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Narrow `target` to `paths`; returns `target` unchanged when `paths` is
+/// empty and merges into an existing path filter instead of nesting.
+pub fn restrict_to_paths(target: ReviewTarget, paths: Vec<String>) -> ReviewTarget {
+    if paths.is_empty() {
+        return target;
+    }
+    match target {
+        ReviewTarget::Paths {
+            paths: mut existing,
+            scope,
+        } => {
+            for path in paths {
+                if !existing.contains(&path) {
+                    existing.push(path);
+                }
+            }
+            ReviewTarget::Paths {
+                paths: existing,
+                scope,
+            }
+        }
+        scope => ReviewTarget::Paths {
+            paths,
+            scope: Box::new(scope),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn commit_count_becomes_head_range() {
+        assert_eq!(
+            parse_commit_range("3").unwrap(),
+            ReviewTarget::CommitRange {
+                from: "HEAD~3".to_string(),
+                to: "HEAD".to_string(),
+            }
+        );
+        assert!(parse_commit_range("0").is_err());
+    }
+
+    #[test]
+    fn open_range_defaults_to_head() {
+        assert_eq!(
+            parse_commit_range("main..").unwrap(),
+            ReviewTarget::CommitRange {
+                from: "main".to_string(),
+                to: "HEAD".to_string(),
+            }
+        );
+        assert!(parse_commit_range("a...b").is_err());
+        assert!(parse_commit_range("..b").is_err());
+        assert!(parse_commit_range("main").is_err());
+    }
+
+    #[test]
+    fn path_filter_wraps_scope_and_merges() {
+        let target = restrict_to_paths(
+            ReviewTarget::StagedChanges,
+            parse_review_paths("src/lib.rs, docs/*.md"),
+        );
+        let target = restrict_to_paths(
+            target,
+            vec!["docs/*.md".to_string(), "Cargo.toml".to_string()],
+        );
+        let ReviewTarget::Paths { paths, scope } = &target else {
+            panic!("expected path filter, got {target:?}");
+        };
+        assert_eq!(paths, &["src/lib.rs", "docs/*.md", "Cargo.toml"]);
+        assert_eq!(**scope, ReviewTarget::StagedChanges);
+
+        let request = review_request_for_target(target);
+        assert!(request.prompt.starts_with(STAGED_PROMPT));
+        assert!(request.prompt.contains("\n- docs/*.md"));
+        assert!(
+            request
+                .prompt
+                .contains("\n- `git diff --cached -- src/lib.rs 'docs/*.md' Cargo.toml`")
+        );
+        assert_eq!(
+            request.user_facing_hint.as_deref(),
+            Some("staged changes in src/lib.rs, docs/*.md, Cargo.toml")
+        );
+    }
+
+    #[test]
+    fn range_commands_carry_the_path_filter() {
+        let target = restrict_to_paths(
+            ReviewTarget::CommitRange {
+                from: "main".to_string(),
+                to: "HEAD".to_string(),
+            },
+            vec!["src".to_string()],
+        );
+        assert_eq!(
+            review_git_commands(&target),
+            vec![
+                vec!["log", "main..HEAD", "--", "src"],
+                vec!["diff", "main..HEAD", "--", "src"],
+            ]
+        );
+        assert!(
+            review_git_commands(&ReviewTarget::Custom {
+                instructions: "look around".to_string(),
+            })
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn path_filter_excludes_other_files_from_the_review_diff() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(repo.path())
+                .output()
+                .expect("run git");
+            assert!(output.status.success(), "git {args:?}: {output:?}");
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir(repo.path().join("src")).expect("mkdir");
+        std::fs::write(repo.path().join("src/lib.rs"), "old\n").expect("write");
+        std::fs::write(repo.path().join("notes.md"), "old\n").expect("write");
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+        std::fs::write(repo.path().join("src/lib.rs"), "new\n").expect("write");
+        std::fs::write(repo.path().join("notes.md"), "new\n").expect("write");
+        git(&["add", "."]);
+
+        let unfiltered = review_git_commands(&ReviewTarget::StagedChanges);
+        let diff = git(&unfiltered[0].iter().map(String::as_str).collect::<Vec<_>>());
+        assert!(diff.contains("notes.md"));
+
+        let target = restrict_to_paths(ReviewTarget::StagedChanges, vec!["src/*.rs".to_string()]);
+        let commands = review_git_commands(&target);
+        assert_eq!(commands.len(), 1);
+        let diff = git(&commands[0].iter().map(String::as_str).collect::<Vec<_>>());
+        assert!(diff.contains("src/lib.rs"), "{diff}");
+        assert!(!diff.contains("notes.md"), "{diff}");
+    }
+}
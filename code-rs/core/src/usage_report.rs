@@ -0,0 +1,337 @@
+//! Usage history reports built from the per-request usage ledger.
+//!
+//! The ledger (`usage/ledger/YYYY-MM.jsonl`, written by
+//! [`crate::account_usage::record_usage_ledger_entry`]) stores one line per
+//! completed model request. Reports bucket those entries into days, weeks or
+//! months (UTC) and group them by account, model, project or session source.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::account_usage::{TokenTotals, UsageLedgerEntry, load_usage_ledger};
+
+const NO_PROJECT_KEY: &str = "(no project)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageReportPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl UsageReportPeriod {
+    /// First day of the bucket containing `ts`. Weeks start on Monday.
+    pub fn bucket_start(self, ts: DateTime<Utc>) -> NaiveDate {
+        let date = ts.date_naive();
+        match self {
+            Self::Daily => date,
+            Self::Weekly => {
+                date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            Self::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// How far back a report reaches when no explicit start is given.
+    pub fn default_lookback(self) -> Duration {
+        match self {
+            Self::Daily => Duration::days(14),
+            Self::Weekly => Duration::weeks(8),
+            Self::Monthly => Duration::days(183),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageReportDimension {
+    Account,
+    Model,
+    Project,
+    Source,
+}
+
+impl UsageReportDimension {
+    fn key_for(self, entry: &UsageLedgerEntry) -> String {
+        match self {
+            Self::Account => entry.account_id.clone(),
+            Self::Model => entry.model.clone(),
+            Self::Project => entry
+                .project
+                .clone()
+                .unwrap_or_else(|| NO_PROJECT_KEY.to_string()),
+            Self::Source => entry.source.label().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UsageReportRow {
+    pub period_start: NaiveDate,
+    pub key: String,
+    pub requests: u64,
+    pub tokens: TokenTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    pub period: UsageReportPeriod,
+    pub group_by: UsageReportDimension,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Sorted by period, then by descending total tokens.
+    pub rows: Vec<UsageReportRow>,
+}
+
+impl UsageReport {
+    /// Aggregate `entries` that fall within `since..until`.
+    pub fn build(
+        entries: &[UsageLedgerEntry],
+        period: UsageReportPeriod,
+        group_by: UsageReportDimension,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Self {
+        let mut buckets: BTreeMap<(NaiveDate, String), (u64, TokenTotals)> = BTreeMap::new();
+        for entry in entries
+            .iter()
+            .filter(|entry| entry.timestamp >= since && entry.timestamp < until)
+        {
+            let key = (period.bucket_start(entry.timestamp), group_by.key_for(entry));
+            let (requests, tokens) = buckets.entry(key).or_default();
+            *requests += 1;
+            tokens.add_totals(&entry.tokens);
+        }
+
+        let mut rows: Vec<UsageReportRow> = buckets
+            .into_iter()
+            .map(|((period_start, key), (requests, tokens))| UsageReportRow {
+                period_start,
+                key,
+                requests,
+                tokens,
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            a.period_start
+                .cmp(&b.period_start)
+                .then_with(|| b.tokens.total_tokens.cmp(&a.tokens.total_tokens))
+                .then_with(|| a.key.cmp(&b.key))
+        });
+
+        Self {
+            period,
+            group_by,
+            since,
+            until,
+            rows,
+        }
+    }
+
+    /// Load the ledger under `code_home` and aggregate it.
+    pub fn load(
+        code_home: &Path,
+        period: UsageReportPeriod,
+        group_by: UsageReportDimension,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> std::io::Result<Self> {
+        let entries = load_usage_ledger(code_home, since, until)?;
+        Ok(Self::build(&entries, period, group_by, since, until))
+    }
+
+    /// Replace row keys, e.g. account ids with display labels.
+    pub fn relabel_keys(&mut self, mut relabel: impl FnMut(&str) -> Option<String>) {
+        for row in &mut self.rows {
+            if let Some(label) = relabel(&row.key) {
+                row.key = label;
+            }
+        }
+    }
+
+    /// Totals per key across the whole report, largest first.
+    pub fn totals_by_key(&self) -> Vec<(String, u64, TokenTotals)> {
+        let mut totals: BTreeMap<&str, (u64, TokenTotals)> = BTreeMap::new();
+        for row in &self.rows {
+            let (requests, tokens) = totals.entry(row.key.as_str()).or_default();
+            *requests += row.requests;
+            tokens.add_totals(&row.tokens);
+        }
+        let mut totals: Vec<(String, u64, TokenTotals)> = totals
+            .into_iter()
+            .map(|(key, (requests, tokens))| (key.to_string(), requests, tokens))
+            .collect();
+        totals.sort_by(|a, b| {
+            b.2.total_tokens
+                .cmp(&a.2.total_tokens)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        totals
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "period_start,key,requests,input_tokens,cached_input_tokens,output_tokens,reasoning_output_tokens,total_tokens\n",
+        );
+        for row in &self.rows {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                row.period_start,
+                csv_field(&row.key),
+                row.requests,
+                row.tokens.input_tokens,
+                row.tokens.cached_input_tokens,
+                row.tokens.output_tokens,
+                row.tokens.reasoning_output_tokens,
+                row.tokens.total_tokens,
+            ));
+        }
+        out
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_usage::UsageSource;
+    use crate::protocol::TokenUsage;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    fn entry(
+        day: u32,
+        account: &str,
+        model: &str,
+        project: Option<&str>,
+        source: UsageSource,
+        total: u64,
+    ) -> UsageLedgerEntry {
+        let usage = TokenUsage {
+            input_tokens: total,
+            total_tokens: total,
+            ..TokenUsage::default()
+        };
+        UsageLedgerEntry::new(
+            account.to_string(),
+            model.to_string(),
+            project.map(str::to_string),
+            source,
+            &usage,
+            Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+        )
+    }
+
+    fn sample_entries() -> Vec<UsageLedgerEntry> {
+        vec![
+            // 2026-03-02 is a Monday.
+            entry(2, "acct-a", "gpt-5", Some("/src/app"), UsageSource::Tui, 100),
+            entry(3, "acct-b", "gpt-5", None, UsageSource::Agent, 300),
+            entry(3, "acct-a", "gpt-5-mini", Some("/src/app"), UsageSource::Exec, 50),
+            entry(10, "acct-a", "gpt-5", Some("/src/lib"), UsageSource::AutoDrive, 40),
+        ]
+    }
+
+    fn window() -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn weekly_report_groups_by_account() {
+        let (since, until) = window();
+        let report = UsageReport::build(
+            &sample_entries(),
+            UsageReportPeriod::Weekly,
+            UsageReportDimension::Account,
+            since,
+            until,
+        );
+        let rows: Vec<(String, String, u64, u64)> = report
+            .rows
+            .iter()
+            .map(|row| {
+                (
+                    row.period_start.to_string(),
+                    row.key.clone(),
+                    row.requests,
+                    row.tokens.total_tokens,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("2026-03-02".to_string(), "acct-b".to_string(), 1, 300),
+                ("2026-03-02".to_string(), "acct-a".to_string(), 2, 150),
+                ("2026-03-09".to_string(), "acct-a".to_string(), 1, 40),
+            ]
+        );
+    }
+
+    #[test]
+    fn totals_by_project_and_source() {
+        let (since, until) = window();
+        let by_project = UsageReport::build(
+            &sample_entries(),
+            UsageReportPeriod::Monthly,
+            UsageReportDimension::Project,
+            since,
+            until,
+        );
+        let totals: Vec<(String, u64)> = by_project
+            .totals_by_key()
+            .into_iter()
+            .map(|(key, _, tokens)| (key, tokens.total_tokens))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                (NO_PROJECT_KEY.to_string(), 300),
+                ("/src/app".to_string(), 150),
+                ("/src/lib".to_string(), 40),
+            ]
+        );
+
+        let by_source = UsageReport::build(
+            &sample_entries(),
+            UsageReportPeriod::Monthly,
+            UsageReportDimension::Source,
+            since,
+            until,
+        );
+        let keys: Vec<String> = by_source.rows.iter().map(|row| row.key.clone()).collect();
+        assert_eq!(keys, vec!["agents", "TUI", "exec", "Auto Drive"]);
+    }
+
+    #[test]
+    fn csv_quotes_keys_with_commas() {
+        let (since, until) = window();
+        let mut report = UsageReport::build(
+            &sample_entries()[..1],
+            UsageReportPeriod::Daily,
+            UsageReportDimension::Account,
+            since,
+            until,
+        );
+        report.relabel_keys(|_| Some("Team, shared".to_string()));
+        let csv = report.to_csv();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("2026-03-02,\"Team, shared\",1,100,0,0,0,100")
+        );
+    }
+}
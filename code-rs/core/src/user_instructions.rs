@@ -6,6 +6,9 @@ use code_protocol::models::ResponseItem;
 
 pub const USER_INSTRUCTIONS_OPEN_TAG_LEGACY: &str = "<user_instructions>";
 pub const USER_INSTRUCTIONS_PREFIX: &str = "# AGENTS.md instructions for ";
+pub const SCOPED_INSTRUCTIONS_PREFIX: &str = "# Nested AGENTS.md instructions for ";
+const SCOPED_INSTRUCTIONS_NOTE: &str = "These apply to files under this directory and take \
+precedence over broader AGENTS.md instructions when they conflict.";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename = "user_instructions", rename_all = "snake_case")]
@@ -40,6 +43,53 @@ impl From<UserInstructions> for ResponseItem {
     }
 }
 
+/// AGENTS.md from a directory below the session's startup search path,
+/// injected the first time a turn touches that directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename = "scoped_instructions", rename_all = "snake_case")]
+pub(crate) struct ScopedInstructions {
+    pub directory: String,
+    pub text: String,
+}
+
+impl ScopedInstructions {
+    pub fn is_scoped_instructions(message: &[ContentItem]) -> bool {
+        matches!(message, [ContentItem::InputText { text }]
+            if text.starts_with(SCOPED_INSTRUCTIONS_PREFIX))
+    }
+
+    /// Recover the directory and text from a message built by the `From`
+    /// impl below.
+    pub fn parse(message: &[ContentItem]) -> Option<Self> {
+        let [ContentItem::InputText { text }] = message else {
+            return None;
+        };
+        let rest = text.strip_prefix(SCOPED_INSTRUCTIONS_PREFIX)?;
+        let (directory, body) = rest.split_once('\n')?;
+        let start = body.find("<INSTRUCTIONS>\n")? + "<INSTRUCTIONS>\n".len();
+        let end = body.rfind("\n</INSTRUCTIONS>")?;
+        Some(Self {
+            directory: directory.to_string(),
+            text: body.get(start..end)?.to_string(),
+        })
+    }
+}
+
+impl From<ScopedInstructions> for ResponseItem {
+    fn from(si: ScopedInstructions) -> Self {
+        let directory = si.directory;
+        let contents = si.text;
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!(
+                    "{SCOPED_INSTRUCTIONS_PREFIX}{directory}\n{SCOPED_INSTRUCTIONS_NOTE}\n\n<INSTRUCTIONS>\n{contents}\n</INSTRUCTIONS>",
+                ),
+            }], end_turn: None, phase: None}
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename = "skill_instructions", rename_all = "snake_case")]
 pub(crate) struct SkillInstructions {
@@ -112,6 +162,21 @@ mod tests {
         ]));
     }
 
+    #[test]
+    fn scoped_instructions_round_trip() {
+        let scoped = ScopedInstructions {
+            directory: "/repo/packages/web".to_string(),
+            text: "Use pnpm.\n\nRun `pnpm test`.".to_string(),
+        };
+        let ResponseItem::Message { content, .. } = ResponseItem::from(scoped.clone()) else {
+            panic!("expected ResponseItem::Message");
+        };
+
+        assert!(ScopedInstructions::is_scoped_instructions(&content));
+        assert!(!UserInstructions::is_user_instructions(&content));
+        assert_eq!(ScopedInstructions::parse(&content), Some(scoped));
+    }
+
     #[test]
     fn test_skill_instructions() {
         let skill_instructions = SkillInstructions {
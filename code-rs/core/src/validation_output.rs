@@ -0,0 +1,265 @@
+//! Parsers that turn the output of `[[validation.custom]]` tools into
+//! individual problems: a regex problem matcher, SARIF and checkstyle XML.
+
+use crate::config_types::ValidationOutputFormat;
+use regex_lite::Regex;
+use serde_json as json;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ParsedProblem {
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    pub severity: Option<String>,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl ParsedProblem {
+    /// `12:5 error: message (code)`; the file is reported separately.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => out.push_str(&format!("{line}:{column} ")),
+            (Some(line), None) => out.push_str(&format!("{line} ")),
+            _ => {}
+        }
+        if let Some(severity) = self.severity.as_deref().filter(|s| !s.is_empty()) {
+            out.push_str(severity);
+            out.push_str(": ");
+        }
+        out.push_str(self.message.trim());
+        if let Some(code) = self.code.as_deref().filter(|c| !c.is_empty()) {
+            out.push_str(&format!(" ({code})"));
+        }
+        out
+    }
+}
+
+/// Parse tool output with `format`. Returns `Ok(None)` for `lines`, which the
+/// harness handles with its usual line collection.
+pub(crate) fn parse_output(
+    format: ValidationOutputFormat,
+    pattern: Option<&str>,
+    output: &str,
+) -> Result<Option<Vec<ParsedProblem>>, String> {
+    match format {
+        ValidationOutputFormat::Lines => Ok(None),
+        ValidationOutputFormat::Regex => {
+            let pattern =
+                pattern.ok_or_else(|| "format \"regex\" requires `pattern`".to_string())?;
+            parse_regex(pattern, output).map(Some)
+        }
+        ValidationOutputFormat::Sarif => parse_sarif(output).map(Some),
+        ValidationOutputFormat::Checkstyle => Ok(Some(parse_checkstyle(output))),
+    }
+}
+
+fn parse_regex(pattern: &str, output: &str) -> Result<Vec<ParsedProblem>, String> {
+    let re = Regex::new(pattern).map_err(|err| format!("invalid pattern: {err}"))?;
+    let mut problems = Vec::new();
+    for line in output.lines() {
+        let Some(caps) = re.captures(line) else {
+            continue;
+        };
+        let group = |name: &str| {
+            caps.name(name)
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        problems.push(ParsedProblem {
+            file: group("file"),
+            line: group("line").and_then(|value| value.parse().ok()),
+            column: group("column").and_then(|value| value.parse().ok()),
+            severity: group("severity"),
+            code: group("code"),
+            message: group("message").unwrap_or_else(|| line.trim().to_string()),
+        });
+    }
+    Ok(problems)
+}
+
+fn parse_sarif(output: &str) -> Result<Vec<ParsedProblem>, String> {
+    let value: json::Value =
+        json::from_str(output.trim()).map_err(|err| format!("invalid SARIF: {err}"))?;
+    let mut problems = Vec::new();
+    let runs = value.get("runs").and_then(|runs| runs.as_array());
+    for run in runs.into_iter().flatten() {
+        let results = run.get("results").and_then(|results| results.as_array());
+        for result in results.into_iter().flatten() {
+            let message = result
+                .pointer("/message/text")
+                .or_else(|| result.pointer("/message/markdown"))
+                .and_then(|text| text.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let location = result.pointer("/locations/0/physicalLocation");
+            let file = location
+                .and_then(|loc| loc.pointer("/artifactLocation/uri"))
+                .and_then(|uri| uri.as_str())
+                .map(|uri| uri.strip_prefix("file://").unwrap_or(uri).to_string());
+            let region = location.and_then(|loc| loc.get("region"));
+            problems.push(ParsedProblem {
+                file,
+                line: region
+                    .and_then(|r| r.get("startLine"))
+                    .and_then(json::Value::as_u64),
+                column: region
+                    .and_then(|r| r.get("startColumn"))
+                    .and_then(json::Value::as_u64),
+                severity: result
+                    .get("level")
+                    .and_then(|level| level.as_str())
+                    .map(str::to_string),
+                code: result
+                    .get("ruleId")
+                    .and_then(|rule| rule.as_str())
+                    .map(str::to_string),
+                message,
+            });
+        }
+    }
+    Ok(problems)
+}
+
+/// Checkstyle reports are flat enough that scanning `<file>` and `<error>`
+/// start tags is sufficient.
+fn parse_checkstyle(output: &str) -> Vec<ParsedProblem> {
+    let mut problems = Vec::new();
+    let mut current_file: Option<String> = None;
+    let mut rest = output;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else { break };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        let tag = tag.trim_end_matches('/');
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        match name {
+            "file" => current_file = xml_attr(attrs, "name"),
+            "/file" => current_file = None,
+            "error" => problems.push(ParsedProblem {
+                file: current_file.clone(),
+                line: xml_attr(attrs, "line").and_then(|value| value.parse().ok()),
+                column: xml_attr(attrs, "column").and_then(|value| value.parse().ok()),
+                severity: xml_attr(attrs, "severity"),
+                code: xml_attr(attrs, "source"),
+                message: xml_attr(attrs, "message").unwrap_or_default(),
+            }),
+            _ => {}
+        }
+    }
+    problems
+}
+
+fn xml_attr(attrs: &str, key: &str) -> Option<String> {
+    let mut rest = attrs;
+    loop {
+        rest = rest.trim_start();
+        let eq = rest.find('=')?;
+        let name = rest[..eq].trim();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let close = after[1..].find(quote)?;
+        let value = &after[1..1 + close];
+        if name == key {
+            return Some(xml_unescape(value));
+        }
+        rest = &after[close + 2..];
+    }
+}
+
+fn xml_unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regex_matcher_reads_named_groups() {
+        let output =
+            "src/lib.rs:10:5: warning: unused variable `x` [unused_variables]\nerror: aborting\n";
+        let pattern = r"^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*?)(?: \[(?P<code>[\w:]+)\])?$";
+        let problems = parse_output(ValidationOutputFormat::Regex, Some(pattern), output)
+            .unwrap()
+            .unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].file.as_deref(), Some("src/lib.rs"));
+        assert_eq!(
+            problems[0].summary(),
+            "10:5 warning: unused variable `x` (unused_variables)"
+        );
+    }
+
+    #[test]
+    fn sarif_results_become_problems() {
+        let output = r#"{"version":"2.1.0","runs":[{"results":[{"ruleId":"F401","level":"error",
+            "message":{"text":"`os` imported but unused"},
+            "locations":[{"physicalLocation":{"artifactLocation":{"uri":"file:///repo/app.py"},
+            "region":{"startLine":1,"startColumn":8}}}]}]}]}"#;
+        let problems = parse_output(ValidationOutputFormat::Sarif, None, output)
+            .unwrap()
+            .unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].file.as_deref(), Some("/repo/app.py"));
+        assert_eq!(
+            problems[0].summary(),
+            "1:8 error: `os` imported but unused (F401)"
+        );
+    }
+
+    #[test]
+    fn checkstyle_errors_keep_their_file() {
+        let output = r#"<?xml version="1.0" encoding="utf-8"?>
+<checkstyle version="8.0">
+    <file name="src/Main.kt">
+        <error line="3" column="1" severity="error" message="Unexpected blank line(s) before &quot;}&quot;" source="standard:no-blank-line-before-rbrace" />
+    </file>
+    <file name="src/Ok.kt"></file>
+</checkstyle>"#;
+        let problems = parse_output(ValidationOutputFormat::Checkstyle, None, output)
+            .unwrap()
+            .unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].file.as_deref(), Some("src/Main.kt"));
+        assert_eq!(problems[0].line, Some(3));
+        assert_eq!(problems[0].message, "Unexpected blank line(s) before \"}\"");
+        assert_eq!(
+            problems[0].code.as_deref(),
+            Some("standard:no-blank-line-before-rbrace")
+        );
+    }
+}
@@ -0,0 +1,482 @@
+//! Inventory and cleanup of the worktrees Code creates under
+//! `~/.magik/working/<repo>/branches` for `/branch` sessions, agents and
+//! reviews.
+//!
+//! Listing gathers each worktree's [`BranchMetadata`], the process that still
+//! holds it (from the per-process session registry), ahead/behind counts
+//! against its base branch, dirty status and disk usage. Pruning refuses to
+//! remove worktrees that are in use, and without `force` also keeps worktrees
+//! with uncommitted changes, commits missing from their base branch, or a base
+//! branch that cannot be resolved to check against.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use tokio::process::Command;
+
+use crate::git_worktree;
+use crate::git_worktree::BranchMetadata;
+use crate::git_worktree::WorktreeOwner;
+use crate::housekeeping;
+
+#[derive(Debug, Clone)]
+pub struct ManagedWorktree {
+    /// Directory name under `branches/`, used to address the worktree.
+    pub name: String,
+    pub path: PathBuf,
+    pub repo_name: String,
+    /// Main checkout the worktree belongs to, when it can be resolved.
+    pub repo_root: Option<PathBuf>,
+    /// Checked-out branch; `None` for detached worktrees.
+    pub branch: Option<String>,
+    pub metadata: Option<BranchMetadata>,
+    /// Live process that registered the worktree, if any.
+    pub active_pid: Option<u32>,
+    /// Ref that ahead/behind counts are measured against.
+    pub base: Option<String>,
+    pub ahead: Option<u32>,
+    pub behind: Option<u32>,
+    pub dirty: bool,
+    pub disk_bytes: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl ManagedWorktree {
+    pub fn owner(&self) -> Option<&WorktreeOwner> {
+        self.metadata.as_ref().and_then(|meta| meta.owner.as_ref())
+    }
+
+    pub fn owner_label(&self) -> String {
+        match (self.owner(), self.active_pid) {
+            (Some(owner), Some(pid)) => format!("{owner}, running as pid {pid}"),
+            (Some(owner), None) => owner.to_string(),
+            (None, Some(pid)) => format!("pid {pid}"),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+
+    /// Reasons removing this worktree could lose work or disturb a running
+    /// session. Empty when pruning is safe. `force` accepts losing work but
+    /// never removes a worktree another process is running in.
+    pub fn prune_blockers(&self, force: bool) -> Vec<String> {
+        let mut blockers = Vec::new();
+        if let Some(pid) = self.active_pid {
+            blockers.push(format!("in use by pid {pid}"));
+        }
+        if force {
+            return blockers;
+        }
+        if self.dirty {
+            blockers.push("uncommitted changes".to_string());
+        }
+        match (self.base.as_deref(), self.ahead) {
+            (Some(base), Some(ahead)) if ahead > 0 => {
+                let noun = if ahead == 1 { "commit" } else { "commits" };
+                blockers.push(format!("{ahead} {noun} not merged into {base}"));
+            }
+            (Some(_), Some(_)) => {}
+            (Some(base), None) => {
+                blockers.push(format!("cannot verify commits are merged into {base}"));
+            }
+            (None, _) => {
+                blockers.push("cannot verify commits are merged (no base branch)".to_string());
+            }
+        }
+        blockers
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruneOutcome {
+    Removed { bytes: u64 },
+    Skipped { reasons: Vec<String> },
+    Failed(String),
+}
+
+/// Root holding every repository's worktrees, matching
+/// [`git_worktree::setup_worktree`].
+fn working_root() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".magik")
+        .join("working")
+}
+
+/// Every worktree under the working root, sorted by repository then name.
+pub async fn list_worktrees() -> Vec<ManagedWorktree> {
+    let root = working_root();
+    let active = active_worktree_pids(&root.join("_session"));
+    let mut worktrees = Vec::new();
+    for (repo_name, path) in worktree_dirs(&root) {
+        worktrees.push(inspect_worktree(repo_name, path, &active).await);
+    }
+    worktrees
+}
+
+/// Look a worktree up by directory name, branch name or path.
+pub async fn find_worktree(query: &str) -> Result<ManagedWorktree, String> {
+    let worktrees = list_worktrees().await;
+    let query_path = fs::canonicalize(query).ok();
+    let mut matches: Vec<ManagedWorktree> = worktrees
+        .into_iter()
+        .filter(|wt| {
+            wt.name == query
+                || wt.branch.as_deref() == Some(query)
+                || query_path
+                    .as_deref()
+                    .is_some_and(|path| fs::canonicalize(&wt.path).is_ok_and(|canon| canon == path))
+        })
+        .collect();
+    match matches.len() {
+        0 => Err(format!("No Code worktree named `{query}`.")),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let repos: Vec<String> = matches.iter().map(|wt| wt.repo_name.clone()).collect();
+            Err(format!(
+                "`{query}` matches worktrees in several repositories ({}); pass the path instead.",
+                repos.join(", ")
+            ))
+        }
+    }
+}
+
+/// Remove a worktree, its metadata and registry entries, and delete its
+/// branch when it is fully merged.
+pub async fn prune_worktree(worktree: &ManagedWorktree, force: bool) -> PruneOutcome {
+    let reasons = worktree.prune_blockers(force);
+    if !reasons.is_empty() {
+        return PruneOutcome::Skipped { reasons };
+    }
+
+    let path = worktree.path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        housekeeping::run_git_worktree_remove(&path);
+        match fs::remove_dir_all(&path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    })
+    .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => return PruneOutcome::Failed(err.to_string()),
+        Err(err) => return PruneOutcome::Failed(err.to_string()),
+    }
+
+    git_worktree::remove_branch_metadata(&worktree.path);
+    housekeeping::purge_session_registry(&working_root().join("_session"), &worktree.path);
+    if let (Some(repo_root), Some(branch)) = (&worktree.repo_root, &worktree.branch)
+        && housekeeping::should_prune_worktree_branch(branch)
+    {
+        housekeeping::run_git_branch_delete_if_merged(repo_root, branch);
+    }
+    let repo_dir = worktree.path.parent().and_then(Path::parent);
+    if let Some(branches_dir) = worktree.path.parent()
+        && fs::read_dir(branches_dir).is_ok_and(|mut entries| entries.next().is_none())
+    {
+        let _ = fs::remove_dir(branches_dir);
+        if let Some(repo_dir) = repo_dir {
+            let _ = fs::remove_dir(repo_dir);
+        }
+    }
+    PruneOutcome::Removed {
+        bytes: worktree.disk_bytes,
+    }
+}
+
+/// Changes in the worktree relative to where it diverged from its base,
+/// including uncommitted edits to tracked files.
+pub async fn worktree_diff(worktree: &ManagedWorktree, stat: bool) -> Result<String, String> {
+    let mut args = vec!["diff".to_string()];
+    if stat {
+        args.push("--stat".to_string());
+    }
+    if let Some(base) = &worktree.base
+        && let Some(merge_base) = git_stdout(&worktree.path, &["merge-base", base, "HEAD"]).await
+    {
+        args.push(merge_base);
+    } else {
+        args.push("HEAD".to_string());
+    }
+    let output = Command::new("git")
+        .current_dir(&worktree.path)
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git diff: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git diff failed: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn worktree_dirs(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut dirs = Vec::new();
+    let Ok(repos) = fs::read_dir(root) else {
+        return dirs;
+    };
+    let mut repos: Vec<fs::DirEntry> = repos.flatten().collect();
+    repos.sort_by_key(fs::DirEntry::file_name);
+    for repo in repos {
+        let repo_name = repo.file_name().to_string_lossy().into_owned();
+        if repo_name.starts_with('_') {
+            continue;
+        }
+        let Ok(branches) = fs::read_dir(repo.path().join("branches")) else {
+            continue;
+        };
+        let mut branches: Vec<fs::DirEntry> = branches
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+            .collect();
+        branches.sort_by_key(fs::DirEntry::file_name);
+        dirs.extend(
+            branches
+                .into_iter()
+                .map(|entry| (repo_name.clone(), entry.path())),
+        );
+    }
+    dirs
+}
+
+/// Map each registered worktree to the live process that registered it.
+fn active_worktree_pids(session_dir: &Path) -> HashMap<PathBuf, u32> {
+    let mut active = HashMap::new();
+    let Ok(entries) = fs::read_dir(session_dir) else {
+        return active;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        if !housekeeping::pid_file_is_active(&file_name).unwrap_or(false) {
+            continue;
+        }
+        let Some(pid) = file_name
+            .to_string_lossy()
+            .strip_prefix("pid-")
+            .and_then(|rest| rest.trim_end_matches(".txt").parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(data) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        for line in data.lines() {
+            if let Some((_, path)) = line.split_once('\t') {
+                let path = PathBuf::from(path);
+                active.insert(fs::canonicalize(&path).unwrap_or(path), pid);
+            }
+        }
+    }
+    active
+}
+
+async fn inspect_worktree(
+    repo_name: String,
+    path: PathBuf,
+    active: &HashMap<PathBuf, u32>,
+) -> ManagedWorktree {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    let active_pid = active.get(&canonical).copied();
+    let metadata = git_worktree::load_branch_metadata(&path);
+    let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+
+    let scan_path = path.clone();
+    let (repo_root, disk_bytes) = tokio::task::spawn_blocking(move || {
+        (
+            housekeeping::detect_repo_root(&scan_path),
+            housekeeping::directory_stats(&scan_path).bytes,
+        )
+    })
+    .await
+    .unwrap_or((None, 0));
+
+    let branch = git_stdout(&path, &["symbolic-ref", "--quiet", "--short", "HEAD"]).await;
+    let dirty = git_stdout(&path, &["status", "--porcelain"])
+        .await
+        .is_some();
+
+    let mut base = metadata.as_ref().and_then(|meta| meta.base_branch.clone());
+    if base.is_none()
+        && let Some(repo_root) = &repo_root
+    {
+        base = git_worktree::detect_default_branch(repo_root).await;
+    }
+    let (behind, ahead) = match &base {
+        Some(base) => ahead_behind(&path, base).await,
+        None => (None, None),
+    };
+
+    ManagedWorktree {
+        name,
+        path,
+        repo_name,
+        repo_root,
+        branch,
+        metadata,
+        active_pid,
+        base,
+        ahead,
+        behind,
+        dirty,
+        disk_bytes,
+        modified,
+    }
+}
+
+/// `(behind, ahead)` of `HEAD` relative to `base`.
+async fn ahead_behind(worktree: &Path, base: &str) -> (Option<u32>, Option<u32>) {
+    let range = format!("{base}...HEAD");
+    let Some(counts) = git_stdout(worktree, &["rev-list", "--left-right", "--count", &range]).await
+    else {
+        return (None, None);
+    };
+    parse_left_right(&counts)
+}
+
+fn parse_left_right(counts: &str) -> (Option<u32>, Option<u32>) {
+    let mut parts = counts
+        .split_whitespace()
+        .map(|part| part.parse::<u32>().ok());
+    let behind = parts.next().flatten();
+    let ahead = parts.next().flatten();
+    (behind, ahead)
+}
+
+/// Trimmed stdout of a successful git command; `None` on failure or when
+/// the output is empty.
+async fn git_stdout(cwd: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(cwd)
+        .args(args)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Human-readable byte count for listings.
+pub fn format_disk_usage(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn worktree() -> ManagedWorktree {
+        ManagedWorktree {
+            name: "code-branch-fix".to_string(),
+            path: PathBuf::from("/tmp/code-branch-fix"),
+            repo_name: "repo".to_string(),
+            repo_root: None,
+            branch: Some("code-branch-fix".to_string()),
+            metadata: None,
+            active_pid: None,
+            base: Some("main".to_string()),
+            ahead: Some(0),
+            behind: Some(3),
+            dirty: false,
+            disk_bytes: 0,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn blockers_cover_activity_changes_and_unmerged_commits() {
+        assert!(worktree().prune_blockers(false).is_empty());
+
+        let mut wt = worktree();
+        wt.active_pid = Some(42);
+        wt.dirty = true;
+        wt.ahead = Some(2);
+        assert_eq!(
+            wt.prune_blockers(false),
+            vec![
+                "in use by pid 42".to_string(),
+                "uncommitted changes".to_string(),
+                "2 commits not merged into main".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_base_or_ahead_count_blocks_pruning() {
+        let mut wt = worktree();
+        wt.ahead = None;
+        assert_eq!(
+            wt.prune_blockers(false),
+            vec!["cannot verify commits are merged into main".to_string()]
+        );
+
+        wt.base = None;
+        assert_eq!(
+            wt.prune_blockers(false),
+            vec!["cannot verify commits are merged (no base branch)".to_string()]
+        );
+        assert!(wt.prune_blockers(true).is_empty());
+    }
+
+    #[tokio::test]
+    async fn force_still_skips_worktrees_in_use() {
+        let mut wt = worktree();
+        wt.active_pid = Some(7);
+        wt.dirty = true;
+        assert_eq!(
+            prune_worktree(&wt, true).await,
+            PruneOutcome::Skipped {
+                reasons: vec!["in use by pid 7".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn parses_rev_list_counts_and_formats_sizes() {
+        assert_eq!(parse_left_right("3\t1"), (Some(3), Some(1)));
+        assert_eq!(parse_left_right("garbage"), (None, None));
+        assert_eq!(format_disk_usage(512), "512 B");
+        assert_eq!(format_disk_usage(3 * 1024 * 1024), "3.0 MB");
+    }
+
+    #[test]
+    fn owner_metadata_round_trips() {
+        let meta = BranchMetadata {
+            owner: Some(WorktreeOwner::Agent {
+                agent_id: "a1".to_string(),
+                model: "claude".to_string(),
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&meta).expect("serialize");
+        assert_eq!(
+            json,
+            r#"{"owner":{"kind":"agent","agent_id":"a1","model":"claude"}}"#
+        );
+        let parsed: BranchMetadata = serde_json::from_str(&json).expect("parse");
+        assert_eq!(parsed.owner, meta.owner);
+    }
+}
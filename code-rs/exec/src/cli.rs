@@ -31,6 +31,9 @@ pub struct Cli {
     #[arg(long, short = 'm')]
     pub model: Option<String>,
 
+    /// Use the local open source model provider; verifies a local inference
+    /// server (Ollama, LM Studio, llama.cpp, vLLM or any OpenAI-compatible
+    /// server) is running.
     #[arg(long = "oss", default_value_t = false)]
     pub oss: bool,
 
@@ -119,6 +122,39 @@ pub struct Cli {
     #[arg(long = "review-output-json", value_name = "FILE")]
     pub review_output_json: Option<PathBuf>,
 
+    /// When running /review, write the final pass's findings as a SARIF 2.1.0
+    /// log to this file (for code-scanning uploads).
+    #[arg(long = "review-output-sarif", value_name = "FILE")]
+    pub review_output_sarif: Option<PathBuf>,
+
+    /// When running /review, write the final pass's findings as pull-request
+    /// review comments (summary body plus comments keyed by path and line).
+    #[arg(long = "review-output-comments", value_name = "FILE")]
+    pub review_output_comments: Option<PathBuf>,
+
+    /// Run /review over only the staged changes.
+    #[arg(long = "review-staged", default_value_t = false, conflicts_with = "auto_drive")]
+    pub review_staged: bool,
+
+    /// Run /review over a commit range (`A..B`, `A..` for through HEAD, or
+    /// `N` for the last N commits).
+    #[arg(
+        long = "review-range",
+        value_name = "RANGE",
+        conflicts_with_all = ["review_staged", "auto_drive"]
+    )]
+    pub review_range: Option<String>,
+
+    /// Restrict /review to these paths or globs (comma separated). Without
+    /// --review-staged or --review-range, narrows the uncommitted changes.
+    #[arg(
+        long = "review-paths",
+        value_name = "PATHS",
+        value_delimiter = ',',
+        conflicts_with = "auto_drive"
+    )]
+    pub review_paths: Vec<String>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT")]
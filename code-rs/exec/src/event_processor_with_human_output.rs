@@ -229,6 +229,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::BrowserSnapshot(_)
             | EventMsg::ListCustomPromptsResponse(_)
             | EventMsg::ListSkillsResponse(_)
+            | EventMsg::ContextInspection(_)
+            | EventMsg::ThreadRolledBack(_)
             | EventMsg::McpListToolsResponse(_)
             | EventMsg::ViewImageToolCall(_) => {
                 // Environment context events are consumed by the TUI; the CLI runner
@@ -11,9 +11,7 @@ use code_auto_drive_core::AutoCoordinatorEvent;
 use code_auto_drive_core::AutoCoordinatorEventSender;
 use code_auto_drive_core::AutoCoordinatorStatus;
 use code_auto_drive_core::AutoDriveHistory;
-use code_auto_drive_core::AutoTurnAgentsAction;
-use code_auto_drive_core::AutoTurnAgentsTiming;
-use code_auto_drive_core::AutoTurnCliAction;
+use code_auto_drive_core::build_auto_prompt;
 use code_auto_drive_core::MODEL_SLUG;
 use code_core::AuthManager;
 use code_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
@@ -42,8 +40,13 @@ use code_core::protocol::Op;
 use code_core::protocol::ReviewOutputEvent;
 use code_core::protocol::ReviewRequest;
 use code_core::protocol::TaskCompleteEvent;
+use code_core::review_export::{review_failure_to_sarif, review_to_comments, review_to_sarif};
+use code_core::review_prompts::{
+    parse_commit_range, parse_review_paths, restrict_to_paths, review_request_for_target,
+};
 use code_protocol::models::ContentItem;
 use code_protocol::models::ResponseItem;
+use code_protocol::protocol::ReviewTarget;
 use code_protocol::protocol::SessionSource;
 use code_ollama::DEFAULT_OSS_MODEL;
 use code_protocol::config_types::SandboxMode;
@@ -124,9 +127,23 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         max_seconds,
         turn_cap,
         review_output_json,
+        review_output_sarif,
+        review_output_comments,
+        review_staged,
+        review_range,
+        review_paths,
         ..
     } = cli;
 
+    let review_target =
+        match review_target_from_cli(review_staged, review_range.as_deref(), &review_paths) {
+            Ok(target) => target,
+            Err(msg) => {
+                eprintln!("{msg}");
+                std::process::exit(1);
+            }
+        };
+
     let run_deadline = max_seconds.map(|seconds| Instant::now() + Duration::from_secs(seconds));
     let run_deadline_std = run_deadline.map(|deadline| deadline.into_std());
 
@@ -140,6 +157,8 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
 
     let prompt = match prompt_arg {
         Some(p) if p != "-" => p,
+        // Review flags fully describe the run; extra focus text is optional.
+        None if review_target.is_some() => String::new(),
         // Either `-` was passed or no positional arg.
         maybe_dash => {
             // When no arg (None) **and** stdin is a TTY, bail out early – unless the
@@ -249,8 +268,9 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
     };
 
     // When using `--oss`, let the bootstrapper pick the model (defaulting to
-    // gpt-oss:20b) and ensure it is present locally. Also, force the built‑in
-    // `oss` model provider.
+    // gpt-oss:20b, or the first model a non-Ollama server is serving) and
+    // ensure it is present locally. Also, force the built‑in `oss` provider.
+    let model_explicit = model_cli_arg.is_some();
     let model = if let Some(model) = model_cli_arg {
         Some(model)
     } else if oss {
@@ -301,6 +321,20 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
     };
 
     let mut config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    if oss {
+        let selection = code_ollama::ensure_oss_ready(&config, model_explicit)
+            .await
+            .map_err(|e| anyhow::anyhow!("OSS setup failed: {e}"))?;
+        code_ollama::apply_oss_selection(&mut config, &selection);
+    }
+    // Flushes traces, metrics and log events when dropped at the end of the run.
+    let _otel = match code_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION")) {
+        Ok(provider) => provider,
+        Err(err) => {
+            tracing::warn!("failed to initialize OpenTelemetry export: {err}");
+            None
+        }
+    };
     config.max_run_seconds = max_seconds;
     config.max_run_deadline = run_deadline_std;
     config.demo_developer_message = cli.demo_developer_message.clone();
@@ -323,23 +357,38 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         subagent_commands: &config.subagent_commands,
     };
 
-    match process_exec_slash_command(prompt_to_send.trim(), slash_context) {
-        Ok(SlashDispatch::NotSlash) => {}
-        Ok(SlashDispatch::ExpandedPrompt { prompt, summary }) => {
-            prompt_to_send = prompt;
-            if auto_drive_goal.is_none() {
-                summary_prompt = summary;
-            }
+    if let Some(target) = review_target {
+        let mut request = review_request_for_target(target);
+        let focus = prompt_to_send.trim();
+        let focus = focus.strip_prefix("/review").unwrap_or(focus).trim();
+        if !focus.is_empty() {
+            request.prompt.push_str("\n\nAdditional focus: ");
+            request.prompt.push_str(focus);
         }
-        Ok(SlashDispatch::Review { request, summary }) => {
-            review_request = Some(request);
-            if auto_drive_goal.is_none() {
-                summary_prompt = summary;
+        summary_prompt = match request.user_facing_hint.as_deref() {
+            Some(hint) => format!("/review {hint}"),
+            None => "/review".to_string(),
+        };
+        review_request = Some(request);
+    } else {
+        match process_exec_slash_command(prompt_to_send.trim(), slash_context) {
+            Ok(SlashDispatch::NotSlash) => {}
+            Ok(SlashDispatch::ExpandedPrompt { prompt, summary }) => {
+                prompt_to_send = prompt;
+                if auto_drive_goal.is_none() {
+                    summary_prompt = summary;
+                }
+            }
+            Ok(SlashDispatch::Review { request, summary }) => {
+                review_request = Some(request);
+                if auto_drive_goal.is_none() {
+                    summary_prompt = summary;
+                }
+            }
+            Err(msg) => {
+                eprintln!("{msg}");
+                std::process::exit(1);
             }
-        }
-        Err(msg) => {
-            eprintln!("{msg}");
-            std::process::exit(1);
         }
     }
 
@@ -455,12 +504,6 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         ))
     };
 
-    if oss {
-        code_ollama::ensure_oss_ready(&config)
-            .await
-            .map_err(|e| anyhow::anyhow!("OSS setup failed: {e}"))?;
-    }
-
     // Print the effective configuration and prompt so users can see what Codex
     // is using.
     let default_cwd = config.cwd.to_path_buf();
@@ -1174,6 +1217,48 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
             let _ = write_review_json(path, &review_outputs, final_review_snapshot.as_ref());
         }
     }
+    if review_output_sarif.is_some() || review_output_comments.is_some() {
+        let repo_root = get_git_repo_root(&config.cwd);
+        match review_outputs.last() {
+            Some(latest) => {
+                if let Some(path) = review_output_sarif {
+                    let sarif =
+                        review_to_sarif(latest, repo_root.as_deref(), env!("CARGO_PKG_VERSION"));
+                    if let Err(err) = write_json_file(&path, &sarif) {
+                        eprintln!(
+                            "Failed to write SARIF review output to {}: {err}",
+                            path.display()
+                        );
+                    }
+                }
+                if let Some(path) = review_output_comments {
+                    let comments = review_to_comments(latest, repo_root.as_deref());
+                    if let Err(err) = write_json_file(&path, &comments) {
+                        eprintln!("Failed to write review comments to {}: {err}", path.display());
+                    }
+                }
+            }
+            None => {
+                // A review without output must not look like a clean one: the
+                // SARIF log records a failed invocation, no comments are
+                // written, and the run exits nonzero.
+                const NO_OUTPUT: &str = "the review finished without producing output";
+                error_seen = true;
+                if let Some(path) = review_output_sarif {
+                    let sarif = review_failure_to_sarif(NO_OUTPUT, env!("CARGO_PKG_VERSION"));
+                    if let Err(err) = write_json_file(&path, &sarif) {
+                        eprintln!(
+                            "Failed to write SARIF review output to {}: {err}",
+                            path.display()
+                        );
+                    }
+                }
+                if let Some(path) = review_output_comments {
+                    eprintln!("Not writing review comments to {}: {NO_OUTPUT}", path.display());
+                }
+            }
+        }
+    }
     if review_runs > 0 {
         eprintln!("Review runs: {} (auto_resolve={} max_attempts={})", review_runs, config.tui.review_auto_resolve, max_auto_resolve_attempts);
     }
@@ -1977,75 +2062,6 @@ fn shutdown_state_after_request(
     (true, true, None)
 }
 
-fn build_auto_prompt(
-    cli_action: &AutoTurnCliAction,
-    agents: &[AutoTurnAgentsAction],
-    agents_timing: Option<AutoTurnAgentsTiming>,
-) -> String {
-    let mut sections: Vec<String> = Vec::new();
-
-    if let Some(ctx) = cli_action
-        .context
-        .as_deref()
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-    {
-        sections.push(ctx.to_string());
-    }
-
-    let cli_prompt = cli_action.prompt.trim();
-    if !cli_prompt.is_empty() {
-        sections.push(cli_prompt.to_string());
-    }
-
-    if !agents.is_empty() {
-        let mut lines: Vec<String> = Vec::new();
-        lines.push("<agents>".to_string());
-        lines.push("Please use agents to help you complete this task.".to_string());
-
-        for action in agents {
-            let prompt = action
-                .prompt
-                .trim()
-                .replace('\n', " ")
-                .replace('"', "\\\"");
-            let write_text = if action.write { "write: true" } else { "write: false" };
-
-            lines.push(String::new());
-            lines.push(format!("prompt: \"{prompt}\" ({write_text})"));
-
-            if let Some(ctx) = action
-                .context
-                .as_deref()
-                .map(str::trim)
-                .filter(|s| !s.is_empty())
-            {
-                lines.push(format!("context: {}", ctx.replace('\n', " ")));
-            }
-
-            if let Some(models) = action.models.as_ref().filter(|list| !list.is_empty()) {
-                lines.push(format!("models: {}", models.join(", ")));
-            }
-        }
-
-        let timing_line = match agents_timing {
-            Some(AutoTurnAgentsTiming::Parallel) =>
-                "Timing: parallel — continue the CLI prompt while agents run; call agent.wait when ready to merge results.".to_string(),
-            Some(AutoTurnAgentsTiming::Blocking) =>
-                "Timing: blocking — launch agents first, wait with agent.wait, then continue the CLI prompt.".to_string(),
-            None =>
-                "Timing: blocking — wait for agent.wait before continuing the CLI prompt.".to_string(),
-        };
-        lines.push(String::new());
-        lines.push(timing_line);
-        lines.push("</agents>".to_string());
-
-        sections.push(lines.join("\n"));
-    }
-
-    sections.join("\n\n")
-}
-
 async fn dispatch_auto_fix(
     conversation: &Arc<CodexConversation>,
     review: &code_core::protocol::ReviewOutputEvent,
@@ -2103,6 +2119,26 @@ fn snapshot_parent_diff_paths(cwd: &Path, parent: &str, head: &str) -> Option<Ve
     Some(paths)
 }
 
+/// Resolve `--review-staged`, `--review-range`, and `--review-paths` into a
+/// review target. Returns `None` when none of the flags were given.
+fn review_target_from_cli(
+    staged: bool,
+    range: Option<&str>,
+    paths: &[String],
+) -> Result<Option<ReviewTarget>, String> {
+    let scope = match range {
+        Some(range) => Some(parse_commit_range(range).map_err(|err| format!("--review-range: {err}"))?),
+        None if staged => Some(ReviewTarget::StagedChanges),
+        None => None,
+    };
+    let paths: Vec<String> = paths.iter().flat_map(|spec| parse_review_paths(spec)).collect();
+    if scope.is_none() && paths.is_empty() {
+        return Ok(None);
+    }
+    let scope = scope.unwrap_or(ReviewTarget::UncommittedChanges);
+    Ok(Some(restrict_to_paths(scope, paths)))
+}
+
 fn apply_commit_scope_to_review_request(
     mut request: ReviewRequest,
     commit: &str,
@@ -2420,6 +2456,12 @@ fn write_review_json(
     std::fs::write(path, json)
 }
 
+fn write_json_file<T: serde::Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    std::fs::write(path, json)
+}
+
 async fn submit_and_wait(
     conversation: &Arc<CodexConversation>,
     event_processor: &mut dyn EventProcessor,
@@ -2546,6 +2588,34 @@ mod tests {
 	    use tempfile::TempDir;
 	    use uuid::Uuid;
 
+	    #[test]
+	    fn review_flags_build_path_filtered_target() {
+	        assert_eq!(review_target_from_cli(false, None, &[]), Ok(None));
+
+	        let target = review_target_from_cli(false, Some("2"), &["src,docs/*.md".to_string()])
+	            .expect("valid flags")
+	            .expect("target");
+	        assert_eq!(
+	            target,
+	            ReviewTarget::Paths {
+	                paths: vec!["src".to_string(), "docs/*.md".to_string()],
+	                scope: Box::new(ReviewTarget::CommitRange {
+	                    from: "HEAD~2".to_string(),
+	                    to: "HEAD".to_string(),
+	                }),
+	            }
+	        );
+
+	        let staged_paths = review_target_from_cli(true, None, &["Cargo.toml".to_string()])
+	            .expect("valid flags");
+	        assert!(matches!(
+	            staged_paths,
+	            Some(ReviewTarget::Paths { ref scope, .. }) if **scope == ReviewTarget::StagedChanges
+	        ));
+
+	        assert!(review_target_from_cli(false, Some("main"), &[]).is_err());
+	    }
+
 	    #[test]
 	    fn shutdown_state_schedules_grace_on_first_request() {
 	        let now = Instant::now();
@@ -12,8 +12,10 @@ use crate::operations::normalize_relative_path;
 use crate::operations::repo_subdir;
 use crate::operations::resolve_head;
 use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_raw_stdout;
 use crate::operations::run_git_for_status;
 use crate::operations::run_git_for_stdout;
+use crate::operations::run_git_with_input;
 
 /// Default commit message used for ghost commits when none is provided.
 const DEFAULT_COMMIT_MESSAGE: &str = "code snapshot";
@@ -185,6 +187,328 @@ pub fn restore_to_commit(repo_path: &Path, commit_id: &str) -> Result<(), GitToo
     Ok(())
 }
 
+/// How a file changed between a snapshot and the current working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFileStatus {
+    /// The file did not exist in the snapshot.
+    Added,
+    Modified,
+    /// The file existed in the snapshot and is gone now.
+    Deleted,
+}
+
+/// One file's changes since a snapshot, split into hunks so callers can
+/// restore part of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotFileDiff {
+    /// Path relative to the repository root.
+    pub path: PathBuf,
+    pub status: SnapshotFileStatus,
+    /// `diff --git` header lines up to the first hunk.
+    pub header: String,
+    /// Hunks in order, each starting with its `@@` line. Empty for binary
+    /// files and mode-only changes.
+    pub hunks: Vec<String>,
+    pub binary: bool,
+}
+
+impl SnapshotFileDiff {
+    /// Full unified diff from the snapshot to the current contents.
+    pub fn patch(&self) -> String {
+        let mut patch = self.header.clone();
+        for hunk in &self.hunks {
+            patch.push_str(hunk);
+        }
+        patch
+    }
+
+    /// Lines added and removed since the snapshot.
+    pub fn line_counts(&self) -> (usize, usize) {
+        let mut added = 0;
+        let mut removed = 0;
+        for line in self.hunks.iter().flat_map(|hunk| hunk.lines().skip(1)) {
+            if line.starts_with('+') {
+                added += 1;
+            } else if line.starts_with('-') {
+                removed += 1;
+            }
+        }
+        (added, removed)
+    }
+}
+
+/// Diff the snapshot `commit_id` against the current working tree, including
+/// untracked files, one entry per changed file.
+pub fn diff_snapshot_against_worktree(
+    repo_path: &Path,
+    commit_id: &str,
+) -> Result<Vec<SnapshotFileDiff>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let repo_prefix = repo_subdir(repo_root.as_path(), repo_path);
+    // Snapshot the current state the same way so untracked files take part
+    // in the comparison.
+    let current = create_ghost_commit(&CreateGhostCommitOptions::new(repo_path))?;
+
+    let mut args = vec![
+        OsString::from("-c"),
+        OsString::from("core.quotePath=false"),
+        OsString::from("diff"),
+        OsString::from("--no-color"),
+        OsString::from("--no-ext-diff"),
+        OsString::from("--no-renames"),
+        OsString::from(commit_id),
+        OsString::from(current.id()),
+        OsString::from("--"),
+    ];
+    if let Some(prefix) = repo_prefix.as_deref() {
+        args.push(prefix.as_os_str().to_os_string());
+    }
+    let diff = run_git_for_raw_stdout(repo_root.as_path(), args, None)?;
+    Ok(parse_snapshot_diff(&diff))
+}
+
+/// Restore whole files from a snapshot and leave every other path alone.
+/// Files that did not exist in the snapshot are removed.
+pub fn restore_files_from_commit(
+    repo_path: &Path,
+    commit_id: &str,
+    paths: &[PathBuf],
+) -> Result<(), GitToolingError> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    ensure_git_repository(repo_path)?;
+    let repo_root = resolve_repository_root(repo_path)?;
+    let paths = paths
+        .iter()
+        .map(|path| normalize_relative_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut ls_args = vec![
+        OsString::from("ls-tree"),
+        OsString::from("-r"),
+        OsString::from("-z"),
+        OsString::from("--name-only"),
+        OsString::from(commit_id),
+        OsString::from("--"),
+    ];
+    ls_args.extend(paths.iter().map(|path| path.as_os_str().to_os_string()));
+    let listed = run_git_for_raw_stdout(repo_root.as_path(), ls_args, None)?;
+    let in_snapshot: Vec<PathBuf> = listed
+        .split('\0')
+        .filter(|entry| !entry.is_empty())
+        .map(PathBuf::from)
+        .collect();
+
+    let (present, absent): (Vec<PathBuf>, Vec<PathBuf>) = paths
+        .into_iter()
+        .partition(|path| in_snapshot.contains(path));
+
+    if !present.is_empty() {
+        let mut restore_args = vec![
+            OsString::from("restore"),
+            OsString::from("--source"),
+            OsString::from(commit_id),
+            OsString::from("--worktree"),
+            OsString::from("--staged"),
+            OsString::from("--"),
+        ];
+        restore_args.extend(present.iter().map(|path| path.as_os_str().to_os_string()));
+        run_git_for_status(repo_root.as_path(), restore_args, None)?;
+    }
+
+    if !absent.is_empty() {
+        let mut rm_args = vec![
+            OsString::from("rm"),
+            OsString::from("--cached"),
+            OsString::from("--ignore-unmatch"),
+            OsString::from("--quiet"),
+            OsString::from("--"),
+        ];
+        rm_args.extend(absent.iter().map(|path| path.as_os_str().to_os_string()));
+        run_git_for_status(repo_root.as_path(), rm_args, None)?;
+        for path in &absent {
+            match std::fs::remove_file(repo_root.join(path)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Undo the selected hunks of `file` in the working tree, putting those
+/// regions back to their snapshot contents.
+pub fn restore_hunks_from_commit(
+    repo_path: &Path,
+    file: &SnapshotFileDiff,
+    hunk_indexes: &[usize],
+) -> Result<(), GitToolingError> {
+    let mut patch = file.header.clone();
+    let mut selected = 0;
+    for (idx, hunk) in file.hunks.iter().enumerate() {
+        if hunk_indexes.contains(&idx) {
+            patch.push_str(hunk);
+            selected += 1;
+        }
+    }
+    if selected == 0 {
+        return Ok(());
+    }
+    ensure_git_repository(repo_path)?;
+    let repo_root = resolve_repository_root(repo_path)?;
+    run_git_with_input(
+        repo_root.as_path(),
+        [
+            OsString::from("apply"),
+            OsString::from("--reverse"),
+            OsString::from("--whitespace=nowarn"),
+            OsString::from("-"),
+        ],
+        patch.as_bytes(),
+    )
+}
+
+/// Namespace for refs that keep ghost commits reachable once the session that
+/// created them exits, so they survive `git gc` and can be restored after a
+/// resume.
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/magik/snapshots/";
+
+/// A persisted snapshot, stored as `refs/magik/snapshots/<session>/<commit>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRef {
+    /// Full ref name.
+    pub name: String,
+    pub session: String,
+    pub commit: GhostCommit,
+    /// Commit time of the snapshot in seconds since the Unix epoch.
+    pub created_at: i64,
+}
+
+/// Keep `commit` reachable under the snapshot namespace for `session`.
+pub fn save_snapshot_ref(
+    repo_path: &Path,
+    session: &str,
+    commit: &GhostCommit,
+) -> Result<(), GitToolingError> {
+    ensure_git_repository(repo_path)?;
+    let name = format!("{SNAPSHOT_REF_PREFIX}{session}/{}", commit.id());
+    run_git_for_status(
+        repo_path,
+        [
+            OsString::from("update-ref"),
+            OsString::from(name),
+            OsString::from(commit.id()),
+        ],
+        None,
+    )
+}
+
+/// Every persisted snapshot in the repository, oldest first.
+pub fn list_snapshot_refs(repo_path: &Path) -> Result<Vec<SnapshotRef>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+    let output = run_git_for_stdout(
+        repo_path,
+        [
+            "for-each-ref",
+            "--sort=committerdate",
+            "--format=%(refname)%09%(objectname)%09%(parent)%09%(committerdate:unix)",
+            SNAPSHOT_REF_PREFIX,
+        ],
+        None,
+    )?;
+    let mut refs = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(name), Some(id), Some(parents), Some(created_at)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Some((session, _)) = name
+            .strip_prefix(SNAPSHOT_REF_PREFIX)
+            .and_then(|rest| rest.split_once('/'))
+        else {
+            continue;
+        };
+        let parent = parents.split_whitespace().next().map(str::to_string);
+        refs.push(SnapshotRef {
+            name: name.to_string(),
+            session: session.to_string(),
+            commit: GhostCommit::new(id.to_string(), parent),
+            created_at: created_at.trim().parse().unwrap_or_default(),
+        });
+    }
+    Ok(refs)
+}
+
+/// Delete the given snapshot refs. The commits become unreachable and are
+/// collected by a later `git gc`.
+pub fn delete_snapshot_refs(repo_path: &Path, refs: &[SnapshotRef]) -> Result<(), GitToolingError> {
+    if refs.is_empty() {
+        return Ok(());
+    }
+    ensure_git_repository(repo_path)?;
+    let mut input = String::new();
+    for snapshot in refs {
+        input.push_str(&format!("delete {}\n", snapshot.name));
+    }
+    run_git_with_input(
+        repo_path,
+        [OsString::from("update-ref"), OsString::from("--stdin")],
+        input.as_bytes(),
+    )
+}
+
+fn parse_snapshot_diff(diff: &str) -> Vec<SnapshotFileDiff> {
+    let mut files: Vec<SnapshotFileDiff> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            files.push(SnapshotFileDiff {
+                path: PathBuf::from(diff_header_path(rest.trim_end_matches(['\n', '\r']))),
+                status: SnapshotFileStatus::Modified,
+                header: line.to_string(),
+                hunks: Vec::new(),
+                binary: false,
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            file.hunks.push(line.to_string());
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.push_str(line);
+        } else {
+            if line.starts_with("new file mode") {
+                file.status = SnapshotFileStatus::Added;
+            } else if line.starts_with("deleted file mode") {
+                file.status = SnapshotFileStatus::Deleted;
+            } else if line.starts_with("Binary files ") || line.starts_with("GIT binary patch") {
+                file.binary = true;
+            }
+            file.header.push_str(line);
+        }
+    }
+    files
+}
+
+/// Extract the path from `a/<path> b/<path>`. Renames are disabled, so both
+/// sides name the same path.
+fn diff_header_path(rest: &str) -> String {
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find("\" ").unwrap_or(quoted.len());
+        let path = &quoted[..end];
+        return path.strip_prefix("a/").unwrap_or(path).to_string();
+    }
+    let len = rest.len().saturating_sub(5) / 2;
+    rest.get(2..2 + len).unwrap_or(rest).to_string()
+}
+
 /// Returns the default author and committer identity for ghost commits.
 fn default_commit_identity() -> Vec<(OsString, OsString)> {
     vec![
@@ -575,4 +899,100 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    /// Restores one hunk and one file from a snapshot while keeping the rest.
+    fn partial_restore_keeps_other_changes() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        let original: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        std::fs::write(repo.join("lines.txt"), &original)?;
+        std::fs::write(repo.join("other.txt"), "other\n")?;
+        run_git_in(repo, &["add", "lines.txt", "other.txt"]);
+        run_git_in(
+            repo,
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+        let ghost = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        let edited = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "line eighteen\n");
+        std::fs::write(repo.join("lines.txt"), &edited)?;
+        std::fs::write(repo.join("other.txt"), "other edited\n")?;
+        std::fs::write(repo.join("added.txt"), "new\n")?;
+
+        let diffs = diff_snapshot_against_worktree(repo, ghost.id())?;
+        let paths: Vec<&Path> = diffs.iter().map(|diff| diff.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("added.txt"),
+                Path::new("lines.txt"),
+                Path::new("other.txt")
+            ]
+        );
+        assert_eq!(diffs[0].status, SnapshotFileStatus::Added);
+        let lines = &diffs[1];
+        assert_eq!(lines.hunks.len(), 2);
+        assert_eq!(lines.line_counts(), (2, 2));
+
+        restore_hunks_from_commit(repo, lines, &[1])?;
+        restore_files_from_commit(repo, ghost.id(), &[PathBuf::from("added.txt")])?;
+
+        let lines_after = std::fs::read_to_string(repo.join("lines.txt"))?;
+        assert_eq!(lines_after, original.replace("line 2\n", "line two\n"));
+        assert_eq!(repo.join("added.txt").exists(), false);
+        let other_after = std::fs::read_to_string(repo.join("other.txt"))?;
+        assert_eq!(other_after, "other edited\n");
+
+        Ok(())
+    }
+
+    #[test]
+    /// Saves, lists and deletes snapshot refs.
+    fn snapshot_refs_roundtrip() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        init_test_repo(repo);
+        std::fs::write(repo.join("notes.txt"), "one\n")?;
+        let first = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+        std::fs::write(repo.join("notes.txt"), "two\n")?;
+        let second = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        save_snapshot_ref(repo, "session-a", &first)?;
+        save_snapshot_ref(repo, "session-b", &second)?;
+
+        let refs = list_snapshot_refs(repo)?;
+        let mut sessions: Vec<(&str, &GhostCommit)> = refs
+            .iter()
+            .map(|snapshot| (snapshot.session.as_str(), &snapshot.commit))
+            .collect();
+        sessions.sort_by_key(|(session, _)| *session);
+        assert_eq!(
+            sessions,
+            vec![("session-a", &first), ("session-b", &second)]
+        );
+        assert!(refs.iter().all(|snapshot| snapshot.created_at > 0));
+
+        let stale: Vec<SnapshotRef> = refs
+            .into_iter()
+            .filter(|snapshot| snapshot.session == "session-a")
+            .collect();
+        delete_snapshot_refs(repo, &stale)?;
+        let remaining = list_snapshot_refs(repo)?;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].commit, second);
+
+        Ok(())
+    }
 }
@@ -11,9 +11,19 @@ mod platform;
 
 pub use errors::GitToolingError;
 pub use ghost_commits::CreateGhostCommitOptions;
+pub use ghost_commits::SNAPSHOT_REF_PREFIX;
+pub use ghost_commits::SnapshotFileDiff;
+pub use ghost_commits::SnapshotFileStatus;
+pub use ghost_commits::SnapshotRef;
 pub use ghost_commits::create_ghost_commit;
+pub use ghost_commits::delete_snapshot_refs;
+pub use ghost_commits::diff_snapshot_against_worktree;
+pub use ghost_commits::list_snapshot_refs;
+pub use ghost_commits::restore_files_from_commit;
 pub use ghost_commits::restore_ghost_commit;
+pub use ghost_commits::restore_hunks_from_commit;
 pub use ghost_commits::restore_to_commit;
+pub use ghost_commits::save_snapshot_ref;
 pub use platform::create_symlink;
 
 /// Details of a ghost commit created from a repository state.
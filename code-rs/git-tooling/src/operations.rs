@@ -1,9 +1,11 @@
 use std::ffi::OsStr;
 use std::ffi::OsString;
+use std::io::Write;
 use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::process::Stdio;
 
 use crate::GitToolingError;
 
@@ -139,7 +141,7 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    run_git(dir, args, env)?;
+    run_git(dir, args, env, None)?;
     Ok(())
 }
 
@@ -152,19 +154,46 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let run = run_git(dir, args, env)?;
-    String::from_utf8(run.output.stdout)
-        .map(|value| value.trim().to_string())
-        .map_err(|source| GitToolingError::GitOutputUtf8 {
-            command: run.command,
-            source,
-        })
+    run_git_for_raw_stdout(dir, args, env).map(|value| value.trim().to_string())
+}
+
+/// Like [`run_git_for_stdout`] but keeps surrounding whitespace, which
+/// matters for patches.
+pub(crate) fn run_git_for_raw_stdout<I, S>(
+    dir: &Path,
+    args: I,
+    env: Option<&[(OsString, OsString)]>,
+) -> Result<String, GitToolingError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let run = run_git(dir, args, env, None)?;
+    String::from_utf8(run.output.stdout).map_err(|source| GitToolingError::GitOutputUtf8 {
+        command: run.command,
+        source,
+    })
+}
+
+/// Runs a git command that reads `input` on stdin, such as `git apply`.
+pub(crate) fn run_git_with_input<I, S>(
+    dir: &Path,
+    args: I,
+    input: &[u8],
+) -> Result<(), GitToolingError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    run_git(dir, args, None, Some(input))?;
+    Ok(())
 }
 
 fn run_git<I, S>(
     dir: &Path,
     args: I,
     env: Option<&[(OsString, OsString)]>,
+    input: Option<&[u8]>,
 ) -> Result<GitRun, GitToolingError>
 where
     I: IntoIterator<Item = S>,
@@ -185,7 +214,20 @@ where
         }
     }
     command.args(&args_vec);
-    let output = command.output()?;
+    let output = match input {
+        Some(input) => {
+            command
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            let mut child = command.spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(input)?;
+            }
+            child.wait_with_output()?
+        }
+        None => command.output()?,
+    };
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
         return Err(GitToolingError::GitCommand {
@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -46,6 +47,44 @@ pub struct ExecApprovalResponse {
     pub decision: ReviewDecision,
 }
 
+/// Fields of the elicitation form advertised in `requestedSchema`. Clients may
+/// return them flat or as the `content` of an MCP `ElicitResult`.
+#[derive(Debug, Default, Deserialize)]
+struct ExecApprovalForm {
+    decision: Option<String>,
+    command: Option<String>,
+    feedback: Option<String>,
+}
+
+fn exec_approval_requested_schema() -> ElicitRequestParamsRequestedSchema {
+    ElicitRequestParamsRequestedSchema {
+        r#type: "object".to_string(),
+        properties: json!({
+            "decision": {
+                "type": "string",
+                "enum": [
+                    "approved",
+                    "approved_for_session",
+                    "approved_with_edits",
+                    "denied",
+                    "denied_with_feedback",
+                    "abort",
+                ],
+                "description": "How to handle the command.",
+            },
+            "command": {
+                "type": "string",
+                "description": "Edited command to run instead, for approved_with_edits. For `bash -lc` style commands this is the script passed to the shell.",
+            },
+            "feedback": {
+                "type": "string",
+                "description": "Why the command was denied, for denied_with_feedback. Sent to the model.",
+            },
+        }),
+        required: Some(vec!["decision".to_string()]),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn handle_exec_approval_request(
     command: Vec<String>,
@@ -66,16 +105,12 @@ pub(crate) async fn handle_exec_approval_request(
 
     let params = ExecApprovalElicitRequestParams {
         message,
-        requested_schema: ElicitRequestParamsRequestedSchema {
-            r#type: "object".to_string(),
-            properties: json!({}),
-            required: None,
-        },
+        requested_schema: exec_approval_requested_schema(),
         code_elicitation: "exec-approval".to_string(),
         code_mcp_tool_call_id: tool_call_id.clone(),
         code_event_id: event_id.clone(),
         code_call_id: call_id.clone(),
-        code_command: command,
+        code_command: command.clone(),
         code_cwd: cwd,
     };
     let params_json = match serde_json::to_value(&params) {
@@ -109,13 +144,14 @@ pub(crate) async fn handle_exec_approval_request(
         // Correlate by call_id for core pending approvals
         let approval_id = call_id.clone();
         tokio::spawn(async move {
-            on_exec_approval_response(approval_id, on_response, codex).await;
+            on_exec_approval_response(approval_id, command, on_response, codex).await;
         });
     }
 }
 
 async fn on_exec_approval_response(
     approval_id: String,
+    command: Vec<String>,
     receiver: tokio::sync::oneshot::Receiver<mcp_types::Result>,
     codex: Arc<CodexConversation>,
 ) {
@@ -128,24 +164,206 @@ async fn on_exec_approval_response(
         }
     };
 
-    // Try to deserialize `value` and then make the appropriate call to `codex`.
-    let response = serde_json::from_value::<ExecApprovalResponse>(value).unwrap_or_else(|err| {
-        error!("failed to deserialize ExecApprovalResponse: {err}");
-        // If we cannot deserialize the response, we deny the request to be
-        // conservative.
-        ExecApprovalResponse {
-            decision: ReviewDecision::Denied,
-        }
-    });
+    let decision = decision_from_response(&command, value);
 
     if let Err(err) = codex
         .submit(Op::ExecApproval {
             id: approval_id,
             turn_id: None,
-            decision: response.decision,
+            decision,
         })
         .await
     {
         error!("failed to submit ExecApproval: {err}");
     }
 }
+
+/// Accepts the legacy `{"decision": ReviewDecision}` shape as well as the
+/// elicitation form, either flat or wrapped in an `ElicitResult`. Anything
+/// that cannot be understood, including a form without a decision, denies
+/// the command to be conservative. `original` is the command the approval
+/// was requested for; edits keep its shell wrapper.
+fn decision_from_response(original: &[String], value: serde_json::Value) -> ReviewDecision {
+    if let Ok(response) = serde_json::from_value::<ExecApprovalResponse>(value.clone()) {
+        return response.decision;
+    }
+
+    let form = match value.get("action").and_then(serde_json::Value::as_str) {
+        Some("accept") => value.get("content").cloned().unwrap_or_default(),
+        Some("decline") => return ReviewDecision::Denied,
+        Some("cancel") => return ReviewDecision::Abort,
+        Some(other) => {
+            error!("unexpected elicitation action: {other}");
+            return ReviewDecision::Denied;
+        }
+        None => value,
+    };
+    let form = match serde_json::from_value::<ExecApprovalForm>(form) {
+        Ok(form) => form,
+        Err(err) => {
+            error!("failed to deserialize exec approval response: {err}");
+            return ReviewDecision::Denied;
+        }
+    };
+
+    let non_empty = |text: Option<String>| text.filter(|text| !text.trim().is_empty());
+    let Some(decision) = form.decision else {
+        error!("exec approval response has no decision; denying");
+        return ReviewDecision::Denied;
+    };
+    match decision.as_str() {
+        "approved" => ReviewDecision::Approved,
+        "approved_for_session" => ReviewDecision::ApprovedForSession,
+        "approved_with_edits" => match non_empty(form.command)
+            .and_then(|text| command_from_edited_text(original, &text))
+        {
+            Some(command) => ReviewDecision::ApprovedWithEdits { command },
+            None => {
+                error!("approved_with_edits without a usable command; denying");
+                ReviewDecision::Denied
+            }
+        },
+        "denied" => ReviewDecision::Denied,
+        "denied_with_feedback" => match non_empty(form.feedback) {
+            Some(feedback) => ReviewDecision::DeniedWithFeedback { feedback },
+            None => ReviewDecision::Denied,
+        },
+        "abort" => ReviewDecision::Abort,
+        other => {
+            error!("unknown exec approval decision: {other}");
+            ReviewDecision::Denied
+        }
+    }
+}
+
+/// Turn an edited command back into an argv shaped like `original`: the
+/// script of a `bash -lc <script>` command stays a single argument inside
+/// the same wrapper, anything else is split like a shell would.
+fn command_from_edited_text(original: &[String], text: &str) -> Option<Vec<String>> {
+    let script = text.trim();
+    if script.is_empty() {
+        return None;
+    }
+    if original.len() == 3 && is_shell_wrapper(&original[0], &original[1]) {
+        return Some(vec![
+            original[0].clone(),
+            original[1].clone(),
+            script.to_string(),
+        ]);
+    }
+    shlex::split(script).filter(|argv| !argv.is_empty())
+}
+
+fn is_shell_wrapper(shell: &str, flag: &str) -> bool {
+    let file_name = Path::new(shell)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or(shell)
+        .to_ascii_lowercase();
+    matches!(file_name.as_str(), "bash" | "sh" | "zsh") && matches!(flag, "-lc" | "-c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_legacy_and_form_responses() {
+        assert_eq!(
+            decision_from_response(
+                &argv(&["git", "status"]),
+                json!({ "decision": "approved_for_session" })
+            ),
+            ReviewDecision::ApprovedForSession
+        );
+        assert_eq!(
+            decision_from_response(
+                &argv(&["git", "status"]),
+                json!({
+                    "decision": { "approved_with_edits": { "command": ["ls", "-la"] } }
+                })
+            ),
+            ReviewDecision::ApprovedWithEdits {
+                command: vec!["ls".to_string(), "-la".to_string()]
+            }
+        );
+        assert_eq!(
+            decision_from_response(
+                &argv(&["git", "status"]),
+                json!({
+                    "action": "accept",
+                    "content": { "decision": "approved_with_edits", "command": "git status --short" }
+                })
+            ),
+            ReviewDecision::ApprovedWithEdits {
+                command: vec![
+                    "git".to_string(),
+                    "status".to_string(),
+                    "--short".to_string()
+                ]
+            }
+        );
+        assert_eq!(
+            decision_from_response(
+                &argv(&["git", "status"]),
+                json!({
+                    "decision": "denied_with_feedback",
+                    "feedback": "use the staging database"
+                })
+            ),
+            ReviewDecision::DeniedWithFeedback {
+                feedback: "use the staging database".to_string()
+            }
+        );
+        assert_eq!(
+            decision_from_response(&argv(&["git", "status"]), json!({ "action": "decline" })),
+            ReviewDecision::Denied
+        );
+        assert_eq!(
+            decision_from_response(
+                &argv(&["git", "status"]),
+                json!({ "decision": "approved_with_edits" })
+            ),
+            ReviewDecision::Denied
+        );
+    }
+
+    #[test]
+    fn missing_or_unknown_decisions_deny() {
+        let original = argv(&["git", "status"]);
+        assert_eq!(
+            decision_from_response(&original, json!({})),
+            ReviewDecision::Denied
+        );
+        assert_eq!(
+            decision_from_response(&original, json!({ "action": "accept" })),
+            ReviewDecision::Denied
+        );
+        assert_eq!(
+            decision_from_response(&original, json!({ "decision": "sure" })),
+            ReviewDecision::Denied
+        );
+    }
+
+    #[test]
+    fn edits_keep_the_shell_wrapper() {
+        let original = argv(&["bash", "-lc", "cargo test | tail -5"]);
+        assert_eq!(
+            decision_from_response(
+                &original,
+                json!({
+                    "decision": "approved_with_edits",
+                    "command": "cargo test -p core 2>&1 | tail -20\n"
+                })
+            ),
+            ReviewDecision::ApprovedWithEdits {
+                command: argv(&["bash", "-lc", "cargo test -p core 2>&1 | tail -20"])
+            }
+        );
+    }
+}
@@ -276,9 +276,9 @@ impl OllamaClient {
 
         Ok(None)
     }
+
     /// Low-level constructor given a raw host root, e.g. "http://localhost:11434".
-    #[cfg(test)]
-    fn from_host_root(host_root: impl Into<String>) -> Self {
+    pub(crate) fn from_host_root(host_root: impl Into<String>) -> Self {
         let client = reqwest::Client::builder()
             .connect_timeout(std::time::Duration::from_secs(5))
             .build()
@@ -1,54 +1,171 @@
 mod client;
+mod local_server;
 mod parser;
 mod pull;
 mod url;
 
+use std::io;
+
 pub use client::OllamaClient;
+use code_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use code_core::config::Config;
+use code_core::model_family::derive_default_model_family;
+use code_core::model_family::find_family_for_model;
+pub use local_server::LocalModel;
+pub use local_server::LocalServer;
+pub use local_server::LocalServerKind;
+pub use local_server::discover_local_servers;
+pub use local_server::is_local_base_url;
 pub use pull::CliProgressReporter;
 pub use pull::PullEvent;
 pub use pull::PullProgressReporter;
 pub use pull::TuiProgressReporter;
+use crate::url::base_url_to_host_root;
 
 /// Default OSS model to use when `--oss` is passed without an explicit `-m`.
 pub const DEFAULT_OSS_MODEL: &str = "gpt-oss:20b";
 
+/// Server and model that `--oss` settled on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OssSelection {
+    pub kind: LocalServerKind,
+    /// OpenAI-compatible base URL of the server, e.g. `http://localhost:8080/v1`.
+    pub base_url: String,
+    pub model: String,
+    /// Context window the server reports for `model`.
+    pub context_window: Option<u64>,
+}
+
 /// Prepare the local OSS environment when `--oss` is selected.
 ///
-/// - Ensures a local Ollama server is reachable.
-/// - Checks if the model exists locally and pulls it if missing.
-pub async fn ensure_oss_ready(config: &Config) -> std::io::Result<()> {
-    // Only download when the requested model is the default OSS model (or when -m is not provided).
-    let model = config.model.as_ref();
-
-    // Verify local Ollama is reachable.
-    let ollama_client = crate::OllamaClient::try_from_oss_provider(config).await?;
-
-    // If the model is not present locally, pull it.
-    match ollama_client.fetch_models().await {
-        Ok(models) => {
-            if !models.iter().any(|m| m == model) {
-                let mut reporter = crate::CliProgressReporter::new();
-                ollama_client
-                    .pull_with_reporter(model, &mut reporter)
-                    .await?;
-            }
+/// - Finds a reachable local inference server: the `oss` provider's base URL
+///   first, then the default ports of the known servers when that URL is on
+///   this machine. `CODEX_OSS_SERVER` forces the server kind.
+/// - On Ollama, pulls the requested model if it is missing. Other servers
+///   cannot pull, so unless `model_explicit` is set the first model they
+///   serve is used in place of the default.
+/// - Detects the model's context window.
+pub async fn ensure_oss_ready(config: &Config, model_explicit: bool) -> io::Result<OssSelection> {
+    let base_url = config
+        .model_providers
+        .get(BUILT_IN_OSS_MODEL_PROVIDER_ID)
+        .and_then(|provider| provider.base_url.clone())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Built-in provider {BUILT_IN_OSS_MODEL_PROVIDER_ID} has no base_url"),
+            )
+        })?;
+    let hint = std::env::var("CODEX_OSS_SERVER")
+        .ok()
+        .and_then(|value| LocalServerKind::from_slug(&value));
+
+    let server = match LocalServer::detect(&base_url, hint).await {
+        Ok(server) => server,
+        Err(err) if is_local_base_url(&base_url) => {
+            tracing::info!("No server at {base_url} ({err}); probing default local ports");
+            discover_local_servers()
+                .await
+                .into_iter()
+                .next()
+                .ok_or_else(|| io::Error::other(no_local_server_message(&base_url)))?
         }
+        Err(err) => return Err(err),
+    };
+    let kind = server.kind();
+    tracing::info!("Using {kind} at {}", server.base_url());
+
+    let (models, listed) = match server.list_models().await {
+        Ok(models) => (models, true),
         Err(err) => {
             // Not fatal; higher layers may still proceed and surface errors later.
-            tracing::warn!("Failed to query local models from Ollama: {}.", err);
+            tracing::warn!("Failed to query local models from {kind}: {err}.");
+            (Vec::new(), false)
         }
-    }
+    };
+
+    let requested = config.model.as_str();
+    let model = if models.iter().any(|m| m.id == requested) {
+        requested.to_string()
+    } else if kind == LocalServerKind::Ollama {
+        // Only Ollama can fetch models on demand.
+        if listed {
+            let host_root = base_url_to_host_root(&server.base_url());
+            let mut reporter = CliProgressReporter::new();
+            OllamaClient::from_host_root(host_root)
+                .pull_with_reporter(requested, &mut reporter)
+                .await?;
+        }
+        requested.to_string()
+    } else if model_explicit || !listed {
+        requested.to_string()
+    } else if let Some(first) = models.first() {
+        tracing::info!("{requested} is not served by {kind}; using {}", first.id);
+        first.id.clone()
+    } else {
+        return Err(io::Error::other(format!(
+            "{kind} at {} is not serving any models. Load a model or pass -m.",
+            server.base_url()
+        )));
+    };
 
-    // Attempt to detect the model's maximum context window and expose it as an
-    // environment variable so downstream request builders can tune `num_ctx`.
-    if let Ok(Some(ctx)) = ollama_client.fetch_model_max_context(model).await {
-        // Avoid exporting obviously tiny defaults (some installs report 2048);
-        // still set it so the client can override server defaults.
-        // Mutating process env requires `unsafe` on Rust 2024; scoped to process.
+    let context_window = match models.iter().find(|m| m.id == model) {
+        Some(found) if found.context_window.is_some() => found.context_window,
+        _ => server.context_window(&model).await.ok().flatten(),
+    };
+    if kind == LocalServerKind::Ollama
+        && let Some(ctx) = context_window
+    {
+        // Expose the detected window so downstream request builders can tune
+        // `num_ctx`. Mutating process env requires `unsafe` on Rust 2024;
+        // scoped to process.
         unsafe { std::env::set_var("CODEX_OLLAMA_NUM_CTX", ctx.to_string()); }
-        tracing::info!("Detected Ollama model context length: {ctx}");
     }
+    if let Some(ctx) = context_window {
+        tracing::info!("Detected {kind} context length for {model}: {ctx}");
+    }
+
+    Ok(OssSelection {
+        kind,
+        base_url: server.base_url(),
+        model,
+        context_window,
+    })
+}
+
+/// Point `config` at the server and model chosen by [`ensure_oss_ready`]. A
+/// detected context window replaces the model family's default, since the
+/// server truncates anything beyond it.
+pub fn apply_oss_selection(config: &mut Config, selection: &OssSelection) {
+    if let Some(provider) = config.model_providers.get_mut(BUILT_IN_OSS_MODEL_PROVIDER_ID) {
+        provider.base_url = Some(selection.base_url.clone());
+    }
+    if config.model_provider_id == BUILT_IN_OSS_MODEL_PROVIDER_ID {
+        config.model_provider.base_url = Some(selection.base_url.clone());
+    }
+    if config.model != selection.model {
+        let family = find_family_for_model(&selection.model)
+            .unwrap_or_else(|| derive_default_model_family(&selection.model));
+        config.model = selection.model.clone();
+        config.model_context_window = family.context_window;
+        config.model_max_output_tokens = family.max_output_tokens;
+        config.model_auto_compact_token_limit = family.auto_compact_token_limit();
+        config.model_family = family;
+    }
+    if let Some(ctx) = selection.context_window {
+        config.model_context_window = Some(ctx);
+    }
+}
 
-    Ok(())
+fn no_local_server_message(base_url: &str) -> String {
+    let ports = LocalServerKind::ALL
+        .iter()
+        .filter_map(|kind| kind.default_port().map(|port| format!("{kind} ({port})")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "No local inference server detected at {base_url} or on the default ports of {ports}. \
+         Start one (for example `ollama serve` or `llama-server -m model.gguf`) or point \
+         CODEX_OSS_BASE_URL at it."
+    )
 }
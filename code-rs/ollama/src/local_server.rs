@@ -0,0 +1,488 @@
+//! Detection and inspection of local inference servers that expose the
+//! OpenAI-compatible `/v1` API: Ollama, LM Studio, llama.cpp's `llama-server`,
+//! vLLM, and anything else that answers `/v1/models`.
+
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use futures::future::join_all;
+use serde_json::Value as JsonValue;
+
+use crate::client::OllamaClient;
+use crate::url::base_url_to_host_root;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The kind of server answering at a local base URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocalServerKind {
+    Ollama,
+    LmStudio,
+    LlamaCpp,
+    Vllm,
+    /// Any other server that implements `/v1/models`.
+    OpenAiCompatible,
+}
+
+impl LocalServerKind {
+    /// Every kind, in the order default ports are probed during discovery.
+    pub const ALL: [LocalServerKind; 5] = [
+        LocalServerKind::Ollama,
+        LocalServerKind::LmStudio,
+        LocalServerKind::LlamaCpp,
+        LocalServerKind::Vllm,
+        LocalServerKind::OpenAiCompatible,
+    ];
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            LocalServerKind::Ollama => "Ollama",
+            LocalServerKind::LmStudio => "LM Studio",
+            LocalServerKind::LlamaCpp => "llama.cpp",
+            LocalServerKind::Vllm => "vLLM",
+            LocalServerKind::OpenAiCompatible => "OpenAI-compatible server",
+        }
+    }
+
+    /// Port the server listens on when started without flags.
+    pub fn default_port(self) -> Option<u16> {
+        match self {
+            LocalServerKind::Ollama => Some(11434),
+            LocalServerKind::LmStudio => Some(1234),
+            LocalServerKind::LlamaCpp => Some(8080),
+            LocalServerKind::Vllm => Some(8000),
+            LocalServerKind::OpenAiCompatible => None,
+        }
+    }
+
+    /// Parse the values accepted by `CODEX_OSS_SERVER`.
+    pub fn from_slug(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ollama" => Some(LocalServerKind::Ollama),
+            "lmstudio" | "lm-studio" | "lm_studio" => Some(LocalServerKind::LmStudio),
+            "llama-cpp" | "llamacpp" | "llama.cpp" | "llama_cpp" | "llama-server" => {
+                Some(LocalServerKind::LlamaCpp)
+            }
+            "vllm" => Some(LocalServerKind::Vllm),
+            "openai" | "openai-compatible" | "openai_compatible" => {
+                Some(LocalServerKind::OpenAiCompatible)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LocalServerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.display_name())
+    }
+}
+
+/// A model served by a local inference server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalModel {
+    pub id: String,
+    /// Context window in tokens, when the server reports one.
+    pub context_window: Option<u64>,
+}
+
+/// A reachable local inference server.
+pub struct LocalServer {
+    client: reqwest::Client,
+    kind: LocalServerKind,
+    host_root: String,
+}
+
+impl LocalServer {
+    /// Probe the server behind `base_url` and identify what it is. When `hint`
+    /// is provided identification is skipped and the hint is trusted.
+    pub async fn detect(base_url: &str, hint: Option<LocalServerKind>) -> io::Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(PROBE_TIMEOUT)
+            .timeout(PROBE_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        let mut server = Self {
+            client,
+            kind: LocalServerKind::OpenAiCompatible,
+            host_root: base_url_to_host_root(base_url),
+        };
+
+        let models = server.get_json("/v1/models").await;
+        let kind = match (hint, &models) {
+            (Some(kind), _) => kind,
+            (None, Ok(models)) => server.identify(models).await,
+            (None, Err(err)) => {
+                // Ollama builds without the OpenAI compatibility layer still
+                // answer the native tags endpoint.
+                if server.get_json("/api/tags").await.is_err() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionRefused,
+                        format!("no inference server answered at {}: {err}", server.host_root),
+                    ));
+                }
+                LocalServerKind::Ollama
+            }
+        };
+        if models.is_err() && kind != LocalServerKind::Ollama {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("{kind} at {} did not answer /v1/models", server.host_root),
+            ));
+        }
+        server.kind = kind;
+        Ok(server)
+    }
+
+    pub fn kind(&self) -> LocalServerKind {
+        self.kind
+    }
+
+    /// OpenAI-compatible base URL for chat requests, e.g. `http://localhost:8080/v1`.
+    pub fn base_url(&self) -> String {
+        format!("{}/v1", self.host_root.trim_end_matches('/'))
+    }
+
+    /// Models the server can serve, with context windows where available.
+    pub async fn list_models(&self) -> io::Result<Vec<LocalModel>> {
+        match self.kind {
+            LocalServerKind::Ollama => {
+                let ollama = OllamaClient::from_host_root(self.host_root.clone());
+                let mut models = Vec::new();
+                for id in ollama.fetch_models().await? {
+                    let context_window = ollama.fetch_model_max_context(&id).await.ok().flatten();
+                    models.push(LocalModel { id, context_window });
+                }
+                Ok(models)
+            }
+            LocalServerKind::LmStudio => match self.get_json("/api/v0/models").await {
+                // The native listing includes context lengths and model type;
+                // embedding models cannot serve chat requests.
+                Ok(native) => Ok(models_from_list(&native, |entry| {
+                    entry.get("type").and_then(JsonValue::as_str) != Some("embeddings")
+                })),
+                Err(_) => self.openai_models().await,
+            },
+            LocalServerKind::LlamaCpp => {
+                let mut models = self.openai_models().await?;
+                // `/props` reports the context the server was started with,
+                // which is what requests are limited to.
+                if let Some(n_ctx) = self
+                    .get_json("/props")
+                    .await
+                    .ok()
+                    .as_ref()
+                    .and_then(llama_cpp_context_window)
+                {
+                    for model in &mut models {
+                        model.context_window = Some(n_ctx);
+                    }
+                }
+                Ok(models)
+            }
+            LocalServerKind::Vllm | LocalServerKind::OpenAiCompatible => {
+                self.openai_models().await
+            }
+        }
+    }
+
+    /// Context window of `model`, if the server reports one.
+    pub async fn context_window(&self, model: &str) -> io::Result<Option<u64>> {
+        if self.kind == LocalServerKind::Ollama {
+            return OllamaClient::from_host_root(self.host_root.clone())
+                .fetch_model_max_context(model)
+                .await;
+        }
+        Ok(self
+            .list_models()
+            .await?
+            .into_iter()
+            .find(|candidate| candidate.id == model)
+            .and_then(|candidate| candidate.context_window))
+    }
+
+    async fn openai_models(&self) -> io::Result<Vec<LocalModel>> {
+        let list = self.get_json("/v1/models").await?;
+        Ok(models_from_list(&list, |_| true))
+    }
+
+    async fn identify(&self, models: &JsonValue) -> LocalServerKind {
+        let owners: Vec<&str> = model_entries(models)
+            .filter_map(|entry| entry.get("owned_by").and_then(JsonValue::as_str))
+            .collect();
+        if owners.contains(&"vllm") {
+            return LocalServerKind::Vllm;
+        }
+        if owners.contains(&"llamacpp") {
+            return LocalServerKind::LlamaCpp;
+        }
+        if let Ok(tags) = self.get_json("/api/tags").await
+            && tags.get("models").is_some_and(JsonValue::is_array)
+        {
+            return LocalServerKind::Ollama;
+        }
+        if let Ok(native) = self.get_json("/api/v0/models").await
+            && native.get("data").is_some_and(JsonValue::is_array)
+        {
+            return LocalServerKind::LmStudio;
+        }
+        if let Ok(props) = self.get_json("/props").await
+            && props.get("default_generation_settings").is_some()
+        {
+            return LocalServerKind::LlamaCpp;
+        }
+        LocalServerKind::OpenAiCompatible
+    }
+
+    async fn get_json(&self, path: &str) -> io::Result<JsonValue> {
+        let url = format!("{}{path}", self.host_root.trim_end_matches('/'));
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+        if !resp.status().is_success() {
+            return Err(io::Error::other(format!("HTTP {} from {url}", resp.status())));
+        }
+        resp.json::<JsonValue>().await.map_err(io::Error::other)
+    }
+}
+
+/// Probe the default localhost port of every known server kind and return
+/// the servers that answered, in [`LocalServerKind::ALL`] order.
+pub async fn discover_local_servers() -> Vec<LocalServer> {
+    let probes = LocalServerKind::ALL
+        .iter()
+        .filter_map(|kind| kind.default_port())
+        .map(|port| async move {
+            LocalServer::detect(&format!("http://localhost:{port}/v1"), None)
+                .await
+                .ok()
+        });
+    join_all(probes).await.into_iter().flatten().collect()
+}
+
+/// Whether `base_url` points at this machine.
+pub fn is_local_base_url(base_url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(base_url) else {
+        return false;
+    };
+    match url.host_str() {
+        Some(host) if host.eq_ignore_ascii_case("localhost") => true,
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|addr| addr.is_loopback()),
+        None => false,
+    }
+}
+
+fn model_entries(list: &JsonValue) -> impl Iterator<Item = &JsonValue> {
+    list.get("data")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn models_from_list(list: &JsonValue, keep: impl Fn(&JsonValue) -> bool) -> Vec<LocalModel> {
+    model_entries(list)
+        .filter(|entry| keep(entry))
+        .filter_map(|entry| {
+            let id = entry.get("id").and_then(JsonValue::as_str)?.to_string();
+            Some(LocalModel {
+                id,
+                context_window: entry_context_window(entry),
+            })
+        })
+        .collect()
+}
+
+/// Context window advertised on a `/v1/models` (or LM Studio `/api/v0/models`)
+/// entry. Servers disagree on the field name; a loaded context length wins over
+/// the model's maximum because it is what requests are actually limited to.
+fn entry_context_window(entry: &JsonValue) -> Option<u64> {
+    const KEYS: [&str; 5] = [
+        "loaded_context_length",
+        "max_model_len",
+        "context_window",
+        "context_length",
+        "max_context_length",
+    ];
+    KEYS.iter()
+        .find_map(|key| entry.get(*key).and_then(JsonValue::as_u64))
+        .or_else(|| {
+            entry
+                .get("meta")
+                .and_then(|meta| meta.get("n_ctx_train"))
+                .and_then(JsonValue::as_u64)
+        })
+}
+
+fn llama_cpp_context_window(props: &JsonValue) -> Option<u64> {
+    props
+        .get("default_generation_settings")
+        .and_then(|settings| settings.get("n_ctx"))
+        .or_else(|| props.get("n_ctx"))
+        .and_then(JsonValue::as_u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn network_disabled() -> bool {
+        std::env::var(code_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok()
+    }
+
+    async fn mount_json(server: &MockServer, route: &str, body: JsonValue) {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn context_window_prefers_loaded_length() {
+        let entry = serde_json::json!({
+            "id": "qwen2.5-coder-7b",
+            "max_context_length": 32768,
+            "loaded_context_length": 8192
+        });
+        assert_eq!(entry_context_window(&entry), Some(8192));
+        let llama = serde_json::json!({ "id": "m.gguf", "meta": { "n_ctx_train": 131072 } });
+        assert_eq!(entry_context_window(&llama), Some(131072));
+        assert_eq!(entry_context_window(&serde_json::json!({ "id": "x" })), None);
+    }
+
+    #[test]
+    fn local_base_urls_are_loopback_only() {
+        assert!(is_local_base_url("http://localhost:8080/v1"));
+        assert!(is_local_base_url("http://127.0.0.1:1234/v1"));
+        assert!(is_local_base_url("http://[::1]:8000/v1"));
+        assert!(!is_local_base_url("https://api.openai.com/v1"));
+        assert!(!is_local_base_url("not a url"));
+    }
+
+    #[test]
+    fn server_slugs_parse() {
+        assert_eq!(LocalServerKind::from_slug("llama.cpp"), Some(LocalServerKind::LlamaCpp));
+        assert_eq!(LocalServerKind::from_slug("LM-Studio"), Some(LocalServerKind::LmStudio));
+        assert_eq!(LocalServerKind::from_slug("tgi"), None);
+    }
+
+    #[tokio::test]
+    async fn detects_llama_cpp_and_reads_runtime_context() {
+        if network_disabled() {
+            return;
+        }
+        let server = MockServer::start().await;
+        mount_json(
+            &server,
+            "/v1/models",
+            serde_json::json!({
+                "object": "list",
+                "data": [{
+                    "id": "qwen2.5-coder-7b-q4_k_m.gguf",
+                    "owned_by": "llamacpp",
+                    "meta": { "n_ctx_train": 32768 }
+                }]
+            }),
+        )
+        .await;
+        mount_json(
+            &server,
+            "/props",
+            serde_json::json!({ "default_generation_settings": { "n_ctx": 16384 } }),
+        )
+        .await;
+
+        let local = LocalServer::detect(&format!("{}/v1", server.uri()), None)
+            .await
+            .expect("detect");
+        assert_eq!(local.kind(), LocalServerKind::LlamaCpp);
+        let models = local.list_models().await.expect("models");
+        assert_eq!(
+            models,
+            vec![LocalModel {
+                id: "qwen2.5-coder-7b-q4_k_m.gguf".to_string(),
+                context_window: Some(16384),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_vllm_and_lm_studio() {
+        if network_disabled() {
+            return;
+        }
+        let vllm = MockServer::start().await;
+        mount_json(
+            &vllm,
+            "/v1/models",
+            serde_json::json!({
+                "data": [{ "id": "Qwen/Qwen2.5-Coder-32B", "owned_by": "vllm", "max_model_len": 65536 }]
+            }),
+        )
+        .await;
+        let local = LocalServer::detect(&vllm.uri(), None).await.expect("detect vllm");
+        assert_eq!(local.kind(), LocalServerKind::Vllm);
+        assert_eq!(
+            local.context_window("Qwen/Qwen2.5-Coder-32B").await.expect("ctx"),
+            Some(65536)
+        );
+
+        let lm_studio = MockServer::start().await;
+        mount_json(
+            &lm_studio,
+            "/v1/models",
+            serde_json::json!({ "data": [{ "id": "qwen2.5-7b", "owned_by": "organization_owner" }] }),
+        )
+        .await;
+        mount_json(
+            &lm_studio,
+            "/api/v0/models",
+            serde_json::json!({
+                "data": [
+                    { "id": "qwen2.5-7b", "type": "llm", "max_context_length": 32768 },
+                    { "id": "nomic-embed", "type": "embeddings", "max_context_length": 2048 }
+                ]
+            }),
+        )
+        .await;
+        let local = LocalServer::detect(&lm_studio.uri(), None).await.expect("detect lm studio");
+        assert_eq!(local.kind(), LocalServerKind::LmStudio);
+        let ids: Vec<String> = local
+            .list_models()
+            .await
+            .expect("models")
+            .into_iter()
+            .map(|model| model.id)
+            .collect();
+        assert_eq!(ids, vec!["qwen2.5-7b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn generic_server_without_metadata_is_openai_compatible() {
+        if network_disabled() {
+            return;
+        }
+        let server = MockServer::start().await;
+        mount_json(&server, "/v1/models", serde_json::json!({ "data": [{ "id": "local" }] })).await;
+        let local = LocalServer::detect(&server.uri(), None).await.expect("detect");
+        assert_eq!(local.kind(), LocalServerKind::OpenAiCompatible);
+        assert_eq!(local.base_url(), format!("{}/v1", server.uri()));
+
+        let missing = MockServer::start().await;
+        assert!(LocalServer::detect(&missing.uri(), None).await.is_err());
+    }
+}
@@ -1,8 +1,10 @@
 pub mod config;
 
+mod metrics;
 pub mod otel_event_manager;
 #[cfg(feature = "otel")]
 pub mod otel_provider;
+pub mod spans;
 
 #[cfg(not(feature = "otel"))]
 mod imp {
@@ -0,0 +1,180 @@
+//! Counters and histograms recorded alongside the log events emitted by
+//! [`crate::otel_event_manager::OtelEventManager`]. Instruments come from the
+//! global meter provider, which [`crate::otel_provider::OtelProvider`]
+//! installs when an exporter is configured; otherwise they are no-ops.
+
+#[cfg(feature = "otel")]
+mod imp {
+    use std::time::Duration;
+
+    use opentelemetry::KeyValue;
+    use opentelemetry::global;
+    use opentelemetry::metrics::Counter;
+    use opentelemetry::metrics::Histogram;
+
+    const METER_NAME: &str = "code_otel";
+    const TOKENS_METRIC: &str = "codex.tokens";
+    const MODEL_REQUEST_DURATION_METRIC: &str = "codex.model_request.duration_ms";
+    const MODEL_REQUEST_RETRY_METRIC: &str = "codex.model_request.retries";
+    const TOOL_CALL_DURATION_METRIC: &str = "codex.tool_call.duration_ms";
+    const TURN_DURATION_METRIC: &str = "codex.turn.duration_ms";
+    const APPROVAL_METRIC: &str = "codex.approvals";
+
+    #[derive(Debug, Clone)]
+    pub(crate) struct OtelMetrics {
+        tokens: Counter<u64>,
+        model_request_duration: Histogram<f64>,
+        model_request_retries: Counter<u64>,
+        tool_call_duration: Histogram<f64>,
+        turn_duration: Histogram<f64>,
+        approvals: Counter<u64>,
+    }
+
+    impl OtelMetrics {
+        pub(crate) fn new() -> Self {
+            let meter = global::meter(METER_NAME);
+            Self {
+                tokens: meter
+                    .u64_counter(TOKENS_METRIC)
+                    .with_description("Tokens consumed, by token type")
+                    .build(),
+                model_request_duration: meter
+                    .f64_histogram(MODEL_REQUEST_DURATION_METRIC)
+                    .with_unit("ms")
+                    .with_description("Time until the model API answered a request")
+                    .build(),
+                model_request_retries: meter
+                    .u64_counter(MODEL_REQUEST_RETRY_METRIC)
+                    .with_description("Model requests retried after a failure")
+                    .build(),
+                tool_call_duration: meter
+                    .f64_histogram(TOOL_CALL_DURATION_METRIC)
+                    .with_unit("ms")
+                    .with_description("Tool call execution time")
+                    .build(),
+                turn_duration: meter
+                    .f64_histogram(TURN_DURATION_METRIC)
+                    .with_unit("ms")
+                    .with_description("Wall time of a turn, from user input to completion")
+                    .build(),
+                approvals: meter
+                    .u64_counter(APPROVAL_METRIC)
+                    .with_description("Tool approval decisions, by decision and source")
+                    .build(),
+            }
+        }
+
+        pub(crate) fn record_tokens(&self, model: &str, token_type: &'static str, count: u64) {
+            if count == 0 {
+                return;
+            }
+            self.tokens.add(
+                count,
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("token.type", token_type),
+                ],
+            );
+        }
+
+        pub(crate) fn record_model_request(
+            &self,
+            model: &str,
+            attempt: u64,
+            status: Option<u16>,
+            duration: Duration,
+        ) {
+            let status = status.map(i64::from).unwrap_or_default();
+            self.model_request_duration.record(
+                duration_ms(duration),
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("http.response.status_code", status),
+                ],
+            );
+            if attempt > 1 {
+                self.record_retry(model, "http");
+            }
+        }
+
+        pub(crate) fn record_retry(&self, model: &str, reason: &'static str) {
+            self.model_request_retries.add(
+                1,
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("reason", reason),
+                ],
+            );
+        }
+
+        pub(crate) fn record_tool_call(&self, tool_name: &str, success: bool, duration: Duration) {
+            self.tool_call_duration.record(
+                duration_ms(duration),
+                &[
+                    KeyValue::new("tool_name", tool_name.to_string()),
+                    KeyValue::new("success", success),
+                ],
+            );
+        }
+
+        pub(crate) fn record_turn(&self, model: &str, kind: &'static str, duration: Duration) {
+            self.turn_duration.record(
+                duration_ms(duration),
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("turn.kind", kind),
+                ],
+            );
+        }
+
+        pub(crate) fn record_approval(&self, tool_name: &str, decision: &str, source: &str) {
+            self.approvals.add(
+                1,
+                &[
+                    KeyValue::new("tool_name", tool_name.to_string()),
+                    KeyValue::new("decision", decision.to_string()),
+                    KeyValue::new("source", source.to_string()),
+                ],
+            );
+        }
+    }
+
+    fn duration_ms(duration: Duration) -> f64 {
+        duration.as_secs_f64() * 1_000.0
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+mod imp {
+    use std::time::Duration;
+
+    #[derive(Debug, Clone)]
+    pub(crate) struct OtelMetrics;
+
+    impl OtelMetrics {
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        pub(crate) fn record_tokens(&self, _model: &str, _token_type: &'static str, _count: u64) {}
+
+        pub(crate) fn record_model_request(
+            &self,
+            _model: &str,
+            _attempt: u64,
+            _status: Option<u16>,
+            _duration: Duration,
+        ) {
+        }
+
+        pub(crate) fn record_retry(&self, _model: &str, _reason: &'static str) {}
+
+        pub(crate) fn record_tool_call(&self, _tool_name: &str, _success: bool, _duration: Duration) {}
+
+        pub(crate) fn record_turn(&self, _model: &str, _kind: &'static str, _duration: Duration) {}
+
+        pub(crate) fn record_approval(&self, _tool_name: &str, _decision: &str, _source: &str) {}
+    }
+}
+
+pub(crate) use imp::OtelMetrics;
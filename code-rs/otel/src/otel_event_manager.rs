@@ -20,6 +20,11 @@ use std::time::Instant;
 use strum_macros::Display;
 use tokio::time::error::Elapsed;
 
+use crate::metrics::OtelMetrics;
+use crate::spans::OtelSpan;
+use crate::spans::TRACEPARENT_ENV_VAR;
+use crate::spans::attr;
+
 #[derive(Debug, Clone, Serialize, Display)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolDecisionSource {
@@ -83,6 +88,10 @@ pub struct OtelEventMetadata {
 #[derive(Debug, Clone)]
 pub struct OtelEventManager {
     metadata: OtelEventMetadata,
+    metrics: OtelMetrics,
+    /// Root span for the conversation; clones share it and it ends once the
+    /// last clone is dropped.
+    session_span: OtelSpan,
 }
 
 impl OtelEventManager {
@@ -95,7 +104,21 @@ impl OtelEventManager {
         log_user_prompts: bool,
         terminal_type: String,
     ) -> OtelEventManager {
+        // A sub-agent process receives its parent's trace context through the
+        // environment; its session links back to the spawning turn.
+        let traceparent = std::env::var(TRACEPARENT_ENV_VAR).ok();
+        let session_span = OtelSpan::root_from_traceparent(
+            "codex.session",
+            vec![
+                attr("conversation.id", conversation_id.to_string()),
+                attr("model", model),
+                attr("terminal.type", terminal_type.as_str()),
+            ],
+            traceparent.as_deref(),
+        );
         Self {
+            metrics: OtelMetrics::new(),
+            session_span,
             metadata: OtelEventMetadata {
                 conversation_id,
                 auth_mode: auth_mode.map(|m| m.to_string()),
@@ -116,6 +139,36 @@ impl OtelEventManager {
         manager
     }
 
+    pub fn session_span(&self) -> &OtelSpan {
+        &self.session_span
+    }
+
+    /// Open a `codex.turn` span under the session span. Instrument the turn's
+    /// future with it so model requests and tool calls nest beneath it.
+    pub fn turn_span(&self, sub_id: &str, kind: &'static str) -> OtelSpan {
+        self.session_span.child(
+            "codex.turn",
+            vec![
+                attr("turn.id", sub_id),
+                attr("turn.kind", kind),
+                attr("model", self.metadata.model.as_str()),
+            ],
+        )
+    }
+
+    pub fn turn_completed(&self, kind: &'static str, duration: Duration) {
+        self.metrics.record_turn(&self.metadata.model, kind, duration);
+    }
+
+    /// A turn retried its model request after a stream or transport failure.
+    pub fn stream_retry(&self, reason: &'static str) {
+        self.metrics.record_retry(&self.metadata.model, reason);
+    }
+
+    pub fn tool_call_completed(&self, tool_name: &str, success: bool, duration: Duration) {
+        self.metrics.record_tool_call(tool_name, success, duration);
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn conversation_starts(
         &self,
@@ -167,6 +220,8 @@ impl OtelEventManager {
             Ok(response) => (Some(response.status().as_u16()), None),
             Err(error) => (error.status().map(|s| s.as_u16()), Some(error.to_string())),
         };
+        self.metrics
+            .record_model_request(&self.metadata.model, attempt, status, duration);
 
         tracing::event!(
             tracing::Level::INFO,
@@ -325,6 +380,13 @@ impl OtelEventManager {
         reasoning_token_count: Option<u64>,
         tool_token_count: u64,
     ) {
+        let model = self.metadata.model.as_str();
+        self.metrics.record_tokens(model, "input", input_token_count);
+        self.metrics.record_tokens(model, "output", output_token_count);
+        self.metrics
+            .record_tokens(model, "cached", cached_token_count.unwrap_or_default());
+        self.metrics
+            .record_tokens(model, "reasoning", reasoning_token_count.unwrap_or_default());
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.sse_event",
@@ -383,6 +445,9 @@ impl OtelEventManager {
         decision: ReviewDecision,
         source: ToolDecisionSource,
     ) {
+        let decision = decision.to_string().to_lowercase();
+        let source = source.to_string();
+        self.metrics.record_approval(tool_name, &decision, &source);
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.tool_decision",
@@ -396,8 +461,8 @@ impl OtelEventManager {
             slug = %self.metadata.slug,
             tool_name = %tool_name,
             call_id = %call_id,
-            decision = %decision.to_string().to_lowercase(),
-            source = %source.to_string(),
+            decision = %decision,
+            source = %source,
         );
     }
 
@@ -421,6 +486,7 @@ impl OtelEventManager {
             Ok(content) => (content, true),
             Err(error) => (&error.to_string(), false),
         };
+        self.metrics.record_tool_call(tool_name, success, duration);
 
         tracing::event!(
             tracing::Level::INFO,
@@ -445,6 +511,7 @@ impl OtelEventManager {
     }
 
     pub fn log_tool_failed(&self, tool_name: &str, error: &str) {
+        self.metrics.record_tool_call(tool_name, false, Duration::ZERO);
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.tool_result",
@@ -473,6 +540,7 @@ impl OtelEventManager {
         output: &str,
     ) {
         let success_str = if success { "true" } else { "false" };
+        self.metrics.record_tool_call(tool_name, success, duration);
 
         tracing::event!(
             tracing::Level::INFO,
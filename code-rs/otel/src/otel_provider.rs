@@ -2,13 +2,20 @@ use crate::config::OtelExporter;
 use crate::config::OtelHttpProtocol;
 use crate::config::OtelSettings;
 use opentelemetry::KeyValue;
+use opentelemetry::global;
 use opentelemetry_otlp::LogExporter;
+use opentelemetry_otlp::MetricExporter;
 use opentelemetry_otlp::Protocol;
+use opentelemetry_otlp::SpanExporter;
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_otlp::WithHttpConfig;
 use opentelemetry_otlp::WithTonicConfig;
 use opentelemetry_sdk::Resource;
 use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry_sdk::metrics::PeriodicReader;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
 use opentelemetry_semantic_conventions as semconv;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
@@ -19,12 +26,21 @@ use tracing::debug;
 
 const ENV_ATTRIBUTE: &str = "env";
 
+/// Log, trace, and metric pipelines sharing one exporter configuration.
+///
+/// The tracer and meter providers are installed as the OpenTelemetry globals,
+/// which is where [`crate::spans::OtelSpan`] and the event manager's metrics
+/// pick them up.
 pub struct OtelProvider {
     pub logger: SdkLoggerProvider,
+    pub tracer: SdkTracerProvider,
+    pub meter: SdkMeterProvider,
 }
 
 impl OtelProvider {
     pub fn shutdown(&self) {
+        let _ = self.tracer.shutdown();
+        let _ = self.meter.shutdown();
         let _ = self.logger.shutdown();
     }
 
@@ -40,7 +56,9 @@ impl OtelProvider {
             ])
             .build();
 
-        let mut builder = SdkLoggerProvider::builder().with_resource(resource);
+        let mut logger = SdkLoggerProvider::builder().with_resource(resource.clone());
+        let mut tracer = SdkTracerProvider::builder().with_resource(resource.clone());
+        let mut meter = SdkMeterProvider::builder().with_resource(resource);
 
         match &settings.exporter {
             OtelExporter::None => {
@@ -59,13 +77,25 @@ impl OtelProvider {
                     }
                 }
 
-                let exporter = LogExporter::builder()
+                let log_exporter = LogExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(endpoint)
+                    .with_metadata(MetadataMap::from_headers(header_map.clone()))
+                    .build()?;
+                let span_exporter = SpanExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(endpoint)
+                    .with_metadata(MetadataMap::from_headers(header_map.clone()))
+                    .build()?;
+                let metric_exporter = MetricExporter::builder()
                     .with_tonic()
                     .with_endpoint(endpoint)
                     .with_metadata(MetadataMap::from_headers(header_map))
                     .build()?;
 
-                builder = builder.with_batch_exporter(exporter);
+                logger = logger.with_batch_exporter(log_exporter);
+                tracer = tracer.with_batch_exporter(span_exporter);
+                meter = meter.with_reader(PeriodicReader::builder(metric_exporter).build());
             }
             OtelExporter::OtlpHttp {
                 endpoint,
@@ -79,25 +109,75 @@ impl OtelProvider {
                     OtelHttpProtocol::Json => Protocol::HttpJson,
                 };
 
-                let exporter = LogExporter::builder()
+                let log_exporter = LogExporter::builder()
                     .with_http()
                     .with_endpoint(endpoint)
                     .with_protocol(protocol)
                     .with_headers(headers.clone())
                     .build()?;
+                let span_exporter = SpanExporter::builder()
+                    .with_http()
+                    .with_endpoint(http_signal_endpoint(endpoint, "traces"))
+                    .with_protocol(protocol)
+                    .with_headers(headers.clone())
+                    .build()?;
+                let metric_exporter = MetricExporter::builder()
+                    .with_http()
+                    .with_endpoint(http_signal_endpoint(endpoint, "metrics"))
+                    .with_protocol(protocol)
+                    .with_headers(headers.clone())
+                    .build()?;
 
-                builder = builder.with_batch_exporter(exporter);
+                logger = logger.with_batch_exporter(log_exporter);
+                tracer = tracer.with_batch_exporter(span_exporter);
+                meter = meter.with_reader(PeriodicReader::builder(metric_exporter).build());
             }
         }
 
-        Ok(Some(Self {
-            logger: builder.build(),
-        }))
+        let provider = Self {
+            logger: logger.build(),
+            tracer: tracer.build(),
+            meter: meter.build(),
+        };
+        global::set_tracer_provider(provider.tracer.clone());
+        global::set_meter_provider(provider.meter.clone());
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        Ok(Some(provider))
     }
 }
 
 impl Drop for OtelProvider {
     fn drop(&mut self) {
-        let _ = self.logger.shutdown();
+        self.shutdown();
+    }
+}
+
+/// OTLP/HTTP uses one URL per signal. The configured endpoint is the logs URL
+/// (conventionally `.../v1/logs`); traces and metrics go to the sibling path,
+/// or to `/v1/<signal>` under the endpoint when it has no signal suffix.
+fn http_signal_endpoint(endpoint: &str, signal: &str) -> String {
+    let trimmed = endpoint.trim_end_matches('/');
+    for known in ["logs", "traces", "metrics"] {
+        if let Some(base) = trimmed.strip_suffix(&format!("/v1/{known}")) {
+            return format!("{base}/v1/{signal}");
+        }
+    }
+    format!("{trimmed}/v1/{signal}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_signal_endpoints_follow_the_logs_endpoint() {
+        assert_eq!(
+            http_signal_endpoint("https://otel.example.com/v1/logs", "traces"),
+            "https://otel.example.com/v1/traces"
+        );
+        assert_eq!(
+            http_signal_endpoint("http://localhost:4318/", "metrics"),
+            "http://localhost:4318/v1/metrics"
+        );
     }
 }
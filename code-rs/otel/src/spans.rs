@@ -0,0 +1,304 @@
+//! Nested OpenTelemetry spans for a run: session → turn → model request /
+//! tool call → exec, MCP, or browser action, plus sub-agent spans linked to
+//! the turn that spawned them.
+//!
+//! Parents travel in the OpenTelemetry context attached to the running future
+//! (see [`OtelSpan::instrument`]), so call sites only start a span and wrap
+//! the future that does the work. Spans end when [`OtelSpan::end`] is called
+//! or when the last handle is dropped. Without a configured exporter the
+//! global tracer is a no-op and these calls cost next to nothing.
+
+use std::future::Future;
+
+/// Environment variable carrying a W3C trace context into child processes.
+pub const TRACEPARENT_ENV_VAR: &str = "TRACEPARENT";
+
+/// Attribute value attached to a span.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpanValue {
+    Str(String),
+    I64(i64),
+    Bool(bool),
+}
+
+impl From<&str> for SpanValue {
+    fn from(value: &str) -> Self {
+        SpanValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for SpanValue {
+    fn from(value: String) -> Self {
+        SpanValue::Str(value)
+    }
+}
+
+impl From<i64> for SpanValue {
+    fn from(value: i64) -> Self {
+        SpanValue::I64(value)
+    }
+}
+
+impl From<u64> for SpanValue {
+    fn from(value: u64) -> Self {
+        SpanValue::I64(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<bool> for SpanValue {
+    fn from(value: bool) -> Self {
+        SpanValue::Bool(value)
+    }
+}
+
+pub type SpanAttribute = (&'static str, SpanValue);
+
+/// Build a span attribute.
+pub fn attr(key: &'static str, value: impl Into<SpanValue>) -> SpanAttribute {
+    (key, value.into())
+}
+
+#[cfg(feature = "otel")]
+mod imp {
+    use std::collections::HashMap;
+    use std::future::Future;
+
+    use opentelemetry::Context;
+    use opentelemetry::KeyValue;
+    use opentelemetry::Value;
+    use opentelemetry::context::FutureExt;
+    use opentelemetry::global;
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::Link;
+    use opentelemetry::trace::Span;
+    use opentelemetry::trace::Status;
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry::trace::Tracer;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+    use super::SpanAttribute;
+    use super::SpanValue;
+
+    const TRACER_NAME: &str = "code_otel";
+
+    fn key_value((key, value): SpanAttribute) -> KeyValue {
+        let value = match value {
+            SpanValue::Str(value) => Value::from(value),
+            SpanValue::I64(value) => Value::from(value),
+            SpanValue::Bool(value) => Value::from(value),
+        };
+        KeyValue::new(key, value)
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct OtelSpan {
+        cx: Option<Context>,
+    }
+
+    impl OtelSpan {
+        pub fn current() -> Self {
+            let cx = Context::current();
+            if cx.has_active_span() {
+                Self { cx: Some(cx) }
+            } else {
+                Self::default()
+            }
+        }
+
+        pub fn start(name: &'static str, attributes: Vec<SpanAttribute>) -> Self {
+            Self::start_with_parent(&Context::current(), name, attributes, Vec::new())
+        }
+
+        pub fn child(&self, name: &'static str, attributes: Vec<SpanAttribute>) -> Self {
+            let parent = self.cx.clone().unwrap_or_default();
+            Self::start_with_parent(&parent, name, attributes, Vec::new())
+        }
+
+        pub fn root_linked(
+            name: &'static str,
+            attributes: Vec<SpanAttribute>,
+            linked: &OtelSpan,
+        ) -> Self {
+            let links = linked
+                .cx
+                .as_ref()
+                .map(|cx| cx.span().span_context().clone())
+                .filter(|span_context| span_context.is_valid())
+                .map(|span_context| vec![Link::with_context(span_context)])
+                .unwrap_or_default();
+            Self::start_with_parent(&Context::new(), name, attributes, links)
+        }
+
+        pub fn root_from_traceparent(
+            name: &'static str,
+            attributes: Vec<SpanAttribute>,
+            traceparent: Option<&str>,
+        ) -> Self {
+            let remote = traceparent.and_then(|traceparent| {
+                let mut carrier = HashMap::new();
+                carrier.insert("traceparent".to_string(), traceparent.to_string());
+                let cx = TraceContextPropagator::new().extract(&carrier);
+                let span_context = cx.span().span_context().clone();
+                span_context.is_valid().then_some(span_context)
+            });
+            let links = remote.map(|sc| vec![Link::with_context(sc)]).unwrap_or_default();
+            Self::start_with_parent(&Context::new(), name, attributes, links)
+        }
+
+        fn start_with_parent(
+            parent: &Context,
+            name: &'static str,
+            attributes: Vec<SpanAttribute>,
+            links: Vec<Link>,
+        ) -> Self {
+            let tracer = global::tracer(TRACER_NAME);
+            let span = tracer
+                .span_builder(name)
+                .with_attributes(attributes.into_iter().map(key_value))
+                .with_links(links)
+                .start_with_context(&tracer, parent);
+            if !span.span_context().is_valid() {
+                // No tracer provider is installed; skip carrying a context.
+                return Self::default();
+            }
+            Self {
+                cx: Some(parent.with_span(span)),
+            }
+        }
+
+        pub fn traceparent(&self) -> Option<String> {
+            let cx = self.cx.as_ref()?;
+            let mut carrier = HashMap::new();
+            TraceContextPropagator::new().inject_context(cx, &mut carrier);
+            carrier.remove("traceparent")
+        }
+
+        pub fn set_attribute(&self, attribute: SpanAttribute) {
+            if let Some(cx) = &self.cx {
+                cx.span().set_attribute(key_value(attribute));
+            }
+        }
+
+        pub fn record_error(&self, message: &str) {
+            if let Some(cx) = &self.cx {
+                cx.span().set_status(Status::error(message.to_string()));
+            }
+        }
+
+        pub fn end(&self) {
+            if let Some(cx) = &self.cx {
+                cx.span().end();
+            }
+        }
+
+        pub fn instrument<F: Future>(
+            &self,
+            future: F,
+        ) -> impl Future<Output = F::Output> + use<F> {
+            let cx = self.cx.clone().unwrap_or_else(Context::current);
+            future.with_context(cx)
+        }
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+mod imp {
+    use std::future::Future;
+
+    use super::SpanAttribute;
+
+    #[derive(Clone, Debug, Default)]
+    pub struct OtelSpan;
+
+    impl OtelSpan {
+        pub fn current() -> Self {
+            Self
+        }
+
+        pub fn start(_name: &'static str, _attributes: Vec<SpanAttribute>) -> Self {
+            Self
+        }
+
+        pub fn child(&self, _name: &'static str, _attributes: Vec<SpanAttribute>) -> Self {
+            Self
+        }
+
+        pub fn root_linked(
+            _name: &'static str,
+            _attributes: Vec<SpanAttribute>,
+            _linked: &OtelSpan,
+        ) -> Self {
+            Self
+        }
+
+        pub fn root_from_traceparent(
+            _name: &'static str,
+            _attributes: Vec<SpanAttribute>,
+            _traceparent: Option<&str>,
+        ) -> Self {
+            Self
+        }
+
+        pub fn traceparent(&self) -> Option<String> {
+            None
+        }
+
+        pub fn set_attribute(&self, _attribute: SpanAttribute) {}
+
+        pub fn record_error(&self, _message: &str) {}
+
+        pub fn end(&self) {}
+
+        pub fn instrument<F: Future>(
+            &self,
+            future: F,
+        ) -> impl Future<Output = F::Output> + use<F> {
+            future
+        }
+    }
+}
+
+/// Handle to an open span. Cloning shares the span; the clones keep it open
+/// until [`OtelSpan::end`] is called or every clone is dropped.
+///
+/// - [`OtelSpan::start`] opens a child of the span attached to the running
+///   future, or a root span when there is none.
+/// - [`OtelSpan::child`] opens a child of an explicit parent.
+/// - [`OtelSpan::root_linked`] opens a new trace linked to another span, for
+///   work that outlives its parent (sub-agents).
+/// - [`OtelSpan::root_from_traceparent`] links a new trace to a span from a
+///   parent process, passed via [`TRACEPARENT_ENV_VAR`].
+pub use imp::OtelSpan;
+
+impl OtelSpan {
+    /// Run `future` with this span as the parent of spans started inside it.
+    pub fn in_span<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        let instrumented = self.instrument(future);
+        async move {
+            let output = instrumented.await;
+            self.end();
+            output
+        }
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_are_inert_without_a_tracer_provider() {
+        let span = OtelSpan::start("codex.test", vec![attr("attempt", 1_u64)]);
+        assert_eq!(span.traceparent(), None);
+        assert_eq!(OtelSpan::current().traceparent(), None);
+        span.record_error("ignored");
+        span.end();
+    }
+
+    #[test]
+    fn attribute_values_convert() {
+        assert_eq!(attr("k", "v").1, SpanValue::Str("v".to_string()));
+        assert_eq!(attr("k", u64::MAX).1, SpanValue::I64(i64::MAX));
+        assert_eq!(attr("k", true).1, SpanValue::Bool(true));
+    }
+}
@@ -1922,6 +1922,29 @@ pub enum ReviewTarget {
         title: Option<String>,
     },
 
+    /// Review only the changes staged in the index.
+    StagedChanges,
+
+    /// Review the changes across a commit range (`from..to`).
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    CommitRange {
+        /// Exclusive start of the range (e.g. `main`, `HEAD~3`).
+        from: String,
+        /// Inclusive end of the range (usually `HEAD`).
+        to: String,
+    },
+
+    /// Narrow another target to a set of paths or globs.
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    Paths {
+        /// Git pathspecs relative to the repository root; globs are allowed.
+        paths: Vec<String>,
+        /// The diff being narrowed.
+        scope: Box<ReviewTarget>,
+    },
+
     /// Arbitrary instructions provided by the user.
     #[serde(rename_all = "camelCase")]
     #[ts(rename_all = "camelCase")]
@@ -2491,11 +2514,19 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User rewrote the proposed command before approving it; the agent runs
+    /// `command` instead of the original argv.
+    ApprovedWithEdits { command: Vec<String> },
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
     Denied,
 
+    /// User has denied this command and explained why; the feedback is
+    /// returned to the model alongside the rejection.
+    DeniedWithFeedback { feedback: String },
+
     /// User has denied this command and the agent should not do anything until
     /// the user's next command.
     Abort,
@@ -2509,7 +2540,9 @@ impl ReviewDecision {
             ReviewDecision::Approved => "approved",
             ReviewDecision::ApprovedExecpolicyAmendment { .. } => "approved_with_amendment",
             ReviewDecision::ApprovedForSession => "approved_for_session",
+            ReviewDecision::ApprovedWithEdits { .. } => "approved_with_edits",
             ReviewDecision::Denied => "denied",
+            ReviewDecision::DeniedWithFeedback { .. } => "denied_with_feedback",
             ReviewDecision::Abort => "abort",
         }
     }
@@ -18,6 +18,7 @@ use code_login::{AuthManager, AuthMode, ServerOptions};
 use portable_pty::PtySize;
 
 use crate::app_event::AppEvent;
+use crate::app_event::ExternalEditorTarget;
 use crate::bottom_pane::SettingsSection;
 use crate::chatwidget::ChatWidget;
 use crate::cloud_tasks_service;
@@ -62,6 +63,10 @@ impl App<'_> {
         let remote_tx = self.app_event_tx.clone();
         let remote_auth_manager = self._server.auth_manager();
         let remote_provider = self.config.model_provider.clone();
+        if crate::local_model_presets::provider_is_local(&remote_provider) {
+            crate::local_model_presets::spawn_local_model_discovery(remote_tx, remote_provider);
+            return;
+        }
         let remote_code_home = self.config.code_home.clone();
         let remote_using_chatgpt_hint = self.config.using_chatgpt_auth;
         tokio::spawn(async move {
@@ -456,6 +461,14 @@ impl App<'_> {
                             }
                         }
                     }
+                    let Some(translated) = crate::keymap::translate_key_event(
+                        &self.config.tui_keybindings,
+                        crate::keymap::GLOBAL_ACTIONS,
+                        key_event,
+                    ) else {
+                        continue;
+                    };
+                    key_event = translated;
                     // Reset double‑Esc timer on any non‑Esc key
                     if !matches!(key_event.code, KeyCode::Esc) {
                         self.last_esc_time = None;
@@ -660,11 +673,19 @@ impl App<'_> {
                 AppEvent::Paste(text) => {
                     self.dispatch_paste_event(text);
                 }
-                AppEvent::OpenExternalEditor { initial } => {
+                AppEvent::OpenExternalEditor { initial, target } => {
                     let was_alt_screen = self.alt_screen_active;
                     self.input_suspended.store(true, Ordering::Release);
+                    let suffix = match &target {
+                        ExternalEditorTarget::Composer => ".md".to_string(),
+                        ExternalEditorTarget::PatchHunk { path, .. } => path
+                            .extension()
+                            .map(|ext| format!(".{}", ext.to_string_lossy()))
+                            .unwrap_or_else(|| ".txt".to_string()),
+                        ExternalEditorTarget::ExecCommand { .. } => ".sh".to_string(),
+                    };
                     let editor_result = match tui::restore() {
-                        Ok(()) => external_editor::run_editor(&initial),
+                        Ok(()) => external_editor::run_editor(&initial, &suffix),
                         Err(err) => Err(external_editor::ExternalEditorError::LaunchFailed(format!(
                             "Failed to reset terminal: {err}",
                         ))),
@@ -685,7 +706,7 @@ impl App<'_> {
                     self.input_suspended.store(false, Ordering::Release);
                     if let AppState::Chat { widget } = &mut self.app_state {
                         match editor_result {
-                            Ok(text) => widget.set_composer_text(text),
+                            Ok(text) => widget.apply_external_editor_result(target, text),
                             Err(err) => widget.debug_notice(err.to_string()),
                         }
                     }
@@ -974,6 +995,22 @@ impl App<'_> {
                     }
                     self.config.account_switching_mode = mode;
                 }
+                AppEvent::SetAccountPins(pins) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.set_account_pins(pins.clone());
+                    }
+                    self.config.account_pins = pins;
+                }
+                AppEvent::SetAccountBudget { account_id, budget } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.set_account_budget(account_id.clone(), budget);
+                    }
+                    if budget == code_core::config_types::AccountBudget::default() {
+                        self.config.account_budgets.remove(&account_id);
+                    } else {
+                        self.config.account_budgets.insert(account_id, budget);
+                    }
+                }
                 AppEvent::SetTuiShowReasoning(enabled) => {
                     if let AppState::Chat { widget } = &mut self.app_state {
                         widget.set_show_reasoning(enabled);
@@ -1144,6 +1181,16 @@ impl App<'_> {
                         widget.perform_undo_restore(commit.as_deref(), restore_files, restore_conversation);
                     }
                 }
+                AppEvent::ShowUndoFilePicker { commit } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.show_undo_file_picker(&commit);
+                    }
+                }
+                AppEvent::PerformPartialUndo { commit, files, hunks } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.perform_partial_undo(&commit, &files, &hunks);
+                    }
+                }
                 AppEvent::DispatchCommand(command, command_text) => {
                     // Persist UI-only slash commands to cross-session history.
                     // For prompt-expanding commands (/plan, /solve, /code) we let the
@@ -1195,6 +1242,16 @@ impl App<'_> {
                                 widget.handle_merge_command();
                             }
                         }
+                        SlashCommand::Worktrees => {
+                            if let AppState::Chat { widget } = &mut self.app_state {
+                                widget.handle_worktrees_command();
+                            }
+                        }
+                        SlashCommand::Evaluate => {
+                            if let AppState::Chat { widget } = &mut self.app_state {
+                                widget.handle_evaluate_command(command_args);
+                            }
+                        }
                         SlashCommand::Push => {
                             if let AppState::Chat { widget } = &mut self.app_state {
                                 widget.handle_push_command();
@@ -1299,6 +1356,11 @@ impl App<'_> {
                                 self.app_event_tx.send(AppEvent::CodexOp(Op::Compact));
                             }
                         }
+                        SlashCommand::Context => {
+                            if let AppState::Chat { widget } = &mut self.app_state {
+                                widget.handle_context_command();
+                            }
+                        }
                         SlashCommand::Quit => { break 'main; }
                         SlashCommand::Login => {
                             if let AppState::Chat { widget } = &mut self.app_state {
@@ -1528,6 +1590,65 @@ impl App<'_> {
                         widget.handle_resume_picker_load_failed(message);
                     }
                 }
+                AppEvent::WorktreesLoaded(worktrees) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.present_worktrees(worktrees);
+                    }
+                }
+                AppEvent::PruneWorktrees { worktrees, force } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.prune_worktrees(worktrees, force);
+                    }
+                }
+                AppEvent::AgentEvaluationFinished(scores) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.present_agent_evaluation(scores);
+                    }
+                }
+                AppEvent::ApplyEvaluatedCandidate(score) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.apply_evaluated_candidate(*score);
+                    }
+                }
+                AppEvent::JudgeEvaluatedCandidates(scores) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.judge_evaluated_candidates(scores);
+                    }
+                }
+                AppEvent::MergeConflictsDetected { context, files } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.present_merge_conflicts(context, files);
+                    }
+                }
+                AppEvent::MergeConflictAction(action) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.handle_merge_conflict_action(action);
+                    }
+                }
+                AppEvent::MergeConflictsUpdated {
+                    unmerged,
+                    started,
+                    notice,
+                } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.handle_merge_conflicts_updated(unmerged, started, notice);
+                    }
+                }
+                AppEvent::ShowWorktreeDiff(worktree) => {
+                    let tx = self.app_event_tx.clone();
+                    tokio::spawn(async move {
+                        let text = match code_core::worktree_manager::worktree_diff(&worktree, false)
+                            .await
+                        {
+                            Ok(diff) if diff.is_empty() => {
+                                format!("`/worktrees` — no changes in {}", worktree.name)
+                            }
+                            Ok(diff) => diff,
+                            Err(err) => format!("Failed to compute diff: {err}"),
+                        };
+                        tx.send(AppEvent::DiffResult(text));
+                    });
+                }
                 AppEvent::SessionRenameCompleted { message } => {
                     if let AppState::Chat { widget } = &mut self.app_state {
                         widget.debug_notice(message);
@@ -1861,6 +1982,21 @@ impl App<'_> {
                         widget.show_review_custom_prompt();
                     }
                 }
+                AppEvent::OpenReviewRangePrompt => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.show_review_range_prompt();
+                    }
+                }
+                AppEvent::OpenReviewPathsPrompt => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.show_review_paths_prompt();
+                    }
+                }
+                AppEvent::RunReviewTarget { target, auto_resolve } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.start_review_with_target(target, auto_resolve);
+                    }
+                }
                 AppEvent::FetchCloudTasks { environment } => {
                     if let AppState::Chat { widget } = &mut self.app_state {
                         widget.show_cloud_tasks_loading();
@@ -2494,6 +2630,7 @@ impl App<'_> {
                                 code_core::protocol::ReplayHistoryEvent {
                                     items: prefix_items,
                                     history_snapshot: None,
+                                    ghost_snapshots: Vec::new(),
                                 }
                             ),
                             order: None,
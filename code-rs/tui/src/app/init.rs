@@ -69,7 +69,13 @@ impl App<'_> {
             let remote_provider = config.model_provider.clone();
             let remote_code_home = config.code_home.clone();
             let remote_using_chatgpt_hint = config.using_chatgpt_auth;
-            if !crate::chatwidget::is_test_mode() {
+            let test_mode = crate::chatwidget::is_test_mode();
+            if !test_mode && crate::local_model_presets::provider_is_local(&remote_provider) {
+                crate::local_model_presets::spawn_local_model_discovery(
+                    remote_tx,
+                    remote_provider,
+                );
+            } else if !test_mode {
                 tokio::spawn(async move {
                     let remote_manager = code_core::remote_models::RemoteModelsManager::new(
                         remote_auth_manager.clone(),
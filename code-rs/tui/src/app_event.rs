@@ -9,6 +9,7 @@ use code_core::protocol::ApprovedCommandMatchKind;
 use code_core::protocol::TokenUsage;
 use code_core::git_info::CommitLogEntry;
 use code_core::protocol::ReviewContextMetadata;
+use code_protocol::protocol::ReviewTarget;
 use code_file_search::FileMatch;
 use code_common::model_presets::ModelPreset;
 use crossterm::event::KeyEvent;
@@ -53,6 +54,21 @@ impl<T> fmt::Debug for Redacted<T> {
     }
 }
 
+/// Where text returned from the external editor should be delivered.
+#[derive(Debug, Clone)]
+pub(crate) enum ExternalEditorTarget {
+    /// Replace the chat composer contents.
+    Composer,
+    /// Replace one hunk in the per-hunk patch approval view.
+    PatchHunk {
+        call_id: String,
+        path: PathBuf,
+        hunk_index: usize,
+    },
+    /// Replace the command shown in a pending exec approval.
+    ExecCommand { call_id: String },
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct TerminalRunController {
     pub tx: StdSender<TerminalRunEvent>,
@@ -170,8 +186,11 @@ pub(crate) enum AppEvent {
     /// Text pasted from the terminal clipboard.
     Paste(String),
 
-    /// Open the external editor with the current composer text.
-    OpenExternalEditor { initial: String },
+    /// Open the external editor with `initial`; the result is routed to `target`.
+    OpenExternalEditor {
+        initial: String,
+        target: ExternalEditorTarget,
+    },
 
     /// Request to exit the application gracefully.
     ExitRequest,
@@ -252,6 +271,19 @@ pub(crate) enum AppEvent {
         restore_conversation: bool,
     },
 
+    /// Open the file/hunk picker for a partial `/undo` from `commit`.
+    ShowUndoFilePicker {
+        commit: String,
+    },
+
+    /// Restore `files` entirely and the listed hunks of other files from the
+    /// snapshot `commit`, keeping the rest of the workspace as is.
+    PerformPartialUndo {
+        commit: String,
+        files: Vec<std::path::PathBuf>,
+        hunks: Vec<(code_git_tooling::SnapshotFileDiff, Vec<usize>)>,
+    },
+
     /// Switch to a new working directory by rebuilding the chat widget with
     /// the same configuration but a different `cwd`. Optionally submits an
     /// initial prompt once the new session is ready.
@@ -266,6 +298,44 @@ pub(crate) enum AppEvent {
     /// Resume picker failed to load
     ResumePickerLoadFailed { message: String },
 
+    /// `/worktrees` finished scanning Code-managed worktrees
+    WorktreesLoaded(Vec<code_core::worktree_manager::ManagedWorktree>),
+
+    /// Remove worktrees chosen in the `/worktrees` panel
+    PruneWorktrees {
+        worktrees: Vec<code_core::worktree_manager::ManagedWorktree>,
+        force: bool,
+    },
+
+    /// Show a worktree's diff against its base in history
+    ShowWorktreeDiff(code_core::worktree_manager::ManagedWorktree),
+
+    /// `/evaluate` finished scoring agent worktrees, best first
+    AgentEvaluationFinished(Vec<code_core::agent_evaluation::CandidateScore>),
+
+    /// Apply the chosen candidate's diff to the session checkout
+    ApplyEvaluatedCandidate(Box<code_core::agent_evaluation::CandidateScore>),
+
+    /// Ask the model to judge the evaluated candidates
+    JudgeEvaluatedCandidates(Vec<code_core::agent_evaluation::CandidateScore>),
+
+    /// `/merge` trial merge found conflicts
+    MergeConflictsDetected {
+        context: crate::bottom_pane::MergeConflictContext,
+        files: Vec<code_core::merge_conflicts::ConflictedFile>,
+    },
+
+    /// Action chosen in the merge conflicts panel
+    MergeConflictAction(crate::bottom_pane::MergeConflictAction),
+
+    /// Unmerged files re-read from the worktree; `started` is set when the
+    /// merge was just started there
+    MergeConflictsUpdated {
+        unmerged: Vec<code_core::merge_conflicts::ConflictedFile>,
+        started: bool,
+        notice: Option<String>,
+    },
+
     /// Session nickname update finished
     SessionRenameCompleted { message: String },
 
@@ -444,6 +514,16 @@ pub(crate) enum AppEvent {
 
     /// Show the multi-line prompt input to collect custom review instructions.
     OpenReviewCustomPrompt,
+    /// Show the prompt that collects a commit range (`A..B` or a commit count).
+    OpenReviewRangePrompt,
+    /// Show the prompt that collects paths or globs to narrow a review to.
+    OpenReviewPathsPrompt,
+    /// Run a review against a structured target (staged changes, commit
+    /// ranges, path filters).
+    RunReviewTarget {
+        target: ReviewTarget,
+        auto_resolve: bool,
+    },
 
     /// Cloud tasks: fetch the latest list based on the active environment filter.
     FetchCloudTasks { environment: Option<String> },
@@ -678,6 +758,13 @@ pub(crate) enum AppEvent {
     SetAutoSwitchAccountsOnRateLimit(bool),
     SetApiKeyFallbackOnAllAccountsLimited(bool),
     SetAccountSwitchingMode(code_core::config_types::AccountSwitchingMode),
+    /// Replace the configured project pin rules.
+    SetAccountPins(Vec<code_core::config_types::AccountPinRule>),
+    /// Update the usage budget for one account.
+    SetAccountBudget {
+        account_id: String,
+        budget: code_core::config_types::AccountBudget,
+    },
     SetTuiShowReasoning(bool),
     SetTuiShowExploreDetails(bool),
     SetTuiShowBlockTypeLabels(bool),
@@ -2,13 +2,42 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::colors;
 use crossterm::event::{KeyCode, KeyEvent};
-use code_core::config_types::AccountSwitchingMode;
+use code_core::config_types::{AccountBudget, AccountPinRule, AccountSwitchingMode};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::prelude::Widget;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Paragraph, Wrap};
+use std::path::PathBuf;
+
+/// Budget caps offered when cycling a window's budget; `None` means no cap.
+const BUDGET_STEPS: [Option<f64>; 6] = [
+    None,
+    Some(50.0),
+    Some(60.0),
+    Some(70.0),
+    Some(80.0),
+    Some(90.0),
+];
+
+/// Project pin and budget state shown alongside the switching toggles.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AccountPolicyState {
+    /// Id and display name of the active account.
+    pub(crate) active_account: Option<(String, String)>,
+    /// Git root of the current project, when inside a repository.
+    pub(crate) project_root: Option<PathBuf>,
+    pub(crate) pins: Vec<AccountPinRule>,
+    /// Index into `pins` of the rule that applies to this project.
+    pub(crate) matching_pin: Option<usize>,
+    /// Display name of the account the matching rule pins to.
+    pub(crate) pinned_account_label: Option<String>,
+    /// Budget configured for the active account.
+    pub(crate) budget: AccountBudget,
+    /// Recent automatic switches, newest last, already formatted.
+    pub(crate) recent_switches: Vec<String>,
+}
 
 pub(crate) struct AccountSwitchSettingsView {
     app_event_tx: AppEventSender,
@@ -16,6 +45,7 @@ pub(crate) struct AccountSwitchSettingsView {
     auto_switch_enabled: bool,
     api_key_fallback_enabled: bool,
     switching_mode: AccountSwitchingMode,
+    policy: AccountPolicyState,
     is_complete: bool,
 }
 
@@ -25,6 +55,7 @@ impl AccountSwitchSettingsView {
         auto_switch_enabled: bool,
         api_key_fallback_enabled: bool,
         switching_mode: AccountSwitchingMode,
+        policy: AccountPolicyState,
     ) -> Self {
         Self {
             app_event_tx,
@@ -32,12 +63,13 @@ impl AccountSwitchSettingsView {
             auto_switch_enabled,
             api_key_fallback_enabled,
             switching_mode,
+            policy,
             is_complete: false,
         }
     }
 
     fn option_count() -> usize {
-        4
+        7
     }
 
     fn toggle_auto_switch(&mut self) {
@@ -100,12 +132,89 @@ impl AccountSwitchSettingsView {
             .send(AppEvent::SetAccountSwitchingMode(self.switching_mode));
     }
 
+    /// Remove the rule pinning this project, or pin the project's git root to
+    /// the active account when no rule applies yet.
+    fn toggle_project_pin(&mut self) {
+        if let Some(idx) = self.policy.matching_pin.take() {
+            self.policy.pins.remove(idx);
+            self.policy.pinned_account_label = None;
+        } else {
+            let (Some((account_id, label)), Some(root)) = (
+                self.policy.active_account.clone(),
+                self.policy.project_root.as_ref(),
+            ) else {
+                return;
+            };
+            self.policy.pins.push(AccountPinRule {
+                account: account_id,
+                path: Some(root.to_string_lossy().into_owned()),
+                remote: None,
+                strict: false,
+            });
+            self.policy.matching_pin = Some(self.policy.pins.len() - 1);
+            self.policy.pinned_account_label = Some(label);
+        }
+        self.app_event_tx
+            .send(AppEvent::SetAccountPins(self.policy.pins.clone()));
+    }
+
+    fn next_budget_step(current: Option<f64>) -> Option<f64> {
+        let idx = BUDGET_STEPS
+            .iter()
+            .position(|step| *step == current)
+            .unwrap_or(0);
+        BUDGET_STEPS[(idx + 1) % BUDGET_STEPS.len()]
+    }
+
+    fn cycle_budget(&mut self, secondary: bool) {
+        let Some((account_id, _)) = self.policy.active_account.clone() else {
+            return;
+        };
+        let budget = &mut self.policy.budget;
+        if secondary {
+            budget.secondary_percent = Self::next_budget_step(budget.secondary_percent);
+        } else {
+            budget.primary_percent = Self::next_budget_step(budget.primary_percent);
+        }
+        self.app_event_tx.send(AppEvent::SetAccountBudget {
+            account_id,
+            budget: *budget,
+        });
+    }
+
+    fn budget_label(percent: Option<f64>) -> String {
+        match percent {
+            Some(percent) => format!("{percent:.0}%"),
+            None => "off".to_string(),
+        }
+    }
+
+    fn pin_description(&self) -> String {
+        match (self.policy.matching_pin, self.policy.project_root.as_ref()) {
+            (Some(idx), _) => {
+                let rule = &self.policy.pins[idx];
+                let account = self
+                    .policy
+                    .pinned_account_label
+                    .clone()
+                    .unwrap_or_else(|| rule.account.clone());
+                let strict = if rule.strict { ", strict" } else { "" };
+                format!("Pinned to {account} ({}{strict}).", rule.describe())
+            }
+            (None, Some(root)) => format!("No pin for {}.", root.display()),
+            (None, None) => "Not in a git repository; pins need a project.".to_string(),
+        }
+    }
+
     fn activate_selected(&mut self) {
         match self.selected_index {
             0 => self.toggle_auto_switch(),
             1 => self.toggle_api_key_fallback(),
             2 => self.cycle_switching_mode(),
-            3 => self.close(),
+            3 => self.toggle_project_pin(),
+            4 => self.cycle_budget(false),
+            5 => self.cycle_budget(true),
+            6 => self.close(),
             _ => {}
         }
     }
@@ -195,9 +304,56 @@ impl AccountSwitchSettingsView {
             Span::styled(Self::switching_mode_description(self.switching_mode), dim),
         ]));
 
+        lines.push(row_toggle(
+            3,
+            "Pin this project to the active account",
+            self.policy.matching_pin.is_some(),
+        ));
+        lines.push(Line::from(vec![
+            Span::raw("    "),
+            Span::styled(self.pin_description(), dim),
+        ]));
+
+        let active_label = self
+            .policy
+            .active_account
+            .as_ref()
+            .map(|(_, label)| label.as_str())
+            .unwrap_or("no active account");
+        lines.push(row_mode(
+            4,
+            "5-hour budget",
+            &Self::budget_label(self.policy.budget.primary_percent),
+        ));
+        lines.push(row_mode(
+            5,
+            "Weekly budget",
+            &Self::budget_label(self.policy.budget.secondary_percent),
+        ));
+        lines.push(Line::from(vec![
+            Span::raw("    "),
+            Span::styled(
+                format!(
+                    "Caps for {active_label}; over-budget accounts are skipped when switching."
+                ),
+                dim,
+            ),
+        ]));
+
+        if !self.policy.recent_switches.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![Span::styled("Recent switches", normal)]));
+            for entry in &self.policy.recent_switches {
+                lines.push(Line::from(vec![
+                    Span::raw("    "),
+                    Span::styled(entry.clone(), dim),
+                ]));
+            }
+        }
+
         lines.push(Line::from(""));
 
-        let close_selected = self.selected_index == 3;
+        let close_selected = self.selected_index == 6;
         let close_style = if close_selected { highlight } else { normal };
         let indicator = if close_selected { ">" } else { " " };
         lines.push(Line::from(vec![
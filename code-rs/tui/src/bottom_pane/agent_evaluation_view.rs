@@ -0,0 +1,339 @@
+use code_core::agent_evaluation::CandidateScore;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::bottom_pane_view::{BottomPaneView, ConditionalUpdate};
+use super::{BottomPane, CancellationEvent};
+
+const MAX_VISIBLE_ROWS: usize = 8;
+
+/// Ranked `/evaluate` results: one row per agent worktree with commands
+/// passed, harness findings and diff size. The top row is the winner.
+pub(crate) struct AgentEvaluationView {
+    scores: Vec<CandidateScore>,
+    selected: usize,
+    top: usize,
+    app_event_tx: AppEventSender,
+    is_complete: bool,
+}
+
+impl AgentEvaluationView {
+    pub fn new(scores: Vec<CandidateScore>, app_event_tx: AppEventSender) -> Self {
+        Self {
+            scores,
+            selected: 0,
+            top: 0,
+            app_event_tx,
+            is_complete: false,
+        }
+    }
+
+    fn move_up(&mut self) {
+        let len = self.scores.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if self.selected == 0 {
+            len - 1
+        } else {
+            self.selected - 1
+        };
+        self.ensure_visible();
+    }
+
+    fn move_down(&mut self) {
+        let len = self.scores.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected + 1) % len;
+        self.ensure_visible();
+    }
+
+    fn ensure_visible(&mut self) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + MAX_VISIBLE_ROWS {
+            self.top = self.selected + 1 - MAX_VISIBLE_ROWS;
+        }
+    }
+
+    fn apply(&mut self) {
+        let Some(score) = self.scores.get(self.selected) else {
+            return;
+        };
+        if !score.is_viable() {
+            return;
+        }
+        self.app_event_tx
+            .send(AppEvent::ApplyEvaluatedCandidate(Box::new(score.clone())));
+        self.is_complete = true;
+    }
+
+    fn diff(&mut self) {
+        if let Some(score) = self.scores.get(self.selected) {
+            let text = if score.patch.is_empty() {
+                format!("`/evaluate` — {} made no changes", score.label)
+            } else {
+                score.patch.clone()
+            };
+            self.app_event_tx.send(AppEvent::DiffResult(text));
+            self.is_complete = true;
+        }
+    }
+
+    fn judge(&mut self) {
+        if self.scores.is_empty() {
+            return;
+        }
+        self.app_event_tx
+            .send(AppEvent::JudgeEvaluatedCandidates(self.scores.clone()));
+        self.is_complete = true;
+    }
+
+    fn row_lines(&self) -> Vec<Line<'static>> {
+        let end = (self.top + MAX_VISIBLE_ROWS).min(self.scores.len());
+        let mut lines = Vec::new();
+        for idx in self.top..end {
+            let score = &self.scores[idx];
+            let selected = idx == self.selected;
+            let base = if selected {
+                Style::default().bg(crate::colors::selection())
+            } else {
+                Style::default()
+            };
+            let marker = if selected { "›" } else { " " };
+            let label_style = if score.is_viable() {
+                base.fg(crate::colors::text()).add_modifier(Modifier::BOLD)
+            } else {
+                base.fg(crate::colors::text_dim())
+            };
+            let mut spans = vec![
+                Span::styled(format!("{marker} "), base.fg(crate::colors::primary())),
+                Span::styled(
+                    format!("#{} ", score.rank),
+                    base.fg(crate::colors::text_dim()),
+                ),
+                Span::styled(format!("{} ", score.label), label_style),
+            ];
+            if let Some(err) = &score.error {
+                spans.push(Span::styled(
+                    format!("not scored: {err}"),
+                    base.fg(crate::colors::error()),
+                ));
+                lines.push(Line::from(spans));
+                continue;
+            }
+            let passed = score.commands_passed();
+            let total = score.commands.len();
+            if total > 0 {
+                let style = if passed == total {
+                    base.fg(crate::colors::success())
+                } else {
+                    base.fg(crate::colors::error())
+                };
+                spans.push(Span::styled(format!("cmds {passed}/{total}  "), style));
+            }
+            let findings_style = if score.findings.is_empty() {
+                base.fg(crate::colors::text_dim())
+            } else {
+                base.fg(crate::colors::warning())
+            };
+            spans.push(Span::styled(
+                format!("findings {}  ", score.findings.len()),
+                findings_style,
+            ));
+            spans.push(Span::styled(
+                format!(
+                    "{} file(s) +{} -{}",
+                    score.files_changed, score.insertions, score.deletions
+                ),
+                base.fg(crate::colors::text_dim()),
+            ));
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
+    fn detail_line(&self) -> Line<'static> {
+        let dim = Style::default().fg(crate::colors::text_dim());
+        let Some(score) = self.scores.get(self.selected) else {
+            return Line::from("");
+        };
+        let mut spans = Vec::new();
+        for command in &score.commands {
+            let (mark, style) = if command.passed {
+                ("✓", Style::default().fg(crate::colors::success()))
+            } else {
+                ("✗", Style::default().fg(crate::colors::error()))
+            };
+            spans.push(Span::styled(format!("{mark} {}", command.name), style));
+            let suffix = if command.timed_out {
+                " (timeout)  ".to_string()
+            } else {
+                format!(" {:.1}s  ", command.duration_ms as f64 / 1000.0)
+            };
+            spans.push(Span::styled(suffix, dim));
+        }
+        if let Some(finding) = score.findings.first() {
+            spans.push(Span::styled(
+                finding.clone(),
+                Style::default().fg(crate::colors::warning()),
+            ));
+        } else if spans.is_empty() {
+            let branch = score.branch.as_deref().unwrap_or("(detached)");
+            spans.push(Span::styled(
+                format!("{branch}  {}", score.worktree.display()),
+                dim,
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        let key = |text: &'static str| {
+            Span::styled(text, Style::default().fg(crate::colors::light_blue()))
+        };
+        Line::from(vec![
+            key("Enter"),
+            Span::raw(" Apply  "),
+            key("d"),
+            Span::raw(" Diff  "),
+            key("j"),
+            Span::raw(" Judge review  "),
+            Span::styled("Esc", Style::default().fg(crate::colors::error())),
+            Span::raw(" Close"),
+        ])
+    }
+}
+
+impl<'a> BottomPaneView<'a> for AgentEvaluationView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Enter | KeyCode::Char('a') | KeyCode::Char('A') => self.apply(),
+            KeyCode::Char('d') | KeyCode::Char('D') => self.diff(),
+            KeyCode::Char('j') | KeyCode::Char('J') => self.judge(),
+            KeyCode::Esc => self.is_complete = true,
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'a>) -> CancellationEvent {
+        self.is_complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn update_status_text(&mut self, _text: String) -> ConditionalUpdate {
+        ConditionalUpdate::NeedsRedraw
+    }
+
+    fn desired_height(&self, _width: u16) -> u16 {
+        let rows = self.scores.len().clamp(1, MAX_VISIBLE_ROWS);
+        // Borders, detail line, and footer.
+        (rows + 4) as u16
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Agent evaluation ({}) ", self.scores.len()))
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [rows_area, detail_area, footer_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        Paragraph::new(self.row_lines())
+            .style(Style::default().bg(crate::colors::background()))
+            .render(rows_area, buf);
+        Paragraph::new(self.detail_line())
+            .style(Style::default().bg(crate::colors::background()))
+            .render(detail_area, buf);
+        Paragraph::new(self.footer_line())
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::mpsc::channel;
+
+    fn score(label: &str, rank: usize, patch: &str) -> CandidateScore {
+        CandidateScore {
+            agent_id: label.to_string(),
+            label: label.to_string(),
+            worktree: PathBuf::from(format!("/tmp/{label}")),
+            branch: None,
+            base_commit: None,
+            patch: patch.to_string(),
+            files_changed: 1,
+            insertions: 1,
+            deletions: 0,
+            commands: Vec::new(),
+            findings: Vec::new(),
+            checks_ran: Vec::new(),
+            error: None,
+            rank,
+        }
+    }
+
+    #[test]
+    fn apply_sends_selected_candidate_and_skips_empty_diffs() {
+        let (tx, rx) = channel();
+        let mut view = AgentEvaluationView::new(
+            vec![
+                score("winner", 1, "diff --git a/x b/x\n"),
+                score("empty", 2, ""),
+            ],
+            AppEventSender::new(tx),
+        );
+        view.move_down();
+        view.apply();
+        assert!(rx.try_recv().is_err());
+        assert!(!view.is_complete);
+
+        view.move_down();
+        view.apply();
+        match rx.try_recv() {
+            Ok(AppEvent::ApplyEvaluatedCandidate(score)) => assert_eq!(score.label, "winner"),
+            other => panic!("expected ApplyEvaluatedCandidate, got {other:?}"),
+        }
+        assert!(view.is_complete);
+    }
+}
@@ -3,6 +3,7 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::widgets::WidgetRef;
 
+use crate::app_event::ExternalEditorTarget;
 use crate::app_event_sender::AppEventSender;
 use crate::chatwidget::BackgroundOrderTicket;
 use crate::user_approval_widget::ApprovalRequest;
@@ -11,6 +12,7 @@ use crate::user_approval_widget::UserApprovalWidget;
 use super::BottomPane;
 use super::BottomPaneView;
 use super::CancellationEvent;
+use super::ConditionalUpdate;
 use std::collections::VecDeque;
 
 /// Modal overlay asking the user to approve/deny a sequence of requests.
@@ -76,6 +78,18 @@ impl<'a> BottomPaneView<'a> for ApprovalModalView<'a> {
         (&self.current).render_ref(area, buf);
     }
 
+    fn apply_external_edit(&mut self, target: &ExternalEditorTarget, text: String) -> bool {
+        self.current.apply_external_edit(target, text)
+    }
+
+    fn handle_paste(&mut self, text: String) -> ConditionalUpdate {
+        if self.current.handle_paste(text) {
+            ConditionalUpdate::NeedsRedraw
+        } else {
+            ConditionalUpdate::NoRedraw
+        }
+    }
+
     fn try_consume_approval_request(
         &mut self,
         req: ApprovalRequest,
@@ -1,3 +1,4 @@
+use crate::app_event::ExternalEditorTarget;
 use crate::chatwidget::BackgroundOrderTicket;
 use crate::user_approval_widget::ApprovalRequest;
 use crossterm::event::KeyEvent;
@@ -82,6 +83,12 @@ pub(crate) trait BottomPaneView<'a> {
         Some((request, ticket))
     }
 
+    /// Deliver text returned from the external editor to the view that
+    /// requested it. Returns `false` when this view does not own `target`.
+    fn apply_external_edit(&mut self, _target: &ExternalEditorTarget, _text: String) -> bool {
+        false
+    }
+
     /// Handle pasted text while this view is active. Return whether a redraw
     /// is needed. Default: ignore paste.
     fn handle_paste(&mut self, _text: String) -> ConditionalUpdate {
@@ -14,6 +14,9 @@ use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
 use super::file_search_popup::FileSearchPopup;
 use super::paste_burst::PasteBurst;
+use super::vim_mode::VimMode;
+use super::vim_mode::VimOutcome;
+use super::vim_mode::VimState;
 use crate::slash_command::{built_in_slash_commands, SlashCommand};
 use code_protocol::custom_prompts::CustomPrompt;
 use code_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
@@ -174,6 +177,8 @@ pub(crate) struct ChatComposer {
     // Auto Review status displayed in the footer
     auto_review_status: Option<AutoReviewFooterStatus>,
     auto_review_symbols_only: bool,
+    // Modal vim editing state; `None` unless `tui.vim_mode` is enabled.
+    vim: Option<VimState>,
     // Agent hint label to display alongside Auto Review footer state
     agent_hint_label: AgentHintLabel,
     // Persistent/ephemeral access-mode indicator shown on the left
@@ -377,6 +382,7 @@ impl ChatComposer {
             standard_terminal_hint: None,
             auto_review_status: None,
             auto_review_symbols_only: false,
+            vim: None,
             agent_hint_label: AgentHintLabel::Agents,
             access_mode_label: None,
             access_mode_label_expiry: None,
@@ -415,6 +421,52 @@ impl ChatComposer {
         self.auto_review_symbols_only = enabled;
     }
 
+    pub(crate) fn set_vim_mode(&mut self, enabled: bool) {
+        match (enabled, self.vim.is_some()) {
+            (true, false) => self.vim = Some(VimState::new()),
+            (false, true) => self.vim = None,
+            _ => {}
+        }
+    }
+
+    pub(crate) fn vim_wants_escape(&self) -> bool {
+        self.has_focus
+            && matches!(self.active_popup, ActivePopup::None)
+            && self.vim.as_ref().is_some_and(VimState::wants_escape)
+    }
+
+    pub(crate) fn vim_escape(&mut self) {
+        if let Some(vim) = self.vim.as_mut() {
+            vim.escape(&mut self.textarea);
+        }
+    }
+
+    /// Route a key through vim normal/visual mode. Returns `None` when the
+    /// composer should handle the key itself.
+    fn handle_vim_key(&mut self, key_event: KeyEvent) -> Option<(InputResult, bool)> {
+        let vim = self.vim.as_mut()?;
+        if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+            return None;
+        }
+        if vim.mode() == VimMode::Insert {
+            vim.record_insert_key(key_event);
+            return None;
+        }
+        let text_before = self.textarea.text().to_string();
+        match vim.handle_key(&mut self.textarea, key_event) {
+            VimOutcome::PassThrough => None,
+            VimOutcome::Handled => {
+                if self.textarea.text() != text_before {
+                    self.history.reset_navigation();
+                    self.pending_pastes
+                        .retain(|(placeholder, _)| self.textarea.text().contains(placeholder));
+                    self.typed_anything = true;
+                }
+                Some((InputResult::None, true))
+            }
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn auto_review_status(&self) -> Option<AutoReviewFooterStatus> {
         self.auto_review_status
@@ -1091,6 +1143,13 @@ impl ChatComposer {
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
         let now = Instant::now();
 
+        if matches!(self.active_popup, ActivePopup::None)
+            && let Some(result) = self.handle_vim_key(key_event)
+        {
+            self.resync_popups();
+            return result;
+        }
+
         // Track rapid plain-character bursts (common when bracketed paste is
         // unavailable) so we can suppress Enter-based submissions and insert
         // literal newlines instead.
@@ -1178,6 +1237,12 @@ impl ChatComposer {
         // Update (or hide/show) popup after processing the key.
         self.resync_popups();
 
+        if matches!(result.0, InputResult::Submitted(_) | InputResult::Command(_))
+            && let Some(vim) = self.vim.as_mut()
+        {
+            vim.reset();
+        }
+
         result
     }
 
@@ -2291,6 +2356,14 @@ impl ChatComposer {
         let mut left_sections: Vec<(u8, Vec<Span<'static>>, bool)> = Vec::new();
         let mut right_sections: Vec<(u8, Vec<Span<'static>>, bool)> = Vec::new();
 
+        if let Some(vim) = self.vim.as_ref() {
+            let mode_style = match vim.mode() {
+                VimMode::Insert => label_style,
+                VimMode::Normal | VimMode::Visual => key_hint_style.add_modifier(Modifier::BOLD),
+            };
+            left_sections.push((0, vec![Span::styled(vim.mode().label(), mode_style)], true));
+        }
+
         if let Some(status) = self.auto_review_status {
             let (status_spans, _) = Self::auto_review_footer_sections(
                 status,
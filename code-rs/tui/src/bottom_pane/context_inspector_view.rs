@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use code_core::protocol::ContextEdit;
+use code_core::protocol::ContextEditAction;
+use code_core::protocol::ContextInspectionEvent;
+use code_core::protocol::ContextItemSummary;
+use code_core::protocol::Op;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::bottom_pane_view::{BottomPaneView, ConditionalUpdate};
+use super::{BottomPane, CancellationEvent};
+
+const MAX_VISIBLE_ITEMS: usize = 10;
+
+/// Breakdown of the model context by category, with a picker that marks the
+/// largest history entries for dropping or collapsing.
+pub(crate) struct ContextInspectorView {
+    inspection: ContextInspectionEvent,
+    pending: HashMap<String, ContextEditAction>,
+    selected: usize,
+    top: usize,
+    app_event_tx: AppEventSender,
+    is_complete: bool,
+}
+
+impl ContextInspectorView {
+    pub fn new(inspection: ContextInspectionEvent, app_event_tx: AppEventSender) -> Self {
+        Self {
+            inspection,
+            pending: HashMap::new(),
+            selected: 0,
+            top: 0,
+            app_event_tx,
+            is_complete: false,
+        }
+    }
+
+    fn items(&self) -> &[ContextItemSummary] {
+        &self.inspection.largest_items
+    }
+
+    fn move_up(&mut self) {
+        let len = self.items().len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if self.selected == 0 { len - 1 } else { self.selected - 1 };
+        self.ensure_visible();
+    }
+
+    fn move_down(&mut self) {
+        let len = self.items().len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected + 1) % len;
+        self.ensure_visible();
+    }
+
+    fn ensure_visible(&mut self) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + MAX_VISIBLE_ITEMS {
+            self.top = self.selected + 1 - MAX_VISIBLE_ITEMS;
+        }
+    }
+
+    fn toggle(&mut self, action: ContextEditAction) {
+        let Some(item) = self.items().get(self.selected) else {
+            return;
+        };
+        let allowed = match action {
+            ContextEditAction::Drop => item.can_drop,
+            ContextEditAction::Collapse => item.can_collapse,
+        };
+        if !allowed {
+            return;
+        }
+        let id = item.id.clone();
+        if self.pending.get(&id) == Some(&action) {
+            self.pending.remove(&id);
+        } else {
+            self.pending.insert(id, action);
+        }
+    }
+
+    fn confirm(&mut self) {
+        if !self.pending.is_empty() {
+            // Keep the list order so edits apply in a predictable sequence.
+            let edits = self
+                .items()
+                .iter()
+                .filter_map(|item| {
+                    self.pending.get(&item.id).map(|action| ContextEdit {
+                        item_id: item.id.clone(),
+                        action: *action,
+                    })
+                })
+                .collect();
+            self.app_event_tx.send(AppEvent::CodexOp(Op::EditContext { edits }));
+        }
+        self.is_complete = true;
+    }
+
+    fn percent_of_window(&self, tokens: u64) -> Option<String> {
+        let window = self.inspection.context_window.filter(|window| *window > 0)?;
+        Some(format!("{:.1}%", tokens as f64 * 100.0 / window as f64))
+    }
+
+    fn summary_lines(&self) -> Vec<Line<'static>> {
+        let dim = Style::default().fg(crate::colors::text_dim());
+        let mut lines = Vec::new();
+        let total = match self.inspection.context_window {
+            Some(window) => format!(
+                "~{} of {} tokens ({})",
+                format_tokens(self.inspection.total_tokens),
+                format_tokens(window),
+                self.percent_of_window(self.inspection.total_tokens)
+                    .unwrap_or_default(),
+            ),
+            None => format!("~{} tokens", format_tokens(self.inspection.total_tokens)),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Total ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(total),
+        ]));
+        for usage in &self.inspection.categories {
+            let mut spans = vec![
+                Span::raw(format!("  {:<22}", usage.category.label())),
+                Span::styled(
+                    format!("{:>8}", format_tokens(usage.tokens)),
+                    Style::default().fg(crate::colors::text()),
+                ),
+            ];
+            if let Some(percent) = self.percent_of_window(usage.tokens) {
+                spans.push(Span::styled(format!("  {percent:>6}"), dim));
+            }
+            spans.push(Span::styled(format!("  {} items", usage.items), dim));
+            lines.push(Line::from(spans));
+        }
+        if let Some(notice) = &self.inspection.notice {
+            lines.push(Line::from(Span::styled(
+                notice.clone(),
+                Style::default().fg(crate::colors::success()),
+            )));
+        }
+        lines
+    }
+
+    fn item_lines(&self) -> Vec<Line<'static>> {
+        if self.items().is_empty() {
+            return vec![Line::from(Span::styled(
+                "Nothing in history yet.",
+                Style::default().fg(crate::colors::text_dim()),
+            ))];
+        }
+        let end = (self.top + MAX_VISIBLE_ITEMS).min(self.items().len());
+        let mut lines = Vec::new();
+        for (idx, item) in self.items()[self.top..end].iter().enumerate() {
+            let selected = self.top + idx == self.selected;
+            let base = if selected {
+                Style::default().bg(crate::colors::selection())
+            } else {
+                Style::default()
+            };
+            let marker = if selected { "›" } else { " " };
+            let (mark, mark_style) = match self.pending.get(&item.id) {
+                Some(ContextEditAction::Drop) => ("drop", base.fg(crate::colors::error())),
+                Some(ContextEditAction::Collapse) => {
+                    ("fold", base.fg(crate::colors::light_blue()))
+                }
+                None if !item.can_drop && !item.can_collapse => {
+                    ("lock", base.fg(crate::colors::text_dim()))
+                }
+                None => ("    ", base),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{marker} "), base.fg(crate::colors::primary())),
+                Span::styled(format!("{mark} "), mark_style),
+                Span::styled(
+                    format!("{:>7} ", format_tokens(item.tokens)),
+                    base.fg(crate::colors::text()),
+                ),
+                Span::styled(
+                    format!("{:<20} ", item.category.label()),
+                    base.fg(crate::colors::text_dim()),
+                ),
+                Span::styled(item.label.clone(), base.fg(crate::colors::text())),
+            ]));
+        }
+        lines
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        let key = |text: &'static str| {
+            Span::styled(text, Style::default().fg(crate::colors::light_blue()))
+        };
+        let apply = if self.pending.is_empty() { " Close  " } else { " Apply  " };
+        Line::from(vec![
+            key("↑↓"),
+            Span::raw(" Navigate  "),
+            key("D"),
+            Span::raw(" Drop  "),
+            key("C"),
+            Span::raw(" Collapse  "),
+            Span::styled("Enter", Style::default().fg(crate::colors::success())),
+            Span::raw(apply),
+            Span::styled("Esc", Style::default().fg(crate::colors::error())),
+            Span::raw(" Cancel"),
+        ])
+    }
+}
+
+impl<'a> BottomPaneView<'a> for ContextInspectorView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Char('d') | KeyCode::Char('D') => self.toggle(ContextEditAction::Drop),
+            KeyCode::Char('c') | KeyCode::Char('C') => self.toggle(ContextEditAction::Collapse),
+            KeyCode::Enter => self.confirm(),
+            KeyCode::Esc => self.is_complete = true,
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'a>) -> CancellationEvent {
+        self.is_complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn update_status_text(&mut self, _text: String) -> ConditionalUpdate {
+        ConditionalUpdate::NeedsRedraw
+    }
+
+    fn desired_height(&self, _width: u16) -> u16 {
+        let summary = self.summary_lines().len();
+        let items = self.items().len().clamp(1, MAX_VISIBLE_ITEMS);
+        // Outer border, items border, and footer.
+        (summary + items + 6) as u16
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Context window ")
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(Style::default().bg(crate::colors::background()).fg(crate::colors::text()))
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let summary = self.summary_lines();
+        let [summary_area, items_area, footer_area] = Layout::vertical([
+            Constraint::Length(summary.len() as u16),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        Paragraph::new(summary)
+            .style(Style::default().bg(crate::colors::background()).fg(crate::colors::text()))
+            .render(summary_area, buf);
+
+        let items_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Largest items ")
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(Style::default().bg(crate::colors::background()).fg(crate::colors::text()));
+        let items_inner = items_block.inner(items_area);
+        items_block.render(items_area, buf);
+        Paragraph::new(self.item_lines())
+            .style(Style::default().bg(crate::colors::background()))
+            .render(items_inner, buf);
+
+        Paragraph::new(self.footer_line())
+            .style(Style::default().bg(crate::colors::background()).fg(crate::colors::text()))
+            .render(footer_area, buf);
+    }
+}
+
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 10_000 {
+        format!("{}k", tokens / 1000)
+    } else if tokens >= 1000 {
+        format!("{:.1}k", tokens as f64 / 1000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::protocol::ContextCategory;
+    use std::sync::mpsc::channel;
+
+    fn item(id: &str, can_drop: bool, can_collapse: bool) -> ContextItemSummary {
+        ContextItemSummary {
+            id: id.to_string(),
+            category: ContextCategory::ToolOutputs,
+            label: id.to_string(),
+            tokens: 100,
+            can_drop,
+            can_collapse,
+        }
+    }
+
+    #[test]
+    fn toggles_respect_item_capabilities() {
+        let (tx, _rx) = channel();
+        let inspection = ContextInspectionEvent {
+            total_tokens: 200,
+            context_window: Some(1000),
+            categories: Vec::new(),
+            largest_items: vec![item("h0", true, true), item("p0", false, false)],
+            notice: None,
+        };
+        let mut view = ContextInspectorView::new(inspection, AppEventSender::new(tx));
+        view.toggle(ContextEditAction::Collapse);
+        assert_eq!(view.pending.get("h0"), Some(&ContextEditAction::Collapse));
+        view.toggle(ContextEditAction::Drop);
+        assert_eq!(view.pending.get("h0"), Some(&ContextEditAction::Drop));
+        view.toggle(ContextEditAction::Drop);
+        assert!(view.pending.is_empty());
+
+        view.move_down();
+        view.toggle(ContextEditAction::Drop);
+        assert!(view.pending.is_empty());
+    }
+}
@@ -0,0 +1,418 @@
+use std::path::PathBuf;
+
+use code_core::merge_conflicts::ConflictKind;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::bottom_pane_view::{BottomPaneView, ConditionalUpdate};
+use super::{BottomPane, CancellationEvent};
+
+const MAX_VISIBLE_ROWS: usize = 10;
+
+/// Branches and checkouts involved in a conflicting `/merge`.
+#[derive(Debug, Clone)]
+pub(crate) struct MergeConflictContext {
+    pub worktree_path: PathBuf,
+    pub worktree_branch: String,
+    pub default_branch: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MergeFileState {
+    /// Conflict markers remain.
+    Conflicted,
+    /// Markers are gone; waiting for the user to approve the result.
+    Resolved,
+    /// Approved and staged.
+    Approved,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MergeConflictRow {
+    pub path: String,
+    pub kind: ConflictKind,
+    pub hunks: usize,
+    pub state: MergeFileState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MergeConflictAction {
+    /// Start (or continue) the agent turn that resolves conflicted files.
+    Resolve,
+    Approve(String),
+    Reject(String),
+    ShowDiff(String),
+    /// Commit the approved merge and run the validation turn.
+    Finish,
+    /// Abandon the in-progress merge in the worktree.
+    Abort,
+}
+
+/// Conflicted files from a trial `/merge`, and once resolution has started,
+/// per-file approval of the agent's edits.
+pub(crate) struct MergeConflictsView {
+    context: MergeConflictContext,
+    rows: Vec<MergeConflictRow>,
+    /// False while only the trial merge has run; the worktree is untouched.
+    resolving: bool,
+    notice: Option<String>,
+    selected: usize,
+    top: usize,
+    app_event_tx: AppEventSender,
+    is_complete: bool,
+}
+
+impl MergeConflictsView {
+    pub fn new(
+        context: MergeConflictContext,
+        rows: Vec<MergeConflictRow>,
+        resolving: bool,
+        notice: Option<String>,
+        app_event_tx: AppEventSender,
+    ) -> Self {
+        Self {
+            context,
+            rows,
+            resolving,
+            notice,
+            selected: 0,
+            top: 0,
+            app_event_tx,
+            is_complete: false,
+        }
+    }
+
+    fn move_up(&mut self) {
+        let len = self.rows.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if self.selected == 0 {
+            len - 1
+        } else {
+            self.selected - 1
+        };
+        self.ensure_visible();
+    }
+
+    fn move_down(&mut self) {
+        let len = self.rows.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected + 1) % len;
+        self.ensure_visible();
+    }
+
+    fn ensure_visible(&mut self) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + MAX_VISIBLE_ROWS {
+            self.top = self.selected + 1 - MAX_VISIBLE_ROWS;
+        }
+    }
+
+    fn all_approved(&self) -> bool {
+        self.rows
+            .iter()
+            .all(|row| row.state == MergeFileState::Approved)
+    }
+
+    fn send(&mut self, action: MergeConflictAction) {
+        self.app_event_tx
+            .send(AppEvent::MergeConflictAction(action));
+        self.is_complete = true;
+    }
+
+    fn selected_row(&self) -> Option<&MergeConflictRow> {
+        self.rows.get(self.selected)
+    }
+
+    fn approve(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        match row.state {
+            MergeFileState::Resolved => {
+                let path = row.path.clone();
+                self.send(MergeConflictAction::Approve(path));
+            }
+            MergeFileState::Conflicted => {
+                self.notice = Some(format!("{} still has conflict markers", row.path));
+            }
+            MergeFileState::Approved => {}
+        }
+    }
+
+    fn reject(&mut self) {
+        if let Some(row) = self.selected_row()
+            && row.state != MergeFileState::Conflicted
+        {
+            let path = row.path.clone();
+            self.send(MergeConflictAction::Reject(path));
+        }
+    }
+
+    fn confirm(&mut self) {
+        if !self.resolving {
+            self.send(MergeConflictAction::Resolve);
+        } else if self.all_approved() {
+            self.send(MergeConflictAction::Finish);
+        } else if self
+            .rows
+            .iter()
+            .any(|row| row.state == MergeFileState::Conflicted)
+        {
+            self.send(MergeConflictAction::Resolve);
+        } else {
+            self.notice = Some("Approve (a) or reject (r) each resolved file first".to_string());
+        }
+    }
+
+    fn row_lines(&self) -> Vec<Line<'static>> {
+        let end = (self.top + MAX_VISIBLE_ROWS).min(self.rows.len());
+        let mut lines = Vec::new();
+        for idx in self.top..end {
+            let row = &self.rows[idx];
+            let selected = idx == self.selected;
+            let base = if selected {
+                Style::default().bg(crate::colors::selection())
+            } else {
+                Style::default()
+            };
+            let marker = if selected { "›" } else { " " };
+            let (state, state_style) = match row.state {
+                MergeFileState::Conflicted => ("conflict", base.fg(crate::colors::error())),
+                MergeFileState::Resolved => ("review  ", base.fg(crate::colors::warning())),
+                MergeFileState::Approved => ("approved", base.fg(crate::colors::success())),
+            };
+            let detail = match row.state {
+                MergeFileState::Conflicted if row.hunks > 0 => {
+                    format!("{}, {} region(s)", row.kind.label(), row.hunks)
+                }
+                _ => row.kind.label().to_string(),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{marker} "), base.fg(crate::colors::primary())),
+                Span::styled(format!("{state} "), state_style),
+                Span::styled(format!("{} ", row.path), base.fg(crate::colors::text())),
+                Span::styled(detail, base.fg(crate::colors::text_dim())),
+            ]));
+        }
+        lines
+    }
+
+    fn header_line(&self) -> Line<'static> {
+        let phase = if self.resolving {
+            "resolving in the worktree"
+        } else {
+            "trial merge; nothing changed yet"
+        };
+        Line::from(vec![
+            Span::styled(
+                format!(
+                    "{} → {}",
+                    self.context.worktree_branch, self.context.default_branch
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {phase}"),
+                Style::default().fg(crate::colors::text_dim()),
+            ),
+        ])
+    }
+
+    fn notice_line(&self) -> Line<'static> {
+        match &self.notice {
+            Some(notice) => Line::from(Span::styled(
+                notice.clone(),
+                Style::default().fg(crate::colors::warning()),
+            )),
+            None => Line::from(""),
+        }
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        let key = |text: &'static str| {
+            Span::styled(text, Style::default().fg(crate::colors::light_blue()))
+        };
+        if !self.resolving {
+            return Line::from(vec![
+                Span::styled("Enter", Style::default().fg(crate::colors::success())),
+                Span::raw(" Resolve with agent  "),
+                Span::styled("Esc", Style::default().fg(crate::colors::error())),
+                Span::raw(" Resolve manually"),
+            ]);
+        }
+        let enter = if self.all_approved() {
+            " Commit & validate  "
+        } else {
+            " Continue with agent  "
+        };
+        Line::from(vec![
+            key("a"),
+            Span::raw(" Approve  "),
+            key("r"),
+            Span::raw(" Reject  "),
+            key("d"),
+            Span::raw(" Diff  "),
+            Span::styled("Enter", Style::default().fg(crate::colors::success())),
+            Span::raw(enter),
+            key("x"),
+            Span::raw(" Abort merge  "),
+            Span::styled("Esc", Style::default().fg(crate::colors::error())),
+            Span::raw(" Close"),
+        ])
+    }
+}
+
+impl<'a> BottomPaneView<'a> for MergeConflictsView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Enter => self.confirm(),
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                if let Some(row) = self.selected_row() {
+                    let path = row.path.clone();
+                    self.send(MergeConflictAction::ShowDiff(path));
+                }
+            }
+            KeyCode::Char('a') | KeyCode::Char('A') if self.resolving => self.approve(),
+            KeyCode::Char('r') | KeyCode::Char('R') if self.resolving => self.reject(),
+            KeyCode::Char('x') | KeyCode::Char('X') if self.resolving => {
+                self.send(MergeConflictAction::Abort)
+            }
+            KeyCode::Esc => self.is_complete = true,
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'a>) -> CancellationEvent {
+        self.is_complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn update_status_text(&mut self, _text: String) -> ConditionalUpdate {
+        ConditionalUpdate::NeedsRedraw
+    }
+
+    fn desired_height(&self, _width: u16) -> u16 {
+        let rows = self.rows.len().clamp(1, MAX_VISIBLE_ROWS);
+        // Borders, header, notice, and footer.
+        (rows + 5) as u16
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Merge conflicts ({}) ", self.rows.len()))
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [header_area, rows_area, notice_area, footer_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let style = Style::default()
+            .bg(crate::colors::background())
+            .fg(crate::colors::text());
+        Paragraph::new(self.header_line())
+            .style(style)
+            .render(header_area, buf);
+        Paragraph::new(self.row_lines())
+            .style(style)
+            .render(rows_area, buf);
+        Paragraph::new(self.notice_line())
+            .style(style)
+            .render(notice_area, buf);
+        Paragraph::new(self.footer_line())
+            .style(style)
+            .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    fn row(path: &str, state: MergeFileState) -> MergeConflictRow {
+        MergeConflictRow {
+            path: path.to_string(),
+            kind: ConflictKind::Content,
+            hunks: usize::from(state == MergeFileState::Conflicted),
+            state,
+        }
+    }
+
+    fn context() -> MergeConflictContext {
+        MergeConflictContext {
+            worktree_path: PathBuf::from("/repo-wt"),
+            worktree_branch: "code-branch-fix".to_string(),
+            default_branch: "main".to_string(),
+        }
+    }
+
+    #[test]
+    fn enter_continues_until_every_file_is_approved() {
+        let (tx, rx) = channel();
+        let rows = vec![
+            row("a.rs", MergeFileState::Approved),
+            row("b.rs", MergeFileState::Resolved),
+        ];
+        let mut view =
+            MergeConflictsView::new(context(), rows, true, None, AppEventSender::new(tx.clone()));
+        view.confirm();
+        assert!(rx.try_recv().is_err());
+        assert!(view.notice.is_some());
+
+        view.move_down();
+        view.approve();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::MergeConflictAction(MergeConflictAction::Approve(path))) if path == "b.rs"
+        ));
+
+        let mut done = MergeConflictsView::new(
+            context(),
+            vec![row("a.rs", MergeFileState::Approved)],
+            true,
+            None,
+            AppEventSender::new(tx),
+        );
+        done.confirm();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::MergeConflictAction(MergeConflictAction::Finish))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 //! Bottom pane: shows the ChatComposer or a BottomPaneView, if one is active.
 
 use crate::app_event::AppEvent;
+use crate::app_event::ExternalEditorTarget;
 use crate::app_event_sender::AppEventSender;
 use crate::auto_drive_style::AutoDriveVariant;
 use crate::bottom_pane::chat_composer::ComposerRenderMode;
@@ -52,6 +53,7 @@ pub mod mcp_settings_view;
 mod login_accounts_view;
 // no direct use of list_selection_view or its items here
 mod textarea;
+mod vim_mode;
 pub mod form_text_field;
 pub mod prompts_settings_view;
 pub mod skills_settings_view;
@@ -61,6 +63,11 @@ mod verbosity_selection_view;
 pub(crate) mod validation_settings_view;
 mod update_settings_view;
 mod undo_timeline_view;
+mod undo_files_view;
+mod context_inspector_view;
+mod worktrees_view;
+mod merge_conflicts_view;
+mod agent_evaluation_view;
 mod notifications_settings_view;
 mod settings_overlay;
 mod request_user_input_view;
@@ -90,7 +97,7 @@ pub(crate) use auto_coordinator_view::{
     CountdownState,
 };
 pub(crate) use auto_drive_settings_view::AutoDriveSettingsView;
-pub(crate) use account_switch_settings_view::AccountSwitchSettingsView;
+pub(crate) use account_switch_settings_view::{AccountPolicyState, AccountSwitchSettingsView};
 pub(crate) use magic_settings_view::MagicSettingsView;
 pub(crate) use login_accounts_view::{
     LoginAccountsState,
@@ -116,6 +123,17 @@ pub(crate) use mcp_settings_view::McpSettingsView;
 pub(crate) use theme_selection_view::ThemeSelectionView;
 use verbosity_selection_view::VerbositySelectionView;
 pub(crate) use undo_timeline_view::{UndoTimelineEntry, UndoTimelineEntryKind, UndoTimelineView};
+pub(crate) use undo_files_view::UndoFilesView;
+pub(crate) use context_inspector_view::ContextInspectorView;
+pub(crate) use worktrees_view::WorktreesView;
+pub(crate) use agent_evaluation_view::AgentEvaluationView;
+pub(crate) use merge_conflicts_view::{
+    MergeConflictAction,
+    MergeConflictContext,
+    MergeConflictRow,
+    MergeConflictsView,
+    MergeFileState,
+};
 pub(crate) use request_user_input_view::RequestUserInputView;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -649,6 +667,22 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
+    pub(crate) fn set_composer_vim_mode(&mut self, enabled: bool) {
+        self.composer.set_vim_mode(enabled);
+        self.request_redraw();
+    }
+
+    /// Whether Esc belongs to the composer's vim mode (leaving insert or
+    /// visual mode, or cancelling a pending command).
+    pub(crate) fn composer_vim_wants_escape(&self) -> bool {
+        self.active_view.is_none() && self.composer.vim_wants_escape()
+    }
+
+    pub(crate) fn composer_vim_escape(&mut self) {
+        self.composer.vim_escape();
+        self.request_redraw();
+    }
+
     #[cfg(test)]
     pub(crate) fn auto_review_status(&self) -> Option<AutoReviewFooterStatus> {
         self.composer.auto_review_status()
@@ -727,6 +761,19 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
+    /// Route text returned from the external editor to the active view
+    /// (e.g. an approval modal editing a command or patch hunk).
+    pub(crate) fn apply_external_edit(&mut self, target: &ExternalEditorTarget, text: String) -> bool {
+        let Some(view) = self.active_view.as_mut() else {
+            return false;
+        };
+        let applied = view.apply_external_edit(target, text);
+        if applied {
+            self.request_redraw();
+        }
+        applied
+    }
+
     /// Called when the agent requests user approval.
     pub fn push_approval_request(
         &mut self,
@@ -895,6 +942,41 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
+    pub fn show_undo_files_view(&mut self, view: UndoFilesView) {
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw();
+    }
+
+    pub fn show_context_inspector_view(&mut self, view: ContextInspectorView) {
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw();
+    }
+
+    pub fn show_worktrees_view(&mut self, view: WorktreesView) {
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw();
+    }
+
+    pub fn show_agent_evaluation_view(&mut self, view: AgentEvaluationView) {
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw();
+    }
+
+    pub fn show_merge_conflicts_view(&mut self, view: MergeConflictsView) {
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw();
+    }
+
     /// Show MCP servers status/toggle UI
     #[allow(dead_code)]
     pub fn show_mcp_settings(&mut self, rows: crate::bottom_pane::mcp_settings_view::McpServerRows) {
@@ -24,6 +24,8 @@ pub(crate) struct TextArea {
     // Simple undo stack capturing full snapshots of text and cursor before edits.
     // This is intentionally simple to reliably undo paste and bulk edits across terminals.
     undo_stack: Vec<UndoSnapshot>,
+    // States reverted by `undo`, replayed by `redo`. Cleared by any new edit.
+    redo_stack: Vec<UndoSnapshot>,
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +54,7 @@ impl TextArea {
             wrap_cache: RefCell::new(None),
             preferred_col: None,
             undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
         }
     }
 
@@ -587,13 +590,41 @@ impl TextArea {
     /// Revert the most recent edit (if any).
     pub fn undo(&mut self) {
         if let Some(prev) = self.undo_stack.pop() {
-            self.text = prev.text;
+            self.redo_stack.push(UndoSnapshot {
+                text: std::mem::replace(&mut self.text, prev.text),
+                cursor_pos: self.cursor_pos,
+            });
             self.cursor_pos = prev.cursor_pos.min(self.text.len());
             self.wrap_cache.replace(None);
             self.preferred_col = None;
         }
     }
 
+    /// Reapply the most recently undone edit (if any).
+    pub fn redo(&mut self) {
+        if let Some(next) = self.redo_stack.pop() {
+            self.undo_stack.push(UndoSnapshot {
+                text: std::mem::replace(&mut self.text, next.text),
+                cursor_pos: self.cursor_pos,
+            });
+            self.cursor_pos = next.cursor_pos.min(self.text.len());
+            self.wrap_cache.replace(None);
+            self.preferred_col = None;
+        }
+    }
+
+    /// Current undo depth; pass to `collapse_undo_since` to group later edits.
+    pub(crate) fn undo_depth(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Merge every edit made after `depth` into a single undo step.
+    pub(crate) fn collapse_undo_since(&mut self, depth: usize) {
+        if self.undo_stack.len() > depth + 1 {
+            self.undo_stack.truncate(depth + 1);
+        }
+    }
+
     /// Move the cursor left by a single grapheme cluster.
     pub fn move_cursor_left(&mut self) {
         self.cursor_pos = self.prev_atomic_boundary(self.cursor_pos);
@@ -807,6 +838,7 @@ impl TextArea {
     }
 
     fn push_undo_snapshot(&mut self) {
+        self.redo_stack.clear();
         // Capture current state before mutation.
         self.undo_stack.push(UndoSnapshot {
             text: self.text.clone(),
@@ -0,0 +1,472 @@
+use std::cmp::max;
+use std::path::PathBuf;
+
+use code_git_tooling::{SnapshotFileDiff, SnapshotFileStatus};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::bottom_pane_view::{BottomPaneView, ConditionalUpdate};
+use super::{BottomPane, CancellationEvent};
+
+const MAX_VISIBLE_ROWS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Row {
+    File(usize),
+    Hunk(usize, usize),
+}
+
+/// Partial `/undo`: pick files, or hunks within a file, to put back to a
+/// snapshot while the remaining changes stay in place.
+pub(crate) struct UndoFilesView {
+    commit: String,
+    title: String,
+    files: Vec<SnapshotFileDiff>,
+    /// One flag per hunk, or a single flag for files without hunks.
+    marked: Vec<Vec<bool>>,
+    expanded: Vec<bool>,
+    rows: Vec<Row>,
+    selected: usize,
+    top: usize,
+    app_event_tx: AppEventSender,
+    is_complete: bool,
+}
+
+impl UndoFilesView {
+    pub fn new(
+        commit: String,
+        title: String,
+        files: Vec<SnapshotFileDiff>,
+        app_event_tx: AppEventSender,
+    ) -> Self {
+        let marked = files
+            .iter()
+            .map(|file| vec![false; file.hunks.len().max(1)])
+            .collect();
+        let expanded = vec![false; files.len()];
+        let mut view = Self {
+            commit,
+            title,
+            files,
+            marked,
+            expanded,
+            rows: Vec::new(),
+            selected: 0,
+            top: 0,
+            app_event_tx,
+            is_complete: false,
+        };
+        view.rebuild_rows();
+        view
+    }
+
+    fn rebuild_rows(&mut self) {
+        let current = self.rows.get(self.selected).copied();
+        self.rows.clear();
+        for (file_idx, file) in self.files.iter().enumerate() {
+            self.rows.push(Row::File(file_idx));
+            if self.expanded[file_idx] && file.hunks.len() > 1 {
+                self.rows
+                    .extend((0..file.hunks.len()).map(|hunk_idx| Row::Hunk(file_idx, hunk_idx)));
+            }
+        }
+        self.selected = current
+            .and_then(|row| self.rows.iter().position(|candidate| *candidate == row))
+            .or_else(|| match current {
+                Some(Row::Hunk(file_idx, _)) => self
+                    .rows
+                    .iter()
+                    .position(|candidate| *candidate == Row::File(file_idx)),
+                _ => None,
+            })
+            .unwrap_or(0);
+        self.ensure_visible();
+    }
+
+    fn move_up(&mut self) {
+        let len = self.rows.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if self.selected == 0 {
+            len - 1
+        } else {
+            self.selected - 1
+        };
+        self.ensure_visible();
+    }
+
+    fn move_down(&mut self) {
+        let len = self.rows.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected + 1) % len;
+        self.ensure_visible();
+    }
+
+    fn ensure_visible(&mut self) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + MAX_VISIBLE_ROWS {
+            self.top = self.selected + 1 - MAX_VISIBLE_ROWS;
+        }
+    }
+
+    fn set_expanded(&mut self, expanded: bool) {
+        let file_idx = match self.rows.get(self.selected) {
+            Some(Row::File(idx)) | Some(Row::Hunk(idx, _)) => *idx,
+            None => return,
+        };
+        if self.expanded[file_idx] != expanded {
+            self.expanded[file_idx] = expanded;
+            self.rebuild_rows();
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        match self.rows.get(self.selected).copied() {
+            Some(Row::File(file_idx)) => {
+                let marks = &mut self.marked[file_idx];
+                let all = marks.iter().all(|marked| *marked);
+                marks.iter_mut().for_each(|marked| *marked = !all);
+            }
+            Some(Row::Hunk(file_idx, hunk_idx)) => {
+                let marked = &mut self.marked[file_idx][hunk_idx];
+                *marked = !*marked;
+            }
+            None => {}
+        }
+    }
+
+    fn show_diff(&mut self) {
+        let Some(file_idx) = self.current_file() else {
+            return;
+        };
+        let file = &self.files[file_idx];
+        let text = if file.binary {
+            format!(
+                "{} is binary; the snapshot and current versions differ",
+                file.path.display()
+            )
+        } else {
+            file.patch()
+        };
+        self.app_event_tx.send(AppEvent::DiffResult(text));
+        self.is_complete = true;
+    }
+
+    fn current_file(&self) -> Option<usize> {
+        match self.rows.get(self.selected)? {
+            Row::File(idx) | Row::Hunk(idx, _) => Some(*idx),
+        }
+    }
+
+    /// Whole files to restore, and files where only some hunks are marked.
+    fn selection(&self) -> (Vec<PathBuf>, Vec<(SnapshotFileDiff, Vec<usize>)>) {
+        let mut files = Vec::new();
+        let mut hunks = Vec::new();
+        for (file, marks) in self.files.iter().zip(&self.marked) {
+            if marks.iter().all(|marked| *marked) {
+                files.push(file.path.clone());
+            } else if marks.iter().any(|marked| *marked) {
+                let indexes = marks
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, marked)| marked.then_some(idx))
+                    .collect();
+                hunks.push((file.clone(), indexes));
+            }
+        }
+        (files, hunks)
+    }
+
+    fn confirm(&mut self) {
+        let (files, hunks) = self.selection();
+        if files.is_empty() && hunks.is_empty() {
+            return;
+        }
+        self.app_event_tx.send(AppEvent::PerformPartialUndo {
+            commit: self.commit.clone(),
+            files,
+            hunks,
+        });
+        self.is_complete = true;
+    }
+
+    fn checkbox(marks: &[bool]) -> &'static str {
+        if marks.iter().all(|marked| *marked) {
+            "[x]"
+        } else if marks.iter().any(|marked| *marked) {
+            "[~]"
+        } else {
+            "[ ]"
+        }
+    }
+
+    fn row_lines(&self) -> Vec<Line<'static>> {
+        let end = (self.top + MAX_VISIBLE_ROWS).min(self.rows.len());
+        let mut lines = Vec::new();
+        for idx in self.top..end {
+            let selected = idx == self.selected;
+            let base = if selected {
+                Style::default().bg(crate::colors::selection())
+            } else {
+                Style::default()
+            };
+            let marker = if selected { "›" } else { " " };
+            let mut spans = vec![Span::styled(
+                format!("{marker} "),
+                base.fg(crate::colors::primary()),
+            )];
+            match self.rows[idx] {
+                Row::File(file_idx) => {
+                    let file = &self.files[file_idx];
+                    let marks = &self.marked[file_idx];
+                    let (status, status_style) = match file.status {
+                        SnapshotFileStatus::Added => ("A", base.fg(crate::colors::success())),
+                        SnapshotFileStatus::Modified => ("M", base.fg(crate::colors::warning())),
+                        SnapshotFileStatus::Deleted => ("D", base.fg(crate::colors::error())),
+                    };
+                    spans.push(Span::styled(
+                        format!("{} ", Self::checkbox(marks)),
+                        base.fg(crate::colors::text()),
+                    ));
+                    spans.push(Span::styled(format!("{status} "), status_style));
+                    spans.push(Span::styled(
+                        file.path.display().to_string(),
+                        base.fg(crate::colors::text()).add_modifier(Modifier::BOLD),
+                    ));
+                    let detail = if file.binary {
+                        "  binary".to_string()
+                    } else {
+                        let (added, removed) = file.line_counts();
+                        let mut detail = format!("  +{added} -{removed}");
+                        if file.hunks.len() > 1 {
+                            let arrow = if self.expanded[file_idx] {
+                                "▾"
+                            } else {
+                                "▸"
+                            };
+                            detail.push_str(&format!("  {arrow} {} hunks", file.hunks.len()));
+                        }
+                        detail
+                    };
+                    spans.push(Span::styled(detail, base.fg(crate::colors::text_dim())));
+                }
+                Row::Hunk(file_idx, hunk_idx) => {
+                    let mark = if self.marked[file_idx][hunk_idx] {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    };
+                    let header = self.files[file_idx].hunks[hunk_idx]
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+                    spans.push(Span::styled(
+                        format!("    {mark} "),
+                        base.fg(crate::colors::text()),
+                    ));
+                    spans.push(Span::styled(header, base.fg(crate::colors::text_dim())));
+                }
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
+    fn preview_lines(&self, max_lines: usize) -> Vec<Line<'static>> {
+        let Some(row) = self.rows.get(self.selected) else {
+            return Vec::new();
+        };
+        let text = match *row {
+            Row::File(file_idx) => {
+                let file = &self.files[file_idx];
+                if file.binary {
+                    return vec![Line::from(Span::styled(
+                        "Binary file; restoring replaces it with the snapshot version.",
+                        Style::default().fg(crate::colors::text_dim()),
+                    ))];
+                }
+                file.hunks.concat()
+            }
+            Row::Hunk(file_idx, hunk_idx) => self.files[file_idx].hunks[hunk_idx].clone(),
+        };
+        text.lines()
+            .take(max_lines)
+            .map(|line| {
+                let color = if line.starts_with("@@") {
+                    crate::colors::primary()
+                } else if line.starts_with('+') {
+                    crate::colors::success()
+                } else if line.starts_with('-') {
+                    crate::colors::error()
+                } else {
+                    crate::colors::text_dim()
+                };
+                Line::from(Span::styled(line.to_string(), Style::default().fg(color)))
+            })
+            .collect()
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        let key = |text: &'static str| {
+            Span::styled(text, Style::default().fg(crate::colors::light_blue()))
+        };
+        Line::from(vec![
+            key("Space"),
+            Span::raw(" Mark  "),
+            key("→/←"),
+            Span::raw(" Hunks  "),
+            key("d"),
+            Span::raw(" Full diff  "),
+            Span::styled("Enter", Style::default().fg(crate::colors::success())),
+            Span::raw(" Restore marked  "),
+            Span::styled("Esc", Style::default().fg(crate::colors::error())),
+            Span::raw(" Close"),
+        ])
+    }
+}
+
+impl<'a> BottomPaneView<'a> for UndoFilesView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Right => self.set_expanded(true),
+            KeyCode::Left => self.set_expanded(false),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Char('d') | KeyCode::Char('D') => self.show_diff(),
+            KeyCode::Enter => self.confirm(),
+            KeyCode::Esc => self.is_complete = true,
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'a>) -> CancellationEvent {
+        self.is_complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn update_status_text(&mut self, _text: String) -> ConditionalUpdate {
+        ConditionalUpdate::NeedsRedraw
+    }
+
+    fn desired_height(&self, _width: u16) -> u16 {
+        max(MAX_VISIBLE_ROWS as u16 + 4, 20)
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Restore from {} ", self.title))
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [body_area, footer_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Fill(1)]).areas(body_area);
+
+        Paragraph::new(self.row_lines())
+            .style(Style::default().bg(crate::colors::background()))
+            .render(list_area, buf);
+
+        let preview_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Snapshot → current ")
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(Style::default().bg(crate::colors::background()));
+        let preview_inner = preview_block.inner(preview_area);
+        preview_block.render(preview_area, buf);
+        Paragraph::new(self.preview_lines(preview_inner.height as usize))
+            .style(Style::default().bg(crate::colors::background()))
+            .render(preview_inner, buf);
+
+        Paragraph::new(self.footer_line())
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    fn file(path: &str, hunks: usize) -> SnapshotFileDiff {
+        SnapshotFileDiff {
+            path: PathBuf::from(path),
+            status: SnapshotFileStatus::Modified,
+            header: format!("diff --git a/{path} b/{path}\n"),
+            hunks: (0..hunks)
+                .map(|idx| format!("@@ -{idx},1 +{idx},1 @@\n-old\n+new\n"))
+                .collect(),
+            binary: false,
+        }
+    }
+
+    #[test]
+    fn marking_a_hunk_restores_only_that_hunk() {
+        let (tx, rx) = channel();
+        let mut view = UndoFilesView::new(
+            "abc123".to_string(),
+            "Snapshot abc123".to_string(),
+            vec![file("a.rs", 2), file("b.rs", 1)],
+            AppEventSender::new(tx),
+        );
+        view.set_expanded(true);
+        assert_eq!(view.rows.len(), 4);
+        view.move_down();
+        view.move_down();
+        view.toggle_mark();
+        view.move_down();
+        view.toggle_mark();
+        view.confirm();
+
+        match rx.try_recv() {
+            Ok(AppEvent::PerformPartialUndo {
+                commit,
+                files,
+                hunks,
+            }) => {
+                assert_eq!(commit, "abc123");
+                assert_eq!(files, vec![PathBuf::from("b.rs")]);
+                assert_eq!(hunks.len(), 1);
+                assert_eq!(hunks[0].0.path, PathBuf::from("a.rs"));
+                assert_eq!(hunks[0].1, vec![1]);
+            }
+            other => panic!("expected PerformPartialUndo, got {other:?}"),
+        }
+        assert!(view.is_complete);
+    }
+}
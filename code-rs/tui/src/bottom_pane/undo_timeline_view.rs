@@ -246,6 +246,20 @@ impl UndoTimelineView {
         }
     }
 
+    fn pick_files(&mut self) {
+        if let Some(UndoTimelineEntry {
+            kind: UndoTimelineEntryKind::Snapshot { commit },
+            files_available: true,
+            ..
+        }) = self.selected_entry()
+        {
+            self.app_event_tx.send(AppEvent::ShowUndoFilePicker {
+                commit: commit.clone(),
+            });
+            self.is_complete = true;
+        }
+    }
+
     fn total_list_height(&self) -> usize {
         self.entries.iter().map(|entry| entry.list_line_count()).sum()
     }
@@ -479,6 +493,8 @@ impl UndoTimelineView {
                 Span::raw(" Toggle files  "),
                 Span::styled("C", Style::default().fg(crate::colors::success())),
                 Span::raw(" Toggle conversation  "),
+                Span::styled("P", Style::default().fg(crate::colors::success())),
+                Span::raw(" Pick files  "),
                 Span::styled("Enter", Style::default().fg(crate::colors::success())),
                 Span::raw(" Restore  "),
                 Span::styled("Esc", Style::default().fg(crate::colors::error())),
@@ -502,6 +518,7 @@ impl<'a> BottomPaneView<'a> for UndoTimelineView {
             KeyCode::Char(' ') => self.toggle_files(),
             KeyCode::Char('c') | KeyCode::Char('C') => self.toggle_conversation(),
             KeyCode::Char('f') | KeyCode::Char('F') => self.toggle_files(),
+            KeyCode::Char('p') | KeyCode::Char('P') => self.pick_files(),
             KeyCode::Tab => {
                 if let Some(entry) = self.selected_entry() {
                     if entry.conversation_available && !entry.files_available {
@@ -0,0 +1,886 @@
+//! Optional vim-style modal editing for the chat composer.
+//!
+//! Insert mode leaves keys to the regular composer/`TextArea` handling; this
+//! module only interprets keys in normal and visual mode. Positions are byte
+//! offsets into the composer text, always on char boundaries.
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+use super::textarea::TextArea;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimMode {
+    Insert,
+    Normal,
+    Visual,
+}
+
+impl VimMode {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            VimMode::Insert => "-- INSERT --",
+            VimMode::Normal => "-- NORMAL --",
+            VimMode::Visual => "-- VISUAL --",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimOutcome {
+    /// The key was consumed by vim.
+    Handled,
+    /// Let the composer handle the key as usual (Enter, Ctrl chords, arrows).
+    PassThrough,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    DocStart,
+    DocEnd,
+}
+
+impl Motion {
+    /// Linewise motions make operators act on whole lines.
+    fn is_linewise(self) -> bool {
+        matches!(self, Motion::Up | Motion::Down | Motion::DocStart | Motion::DocEnd)
+    }
+
+    /// Inclusive motions include the character under the target position.
+    fn is_inclusive(self) -> bool {
+        matches!(self, Motion::WordEnd | Motion::LineEnd)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct VimState {
+    mode: VimMode,
+    count: Option<usize>,
+    pending_op: Option<(Operator, usize)>,
+    pending_g: bool,
+    visual_anchor: usize,
+    register: Register,
+    /// Keys of the command being typed, for `.` repeat.
+    keys: Vec<KeyEvent>,
+    /// Keys of the last completed change.
+    last_change: Vec<KeyEvent>,
+    /// True while insert-mode keys belong to a change started in normal mode.
+    recording_insert: bool,
+    replaying: bool,
+    /// Undo depth when insert mode was entered, so the whole insert undoes
+    /// as one step.
+    insert_undo_depth: Option<usize>,
+}
+
+impl VimState {
+    pub(crate) fn new() -> Self {
+        Self {
+            mode: VimMode::Insert,
+            count: None,
+            pending_op: None,
+            pending_g: false,
+            visual_anchor: 0,
+            register: Register::default(),
+            keys: Vec::new(),
+            last_change: Vec::new(),
+            recording_insert: false,
+            replaying: false,
+            insert_undo_depth: None,
+        }
+    }
+
+    pub(crate) fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Whether Esc should go to vim (leave insert/visual mode or cancel a
+    /// pending command) rather than the app-level Esc handling.
+    pub(crate) fn wants_escape(&self) -> bool {
+        self.mode != VimMode::Normal
+            || self.count.is_some()
+            || self.pending_op.is_some()
+            || self.pending_g
+    }
+
+    /// Return to insert mode with no pending state (e.g. after submitting).
+    pub(crate) fn reset(&mut self) {
+        self.mode = VimMode::Insert;
+        self.clear_pending();
+        self.recording_insert = false;
+        self.insert_undo_depth = None;
+    }
+
+    pub(crate) fn record_insert_key(&mut self, key: KeyEvent) {
+        if self.recording_insert && !self.replaying {
+            self.keys.push(key);
+        }
+    }
+
+    pub(crate) fn escape(&mut self, textarea: &mut TextArea) {
+        match self.mode {
+            VimMode::Insert => {
+                if let Some(depth) = self.insert_undo_depth.take() {
+                    textarea.collapse_undo_since(depth);
+                }
+                if self.recording_insert && !self.replaying {
+                    self.keys.push(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+                    self.last_change = std::mem::take(&mut self.keys);
+                }
+                self.recording_insert = false;
+                self.mode = VimMode::Normal;
+                // Like vim, the cursor steps back onto the last inserted char.
+                let text = textarea.text();
+                let pos = textarea.cursor();
+                if pos > line_start(text, pos) {
+                    let back = prev_char(text, pos);
+                    textarea.set_cursor(back);
+                }
+            }
+            VimMode::Visual => {
+                self.mode = VimMode::Normal;
+            }
+            VimMode::Normal => {}
+        }
+        self.clear_pending();
+        clamp_normal_cursor(textarea);
+    }
+
+    /// Interpret a key in normal or visual mode.
+    pub(crate) fn handle_key(&mut self, textarea: &mut TextArea, key: KeyEvent) -> VimOutcome {
+        if self.mode == VimMode::Insert {
+            return VimOutcome::PassThrough;
+        }
+        let ch = match key.code {
+            KeyCode::Char(c)
+                if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+            {
+                c
+            }
+            KeyCode::Backspace if key.modifiers.is_empty() => 'h',
+            _ => return VimOutcome::PassThrough,
+        };
+        if !self.replaying {
+            self.keys.push(key);
+        }
+        if self.mode == VimMode::Visual {
+            self.handle_visual_char(textarea, ch);
+        } else {
+            self.handle_normal_char(textarea, ch);
+        }
+        VimOutcome::Handled
+    }
+
+    fn handle_normal_char(&mut self, textarea: &mut TextArea, ch: char) {
+        if let Some(count) = self.take_count_digit(ch) {
+            self.count = Some(count);
+            return;
+        }
+
+        if self.pending_g {
+            self.pending_g = false;
+            if ch == 'g' {
+                self.run_motion(textarea, Motion::DocStart);
+            } else {
+                self.clear_pending();
+            }
+            return;
+        }
+
+        if let Some((op, op_count)) = self.pending_op {
+            let doubled = matches!(
+                (op, ch),
+                (Operator::Delete, 'd') | (Operator::Change, 'c') | (Operator::Yank, 'y')
+            );
+            if doubled {
+                let count = op_count * self.count.take().unwrap_or(1);
+                self.pending_op = None;
+                self.apply_linewise_lines(textarea, op, count);
+                return;
+            }
+            if ch == 'g' {
+                self.pending_g = true;
+                return;
+            }
+        }
+
+        if let Some(motion) = motion_for_char(ch) {
+            self.run_motion(textarea, motion);
+            return;
+        }
+
+        let count = self.count.take().unwrap_or(1);
+        if self.pending_op.is_some() {
+            // Not a motion: abandon the operator.
+            self.clear_pending();
+            return;
+        }
+
+        match ch {
+            'g' => {
+                self.count = Some(count).filter(|c| *c > 1);
+                self.pending_g = true;
+            }
+            'd' => self.pending_op = Some((Operator::Delete, count)),
+            'c' => self.pending_op = Some((Operator::Change, count)),
+            'y' => self.pending_op = Some((Operator::Yank, count)),
+            'x' => {
+                self.count = Some(count);
+                self.pending_op = Some((Operator::Delete, 1));
+                self.run_motion(textarea, Motion::Right);
+            }
+            'D' => {
+                self.count = Some(count);
+                self.pending_op = Some((Operator::Delete, 1));
+                self.run_motion(textarea, Motion::LineEnd);
+            }
+            'C' => {
+                self.count = Some(count);
+                self.pending_op = Some((Operator::Change, 1));
+                self.run_motion(textarea, Motion::LineEnd);
+            }
+            'p' | 'P' => {
+                self.paste(textarea, ch == 'p', count);
+                self.finish_change();
+            }
+            'i' => self.enter_insert(textarea, None),
+            'a' => {
+                let text = textarea.text();
+                let pos = textarea.cursor();
+                let target = if pos < line_end(text, pos) {
+                    next_char(text, pos)
+                } else {
+                    pos
+                };
+                self.enter_insert(textarea, Some(target));
+            }
+            'I' => {
+                let target = first_non_blank(textarea.text(), textarea.cursor());
+                self.enter_insert(textarea, Some(target));
+            }
+            'A' => {
+                let target = line_end(textarea.text(), textarea.cursor());
+                self.enter_insert(textarea, Some(target));
+            }
+            'o' | 'O' => {
+                let depth = textarea.undo_depth();
+                let text = textarea.text();
+                let pos = textarea.cursor();
+                if ch == 'o' {
+                    let end = line_end(text, pos);
+                    textarea.insert_str_at(end, "\n");
+                    textarea.set_cursor(end + 1);
+                } else {
+                    let start = line_start(text, pos);
+                    textarea.insert_str_at(start, "\n");
+                    textarea.set_cursor(start);
+                }
+                self.enter_insert(textarea, None);
+                self.insert_undo_depth = Some(depth);
+            }
+            'v' => {
+                self.visual_anchor = textarea.cursor();
+                self.mode = VimMode::Visual;
+                self.keys.clear();
+            }
+            'u' => {
+                for _ in 0..count {
+                    textarea.undo();
+                }
+                self.keys.clear();
+            }
+            'U' => {
+                for _ in 0..count {
+                    textarea.redo();
+                }
+                self.keys.clear();
+            }
+            '.' => {
+                self.keys.clear();
+                self.repeat_last_change(textarea, count);
+            }
+            _ => self.clear_pending(),
+        }
+        clamp_normal_cursor(textarea);
+    }
+
+    fn handle_visual_char(&mut self, textarea: &mut TextArea, ch: char) {
+        self.keys.clear();
+        if let Some(count) = self.take_count_digit(ch) {
+            self.count = Some(count);
+            return;
+        }
+        if self.pending_g {
+            self.pending_g = false;
+            if ch == 'g' {
+                let target = motion_target(textarea, Motion::DocStart, 1);
+                textarea.set_cursor(target);
+            }
+            return;
+        }
+        if let Some(motion) = motion_for_char(ch) {
+            let count = self.count.take().unwrap_or(1);
+            let target = motion_target(textarea, motion, count);
+            textarea.set_cursor(target);
+            return;
+        }
+        let op = match ch {
+            'g' => {
+                self.pending_g = true;
+                return;
+            }
+            'd' | 'x' => Operator::Delete,
+            'c' | 's' => Operator::Change,
+            'y' => Operator::Yank,
+            'v' => {
+                self.mode = VimMode::Normal;
+                self.clear_pending();
+                return;
+            }
+            _ => return,
+        };
+        let text = textarea.text();
+        let cursor = textarea.cursor();
+        let start = self.visual_anchor.min(cursor).min(text.len());
+        let end = next_char(text, self.visual_anchor.max(cursor).min(text.len()));
+        self.mode = VimMode::Normal;
+        self.count = None;
+        self.apply_operator(textarea, op, start..end, false);
+        if op != Operator::Change {
+            clamp_normal_cursor(textarea);
+        }
+    }
+
+    fn take_count_digit(&self, ch: char) -> Option<usize> {
+        let digit = ch.to_digit(10)? as usize;
+        if digit == 0 && self.count.is_none() {
+            // A bare `0` is the line-start motion.
+            return None;
+        }
+        Some(self.count.unwrap_or(0).saturating_mul(10).saturating_add(digit).min(9999))
+    }
+
+    fn run_motion(&mut self, textarea: &mut TextArea, motion: Motion) {
+        let count = self.count.take().unwrap_or(1);
+        let Some((op, op_count)) = self.pending_op.take() else {
+            let target = motion_target(textarea, motion, count);
+            textarea.set_cursor(target);
+            self.keys.clear();
+            clamp_normal_cursor(textarea);
+            return;
+        };
+        let count = count * op_count;
+        let text = textarea.text();
+        let pos = textarea.cursor();
+
+        if motion.is_linewise() {
+            let target = motion_target(textarea, motion, count);
+            let block = line_block(text, pos.min(target), pos.max(target));
+            self.apply_operator(textarea, op, block, true);
+            return;
+        }
+
+        // `cw` on a word behaves like `ce`, as in vim.
+        let motion = if op == Operator::Change
+            && motion == Motion::WordForward
+            && char_at(text, pos).is_some_and(|c| !c.is_whitespace())
+        {
+            Motion::WordEnd
+        } else {
+            motion
+        };
+        let mut target = motion_target(textarea, motion, count);
+        if motion == Motion::WordForward && target > line_end(text, pos) {
+            // `dw` on the last word of a line stops at the line end.
+            target = line_end(text, pos);
+        }
+        let (start, mut end) = (pos.min(target), pos.max(target));
+        if motion.is_inclusive() {
+            end = next_char(text, end).min(line_end(text, end).max(end));
+        }
+        self.apply_operator(textarea, op, start..end, false);
+    }
+
+    /// `dd`, `cc`, `yy` over `count` lines starting at the cursor line.
+    fn apply_linewise_lines(&mut self, textarea: &mut TextArea, op: Operator, count: usize) {
+        let text = textarea.text();
+        let from = textarea.cursor();
+        let mut to = from;
+        for _ in 1..count {
+            let end = line_end(text, to);
+            if end >= text.len() {
+                break;
+            }
+            to = end + 1;
+        }
+        let block = line_block(text, from, to);
+        self.apply_operator(textarea, op, block, true);
+    }
+
+    fn apply_operator(
+        &mut self,
+        textarea: &mut TextArea,
+        op: Operator,
+        range: std::ops::Range<usize>,
+        linewise: bool,
+    ) {
+        let text = textarea.text().to_string();
+        let start = range.start.min(text.len());
+        let end = range.end.min(text.len());
+        if start == end && !linewise {
+            // Nothing to act on (e.g. `x` on an empty line); keep the register.
+            if op == Operator::Change {
+                self.enter_insert(textarea, Some(start));
+            } else {
+                self.keys.clear();
+            }
+            return;
+        }
+        let mut yanked = text[start..end].to_string();
+        if linewise && !yanked.ends_with('\n') {
+            // The last line has no trailing newline; the register still holds a full line.
+            yanked.push('\n');
+        }
+        self.register = Register {
+            text: yanked,
+            linewise,
+        };
+
+        match op {
+            Operator::Yank => {
+                textarea.set_cursor(start);
+                self.keys.clear();
+            }
+            Operator::Delete => {
+                let (del_start, del_end) = if linewise && end == text.len() && start > 0 && !text[start..end].ends_with('\n') {
+                    // Deleting the last line also removes the newline before it.
+                    (start - 1, end)
+                } else {
+                    (start, end)
+                };
+                if del_start < del_end {
+                    textarea.replace_range(del_start..del_end, "");
+                }
+                let new_text = textarea.text();
+                let cursor = if linewise {
+                    first_non_blank(new_text, del_start.min(new_text.len()))
+                } else {
+                    del_start.min(new_text.len())
+                };
+                textarea.set_cursor(cursor);
+                self.finish_change();
+            }
+            Operator::Change => {
+                let depth = textarea.undo_depth();
+                let (chg_start, chg_end) = if linewise {
+                    // Keep the line break; replace the line contents.
+                    let content_end = if text[start..end].ends_with('\n') { end - 1 } else { end };
+                    (start, content_end)
+                } else {
+                    (start, end)
+                };
+                if chg_start < chg_end {
+                    textarea.replace_range(chg_start..chg_end, "");
+                }
+                textarea.set_cursor(chg_start);
+                self.mode = VimMode::Insert;
+                self.recording_insert = true;
+                self.insert_undo_depth = Some(depth);
+            }
+        }
+    }
+
+    fn paste(&mut self, textarea: &mut TextArea, after: bool, count: usize) {
+        if self.register.text.is_empty() {
+            return;
+        }
+        let text = textarea.text();
+        let pos = textarea.cursor();
+        if self.register.linewise {
+            let body = self.register.text.trim_end_matches('\n').to_string();
+            let block = vec![body; count].join("\n");
+            if after {
+                let end = line_end(text, pos);
+                textarea.insert_str_at(end, &format!("\n{block}"));
+                textarea.set_cursor(end + 1);
+            } else {
+                let start = line_start(text, pos);
+                textarea.insert_str_at(start, &format!("{block}\n"));
+                textarea.set_cursor(start);
+            }
+        } else {
+            let block = self.register.text.repeat(count);
+            let at = if after && pos < line_end(text, pos) {
+                next_char(text, pos)
+            } else {
+                pos
+            };
+            textarea.insert_str_at(at, &block);
+            let last = prev_char(textarea.text(), at + block.len());
+            textarea.set_cursor(last);
+        }
+    }
+
+    fn enter_insert(&mut self, textarea: &mut TextArea, at: Option<usize>) {
+        if let Some(at) = at {
+            textarea.set_cursor(at);
+        }
+        self.mode = VimMode::Insert;
+        self.recording_insert = true;
+        self.insert_undo_depth = Some(textarea.undo_depth());
+        self.count = None;
+        self.pending_op = None;
+        self.pending_g = false;
+    }
+
+    fn finish_change(&mut self) {
+        if !self.replaying {
+            self.last_change = std::mem::take(&mut self.keys);
+        }
+        self.keys.clear();
+    }
+
+    fn repeat_last_change(&mut self, textarea: &mut TextArea, count: usize) {
+        if self.last_change.is_empty() {
+            return;
+        }
+        let keys = self.last_change.clone();
+        self.replaying = true;
+        for _ in 0..count {
+            for key in &keys {
+                match (self.mode, key.code) {
+                    (VimMode::Insert, KeyCode::Esc) => self.escape(textarea),
+                    (VimMode::Insert, _) => textarea.input(*key),
+                    _ => {
+                        self.handle_key(textarea, *key);
+                    }
+                }
+            }
+            if self.mode == VimMode::Insert {
+                self.escape(textarea);
+            }
+        }
+        self.replaying = false;
+        self.keys.clear();
+    }
+
+    fn clear_pending(&mut self) {
+        self.count = None;
+        self.pending_op = None;
+        self.pending_g = false;
+        if !self.recording_insert {
+            self.keys.clear();
+        }
+    }
+}
+
+fn motion_for_char(ch: char) -> Option<Motion> {
+    Some(match ch {
+        'h' => Motion::Left,
+        'l' | ' ' => Motion::Right,
+        'j' => Motion::Down,
+        'k' => Motion::Up,
+        'w' | 'W' => Motion::WordForward,
+        'b' | 'B' => Motion::WordBackward,
+        'e' | 'E' => Motion::WordEnd,
+        '0' => Motion::LineStart,
+        '^' => Motion::FirstNonBlank,
+        '$' => Motion::LineEnd,
+        'G' => Motion::DocEnd,
+        _ => return None,
+    })
+}
+
+fn motion_target(textarea: &TextArea, motion: Motion, count: usize) -> usize {
+    let text = textarea.text();
+    let mut pos = textarea.cursor();
+    match motion {
+        Motion::Left => {
+            let start = line_start(text, pos);
+            for _ in 0..count {
+                if pos > start {
+                    pos = prev_char(text, pos);
+                }
+            }
+        }
+        Motion::Right => {
+            let end = line_end(text, pos);
+            for _ in 0..count {
+                if pos < end {
+                    pos = next_char(text, pos);
+                }
+            }
+        }
+        Motion::Up | Motion::Down => {
+            let col = text[line_start(text, pos)..pos].chars().count();
+            for _ in 0..count {
+                pos = if motion == Motion::Up {
+                    let start = line_start(text, pos);
+                    if start == 0 {
+                        break;
+                    }
+                    line_start(text, start - 1)
+                } else {
+                    let end = line_end(text, pos);
+                    if end >= text.len() {
+                        break;
+                    }
+                    end + 1
+                };
+            }
+            let start = line_start(text, pos);
+            let end = line_end(text, pos);
+            pos = text[start..end]
+                .char_indices()
+                .nth(col)
+                .map(|(idx, _)| start + idx)
+                .unwrap_or(end);
+        }
+        Motion::WordForward => {
+            for _ in 0..count {
+                pos = next_word_start(text, pos);
+            }
+        }
+        Motion::WordBackward => {
+            for _ in 0..count {
+                pos = prev_word_start(text, pos);
+            }
+        }
+        Motion::WordEnd => {
+            for _ in 0..count {
+                pos = word_end(text, pos);
+            }
+        }
+        Motion::LineStart => pos = line_start(text, pos),
+        Motion::FirstNonBlank => pos = first_non_blank(text, pos),
+        Motion::LineEnd => {
+            let mut end = line_end(text, pos);
+            for _ in 1..count {
+                if end >= text.len() {
+                    break;
+                }
+                end = line_end(text, end + 1);
+            }
+            pos = if end > line_start(text, end) {
+                prev_char(text, end)
+            } else {
+                end
+            };
+        }
+        Motion::DocStart => pos = 0,
+        Motion::DocEnd => pos = line_start(text, text.len()),
+    }
+    pos
+}
+
+/// Byte range covering whole lines from the line of `from` through the line
+/// of `to`, including the final line break when there is one.
+fn line_block(text: &str, from: usize, to: usize) -> std::ops::Range<usize> {
+    let start = line_start(text, from);
+    let end = line_end(text, to);
+    let end = if end < text.len() { end + 1 } else { end };
+    start..end
+}
+
+/// Keep the normal-mode cursor on a character rather than past line end.
+fn clamp_normal_cursor(textarea: &mut TextArea) {
+    let text = textarea.text();
+    let pos = textarea.cursor();
+    if pos > line_start(text, pos) && pos >= line_end(text, pos) {
+        let back = prev_char(text, pos);
+        textarea.set_cursor(back);
+    }
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos.min(text.len())].rfind('\n').map(|idx| idx + 1).unwrap_or(0)
+}
+
+fn line_end(text: &str, pos: usize) -> usize {
+    let pos = pos.min(text.len());
+    text[pos..].find('\n').map(|idx| pos + idx).unwrap_or(text.len())
+}
+
+fn first_non_blank(text: &str, pos: usize) -> usize {
+    let start = line_start(text, pos);
+    let end = line_end(text, pos);
+    text[start..end]
+        .char_indices()
+        .find(|(_, c)| !matches!(c, ' ' | '\t'))
+        .map(|(idx, _)| start + idx)
+        .unwrap_or(end)
+}
+
+fn char_at(text: &str, pos: usize) -> Option<char> {
+    text.get(pos..)?.chars().next()
+}
+
+fn next_char(text: &str, pos: usize) -> usize {
+    char_at(text, pos).map(|c| pos + c.len_utf8()).unwrap_or(text.len())
+}
+
+fn prev_char(text: &str, pos: usize) -> usize {
+    text[..pos.min(text.len())]
+        .chars()
+        .next_back()
+        .map(|c| pos - c.len_utf8())
+        .unwrap_or(0)
+}
+
+#[derive(PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punct,
+}
+
+fn class_of(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Blank
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+fn next_word_start(text: &str, pos: usize) -> usize {
+    let mut pos = pos;
+    if let Some(c) = char_at(text, pos) {
+        let class = class_of(c);
+        if class != CharClass::Blank {
+            while char_at(text, pos).is_some_and(|c| class_of(c) == class) {
+                pos = next_char(text, pos);
+            }
+        }
+    }
+    while char_at(text, pos).is_some_and(char::is_whitespace) {
+        pos = next_char(text, pos);
+    }
+    pos
+}
+
+fn prev_word_start(text: &str, pos: usize) -> usize {
+    let mut pos = pos;
+    while pos > 0 && char_at(text, prev_char(text, pos)).is_some_and(char::is_whitespace) {
+        pos = prev_char(text, pos);
+    }
+    if pos == 0 {
+        return 0;
+    }
+    pos = prev_char(text, pos);
+    let Some(class) = char_at(text, pos).map(class_of) else {
+        return pos;
+    };
+    while pos > 0 && char_at(text, prev_char(text, pos)).is_some_and(|c| class_of(c) == class) {
+        pos = prev_char(text, pos);
+    }
+    pos
+}
+
+fn word_end(text: &str, pos: usize) -> usize {
+    let mut pos = next_char(text, pos);
+    while char_at(text, pos).is_some_and(char::is_whitespace) {
+        pos = next_char(text, pos);
+    }
+    let Some(class) = char_at(text, pos).map(class_of) else {
+        return prev_char(text, text.len());
+    };
+    while char_at(text, next_char(text, pos)).is_some_and(|c| class_of(c) == class) {
+        pos = next_char(text, pos);
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(text: &str, cursor: usize) -> (VimState, TextArea) {
+        let mut textarea = TextArea::new();
+        textarea.set_text(text);
+        textarea.set_cursor(cursor);
+        let mut vim = VimState::new();
+        vim.escape(&mut textarea);
+        textarea.set_cursor(cursor);
+        (vim, textarea)
+    }
+
+    fn keys(vim: &mut VimState, textarea: &mut TextArea, input: &str) {
+        for c in input.chars() {
+            vim.handle_key(textarea, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn delete_word_with_count_and_undo_redo() {
+        let (mut vim, mut textarea) = setup("one two three four", 0);
+        keys(&mut vim, &mut textarea, "2dw");
+        assert_eq!(textarea.text(), "three four");
+        keys(&mut vim, &mut textarea, "u");
+        assert_eq!(textarea.text(), "one two three four");
+        keys(&mut vim, &mut textarea, "U");
+        assert_eq!(textarea.text(), "three four");
+    }
+
+    #[test]
+    fn yank_line_and_paste_below() {
+        let (mut vim, mut textarea) = setup("alpha\nbeta", 0);
+        keys(&mut vim, &mut textarea, "yyjp");
+        assert_eq!(textarea.text(), "alpha\nbeta\nalpha");
+        keys(&mut vim, &mut textarea, "ggdd");
+        assert_eq!(textarea.text(), "beta\nalpha");
+    }
+
+    #[test]
+    fn change_word_then_dot_repeats() {
+        let (mut vim, mut textarea) = setup("foo foo", 0);
+        keys(&mut vim, &mut textarea, "cw");
+        assert_eq!(vim.mode(), VimMode::Insert);
+        for c in "bar".chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            vim.record_insert_key(key);
+            textarea.input(key);
+        }
+        vim.escape(&mut textarea);
+        assert_eq!(textarea.text(), "bar foo");
+        keys(&mut vim, &mut textarea, "w.");
+        assert_eq!(textarea.text(), "bar bar");
+        keys(&mut vim, &mut textarea, "u");
+        assert_eq!(textarea.text(), "bar foo");
+    }
+
+    #[test]
+    fn visual_delete_and_line_motions() {
+        let (mut vim, mut textarea) = setup("hello world", 0);
+        keys(&mut vim, &mut textarea, "vlld");
+        assert_eq!(textarea.text(), "lo world");
+        keys(&mut vim, &mut textarea, "$x");
+        assert_eq!(textarea.text(), "lo worl");
+        keys(&mut vim, &mut textarea, "0D");
+        assert_eq!(textarea.text(), "");
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashSet;
+
+use code_core::worktree_manager::{self, ManagedWorktree};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::bottom_pane_view::{BottomPaneView, ConditionalUpdate};
+use super::{BottomPane, CancellationEvent};
+
+const MAX_VISIBLE_ROWS: usize = 10;
+
+/// Every worktree created by `/branch` or agents, with owner, divergence,
+/// dirty state and size. Rows can be marked for bulk pruning.
+pub(crate) struct WorktreesView {
+    worktrees: Vec<ManagedWorktree>,
+    marked: HashSet<usize>,
+    selected: usize,
+    top: usize,
+    app_event_tx: AppEventSender,
+    is_complete: bool,
+}
+
+impl WorktreesView {
+    pub fn new(worktrees: Vec<ManagedWorktree>, app_event_tx: AppEventSender) -> Self {
+        Self {
+            worktrees,
+            marked: HashSet::new(),
+            selected: 0,
+            top: 0,
+            app_event_tx,
+            is_complete: false,
+        }
+    }
+
+    fn move_up(&mut self) {
+        let len = self.worktrees.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if self.selected == 0 {
+            len - 1
+        } else {
+            self.selected - 1
+        };
+        self.ensure_visible();
+    }
+
+    fn move_down(&mut self) {
+        let len = self.worktrees.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected + 1) % len;
+        self.ensure_visible();
+    }
+
+    fn ensure_visible(&mut self) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + MAX_VISIBLE_ROWS {
+            self.top = self.selected + 1 - MAX_VISIBLE_ROWS;
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        if self.selected >= self.worktrees.len() {
+            return;
+        }
+        if !self.marked.remove(&self.selected) {
+            self.marked.insert(self.selected);
+        }
+    }
+
+    /// Marked rows, or the selected row when nothing is marked.
+    fn targets(&self) -> Vec<ManagedWorktree> {
+        if self.marked.is_empty() {
+            return self
+                .worktrees
+                .get(self.selected)
+                .cloned()
+                .into_iter()
+                .collect();
+        }
+        let mut indices: Vec<usize> = self.marked.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .filter_map(|idx| self.worktrees.get(idx).cloned())
+            .collect()
+    }
+
+    fn prune(&mut self, force: bool) {
+        let worktrees = self.targets();
+        if worktrees.is_empty() {
+            return;
+        }
+        self.app_event_tx
+            .send(AppEvent::PruneWorktrees { worktrees, force });
+        self.is_complete = true;
+    }
+
+    fn open(&mut self) {
+        if let Some(worktree) = self.worktrees.get(self.selected) {
+            self.app_event_tx
+                .send(AppEvent::SwitchCwd(worktree.path.clone(), None));
+            self.is_complete = true;
+        }
+    }
+
+    fn diff(&mut self) {
+        if let Some(worktree) = self.worktrees.get(self.selected) {
+            self.app_event_tx
+                .send(AppEvent::ShowWorktreeDiff(worktree.clone()));
+            self.is_complete = true;
+        }
+    }
+
+    fn row_lines(&self) -> Vec<Line<'static>> {
+        if self.worktrees.is_empty() {
+            return vec![Line::from(Span::styled(
+                "No worktrees found.",
+                Style::default().fg(crate::colors::text_dim()),
+            ))];
+        }
+        let end = (self.top + MAX_VISIBLE_ROWS).min(self.worktrees.len());
+        let mut lines = Vec::new();
+        for idx in self.top..end {
+            let worktree = &self.worktrees[idx];
+            let selected = idx == self.selected;
+            let base = if selected {
+                Style::default().bg(crate::colors::selection())
+            } else {
+                Style::default()
+            };
+            let marker = if selected { "›" } else { " " };
+            let check = if self.marked.contains(&idx) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            let counts = match (worktree.ahead, worktree.behind) {
+                (Some(ahead), Some(behind)) => format!("+{ahead} -{behind}"),
+                _ => "?".to_string(),
+            };
+            let counts_style = if worktree.ahead.is_some_and(|ahead| ahead > 0) {
+                base.fg(crate::colors::warning())
+            } else {
+                base.fg(crate::colors::text_dim())
+            };
+            let mut spans = vec![
+                Span::styled(format!("{marker} "), base.fg(crate::colors::primary())),
+                Span::styled(format!("{check} "), base.fg(crate::colors::text())),
+                Span::styled(
+                    format!("{}/{} ", worktree.repo_name, worktree.name),
+                    base.fg(crate::colors::text()).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("{counts} "), counts_style),
+            ];
+            if worktree.dirty {
+                spans.push(Span::styled("dirty ", base.fg(crate::colors::warning())));
+            }
+            spans.push(Span::styled(
+                format!(
+                    "{}  {}",
+                    worktree_manager::format_disk_usage(worktree.disk_bytes),
+                    worktree.owner_label()
+                ),
+                base.fg(crate::colors::text_dim()),
+            ));
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
+    fn detail_line(&self) -> Line<'static> {
+        let dim = Style::default().fg(crate::colors::text_dim());
+        let Some(worktree) = self.worktrees.get(self.selected) else {
+            return Line::from("");
+        };
+        let branch = worktree.branch.as_deref().unwrap_or("(detached)");
+        let base = worktree.base.as_deref().unwrap_or("unknown base");
+        let blockers = worktree.prune_blockers(false);
+        let mut spans = vec![Span::styled(format!("{branch} vs {base}"), dim)];
+        if !blockers.is_empty() {
+            spans.push(Span::styled(
+                format!("  keep: {}", blockers.join(", ")),
+                Style::default().fg(crate::colors::warning()),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        let key = |text: &'static str| {
+            Span::styled(text, Style::default().fg(crate::colors::light_blue()))
+        };
+        Line::from(vec![
+            key("Space"),
+            Span::raw(" Mark  "),
+            key("Enter"),
+            Span::raw(" Open  "),
+            key("d"),
+            Span::raw(" Diff  "),
+            key("p"),
+            Span::raw(" Prune  "),
+            key("Shift+P"),
+            Span::raw(" Force prune  "),
+            Span::styled("Esc", Style::default().fg(crate::colors::error())),
+            Span::raw(" Close"),
+        ])
+    }
+}
+
+impl<'a> BottomPaneView<'a> for WorktreesView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Enter => self.open(),
+            KeyCode::Char('d') | KeyCode::Char('D') => self.diff(),
+            KeyCode::Char('P') => self.prune(true),
+            KeyCode::Char('p') => self.prune(key_event.modifiers.contains(KeyModifiers::SHIFT)),
+            KeyCode::Esc => self.is_complete = true,
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'a>) -> CancellationEvent {
+        self.is_complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn update_status_text(&mut self, _text: String) -> ConditionalUpdate {
+        ConditionalUpdate::NeedsRedraw
+    }
+
+    fn desired_height(&self, _width: u16) -> u16 {
+        let rows = self.worktrees.len().clamp(1, MAX_VISIBLE_ROWS);
+        // Borders, detail line, and footer.
+        (rows + 4) as u16
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Clear.render(area, buf);
+        let title = if self.marked.is_empty() {
+            format!(" Worktrees ({}) ", self.worktrees.len())
+        } else {
+            format!(
+                " Worktrees ({}, {} marked) ",
+                self.worktrees.len(),
+                self.marked.len()
+            )
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [rows_area, detail_area, footer_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        Paragraph::new(self.row_lines())
+            .style(Style::default().bg(crate::colors::background()))
+            .render(rows_area, buf);
+        Paragraph::new(self.detail_line())
+            .style(Style::default().bg(crate::colors::background()))
+            .render(detail_area, buf);
+        Paragraph::new(self.footer_line())
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::mpsc::channel;
+
+    fn worktree(name: &str) -> ManagedWorktree {
+        ManagedWorktree {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/tmp/{name}")),
+            repo_name: "repo".to_string(),
+            repo_root: None,
+            branch: Some(name.to_string()),
+            metadata: None,
+            active_pid: None,
+            base: Some("main".to_string()),
+            ahead: Some(0),
+            behind: Some(0),
+            dirty: false,
+            disk_bytes: 0,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn prune_targets_marked_rows_or_selection() {
+        let (tx, rx) = channel();
+        let mut view = WorktreesView::new(
+            vec![worktree("a"), worktree("b"), worktree("c")],
+            AppEventSender::new(tx),
+        );
+        view.move_down();
+        assert_eq!(view.targets().len(), 1);
+        assert_eq!(view.targets()[0].name, "b");
+
+        view.toggle_mark();
+        view.move_down();
+        view.toggle_mark();
+        view.prune(false);
+        match rx.try_recv() {
+            Ok(AppEvent::PruneWorktrees { worktrees, force }) => {
+                let names: Vec<&str> = worktrees.iter().map(|wt| wt.name.as_str()).collect();
+                assert_eq!(names, vec!["b", "c"]);
+                assert!(!force);
+            }
+            other => panic!("expected PruneWorktrees, got {other:?}"),
+        }
+        assert!(view.is_complete);
+    }
+}
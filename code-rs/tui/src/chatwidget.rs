@@ -38,6 +38,7 @@ use code_core::git_info::CommitLogEntry;
 use code_core::config_types::AgentConfig;
 use code_core::config_types::AutoDriveContinueMode;
 use code_core::config_types::AutoDriveModelRoutingEntry;
+use code_core::config_types::KeyAction;
 use code_core::config_types::Notifications;
 use code_core::config_types::ReasoningEffort;
 use code_core::config_types::TextVerbosity;
@@ -72,6 +73,7 @@ mod diff_ui;
 mod exec_tools;
 mod gh_actions;
 mod history_render;
+mod history_search;
 mod help_handlers;
 mod settings_handlers;
 mod settings_overlay;
@@ -597,6 +599,52 @@ impl MergeRepoState {
     }
 }
 
+/// A conflicting `/merge` being resolved inside the branch worktree.
+struct MergeResolution {
+    context: MergeConflictContext,
+    /// Files that conflicted when the merge started, in display order.
+    files: Vec<code_core::merge_conflicts::ConflictedFile>,
+    /// Files still unmerged as of the last refresh.
+    unmerged: Vec<code_core::merge_conflicts::ConflictedFile>,
+    /// False until the merge has been started in the worktree.
+    resolving: bool,
+    /// An agent turn is editing files; refresh the panel when it ends.
+    awaiting_agent: bool,
+}
+
+impl MergeResolution {
+    fn rows(&self) -> Vec<MergeConflictRow> {
+        self.files
+            .iter()
+            .map(|file| {
+                let current = self.unmerged.iter().find(|u| u.path == file.path);
+                let (hunks, state) = match current {
+                    Some(current) if current.hunks > 0 || !self.resolving => {
+                        (current.hunks, MergeFileState::Conflicted)
+                    }
+                    Some(_) => (0, MergeFileState::Resolved),
+                    None if self.resolving => (0, MergeFileState::Approved),
+                    None => (file.hunks, MergeFileState::Conflicted),
+                };
+                MergeConflictRow {
+                    path: file.path.clone(),
+                    kind: file.kind,
+                    hunks,
+                    state,
+                }
+            })
+            .collect()
+    }
+
+    fn remaining_conflicts(&self) -> Vec<code_core::merge_conflicts::ConflictedFile> {
+        self.unmerged
+            .iter()
+            .filter(|file| file.hunks > 0)
+            .cloned()
+            .collect()
+    }
+}
+
 async fn run_fast_forward_merge(state: &MergeRepoState) -> Result<(), String> {
     use tokio::process::Command;
 
@@ -800,10 +848,15 @@ impl ChatWidget<'_> {
 }
 use code_git_tooling::{
     create_ghost_commit,
+    diff_snapshot_against_worktree,
+    list_snapshot_refs,
+    restore_files_from_commit,
     restore_ghost_commit,
+    restore_hunks_from_commit,
     CreateGhostCommitOptions,
     GhostCommit,
     GitToolingError,
+    SnapshotFileDiff,
 };
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
@@ -928,6 +981,7 @@ use crate::app_event::{
     AppEvent,
     AutoContinueMode,
     BackgroundPlacement,
+    ExternalEditorTarget,
     GitInitResume,
     ModelSelectionKind,
     TerminalAfter,
@@ -945,7 +999,17 @@ use crate::bottom_pane::model_selection_view::ModelSelectionTarget;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::TerminalFooterMeta;
-use crate::bottom_pane::{UndoTimelineEntry, UndoTimelineEntryKind, UndoTimelineView};
+use crate::bottom_pane::{UndoFilesView, UndoTimelineEntry, UndoTimelineEntryKind, UndoTimelineView};
+use crate::bottom_pane::ContextInspectorView;
+use crate::bottom_pane::WorktreesView;
+use crate::bottom_pane::AgentEvaluationView;
+use crate::bottom_pane::{
+    MergeConflictAction,
+    MergeConflictContext,
+    MergeConflictRow,
+    MergeConflictsView,
+    MergeFileState,
+};
 use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::InputResult;
 use crate::bottom_pane::LoginAccountsState;
@@ -1035,8 +1099,8 @@ use code_core::config_types::{validation_tool_category, ValidationCategory};
 use code_core::protocol::RateLimitSnapshotEvent;
 use code_core::protocol::ValidationGroup;
 use crate::rate_limits_view::{
-    build_limits_view, RateLimitDisplayConfig, RateLimitResetInfo, DEFAULT_DISPLAY_CONFIG,
-    DEFAULT_GRID_CONFIG,
+    build_limits_view, build_usage_report_lines, RateLimitDisplayConfig, RateLimitResetInfo,
+    DEFAULT_DISPLAY_CONFIG, DEFAULT_GRID_CONFIG,
 };
 use crate::session_log;
 use code_core::review_format::format_review_findings_block;
@@ -1616,6 +1680,7 @@ pub(crate) struct ChatWidget<'a> {
     last_developer_message: Option<String>,
     pending_turn_origin: Option<TurnOrigin>,
     pending_request_user_input: Option<PendingRequestUserInput>,
+    merge_resolution: Option<MergeResolution>,
     current_turn_origin: Option<TurnOrigin>,
     // Tracks whether lingering running exec/tool cells have been cleared for the
     // current turn. Reset on TaskStarted; set after the first assistant message
@@ -1711,6 +1776,9 @@ pub(crate) struct ChatWidget<'a> {
     // Help overlay state
     help: HelpState,
 
+    // Transcript search (Ctrl+P by default) state
+    history_search: Option<history_search::HistorySearch>,
+
     // Settings overlay state
     settings: SettingsState,
     // When a standalone picker (model selection) closes, optionally reopen the settings overlay
@@ -1898,8 +1966,10 @@ struct GhostSnapshot {
     commit: GhostCommit,
     captured_at: DateTime<Local>,
     summary: Option<String>,
-    conversation: ConversationSnapshot,
-    history: HistorySnapshot,
+    /// `None` for snapshots from before a resume; only their files can be
+    /// restored.
+    conversation: Option<ConversationSnapshot>,
+    history: Option<HistorySnapshot>,
 }
 
 #[derive(Clone, Copy)]
@@ -1927,8 +1997,19 @@ impl GhostSnapshot {
             commit,
             captured_at: Local::now(),
             summary,
-            conversation,
-            history,
+            conversation: Some(conversation),
+            history: Some(history),
+        }
+    }
+
+    /// A snapshot recorded in the rollout of a resumed session.
+    fn from_earlier_session(commit: GhostCommit, captured_at: DateTime<Local>) -> Self {
+        Self {
+            commit,
+            captured_at,
+            summary: None,
+            conversation: None,
+            history: None,
         }
     }
 
@@ -4892,7 +4973,11 @@ impl ChatWidget<'_> {
                         kind = RenderRequestKind::Explore {
                             id: history_id,
                             hold_header,
-                            full_detail: self.config.tui.show_explore_details,
+                            full_detail: self.config.tui.show_explore_details
+                                || cell
+                                    .as_any()
+                                    .downcast_ref::<history_cell::ExploreAggregationCell>()
+                                    .is_some_and(history_cell::ExploreAggregationCell::is_expanded),
                         };
                     }
                     HistoryRecord::Diff(_) => {
@@ -5306,8 +5391,9 @@ impl ChatWidget<'_> {
             grant_root,
         } = ev;
 
-        // Clone for session storage before moving into history
+        // Clone for session storage and the approval modal before moving into history
         let changes_clone = changes.clone();
+        let approval_changes = changes.clone();
         // Surface the patch summary in the main conversation
         let key = self.next_internal_key();
         let _ = self.history_insert_with_key_global(
@@ -5362,6 +5448,7 @@ impl ChatWidget<'_> {
             id: call_id,
             reason,
             grant_root,
+            changes: approval_changes,
         };
         let ticket = self.make_background_before_next_output_ticket();
         self.bottom_pane.push_approval_request(request, ticket);
@@ -6026,6 +6113,20 @@ impl ChatWidget<'_> {
         self.bottom_pane.set_composer_text(text);
     }
 
+    /// Deliver text returned from the external editor to its target.
+    pub(crate) fn apply_external_editor_result(&mut self, target: ExternalEditorTarget, text: String) {
+        match target {
+            ExternalEditorTarget::Composer => self.set_composer_text(text),
+            other => {
+                if !self.bottom_pane.apply_external_edit(&other, text) {
+                    self.debug_notice(
+                        "Approval is no longer pending; edited text discarded.".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
     // Removed: pending insert sequencing is not used under strict ordering.
 
     pub(crate) fn register_pasted_image(&mut self, placeholder: String, path: std::path::PathBuf) {
@@ -6555,6 +6656,7 @@ impl ChatWidget<'_> {
             last_developer_message: None,
             pending_turn_origin: None,
             pending_request_user_input: None,
+            merge_resolution: None,
             current_turn_origin: None,
             cleared_lingering_execs_this_turn: true,
             exec: ExecState {
@@ -6651,6 +6753,7 @@ impl ChatWidget<'_> {
                 overlay: None,
                 body_visible_rows: std::cell::Cell::new(0),
             },
+            history_search: None,
             settings: SettingsState::default(),
             limits: LimitsState::default(),
             terminal: TerminalState::default(),
@@ -6779,6 +6882,7 @@ impl ChatWidget<'_> {
         w.set_standard_terminal_mode(!config.tui.alternate_screen);
         w.bottom_pane
             .set_auto_review_symbols_only(w.config.tui.auto_review_symbols_only);
+        w.bottom_pane.set_composer_vim_mode(w.config.tui.vim_mode);
         if config.experimental_resume.is_none() {
             if !w.test_mode {
                 w.history_push_top_next_req(history_cell::new_animated_welcome()); // tag: prelude
@@ -6937,6 +7041,7 @@ impl ChatWidget<'_> {
             last_developer_message: None,
             pending_turn_origin: None,
             pending_request_user_input: None,
+            merge_resolution: None,
             current_turn_origin: None,
             cleared_lingering_execs_this_turn: true,
             exec: ExecState {
@@ -7050,6 +7155,7 @@ impl ChatWidget<'_> {
                 overlay: None,
                 body_visible_rows: std::cell::Cell::new(0),
             },
+            history_search: None,
             settings: SettingsState::default(),
             pending_settings_return: None,
             limits: LimitsState::default(),
@@ -7164,6 +7270,7 @@ impl ChatWidget<'_> {
         w.set_standard_terminal_mode(!config.tui.alternate_screen);
         w.bottom_pane
             .set_auto_review_symbols_only(w.config.tui.auto_review_symbols_only);
+        w.bottom_pane.set_composer_vim_mode(w.config.tui.vim_mode);
         if show_welcome && !w.test_mode {
             w.history_push_top_next_req(history_cell::new_animated_welcome());
         }
@@ -7972,6 +8079,9 @@ impl ChatWidget<'_> {
         if self.diffs.overlay.is_some() {
             return;
         }
+        if history_search::handle_history_search_key(self, key_event) {
+            return;
+        }
         if self.browser_overlay_visible {
             let is_ctrl_b = matches!(
                 key_event,
@@ -8230,8 +8340,10 @@ impl ChatWidget<'_> {
         {
             if !self.bottom_pane.has_active_modal_view() {
                 let initial = self.bottom_pane.composer_text();
-                self.app_event_tx
-                    .send(AppEvent::OpenExternalEditor { initial });
+                self.app_event_tx.send(AppEvent::OpenExternalEditor {
+                    initial,
+                    target: ExternalEditorTarget::Composer,
+                });
             }
             return;
         }
@@ -8329,6 +8441,18 @@ impl ChatWidget<'_> {
             }
         }
 
+        let key_event = if self.bottom_pane.has_active_modal_view() {
+            key_event
+        } else {
+            match crate::keymap::translate_key_event(
+                &self.config.tui_keybindings,
+                crate::keymap::COMPOSER_ACTIONS,
+                key_event,
+            ) {
+                Some(translated) => translated,
+                None => return,
+            }
+        };
         let composer_was_empty = self.bottom_pane.composer_is_empty();
         let input_result = self.bottom_pane.handle_key_event(key_event);
         let composer_is_empty = self.bottom_pane.composer_is_empty();
@@ -8911,6 +9035,10 @@ impl ChatWidget<'_> {
             }
         }
 
+        if let Some(tab) = Self::usage_report_tab(&code_home, &account_map) {
+            tabs.push(tab);
+        }
+
         if tabs.is_empty() {
             let mut lines = Self::usage_history_lines(None, false);
             lines.push(Self::dim_line(
@@ -8922,6 +9050,48 @@ impl ChatWidget<'_> {
         tabs
     }
 
+    /// "Report" tab: the last two weeks of the usage ledger broken down by
+    /// account, model, project and session source. Omitted when the ledger
+    /// has nothing for that window.
+    fn usage_report_tab(
+        code_home: &Path,
+        account_map: &HashMap<String, StoredAccount>,
+    ) -> Option<LimitsTab> {
+        use code_core::usage_report::{UsageReport, UsageReportDimension, UsageReportPeriod};
+
+        let period = UsageReportPeriod::Daily;
+        let until = Utc::now();
+        let since = until - period.default_lookback();
+        let entries = account_usage::load_usage_ledger(code_home, since, until).ok()?;
+        if entries.is_empty() {
+            return None;
+        }
+        let report = |group_by| UsageReport::build(&entries, period, group_by, since, until);
+
+        let timeline = report(UsageReportDimension::Account);
+        let mut by_account = timeline.clone();
+        by_account.relabel_keys(|id| account_map.get(id).map(account_display_label));
+        let by_model = report(UsageReportDimension::Model);
+        let by_project = report(UsageReportDimension::Project);
+        let by_source = report(UsageReportDimension::Source);
+
+        let mut lines = build_usage_report_lines(
+            &timeline,
+            &[
+                ("By account", &by_account),
+                ("By model", &by_model),
+                ("By project", &by_project),
+                ("By source", &by_source),
+            ],
+        );
+        lines.push(Self::dim_line(String::new()));
+        lines.push(Self::dim_line(
+            " Export with `magik usage report --format csv` (or json).",
+        ));
+        let header = vec![Self::dim_line(" Last 14 days, all accounts")];
+        Some(LimitsTab::message("Report", header, lines))
+    }
+
     fn usage_cost_usd_from_totals(totals: &TokenTotals) -> f64 {
         let non_cached_input = totals
             .input_tokens
@@ -12303,6 +12473,37 @@ impl ChatWidget<'_> {
         });
     }
 
+    /// Rebuild the `/undo` timeline from snapshots recorded in a resumed
+    /// rollout. Snapshots whose refs were pruned by housekeeping are skipped.
+    fn restore_ghost_snapshots_from_rollout(&mut self, commits: Vec<GhostCommit>) {
+        if commits.is_empty() || !self.config.tui.git_snapshots_enabled {
+            return;
+        }
+        let refs = match list_snapshot_refs(&self.config.cwd) {
+            Ok(refs) => refs,
+            Err(err) => {
+                tracing::debug!("failed to list snapshot refs: {err}");
+                return;
+            }
+        };
+        let created: HashMap<&str, i64> = refs
+            .iter()
+            .map(|snapshot| (snapshot.commit.id(), snapshot.created_at))
+            .collect();
+        let mut restored: Vec<GhostSnapshot> = commits
+            .into_iter()
+            .filter_map(|commit| {
+                let created_at = *created.get(commit.id())?;
+                let captured_at = Local.timestamp_opt(created_at, 0).single()?;
+                Some(GhostSnapshot::from_earlier_session(commit, captured_at))
+            })
+            .collect();
+        restored.append(&mut self.ghost_snapshots);
+        let overflow = restored.len().saturating_sub(MAX_TRACKED_GHOST_COMMITS);
+        restored.drain(..overflow);
+        self.ghost_snapshots = restored;
+    }
+
     fn finalize_ghost_snapshot(
         &mut self,
         request: GhostSnapshotRequest,
@@ -12324,11 +12525,22 @@ impl ChatWidget<'_> {
                     request.history,
                 );
                 self.ghost_snapshots.push(snapshot.clone());
-                session_log::log_history_snapshot(
-                    snapshot.commit().id(),
-                    snapshot.summary.as_deref(),
-                    &snapshot.history,
-                );
+                if let Some(history) = snapshot.history.as_ref() {
+                    session_log::log_history_snapshot(
+                        snapshot.commit().id(),
+                        snapshot.summary.as_deref(),
+                        history,
+                    );
+                }
+                if self
+                    .code_op_tx
+                    .send(Op::RecordGhostSnapshot {
+                        ghost_commit: snapshot.commit().clone(),
+                    })
+                    .is_err()
+                {
+                    tracing::warn!("failed to send ghost snapshot to core");
+                }
                 if self.ghost_snapshots.len() > MAX_TRACKED_GHOST_COMMITS {
                     self.ghost_snapshots.remove(0);
                 }
@@ -12679,8 +12891,14 @@ impl ChatWidget<'_> {
         let relative_time = snapshot
             .age_from(Local::now())
             .map(|age| format!("captured {} ago", format_duration(age)));
-        let (user_delta, assistant_delta) = self.conversation_delta_since(&snapshot.conversation);
-        let stats_line = if user_delta == 0 && assistant_delta == 0 {
+        let (user_delta, assistant_delta) = snapshot
+            .conversation
+            .as_ref()
+            .map(|conversation| self.conversation_delta_since(conversation))
+            .unwrap_or_default();
+        let stats_line = if snapshot.conversation.is_none() {
+            Some("files only • captured before this session was resumed".to_string())
+        } else if user_delta == 0 && assistant_delta == 0 {
             Some("conversation already matches current state".to_string())
         } else if assistant_delta == 0 {
             Some(format!(
@@ -12698,7 +12916,11 @@ impl ChatWidget<'_> {
             ))
         };
 
-        let conversation_lines = Self::conversation_preview_lines_from_snapshot(&snapshot.history);
+        let conversation_lines = snapshot
+            .history
+            .as_ref()
+            .map(Self::conversation_preview_lines_from_snapshot)
+            .unwrap_or_default();
         let file_lines = self.timeline_file_lines_for_commit(snapshot.commit().id());
 
         UndoTimelineEntry {
@@ -13229,9 +13451,16 @@ impl ChatWidget<'_> {
         }
 
         if restore_conversation {
-            let (user_delta, assistant_delta) =
-                self.conversation_delta_since(&snapshot.conversation);
-            if user_delta == 0 {
+            let (user_delta, assistant_delta) = snapshot
+                .conversation
+                .as_ref()
+                .map(|conversation| self.conversation_delta_since(conversation))
+                .unwrap_or_default();
+            if snapshot.conversation.is_none() {
+                self.push_background_tail(
+                    "Conversation rewind is unavailable for snapshots captured before the resume.".to_string(),
+                );
+            } else if user_delta == 0 {
                 self.push_background_tail(
                     "Conversation already matches selected snapshot; nothing to rewind.".to_string(),
                 );
@@ -13239,7 +13468,7 @@ impl ChatWidget<'_> {
                 self.app_event_tx.send(AppEvent::JumpBack {
                     nth: user_delta,
                     prefill: String::new(),
-                    history_snapshot: Some(snapshot.history.clone()),
+                    history_snapshot: snapshot.history.clone(),
                 });
                 if assistant_delta > 0 {
                     self.push_background_tail(format!(
@@ -13299,6 +13528,108 @@ impl ChatWidget<'_> {
         self.request_redraw();
     }
 
+    pub(crate) fn show_undo_file_picker(&mut self, commit_id: &str) {
+        let Some(snapshot) = self
+            .ghost_snapshots
+            .iter()
+            .find(|snap| snap.commit().id() == commit_id)
+            .cloned()
+        else {
+            self.push_background_tail("Selected snapshot is no longer available.".to_string());
+            return;
+        };
+
+        match diff_snapshot_against_worktree(&self.config.cwd, commit_id) {
+            Ok(files) if files.is_empty() => {
+                self.push_background_tail(format!(
+                    "Workspace files already match snapshot {}.",
+                    snapshot.short_id()
+                ));
+            }
+            Ok(files) => {
+                let view = UndoFilesView::new(
+                    commit_id.to_string(),
+                    format!("snapshot {}", snapshot.short_id()),
+                    files,
+                    self.app_event_tx.clone(),
+                );
+                self.bottom_pane.show_undo_files_view(view);
+            }
+            Err(err) => {
+                self.history_push_plain_state(history_cell::new_error_event(format!(
+                    "Failed to diff snapshot {}: {err}",
+                    snapshot.short_id()
+                )));
+            }
+        }
+        self.request_redraw();
+    }
+
+    pub(crate) fn perform_partial_undo(
+        &mut self,
+        commit_id: &str,
+        files: &[PathBuf],
+        hunks: &[(SnapshotFileDiff, Vec<usize>)],
+    ) {
+        if !self.config.tui.git_snapshots_enabled {
+            self.push_background_tail(
+                "Undo restore unavailable because Git snapshots are disabled in /settings review.".to_string(),
+            );
+            return;
+        }
+        let short_id: String = commit_id.chars().take(8).collect();
+
+        // Checkpoint first so the partial restore can itself be undone.
+        let _ = self.capture_ghost_snapshot_blocking(Some("Pre-undo checkpoint".to_string()));
+
+        let mut errors: Vec<String> = Vec::new();
+        let mut restored_files = 0usize;
+        if let Err(err) = restore_files_from_commit(&self.config.cwd, commit_id, files) {
+            errors.push(format!("Failed to restore files from snapshot {short_id}: {err}"));
+        } else {
+            restored_files = files.len();
+        }
+
+        let mut restored_hunks = 0usize;
+        for (file, indexes) in hunks {
+            match restore_hunks_from_commit(&self.config.cwd, file, indexes) {
+                Ok(()) => restored_hunks += indexes.len(),
+                Err(err) => errors.push(format!(
+                    "Failed to restore hunks of {}: {err}",
+                    file.path.display()
+                )),
+            }
+        }
+        if restored_files > 0 || restored_hunks > 0 {
+            bump_snapshot_epoch_for(&self.config.cwd);
+        }
+
+        for err in errors {
+            self.history_push_plain_state(history_cell::new_error_event(err));
+        }
+
+        let mut parts: Vec<String> = Vec::new();
+        if restored_files > 0 {
+            parts.push(format!(
+                "{restored_files} file{}",
+                if restored_files == 1 { "" } else { "s" }
+            ));
+        }
+        if restored_hunks > 0 {
+            parts.push(format!(
+                "{restored_hunks} hunk{}",
+                if restored_hunks == 1 { "" } else { "s" }
+            ));
+        }
+        if !parts.is_empty() {
+            self.push_background_tail(format!(
+                "Restored {} from snapshot {short_id} • other changes kept",
+                parts.join(" and ")
+            ));
+        }
+        self.request_redraw();
+    }
+
     fn reset_after_conversation_restore(&mut self) {
         self.pending_dispatched_user_messages.clear();
         self.pending_user_prompts_for_next_turn = 0;
@@ -13667,7 +13998,12 @@ impl ChatWidget<'_> {
             }
             EventMsg::ReplayHistory(ev) => {
                 self.clear_resume_placeholder();
-                let code_core::protocol::ReplayHistoryEvent { items, history_snapshot } = ev;
+                let code_core::protocol::ReplayHistoryEvent {
+                    items,
+                    history_snapshot,
+                    ghost_snapshots,
+                } = ev;
+                self.restore_ghost_snapshots_from_rollout(ghost_snapshots);
                 self.replay_history_depth = self.replay_history_depth.saturating_add(1);
                 let max_req = self.last_seen_request_index;
                 let mut processed_snapshot = false;
@@ -14002,6 +14338,7 @@ impl ChatWidget<'_> {
                 // Final re-check for idle state
                 self.maybe_hide_spinner();
                 self.maybe_trigger_auto_review();
+                self.refresh_merge_resolution_after_turn();
                 self.emit_turn_complete_notification(last_agent_message);
                 self.suppress_next_agent_hint = false;
                 self.mark_needs_redraw();
@@ -15259,6 +15596,10 @@ impl ChatWidget<'_> {
                 self.mcp_server_failures = ev.server_failures.unwrap_or_default();
                 self.refresh_mcp_settings_overlay();
             }
+            EventMsg::ContextInspection(ev) => {
+                let view = ContextInspectorView::new(ev, self.app_event_tx.clone());
+                self.bottom_pane.show_context_inspector_view(view);
+            }
             EventMsg::ListSkillsResponse(ev) => {
                 let len = ev.skills.len();
                 debug!("received {len} skills");
@@ -15570,6 +15911,7 @@ impl ChatWidget<'_> {
                 self.pending_request_user_input = None;
             }
             EventMsg::ConversationPath(_) => {}
+            EventMsg::ThreadRolledBack(_) => {}
             EventMsg::EnteredReviewMode(review_request) => {
                 if self.auto_resolve_enabled() {
                     self.auto_resolve_handle_review_enter();
@@ -16990,6 +17332,197 @@ impl ChatWidget<'_> {
         self.show_settings_overlay(Some(SettingsSection::Agents));
     }
 
+    pub(crate) fn handle_context_command(&mut self) {
+        self.submit_op(Op::InspectContext);
+    }
+
+    pub(crate) fn handle_worktrees_command(&mut self) {
+        self.bottom_pane
+            .flash_footer_notice("Scanning worktrees…".to_string());
+        self.request_redraw();
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let worktrees = code_core::worktree_manager::list_worktrees().await;
+            tx.send(AppEvent::WorktreesLoaded(worktrees));
+        });
+    }
+
+    pub(crate) fn present_worktrees(
+        &mut self,
+        worktrees: Vec<code_core::worktree_manager::ManagedWorktree>,
+    ) {
+        if worktrees.is_empty() {
+            self.bottom_pane
+                .flash_footer_notice("No worktrees from /branch or agents found".to_string());
+            self.request_redraw();
+            return;
+        }
+        let view = WorktreesView::new(worktrees, self.app_event_tx.clone());
+        self.bottom_pane.show_worktrees_view(view);
+    }
+
+    pub(crate) fn prune_worktrees(
+        &mut self,
+        worktrees: Vec<code_core::worktree_manager::ManagedWorktree>,
+        force: bool,
+    ) {
+        use code_core::worktree_manager::PruneOutcome;
+
+        let tx = self.app_event_tx.clone();
+        let ticket = self.make_background_tail_ticket();
+        tokio::spawn(async move {
+            let mut lines = Vec::new();
+            let mut freed = 0u64;
+            for worktree in &worktrees {
+                let label = format!("{}/{}", worktree.repo_name, worktree.name);
+                match code_core::worktree_manager::prune_worktree(worktree, force).await {
+                    PruneOutcome::Removed { bytes } => {
+                        freed += bytes;
+                        lines.push(format!("  removed {label}"));
+                    }
+                    PruneOutcome::Skipped { reasons } => {
+                        lines.push(format!("  kept {label}: {}", reasons.join(", ")));
+                    }
+                    PruneOutcome::Failed(err) => {
+                        lines.push(format!("  failed {label}: {err}"));
+                    }
+                }
+            }
+            tx.send_background_event_with_ticket(
+                &ticket,
+                format!(
+                    "`/worktrees` — freed {}\n{}",
+                    code_core::worktree_manager::format_disk_usage(freed),
+                    lines.join("\n")
+                ),
+            );
+        });
+    }
+
+    pub(crate) fn handle_evaluate_command(&mut self, args: String) {
+        use code_core::agent_evaluation::{CandidateInput, EvaluationSettings};
+
+        let requested_batch = args.trim().to_string();
+        let settings = EvaluationSettings {
+            project_root: self.config.cwd.clone(),
+            commands: self.config.project_commands.clone(),
+            validation: self.config.validation.clone(),
+            github: self.config.github.clone(),
+        };
+        let tx = self.app_event_tx.clone();
+        let ticket = self.make_background_tail_ticket();
+        self.bottom_pane
+            .flash_footer_notice("Collecting agent worktrees…".to_string());
+        self.request_redraw();
+        tokio::spawn(async move {
+            let agents = {
+                let manager = code_core::AGENT_MANAGER.read().await;
+                manager.list_agents(Some(code_core::AgentStatus::Completed), None, false)
+            };
+            let mut agents: Vec<_> = agents
+                .into_iter()
+                .filter(|agent| {
+                    agent.worktree_path.is_some()
+                        && !matches!(agent.source_kind, Some(AgentSourceKind::AutoReview))
+                })
+                .collect();
+            // Default to the batch of the most recently created agent.
+            let batch = if requested_batch.is_empty() {
+                agents
+                    .iter()
+                    .max_by_key(|agent| agent.created_at)
+                    .and_then(|agent| agent.batch_id.clone())
+            } else {
+                Some(requested_batch)
+            };
+            let Some(batch) = batch else {
+                tx.send_background_event_with_ticket(
+                    &ticket,
+                    "`/evaluate` — no completed /code batch with agent worktrees found".to_string(),
+                );
+                return;
+            };
+            agents.retain(|agent| agent.batch_id.as_deref() == Some(batch.as_str()));
+            if agents.len() < 2 {
+                tx.send_background_event_with_ticket(
+                    &ticket,
+                    "`/evaluate` — needs at least two completed agents with worktrees from the \
+                     same /code batch"
+                        .to_string(),
+                );
+                return;
+            }
+            agents.sort_by_key(|agent| agent.created_at);
+            let candidates: Vec<CandidateInput> = agents
+                .into_iter()
+                .filter_map(|agent| {
+                    let worktree = agent.worktree_path.map(PathBuf::from)?;
+                    Some(CandidateInput {
+                        label: agent.name.unwrap_or(agent.model),
+                        agent_id: agent.id,
+                        worktree,
+                        branch: agent.branch_name,
+                        base: agent.worktree_base,
+                    })
+                })
+                .collect();
+            let scores = code_core::agent_evaluation::evaluate_candidates(
+                candidates,
+                &settings,
+                |message| {
+                    tx.send_background_event_with_ticket(
+                        &ticket,
+                        format!("`/evaluate` — {message}"),
+                    );
+                },
+            )
+            .await;
+            tx.send(AppEvent::AgentEvaluationFinished(scores));
+        });
+    }
+
+    pub(crate) fn present_agent_evaluation(
+        &mut self,
+        scores: Vec<code_core::agent_evaluation::CandidateScore>,
+    ) {
+        let view = AgentEvaluationView::new(scores, self.app_event_tx.clone());
+        self.bottom_pane.show_agent_evaluation_view(view);
+    }
+
+    pub(crate) fn apply_evaluated_candidate(
+        &mut self,
+        score: code_core::agent_evaluation::CandidateScore,
+    ) {
+        let cwd = self.config.cwd.clone();
+        let tx = self.app_event_tx.clone();
+        let ticket = self.make_background_tail_ticket();
+        tokio::spawn(async move {
+            let message = match code_core::agent_evaluation::apply_candidate(&cwd, &score).await {
+                Ok(()) => format!(
+                    "`/evaluate` — applied {} ({} file(s), +{} -{}) to {}. Review with `git diff`.",
+                    score.label,
+                    score.files_changed,
+                    score.insertions,
+                    score.deletions,
+                    cwd.display()
+                ),
+                Err(err) => format!("`/evaluate` — could not apply {}: {err}", score.label),
+            };
+            tx.send_background_event_with_ticket(&ticket, message);
+        });
+    }
+
+    pub(crate) fn judge_evaluated_candidates(
+        &mut self,
+        scores: Vec<code_core::agent_evaluation::CandidateScore>,
+    ) {
+        let preface = code_core::agent_evaluation::judge_preface(&scores);
+        self.submit_text_message_with_preface(
+            "Judge the /evaluate candidates".to_string(),
+            preface,
+        );
+    }
+
     pub(crate) fn handle_limits_command(&mut self, args: String) {
         let trimmed = args.trim();
         if trimmed.is_empty() {
@@ -17191,10 +17724,74 @@ impl ChatWidget<'_> {
             self.config.auto_switch_accounts_on_rate_limit,
             self.config.api_key_fallback_on_all_accounts_limited,
             self.config.account_switching_mode,
+            self.account_policy_state(),
         );
         AccountsSettingsContent::new(view)
     }
 
+    /// Collect pin, budget and switch-history state for the accounts view.
+    fn account_policy_state(&self) -> crate::bottom_pane::AccountPolicyState {
+        let code_home = &self.config.code_home;
+        let accounts = auth_accounts::list_accounts(code_home).unwrap_or_default();
+        let label_for = |account_id: &str| {
+            accounts
+                .iter()
+                .find(|account| account.id == account_id)
+                .map(account_display_label)
+                .unwrap_or_else(|| account_id.to_string())
+        };
+
+        let active_account = auth_accounts::get_active_account_id(code_home)
+            .ok()
+            .flatten()
+            .map(|id| {
+                let label = label_for(&id);
+                (id, label)
+            });
+        let project = code_core::ProjectIdentity::discover(&self.config.cwd);
+        let pins = self.config.account_pins.clone();
+        let matching_pin = project
+            .as_ref()
+            .and_then(|project| pins.iter().position(|rule| rule.matches(project)));
+        let pinned_account_label = matching_pin.and_then(|idx| {
+            accounts
+                .iter()
+                .find(|account| code_core::pin_refers_to(&pins[idx].account, account))
+                .map(account_display_label)
+        });
+        let budget = active_account
+            .as_ref()
+            .and_then(|(id, _)| self.config.account_budgets.get(id).copied())
+            .unwrap_or_default();
+        let recent_switches = code_core::account_usage::list_account_switches(code_home, 3)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|record| {
+                let when = record
+                    .switched_at
+                    .with_timezone(&Local)
+                    .format("%b %-d %H:%M");
+                let from = record
+                    .from_account_id
+                    .as_deref()
+                    .map(&label_for)
+                    .unwrap_or_else(|| "none".to_string());
+                let to = label_for(&record.to_account_id);
+                format!("{when}  {from} -> {to} ({})", record.reason.label())
+            })
+            .collect();
+
+        crate::bottom_pane::AccountPolicyState {
+            active_account,
+            project_root: project.map(|project| project.root),
+            pins,
+            matching_pin,
+            pinned_account_label,
+            budget,
+            recent_switches,
+        }
+    }
+
     fn build_magic_settings_content(&self) -> MagicSettingsContent {
         let view = crate::bottom_pane::MagicSettingsView::new(
             self.app_event_tx.clone(),
@@ -22635,20 +23232,27 @@ Have we met every part of this goal and is there no further work to do?"#
             "Rotate agent between Read Only / Write with Approval / Full Access",
         ));
 
+        // Rebindable actions show their effective chords from `[tui.keybindings]`.
+        let keys = &self.config.tui_keybindings;
+        let bound = |action: KeyAction| kv(&keys.display(action), action.description());
+
         // Global
         lines.push(kv("F1", "Help overlay"));
-        lines.push(kv("Ctrl+G", "Open external editor"));
-        lines.push(kv("Ctrl+Y", "Switch model"));
+        lines.push(bound(KeyAction::OpenEditor));
+        lines.push(bound(KeyAction::SwitchModel));
         lines.push(kv("Ctrl+N", "Cycle reasoning level"));
-        lines.push(kv("Ctrl+R", "Toggle reasoning"));
+        lines.push(bound(KeyAction::ToggleReasoning));
         lines.push(kv("Ctrl+E", "Toggle explore details"));
         lines.push(kv("Ctrl+T", "Toggle screen"));
         lines.push(kv("Ctrl+D", "Diff viewer"));
+        lines.push(bound(KeyAction::ScrollPageUp));
+        lines.push(bound(KeyAction::ScrollPageDown));
+        lines.push(bound(KeyAction::HistorySearch));
         lines.push(kv("Esc", &format!("{} / close popups", Self::double_esc_hint_label())));
         // Task control shortcuts
         lines.push(kv("Esc", "End current task"));
-        lines.push(kv("Ctrl+C", "End current task"));
-        lines.push(kv("Ctrl+C twice", "Quit"));
+        lines.push(bound(KeyAction::Interrupt));
+        lines.push(kv(&format!("{} twice", keys.display(KeyAction::Interrupt)), "Quit"));
         lines.push(RtLine::from(""));
 
         // Composer
@@ -22656,17 +23260,15 @@ Have we met every part of this goal and is there no further work to do?"#
             "Compose field",
             t_fg.add_modifier(Modifier::BOLD),
         )]));
-        lines.push(kv("Enter", "Send message"));
+        lines.push(bound(KeyAction::Submit));
         lines.push(kv(
             "Ctrl+O",
             "Snatch prompt (copy to clipboard + clear)",
         ));
         lines.push(kv("Ctrl+L", "Clear input"));
-        lines.push(kv("Ctrl+J", "Insert newline"));
-        lines.push(kv("Shift+Enter", "Insert newline"));
-        // Split combined shortcuts into separate rows for readability
-        lines.push(kv("Shift+Up", "Browse input history"));
-        lines.push(kv("Shift+Down", "Browse input history"));
+        lines.push(bound(KeyAction::Newline));
+        lines.push(bound(KeyAction::HistoryPrev));
+        lines.push(bound(KeyAction::HistoryNext));
         lines.push(kv("Ctrl+B", "Move left"));
         lines.push(kv("Ctrl+F", "Move right"));
         lines.push(kv("Alt+Left", "Move by word"));
@@ -24737,6 +25339,91 @@ Have we met every part of this goal and is there no further work to do?"#
         self.request_redraw();
     }
 
+    fn refresh_accounts_settings_content(&mut self) {
+        let should_refresh_accounts = matches!(
+            self.settings
+                .overlay
+                .as_ref()
+                .map(|overlay| overlay.active_section()),
+            Some(SettingsSection::Accounts)
+        );
+        if should_refresh_accounts {
+            let content = self.build_accounts_settings_content();
+            if let Some(overlay) = self.settings.overlay.as_mut() {
+                overlay.set_accounts_content(content);
+            }
+        }
+    }
+
+    pub(crate) fn set_account_pins(
+        &mut self,
+        pins: Vec<code_core::config_types::AccountPinRule>,
+    ) {
+        if self.config.account_pins == pins {
+            return;
+        }
+        self.config.account_pins = pins.clone();
+
+        let code_home = self.config.code_home.clone();
+        tokio::spawn(async move {
+            if let Err(err) =
+                code_core::config_edit::persist_account_pins(&code_home, &pins).await
+            {
+                tracing::warn!("failed to persist account pins: {err}");
+            }
+        });
+
+        let pinned = code_core::ProjectIdentity::discover(&self.config.cwd).is_some_and(|project| {
+            code_core::matching_pin(&self.config.account_pins, &project).is_some()
+        });
+        let notice = if pinned {
+            "Project pinned to account"
+        } else {
+            "Project pin removed"
+        };
+        self.bottom_pane.flash_footer_notice(notice.to_string());
+
+        self.refresh_accounts_settings_content();
+        self.request_redraw();
+    }
+
+    pub(crate) fn set_account_budget(
+        &mut self,
+        account_id: String,
+        budget: code_core::config_types::AccountBudget,
+    ) {
+        if budget == code_core::config_types::AccountBudget::default() {
+            self.config.account_budgets.remove(&account_id);
+        } else {
+            self.config.account_budgets.insert(account_id.clone(), budget);
+        }
+
+        let code_home = self.config.code_home.clone();
+        tokio::spawn(async move {
+            if let Err(err) =
+                code_core::config_edit::persist_account_budget(&code_home, &account_id, &budget)
+                    .await
+            {
+                tracing::warn!("failed to persist account budget: {err}");
+            }
+        });
+
+        let format_cap = |percent: Option<f64>| {
+            percent
+                .map(|percent| format!("{percent:.0}%"))
+                .unwrap_or_else(|| "off".to_string())
+        };
+        let notice = format!(
+            "Account budget: 5h {}, weekly {}",
+            format_cap(budget.primary_percent),
+            format_cap(budget.secondary_percent)
+        );
+        self.bottom_pane.flash_footer_notice(notice);
+
+        self.refresh_accounts_settings_content();
+        self.request_redraw();
+    }
+
     /// Forward file-search results to the bottom pane.
     pub(crate) fn apply_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
         self.bottom_pane.on_file_search_result(query, matches);
@@ -32708,6 +33395,28 @@ use code_core::protocol::OrderMeta;
         assert!(metadata.is_none(), "workspace review should not attach metadata");
     }
 
+    #[test]
+    fn review_dialog_staged_option_dispatches_structured_target() {
+        let mut harness = ChatWidgetHarness::new();
+        let chat = harness.chat();
+
+        chat.open_review_dialog();
+        chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        let target = harness
+            .drain_events()
+            .into_iter()
+            .find_map(|event| match event {
+                AppEvent::RunReviewTarget { target, .. } => Some(target),
+                _ => None,
+            })
+            .expect("staged preset should dispatch a structured review");
+
+        assert_eq!(target, code_protocol::protocol::ReviewTarget::StagedChanges);
+    }
+
     #[test]
     fn esc_router_prioritizes_auto_stop_when_waiting_for_review() {
         let mut harness = ChatWidgetHarness::new();
@@ -35983,6 +36692,27 @@ impl ChatWidget<'_> {
             })],
         });
 
+        items.push(SelectionItem {
+            name: "Review staged changes".to_string(),
+            description: Some("Only what is staged in the index".to_string()),
+            is_current: false,
+            actions: vec![Box::new(move |tx: &crate::app_event_sender::AppEventSender| {
+                tx.send(crate::app_event::AppEvent::RunReviewTarget {
+                    target: code_protocol::protocol::ReviewTarget::StagedChanges,
+                    auto_resolve: workspace_auto_resolve,
+                });
+            })],
+        });
+
+        items.push(SelectionItem {
+            name: "Review specific paths".to_string(),
+            description: Some("Limit uncommitted changes to paths or globs".to_string()),
+            is_current: false,
+            actions: vec![Box::new(|tx: &crate::app_event_sender::AppEventSender| {
+                tx.send(crate::app_event::AppEvent::OpenReviewPathsPrompt);
+            })],
+        });
+
         items.push(SelectionItem {
             name: "Review /branch changes".to_string(),
             description: Some("Compare your worktree branch against its merge target".to_string()),
@@ -36001,6 +36731,15 @@ impl ChatWidget<'_> {
             })],
         });
 
+        items.push(SelectionItem {
+            name: "Review a commit range".to_string(),
+            description: Some("A..B or the last N commits".to_string()),
+            is_current: false,
+            actions: vec![Box::new(|tx: &crate::app_event_sender::AppEventSender| {
+                tx.send(crate::app_event::AppEvent::OpenReviewRangePrompt);
+            })],
+        });
+
         items.push(SelectionItem {
             name: "Review against a base branch".to_string(),
             description: Some("Diff current branch against another".to_string()),
@@ -36025,7 +36764,7 @@ impl ChatWidget<'_> {
             Some("Enter select · Esc cancel".to_string()),
             items,
             self.app_event_tx.clone(),
-            6,
+            9,
         );
 
         self.bottom_pane.show_list_selection(
@@ -36052,6 +36791,65 @@ impl ChatWidget<'_> {
         self.bottom_pane.show_custom_prompt(view);
     }
 
+    pub(crate) fn show_review_range_prompt(&mut self) {
+        let submit_tx = self.app_event_tx.clone();
+        let auto_resolve = self.config.tui.review_auto_resolve;
+        let on_submit: Box<dyn Fn(String) + Send + Sync> = Box::new(move |text: String| {
+            match code_core::review_prompts::parse_commit_range(&text) {
+                Ok(target) => submit_tx.send(crate::app_event::AppEvent::RunReviewTarget {
+                    target,
+                    auto_resolve,
+                }),
+                Err(err) => submit_tx.send_background_event_with_placement_and_order(
+                    format!("`/review` — {err}"),
+                    crate::app_event::BackgroundPlacement::Tail,
+                    None,
+                ),
+            }
+        });
+        let view = CustomPromptView::new(
+            "Review a commit range".to_string(),
+            "A..B, A.. (through HEAD), or N for the last N commits".to_string(),
+            Some("Press Enter to submit · Esc cancel".to_string()),
+            self.app_event_tx.clone(),
+            None,
+            on_submit,
+        );
+        self.bottom_pane.show_custom_prompt(view);
+    }
+
+    pub(crate) fn show_review_paths_prompt(&mut self) {
+        let submit_tx = self.app_event_tx.clone();
+        let auto_resolve = self.config.tui.review_auto_resolve;
+        let on_submit: Box<dyn Fn(String) + Send + Sync> = Box::new(move |text: String| {
+            let paths = code_core::review_prompts::parse_review_paths(&text);
+            if paths.is_empty() {
+                submit_tx.send_background_event_with_placement_and_order(
+                    "`/review` — enter at least one path or glob.",
+                    crate::app_event::BackgroundPlacement::Tail,
+                    None,
+                );
+                return;
+            }
+            submit_tx.send(crate::app_event::AppEvent::RunReviewTarget {
+                target: code_core::review_prompts::restrict_to_paths(
+                    code_protocol::protocol::ReviewTarget::UncommittedChanges,
+                    paths,
+                ),
+                auto_resolve,
+            });
+        });
+        let view = CustomPromptView::new(
+            "Review specific paths".to_string(),
+            "Paths or globs, comma separated (e.g. core/src, docs/*.md)".to_string(),
+            Some("Press Enter to submit · Esc cancel".to_string()),
+            self.app_event_tx.clone(),
+            None,
+            on_submit,
+        );
+        self.bottom_pane.show_custom_prompt(view);
+    }
+
     pub(crate) fn set_review_auto_resolve_enabled(&mut self, enabled: bool) {
         if self.config.tui.review_auto_resolve == enabled {
             return;
@@ -37657,11 +38455,57 @@ impl ChatWidget<'_> {
         self.begin_review(prompt, hint, preparation_label);
     }
 
+    /// Start a structured review; the prompt and hint come from the shared
+    /// review target wording so exec and the app server match the TUI.
+    pub(crate) fn start_review_with_target(
+        &mut self,
+        target: code_protocol::protocol::ReviewTarget,
+        auto_resolve: bool,
+    ) {
+        if self.is_task_running() {
+            self.history_push_plain_state(crate::history_cell::new_error_event(
+                "`/review` — complete or cancel the current task before starting a new review.".to_string(),
+            ));
+            self.request_redraw();
+            return;
+        }
+
+        let ReviewRequest {
+            target,
+            user_facing_hint,
+            prompt,
+        } = code_core::review_prompts::review_request_for_target(target);
+        let hint = user_facing_hint.unwrap_or_default();
+        if auto_resolve {
+            let max_re_reviews = self.configured_auto_resolve_re_reviews();
+            self.auto_resolve_state = Some(AutoResolveState::new_with_limit(
+                prompt.clone(),
+                hint.clone(),
+                None,
+                max_re_reviews,
+            ));
+        } else {
+            self.auto_resolve_state = None;
+        }
+
+        self.begin_review_with_target(prompt, hint, None, Some(target));
+    }
+
     fn begin_review(
         &mut self,
         prompt: String,
         hint: String,
         preparation_label: Option<String>,
+    ) {
+        self.begin_review_with_target(prompt, hint, preparation_label, None);
+    }
+
+    fn begin_review_with_target(
+        &mut self,
+        prompt: String,
+        hint: String,
+        preparation_label: Option<String>,
+        target: Option<code_protocol::protocol::ReviewTarget>,
     ) {
         self.active_review_hint = None;
         self.active_review_prompt = None;
@@ -37683,9 +38527,9 @@ impl ChatWidget<'_> {
         } else {
             Some(hint)
         };
-        let target = code_protocol::protocol::ReviewTarget::Custom {
+        let target = target.unwrap_or_else(|| code_protocol::protocol::ReviewTarget::Custom {
             instructions: prompt.clone(),
-        };
+        });
         let review_request = ReviewRequest {
             target,
             prompt,
@@ -38491,6 +39335,7 @@ impl ChatWidget<'_> {
         }
         let args_trim = args.trim().to_string();
         let cwd = self.config.cwd.clone();
+        let owner_session_id = self.session_id.map(|id| id.to_string());
         let tx = self.app_event_tx.clone();
         let branch_tail_ticket = self.make_background_tail_ticket();
         // Add a quick notice into history, include task preview if provided
@@ -38578,16 +39423,7 @@ impl ChatWidget<'_> {
             .await
             {
                 Ok(meta_option) => {
-                    if let Some(meta) = meta_option.clone() {
-                        if let Err(e) = code_core::git_worktree::write_branch_metadata(&worktree, &meta).await
-                        {
-                            tx.send_background_event_with_ticket(
-                                &ticket,
-                                format!("`/branch` — failed to record branch metadata: {}", e),
-                            );
-                        }
-                        branch_metadata = meta_option;
-                    }
+                    branch_metadata = meta_option;
                 }
                 Err(err) => {
                     tx.send_background_event_with_ticket(
@@ -38599,6 +39435,23 @@ impl ChatWidget<'_> {
                     );
                 }
             }
+            // Always record the owning session so `/worktrees` can trace the worktree back.
+            let mut meta = branch_metadata.clone().unwrap_or_else(|| {
+                code_core::git_worktree::BranchMetadata {
+                    base_branch: current_base_branch.clone(),
+                    ..Default::default()
+                }
+            });
+            meta.owner = Some(code_core::git_worktree::WorktreeOwner::Session {
+                session_id: owner_session_id,
+                pid: std::process::id(),
+            });
+            if let Err(e) = code_core::git_worktree::write_branch_metadata(&worktree, &meta).await {
+                tx.send_background_event_with_ticket(
+                    &ticket,
+                    format!("`/branch` — failed to record branch metadata: {}", e),
+                );
+            }
 
             // Attempt to set upstream for the new branch to match the source branch's upstream,
             // falling back to origin/<default> when available. Also ensure origin/HEAD is set.
@@ -39061,6 +39914,15 @@ impl ChatWidget<'_> {
             self.request_redraw();
             return;
         }
+        if self
+            .merge_resolution
+            .as_ref()
+            .is_some_and(|res| res.resolving && res.context.worktree_path == self.config.cwd)
+        {
+            // Conflict resolution is already underway; reopen its panel.
+            self.spawn_merge_conflicts_refresh(None);
+            return;
+        }
 
         let merge_ticket = self.make_background_tail_ticket();
         let tx = self.app_event_tx.clone();
@@ -39175,10 +40037,303 @@ impl ChatWidget<'_> {
                 }
             }
 
+            if !state.fast_forward_possible
+                && state.default_branch_exists
+                && !state.repo_has_in_progress_op
+                && let Some(default_branch) = state.default_branch.clone()
+            {
+                use code_core::merge_conflicts::TrialMergeOutcome;
+                match code_core::merge_conflicts::trial_merge(
+                    &state.git_root,
+                    &default_branch,
+                    &state.worktree_branch,
+                )
+                .await
+                {
+                    Ok(TrialMergeOutcome::Conflicts(files)) => {
+                        send_background(
+                            &tx,
+                            &ticket,
+                            format!(
+                                "`/merge` — trial merge of '{}' into '{}' conflicts in {} file(s)",
+                                state.worktree_branch,
+                                default_branch,
+                                files.len()
+                            ),
+                        );
+                        let context = MergeConflictContext {
+                            worktree_path: state.worktree_path.clone(),
+                            worktree_branch: state.worktree_branch.clone(),
+                            default_branch,
+                        };
+                        tx.send(AppEvent::MergeConflictsDetected { context, files });
+                        return;
+                    }
+                    Ok(TrialMergeOutcome::Clean) => {
+                        send_background(
+                            &tx,
+                            &ticket,
+                            format!(
+                                "`/merge` — trial merge of '{}' into '{}' is clean",
+                                state.worktree_branch, default_branch
+                            ),
+                        );
+                    }
+                    Err(err) => {
+                        send_background(&tx, &ticket, format!("`/merge` — trial merge skipped: {err}"));
+                    }
+                }
+            }
+
             handoff_to_agent(&tx, &ticket, state, blockers);
         });
     }
 
+    pub(crate) fn present_merge_conflicts(
+        &mut self,
+        context: MergeConflictContext,
+        files: Vec<code_core::merge_conflicts::ConflictedFile>,
+    ) {
+        self.merge_resolution = Some(MergeResolution {
+            context,
+            unmerged: files.clone(),
+            files,
+            resolving: false,
+            awaiting_agent: false,
+        });
+        self.show_merge_conflicts_view(None);
+    }
+
+    fn show_merge_conflicts_view(&mut self, notice: Option<String>) {
+        let Some(resolution) = self.merge_resolution.as_ref() else {
+            return;
+        };
+        let view = MergeConflictsView::new(
+            resolution.context.clone(),
+            resolution.rows(),
+            resolution.resolving,
+            notice,
+            self.app_event_tx.clone(),
+        );
+        self.bottom_pane.show_merge_conflicts_view(view);
+    }
+
+    /// Re-read unmerged files from the worktree and reopen the panel.
+    fn spawn_merge_conflicts_refresh(&mut self, notice: Option<String>) {
+        let Some(resolution) = self.merge_resolution.as_ref() else {
+            return;
+        };
+        let worktree = resolution.context.worktree_path.clone();
+        let tx = self.app_event_tx.clone();
+        let ticket = self.make_background_tail_ticket();
+        tokio::spawn(async move {
+            match code_core::merge_conflicts::conflicted_files(&worktree).await {
+                Ok(unmerged) => tx.send(AppEvent::MergeConflictsUpdated {
+                    unmerged,
+                    started: false,
+                    notice,
+                }),
+                Err(err) => tx.send_background_event_with_ticket(&ticket, format!("`/merge` — {err}")),
+            }
+        });
+    }
+
+    pub(crate) fn handle_merge_conflicts_updated(
+        &mut self,
+        unmerged: Vec<code_core::merge_conflicts::ConflictedFile>,
+        started: bool,
+        notice: Option<String>,
+    ) {
+        let Some(resolution) = self.merge_resolution.as_mut() else {
+            return;
+        };
+        if started {
+            resolution.files = unmerged.clone();
+            resolution.resolving = true;
+        }
+        resolution.unmerged = unmerged;
+        if started && !resolution.remaining_conflicts().is_empty() {
+            self.submit_merge_resolution_turn();
+            return;
+        }
+        self.show_merge_conflicts_view(notice);
+    }
+
+    fn submit_merge_resolution_turn(&mut self) {
+        let Some(resolution) = self.merge_resolution.as_mut() else {
+            return;
+        };
+        let files = resolution.remaining_conflicts();
+        if files.is_empty() {
+            return;
+        }
+        resolution.awaiting_agent = true;
+        let context = &resolution.context;
+        let visible = format!(
+            "Resolve merge conflicts with '{}' in {} file(s)",
+            context.default_branch,
+            files.len()
+        );
+        let preface = code_core::merge_conflicts::resolution_preface(
+            &context.worktree_path,
+            &context.default_branch,
+            &context.worktree_branch,
+            &files,
+        );
+        self.submit_text_message_with_preface(visible, preface);
+    }
+
+    fn refresh_merge_resolution_after_turn(&mut self) {
+        let Some(resolution) = self.merge_resolution.as_mut() else {
+            return;
+        };
+        if !resolution.awaiting_agent {
+            return;
+        }
+        resolution.awaiting_agent = false;
+        self.spawn_merge_conflicts_refresh(Some(
+            "Review each resolved file: a approves and stages it, r restores its conflict".to_string(),
+        ));
+    }
+
+    pub(crate) fn handle_merge_conflict_action(&mut self, action: MergeConflictAction) {
+        let Some(resolution) = self.merge_resolution.as_ref() else {
+            return;
+        };
+        let context = resolution.context.clone();
+        let resolving = resolution.resolving;
+        let worktree = context.worktree_path.clone();
+        let tx = self.app_event_tx.clone();
+        let ticket = self.make_background_tail_ticket();
+        match action {
+            MergeConflictAction::Resolve if !resolving => {
+                self.push_background_before_next_output(format!(
+                    "`/merge` — merging '{}' into '{}' in the worktree to resolve conflicts",
+                    context.default_branch, context.worktree_branch
+                ));
+                tokio::spawn(async move {
+                    match code_core::merge_conflicts::start_resolution(
+                        &worktree,
+                        &context.default_branch,
+                    )
+                    .await
+                    {
+                        Ok(unmerged) => tx.send(AppEvent::MergeConflictsUpdated {
+                            unmerged,
+                            started: true,
+                            notice: None,
+                        }),
+                        Err(err) => tx.send_background_event_with_ticket(&ticket, format!("`/merge` — {err}")),
+                    }
+                });
+            }
+            MergeConflictAction::Resolve => self.submit_merge_resolution_turn(),
+            MergeConflictAction::Approve(path) => {
+                tokio::spawn(async move {
+                    let notice = match code_core::merge_conflicts::approve_file(&worktree, &path)
+                        .await
+                    {
+                        Ok(()) => format!("Approved {path}"),
+                        Err(err) => err,
+                    };
+                    match code_core::merge_conflicts::conflicted_files(&worktree).await {
+                        Ok(unmerged) => tx.send(AppEvent::MergeConflictsUpdated {
+                            unmerged,
+                            started: false,
+                            notice: Some(notice),
+                        }),
+                        Err(err) => tx.send_background_event_with_ticket(&ticket, format!("`/merge` — {err}")),
+                    }
+                });
+            }
+            MergeConflictAction::Reject(path) => {
+                tokio::spawn(async move {
+                    let notice = match code_core::merge_conflicts::reject_file(&worktree, &path)
+                        .await
+                    {
+                        Ok(()) => format!("Restored the conflict in {path}"),
+                        Err(err) => err,
+                    };
+                    match code_core::merge_conflicts::conflicted_files(&worktree).await {
+                        Ok(unmerged) => tx.send(AppEvent::MergeConflictsUpdated {
+                            unmerged,
+                            started: false,
+                            notice: Some(notice),
+                        }),
+                        Err(err) => tx.send_background_event_with_ticket(&ticket, format!("`/merge` — {err}")),
+                    }
+                });
+            }
+            MergeConflictAction::ShowDiff(path) => {
+                let base = if resolving { "HEAD" } else { "" };
+                tokio::spawn(async move {
+                    let mut args = vec!["diff"];
+                    if !base.is_empty() {
+                        args.push(base);
+                    }
+                    args.extend(["--", path.as_str()]);
+                    let text = match tokio::process::Command::new("git")
+                        .current_dir(&worktree)
+                        .args(&args)
+                        .output()
+                        .await
+                    {
+                        Ok(out) if out.status.success() => {
+                            let diff = String::from_utf8_lossy(&out.stdout).into_owned();
+                            if diff.trim().is_empty() {
+                                format!("`/merge` — no changes in {path}")
+                            } else {
+                                diff
+                            }
+                        }
+                        Ok(out) => format!(
+                            "Failed to compute diff: {}",
+                            describe_command_failure(&out, "git diff failed")
+                        ),
+                        Err(err) => format!("Failed to compute diff: {err}"),
+                    };
+                    tx.send(AppEvent::DiffResult(text));
+                });
+            }
+            MergeConflictAction::Finish => {
+                self.merge_resolution = None;
+                tokio::spawn(async move {
+                    match code_core::merge_conflicts::commit_resolution(&worktree).await {
+                        Ok(()) => {
+                            let visible = format!(
+                                "Validate the merge of '{}' into '{}'",
+                                context.default_branch, context.worktree_branch
+                            );
+                            let preface = code_core::merge_conflicts::validation_preface(
+                                &worktree,
+                                &context.default_branch,
+                                &context.worktree_branch,
+                            );
+                            tx.send(AppEvent::SubmitTextWithPreface { visible, preface });
+                        }
+                        Err(err) => tx.send_background_event_with_ticket(&ticket, format!(
+                            "`/merge` — failed to commit the merge: {err}"
+                        )),
+                    }
+                });
+            }
+            MergeConflictAction::Abort => {
+                self.merge_resolution = None;
+                tokio::spawn(async move {
+                    let message =
+                        match code_core::merge_conflicts::abort_resolution(&worktree).await {
+                            Ok(()) => format!(
+                                "`/merge` — aborted the merge; '{}' is back to its last commit",
+                                context.worktree_branch
+                            ),
+                            Err(err) => format!("`/merge` — failed to abort the merge: {err}"),
+                        };
+                    tx.send_background_event_with_ticket(&ticket, message);
+                });
+            }
+        }
+    }
+
 }
 
 impl ChatWidget<'_> {
@@ -41100,6 +42255,12 @@ impl WidgetRef for &ChatWidget<'_> {
                     .set(max_scroll.saturating_sub(prev_scroll_from_top));
             }
 
+            if let Some(offset) =
+                history_search::take_jump_scroll_offset(self, max_scroll, content_area.height)
+            {
+                self.layout.scroll_offset.set(offset);
+            }
+
             // Update cache and clamp for display only.
             self.layout.last_max_scroll.set(max_scroll);
             let clamped_scroll_offset = self.layout.scroll_offset.get().min(max_scroll);
@@ -41973,6 +43134,8 @@ impl WidgetRef for &ChatWidget<'_> {
             }
         }
 
+        history_search::render_history_search(self, content_area, start_y, scroll_pos, buf);
+
         // Render vertical scrollbar when content is scrollable and currently visible
         // Auto-hide after a short delay to avoid copying it along with text.
         let now = std::time::Instant::now();
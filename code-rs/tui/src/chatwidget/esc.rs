@@ -7,7 +7,9 @@ use super::{ChatWidget, AUTO_ESC_EXIT_HINT, AUTO_ESC_EXIT_HINT_DOUBLE, DOUBLE_ES
 pub(crate) enum EscIntent {
     DismissModal,
     CloseSettings,
+    CloseHistorySearch,
     CloseFilePopup,
+    VimNormalMode,
     AutoPauseForEdit,
     AutoStopDuringApproval,
     AutoStopActive,
@@ -115,10 +117,18 @@ impl ChatWidget<'_> {
             return EscRoute::new(EscIntent::AgentsTerminal, true, false);
         }
 
+        if self.history_search.is_some() {
+            return EscRoute::new(EscIntent::CloseHistorySearch, true, false);
+        }
+
         if self.bottom_pane.file_popup_visible() {
             return EscRoute::new(EscIntent::CloseFilePopup, false, false);
         }
 
+        if self.bottom_pane.composer_vim_wants_escape() {
+            return EscRoute::new(EscIntent::VimNormalMode, true, false);
+        }
+
         if self.auto_state.is_active() {
             let prompt_visible = self.auto_state.awaiting_coordinator_submit()
                 && !self.auto_state.is_paused_manual()
@@ -200,7 +210,15 @@ impl ChatWidget<'_> {
                 self.handle_key_event(key_event);
                 true
             }
+            EscIntent::CloseHistorySearch => {
+                super::history_search::close_history_search(self);
+                true
+            }
             EscIntent::CloseFilePopup => self.close_file_popup_if_active(),
+            EscIntent::VimNormalMode => {
+                self.bottom_pane.composer_vim_escape();
+                true
+            }
             EscIntent::AutoPauseForEdit => {
                 self.auto_pause_for_manual_edit(false);
                 true
@@ -0,0 +1,490 @@
+//! Incremental search over the rendered transcript (Ctrl+P by default).
+//!
+//! Matches are collected from every history cell's text, including the full
+//! body of collapsed reasoning cells, the whole output of exec cells and every
+//! entry of explore cells. Jumping to a hit expands the cell that contains it
+//! and scrolls it into view on the next frame, once prefix sums reflect the
+//! new height. Highlighting is applied to the rendered buffer so
+//! custom-rendered cells get it for free.
+
+use std::cell::Cell;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Widget};
+
+use super::ChatWidget;
+use crate::history_cell::{
+    CollapsibleReasoningCell, ExecCell, ExploreAggregationCell, HistoryCell, MergedExecCell,
+};
+use code_core::config_types::KeyAction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SearchHit {
+    pub(super) cell_idx: usize,
+    pub(super) line_idx: usize,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct HistorySearch {
+    query: String,
+    hits: Vec<SearchHit>,
+    current: Option<usize>,
+    /// History length the hits were computed against.
+    indexed_len: usize,
+    /// Hit to scroll into view during the next render.
+    pending_jump: Cell<Option<SearchHit>>,
+}
+
+impl HistorySearch {
+    fn current_hit(&self) -> Option<SearchHit> {
+        self.current.and_then(|idx| self.hits.get(idx).copied())
+    }
+}
+
+/// Returns true when the key was consumed by transcript search.
+pub(super) fn handle_history_search_key(chat: &mut ChatWidget<'_>, key_event: KeyEvent) -> bool {
+    if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+        return chat.history_search.is_some();
+    }
+    let is_toggle = crate::keymap::matches_action(
+        &chat.config.tui_keybindings,
+        KeyAction::HistorySearch,
+        &key_event,
+    );
+    if chat.history_search.is_none() {
+        if !is_toggle || chat.has_active_modal_view() {
+            return false;
+        }
+        chat.history_search = Some(HistorySearch::default());
+        chat.request_redraw();
+        return true;
+    }
+    if is_toggle {
+        close_history_search(chat);
+        return true;
+    }
+
+    let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+    match key_event.code {
+        KeyCode::Enter if shift => step(chat, false),
+        KeyCode::Enter | KeyCode::Up => step(chat, true),
+        KeyCode::Down => step(chat, false),
+        KeyCode::Backspace => {
+            if let Some(search) = chat.history_search.as_mut() {
+                search.query.pop();
+            }
+            refresh_hits(chat);
+        }
+        KeyCode::Char('u') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+            if let Some(search) = chat.history_search.as_mut() {
+                search.query.clear();
+            }
+            refresh_hits(chat);
+        }
+        KeyCode::Char(c)
+            if !key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            if let Some(search) = chat.history_search.as_mut() {
+                search.query.push(c);
+            }
+            refresh_hits(chat);
+        }
+        // Let scrolling keys through so the user can browse around hits.
+        KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => return false,
+        _ => {}
+    }
+    true
+}
+
+pub(super) fn close_history_search(chat: &mut ChatWidget<'_>) {
+    if chat.history_search.take().is_some() {
+        chat.request_redraw();
+    }
+}
+
+/// Recompute hits for the current query and jump to the newest one.
+fn refresh_hits(chat: &mut ChatWidget<'_>) {
+    let Some(query) = chat.history_search.as_ref().map(|search| search.query.clone()) else {
+        return;
+    };
+    let cells: Vec<Vec<String>> = chat
+        .history_cells
+        .iter()
+        .map(|cell| searchable_lines(cell.as_ref()))
+        .collect();
+    let hits = find_hits(&cells, &query);
+    if let Some(search) = chat.history_search.as_mut() {
+        search.current = hits.len().checked_sub(1);
+        search.hits = hits;
+        search.indexed_len = cells.len();
+    }
+    focus_current(chat);
+}
+
+/// Move to the previous (older) hit when `older`, otherwise the next one.
+fn step(chat: &mut ChatWidget<'_>, older: bool) {
+    let stale = chat
+        .history_search
+        .as_ref()
+        .is_some_and(|search| search.indexed_len != chat.history_cells.len());
+    if stale {
+        // New output arrived since the last keystroke; keep our place by
+        // re-anchoring to the same hit when it still exists.
+        let previous = chat.history_search.as_ref().and_then(HistorySearch::current_hit);
+        refresh_hits(chat);
+        if let (Some(previous), Some(search)) = (previous, chat.history_search.as_mut()) {
+            if let Some(idx) = search.hits.iter().position(|hit| *hit == previous) {
+                search.current = Some(idx);
+            }
+        }
+    }
+    let Some(search) = chat.history_search.as_mut() else {
+        return;
+    };
+    if search.hits.is_empty() {
+        return;
+    }
+    let last = search.hits.len() - 1;
+    search.current = Some(match (search.current, older) {
+        (None, _) => last,
+        (Some(0), true) => last,
+        (Some(idx), true) => idx - 1,
+        (Some(idx), false) if idx >= last => 0,
+        (Some(idx), false) => idx + 1,
+    });
+    focus_current(chat);
+}
+
+/// Expand the cell holding the current hit and queue a scroll to it.
+fn focus_current(chat: &mut ChatWidget<'_>) {
+    let Some(hit) = chat.history_search.as_ref().and_then(HistorySearch::current_hit) else {
+        chat.request_redraw();
+        return;
+    };
+    let expanded = chat
+        .history_cells
+        .get(hit.cell_idx)
+        .is_some_and(|cell| expand_cell(cell.as_ref()));
+    if expanded {
+        chat.invalidate_height_cache();
+    }
+    if let Some(search) = chat.history_search.as_ref() {
+        search.pending_jump.set(Some(hit));
+    }
+    chat.bottom_pane.set_compact_compose(true);
+    chat.request_redraw();
+}
+
+/// Bottom-anchored scroll offset that brings the pending hit into view, if
+/// a jump was requested. Called from render once prefix sums are current.
+pub(super) fn take_jump_scroll_offset(
+    chat: &ChatWidget<'_>,
+    max_scroll: u16,
+    viewport_rows: u16,
+) -> Option<u16> {
+    let hit = chat.history_search.as_ref()?.pending_jump.take()?;
+    let prefix = chat.history_render.prefix_sums.borrow();
+    let top = *prefix.get(hit.cell_idx)?;
+    let bottom = prefix.get(hit.cell_idx + 1).copied().unwrap_or(top);
+    let cell_rows = bottom.saturating_sub(top).max(1);
+    // Line indices are pre-wrap, so this is an approximation that stays
+    // inside the cell; leave some context above the hit.
+    let line_row = u16::try_from(hit.line_idx)
+        .unwrap_or(u16::MAX)
+        .min(cell_rows - 1);
+    let target_from_top = top
+        .saturating_add(line_row)
+        .saturating_sub(viewport_rows / 3)
+        .min(max_scroll);
+    Some(max_scroll.saturating_sub(target_from_top))
+}
+
+/// Highlight visible matches and draw the search bar on the last history row.
+///
+/// `start_y`/`scroll_pos` map screen rows back to history rows so matches in
+/// the cell holding the current hit get the stronger style.
+pub(super) fn render_history_search(
+    chat: &ChatWidget<'_>,
+    area: Rect,
+    start_y: u16,
+    scroll_pos: u16,
+    buf: &mut Buffer,
+) {
+    let Some(search) = chat.history_search.as_ref() else {
+        return;
+    };
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+    let bar_y = area.y + area.height - 1;
+
+    if !search.query.is_empty() {
+        let needle = Needle::new(&search.query);
+        let current_rows = search.current_hit().and_then(|hit| {
+            let prefix = chat.history_render.prefix_sums.borrow();
+            let top = *prefix.get(hit.cell_idx)?;
+            let bottom = prefix.get(hit.cell_idx + 1).copied().unwrap_or(top);
+            Some(top..bottom)
+        });
+        let match_style = Style::default()
+            .bg(crate::colors::selection())
+            .fg(crate::colors::text_bright());
+        let current_style = Style::default()
+            .bg(crate::colors::warning())
+            .fg(crate::colors::background())
+            .add_modifier(Modifier::BOLD);
+        for y in start_y.max(area.y)..bar_y {
+            let history_row = scroll_pos.saturating_add(y - start_y);
+            let style = match &current_rows {
+                Some(rows) if rows.contains(&history_row) => current_style,
+                _ => match_style,
+            };
+            highlight_row(buf, area.x, area.width, y, &needle, style);
+        }
+    }
+
+    let status = if search.query.is_empty() {
+        String::new()
+    } else if search.hits.is_empty() {
+        "no matches".to_string()
+    } else {
+        format!(
+            "{}/{}",
+            search.current.map(|idx| idx + 1).unwrap_or(0),
+            search.hits.len()
+        )
+    };
+    let t_dim = Style::default().fg(crate::colors::text_dim());
+    let line = Line::from(vec![
+        Span::styled("Search: ", Style::default().fg(crate::colors::primary())),
+        Span::styled(search.query.clone(), Style::default().fg(crate::colors::text())),
+        Span::styled("▏", Style::default().fg(crate::colors::primary())),
+        Span::raw("  "),
+        Span::styled(status, t_dim),
+        Span::styled("   Enter/↑ older · Shift+Enter/↓ newer · Esc close", t_dim),
+    ]);
+    let bar = Rect::new(area.x, bar_y, area.width, 1);
+    Paragraph::new(line)
+        .style(Style::default().bg(crate::colors::background()))
+        .render(bar, buf);
+}
+
+/// Expand a collapsed cell so a hit inside it is visible. Returns true when
+/// the cell's height changed.
+fn expand_cell(cell: &dyn HistoryCell) -> bool {
+    let any = cell.as_any();
+    if let Some(reasoning) = any.downcast_ref::<CollapsibleReasoningCell>() {
+        if !reasoning.is_collapsed() {
+            return false;
+        }
+        reasoning.set_collapsed(false);
+        reasoning.set_hide_when_collapsed(false);
+        return true;
+    }
+    if let Some(exec) = any.downcast_ref::<ExecCell>() {
+        let changed = !exec.is_output_expanded();
+        exec.set_output_expanded(true);
+        return changed;
+    }
+    if let Some(merged) = any.downcast_ref::<MergedExecCell>() {
+        let changed = !merged.is_output_expanded();
+        merged.set_output_expanded(true);
+        return changed;
+    }
+    if let Some(explore) = any.downcast_ref::<ExploreAggregationCell>() {
+        let changed = !explore.is_expanded();
+        explore.set_expanded(true);
+        return changed;
+    }
+    false
+}
+
+fn searchable_lines(cell: &dyn HistoryCell) -> Vec<String> {
+    let any = cell.as_any();
+    let lines = if let Some(reasoning) = any.downcast_ref::<CollapsibleReasoningCell>() {
+        reasoning.expanded_lines()
+    } else if let Some(exec) = any.downcast_ref::<ExecCell>() {
+        exec.expanded_lines()
+    } else if let Some(merged) = any.downcast_ref::<MergedExecCell>() {
+        merged.expanded_lines()
+    } else if let Some(explore) = any.downcast_ref::<ExploreAggregationCell>() {
+        explore.expanded_lines()
+    } else {
+        cell.display_lines()
+    };
+    lines
+        .iter()
+        .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+        .collect()
+}
+
+/// Case-insensitive unless the query contains an uppercase letter.
+struct Needle {
+    chars: Vec<char>,
+    case_sensitive: bool,
+}
+
+impl Needle {
+    fn new(query: &str) -> Self {
+        let case_sensitive = query.chars().any(char::is_uppercase);
+        let chars = if case_sensitive {
+            query.chars().collect()
+        } else {
+            query.chars().flat_map(char::to_lowercase).collect()
+        };
+        Self {
+            chars,
+            case_sensitive,
+        }
+    }
+
+    fn normalize(&self, text: &str) -> Vec<char> {
+        if self.case_sensitive {
+            text.chars().collect()
+        } else {
+            text.chars().flat_map(char::to_lowercase).collect()
+        }
+    }
+
+    fn is_in(&self, text: &str) -> bool {
+        if self.chars.is_empty() {
+            return false;
+        }
+        self.normalize(text)
+            .windows(self.chars.len())
+            .any(|window| window == self.chars.as_slice())
+    }
+}
+
+fn find_hits(cells: &[Vec<String>], query: &str) -> Vec<SearchHit> {
+    let needle = Needle::new(query);
+    cells
+        .iter()
+        .enumerate()
+        .flat_map(|(cell_idx, lines)| {
+            lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| needle.is_in(line))
+                .map(move |(line_idx, _)| SearchHit { cell_idx, line_idx })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn highlight_row(buf: &mut Buffer, x: u16, width: u16, y: u16, needle: &Needle, style: Style) {
+    let len = needle.chars.len();
+    if len == 0 {
+        return;
+    }
+    // One normalized char per buffer cell; multi-char graphemes never match.
+    let row: Vec<Option<char>> = (x..x + width)
+        .map(|col| {
+            let normalized = needle.normalize(buf[(col, y)].symbol());
+            match normalized.as_slice() {
+                [c] => Some(*c),
+                _ => None,
+            }
+        })
+        .collect();
+    let mut start = 0;
+    while start + len <= row.len() {
+        let matched = row[start..start + len]
+            .iter()
+            .zip(&needle.chars)
+            .all(|(cell, want)| *cell == Some(*want));
+        if matched {
+            for col in start..start + len {
+                buf[(x + col as u16, y)].set_style(style);
+            }
+            start += len;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn cells(raw: &[&[&str]]) -> Vec<Vec<String>> {
+        raw.iter()
+            .map(|lines| lines.iter().map(|line| line.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn finds_one_hit_per_matching_line() {
+        let cells = cells(&[&["cargo build", "ok"], &["Running cargo test", "cargo cargo"]]);
+        assert_eq!(
+            find_hits(&cells, "cargo"),
+            vec![
+                SearchHit { cell_idx: 0, line_idx: 0 },
+                SearchHit { cell_idx: 1, line_idx: 0 },
+                SearchHit { cell_idx: 1, line_idx: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn uppercase_query_is_case_sensitive() {
+        let cells = cells(&[&["Error: boom"], &["no error here"]]);
+        assert_eq!(find_hits(&cells, "error").len(), 2);
+        assert_eq!(
+            find_hits(&cells, "Error"),
+            vec![SearchHit { cell_idx: 0, line_idx: 0 }]
+        );
+        assert!(find_hits(&cells, "").is_empty());
+    }
+
+    #[test]
+    fn highlights_matches_in_buffer() {
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "foo bar Foo", Style::default());
+        let style = Style::default().add_modifier(Modifier::REVERSED);
+        highlight_row(&mut buf, 0, 12, 0, &Needle::new("foo"), style);
+        let reversed: Vec<u16> = (0..12)
+            .filter(|x| buf[(*x, 0)].modifier.contains(Modifier::REVERSED))
+            .collect();
+        assert_eq!(reversed, vec![0, 1, 2, 8, 9, 10]);
+    }
+
+    #[test]
+    fn exec_output_hidden_by_preview_is_searchable_and_expands() {
+        let stdout: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        let cell = crate::history_cell::new_completed_exec_command(
+            vec!["seq".to_string(), "20".to_string()],
+            Vec::new(),
+            crate::history_cell::CommandOutput {
+                exit_code: 0,
+                stdout,
+                stderr: String::new(),
+            },
+        );
+        let shows_middle = |cell: &ExecCell| {
+            let (_, out, _) = cell.exec_render_parts();
+            out.iter().any(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+                    .contains("line 10")
+            })
+        };
+
+        assert!(!shows_middle(&cell));
+        assert!(searchable_lines(&cell).iter().any(|line| line.contains("line 10")));
+        assert!(expand_cell(&cell));
+        assert!(shows_middle(&cell));
+        assert!(!expand_cell(&cell));
+    }
+}
@@ -25,7 +25,8 @@ pub struct Cli {
     pub model: Option<String>,
 
     /// Convenience flag to select the local open source model provider.
-    /// Equivalent to -c model_provider=oss; verifies a local Ollama server is
+    /// Equivalent to -c model_provider=oss; verifies a local inference server
+    /// (Ollama, LM Studio, llama.cpp, vLLM or any OpenAI-compatible server) is
     /// running.
     #[arg(long = "oss", default_value_t = false)]
     pub oss: bool,
@@ -8,6 +8,8 @@ use code_cloud_tasks_client::ApplyOutcome;
 use code_cloud_tasks_client::CloudBackend;
 use code_cloud_tasks_client::CreatedTask;
 use code_cloud_tasks_client::HttpClient;
+use code_cloud_tasks_client::LocalBackend;
+use code_cloud_tasks_client::LOCAL_ENVIRONMENT_ID;
 use code_cloud_tasks_client::MockClient;
 use code_cloud_tasks_client::TaskId;
 use code_cloud_tasks_client::TaskSummary;
@@ -33,11 +35,12 @@ struct CloudTasksConfig {
     token: Option<String>,
     account_id: Option<String>,
     use_mock: bool,
+    use_local: bool,
 }
 
 pub async fn fetch_tasks(environment: Option<String>) -> Result<Vec<TaskSummary>> {
     let config = load_config().await?;
-    let backend = build_backend(&config)?;
+    let backend = build_backend(&config).await?;
     backend
         .list_tasks(environment.as_deref())
         .await
@@ -46,7 +49,7 @@ pub async fn fetch_tasks(environment: Option<String>) -> Result<Vec<TaskSummary>
 
 pub async fn fetch_task_diff(task_id: TaskId) -> Result<Option<String>> {
     let config = load_config().await?;
-    let backend = build_backend(&config)?;
+    let backend = build_backend(&config).await?;
     backend
         .get_task_diff(task_id.clone())
         .await
@@ -55,7 +58,7 @@ pub async fn fetch_task_diff(task_id: TaskId) -> Result<Option<String>> {
 
 pub async fn fetch_task_messages(task_id: TaskId) -> Result<Vec<String>> {
     let config = load_config().await?;
-    let backend = build_backend(&config)?;
+    let backend = build_backend(&config).await?;
     backend
         .get_task_messages(task_id.clone())
         .await
@@ -64,7 +67,7 @@ pub async fn fetch_task_messages(task_id: TaskId) -> Result<Vec<String>> {
 
 pub async fn apply_task(task_id: TaskId, preflight: bool) -> Result<ApplyOutcome> {
     let config = load_config().await?;
-    let backend = build_backend(&config)?;
+    let backend = build_backend(&config).await?;
     let fut = if preflight {
         backend.apply_task_preflight(task_id.clone(), None)
     } else {
@@ -76,15 +79,19 @@ pub async fn apply_task(task_id: TaskId, preflight: bool) -> Result<ApplyOutcome
 pub async fn create_task(env_id: String, prompt: String, best_of_n: usize) -> Result<CreatedTask> {
     let config = load_config().await?;
     if config.use_mock {
-        let backend = build_backend(&config)?;
+        let backend = build_backend(&config).await?;
         return backend
             .create_task(&env_id, &prompt, "main", false, best_of_n)
             .await
             .map_err(|err| anyhow!("create mock task failed: {err}"));
     }
 
-    let backend = build_backend(&config)?;
-    let git_ref = detect_git_ref().await.unwrap_or_else(|| "main".to_string());
+    let backend = build_backend(&config).await?;
+    // Local attempts branch from the commit currently checked out.
+    let fallback_ref = if config.use_local { "HEAD" } else { "main" };
+    let git_ref = detect_git_ref()
+        .await
+        .unwrap_or_else(|| fallback_ref.to_string());
     backend
         .create_task(&env_id, &prompt, &git_ref, false, best_of_n)
         .await
@@ -101,6 +108,14 @@ pub async fn fetch_environments() -> Result<Vec<CloudEnvironment>> {
             is_pinned: true,
         }]);
     }
+    if config.use_local {
+        return Ok(vec![CloudEnvironment {
+            id: LOCAL_ENVIRONMENT_ID.to_string(),
+            label: Some("Local worktrees".to_string()),
+            repo_hints: None,
+            is_pinned: true,
+        }]);
+    }
 
     let client = reqwest::Client::builder()
         .build()
@@ -182,10 +197,19 @@ async fn detect_git_ref() -> Option<String> {
     code_core::git_info::current_branch_name(&cwd).await
 }
 
-fn build_backend(config: &CloudTasksConfig) -> Result<Arc<dyn CloudBackend>> {
+async fn build_backend(config: &CloudTasksConfig) -> Result<Arc<dyn CloudBackend>> {
     if config.use_mock {
         return Ok(Arc::new(MockClient));
     }
+    if config.use_local {
+        let code_home = code_core::config::find_code_home()
+            .context("determine codex home directory")?;
+        let cwd = std::env::current_dir().context("determine current directory")?;
+        let backend = LocalBackend::discover(&code_home, &cwd)
+            .await
+            .map_err(|err| anyhow!("local cloud tasks need a git repository: {err}"))?;
+        return Ok(Arc::new(backend));
+    }
 
     let ua = code_core::default_client::get_code_user_agent(None);
     let mut client = HttpClient::new(config.base_url.clone()).context("create cloud http client")?;
@@ -203,16 +227,16 @@ async fn load_config() -> Result<CloudTasksConfig> {
     let base_url_env = std::env::var("CODEX_CLOUD_TASKS_BASE_URL")
         .unwrap_or_else(|_| "https://chatgpt.com/backend-api".to_string());
     let base_url = normalize_base_url(&base_url_env);
-    let use_mock = std::env::var("CODEX_CLOUD_TASKS_MODE")
-        .ok()
-        .map(|mode| mode.eq_ignore_ascii_case("mock"))
-        .unwrap_or(false);
-    if use_mock {
+    let mode = std::env::var("CODEX_CLOUD_TASKS_MODE").unwrap_or_default();
+    let use_mock = mode.eq_ignore_ascii_case("mock");
+    let use_local = mode.eq_ignore_ascii_case("local");
+    if use_mock || use_local {
         return Ok(CloudTasksConfig {
             base_url,
             token: None,
             account_id: None,
             use_mock,
+            use_local,
         });
     }
 
@@ -241,6 +265,7 @@ async fn load_config() -> Result<CloudTasksConfig> {
         token: Some(token),
         account_id,
         use_mock: false,
+        use_local: false,
     })
 }
 
@@ -7,6 +7,8 @@ use ratatui::text::Span as RtSpan;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use code_core::patch_review::HunkLineKind;
+use code_core::patch_review::PatchHunk;
 use code_core::protocol::FileChange;
 
 use crate::history_cell::PatchEventType;
@@ -667,6 +669,34 @@ pub(super) fn create_diff_details_only(
     render_patch_details(changes)
 }
 
+/// Render a single reviewable hunk with line numbers, wrapped to `term_cols`.
+/// Used by the per-hunk patch approval view.
+pub(crate) fn render_hunk_lines(hunk: &PatchHunk, term_cols: usize) -> Vec<RtLine<'static>> {
+    let mut old_ln = hunk.old_start + 1;
+    let mut new_ln = hunk.new_start + 1;
+    let mut out: Vec<RtLine<'static>> = Vec::new();
+    for (line_kind, raw) in hunk.lines() {
+        let text = sanitize_diff_text(&raw);
+        let (line_number, kind) = match line_kind {
+            HunkLineKind::Delete => {
+                old_ln += 1;
+                (old_ln - 1, DiffLineType::Delete)
+            }
+            HunkLineKind::Insert => {
+                new_ln += 1;
+                (new_ln - 1, DiffLineType::Insert)
+            }
+            HunkLineKind::Context => {
+                old_ln += 1;
+                new_ln += 1;
+                (new_ln - 1, DiffLineType::Context)
+            }
+        };
+        out.extend(push_wrapped_diff_line_with_width(line_number, kind, &text, term_cols));
+    }
+    out
+}
+
 #[allow(dead_code)]
 fn push_wrapped_diff_line_with_width(
     line_number: usize,
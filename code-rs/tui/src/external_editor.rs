@@ -22,7 +22,9 @@ pub(crate) enum ExternalEditorError {
     ReadFailed(String),
 }
 
-pub(crate) fn run_editor(initial: &str) -> Result<String, ExternalEditorError> {
+/// Open `initial` in the user's editor. The temp file is named with `suffix`
+/// so editors pick the right syntax mode (e.g. `.rs` when editing a patch hunk).
+pub(crate) fn run_editor(initial: &str, suffix: &str) -> Result<String, ExternalEditorError> {
     let command = resolve_editor_command()?;
     let (program, args) = command
         .split_first()
@@ -30,7 +32,7 @@ pub(crate) fn run_editor(initial: &str) -> Result<String, ExternalEditorError> {
 
     let mut temp = Builder::new()
         .prefix("code-edit-")
-        .suffix(".md")
+        .suffix(suffix)
         .tempfile()
         .map_err(|e| ExternalEditorError::ReadFailed(e.to_string()))?;
     if !initial.is_empty() {
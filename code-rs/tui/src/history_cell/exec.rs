@@ -38,6 +38,7 @@ use super::{
     ExecKind,
     HistoryCell,
     HistoryCellType,
+    full_output_lines,
     output_lines,
 };
 
@@ -67,6 +68,8 @@ pub(crate) struct ExecCell {
     parsed_meta: Option<ParsedExecMetadata>,
     has_bold_command: bool,
     wait_state: std::cell::RefCell<ExecWaitState>,
+    /// Show the whole output instead of the head/tail preview.
+    output_expanded: std::cell::Cell<bool>,
 }
 
 const STREAMING_EXIT_CODE: i32 = i32::MIN;
@@ -401,9 +404,28 @@ impl ExecCell {
             parsed_meta,
             has_bold_command,
             wait_state: std::cell::RefCell::new(wait_state),
+            output_expanded: std::cell::Cell::new(false),
         }
     }
 
+    pub(crate) fn is_output_expanded(&self) -> bool {
+        self.output_expanded.get()
+    }
+
+    pub(crate) fn set_output_expanded(&self, expanded: bool) {
+        self.output_expanded.set(expanded);
+    }
+
+    /// Preamble and full output, as shown once the cell is expanded.
+    pub(crate) fn expanded_lines(&self) -> Vec<Line<'static>> {
+        let expanded = self.output_expanded.replace(true);
+        let (mut lines, out, status) = self.exec_render_parts();
+        self.output_expanded.set(expanded);
+        lines.extend(out);
+        lines.extend(status);
+        lines
+    }
+
     pub(crate) fn parsed_action(&self) -> ExecAction {
         self
             .parsed_meta
@@ -616,6 +638,7 @@ impl ExecCell {
                     self.stream_preview.as_ref(),
                     elapsed_since_start,
                     status_label,
+                    self.output_expanded.get(),
                 ),
                 None => exec_render_parts_parsed(
                     &self.parsed,
@@ -623,6 +646,7 @@ impl ExecCell {
                     self.stream_preview.as_ref(),
                     elapsed_since_start,
                     status_label,
+                    self.output_expanded.get(),
                 ),
             }
         };
@@ -712,7 +736,11 @@ impl ExecCell {
             .output
             .as_ref()
             .or(self.stream_preview.as_ref());
-        let mut out = output_lines(display_output, false, false);
+        let mut out = if self.output_expanded.get() {
+            full_output_lines(display_output, false)
+        } else {
+            output_lines(display_output, false, false)
+        };
         let has_output = !trim_empty_lines(out.clone()).is_empty();
 
         if self.output.is_none() && has_output {
@@ -12,7 +12,7 @@ use crate::history::compat::ExecAction;
 
 use super::core::CommandOutput;
 use super::exec::ParsedExecMetadata;
-use super::formatting::output_lines;
+use super::formatting::{full_output_lines, output_lines};
 
 pub(crate) fn action_enum_from_parsed(
     parsed: &[code_core::parse_command::ParsedCommand],
@@ -68,6 +68,7 @@ pub(crate) fn exec_render_parts_parsed_with_meta(
     stream_preview: Option<&CommandOutput>,
     elapsed_since_start: Option<Duration>,
     status_label: &str,
+    full_output: bool,
 ) -> (
     Vec<Line<'static>>,
     Vec<Line<'static>>,
@@ -166,7 +167,11 @@ pub(crate) fn exec_render_parts_parsed_with_meta(
     // Compute output preview first to know whether to draw the downward corner.
     let show_stdout = matches!(action, ExecAction::Run);
     let display_output = output.or(stream_preview);
-    let mut out = output_lines(display_output, !show_stdout, false);
+    let mut out = if full_output {
+        full_output_lines(display_output, !show_stdout)
+    } else {
+        output_lines(display_output, !show_stdout, false)
+    };
     let mut any_content_emitted = false;
     // Determine allowed label(s) for this cell's primary action
     let expected_label: Option<&'static str> = match action {
@@ -493,6 +498,7 @@ pub(crate) fn exec_render_parts_parsed(
     stream_preview: Option<&CommandOutput>,
     elapsed_since_start: Option<Duration>,
     status_label: &str,
+    full_output: bool,
 ) -> (
     Vec<Line<'static>>,
     Vec<Line<'static>>,
@@ -506,6 +512,7 @@ pub(crate) fn exec_render_parts_parsed(
         stream_preview,
         elapsed_since_start,
         status_label,
+        full_output,
     )
 }
 
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 use ratatui::prelude::{Buffer, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span, Text};
@@ -30,13 +32,17 @@ impl MergedExecSegment {
         Self { record }
     }
 
-    fn exec_parts(&self) -> (Vec<Line<'static>>, Vec<Line<'static>>, Option<Line<'static>>) {
+    fn exec_parts(
+        &self,
+        output_expanded: bool,
+    ) -> (Vec<Line<'static>>, Vec<Line<'static>>, Option<Line<'static>>) {
         let exec_cell = ExecCell::from_record(self.record.clone());
+        exec_cell.set_output_expanded(output_expanded);
         exec_cell.exec_render_parts()
     }
 
-    fn lines(&self) -> (Vec<Line<'static>>, Vec<Line<'static>>) {
-        let (pre, mut out, status_line) = self.exec_parts();
+    fn lines(&self, output_expanded: bool) -> (Vec<Line<'static>>, Vec<Line<'static>>) {
+        let (pre, mut out, status_line) = self.exec_parts(output_expanded);
         if let Some(status) = status_line {
             out.push(status);
         }
@@ -48,6 +54,8 @@ pub(crate) struct MergedExecCell {
     segments: Vec<MergedExecSegment>,
     kind: ExecKind,
     history_id: HistoryId,
+    /// Show every segment's whole output instead of the head/tail preview.
+    output_expanded: Cell<bool>,
 }
 
 impl MergedExecCell {
@@ -57,6 +65,22 @@ impl MergedExecCell {
         self.history_id = id;
     }
 
+    pub(crate) fn is_output_expanded(&self) -> bool {
+        self.output_expanded.get()
+    }
+
+    pub(crate) fn set_output_expanded(&self, expanded: bool) {
+        self.output_expanded.set(expanded);
+    }
+
+    /// Every segment with its full output, as shown once the cell is expanded.
+    pub(crate) fn expanded_lines(&self) -> Vec<Line<'static>> {
+        let expanded = self.output_expanded.replace(true);
+        let lines = self.display_lines();
+        self.output_expanded.set(expanded);
+        lines
+    }
+
     pub(crate) fn to_record(&self) -> MergedExecRecord {
         MergedExecRecord {
             id: self.history_id,
@@ -78,6 +102,7 @@ impl MergedExecCell {
             segments: segments.into_iter().map(MergedExecSegment::new).collect(),
             kind: action.into(),
             history_id,
+            output_expanded: Cell::new(false),
         }
     }
 
@@ -93,6 +118,7 @@ impl MergedExecCell {
             segments,
             kind,
             history_id,
+            output_expanded: Cell::new(false),
         }
     }
 
@@ -142,7 +168,7 @@ impl MergedExecCell {
 
         let mut kept: Vec<Line<'static>> = Vec::new();
         for (seg_idx, segment) in self.segments.iter().enumerate() {
-            let (pre_raw, _, _) = segment.exec_parts();
+            let (pre_raw, _, _) = segment.exec_parts(self.output_expanded.get());
             let mut pre = trim_empty_lines(pre_raw);
             if !pre.is_empty() {
                 pre.remove(0);
@@ -213,7 +239,7 @@ impl HistoryCell for MergedExecCell {
                 .unwrap_or(0);
             total = total.saturating_add(pre_rows);
             for segment in &self.segments {
-                let (_, out_raw) = segment.lines();
+                let (_, out_raw) = segment.lines(self.output_expanded.get());
                 let out = trim_empty_lines(out_raw);
                 let out_rows: u16 = Paragraph::new(Text::from(out))
                     .wrap(Wrap { trim: false })
@@ -227,7 +253,7 @@ impl HistoryCell for MergedExecCell {
 
         let mut added_corner = false;
         for segment in &self.segments {
-            let (pre_raw, out_raw) = segment.lines();
+            let (pre_raw, out_raw) = segment.lines(self.output_expanded.get());
             let mut pre = trim_empty_lines(pre_raw);
             if self.kind != ExecKind::Run && !pre.is_empty() {
                 pre.remove(0);
@@ -272,7 +298,7 @@ impl HistoryCell for MergedExecCell {
     fn display_lines(&self) -> Vec<Line<'static>> {
         let mut out: Vec<Line<'static>> = Vec::new();
         for (i, segment) in self.segments.iter().enumerate() {
-            let (pre_raw, out_raw) = segment.lines();
+            let (pre_raw, out_raw) = segment.lines(self.output_expanded.get());
             if i > 0 {
                 out.push(Line::from(""));
             }
@@ -402,7 +428,7 @@ impl HistoryCell for MergedExecCell {
                     if cur_y >= end_y {
                         break;
                     }
-                    let (_, out_raw) = segment.lines();
+                    let (_, out_raw) = segment.lines(self.output_expanded.get());
                     let out = trim_empty_lines(out_raw);
                     let out_text = Text::from(out.clone());
                     let out_total: u16 = Paragraph::new(out_text.clone())
@@ -458,7 +484,7 @@ impl HistoryCell for MergedExecCell {
             if cur_y >= end_y {
                 break;
             }
-            let (pre_raw, out_raw) = segment.lines();
+            let (pre_raw, out_raw) = segment.lines(self.output_expanded.get());
             let mut pre = trim_empty_lines(pre_raw);
             if self.kind != ExecKind::Run && !pre.is_empty() {
                 pre.remove(0);
@@ -14,6 +14,8 @@ use std::path::{Component, Path};
 pub(crate) struct ExploreAggregationCell {
     record: ExploreRecord,
     force_exploring_header: bool,
+    /// List every entry even when explore details are hidden.
+    expanded: std::cell::Cell<bool>,
 }
 
 impl ExploreAggregationCell {
@@ -21,9 +23,23 @@ impl ExploreAggregationCell {
         Self {
             record,
             force_exploring_header: false,
+            expanded: std::cell::Cell::new(false),
         }
     }
 
+    pub(crate) fn is_expanded(&self) -> bool {
+        self.expanded.get()
+    }
+
+    pub(crate) fn set_expanded(&self, expanded: bool) {
+        self.expanded.set(expanded);
+    }
+
+    /// Every entry, as shown once the cell is expanded.
+    pub(crate) fn expanded_lines(&self) -> Vec<Line<'static>> {
+        explore_lines_without_truncation(&self.record, self.force_exploring_header)
+    }
+
     pub(crate) fn record(&self) -> &ExploreRecord {
         &self.record
     }
@@ -78,6 +94,9 @@ impl HistoryCell for ExploreAggregationCell {
     }
 
     fn display_lines(&self) -> Vec<Line<'static>> {
+        if self.expanded.get() {
+            return self.expanded_lines();
+        }
         explore_lines_from_record_with_force(&self.record, self.force_exploring_header)
     }
 
@@ -244,6 +244,10 @@ pub(crate) fn normalize_overwrite_sequences(input: &str) -> String {
 }
 
 pub(crate) fn build_preview_lines(text: &str, _include_left_pipe: bool) -> Vec<Line<'static>> {
+    build_output_text_lines(text, false)
+}
+
+fn build_output_text_lines(text: &str, full: bool) -> Vec<Line<'static>> {
     // Prefer UI‑themed JSON highlighting when the (ANSI‑stripped) text parses as JSON.
     let stripped_plain = sanitize_for_tui(
         text,
@@ -258,6 +262,9 @@ pub(crate) fn build_preview_lines(text: &str, _include_left_pipe: bool) -> Vec<L
         let pretty =
             serde_json::to_string_pretty(&json_val).unwrap_or_else(|_| json_val.to_string());
         let highlighted = crate::syntax_highlight::highlight_code_block(&pretty, Some("json"));
+        if full {
+            return highlighted;
+        }
         return select_preview_from_lines(&highlighted, PREVIEW_HEAD_LINES, PREVIEW_TAIL_LINES);
     }
 
@@ -280,7 +287,7 @@ pub(crate) fn build_preview_lines(text: &str, _include_left_pipe: bool) -> Vec<L
         Line(&'a str),
         Ellipsis,
     }
-    let segments: Vec<Seg> = if non_empty.len() <= PREVIEW_HEAD_LINES + PREVIEW_TAIL_LINES {
+    let segments: Vec<Seg> = if full || non_empty.len() <= PREVIEW_HEAD_LINES + PREVIEW_TAIL_LINES {
         non_empty.iter().map(|s| Seg::Line(s)).collect()
     } else {
         let mut v: Vec<Seg> = Vec::with_capacity(PREVIEW_HEAD_LINES + PREVIEW_TAIL_LINES + 1);
@@ -340,7 +347,21 @@ fn clip_preview_text(text: &str, limit: usize) -> (String, bool) {
 pub(crate) fn output_lines(
     output: Option<&CommandOutput>,
     only_err: bool,
-    include_angle_pipe: bool,
+    _include_angle_pipe: bool,
+) -> Vec<Line<'static>> {
+    output_lines_with_limit(output, only_err, false)
+}
+
+/// Like [`output_lines`], but keeps every stdout line instead of the
+/// head/tail preview.
+pub(crate) fn full_output_lines(output: Option<&CommandOutput>, only_err: bool) -> Vec<Line<'static>> {
+    output_lines_with_limit(output, only_err, true)
+}
+
+fn output_lines_with_limit(
+    output: Option<&CommandOutput>,
+    only_err: bool,
+    full: bool,
 ) -> Vec<Line<'static>> {
     let CommandOutput {
         exit_code,
@@ -355,7 +376,7 @@ pub(crate) fn output_lines(
     let is_streaming_preview = *exit_code == STREAMING_EXIT_CODE;
 
     if !only_err && !stdout.is_empty() {
-        lines.extend(build_preview_lines(stdout, include_angle_pipe));
+        lines.extend(build_output_text_lines(stdout, full));
     }
 
     if !stderr.is_empty() && (is_streaming_preview || *exit_code != 0) {
@@ -92,6 +92,7 @@ pub(crate) use explore::{
 pub(crate) use explore::explore_lines_from_record;
 pub(crate) use formatting::{
     clean_wait_command,
+    full_output_lines,
     normalize_overwrite_sequences,
     output_lines,
     pretty_provider_name,
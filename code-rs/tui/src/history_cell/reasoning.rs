@@ -154,6 +154,13 @@ impl CollapsibleReasoningCell {
         self.collapsed.get()
     }
 
+    /// Full reasoning body, regardless of whether the cell is collapsed.
+    pub(crate) fn expanded_lines(&self) -> Vec<Line<'static>> {
+        let state = self.state.borrow();
+        let theme = crate::theme::current_theme();
+        normalized_lines(&sections_to_ratatui_lines(&state.sections, &theme))
+    }
+
     pub(crate) fn set_hide_when_collapsed(&self, hide: bool) -> bool {
         let mut state = self.state.borrow_mut();
         if state.hide_when_collapsed == hide {
@@ -0,0 +1,216 @@
+//! Applies `[tui.keybindings]` to incoming key events.
+//!
+//! Key handling throughout the TUI matches the default chords directly, so a
+//! rebound chord is rewritten into its action's canonical default chord before
+//! dispatch. A default chord the user moved away from is swallowed unless it
+//! is now bound to another action; everything else passes through as-is.
+
+use code_core::config_types::KeyAction;
+use code_core::keybindings::KeyBindings;
+use code_core::keybindings::KeyChord;
+use code_core::keybindings::KeyName;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+/// Actions handled at the app level regardless of focus.
+pub(crate) const GLOBAL_ACTIONS: &[KeyAction] = &[
+    KeyAction::ToggleReasoning,
+    KeyAction::SwitchModel,
+    KeyAction::ScrollPageUp,
+    KeyAction::ScrollPageDown,
+    KeyAction::Interrupt,
+    KeyAction::OpenEditor,
+];
+
+/// Actions that only apply while the composer has input focus.
+pub(crate) const COMPOSER_ACTIONS: &[KeyAction] = &[
+    KeyAction::Submit,
+    KeyAction::Newline,
+    KeyAction::HistoryPrev,
+    KeyAction::HistoryNext,
+];
+
+/// Rewrite `key_event` into the canonical chord of the action it is bound
+/// to, when that action is in `scope` and has been rebound. Returns `None`
+/// for a default chord of a rebound in-scope action that nothing else claims,
+/// so the hard-coded handler for that chord no longer fires.
+pub(crate) fn translate_key_event(
+    bindings: &KeyBindings,
+    scope: &[KeyAction],
+    key_event: KeyEvent,
+) -> Option<KeyEvent> {
+    let Some(chord) = chord_from_key_event(&key_event) else {
+        return Some(key_event);
+    };
+    let Some(action) = bindings.action_for(&chord) else {
+        let abandoned = scope.iter().any(|action| {
+            bindings.is_rebound(*action)
+                && action.default_chords().iter().any(|raw| {
+                    raw.parse::<KeyChord>()
+                        .is_ok_and(|default| default == chord)
+                })
+        });
+        return (!abandoned).then_some(key_event);
+    };
+    if !scope.contains(&action) || !bindings.is_rebound(action) {
+        return Some(key_event);
+    }
+    let Some(canonical) = action
+        .default_chords()
+        .first()
+        .and_then(|raw| raw.parse::<KeyChord>().ok())
+    else {
+        return Some(key_event);
+    };
+    if canonical == chord {
+        return Some(key_event);
+    }
+    let (code, modifiers) = key_parts(&canonical);
+    Some(KeyEvent {
+        code,
+        modifiers,
+        kind: key_event.kind,
+        state: key_event.state,
+    })
+}
+
+/// Whether `key_event` is one of the chords currently bound to `action`.
+/// Used for actions with no legacy hard-coded chord to translate into.
+pub(crate) fn matches_action(bindings: &KeyBindings, action: KeyAction, key_event: &KeyEvent) -> bool {
+    chord_from_key_event(key_event).is_some_and(|chord| bindings.chords(action).contains(&chord))
+}
+
+fn chord_from_key_event(key_event: &KeyEvent) -> Option<KeyChord> {
+    let supported = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+    if !supported.contains(key_event.modifiers) {
+        return None;
+    }
+    let mut shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+    let key = match key_event.code {
+        KeyCode::Char(c) => {
+            // Terminals report Shift+letter as an uppercase char, with or
+            // without the SHIFT modifier.
+            if c.is_ascii_uppercase() {
+                shift = true;
+            }
+            KeyName::Char(c.to_ascii_lowercase())
+        }
+        KeyCode::Enter => KeyName::Enter,
+        KeyCode::Tab => KeyName::Tab,
+        KeyCode::BackTab => {
+            shift = true;
+            KeyName::Tab
+        }
+        KeyCode::Esc => KeyName::Esc,
+        KeyCode::Backspace => KeyName::Backspace,
+        KeyCode::Delete => KeyName::Delete,
+        KeyCode::Insert => KeyName::Insert,
+        KeyCode::Up => KeyName::Up,
+        KeyCode::Down => KeyName::Down,
+        KeyCode::Left => KeyName::Left,
+        KeyCode::Right => KeyName::Right,
+        KeyCode::Home => KeyName::Home,
+        KeyCode::End => KeyName::End,
+        KeyCode::PageUp => KeyName::PageUp,
+        KeyCode::PageDown => KeyName::PageDown,
+        KeyCode::F(n) => KeyName::F(n),
+        _ => return None,
+    };
+    Some(KeyChord {
+        key,
+        ctrl: key_event.modifiers.contains(KeyModifiers::CONTROL),
+        alt: key_event.modifiers.contains(KeyModifiers::ALT),
+        shift,
+    })
+}
+
+fn key_parts(chord: &KeyChord) -> (KeyCode, KeyModifiers) {
+    let code = match chord.key {
+        KeyName::Char(c) => KeyCode::Char(c),
+        KeyName::Enter => KeyCode::Enter,
+        KeyName::Tab if chord.shift => KeyCode::BackTab,
+        KeyName::Tab => KeyCode::Tab,
+        KeyName::Esc => KeyCode::Esc,
+        KeyName::Backspace => KeyCode::Backspace,
+        KeyName::Delete => KeyCode::Delete,
+        KeyName::Insert => KeyCode::Insert,
+        KeyName::Up => KeyCode::Up,
+        KeyName::Down => KeyCode::Down,
+        KeyName::Left => KeyCode::Left,
+        KeyName::Right => KeyCode::Right,
+        KeyName::Home => KeyCode::Home,
+        KeyName::End => KeyCode::End,
+        KeyName::PageUp => KeyCode::PageUp,
+        KeyName::PageDown => KeyCode::PageDown,
+        KeyName::F(n) => KeyCode::F(n),
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    if chord.ctrl {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    if chord.alt {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if chord.shift {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    (code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::config_types::KeyBindingSpec;
+    use std::collections::BTreeMap;
+
+    fn bindings(action: KeyAction, chord: &str) -> KeyBindings {
+        KeyBindings::resolve(&BTreeMap::from([(action, KeyBindingSpec::One(chord.to_string()))]))
+            .expect("valid bindings")
+    }
+
+    #[test]
+    fn rebound_chord_becomes_canonical_default() {
+        let bindings = bindings(KeyAction::OpenEditor, "alt+e");
+        let translated = translate_key_event(
+            &bindings,
+            GLOBAL_ACTIONS,
+            KeyEvent::new(KeyCode::Char('e'), KeyModifiers::ALT),
+        )
+        .expect("translated");
+        assert_eq!(translated.code, KeyCode::Char('g'));
+        assert_eq!(translated.modifiers, KeyModifiers::CONTROL);
+    }
+
+    #[test]
+    fn actions_outside_scope_pass_through() {
+        let bindings = bindings(KeyAction::Submit, "ctrl+enter");
+        let event = KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL);
+        assert_eq!(translate_key_event(&bindings, GLOBAL_ACTIONS, event), Some(event));
+        let translated =
+            translate_key_event(&bindings, COMPOSER_ACTIONS, event).expect("translated");
+        assert_eq!(translated.code, KeyCode::Enter);
+        assert_eq!(translated.modifiers, KeyModifiers::NONE);
+    }
+
+    #[test]
+    fn abandoned_default_chord_is_swallowed() {
+        let bindings = bindings(KeyAction::OpenEditor, "alt+e");
+        let old_default = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL);
+        assert_eq!(translate_key_event(&bindings, GLOBAL_ACTIONS, old_default), None);
+        // Out of scope, the chord is left to whoever handles it there.
+        assert_eq!(
+            translate_key_event(&bindings, COMPOSER_ACTIONS, old_default),
+            Some(old_default)
+        );
+
+        let moved = KeyBindings::resolve(&BTreeMap::from([
+            (KeyAction::OpenEditor, KeyBindingSpec::One("alt+e".to_string())),
+            (KeyAction::SwitchModel, KeyBindingSpec::One("ctrl+g".to_string())),
+        ]))
+        .expect("valid bindings");
+        let translated =
+            translate_key_event(&moved, GLOBAL_ACTIONS, old_default).expect("translated");
+        assert_eq!(translated.code, KeyCode::Char('y'));
+    }
+}
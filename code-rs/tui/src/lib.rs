@@ -78,7 +78,9 @@ mod header_wave;
 mod history_cell;
 mod history;
 mod insert_history;
+mod keymap;
 pub mod live_wrap;
+mod local_model_presets;
 mod markdown;
 mod markdown_render;
 mod markdown_renderer;
@@ -89,6 +91,7 @@ pub mod onboarding;
 pub mod public_widgets;
 mod render;
 mod model_migration;
+mod patch_hunk_review;
 // mod scroll_view; // Orphaned after trait-based HistoryCell migration
 mod session_log;
 mod shimmer;
@@ -419,8 +422,8 @@ pub async fn run_main(
     };
 
     // When using `--oss`, let the bootstrapper pick the model (defaulting to
-    // gpt-oss:20b) and ensure it is present locally. Also, force the built‑in
-    // `oss` model provider.
+    // gpt-oss:20b, or the first model a non-Ollama server is serving) and
+    // ensure it is present locally. Also, force the built‑in `oss` provider.
     let model = if let Some(model) = &cli.model {
         Some(model.clone())
     } else if cli.oss {
@@ -720,9 +723,10 @@ pub async fn run_main(
         .try_init();
 
     if cli.oss {
-        code_ollama::ensure_oss_ready(&config)
+        let selection = code_ollama::ensure_oss_ready(&config, cli.model.is_some())
             .await
             .map_err(|e| std::io::Error::other(format!("OSS setup failed: {e}")))?;
+        code_ollama::apply_oss_selection(&mut config, &selection);
     }
 
     let _otel = code_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION"));
@@ -0,0 +1,133 @@
+use code_common::model_presets::ModelPreset;
+use code_common::model_presets::ReasoningEffortPreset;
+use code_core::ModelProviderInfo;
+use code_core::config_types::TextVerbosity as TextVerbosityConfig;
+use code_core::protocol_config_types::ReasoningEffort as ProtocolReasoningEffort;
+use code_ollama::LocalModel;
+use code_ollama::LocalServer;
+use code_ollama::LocalServerKind;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+const LOCAL_TEXT_VERBOSITY: &[TextVerbosityConfig] = &[TextVerbosityConfig::Medium];
+
+/// Whether the model picker should list the provider's local server models
+/// instead of the OpenAI catalog.
+pub(crate) fn provider_is_local(provider: &ModelProviderInfo) -> bool {
+    !provider.requires_openai_auth
+        && provider
+            .base_url
+            .as_deref()
+            .is_some_and(code_ollama::is_local_base_url)
+}
+
+/// Ask the provider's local server for its models and publish them as the
+/// model picker presets.
+pub(crate) fn spawn_local_model_discovery(app_event_tx: AppEventSender, provider: ModelProviderInfo) {
+    let Some(base_url) = provider.base_url else {
+        return;
+    };
+    tokio::spawn(async move {
+        let server = match LocalServer::detect(&base_url, None).await {
+            Ok(server) => server,
+            Err(err) => {
+                tracing::debug!("local model discovery skipped: {err}");
+                return;
+            }
+        };
+        let models = match server.list_models().await {
+            Ok(models) => models,
+            Err(err) => {
+                tracing::warn!("failed to list models from {}: {err}", server.kind());
+                return;
+            }
+        };
+        let presets = local_models_to_presets(server.kind(), models);
+        if presets.is_empty() {
+            return;
+        }
+        app_event_tx.send(AppEvent::ModelPresetsUpdated {
+            presets,
+            default_model: None,
+        });
+    });
+}
+
+fn local_models_to_presets(kind: LocalServerKind, models: Vec<LocalModel>) -> Vec<ModelPreset> {
+    models
+        .into_iter()
+        .enumerate()
+        .map(|(index, model)| {
+            let description = match model.context_window {
+                Some(window) => format!("Served by {kind} · {} context", format_tokens(window)),
+                None => format!("Served by {kind}"),
+            };
+            ModelPreset {
+                id: model.id.clone(),
+                model: model.id.clone(),
+                display_name: model.id,
+                description: description.clone(),
+                // Local servers do not expose reasoning controls, so each model
+                // gets a single entry.
+                default_reasoning_effort: ProtocolReasoningEffort::Medium,
+                supported_reasoning_efforts: vec![ReasoningEffortPreset {
+                    effort: ProtocolReasoningEffort::Medium,
+                    description,
+                }],
+                supported_text_verbosity: LOCAL_TEXT_VERBOSITY,
+                is_default: index == 0,
+                upgrade: None,
+                pro_only: false,
+                show_in_picker: true,
+            }
+        })
+        .collect()
+}
+
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1024 && tokens.is_multiple_of(1024) {
+        format!("{}k", tokens / 1024)
+    } else if tokens >= 1000 {
+        format!("{}k", tokens / 1000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn local_models_become_single_effort_presets() {
+        let presets = local_models_to_presets(
+            LocalServerKind::LlamaCpp,
+            vec![
+                LocalModel {
+                    id: "qwen2.5-coder-7b.gguf".to_string(),
+                    context_window: Some(32768),
+                },
+                LocalModel {
+                    id: "phi-4".to_string(),
+                    context_window: None,
+                },
+            ],
+        );
+        assert_eq!(presets.len(), 2);
+        assert!(presets[0].is_default);
+        assert!(!presets[1].is_default);
+        assert_eq!(presets[0].description, "Served by llama.cpp · 32k context");
+        assert_eq!(presets[1].description, "Served by llama.cpp");
+        assert_eq!(presets[1].supported_reasoning_efforts.len(), 1);
+    }
+
+    #[test]
+    fn only_loopback_providers_without_openai_auth_are_local() {
+        let mut provider = code_core::create_oss_provider_with_base_url("http://localhost:8080/v1");
+        assert!(provider_is_local(&provider));
+        provider.base_url = Some("https://models.example.com/v1".to_string());
+        assert!(!provider_is_local(&provider));
+    }
+}
@@ -0,0 +1,308 @@
+//! State for reviewing an `apply_patch` approval hunk by hunk.
+//!
+//! Hunks come from `code_core::patch_review` so indices line up with what
+//! core recomputes when the `ReviewDecision::ApprovedHunks` decision arrives.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use code_core::patch_review::PatchHunk;
+use code_core::patch_review::hunks_for_file_change;
+use code_core::protocol::FileChange;
+use code_core::protocol::HunkDecision;
+use code_core::protocol::PatchHunkReview;
+use ratatui::prelude::*;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+use crate::diff_render::render_hunk_lines;
+
+/// Maximum diff rows shown for the focused hunk before truncating.
+const MAX_HUNK_ROWS: usize = 14;
+
+struct HunkEntry {
+    path: PathBuf,
+    file_index: usize,
+    hunk_index: usize,
+    hunks_in_file: usize,
+    /// `None` for whole-file deletions, which have no hunks.
+    hunk: Option<PatchHunk>,
+    decision: Option<HunkDecision>,
+}
+
+pub(crate) struct PatchHunkReviewState {
+    entries: Vec<HunkEntry>,
+    file_count: usize,
+    cursor: usize,
+}
+
+impl PatchHunkReviewState {
+    pub(crate) fn new(changes: &HashMap<PathBuf, FileChange>) -> Self {
+        let mut paths: Vec<&PathBuf> = changes.keys().collect();
+        paths.sort();
+
+        let mut entries = Vec::new();
+        for (file_index, path) in paths.iter().enumerate() {
+            let change = &changes[*path];
+            if matches!(change, FileChange::Delete) {
+                entries.push(HunkEntry {
+                    path: (*path).clone(),
+                    file_index,
+                    hunk_index: 0,
+                    hunks_in_file: 1,
+                    hunk: None,
+                    decision: None,
+                });
+                continue;
+            }
+            let hunks = hunks_for_file_change(change);
+            let hunks_in_file = hunks.len();
+            for (hunk_index, hunk) in hunks.into_iter().enumerate() {
+                entries.push(HunkEntry {
+                    path: (*path).clone(),
+                    file_index,
+                    hunk_index,
+                    hunks_in_file,
+                    hunk: Some(hunk),
+                    decision: None,
+                });
+            }
+        }
+
+        Self {
+            entries,
+            file_count: paths.len(),
+            cursor: 0,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn next(&mut self) {
+        if self.cursor + 1 < self.entries.len() {
+            self.cursor += 1;
+        }
+    }
+
+    pub(crate) fn prev(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Record a decision for the focused hunk and move to the next one.
+    pub(crate) fn decide(&mut self, decision: HunkDecision) {
+        if let Some(entry) = self.entries.get_mut(self.cursor) {
+            entry.decision = Some(decision);
+        }
+        self.next();
+    }
+
+    /// Apply `decision` to every undecided hunk in the focused file.
+    pub(crate) fn decide_rest_of_file(&mut self, decision: HunkDecision) {
+        let Some(path) = self.entries.get(self.cursor).map(|e| e.path.clone()) else {
+            return;
+        };
+        for entry in self.entries.iter_mut().filter(|e| e.path == path) {
+            if entry.decision.is_none() {
+                entry.decision = Some(decision.clone());
+            }
+        }
+        if let Some(idx) = self.entries.iter().position(|e| e.path > path) {
+            self.cursor = idx;
+        }
+    }
+
+    /// Text handed to `$EDITOR` for the focused hunk, or `None` when the
+    /// focused entry cannot be edited (deletions).
+    pub(crate) fn edit_target(&self) -> Option<(PathBuf, usize, String)> {
+        let entry = self.entries.get(self.cursor)?;
+        let hunk = entry.hunk.as_ref()?;
+        let initial = match &entry.decision {
+            Some(HunkDecision::Edit { replacement }) => replacement.clone(),
+            _ => hunk.new_text(),
+        };
+        Some((entry.path.clone(), entry.hunk_index, initial))
+    }
+
+    /// Store the edited replacement for a hunk returned from `$EDITOR`.
+    pub(crate) fn apply_edit(&mut self, path: &Path, hunk_index: usize, text: String) {
+        if let Some(pos) = self
+            .entries
+            .iter()
+            .position(|e| e.path == path && e.hunk_index == hunk_index)
+        {
+            let unchanged = self.entries[pos]
+                .hunk
+                .as_ref()
+                .is_some_and(|h| h.new_text() == text);
+            self.entries[pos].decision = Some(if unchanged {
+                HunkDecision::Accept
+            } else {
+                HunkDecision::Edit { replacement: text }
+            });
+            self.cursor = pos;
+            self.next();
+        }
+    }
+
+    pub(crate) fn undecided_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.decision.is_none()).count()
+    }
+
+    /// Decisions to send to core. Undecided hunks are omitted, which core
+    /// treats as rejected.
+    pub(crate) fn reviews(&self) -> Vec<PatchHunkReview> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                entry.decision.clone().map(|decision| PatchHunkReview {
+                    path: entry.path.clone(),
+                    hunk_index: entry.hunk_index,
+                    decision,
+                })
+            })
+            .collect()
+    }
+
+    /// One-line summary for the background history notice.
+    pub(crate) fn summary(&self) -> String {
+        let mut accepted = 0;
+        let mut rejected = 0;
+        let mut edited = 0;
+        for entry in &self.entries {
+            match entry.decision {
+                Some(HunkDecision::Accept) => accepted += 1,
+                Some(HunkDecision::Edit { .. }) => edited += 1,
+                Some(HunkDecision::Reject) | None => rejected += 1,
+            }
+        }
+        format!("{accepted} accepted, {edited} edited, {rejected} rejected")
+    }
+
+    pub(crate) fn render_lines(&self, width: u16) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line<'static>> = Vec::new();
+        let Some(entry) = self.entries.get(self.cursor) else {
+            return lines;
+        };
+
+        let (status, status_style) = match &entry.decision {
+            None => ("pending", Style::default().fg(crate::colors::text_dim())),
+            Some(HunkDecision::Accept) => ("accepted", Style::default().fg(crate::colors::success())),
+            Some(HunkDecision::Reject) => ("rejected", Style::default().fg(crate::colors::error())),
+            Some(HunkDecision::Edit { .. }) => ("edited", Style::default().fg(crate::colors::warning())),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("File {}/{} ", entry.file_index + 1, self.file_count),
+                Style::default().fg(crate::colors::text_dim()),
+            ),
+            Span::styled(
+                entry.path.display().to_string(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  hunk {}/{}  ", entry.hunk_index + 1, entry.hunks_in_file),
+                Style::default().fg(crate::colors::text_dim()),
+            ),
+            Span::styled(format!("[{status}]"), status_style),
+        ]));
+
+        match &entry.hunk {
+            Some(hunk) => {
+                lines.push(Line::from(Span::styled(
+                    hunk.header(),
+                    Style::default().fg(crate::colors::info()),
+                )));
+                let mut rows = render_hunk_lines(hunk, width.saturating_sub(2) as usize);
+                if rows.len() > MAX_HUNK_ROWS {
+                    let hidden = rows.len() - MAX_HUNK_ROWS;
+                    rows.truncate(MAX_HUNK_ROWS);
+                    rows.push(Line::from(Span::styled(
+                        format!("  … {hidden} more line(s); press e to view in $EDITOR"),
+                        Style::default().fg(crate::colors::text_dim()),
+                    )));
+                }
+                lines.extend(rows);
+            }
+            None => {
+                lines.push(Line::from(Span::styled(
+                    "Delete this file",
+                    Style::default().fg(crate::colors::error()),
+                )));
+            }
+        }
+
+        lines.push(Line::from(""));
+        let undecided = self.undecided_count();
+        let mut footer = "y accept · n reject · e edit · a/d rest of file · ←/→ move · Enter apply · Esc back".to_string();
+        if undecided > 0 {
+            footer.push_str(&format!("  ({undecided} pending, applied as rejected)"));
+        }
+        lines.push(Line::from(Span::styled(
+            footer,
+            Style::default().fg(crate::colors::text_dim()),
+        )));
+        lines
+    }
+
+    /// Upper bound on rows `render_lines` produces, used for layout.
+    pub(crate) fn desired_height(&self) -> u16 {
+        // header + hunk header + rows (+ truncation notice) + spacer + footer
+        (MAX_HUNK_ROWS + 5) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_changes() -> HashMap<PathBuf, FileChange> {
+        let original = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n".to_string();
+        let new_content = "A\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nM\n".to_string();
+        HashMap::from([
+            (
+                PathBuf::from("/repo/src/lib.rs"),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: None,
+                    original_content: original,
+                    new_content,
+                },
+            ),
+            (PathBuf::from("/repo/old.txt"), FileChange::Delete),
+        ])
+    }
+
+    #[test]
+    fn walks_files_in_path_order_and_collects_decisions() {
+        let mut state = PatchHunkReviewState::new(&sample_changes());
+        assert_eq!(state.undecided_count(), 3);
+
+        state.decide(HunkDecision::Reject); // /repo/old.txt deletion
+        state.decide(HunkDecision::Accept); // lib.rs hunk 0
+        state.apply_edit(Path::new("/repo/src/lib.rs"), 1, "X\n".to_string());
+
+        let reviews = state.reviews();
+        assert_eq!(reviews.len(), 3);
+        assert_eq!(reviews[0].path, PathBuf::from("/repo/old.txt"));
+        assert_eq!(reviews[0].decision, HunkDecision::Reject);
+        assert_eq!(reviews[1].hunk_index, 0);
+        assert_eq!(
+            reviews[2].decision,
+            HunkDecision::Edit {
+                replacement: "X\n".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unchanged_edit_counts_as_accept() {
+        let mut state = PatchHunkReviewState::new(&sample_changes());
+        state.next();
+        let (path, hunk_index, initial) = state.edit_target().expect("editable hunk");
+        state.apply_edit(&path, hunk_index, initial);
+        assert_eq!(state.reviews()[0].decision, HunkDecision::Accept);
+    }
+}
@@ -5,6 +5,7 @@ use crate::colors;
 use chrono::{DateTime, Datelike, Local, Utc};
 use code_common::elapsed::format_duration;
 use code_core::protocol::RateLimitSnapshotEvent;
+use code_core::usage_report::{UsageReport, UsageReportPeriod};
 use code_protocol::num_format::format_with_separators_u64;
 use ratatui::prelude::*;
 use ratatui::style::Stylize;
@@ -17,6 +18,8 @@ const BAR_SLOTS: usize = 20;
 const BAR_FILLED: &str = "▰";
 const BAR_EMPTY: &str = "▱";
 const CHART_LINE_PREFIX: &str = "    ";
+const REPORT_KEY_WIDTH: usize = 24;
+const REPORT_MAX_KEYS: usize = 5;
 struct IndentSpec {
     header: &'static str,
     label_extra: &'static str,
@@ -945,6 +948,118 @@ fn compute_grid_state(metrics: &RateLimitMetrics, capacity_fraction: f64) -> Opt
     })
 }
 
+/// Lines for the usage report tab of `/limits`: total tokens per period from
+/// `timeline`, then the share of each key for every `(title, report)`
+/// breakdown. Keys beyond the top few are folded into "other".
+pub(crate) fn build_usage_report_lines(
+    timeline: &UsageReport,
+    breakdowns: &[(&str, &UsageReport)],
+) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let dim = Style::default().fg(colors::text_dim());
+
+    let mut per_period: Vec<(chrono::NaiveDate, u64)> = Vec::new();
+    for row in &timeline.rows {
+        match per_period.last_mut() {
+            Some((start, total)) if *start == row.period_start => {
+                *total = total.saturating_add(row.tokens.total_tokens);
+            }
+            _ => per_period.push((row.period_start, row.tokens.total_tokens)),
+        }
+    }
+
+    if per_period.is_empty() {
+        lines.push(Line::from(Span::styled(
+            label_text("No usage recorded in this window yet."),
+            dim,
+        )));
+        return lines;
+    }
+
+    lines.push(section_header(match timeline.period {
+        UsageReportPeriod::Daily => "Tokens per day",
+        UsageReportPeriod::Weekly => "Tokens per week",
+        UsageReportPeriod::Monthly => "Tokens per month",
+    }));
+    let max_total = per_period.iter().map(|(_, total)| *total).max().unwrap_or(0);
+    for (start, total) in &per_period {
+        let percent = if max_total == 0 {
+            0.0
+        } else {
+            (*total as f64 / max_total as f64) * 100.0
+        };
+        let mut spans = vec![Span::styled(
+            format!("{}{}  ", label_indent(), start.format("%a %b %d")),
+            dim,
+        )];
+        spans.extend(render_percent_bar(percent));
+        spans.push(Span::raw(format!(
+            " {} tokens",
+            format_with_separators_u64(*total)
+        )));
+        lines.push(Line::from(spans));
+    }
+
+    for (title, report) in breakdowns {
+        let totals = report.totals_by_key();
+        if totals.is_empty() {
+            continue;
+        }
+        let grand_total: u64 = totals
+            .iter()
+            .map(|(_, _, tokens)| tokens.total_tokens)
+            .sum();
+        let mut shown: Vec<(String, u64)> = totals
+            .iter()
+            .take(REPORT_MAX_KEYS)
+            .map(|(key, _, tokens)| (key.clone(), tokens.total_tokens))
+            .collect();
+        let other: u64 = totals
+            .iter()
+            .skip(REPORT_MAX_KEYS)
+            .map(|(_, _, tokens)| tokens.total_tokens)
+            .sum();
+        if other > 0 {
+            shown.push(("other".to_string(), other));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(section_header(title));
+        for (key, total) in shown {
+            let percent = if grand_total == 0 {
+                0.0
+            } else {
+                (total as f64 / grand_total as f64) * 100.0
+            };
+            let mut spans = vec![Span::raw(format!(
+                "{}{}  ",
+                label_indent(),
+                fit_report_key(&key)
+            ))];
+            spans.extend(render_percent_bar(percent));
+            spans.push(Span::raw(format!(" {}", format_percent(percent))));
+            spans.push(Span::styled(
+                format!(" · {} tokens", format_with_separators_u64(total)),
+                dim,
+            ));
+            lines.push(Line::from(spans));
+        }
+    }
+
+    lines
+}
+
+/// Pad or shorten `key` to the report label column, keeping the tail of long
+/// keys (project paths differ at the end).
+fn fit_report_key(key: &str) -> String {
+    let count = key.chars().count();
+    if count <= REPORT_KEY_WIDTH {
+        return format!("{key:<REPORT_KEY_WIDTH$}");
+    }
+    let tail: String = key.chars().skip(count - (REPORT_KEY_WIDTH - 1)).collect();
+    format!("…{tail}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -992,6 +1107,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn usage_report_lines_show_daily_totals_and_shares() {
+        use chrono::TimeZone;
+        use code_core::account_usage::{UsageLedgerEntry, UsageSource};
+        use code_core::protocol::TokenUsage;
+        use code_core::usage_report::UsageReportDimension;
+
+        let at = |day: u32| Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+        let entry = |day: u32, model: &str, total: u64| {
+            let usage = TokenUsage {
+                total_tokens: total,
+                ..TokenUsage::default()
+            };
+            UsageLedgerEntry::new(
+                "acct".to_string(),
+                model.to_string(),
+                None,
+                UsageSource::Tui,
+                &usage,
+                at(day),
+            )
+        };
+        let entries = vec![entry(2, "gpt-5", 300), entry(3, "gpt-5-mini", 100)];
+        let since = at(1);
+        let until = at(5);
+        let timeline = UsageReport::build(
+            &entries,
+            UsageReportPeriod::Daily,
+            UsageReportDimension::Account,
+            since,
+            until,
+        );
+        let by_model = UsageReport::build(
+            &entries,
+            UsageReportPeriod::Daily,
+            UsageReportDimension::Model,
+            since,
+            until,
+        );
+
+        let rendered: Vec<String> =
+            build_usage_report_lines(&timeline, &[("By model", &by_model)])
+                .iter()
+                .map(plain_text)
+                .collect();
+        let has = |needle: &str, value: &str| {
+            rendered
+                .iter()
+                .any(|line| line.contains(needle) && line.contains(value))
+        };
+        assert!(has("Mon Mar 02", "300 tokens"), "{rendered:#?}");
+        assert!(has("gpt-5 ", "75%"), "{rendered:#?}");
+        assert!(has("gpt-5-mini", "25%"), "{rendered:#?}");
+    }
+
     #[test]
     fn hides_usage_sections_when_disabled() {
         let snapshot = base_snapshot();
@@ -55,6 +55,7 @@ pub enum SlashCommand {
     New,
     Init,
     Compact,
+    Context,
     Undo,
     Review,
     Cloud,
@@ -79,6 +80,8 @@ pub enum SlashCommand {
     Auto,
     Branch,
     Merge,
+    Worktrees,
+    Evaluate,
     Push,
     Validation,
     Mcp,
@@ -111,6 +114,7 @@ impl SlashCommand {
             SlashCommand::New => "start a new chat during a conversation",
             SlashCommand::Init => "create an AGENTS.md file with instructions for Code",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
+            SlashCommand::Context => "show what fills the context window; drop or collapse items",
             SlashCommand::Undo => "restore the workspace to the last Code snapshot",
             SlashCommand::Review => "review your changes for potential issues",
             SlashCommand::Cloud => "browse, apply, and create cloud tasks",
@@ -133,6 +137,8 @@ impl SlashCommand {
                 "work in an isolated /branch then /merge when done (great for parallel work)"
             }
             SlashCommand::Merge => "merge current worktree branch back to default",
+            SlashCommand::Worktrees => "list, open, diff and prune worktrees from /branch and agents",
+            SlashCommand::Evaluate => "score the last /code batch's worktrees and apply the winner",
             SlashCommand::Push => "commit, push, and monitor workflows",
             SlashCommand::Validation => "control validation harness (status/on/off)",
             SlashCommand::Mcp => "manage MCP servers",
@@ -6,8 +6,11 @@
 //! UI to Rust using [`ratatui`]. The goal is feature‑parity for the keyboard
 //! driven workflow – a fully‑fledged visual match is not required.
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use code_core::protocol::FileChange;
+use code_core::protocol::HunkDecision;
 use code_core::protocol::Op;
 use code_core::protocol::ReviewDecision;
 use crossterm::event::KeyCode;
@@ -26,9 +29,12 @@ use ratatui::widgets::Wrap;
 use shlex::split as shlex_split;
 
 use crate::app_event::AppEvent;
+use crate::app_event::ExternalEditorTarget;
 use crate::app_event_sender::AppEventSender;
+use crate::bottom_pane::form_text_field::FormTextField;
 use crate::chatwidget::BackgroundOrderTicket;
 use crate::exec_command::strip_bash_lc_and_escape;
+use crate::patch_hunk_review::PatchHunkReviewState;
 use code_core::protocol::ApprovedCommandMatchKind;
 
 /// Request coming from the agent that needs user approval.
@@ -42,6 +48,7 @@ pub(crate) enum ApprovalRequest {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        changes: HashMap<PathBuf, FileChange>,
     },
     TerminalCommand {
         id: u64,
@@ -66,6 +73,9 @@ enum SelectAction {
         persist: bool,
         semantic_prefix: Option<Vec<String>>,
     },
+    ReviewHunks,
+    EditCommand,
+    Feedback,
     Abort,
 }
 
@@ -80,6 +90,15 @@ pub(crate) struct UserApprovalWidget<'a> {
     /// Currently selected index in *select* mode.
     selected_option: usize,
 
+    /// Present while the user walks an `apply_patch` request hunk by hunk.
+    hunk_review: Option<PatchHunkReviewState>,
+
+    /// Present while the user types feedback to send along with a denial.
+    feedback_input: Option<FormTextField>,
+
+    /// Command the user rewrote in `$EDITOR`; approving runs this instead.
+    edited_command: Option<Vec<String>>,
+
     /// Set to `true` once a decision has been sent – the parent view can then
     /// remove this widget from its queue.
     done: bool,
@@ -94,26 +113,7 @@ impl UserApprovalWidget<'_> {
         let confirmation_prompt = match &approval_request {
             ApprovalRequest::Exec {
                 command, reason, ..
-            } => {
-                let cmd = strip_bash_lc_and_escape(command);
-                // Present a single-line summary without cwd: "codex wants to run: <cmd>"
-                let mut cmd_span: Span = cmd.clone().into();
-                cmd_span.style = cmd_span.style.add_modifier(Modifier::DIM);
-                let mut contents: Vec<Line> = vec![
-                    Line::from(""), // extra spacing above the prompt
-                    Line::from(vec![
-                        "? ".fg(crate::colors::info()),
-                        "Code wants to run ".bold(),
-                        cmd_span,
-                    ]),
-                    Line::from(""),
-                ];
-                if let Some(reason) = reason {
-                    contents.push(Line::from(reason.clone().italic()));
-                    contents.push(Line::from(""));
-                }
-                Paragraph::new(contents).wrap(Wrap { trim: false })
-            }
+            } => build_exec_prompt(command, reason.as_deref(), false),
             ApprovalRequest::ApplyPatch {
                 reason, grant_root, ..
             } => {
@@ -164,6 +164,9 @@ impl UserApprovalWidget<'_> {
             confirmation_prompt,
             select_options,
             selected_option: 0,
+            hunk_review: None,
+            feedback_input: None,
+            edited_command: None,
             done: false,
         }
     }
@@ -188,7 +191,134 @@ impl UserApprovalWidget<'_> {
         // may emit an initial Repeat for some keys (e.g. Enter) when keyboard
         // enhancement flags are enabled.
         if matches!(key.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
-            self.handle_select_key(key);
+            if self.feedback_input.is_some() {
+                self.handle_feedback_key(key);
+            } else if self.hunk_review.is_some() {
+                self.handle_hunk_review_key(key);
+            } else {
+                self.handle_select_key(key);
+            }
+        }
+    }
+
+    fn handle_hunk_review_key(&mut self, key_event: KeyEvent) {
+        let Some(review) = self.hunk_review.as_mut() else {
+            return;
+        };
+        match Self::normalize_keycode(key_event.code) {
+            KeyCode::Left | KeyCode::Up | KeyCode::Char('k') => review.prev(),
+            KeyCode::Right | KeyCode::Down | KeyCode::Char('j') => review.next(),
+            KeyCode::Char('y') => review.decide(HunkDecision::Accept),
+            KeyCode::Char('n') => review.decide(HunkDecision::Reject),
+            KeyCode::Char('a') => review.decide_rest_of_file(HunkDecision::Accept),
+            KeyCode::Char('d') => review.decide_rest_of_file(HunkDecision::Reject),
+            KeyCode::Char('e') => {
+                if let (Some((path, hunk_index, initial)), ApprovalRequest::ApplyPatch { id, .. }) =
+                    (review.edit_target(), &self.approval_request)
+                {
+                    self.app_event_tx.send(AppEvent::OpenExternalEditor {
+                        initial,
+                        target: ExternalEditorTarget::PatchHunk {
+                            call_id: id.clone(),
+                            path,
+                            hunk_index,
+                        },
+                    });
+                }
+            }
+            KeyCode::Enter => {
+                let hunks = review.reviews();
+                self.send_decision(ReviewDecision::ApprovedHunks { hunks });
+            }
+            KeyCode::Esc => {
+                self.hunk_review = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_feedback_key(&mut self, key_event: KeyEvent) {
+        let Some(field) = self.feedback_input.as_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Enter => {
+                let feedback = field.text().trim().to_string();
+                self.feedback_input = None;
+                if feedback.is_empty() {
+                    // No feedback given: stop the turn so the user can reply
+                    // in the composer instead.
+                    self.send_decision(ReviewDecision::Abort);
+                } else {
+                    self.send_decision_with_feedback(
+                        ReviewDecision::DeniedWithFeedback {
+                            feedback: feedback.clone(),
+                        },
+                        feedback,
+                    );
+                }
+            }
+            KeyCode::Esc => {
+                self.feedback_input = None;
+            }
+            _ => {
+                field.handle_key(key_event);
+            }
+        }
+    }
+
+    /// Paste into the feedback field when it is open. Returns whether the
+    /// paste was consumed.
+    pub(crate) fn handle_paste(&mut self, text: String) -> bool {
+        match self.feedback_input.as_mut() {
+            Some(field) => {
+                field.handle_paste(text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply text returned from `$EDITOR` for this request (an edited command
+    /// or patch hunk). Returns `false` if `target` belongs to another request.
+    pub(crate) fn apply_external_edit(&mut self, target: &ExternalEditorTarget, text: String) -> bool {
+        if self.done {
+            return false;
+        }
+        match (target, &self.approval_request) {
+            (
+                ExternalEditorTarget::PatchHunk {
+                    call_id,
+                    path,
+                    hunk_index,
+                },
+                ApprovalRequest::ApplyPatch { id, .. },
+            ) if id == call_id => match self.hunk_review.as_mut() {
+                Some(review) => {
+                    review.apply_edit(path, *hunk_index, text);
+                    true
+                }
+                None => false,
+            },
+            (ExternalEditorTarget::ExecCommand { call_id }, ApprovalRequest::Exec { id, command, reason })
+                if id == call_id =>
+            {
+                let Some(edited) = command_from_edited_text(command, &text) else {
+                    return true;
+                };
+                if edited == *command {
+                    self.edited_command = None;
+                    self.confirmation_prompt = build_exec_prompt(command, reason.as_deref(), false);
+                    self.select_options = build_exec_select_options(command);
+                } else {
+                    self.confirmation_prompt = build_exec_prompt(&edited, reason.as_deref(), true);
+                    self.select_options = build_edited_exec_select_options();
+                    self.edited_command = Some(edited);
+                }
+                self.selected_option = 0;
+                true
+            }
+            _ => false,
         }
     }
 
@@ -264,16 +394,28 @@ impl UserApprovalWidget<'_> {
         let message = match &self.approval_request {
             ApprovalRequest::Exec { command, .. } => {
                 let cmd = strip_bash_lc_and_escape(command);
-                match decision {
+                match &decision {
                     ReviewDecision::Approved => format!("approved: run {} (this time)", cmd),
                     ReviewDecision::ApprovedForSession => format!("approved: run {} (every time this session)", cmd),
-                    ReviewDecision::Denied => format!("not approved: run {}", cmd),
+                    ReviewDecision::ApprovedWithEdits { command } => format!(
+                        "approved: run {} (edited from {})",
+                        strip_bash_lc_and_escape(command),
+                        cmd
+                    ),
+                    ReviewDecision::Denied
+                    | ReviewDecision::DeniedWithFeedback { .. }
+                    | ReviewDecision::ApprovedHunks { .. } => {
+                        format!("not approved: run {}", cmd)
+                    }
                     ReviewDecision::Abort => format!("canceled: run {}", cmd),
                 }
             }
-            ApprovalRequest::ApplyPatch { .. } => {
-                format!("patch approval decision: {:?}", decision)
-            }
+            ApprovalRequest::ApplyPatch { .. } => match (&decision, self.hunk_review.as_ref()) {
+                (ReviewDecision::ApprovedHunks { .. }, Some(review)) => {
+                    format!("patch approved in part: {}", review.summary())
+                }
+                _ => format!("patch approval decision: {:?}", decision),
+            },
             ApprovalRequest::TerminalCommand { .. } => unreachable!("terminal approvals handled earlier"),
         };
         let message = if feedback.trim().is_empty() {
@@ -289,17 +431,23 @@ impl UserApprovalWidget<'_> {
         // If the user aborted an exec approval, immediately cancel any running task
         // so the UI reflects their intent (clear spinner/status) without waiting
         // for backend cleanup. Core still receives the Abort below.
-        match (&self.approval_request, decision) {
+        match (&self.approval_request, &decision) {
             (ApprovalRequest::Exec { .. }, ReviewDecision::Abort) => {
                 self.app_event_tx.send(AppEvent::CancelRunningTask);
             }
-            (ApprovalRequest::Exec { .. }, ReviewDecision::Denied) => {
+            (
+                ApprovalRequest::Exec { .. },
+                ReviewDecision::Denied | ReviewDecision::DeniedWithFeedback { .. },
+            ) => {
                 self.app_event_tx.send(AppEvent::MarkTaskIdle);
             }
             (ApprovalRequest::ApplyPatch { .. }, ReviewDecision::Abort) => {
                 self.app_event_tx.send(AppEvent::CancelRunningTask);
             }
-            (ApprovalRequest::ApplyPatch { .. }, ReviewDecision::Denied) => {
+            (
+                ApprovalRequest::ApplyPatch { .. },
+                ReviewDecision::Denied | ReviewDecision::DeniedWithFeedback { .. },
+            ) => {
                 self.app_event_tx.send(AppEvent::MarkTaskIdle);
             }
             _ => {}
@@ -324,8 +472,21 @@ impl UserApprovalWidget<'_> {
 
     fn perform_action(&mut self, action: SelectAction) {
         match action {
-            SelectAction::ApproveOnce => {
-                self.send_decision(ReviewDecision::Approved);
+            SelectAction::ApproveOnce => match self.edited_command.clone() {
+                Some(command) => self.send_decision(ReviewDecision::ApprovedWithEdits { command }),
+                None => self.send_decision(ReviewDecision::Approved),
+            },
+            SelectAction::EditCommand => {
+                if let ApprovalRequest::Exec { id, command, .. } = &self.approval_request {
+                    let current = self.edited_command.as_ref().unwrap_or(command);
+                    self.app_event_tx.send(AppEvent::OpenExternalEditor {
+                        initial: editable_command_text(current),
+                        target: ExternalEditorTarget::ExecCommand { call_id: id.clone() },
+                    });
+                }
+            }
+            SelectAction::Feedback => {
+                self.feedback_input = Some(FormTextField::new_single_line());
             }
             SelectAction::ApproveForSession {
                 command,
@@ -341,6 +502,14 @@ impl UserApprovalWidget<'_> {
                 });
                 self.send_decision(ReviewDecision::ApprovedForSession);
             }
+            SelectAction::ReviewHunks => {
+                if let ApprovalRequest::ApplyPatch { changes, .. } = &self.approval_request {
+                    let review = PatchHunkReviewState::new(changes);
+                    if !review.is_empty() {
+                        self.hunk_review = Some(review);
+                    }
+                }
+            }
             SelectAction::Abort => {
                 self.send_decision(ReviewDecision::Abort);
             }
@@ -355,6 +524,13 @@ impl UserApprovalWidget<'_> {
 
     pub(crate) fn desired_height(&self, width: u16) -> u16 {
         let prompt = self.get_confirmation_prompt_height(width);
+        if self.feedback_input.is_some() {
+            // label + field + hint
+            return prompt + 5;
+        }
+        if let Some(review) = self.hunk_review.as_ref() {
+            return prompt + review.desired_height() + 2;
+        }
         let option_lines = (self.select_options.len() as u16).saturating_mul(2);
         prompt + option_lines + 2
     }
@@ -371,6 +547,44 @@ impl WidgetRef for &UserApprovalWidget<'_> {
 
         self.confirmation_prompt.clone().render(prompt_chunk, buf);
 
+        if let Some(field) = self.feedback_input.as_ref() {
+            let inner = options_chunk.inner(Margin::new(1, 0));
+            let [label_area, field_area, hint_area] = Layout::vertical([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .areas(inner);
+            Paragraph::new(Line::from(Span::styled(
+                "Tell Code what to do instead:",
+                Style::default().add_modifier(Modifier::BOLD),
+            )))
+            .render(label_area, buf);
+            field.render(field_area, buf, true);
+            Paragraph::new(Line::from(Span::styled(
+                "Enter send · empty Enter stops the turn · Esc back",
+                Style::default().fg(crate::colors::text_dim()),
+            )))
+            .render(hint_area.inner(Margin::new(0, 1)), buf);
+            Block::bordered()
+                .border_type(BorderType::QuadrantOutside)
+                .border_style(Style::default().fg(crate::colors::light_blue()))
+                .borders(Borders::LEFT)
+                .render_ref(Rect::new(0, options_chunk.y, 1, options_chunk.height), buf);
+            return;
+        }
+
+        if let Some(review) = self.hunk_review.as_ref() {
+            let inner = options_chunk.inner(Margin::new(1, 0));
+            Paragraph::new(review.render_lines(inner.width)).render(inner, buf);
+            Block::bordered()
+                .border_type(BorderType::QuadrantOutside)
+                .border_style(Style::default().fg(crate::colors::light_blue()))
+                .borders(Borders::LEFT)
+                .render_ref(Rect::new(0, options_chunk.y, 1, options_chunk.height), buf);
+            return;
+        }
+
         let mut lines: Vec<Line> = Vec::new();
         for (idx, option) in self.select_options.iter().enumerate() {
             let selected = idx == self.selected_option;
@@ -421,6 +635,13 @@ fn build_exec_select_options(command: &[String]) -> Vec<SelectOption> {
         action: SelectAction::ApproveOnce,
     });
 
+    options.push(SelectOption {
+        label: "Edit, then run".to_string(),
+        description: "Change the command in $EDITOR before approving it".to_string(),
+        hotkey: KeyCode::Char('e'),
+        action: SelectAction::EditCommand,
+    });
+
     let full_display = strip_bash_lc_and_escape(command);
     options.push(SelectOption {
         label: format!("Always allow '{full_display}' for this project"),
@@ -457,12 +678,81 @@ fn build_exec_select_options(command: &[String]) -> Vec<SelectOption> {
         label: "No, provide feedback".to_string(),
         description: "Do not run the command; provide feedback".to_string(),
         hotkey: KeyCode::Char('n'),
-        action: SelectAction::Abort,
+        action: SelectAction::Feedback,
     });
 
     options
 }
 
+fn build_edited_exec_select_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption {
+            label: "Yes, run the edited command".to_string(),
+            description: "Approve and run the command as you edited it".to_string(),
+            hotkey: KeyCode::Char('y'),
+            action: SelectAction::ApproveOnce,
+        },
+        SelectOption {
+            label: "Edit again".to_string(),
+            description: "Reopen the command in $EDITOR".to_string(),
+            hotkey: KeyCode::Char('e'),
+            action: SelectAction::EditCommand,
+        },
+        SelectOption {
+            label: "No, provide feedback".to_string(),
+            description: "Do not run the command; provide feedback".to_string(),
+            hotkey: KeyCode::Char('n'),
+            action: SelectAction::Feedback,
+        },
+    ]
+}
+
+fn build_exec_prompt(command: &[String], reason: Option<&str>, edited: bool) -> Paragraph<'static> {
+    let cmd = strip_bash_lc_and_escape(command);
+    // Present a single-line summary without cwd: "codex wants to run: <cmd>"
+    let mut cmd_span: Span = cmd.into();
+    cmd_span.style = cmd_span.style.add_modifier(Modifier::DIM);
+    let verb = if edited { "Run your edited command " } else { "Code wants to run " };
+    let mut contents: Vec<Line> = vec![
+        Line::from(""), // extra spacing above the prompt
+        Line::from(vec![
+            "? ".fg(crate::colors::info()),
+            verb.bold(),
+            cmd_span,
+        ]),
+        Line::from(""),
+    ];
+    if let Some(reason) = reason {
+        contents.push(Line::from(reason.to_string().italic()));
+        contents.push(Line::from(""));
+    }
+    Paragraph::new(contents).wrap(Wrap { trim: false })
+}
+
+/// Text handed to `$EDITOR` when editing a command: the script for
+/// `bash -lc <script>` wrappers, otherwise the shell-quoted argv.
+fn editable_command_text(command: &[String]) -> String {
+    if command.len() == 3 && is_shell_wrapper(&command[0], &command[1]) {
+        return format!("{}\n", command[2]);
+    }
+    let joined = shlex::try_join(command.iter().map(String::as_str))
+        .unwrap_or_else(|_| command.join(" "));
+    format!("{joined}\n")
+}
+
+/// Turn edited editor text back into an argv shaped like `original`.
+/// Returns `None` when the edit leaves nothing to run.
+fn command_from_edited_text(original: &[String], text: &str) -> Option<Vec<String>> {
+    let script = text.trim();
+    if script.is_empty() {
+        return None;
+    }
+    if original.len() == 3 && is_shell_wrapper(&original[0], &original[1]) {
+        return Some(vec![original[0].clone(), original[1].clone(), script.to_string()]);
+    }
+    shlex_split(script).filter(|argv| !argv.is_empty())
+}
+
 fn build_patch_select_options() -> Vec<SelectOption> {
     vec![
         SelectOption {
@@ -471,11 +761,17 @@ fn build_patch_select_options() -> Vec<SelectOption> {
             hotkey: KeyCode::Char('y'),
             action: SelectAction::ApproveOnce,
         },
+        SelectOption {
+            label: "Review hunks".to_string(),
+            description: "Accept, reject or edit each hunk; apply only what you approve".to_string(),
+            hotkey: KeyCode::Char('r'),
+            action: SelectAction::ReviewHunks,
+        },
         SelectOption {
             label: "No, provide feedback".to_string(),
             description: "Do not apply the changes; provide feedback".to_string(),
             hotkey: KeyCode::Char('n'),
-            action: SelectAction::Abort,
+            action: SelectAction::Feedback,
         },
     ]
 }
@@ -560,3 +856,33 @@ fn hotkey_suffix(key: KeyCode) -> String {
         _ => String::new(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|part| (*part).to_string()).collect()
+    }
+
+    #[test]
+    fn edited_shell_script_keeps_wrapper() {
+        let original = argv(&["bash", "-lc", "cargo test"]);
+        assert_eq!(editable_command_text(&original), "cargo test\n");
+        assert_eq!(
+            command_from_edited_text(&original, "cargo test -p code-core\n"),
+            Some(argv(&["bash", "-lc", "cargo test -p code-core"]))
+        );
+    }
+
+    #[test]
+    fn edited_argv_is_shell_split() {
+        let original = argv(&["rm", "-rf", "build dir"]);
+        assert_eq!(editable_command_text(&original), "rm -rf 'build dir'\n");
+        assert_eq!(
+            command_from_edited_text(&original, "rm -r 'build dir'\n"),
+            Some(argv(&["rm", "-r", "build dir"]))
+        );
+        assert_eq!(command_from_edited_text(&original, "  \n"), None);
+    }
+}
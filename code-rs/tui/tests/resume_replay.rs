@@ -151,6 +151,7 @@ fn replay_history_duplicates_short_assistant_messages() {
         msg: EventMsg::ReplayHistory(ReplayHistoryEvent {
             items,
             history_snapshot: None,
+            ghost_snapshots: Vec::new(),
         }),
         order: None,
     });
@@ -181,6 +182,7 @@ fn replay_history_handles_prefixed_revisions() {
         msg: EventMsg::ReplayHistory(ReplayHistoryEvent {
             items,
             history_snapshot: None,
+            ghost_snapshots: Vec::new(),
         }),
         order: None,
     });
@@ -207,6 +209,7 @@ fn replay_history_hides_interleaved_reasoning_after_exploring() {
         msg: EventMsg::ReplayHistory(ReplayHistoryEvent {
             items: Vec::new(),
             history_snapshot: Some(snapshot_json),
+            ghost_snapshots: Vec::new(),
         }),
         order: None,
     });
@@ -236,6 +239,7 @@ fn replay_history_keeps_spacing_before_final_reasoning() {
         msg: EventMsg::ReplayHistory(ReplayHistoryEvent {
             items: Vec::new(),
             history_snapshot: Some(snapshot_json),
+            ghost_snapshots: Vec::new(),
         }),
         order: None,
     });